@@ -0,0 +1,101 @@
+//! Checks that the system libraries the `zstd` and `sqlite` features link are there, so a build
+//! without them fails with what to install instead of an error from the linker.
+
+use std::env;
+use std::path::{Path, PathBuf};
+use std::process::{self, Command};
+
+/// The libraries linked by features: the feature, the library, its pkg-config name, the
+/// variable naming the folder it is in, and the package that usually has it.
+const LIBRARIES: [(&str, &str, &str, &str, &str); 2] = [
+    ("ZSTD", "zstd", "libzstd", "ZSTD_LIB_DIR", "libzstd-dev"),
+    (
+        "SQLITE",
+        "sqlite3",
+        "sqlite3",
+        "SQLITE3_LIB_DIR",
+        "libsqlite3-dev",
+    ),
+];
+
+fn main() {
+    for (feature, lib, pkg, var, package) in LIBRARIES {
+        println!("cargo:rerun-if-env-changed={}", var);
+        if env::var_os(format!("CARGO_FEATURE_{}", feature)).is_none() {
+            continue;
+        }
+        match find(lib, pkg, var) {
+            Some(dir) => println!("cargo:rustc-link-search=native={}", dir.display()),
+            None => {
+                eprintln!(
+                    "error: the {} feature links the system lib{}, which was not found.\n\
+                     Install it (e.g. {}), point {} at the folder with lib{}.so, or build \
+                     without the feature.",
+                    feature.to_lowercase(),
+                    lib,
+                    package,
+                    var,
+                    lib
+                );
+                process::exit(1);
+            }
+        }
+    }
+}
+
+/// Returns the folder with the library `lib`, looking at `var`, the `-L` folders of RUSTFLAGS,
+/// pkg-config and the usual system folders in turn.
+fn find(lib: &str, pkg: &str, var: &str) -> Option<PathBuf> {
+    let mut dirs: Vec<PathBuf> = env::var_os(var).map(PathBuf::from).into_iter().collect();
+    if let Ok(flags) = env::var("CARGO_ENCODED_RUSTFLAGS") {
+        let flags: Vec<&str> = flags.split('\x1f').collect();
+        for (i, flag) in flags.iter().enumerate() {
+            let dir = match flag.strip_prefix("-L") {
+                Some("") => flags.get(i + 1).copied(),
+                Some(dir) => Some(dir),
+                None => None,
+            };
+            if let Some(dir) = dir {
+                let dir = dir.rsplit('=').next().unwrap();
+                dirs.push(PathBuf::from(dir));
+            }
+        }
+    }
+    if let Ok(output) = Command::new("pkg-config")
+        .args(["--libs-only-L", pkg])
+        .output()
+    {
+        let output = String::from_utf8_lossy(&output.stdout);
+        for flag in output.split_whitespace() {
+            if let Some(dir) = flag.strip_prefix("-L") {
+                dirs.push(PathBuf::from(dir));
+            }
+        }
+    }
+    let arch = env::var("CARGO_CFG_TARGET_ARCH").unwrap_or_default();
+    let os = env::var("CARGO_CFG_TARGET_OS").unwrap_or_default();
+    for dir in [
+        "/usr/local/lib",
+        "/usr/lib",
+        "/usr/lib64",
+        "/opt/homebrew/lib",
+    ] {
+        dirs.push(PathBuf::from(dir));
+    }
+    dirs.push(PathBuf::from(format!("/usr/lib/{}-{}-gnu", arch, os)));
+
+    dirs.into_iter().find(|dir| has_library(dir, lib))
+}
+
+/// Returns true if the linker finds `lib` in `dir`: the static archive or the unversioned
+/// shared library, which runtime-only packages leave out.
+fn has_library(dir: &Path, lib: &str) -> bool {
+    ["a", "so", "dylib", "lib"].iter().any(|ext| {
+        let name = if *ext == "lib" {
+            format!("{}.lib", lib)
+        } else {
+            format!("lib{}.{}", lib, ext)
+        };
+        dir.join(name).exists()
+    })
+}
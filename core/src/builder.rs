@@ -0,0 +1,334 @@
+//! Builds a [`Config`] in code, for embedders that run the summarizer without a command line.
+
+use crate::models::dict::TERM_CACHE;
+use crate::models::node_set::BITMAP_THRESHOLD;
+use crate::parser::exclusion::AutoExclude;
+use crate::parser::inverse::InversePreds;
+use crate::updater;
+use crate::util::io;
+use crate::{Coalescing, Config, Directions, DuplicatePolicy, Grouping, SplitStrategy};
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// Sets up a [`Config`] one setting at a time, starting from the defaults of the command line:
+///
+/// ```no_run
+/// # fn main() -> Result<(), &'static str> {
+/// let config = teriyaki_core::Config::builder()
+///     .dataset("summary.nt")
+///     .update("update.nt")
+///     .meta_folder("meta")
+///     .threads(8)
+///     .build()?;
+/// # Ok(())
+/// # }
+/// ```
+///
+/// [`build`](ConfigBuilder::build) rejects settings that do not go together. Whether the files
+/// exist is checked by [`Config::validate`], right before a run.
+pub struct ConfigBuilder {
+    config: Config,
+    seeded: bool,
+}
+
+impl Config {
+    /// Returns a builder for a config.
+    pub fn builder() -> ConfigBuilder {
+        ConfigBuilder {
+            config: Config {
+                channel_capacity: io::STREAM_CAPACITY,
+                term_cache: TERM_CACHE,
+                clique_bitmap_threshold: BITMAP_THRESHOLD,
+                chunk_size: updater::CHUNK_SIZE,
+                #[cfg(feature = "endpoint")]
+                page_size: 10000,
+                #[cfg(feature = "endpoint")]
+                endpoint_timeout: crate::util::http::DEFAULT_TIMEOUT,
+                ..Default::default()
+            },
+            seeded: false,
+        }
+    }
+}
+
+impl ConfigBuilder {
+    /// Sets the dataset, or the summary of the previous run.
+    pub fn dataset(mut self, path: impl Into<PathBuf>) -> Self {
+        self.config.dataset_path = path.into();
+        self
+    }
+
+    /// Sets the update, whose lines starting with `-` are deletions.
+    pub fn update(mut self, path: impl Into<PathBuf>) -> Self {
+        self.config.update_path = path.into();
+        self
+    }
+
+    /// Sets the meta folder, which fast mode creates and later runs read.
+    pub fn meta_folder(mut self, path: impl Into<PathBuf>) -> Self {
+        self.config.meta_folder_path = path.into();
+        self
+    }
+
+    /// Sets whether the dataset is summarized from scratch into a new meta folder.
+    pub fn fast(mut self, fast: bool) -> Self {
+        self.config.use_fast = fast;
+        self
+    }
+
+    /// Sets the number of threads the meta file is parsed on; one per core by default.
+    pub fn threads(mut self, threads: usize) -> Self {
+        self.config.threads = Some(threads);
+        self
+    }
+
+    pub fn grouping(mut self, grouping: Grouping) -> Self {
+        self.config.grouping = grouping;
+        self
+    }
+
+    pub fn duplicates(mut self, duplicates: DuplicatePolicy) -> Self {
+        self.config.duplicates = duplicates;
+        self
+    }
+
+    pub fn coalescing(mut self, coalescing: Coalescing) -> Self {
+        self.config.coalescing = coalescing;
+        self
+    }
+
+    pub fn split_strategy(mut self, split_strategy: SplitStrategy) -> Self {
+        self.config.split_strategy = split_strategy;
+        self
+    }
+
+    pub fn directions(mut self, directions: Directions) -> Self {
+        self.config.directions = directions;
+        self
+    }
+
+    /// Sets whether the cliques are verified after every change.
+    pub fn paranoid(mut self, paranoid: bool) -> Self {
+        self.config.paranoid = paranoid;
+        self
+    }
+
+    /// Sets whether the grouping of the whole summary is repaired after the update.
+    pub fn recluster(mut self, recluster: bool) -> Self {
+        self.config.recluster = recluster;
+        self
+    }
+
+    /// Sets whether changes the summary is inconsistent around are skipped instead of failing
+    /// the update.
+    pub fn lenient(mut self, lenient: bool) -> Self {
+        self.config.lenient = lenient;
+        self
+    }
+
+    /// Sets whether the triples of every subject are known to be next to each other in the
+    /// summary.
+    pub fn sorted_input(mut self, sorted: bool) -> Self {
+        self.config.sorted_input = sorted;
+        self
+    }
+
+    /// Sets the time after which the rest of the update is left pending.
+    pub fn time_budget(mut self, budget: Duration) -> Self {
+        self.config.time_budget = Some(budget);
+        self
+    }
+
+    pub fn max_supernode_size(mut self, max: usize) -> Self {
+        self.config.max_supernode_size = Some(max);
+        self
+    }
+
+    /// Sets the number of malformed input lines skipped before the run fails.
+    pub fn max_errors(mut self, max: usize) -> Self {
+        self.config.max_errors = Some(max);
+        self
+    }
+
+    /// Sets the fraction of the triples to summarize.
+    pub fn sample(mut self, fraction: f64) -> Self {
+        self.config.sample = Some(fraction);
+        self
+    }
+
+    /// Sets the seed that picks the sampled triples.
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.config.seed = seed;
+        self.seeded = true;
+        self
+    }
+
+    /// Sets the only predicates whose cliques decide the grouping.
+    pub fn focus_preds(mut self, preds: Vec<String>) -> Self {
+        self.config.focus_preds = Some(preds);
+        self
+    }
+
+    /// Sets the predicates whose triples are passed through instead of summarized.
+    pub fn excluded_preds(mut self, preds: Vec<String>) -> Self {
+        self.config.excluded_preds = preds;
+        self
+    }
+
+    pub fn inverse_preds(mut self, inverses: InversePreds) -> Self {
+        self.config.inverse_preds = inverses;
+        self
+    }
+
+    /// Sets the schema file the insertions are checked against.
+    pub fn schema(mut self, path: impl Into<PathBuf>) -> Self {
+        self.config.schema = Some(path.into());
+        self
+    }
+
+    /// Sets the file of the groups of nodes that supernodes keep together and apart.
+    pub fn seeds(mut self, path: impl Into<PathBuf>) -> Self {
+        self.config.seeds = Some(path.into());
+        self
+    }
+
+    pub fn auto_exclude(mut self, mode: AutoExclude) -> Self {
+        self.config.auto_exclude = Some(mode);
+        self
+    }
+
+    /// Sets whether insertions without a graph term get the update file as their source.
+    pub fn provenance(mut self, provenance: bool) -> Self {
+        self.config.provenance = provenance;
+        self
+    }
+
+    /// Sets whether a compact meta file for queries is written next to the meta file.
+    pub fn compact_meta(mut self, compact: bool) -> Self {
+        self.config.compact_meta = compact;
+        self
+    }
+
+    /// Sets whether the meta is kept in a SQLite database instead of the meta file.
+    #[cfg(feature = "sqlite")]
+    pub fn sqlite_meta(mut self, sqlite: bool) -> Self {
+        self.config.sqlite_meta = sqlite;
+        self
+    }
+
+    /// Sets the zstd level the meta file is compressed at, from 1 to 22.
+    #[cfg(feature = "zstd")]
+    pub fn zstd_meta(mut self, level: i32) -> Self {
+        self.config.zstd_meta = Some(level);
+        self
+    }
+
+    /// Sets whether the peak memory and the estimated size of the models are printed at exit.
+    pub fn profile_memory(mut self, profile: bool) -> Self {
+        self.config.profile_memory = profile;
+        self
+    }
+
+    /// Sets whether the repl logs changes to a write-ahead log before applying them.
+    pub fn wal(mut self, wal: bool) -> Self {
+        self.config.wal = wal;
+        self
+    }
+
+    /// Sets the number of changes applied between snapshots of the summary, 0 for none.
+    pub fn chunk_size(mut self, size: usize) -> Self {
+        self.config.chunk_size = size;
+        self
+    }
+
+    /// Sets the number of decoded terms the dict keeps, 0 for none.
+    pub fn term_cache(mut self, size: usize) -> Self {
+        self.config.term_cache = size;
+        self
+    }
+
+    /// Sets the number of nodes above which a clique keeps its nodes in a bitmap, 0 for never.
+    pub fn clique_bitmap_threshold(mut self, nodes: usize) -> Self {
+        self.config.clique_bitmap_threshold = nodes;
+        self
+    }
+
+    /// Sets the length in bytes above which new literals are kept in a side file.
+    pub fn keep_literals_over(mut self, bytes: usize) -> Self {
+        self.config.keep_literals_over = Some(bytes);
+        self
+    }
+
+    /// Sets the longest line read from the summary and update files, and what happens to longer
+    /// ones.
+    pub fn line_limit(mut self, limit: io::LineLimit) -> Self {
+        self.config.line_limit = Some(limit);
+        self
+    }
+
+    /// Sets the number of batches of summary lines read ahead of the parser.
+    pub fn channel_capacity(mut self, capacity: usize) -> Self {
+        self.config.channel_capacity = capacity;
+        self
+    }
+
+    /// Sets the SPARQL endpoint the dataset is read from instead of the dataset file.
+    #[cfg(feature = "endpoint")]
+    pub fn endpoint(mut self, url: impl Into<String>) -> Self {
+        self.config.endpoint = Some(url.into());
+        self
+    }
+
+    /// Returns the config, or why its settings do not go together.
+    pub fn build(self) -> Result<Config, &'static str> {
+        let config = self.config;
+        #[cfg(feature = "endpoint")]
+        let has_dataset = config.endpoint.is_some() || !config.dataset_path.as_os_str().is_empty();
+        #[cfg(not(feature = "endpoint"))]
+        let has_dataset = !config.dataset_path.as_os_str().is_empty();
+        if !has_dataset {
+            return Err("a config needs a dataset");
+        }
+        if config.update_path.as_os_str().is_empty() {
+            return Err("a config needs an update");
+        }
+        if config.meta_folder_path.as_os_str().is_empty() {
+            return Err("a config needs a meta folder");
+        }
+        if config.threads == Some(0) {
+            return Err("the meta cannot be parsed on zero threads");
+        }
+        if config.channel_capacity == 0 {
+            return Err("the channel capacity must be positive");
+        }
+        if config.max_supernode_size.is_some_and(|max| max < 2) {
+            return Err("the maximum supernode size must be at least 2");
+        }
+        match config.sample {
+            Some(f) if !(f > 0.0 && f <= 1.0) => {
+                return Err("the sample must be a fraction between 0 and 1")
+            }
+            None if self.seeded => return Err("a seed only applies to a sample"),
+            _ => {}
+        }
+        // As on the command line: the predicates found depend on the summary read.
+        if config.auto_exclude == Some(AutoExclude::Apply) && !config.use_fast {
+            return Err("applying auto exclusion only applies to fast mode");
+        }
+        if let SplitStrategy::Lazy(0) = config.split_strategy {
+            return Err("a lazy split strategy needs a positive interval");
+        }
+        #[cfg(feature = "zstd")]
+        if config
+            .zstd_meta
+            .is_some_and(|level| !(1..=crate::util::zstd::MAX_LEVEL).contains(&level))
+        {
+            return Err("the zstd level of the meta must be between 1 and 22");
+        }
+        #[cfg(all(feature = "zstd", feature = "sqlite"))]
+        if config.zstd_meta.is_some() && config.sqlite_meta {
+            return Err("a meta kept in SQLite cannot also be compressed");
+        }
+        Ok(config)
+    }
+}
@@ -0,0 +1,542 @@
+//! Incremental summaries of RDF graphs: nodes are grouped into supernodes by the cliques of
+//! their predicates, and the summary is kept up to date as triples are inserted and deleted.
+//!
+//! A run reads a summary, its meta folder and an update with [`parser::run`], applies the update
+//! with [`updater::run`] and writes the new summary and meta with [`writer::run`], all as set
+//! by a [`Config`].
+
+use metrics::drift::DriftThresholds;
+use parser::exclusion::AutoExclude;
+use parser::inverse::InversePreds;
+use std::{
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
+};
+use util::{io, source, wal};
+
+mod builder;
+pub mod metrics;
+pub mod models;
+pub mod parser;
+pub mod rdf;
+pub mod summarizer;
+#[cfg(test)]
+mod tests;
+pub mod updater;
+pub mod util;
+pub mod writer;
+
+pub use builder::ConfigBuilder;
+
+/// Selects which part of a node's signature decides its supernode.
+#[derive(Clone, Copy, Default, PartialEq)]
+pub enum Grouping {
+    /// Group by predicate cliques only; `rdf:type` triples are kept aside (the default).
+    #[default]
+    Preds,
+    /// Group by `rdf:type` sets only; all other triples are passed through unchanged.
+    Types,
+    /// Group by predicate cliques where each `rdf:type` class counts as a predicate.
+    Both,
+    /// Group by predicate cliques and drop `rdf:type` triples from the summary.
+    Untyped,
+}
+
+impl Grouping {
+    pub fn parse(arg: Option<&String>) -> Result<Self, &'static str> {
+        match arg.map(|s| s.as_str()) {
+            Some("preds") => Ok(Grouping::Preds),
+            Some("types") => Ok(Grouping::Types),
+            Some("both") => Ok(Grouping::Both),
+            _ => Err("--grouping expects preds, types or both"),
+        }
+    }
+
+    /// Parses the handling of `rdf:type` triples when grouping by predicates: `aside` keeps
+    /// them out of the cliques and attaches them to supernodes afterwards, `include` puts each
+    /// class into the cliques as a predicate and `exclude` drops them.
+    pub fn parse_types(arg: Option<&String>) -> Result<Self, &'static str> {
+        match arg.map(|s| s.as_str()) {
+            Some("aside") => Ok(Grouping::Preds),
+            Some("include") => Ok(Grouping::Both),
+            Some("exclude") => Ok(Grouping::Untyped),
+            _ => Err("--types expects aside, include or exclude"),
+        }
+    }
+}
+
+/// Whether the incoming and outgoing predicates of a node decide its supernode apart or together.
+#[derive(Clone, Copy, Default, PartialEq)]
+pub enum Directions {
+    /// Group nodes that share both their source clique and their target clique (the default).
+    #[default]
+    Separate,
+    /// Group nodes whose incoming and outgoing predicates together fall in the same clique, as a
+    /// node with predicates in both directions joins its source and target cliques. This gives
+    /// fewer, larger supernodes.
+    Joint,
+}
+
+impl Directions {
+    pub fn parse(arg: Option<&String>) -> Result<Self, &'static str> {
+        match arg.map(|s| s.as_str()) {
+            Some("separate") => Ok(Directions::Separate),
+            Some("joint") => Ok(Directions::Joint),
+            _ => Err("--directions expects separate or joint"),
+        }
+    }
+}
+
+/// What to do when a triple is added that already exists.
+#[derive(Clone, Copy, Default, PartialEq)]
+pub enum DuplicatePolicy {
+    /// Drop the duplicate silently (the default).
+    #[default]
+    Dedupe,
+    /// Keep track of how many times the triple was added; a deletion removes one occurrence.
+    Count,
+    /// Abort with an error.
+    Error,
+}
+
+/// What to do when the update both inserts and deletes a triple.
+#[derive(Clone, Copy, Default, PartialEq)]
+pub enum Coalescing {
+    /// Apply only the change that comes last in the update (the default).
+    #[default]
+    Sequence,
+    /// Skip both changes, leaving the triple as it was.
+    Set,
+}
+
+impl Coalescing {
+    pub fn parse(arg: Option<&String>) -> Result<Self, &'static str> {
+        match arg.map(|s| s.as_str()) {
+            Some("sequence") => Ok(Coalescing::Sequence),
+            Some("set") => Ok(Coalescing::Set),
+            _ => Err("--coalesce expects sequence or set"),
+        }
+    }
+}
+
+/// What to do with a supernode after a member was split off because it gained a predicate.
+#[derive(Clone, Copy, Default, PartialEq)]
+pub enum SplitStrategy {
+    /// Only eject the member; the others stay together (the default).
+    #[default]
+    Eject,
+    /// Re-partition the remaining members by their cliques right away.
+    Repartition,
+    /// Mark the supernode and re-partition marked supernodes every so many insertions and at
+    /// the end of the update.
+    Lazy(usize),
+}
+
+impl SplitStrategy {
+    pub fn parse(arg: Option<&String>) -> Result<Self, &'static str> {
+        match arg.map(|s| s.as_str()) {
+            Some("eject") => Ok(SplitStrategy::Eject),
+            Some("repartition") => Ok(SplitStrategy::Repartition),
+            Some("lazy") => Ok(SplitStrategy::Lazy(1000)),
+            _ => Err("--split-strategy expects eject, repartition or lazy"),
+        }
+    }
+}
+
+impl DuplicatePolicy {
+    pub fn parse(arg: Option<&String>) -> Result<Self, &'static str> {
+        match arg.map(|s| s.as_str()) {
+            Some("dedupe") => Ok(DuplicatePolicy::Dedupe),
+            Some("count") => Ok(DuplicatePolicy::Count),
+            Some("error") => Ok(DuplicatePolicy::Error),
+            _ => Err("--duplicates expects dedupe, count or error"),
+        }
+    }
+}
+
+/// The settings of a run: where its inputs are and how they are summarized.
+#[derive(Default)]
+pub struct Config {
+    pub dataset_path: PathBuf,
+    pub meta_folder_path: PathBuf,
+    pub update_path: PathBuf,
+    pub use_fast: bool,
+    pub centrality: bool,
+    pub pagerank: bool,
+    /// Report the compression of each clique in stats.
+    pub clique_stats: bool,
+    pub focus_preds: Option<Vec<String>>,
+    /// Predicates whose triples are passed through instead of summarized.
+    pub excluded_preds: Vec<String>,
+    /// Pairs of inverse predicates whose triples are read as one.
+    pub inverse_preds: InversePreds,
+    /// Schema file the insertions are checked against, if any.
+    pub schema: Option<PathBuf>,
+    /// File of the groups of nodes that supernodes keep together and apart, if any.
+    pub seeds: Option<PathBuf>,
+    /// Whether predicates that barely compress are looked for, and excluded.
+    pub auto_exclude: Option<AutoExclude>,
+    pub grouping: Grouping,
+    pub duplicates: DuplicatePolicy,
+    pub coalescing: Coalescing,
+    pub split_strategy: SplitStrategy,
+    pub directions: Directions,
+    pub paranoid: bool,
+    /// Whether the grouping of the whole summary is repaired after the update.
+    pub recluster: bool,
+    /// Whether changes the summary is inconsistent around are skipped instead of failing the run.
+    pub lenient: bool,
+    /// Whether the triples of every subject are known to be next to each other in the summary,
+    /// even if its lines are not sorted.
+    pub sorted_input: bool,
+    pub timings: bool,
+    /// Whether the peak memory and the estimated size of the models are printed at exit.
+    pub profile_memory: bool,
+    /// Whether the repl logs each change to the write-ahead log in the meta folder before
+    /// applying it, so changes survive a crash.
+    pub wal: bool,
+    pub time_budget: Option<Duration>,
+    /// Number of changes applied between snapshots of the summary, or 0 to apply all of them
+    /// before writing it.
+    pub chunk_size: usize,
+    pub max_supernode_size: Option<usize>,
+    /// Number of malformed input lines skipped before aborting, or `None` to not check lines.
+    pub max_errors: Option<usize>,
+    /// Fraction of the triples to summarize, or `None` to summarize all of them.
+    pub sample: Option<f64>,
+    /// Seed that picks the sampled triples.
+    pub seed: u64,
+    /// Number of recent changes applied in the repl that a repeated change is skipped within.
+    pub dedup_window: usize,
+    /// Levels of the drift indicators past which the repl re-clusters the summary.
+    pub drift: DriftThresholds,
+    /// Whether insertions without a graph term get the update file as their source.
+    pub provenance: bool,
+    /// Number of batches of summary lines read ahead of the parser.
+    pub channel_capacity: usize,
+    /// Number of threads the meta file is parsed on, or `None` for one per core.
+    pub threads: Option<usize>,
+    /// Whether a compact meta file for queries is written next to the meta file.
+    pub compact_meta: bool,
+    /// Whether the meta is kept in a SQLite database instead of the meta file.
+    #[cfg(feature = "sqlite")]
+    pub sqlite_meta: bool,
+    /// Level the meta file is compressed at with zstd, or `None` to write it uncompressed.
+    #[cfg(feature = "zstd")]
+    pub zstd_meta: Option<i32>,
+    /// Number of decoded terms the dict keeps, 0 for none.
+    pub term_cache: usize,
+    /// Number of nodes above which the nodes of a clique are kept in a bitmap rather than a
+    /// list, 0 for never.
+    pub clique_bitmap_threshold: usize,
+    /// Length in bytes above which new literals are kept in a side file instead of the dict.
+    pub keep_literals_over: Option<usize>,
+    /// Longest line read from the summary and update files, or `None` for no limit.
+    pub line_limit: Option<io::LineLimit>,
+    #[cfg(feature = "endpoint")]
+    pub endpoint: Option<String>,
+    #[cfg(feature = "endpoint")]
+    pub page_size: usize,
+    /// How long connecting to an endpoint, and each read or write of a request, may take. Zero
+    /// waits forever.
+    #[cfg(feature = "endpoint")]
+    pub endpoint_timeout: Duration,
+    #[cfg(feature = "endpoint")]
+    pub push: Option<writer::endpoint::PushConfig>,
+}
+
+impl Config {
+    /// Checks that the input files of a summary run exist and look like N-Triples, and that
+    /// its outputs can be written, so a bad path is found before a long parse instead of after.
+    ///
+    /// Returns every problem found, not just the first. The outputs are only checked if the run
+    /// `writes` them.
+    pub fn validate(&self, writes: bool) -> Result<(), Vec<String>> {
+        let mut problems: Vec<String> = Vec::new();
+
+        #[cfg(feature = "endpoint")]
+        let local_dataset = self.endpoint.is_none();
+        #[cfg(not(feature = "endpoint"))]
+        let local_dataset = true;
+        if local_dataset {
+            check_input(
+                &self.dataset_path,
+                "dataset",
+                false,
+                self.max_errors,
+                &mut problems,
+            );
+        }
+        #[cfg(feature = "endpoint")]
+        {
+            let push = self.push.as_ref().map(|push| &push.url);
+            for url in self.endpoint.iter().chain(push) {
+                if url.starts_with("https://") {
+                    problems.push(format!(
+                        "endpoint {} uses https, which needs TLS that this build does not include; use an http:// endpoint",
+                        url
+                    ));
+                }
+            }
+        }
+        check_input(
+            &self.update_path,
+            "update",
+            true,
+            self.max_errors,
+            &mut problems,
+        );
+
+        if let Some(schema) = &self.schema {
+            if !schema.is_file() {
+                problems.push(format!("schema file {} does not exist", schema.display()));
+            }
+        }
+        if let Some(seeds) = &self.seeds {
+            if !seeds.is_file() {
+                problems.push(format!("seeds file {} does not exist", seeds.display()));
+            }
+        }
+
+        if self.use_fast && self.meta_folder_path.exists() {
+            problems.push(format!(
+                "meta folder {} already exists; fast mode creates it, so remove it or leave out --fast",
+                self.meta_folder_path.display()
+            ));
+        } else if !self.use_fast && !self.meta_folder_path.is_dir() {
+            problems.push(format!(
+                "meta folder {} does not exist; create it with a first run using --fast",
+                self.meta_folder_path.display()
+            ));
+        }
+
+        if writes {
+            let mut dirs = vec![parent_dir(&self.summary_path())];
+            if self.use_fast {
+                dirs.push(parent_dir(&self.meta_folder_path));
+            } else if self.meta_folder_path.is_dir() {
+                dirs.push(self.meta_folder_path.clone());
+            }
+            for dir in dirs {
+                let dir = if dir.as_os_str().is_empty() {
+                    PathBuf::from(".")
+                } else {
+                    dir
+                };
+                if !io::is_writable(&dir) {
+                    problems.push(format!("cannot write to {}", dir.display()));
+                }
+            }
+        }
+
+        if problems.is_empty() {
+            Ok(())
+        } else {
+            Err(problems)
+        }
+    }
+
+    /// Returns the options for applying the update. The time budget starts now.
+    pub fn update_options(&self) -> updater::Options {
+        updater::Options {
+            duplicates: self.duplicates,
+            coalescing: self.coalescing,
+            split_strategy: self.split_strategy,
+            paranoid: self.paranoid,
+            deadline: self.time_budget.map(|b| Instant::now() + b),
+            max_supernode_size: self.max_supernode_size,
+            recluster: self.recluster,
+            directions: self.directions,
+            lenient: self.lenient,
+        }
+    }
+
+    /// Returns the path changes left over by an interrupted update are written to, next to the
+    /// summary.
+    pub fn pending_path(&self) -> PathBuf {
+        parent_dir(&self.summary_path()).join("pending.nt")
+    }
+
+    /// Returns the path of the write-ahead log of changes not yet in the meta folder.
+    pub fn wal_path(&self) -> PathBuf {
+        self.meta_folder_path.join(wal::WAL_FILE)
+    }
+
+    /// Returns the path of the lock on the meta folder, next to the meta folder.
+    pub fn lock_path(&self) -> PathBuf {
+        let name = match self.meta_folder_path.file_name() {
+            Some(name) => name.to_string_lossy().to_string(),
+            None => String::from("meta"),
+        };
+        parent_dir(&self.meta_folder_path).join(format!("{}.lock", name))
+    }
+
+    /// Returns the path rejected changes are written to, next to the summary.
+    pub fn rejected_path(&self) -> PathBuf {
+        parent_dir(&self.summary_path()).join("rejected.nt")
+    }
+
+    /// Returns the path insertions violating the schema are written to, next to the summary.
+    pub fn violations_path(&self) -> PathBuf {
+        parent_dir(&self.summary_path()).join("violations.nt")
+    }
+
+    /// Returns the path changes skipped by `lenient` runs are written to, next to the summary.
+    pub fn inconsistent_path(&self) -> PathBuf {
+        parent_dir(&self.summary_path()).join("inconsistent.nt")
+    }
+
+    /// Returns the path malformed input lines are written to, next to the summary.
+    pub fn skipped_path(&self) -> PathBuf {
+        parent_dir(&self.summary_path()).join("skipped.nt")
+    }
+
+    /// Returns the path the summary graph is written to.
+    ///
+    /// The summary is placed next to the dataset, or next to the meta folder when the dataset
+    /// is read from a SPARQL endpoint or a remote file.
+    pub fn summary_path(&self) -> PathBuf {
+        #[cfg(feature = "endpoint")]
+        if self.endpoint.is_some() {
+            return parent_dir(&self.meta_folder_path).join("summary.nt");
+        }
+        if source::is_remote(&self.dataset_path) {
+            return parent_dir(&self.meta_folder_path).join("summary.nt");
+        }
+        parent_dir(&self.dataset_path).join("summary.nt")
+    }
+}
+
+/// Number of malformed lines of an update file reported by [`Config::validate`].
+const MALFORMED_LINES_REPORTED: usize = 5;
+
+/// Adds a problem if the `kind` file at `path` is missing, or if its first line is not an
+/// N-Triples line. Every line of a local update file is checked, as an empty or malformed line
+/// would stop the run after the summary is read. Malformed lines are allowed when up to
+/// `max_errors` lines may be skipped.
+fn check_input(
+    path: &Path,
+    kind: &str,
+    is_update: bool,
+    max_errors: Option<usize>,
+    problems: &mut Vec<String>,
+) {
+    let remote = source::is_remote(path);
+    if !remote && !path.is_file() {
+        return problems.push(format!("{} file {} does not exist", kind, path.display()));
+    }
+    // N3 and Turtle are read as a whole, and their lines need not be triples.
+    if parser::n3::is_n3(path) {
+        return;
+    }
+    if let Some(syntax) = rdf::unsupported_syntax(path) {
+        return problems.push(format!(
+            "{} file {} looks like {}, but only N-Triples, N-Quads, Turtle and N3 are read; convert it first, e.g. with `riot --output=nt`",
+            kind,
+            path.display(),
+            syntax
+        ));
+    }
+    if is_update && !remote {
+        if max_errors.is_none() {
+            check_update_lines(path, kind, problems);
+        }
+        return;
+    }
+    let line = match source::first_line(path) {
+        Ok(Some(line)) => line,
+        Ok(None) => return,
+        Err(err) => {
+            return problems.push(format!(
+                "cannot read {} file {}: {}",
+                kind,
+                path.display(),
+                err
+            ))
+        }
+    };
+    let triple = if is_update {
+        line.strip_prefix('-').unwrap_or(&line)
+    } else {
+        line.as_str()
+    };
+    if let (Some(reason), None) = (rdf::check_line(triple), max_errors) {
+        problems.push(format!(
+            "{} file {} does not look like N-Triples, line 1: {}",
+            kind,
+            path.display(),
+            reason
+        ));
+    }
+}
+
+/// Adds a problem for each of the first empty or malformed lines of the local update file at
+/// `path`, and one for the number of the others.
+fn check_update_lines(path: &Path, kind: &str, problems: &mut Vec<String>) {
+    let lines = match std::fs::File::open(path) {
+        Ok(file) => io::LineReader::new(std::io::BufReader::new(file), None),
+        Err(err) => {
+            return problems.push(format!(
+                "cannot read {} file {}: {}",
+                kind,
+                path.display(),
+                err
+            ))
+        }
+    };
+    let mut malformed = 0;
+    for (i, line) in lines.enumerate() {
+        let line = match line {
+            Ok(line) => line,
+            Err(err) => {
+                return problems.push(format!(
+                    "cannot read {} file {}: {}",
+                    kind,
+                    path.display(),
+                    err
+                ))
+            }
+        };
+        let triple = line.strip_prefix('-').unwrap_or(&line);
+        let reason = match rdf::check_line(triple) {
+            _ if line.trim().is_empty() => "empty line",
+            Some(reason) => reason,
+            None => continue,
+        };
+        malformed += 1;
+        if malformed <= MALFORMED_LINES_REPORTED {
+            problems.push(format!(
+                "{} file {} does not look like N-Triples, line {}: {}",
+                kind,
+                path.display(),
+                i + 1,
+                reason
+            ));
+        }
+    }
+    if malformed > MALFORMED_LINES_REPORTED {
+        problems.push(format!(
+            "{} file {} has {} more malformed lines; skip them with --max-errors",
+            kind,
+            path.display(),
+            malformed - MALFORMED_LINES_REPORTED
+        ));
+    }
+}
+
+/// Wraps `term` in angle brackets unless it already is an IRI in N-Triples notation.
+pub fn as_iri(term: &str) -> String {
+    if term.starts_with('<') {
+        term.to_string()
+    } else {
+        format!("<{}>", term)
+    }
+}
+
+/// Returns the folder `path` is in, empty for a bare file name.
+pub fn parent_dir(path: &Path) -> PathBuf {
+    match path.parent() {
+        Some(p) => p.to_path_buf(),
+        None => PathBuf::new(),
+    }
+}
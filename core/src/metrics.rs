@@ -0,0 +1,6 @@
+pub mod cache;
+pub mod centrality;
+pub mod compression;
+pub mod drift;
+pub mod memory;
+pub mod timing;
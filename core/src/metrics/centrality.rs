@@ -0,0 +1,67 @@
+use crate::models::graph::Graph;
+use crate::util::hash::HashMap;
+
+const DAMPING: f64 = 0.85;
+const ITERATIONS: usize = 50;
+const TOLERANCE: f64 = 1e-9;
+
+/// In- and out-degree of a node in the summary graph.
+#[derive(Clone, Copy, Default)]
+pub struct Degree {
+    pub incoming: usize,
+    pub outgoing: usize,
+}
+
+impl Degree {
+    pub fn total(&self) -> usize {
+        self.incoming + self.outgoing
+    }
+}
+
+/// Computes the in- and out-degree of every node in `graph`.
+pub fn degrees(graph: &Graph) -> HashMap<u32, Degree> {
+    graph
+        .nodes()
+        .map(|n| {
+            let degree = Degree {
+                incoming: graph.incoming(n).len(),
+                outgoing: graph.outgoing(n).len(),
+            };
+            (*n, degree)
+        })
+        .collect()
+}
+
+/// Computes the PageRank of every node in `graph`.
+///
+/// Rank of nodes without outgoing edges is spread evenly over all nodes.
+pub fn pagerank(graph: &Graph) -> HashMap<u32, f64> {
+    let n = graph.node_count() as f64;
+    if graph.node_count() == 0 {
+        return HashMap::default();
+    }
+
+    let mut ranks: HashMap<u32, f64> = graph.nodes().map(|k| (*k, 1.0 / n)).collect();
+
+    for _ in 0..ITERATIONS {
+        let dangling: f64 = graph
+            .nodes()
+            .filter(|k| graph.outgoing(k).is_empty())
+            .map(|k| ranks[k])
+            .sum();
+        let base = (1.0 - DAMPING) / n + DAMPING * dangling / n;
+
+        let mut next: HashMap<u32, f64> = graph.nodes().map(|k| (*k, base)).collect();
+        for [s, _, o] in graph.edges() {
+            let share = ranks[&s] / graph.outgoing(&s).len() as f64;
+            *next.get_mut(&o).unwrap() += DAMPING * share;
+        }
+
+        let delta: f64 = next.iter().map(|(k, v)| (v - ranks[k]).abs()).sum();
+        ranks = next;
+        if delta < TOLERANCE {
+            break;
+        }
+    }
+    ranks
+}
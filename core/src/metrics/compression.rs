@@ -0,0 +1,84 @@
+use crate::models::clique::CliqueCollection;
+use crate::models::meta::Meta;
+use crate::models::triple::TripleCollection;
+use std::collections::HashMap;
+
+/// How much of the graph a clique covers, and how much smaller the summary makes that part.
+///
+/// A source clique covers the triples whose predicate is one of its predicates, as do target
+/// cliques, so every triple is covered by one source and one target clique.
+pub struct CliqueCompression {
+    pub is_source: bool,
+    pub preds: Vec<u32>,
+    /// Number of summary nodes in the clique.
+    pub nodes: usize,
+    /// Original triples with a predicate of the clique.
+    pub original_triples: usize,
+    /// Summary triples with a predicate of the clique.
+    pub summary_triples: usize,
+}
+
+impl CliqueCompression {
+    /// Number of triples the summary saves on this clique.
+    pub fn saved(&self) -> usize {
+        self.original_triples.saturating_sub(self.summary_triples)
+    }
+
+    /// Summary triples per original triple, or 1 if the clique covers no triples.
+    pub fn ratio(&self) -> f64 {
+        if self.original_triples == 0 {
+            1.0
+        } else {
+            self.summary_triples as f64 / self.original_triples as f64
+        }
+    }
+}
+
+/// Computes the compression of every clique with predicates in `sc` and `tc`, the cliques that
+/// save the most triples first.
+///
+/// The original triples are the edges recorded in `meta`, the summary triples are `triples`.
+pub fn clique_compression(
+    sc: &CliqueCollection,
+    tc: &CliqueCollection,
+    meta: &Meta,
+    triples: &TripleCollection,
+) -> Vec<CliqueCompression> {
+    let mut original: HashMap<u32, usize> = HashMap::new();
+    for info in meta.get_nodes().values() {
+        for [p, _] in &info.outgoing {
+            *original.entry(*p).or_default() += 1;
+        }
+    }
+    let mut summary: HashMap<u32, usize> = HashMap::new();
+    for t in &triples.data_triples {
+        *summary.entry(t.pred).or_default() += 1;
+    }
+    let count = |counts: &HashMap<u32, usize>, preds: &[u32]| -> usize {
+        preds.iter().filter_map(|p| counts.get(p)).sum()
+    };
+
+    let mut cliques: Vec<CliqueCompression> = [(true, sc), (false, tc)]
+        .into_iter()
+        .flat_map(|(is_source, cc)| cc.cliques().iter().map(move |c| (is_source, c)))
+        .filter(|(_, c)| !c.preds.is_empty())
+        .map(|(is_source, c)| {
+            let mut preds = c.preds.clone();
+            preds.sort();
+            CliqueCompression {
+                is_source,
+                nodes: c.nodes.len(),
+                original_triples: count(&original, &preds),
+                summary_triples: count(&summary, &preds),
+                preds,
+            }
+        })
+        .collect();
+    cliques.sort_by(|a, b| {
+        b.saved()
+            .cmp(&a.saved())
+            .then(b.is_source.cmp(&a.is_source))
+            .then(a.preds.cmp(&b.preds))
+    });
+    cliques
+}
@@ -0,0 +1,140 @@
+//! Indicators of how far a summary kept in memory has drifted from the grouping a re-clustering
+//! pass would give it, as changes are applied one at a time.
+
+use crate::models::meta::Meta;
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::fmt;
+use std::time::{Duration, Instant};
+
+/// Period over which splits are counted.
+const WINDOW: Duration = Duration::from_secs(60);
+
+/// Levels of the indicators past which the summary is re-clustered. Unset levels never alert.
+#[derive(Clone, Copy, Default)]
+pub struct DriftThresholds {
+    /// Largest share of the summary nodes that are single nodes outside any supernode.
+    pub max_singleton_share: Option<f64>,
+    /// Smallest average number of members of a supernode.
+    pub min_average_size: Option<f64>,
+    /// Largest number of supernodes split up in the last minute.
+    pub max_splits_per_minute: Option<usize>,
+}
+
+#[derive(Serialize)]
+pub struct DriftReport {
+    /// Share of the summary nodes that are single nodes outside any supernode.
+    pub singleton_share: f64,
+    pub average_supernode_size: f64,
+    pub splits_per_minute: usize,
+    /// The indicators past their threshold.
+    pub alerts: Vec<&'static str>,
+    /// Number of re-clustering passes the alerts triggered so far.
+    pub reclusterings: usize,
+}
+
+impl fmt::Display for DriftReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "singleton share:        {:.3}", self.singleton_share)?;
+        writeln!(
+            f,
+            "average supernode size: {:.2}",
+            self.average_supernode_size
+        )?;
+        writeln!(f, "splits per minute:      {}", self.splits_per_minute)?;
+        writeln!(f, "re-clusterings:         {}", self.reclusterings)?;
+        for alert in &self.alerts {
+            writeln!(f, "alert: {} past its threshold", alert)?;
+        }
+        Ok(())
+    }
+}
+
+/// Tracks the drift indicators of a summary and tells when its thresholds call for a
+/// re-clustering pass.
+///
+/// An alert triggers a pass when it is raised, not for as long as it lasts, since a pass cannot
+/// always bring an indicator back: a summary of mostly unrelated nodes keeps a high singleton
+/// share however it is grouped.
+#[derive(Default)]
+pub struct DriftMonitor {
+    thresholds: DriftThresholds,
+    /// When each split of the last minute happened, oldest first.
+    splits: VecDeque<Instant>,
+    /// The alerts of the last check.
+    raised: Vec<&'static str>,
+    reclusterings: usize,
+}
+
+impl DriftMonitor {
+    pub fn new(thresholds: DriftThresholds) -> Self {
+        Self {
+            thresholds,
+            ..Default::default()
+        }
+    }
+
+    /// Records that `count` supernodes were split up at `now`.
+    pub fn record_splits(&mut self, count: usize, now: Instant) {
+        self.splits.extend(std::iter::repeat_n(now, count));
+    }
+
+    /// Returns the indicators of `meta` at `now`.
+    pub fn report(&mut self, meta: &Meta, now: Instant) -> DriftReport {
+        while self
+            .splits
+            .front()
+            .is_some_and(|t| now.duration_since(*t) >= WINDOW)
+        {
+            self.splits.pop_front();
+        }
+
+        let snodes = meta.get_supernodes();
+        let members: usize = snodes.values().map(|m| m.len()).sum();
+        let summary_nodes = meta.top_level_nodes().len();
+        let share = |part: usize, whole: usize| match whole {
+            0 => 0.0,
+            _ => part as f64 / whole as f64,
+        };
+        let mut report = DriftReport {
+            singleton_share: share(summary_nodes - snodes.len(), summary_nodes),
+            average_supernode_size: share(members, snodes.len()),
+            splits_per_minute: self.splits.len(),
+            alerts: Vec::new(),
+            reclusterings: self.reclusterings,
+        };
+
+        let t = &self.thresholds;
+        if t.max_singleton_share
+            .is_some_and(|m| report.singleton_share > m)
+        {
+            report.alerts.push("singleton share");
+        }
+        if t.min_average_size
+            .is_some_and(|m| report.average_supernode_size < m)
+        {
+            report.alerts.push("average supernode size");
+        }
+        if t.max_splits_per_minute
+            .is_some_and(|m| report.splits_per_minute > m)
+        {
+            report.alerts.push("splits per minute");
+        }
+        report
+    }
+
+    /// Returns true if an alert was raised since the last check, so the summary should be
+    /// re-clustered.
+    pub fn check(&mut self, meta: &Meta, now: Instant) -> bool {
+        let alerts = self.report(meta, now).alerts;
+        let raised = alerts.iter().any(|a| !self.raised.contains(a));
+        self.raised = alerts;
+        raised
+    }
+
+    /// Records a re-clustering pass. The splits before it no longer count.
+    pub fn reclustered(&mut self) {
+        self.splits.clear();
+        self.reclusterings += 1;
+    }
+}
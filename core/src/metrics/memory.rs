@@ -0,0 +1,73 @@
+//! Peak memory of the process and the estimated size of the models, to predict what larger
+//! inputs need.
+
+use crate::models::clique::CliqueCollection;
+use crate::models::dataset::Dataset;
+use crate::models::meta::Meta;
+use crate::util::heap_size::HeapSize;
+use serde::Serialize;
+use std::fmt;
+use std::fs;
+
+const MIB: f64 = 1024.0 * 1024.0;
+
+/// Estimated heap bytes per model, next to the peak resident memory.
+#[derive(Clone, Debug, Serialize)]
+pub struct MemoryReport {
+    /// Most memory the process held at once, or `None` where the platform does not tell.
+    pub peak_rss_bytes: Option<u64>,
+    pub dict_bytes: usize,
+    /// Summary triples and changes.
+    pub triples_bytes: usize,
+    /// Source and target cliques.
+    pub cliques_bytes: usize,
+    pub meta_bytes: usize,
+}
+
+impl MemoryReport {
+    pub fn new(
+        dataset: &Dataset,
+        meta: &Meta,
+        sc: &CliqueCollection,
+        tc: &CliqueCollection,
+    ) -> Self {
+        MemoryReport {
+            peak_rss_bytes: peak_rss(),
+            dict_bytes: dataset.dict_heap_size(),
+            triples_bytes: dataset.triples_heap_size(),
+            cliques_bytes: sc.heap_size() + tc.heap_size(),
+            meta_bytes: meta.heap_size(),
+        }
+    }
+
+    /// Sum of the estimates, which leaves out buffers and the memory of finished phases.
+    pub fn estimated_bytes(&self) -> usize {
+        self.dict_bytes + self.triples_bytes + self.cliques_bytes + self.meta_bytes
+    }
+}
+
+impl fmt::Display for MemoryReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.peak_rss_bytes {
+            Some(bytes) => writeln!(f, "peak memory: {:.1} MiB", bytes as f64 / MIB)?,
+            None => writeln!(f, "peak memory: unknown")?,
+        }
+        writeln!(f, "dict:        {:.1} MiB", self.dict_bytes as f64 / MIB)?;
+        writeln!(f, "triples:     {:.1} MiB", self.triples_bytes as f64 / MIB)?;
+        writeln!(f, "cliques:     {:.1} MiB", self.cliques_bytes as f64 / MIB)?;
+        writeln!(f, "meta:        {:.1} MiB", self.meta_bytes as f64 / MIB)?;
+        writeln!(
+            f,
+            "estimated:   {:.1} MiB",
+            self.estimated_bytes() as f64 / MIB
+        )
+    }
+}
+
+/// Returns the peak resident memory of the process, read from `/proc` on Linux.
+pub fn peak_rss() -> Option<u64> {
+    let status = fs::read_to_string("/proc/self/status").ok()?;
+    let line = status.lines().find(|l| l.starts_with("VmHWM:"))?;
+    let kib: u64 = line.split_whitespace().nth(1)?.parse().ok()?;
+    Some(kib * 1024)
+}
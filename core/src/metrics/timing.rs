@@ -0,0 +1,125 @@
+//! Time spent in the phases of the updater.
+//!
+//! Phases nest per thread; time spent in a nested phase is only counted for the inner phase.
+
+use serde::Serialize;
+use std::cell::RefCell;
+use std::fmt;
+use std::time::{Duration, Instant};
+
+#[derive(Clone, Copy)]
+pub enum Phase {
+    /// Adding and removing edges of nodes in `Meta`.
+    MetaEdges,
+    /// Moving nodes and preds between cliques and merging cliques.
+    Cliques,
+    /// Re-indexing preds and nodes of whole cliques in `pred_index` and `index_map`.
+    Index,
+    /// Splitting nodes off supernodes and dissolving supernodes.
+    Split,
+    /// Forming new supernodes from clique changes.
+    Supernodes,
+}
+
+/// Self time per phase, in microseconds.
+#[derive(Default, Clone, Serialize)]
+pub struct PhaseTimings {
+    pub meta_edges_us: u64,
+    pub cliques_us: u64,
+    pub index_us: u64,
+    pub split_us: u64,
+    pub supernodes_us: u64,
+}
+
+impl PhaseTimings {
+    /// Adds the times of `other` to these.
+    pub fn add_all(&mut self, other: &PhaseTimings) {
+        self.meta_edges_us += other.meta_edges_us;
+        self.cliques_us += other.cliques_us;
+        self.index_us += other.index_us;
+        self.split_us += other.split_us;
+        self.supernodes_us += other.supernodes_us;
+    }
+
+    fn add(&mut self, phase: Phase, time: Duration) {
+        let field = match phase {
+            Phase::MetaEdges => &mut self.meta_edges_us,
+            Phase::Cliques => &mut self.cliques_us,
+            Phase::Index => &mut self.index_us,
+            Phase::Split => &mut self.split_us,
+            Phase::Supernodes => &mut self.supernodes_us,
+        };
+        *field += time.as_micros() as u64;
+    }
+}
+
+impl fmt::Display for PhaseTimings {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "meta edges {}us, cliques {}us, index {}us, split {}us, supernodes {}us",
+            self.meta_edges_us, self.cliques_us, self.index_us, self.split_us, self.supernodes_us
+        )
+    }
+}
+
+/// Phase timings of the batches of an update.
+#[derive(Default, Clone, Serialize)]
+pub struct UpdateTimings {
+    pub insertions: PhaseTimings,
+    pub deletions: PhaseTimings,
+}
+
+impl fmt::Display for UpdateTimings {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "insertions: {}", self.insertions)?;
+        writeln!(f, "deletions:  {}", self.deletions)
+    }
+}
+
+struct Frame {
+    phase: Phase,
+    start: Instant,
+    children: Duration,
+}
+
+thread_local! {
+    static STACK: RefCell<Vec<Frame>> = const { RefCell::new(Vec::new()) };
+    static TIMINGS: RefCell<PhaseTimings> = RefCell::new(PhaseTimings::default());
+}
+
+/// Counts the time until the returned guard is dropped towards `phase`.
+pub fn phase(phase: Phase) -> PhaseGuard {
+    STACK.with(|s| {
+        s.borrow_mut().push(Frame {
+            phase,
+            start: Instant::now(),
+            children: Duration::ZERO,
+        })
+    });
+    PhaseGuard
+}
+
+/// Returns the timings recorded on this thread since the last call and resets them.
+pub fn take() -> PhaseTimings {
+    TIMINGS.with(|t| t.take())
+}
+
+pub struct PhaseGuard;
+
+impl Drop for PhaseGuard {
+    fn drop(&mut self) {
+        STACK.with(|s| {
+            let mut stack = s.borrow_mut();
+            let frame = stack.pop().unwrap();
+            let elapsed = frame.start.elapsed();
+            if let Some(parent) = stack.last_mut() {
+                parent.children += elapsed;
+            }
+            TIMINGS.with(|t| {
+                t.borrow_mut()
+                    .add(frame.phase, elapsed.saturating_sub(frame.children))
+            });
+        });
+    }
+}
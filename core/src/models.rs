@@ -0,0 +1,16 @@
+pub mod canonical;
+pub mod clique;
+pub mod compact_meta;
+pub mod dataset;
+pub mod dict;
+pub mod edges;
+pub mod graph;
+pub mod id_allocator;
+pub mod literals;
+pub mod meta;
+pub mod node_set;
+pub mod seeds;
+pub mod shared_meta;
+pub mod triple;
+pub mod triple_filter;
+pub mod triple_index;
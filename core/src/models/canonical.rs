@@ -0,0 +1,94 @@
+use super::dataset::Dataset;
+use super::meta::Meta;
+use super::triple::TYPE_STRING;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fmt;
+
+/// Form of a summary that does not depend on internal ids or the order of changes, so two
+/// summaries of the same graph compare equal.
+///
+/// Supernodes are numbered by their sorted member IRIs and edges are sorted.
+#[derive(Debug, PartialEq, Eq, Serialize)]
+pub struct CanonicalSummary {
+    /// Sorted member IRIs of each supernode; the index is the canonical id of the supernode.
+    pub supernodes: Vec<Vec<String>>,
+    /// Sorted summary triples.
+    pub edges: Vec<(Term, String, Term)>,
+}
+
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Term {
+    /// A canonical supernode id.
+    Supernode(usize),
+    /// A node or literal outside any supernode.
+    Node(String),
+}
+
+impl CanonicalSummary {
+    pub fn new(dataset: &Dataset, meta: &Meta) -> Self {
+        let name = |id: &u32| dataset.key_by_value(id).unwrap();
+
+        let mut snodes: Vec<(Vec<String>, u32)> = meta
+            .get_supernodes()
+            .iter()
+            .map(|(id, members)| {
+                let mut names: Vec<String> = members.iter().map(name).collect();
+                names.sort();
+                (names, *id)
+            })
+            .collect();
+        snodes.sort();
+        let index: HashMap<u32, usize> = snodes
+            .iter()
+            .enumerate()
+            .map(|(i, (_, id))| (*id, i))
+            .collect();
+
+        let term = |id: &u32| match index.get(id) {
+            Some(i) => Term::Supernode(*i),
+            None => Term::Node(name(id)),
+        };
+        let mut edges: Vec<(Term, String, Term)> = dataset
+            .triples
+            .data_triples
+            .iter()
+            .map(|t| {
+                let pred = if t.is_type {
+                    TYPE_STRING.to_string()
+                } else {
+                    dataset.pred_term(&t.pred).unwrap()
+                };
+                (term(&t.sub), pred, term(&t.obj))
+            })
+            .collect();
+        edges.sort();
+
+        CanonicalSummary {
+            supernodes: snodes.into_iter().map(|(names, _)| names).collect(),
+            edges,
+        }
+    }
+}
+
+impl fmt::Display for Term {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Term::Supernode(i) => write!(f, "_:s{}", i),
+            Term::Node(name) => write!(f, "{}", name),
+        }
+    }
+}
+
+impl fmt::Display for CanonicalSummary {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (i, members) in self.supernodes.iter().enumerate() {
+            writeln!(f, "_:s{} = {{{}}}", i, members.join(" "))?;
+        }
+        for (s, p, o) in &self.edges {
+            writeln!(f, "{} {} {} .", s, p, o)?;
+        }
+        Ok(())
+    }
+}
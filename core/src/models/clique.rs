@@ -0,0 +1,700 @@
+use crate::util::hash::{HashMap, HashSet};
+use crate::util::heap_size::HeapSize;
+use std::collections::VecDeque;
+
+use crate::metrics::timing::{self, Phase};
+
+use super::meta::Meta;
+use super::node_set::{NodeSet, BITMAP_THRESHOLD};
+use super::triple::Triple;
+
+#[derive(Clone)]
+pub struct Clique {
+    pub preds: Vec<u32>,
+    pub nodes: NodeSet,
+}
+
+impl Clique {
+    /// Creates a new `Clique` with the given `preds` and `nodes`.
+    pub fn new(preds: &Vec<u32>, nodes: &Vec<u32>) -> Self {
+        Self {
+            preds: preds.clone(),
+            nodes: NodeSet::List(nodes.clone()),
+        }
+    }
+
+    /// Adds the preds and nodes of `other` to the `Clique`.
+    pub fn merge(&mut self, other: &Clique) {
+        self.preds.extend(&other.preds);
+        self.nodes.extend(other.nodes.iter());
+    }
+
+    pub fn get_all_edges(
+        &self,
+        is_source: bool,
+        meta: &mut Meta,
+    ) -> (Vec<u32>, Vec<Vec<u32>>, Vec<Vec<u32>>) {
+        let mut singlenodes: Vec<u32> = Vec::new();
+        let mut supernodes: Vec<Vec<u32>> = Vec::new();
+        let mut edges: Vec<Vec<u32>> = Vec::new();
+
+        for n in self.nodes.iter() {
+            if let Some(child_nodes) = meta.get_supernode(&n) {
+                supernodes.push(child_nodes.to_vec());
+
+                for c in child_nodes {
+                    singlenodes.push(*c);
+                    edges.push(meta.get_preds(c, is_source));
+                }
+            } else {
+                singlenodes.push(n);
+                edges.push(meta.get_preds(&n, is_source));
+            }
+        }
+        return (singlenodes, supernodes, edges);
+    }
+}
+
+pub struct CliqueCollection {
+    cliques: Vec<Clique>,
+    queue: VecDeque<usize>,
+    /// Clique index of each node.
+    index_map: HashMap<u32, usize>,
+    /// Position of each node in the nodes of its clique, so nodes are removed without a scan.
+    /// Nodes of cliques kept in a bitmap have none.
+    positions: HashMap<u32, usize>,
+    /// Clique index of each pred. Preds have their own ids, which overlap with node ids.
+    pred_index: HashMap<u32, usize>,
+    /// Number of nodes above which the nodes of a clique are kept in a bitmap, 0 for never.
+    bitmap_threshold: usize,
+}
+
+impl CliqueCollection {
+    /// Creates an empty `CliqueCollection`.
+    ///
+    /// Initially there is only the empty clique.
+    pub fn new() -> Self {
+        Self {
+            cliques: vec![Clique::new(&vec![], &vec![])],
+            queue: VecDeque::new(),
+            index_map: HashMap::default(),
+            positions: HashMap::default(),
+            pred_index: HashMap::default(),
+            bitmap_threshold: BITMAP_THRESHOLD,
+        }
+    }
+
+    /// Sets the number of nodes above which the nodes of a clique are kept in a bitmap, 0 for
+    /// never, and converts the cliques it changes the choice for.
+    pub fn set_bitmap_threshold(&mut self, threshold: usize) {
+        self.bitmap_threshold = threshold;
+        for index in 0..self.cliques.len() {
+            self.fit(index);
+        }
+    }
+
+    /// Converts the nodes of the clique at `index` to a bitmap once it grows above the
+    /// threshold, and back to a list once it shrinks below half of it, so a clique near the
+    /// threshold does not switch back and forth.
+    fn fit(&mut self, index: usize) {
+        let threshold = self.bitmap_threshold;
+        let nodes = &mut self.cliques[index].nodes;
+        match nodes {
+            NodeSet::List(list) if threshold != 0 && list.len() > threshold => {
+                for n in list.iter() {
+                    self.positions.remove(n);
+                }
+                *nodes = NodeSet::Bitmap(list.iter().copied().collect());
+            }
+            NodeSet::Bitmap(bitmap) if threshold == 0 || bitmap.len() < threshold / 2 => {
+                let list: Vec<u32> = bitmap.iter().collect();
+                for (i, n) in list.iter().enumerate() {
+                    self.positions.insert(*n, i);
+                }
+                *nodes = NodeSet::List(list);
+            }
+            _ => {}
+        }
+    }
+
+    /// Restores a `CliqueCollection` from the parts returned by [`CliqueCollection::cliques`],
+    /// [`CliqueCollection::queued`], [`CliqueCollection::node_indices`] and
+    /// [`CliqueCollection::pred_indices`].
+    pub fn from_parts(
+        cliques: Vec<Clique>,
+        queue: VecDeque<usize>,
+        index_map: HashMap<u32, usize>,
+        pred_index: HashMap<u32, usize>,
+    ) -> Self {
+        let positions = cliques
+            .iter()
+            .filter(|c| !c.nodes.is_bitmap())
+            .flat_map(|c| c.nodes.iter().enumerate().map(|(i, n)| (n, i)))
+            .collect();
+        let mut cc = Self {
+            cliques,
+            queue,
+            index_map,
+            positions,
+            pred_index,
+            bitmap_threshold: BITMAP_THRESHOLD,
+        };
+        cc.set_bitmap_threshold(BITMAP_THRESHOLD);
+        cc
+    }
+
+    /// Adds the `node` and `pred` of a new triple to the `CliqueCollection`.
+    ///
+    /// `node` and `pred` do not have to been previosly known.
+    pub fn new_triple(&mut self, node: &u32, pred: &u32) {
+        let node_exists = self.contains_node(&node);
+        let pred_exists = self.contains_pred(&pred);
+
+        if !node_exists && !pred_exists {
+            self.new_clique(&vec![*pred], &vec![*node]);
+        } else if !node_exists && pred_exists {
+            self.add_node_to_clique(node, pred);
+        } else if node_exists && !pred_exists {
+            self.add_pred_to_clique(node, pred);
+        } else {
+            if !self.in_pred_clique(node, pred) {
+                self.merge_cliques(node, pred);
+            }
+        }
+    }
+
+    /// Adds the triples of `node` with each of `preds`, like [`CliqueCollection::new_triple`]
+    /// for each of them in turn, but looks up the clique of `node` only once.
+    pub fn add_signature(&mut self, node: &u32, preds: &[u32]) {
+        let mut index = self.index_map.get(node).copied();
+        for pred in preds {
+            match (index, self.pred_index.get(pred).copied()) {
+                (None, None) => {
+                    self.new_clique(&vec![*pred], &vec![*node]);
+                    index = Some(self.get_index(node));
+                }
+                (None, Some(pred_index)) => {
+                    self.add_node_at(node, pred_index);
+                    index = Some(pred_index);
+                }
+                (Some(_), None) => self.add_pred_to_clique(node, pred),
+                (Some(a), Some(b)) if a != b => self.merge_indices(a, b),
+                _ => {}
+            }
+        }
+    }
+
+    /// Merges the cliques containing `node` and `pred`.
+    ///
+    /// `pred`'s clique is merged into `node`'s clique, leaving `pred`'s clique empty.
+    pub fn merge_cliques(&mut self, node: &u32, pred: &u32) {
+        let a_index = self.get_index(node);
+        let b_index = self.get_pred_index(pred);
+        self.merge_indices(a_index, b_index);
+    }
+
+    /// Merges the clique at `b_index` into the clique at `a_index`, leaving it empty.
+    fn merge_indices(&mut self, a_index: usize, b_index: usize) {
+        let b_clique = self.cliques[b_index].clone();
+        let offset = self.cliques[a_index].nodes.len();
+        self.cliques[a_index].merge(&b_clique);
+        {
+            let _phase = timing::phase(Phase::Index);
+            for p in &b_clique.preds {
+                self.pred_index.insert(*p, a_index);
+            }
+            for n in b_clique.nodes.iter() {
+                self.index_map.insert(n, a_index);
+            }
+            match &self.cliques[a_index].nodes {
+                NodeSet::List(list) => {
+                    for (i, n) in list[offset..].iter().enumerate() {
+                        self.positions.insert(*n, offset + i);
+                    }
+                }
+                NodeSet::Bitmap(_) => {
+                    for n in b_clique.nodes.iter() {
+                        self.positions.remove(&n);
+                    }
+                }
+            }
+        }
+        self.fit(a_index);
+
+        self.remove_clique_by_index(b_index);
+    }
+
+    /// Adds `pred` to the clique containing `node`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `node` is in the empty clique.
+    fn add_pred_to_clique(&mut self, node: &u32, pred: &u32) {
+        let index = *self.index_map.get(node).unwrap();
+        if index == 0 {
+            panic!("Attempting to add new pred to empty clique. wtf?")
+        }
+
+        self.cliques[index].preds.push(*pred);
+        self.pred_index.insert(*pred, index);
+    }
+
+    /// Adds `node` to the clique containing `pred`.
+    fn add_node_to_clique(&mut self, node: &u32, pred: &u32) {
+        self.add_node_at(node, self.get_pred_index(pred));
+    }
+
+    /// Adds `node` to the clique containing the node `other`.
+    fn add_node_beside(&mut self, node: &u32, other: &u32) {
+        self.add_node_at(node, self.get_index(other));
+    }
+
+    fn add_node_at(&mut self, node: &u32, index: usize) {
+        let nodes = &mut self.cliques[index].nodes;
+        if let NodeSet::List(list) = nodes {
+            self.positions.insert(*node, list.len());
+        }
+        nodes.push(*node);
+        self.index_map.insert(*node, index);
+        self.fit(index);
+    }
+
+    /// Adds the node `node` to the empty clique.
+    pub fn add_node_to_empty_clique(&mut self, node: &u32) {
+        self.add_node_at(node, 0);
+    }
+
+    /// Returns a mutable reference to the clique containing `pred`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the `CliqueCollection` does not contain a clique with `pred`.
+    // fn clique_by_pred_mut(&mut self, pred: &u32) -> &mut Clique {
+    //     if let Some(index) = self.pred_index.get(pred) {
+    //         return &mut self.cliques[*index];
+    //     }
+    //     panic!("No clique found for predicate {}", pred);
+    // }
+
+    /// Adds a new clique to the `CliqueCollection` containing `nodes` and `preds`.
+    pub fn new_clique(&mut self, preds: &Vec<u32>, nodes: &Vec<u32>) {
+        let index = if let Some(index) = self.queue.pop_front() {
+            self.cliques[index] = Clique::new(&preds, &nodes);
+            index
+        } else {
+            self.cliques.push(Clique::new(&preds, &nodes));
+            self.cliques.len() - 1
+        };
+        self.set_index(preds, nodes, index);
+        self.fit(index);
+    }
+
+    /// Sets the indices of `nodes` and `preds` to `index`, with `nodes` at the start of the
+    /// nodes of the clique.
+    fn set_index(&mut self, preds: &[u32], nodes: &[u32], index: usize) {
+        let _phase = timing::phase(Phase::Index);
+        for p in preds {
+            self.pred_index.insert(*p, index);
+        }
+        for (i, n) in nodes.iter().enumerate() {
+            self.index_map.insert(*n, index);
+            self.positions.insert(*n, i);
+        }
+    }
+
+    /// Returns true if the `CliqueCollection` contains a clique with `pred`.
+    pub fn contains_pred(&self, pred: &u32) -> bool {
+        self.pred_index.contains_key(pred)
+    }
+
+    /// Returns true if the `CliqueCollection` contains a clique with `node`.
+    pub fn contains_node(&self, node: &u32) -> bool {
+        return self.index_map.contains_key(node);
+    }
+
+    pub fn new_pred(&mut self, pred: &u32) {
+        if self.contains_pred(pred) {
+            panic!(
+                "Attempting to add new pred {} that already exists. wtf?",
+                pred
+            );
+        }
+        self.new_clique(&vec![*pred], &vec![]);
+    }
+
+    pub fn in_same_clique(&self, a: &u32, b: &u32) -> bool {
+        return self.index_map.get(a).unwrap() == self.index_map.get(b).unwrap();
+    }
+
+    pub fn in_empty_clique(&self, node: &u32) -> bool {
+        return *self.index_map.get(node).unwrap() == 0;
+    }
+
+    /// Returns true if `node` is in the clique containing `pred`.
+    pub fn in_pred_clique(&self, node: &u32, pred: &u32) -> bool {
+        self.get_index(node) == self.get_pred_index(pred)
+    }
+
+    pub fn get_index(&self, node: &u32) -> usize {
+        *self.index_map.get(node).unwrap()
+    }
+
+    pub fn get_pred_index(&self, pred: &u32) -> usize {
+        *self.pred_index.get(pred).unwrap()
+    }
+
+    pub fn get_nodes(&self, index: usize) -> Vec<u32> {
+        self.cliques[index].nodes.to_vec()
+    }
+
+    pub fn get_clique_by_node(&self, id: &u32) -> Clique {
+        return self.cliques[self.get_index(id)].clone();
+    }
+
+    pub fn clique_len(&self, index: usize) -> usize {
+        return self.cliques[index].nodes.len();
+    }
+
+    /// Moves `node` to the clique containing `pred`.
+    pub fn move_node(&mut self, node: &u32, pred: &u32) {
+        self.remove_node(node);
+        self.add_node_to_clique(node, pred);
+    }
+
+    pub fn move_node_to_empty_clique(&mut self, node: &u32) {
+        self.remove_node(node);
+        self.add_node_to_empty_clique(node);
+    }
+
+    pub fn remove_node(&mut self, node: &u32) {
+        let index = self.get_index(node);
+        self.take_node(index, node);
+        self.index_map.remove(node);
+
+        if index != 0 && self.cliques[index].nodes.is_empty() {
+            self.queue.push_back(index);
+            // panic!("You just removed the last node from a clique. wtf?");
+        }
+    }
+
+    /// Removes `preds` and `nodes` from the clique at `index` without touching their entries in
+    /// `index_map` and `pred_index`.
+    ///
+    /// Used when `preds` and `nodes` are about to be added to another clique.
+    pub fn detach(&mut self, index: usize, preds: &[u32], nodes: &[u32]) {
+        self.cliques[index].preds.retain(|p| !preds.contains(p));
+        for n in nodes {
+            self.take_node(index, n);
+        }
+    }
+
+    /// Takes `node` out of the nodes of the clique at `index`. In a list, the last node moves
+    /// into its place.
+    fn take_node(&mut self, index: usize, node: &u32) {
+        match &mut self.cliques[index].nodes {
+            NodeSet::List(nodes) => {
+                let position = self.positions.remove(node).unwrap();
+                nodes.swap_remove(position);
+                if let Some(moved) = nodes.get(position) {
+                    self.positions.insert(*moved, position);
+                }
+            }
+            NodeSet::Bitmap(nodes) => {
+                nodes.remove(*node);
+            }
+        }
+        self.fit(index);
+    }
+
+    /// Returns the nodes of the clique at `index` that are also in the clique at `other_index`
+    /// of `other`.
+    pub fn nodes_shared_with(
+        &self,
+        index: usize,
+        other: &CliqueCollection,
+        other_index: usize,
+    ) -> Vec<u32> {
+        self.cliques[index]
+            .nodes
+            .iter()
+            .filter(|n| other.index_map.get(n) == Some(&other_index))
+            .collect()
+    }
+
+    pub fn snode_split_and_move(&mut self, node: &u32, target: &u32) {
+        self.add_node_to_clique(node, target);
+    }
+
+    pub fn snode_split(&mut self, node: &u32, parent: &u32) {
+        self.add_node_beside(node, parent);
+    }
+
+    pub fn to_single_node(&mut self, snode: &u32, single: &u32) {
+        self.add_node_beside(single, snode);
+        self.remove_node(snode);
+    }
+
+    pub fn new_snode(&mut self, old: &Vec<u32>, new: &u32) {
+        self.add_node_beside(new, &old[0]);
+        for n in old {
+            self.remove_node(n);
+        }
+    }
+
+    /// Replaces the nodes `old` by the supernode `new`, merging the cliques of `old` so its
+    /// preds stay in one clique. Unlike [`CliqueCollection::new_snode`], the nodes of `old` may
+    /// be in different cliques.
+    pub fn join_snode(&mut self, old: &[u32], new: &u32) {
+        let mut index = 0;
+        for n in old {
+            match self.get_index(n) {
+                0 => {}
+                i if index == 0 => index = i,
+                i if i != index => self.merge_indices(index, i),
+                _ => {}
+            }
+        }
+        self.add_node_at(new, index);
+        for n in old {
+            self.remove_node(n);
+        }
+    }
+
+    pub fn get_all_edges(
+        &self,
+        target: &u32,
+        is_source: bool,
+        meta: &mut Meta,
+    ) -> (Vec<u32>, Vec<Vec<u32>>, Vec<Vec<u32>>) {
+        return self
+            .get_clique_by_node(target)
+            .get_all_edges(is_source, meta);
+    }
+
+    pub fn cliques(&self) -> &[Clique] {
+        &self.cliques
+    }
+
+    /// Returns the indices of removed cliques, in the order they are reused.
+    pub fn queued(&self) -> &VecDeque<usize> {
+        &self.queue
+    }
+
+    pub fn node_indices(&self) -> &HashMap<u32, usize> {
+        &self.index_map
+    }
+
+    pub fn pred_indices(&self) -> &HashMap<u32, usize> {
+        &self.pred_index
+    }
+
+    /// Returns the number of cliques with at least one predicate.
+    pub fn clique_count(&self) -> usize {
+        self.cliques.iter().filter(|c| !c.preds.is_empty()).count()
+    }
+
+    /// Checks that every node in `nodes` is in exactly one clique, that other nodes are at most
+    /// in the empty clique, and that `index_map`, `positions` and `pred_index` agree with the
+    /// contents of the cliques.
+    pub fn verify(&self, nodes: &[u32]) -> Result<(), String> {
+        let mut seen: HashMap<u32, usize> = HashMap::default();
+
+        for (i, c) in self.cliques.iter().enumerate() {
+            for n in c.nodes.iter() {
+                if let Some(other) = seen.insert(n, i) {
+                    return Err(format!("node {} is in cliques {} and {}", n, other, i));
+                }
+                let position = self.positions.get(&n);
+                let indexed = match &c.nodes {
+                    NodeSet::List(list) => position.map(|p| list.get(*p)) == Some(Some(&n)),
+                    NodeSet::Bitmap(_) => position.is_none(),
+                };
+                if !indexed {
+                    return Err(format!(
+                        "node {} is in clique {}, but not where it is indexed",
+                        n, i
+                    ));
+                }
+                if self.index_map.get(&n) != Some(&i) {
+                    return Err(format!(
+                        "node {} is in clique {}, but indexed as {:?}",
+                        n,
+                        i,
+                        self.index_map.get(&n)
+                    ));
+                }
+            }
+            for p in &c.preds {
+                if self.pred_index.get(p) != Some(&i) {
+                    return Err(format!(
+                        "pred {} is in clique {}, but indexed as {:?}",
+                        p,
+                        i,
+                        self.pred_index.get(p)
+                    ));
+                }
+            }
+        }
+
+        for n in nodes {
+            if !seen.contains_key(n) {
+                return Err(format!("node {} is in no clique", n));
+            }
+        }
+        let nodes: HashSet<&u32> = nodes.iter().collect();
+        for (n, i) in &seen {
+            if *i != 0 && !nodes.contains(n) {
+                return Err(format!("unknown node {} is in clique {}", n, i));
+            }
+        }
+        Ok(())
+    }
+
+    pub fn remove_clique_by_index(&mut self, index: usize) {
+        self.cliques[index].nodes = NodeSet::default();
+        self.cliques[index].preds = vec![];
+        self.queue.push_back(index);
+    }
+
+    pub fn remove_supernode(&mut self, p: &u32, meta: &Meta) {
+        for n in meta.get_supernode(p).unwrap() {
+            self.add_node_beside(n, p);
+        }
+        self.remove_node(p);
+    }
+}
+
+/// Which end of its triples a clique groups nodes by.
+///
+/// Source cliques group subjects by their outgoing preds, target cliques group objects by their
+/// incoming preds.
+#[derive(Clone, Copy, PartialEq)]
+pub enum Direction {
+    Source,
+    Target,
+}
+
+impl Direction {
+    pub fn is_source(&self) -> bool {
+        *self == Direction::Source
+    }
+
+    /// Returns the node of `triple` at this end.
+    pub fn node<'a>(&self, triple: &'a Triple) -> &'a u32 {
+        match self {
+            Direction::Source => &triple.sub,
+            Direction::Target => &triple.obj,
+        }
+    }
+}
+
+/// The cliques of one `Direction` together with the cliques of the other one, so algorithms
+/// that mirror each other for source and target cliques can be written once.
+pub struct CliqueSide<'a> {
+    pub direction: Direction,
+    /// The cliques of `direction`.
+    pub cliques: &'a mut CliqueCollection,
+    /// The cliques of the opposite direction.
+    pub other: &'a mut CliqueCollection,
+}
+
+impl<'a> CliqueSide<'a> {
+    pub fn new(
+        direction: Direction,
+        sc: &'a mut CliqueCollection,
+        tc: &'a mut CliqueCollection,
+    ) -> Self {
+        let (cliques, other) = match direction {
+            Direction::Source => (sc, tc),
+            Direction::Target => (tc, sc),
+        };
+        Self {
+            direction,
+            cliques,
+            other,
+        }
+    }
+
+    /// Returns a `CliqueChange` of `new_nodes` in the clique at `index` on this side.
+    pub fn change(&self, index: usize, new_nodes: Vec<u32>) -> CliqueChange {
+        CliqueChange::new(index, new_nodes, self.direction.is_source())
+    }
+}
+
+#[derive(Clone)]
+pub struct CliqueChange {
+    pub clique_index: usize,
+    pub new_nodes: Vec<u32>,
+    pub is_source: bool,
+}
+
+impl CliqueChange {
+    pub fn new(clique_index: usize, new_nodes: Vec<u32>, is_source: bool) -> Self {
+        Self {
+            clique_index,
+            new_nodes,
+            is_source,
+        }
+    }
+
+    /// Returns the change of merging the clique of `pred` into the clique of `node`.
+    pub fn new_merge(cc: &CliqueCollection, node: &u32, pred: &u32, is_source: bool) -> Self {
+        let a_index = cc.get_index(node);
+        let b_index = cc.get_pred_index(pred);
+
+        let change = Self::new(
+            a_index,
+            if cc.clique_len(a_index) < cc.clique_len(b_index) {
+                cc.get_nodes(a_index)
+            } else {
+                cc.get_nodes(b_index)
+            },
+            is_source,
+        );
+
+        return change;
+    }
+
+    pub fn get_super_nodes(
+        self,
+        sc: &mut CliqueCollection,
+        tc: &mut CliqueCollection,
+    ) -> Vec<Vec<u32>> {
+        let mut super_nodes: Vec<Vec<u32>> = Vec::new();
+        let (cc, other) = if self.is_source { (sc, tc) } else { (tc, sc) };
+
+        let mut seen: HashSet<usize> = HashSet::default();
+        for node in self.new_nodes {
+            // Nodes of the same other clique have the same intersection.
+            let other_index = other.get_index(&node);
+            if !seen.insert(other_index) {
+                continue;
+            }
+            // Nodes in both empty cliques have no edges left, and are not in the summary.
+            if self.clique_index == 0 && other_index == 0 {
+                continue;
+            }
+            let intersect = cc.nodes_shared_with(self.clique_index, other, other_index);
+            if intersect.len() >= 2 {
+                super_nodes.push(intersect);
+            }
+        }
+        return super_nodes;
+    }
+}
+
+impl HeapSize for Clique {
+    fn heap_size(&self) -> usize {
+        self.preds.heap_size() + self.nodes.heap_size()
+    }
+}
+
+impl HeapSize for CliqueCollection {
+    fn heap_size(&self) -> usize {
+        self.cliques.heap_size()
+            + self.queue.heap_size()
+            + self.index_map.heap_size()
+            + self.positions.heap_size()
+            + self.pred_index.heap_size()
+    }
+}
@@ -0,0 +1,239 @@
+use crate::util::mmap::Words;
+use std::io::{self, ErrorKind};
+use std::ops::Range;
+
+use super::graph::Graph;
+use super::meta::Meta;
+
+/// First word of a compact meta file, "TRKM".
+const MAGIC: u32 = u32::from_le_bytes(*b"TRKM");
+const VERSION: u32 = 1;
+
+/// Sections of a compact meta file, in file order. Each is a flat array of words.
+const NODES: usize = 0;
+/// Parent of each node, or `NO_PARENT`.
+const PARENTS: usize = 1;
+/// Start of the edges of each node, in `[pred, other]` pairs, plus the end of the last one.
+const OUT_OFFSETS: usize = 2;
+const OUT_EDGES: usize = 3;
+const IN_OFFSETS: usize = 4;
+const IN_EDGES: usize = 5;
+const SUPERNODES: usize = 6;
+const MEMBER_OFFSETS: usize = 7;
+const MEMBERS: usize = 8;
+const ALIAS_SUPERNODES: usize = 9;
+/// Start of each alias in bytes of `ALIAS_BYTES`, plus the end of the last one.
+const ALIAS_OFFSETS: usize = 10;
+/// The aliases, in UTF-8, four bytes to a word.
+const ALIAS_BYTES: usize = 11;
+const SECTIONS: usize = 12;
+
+/// Length of the header: magic, version and the length of each section.
+const HEADER: usize = 2 + SECTIONS;
+
+/// Ids of terms start at 1.
+const NO_PARENT: u32 = 0;
+
+/// Read-only meta for queries, backed by the flat arrays that `Meta::serialize_compact` writes.
+///
+/// The arrays are used as they are in the file, so loading costs nothing up front and processes
+/// mapping the same file share its memory. Nodes and supernodes are sorted by id and found by
+/// binary search. Sources and the types kept apart from the edges are not kept.
+pub struct CompactMeta {
+    words: Words,
+    sections: [Range<usize>; SECTIONS],
+}
+
+impl CompactMeta {
+    /// Checks the header of the compact meta `words` and locates its sections.
+    pub fn new(words: Words) -> io::Result<Self> {
+        let invalid = |message: &str| io::Error::new(ErrorKind::InvalidData, message.to_string());
+        if words.len() < HEADER || words[0] != MAGIC {
+            return Err(invalid("not a compact meta file"));
+        }
+        if words[1] != VERSION {
+            return Err(invalid("unsupported version of compact meta"));
+        }
+
+        let mut sections: [Range<usize>; SECTIONS] = Default::default();
+        let mut start = HEADER;
+        for (i, section) in sections.iter_mut().enumerate() {
+            let end = start + words[2 + i] as usize;
+            *section = start..end;
+            start = end;
+        }
+        if start != words.len() {
+            return Err(invalid("truncated compact meta file"));
+        }
+
+        let meta = CompactMeta { words, sections };
+        let len = |i: usize| meta.section(i).len();
+        let ends_at =
+            |offsets: usize, end: usize| meta.section(offsets).last() == Some(&(end as u32));
+        let nodes = len(NODES);
+        let consistent = len(PARENTS) == nodes
+            && len(OUT_OFFSETS) == nodes + 1
+            && ends_at(OUT_OFFSETS, len(OUT_EDGES) / 2)
+            && len(IN_OFFSETS) == nodes + 1
+            && ends_at(IN_OFFSETS, len(IN_EDGES) / 2)
+            && len(MEMBER_OFFSETS) == len(SUPERNODES) + 1
+            && ends_at(MEMBER_OFFSETS, len(MEMBERS))
+            && len(ALIAS_OFFSETS) == len(ALIAS_SUPERNODES) + 1
+            && meta.section(ALIAS_OFFSETS).last().unwrap().div_ceil(4) == len(ALIAS_BYTES) as u32
+            && len(OUT_EDGES).is_multiple_of(2)
+            && len(IN_EDGES).is_multiple_of(2);
+        if !consistent {
+            return Err(invalid("inconsistent compact meta file"));
+        }
+        Ok(meta)
+    }
+
+    fn section(&self, i: usize) -> &[u32] {
+        &self.words[self.sections[i].clone()]
+    }
+
+    /// Returns entry `i` of the `items` section, located by the `offsets` section in units of
+    /// `width` words.
+    fn slice(&self, offsets: usize, items: usize, i: usize, width: usize) -> &[u32] {
+        let offsets = self.section(offsets);
+        &self.section(items)[offsets[i] as usize * width..offsets[i + 1] as usize * width]
+    }
+
+    fn node_index(&self, node: &u32) -> Option<usize> {
+        self.section(NODES).binary_search(node).ok()
+    }
+
+    fn supernode_index(&self, snode: &u32) -> Option<usize> {
+        self.section(SUPERNODES).binary_search(snode).ok()
+    }
+
+    pub fn contains(&self, node: &u32) -> bool {
+        self.node_index(node).is_some()
+    }
+
+    pub fn contains_supernode(&self, node: &u32) -> bool {
+        self.supernode_index(node).is_some()
+    }
+
+    pub fn get_parent(&self, node: &u32) -> Option<u32> {
+        let i = self.node_index(node)?;
+        Some(self.section(PARENTS)[i]).filter(|p| *p != NO_PARENT)
+    }
+
+    /// Returns the outgoing edges of `node` as `[pred, obj]`, none if it is not a node.
+    pub fn outgoing(&self, node: &u32) -> &[[u32; 2]] {
+        match self.node_index(node) {
+            Some(i) => self.slice(OUT_OFFSETS, OUT_EDGES, i, 2).as_chunks().0,
+            None => &[],
+        }
+    }
+
+    /// Returns the incoming edges of `node` as `[pred, sub]`, none if it is not a node.
+    pub fn incoming(&self, node: &u32) -> &[[u32; 2]] {
+        match self.node_index(node) {
+            Some(i) => self.slice(IN_OFFSETS, IN_EDGES, i, 2).as_chunks().0,
+            None => &[],
+        }
+    }
+
+    /// Returns the members of the supernode `snode`, sorted.
+    pub fn get_supernode(&self, snode: &u32) -> Option<&[u32]> {
+        let i = self.supernode_index(snode)?;
+        Some(self.slice(MEMBER_OFFSETS, MEMBERS, i, 1))
+    }
+
+    pub fn get_alias(&self, snode: &u32) -> Option<String> {
+        let i = self.section(ALIAS_SUPERNODES).binary_search(snode).ok()?;
+        Some(self.alias(i))
+    }
+
+    /// Returns the supernode with the alias `alias`.
+    pub fn supernode_by_alias(&self, alias: &str) -> Option<u32> {
+        let snodes = self.section(ALIAS_SUPERNODES);
+        (0..snodes.len())
+            .find(|i| self.alias(*i) == alias)
+            .map(|i| snodes[i])
+    }
+
+    /// Returns alias `i` of the `ALIAS_BYTES` section.
+    fn alias(&self, i: usize) -> String {
+        let offsets = self.section(ALIAS_OFFSETS);
+        let (start, end) = (offsets[i] as usize, offsets[i + 1] as usize);
+        let bytes: Vec<u8> = self.section(ALIAS_BYTES)[start / 4..end.div_ceil(4)]
+            .iter()
+            .flat_map(|w| w.to_le_bytes())
+            .skip(start % 4)
+            .take(end - start)
+            .collect();
+        String::from_utf8_lossy(&bytes).into_owned()
+    }
+
+    /// Materializes the quotient graph of the summary, like `Meta::summary_graph`.
+    pub fn summary_graph(&self) -> Graph {
+        let representative = |n: &u32| self.get_parent(n).unwrap_or(*n);
+
+        let mut graph = Graph::from_edges(self.section(NODES).iter().flat_map(|n| {
+            let s = representative(n);
+            self.outgoing(n)
+                .iter()
+                .map(move |[p, o]| [s, *p, representative(o)])
+        }));
+        for n in self.section(NODES) {
+            graph.add_node(representative(n));
+        }
+        graph
+    }
+}
+
+/// Returns `meta` in the compact format, as little-endian bytes.
+pub(super) fn serialize(meta: &Meta) -> Vec<u8> {
+    let mut sections: [Vec<u32>; SECTIONS] = Default::default();
+
+    let mut nodes: Vec<u32> = meta.get_nodes().keys().copied().collect();
+    nodes.sort_unstable();
+    sections[OUT_OFFSETS].push(0);
+    sections[IN_OFFSETS].push(0);
+    for n in &nodes {
+        let info = &meta.get_nodes()[n];
+        sections[PARENTS].push(info.parent.unwrap_or(NO_PARENT));
+        sections[OUT_EDGES].extend(info.outgoing.iter().flatten());
+        sections[OUT_OFFSETS]
+            .push(info.outgoing.len() as u32 + sections[OUT_OFFSETS].last().unwrap());
+        sections[IN_EDGES].extend(info.incoming.iter().flatten());
+        sections[IN_OFFSETS]
+            .push(info.incoming.len() as u32 + sections[IN_OFFSETS].last().unwrap());
+    }
+    sections[NODES] = nodes;
+
+    let mut snodes: Vec<u32> = meta.get_supernodes().keys().copied().collect();
+    snodes.sort_unstable();
+    sections[MEMBER_OFFSETS].push(0);
+    for s in &snodes {
+        sections[MEMBERS].extend(&meta.get_supernodes()[s]);
+        sections[MEMBER_OFFSETS].push(sections[MEMBERS].len() as u32);
+    }
+    sections[SUPERNODES] = snodes;
+
+    let mut aliases: Vec<(&u32, &String)> = meta.get_aliases().iter().collect();
+    aliases.sort_unstable();
+    let mut alias_bytes: Vec<u8> = Vec::new();
+    sections[ALIAS_OFFSETS].push(0);
+    for (s, alias) in aliases {
+        sections[ALIAS_SUPERNODES].push(*s);
+        alias_bytes.extend(alias.as_bytes());
+        sections[ALIAS_OFFSETS].push(alias_bytes.len() as u32);
+    }
+    sections[ALIAS_BYTES] = alias_bytes
+        .chunks(4)
+        .map(|w| {
+            let mut word = [0u8; 4];
+            word[..w.len()].copy_from_slice(w);
+            u32::from_le_bytes(word)
+        })
+        .collect();
+
+    let mut words: Vec<u32> = vec![MAGIC, VERSION];
+    words.extend(sections.iter().map(|s| s.len() as u32));
+    words.extend(sections.iter().flatten());
+    words.iter().flat_map(|w| w.to_le_bytes()).collect()
+}
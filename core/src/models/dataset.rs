@@ -0,0 +1,439 @@
+use crate::metrics::cache::CacheStats;
+use crate::models::dict::Dict;
+use crate::models::literals::Literals;
+use crate::models::triple::TripleCollection;
+
+use super::meta::Meta;
+use super::seeds::Seeds;
+use super::triple::{Triple, TYPE_STRING};
+use crate::util::hash::HashSet;
+use crate::util::heap_size::HeapSize;
+use crate::{rdf, DuplicatePolicy, Grouping};
+use std::io::{Error, ErrorKind};
+use std::path::Path;
+use std::rc::Rc;
+
+/// Start of the IRIs of supernodes named after a quoted triple, which is not an IRI itself.
+const QUOTED_SNODE_PREFIX: &str = "urn:teriyaki:quoted:";
+
+pub struct Dataset {
+    dict: Dict,
+    pub triples: TripleCollection,
+    pub insertions: TripleCollection,
+    pub deletions: TripleCollection,
+    /// Lines outside the focused predicates, which are not summarized.
+    pub passthrough: Vec<String>,
+    /// Malformed input lines skipped by the parser, each after a comment giving the reason.
+    pub skipped: Vec<String>,
+    /// Insertions that violate the schema, each after a comment giving the reason.
+    pub violations: Vec<String>,
+    /// Insertions that follow a deletion of the same triple in the update.
+    pub reinserted: HashSet<Triple>,
+    /// Fingerprint of the summary lines read, if all of them are summarized, to find the
+    /// cliques saved for them.
+    pub fingerprint: Option<u64>,
+    /// Whether the summary lines were read in sorted order, which keeps the triples of every
+    /// subject next to each other.
+    pub sorted: bool,
+    /// Groups of nodes set by the user, which supernodes must keep together and apart.
+    pub seeds: Seeds,
+}
+
+impl Dataset {
+    pub fn new(
+        t_l: impl IntoIterator<Item = String>,
+        i_l: Vec<String>,
+        d_l: Vec<String>,
+        meta: &mut Meta,
+        grouping: &Grouping,
+    ) -> Self {
+        Self::new_in(Dict::empty(), t_l, i_l, d_l, meta, grouping)
+    }
+
+    /// Like [`Dataset::new`], keeping the large literals in the side file `literals`.
+    pub fn new_with_literals(
+        t_l: impl IntoIterator<Item = String>,
+        i_l: Vec<String>,
+        d_l: Vec<String>,
+        literals: Literals,
+        meta: &mut Meta,
+        grouping: &Grouping,
+    ) -> Self {
+        let mut dict = Dict::empty();
+        dict.keep_literals(literals);
+        Self::new_in(dict, t_l, i_l, d_l, meta, grouping)
+    }
+
+    fn new_in(
+        mut dict: Dict,
+        t_l: impl IntoIterator<Item = String>,
+        i_l: Vec<String>,
+        d_l: Vec<String>,
+        meta: &mut Meta,
+        grouping: &Grouping,
+    ) -> Self {
+        let triples = TripleCollection::new(t_l, &mut dict, meta, true, grouping);
+        let insertions = TripleCollection::new(i_l, &mut dict, meta, true, grouping);
+        let deletions = TripleCollection::new_with_deletion(d_l, &mut dict, meta, grouping);
+
+        Self {
+            dict,
+            triples,
+            insertions,
+            deletions,
+            passthrough: Vec::new(),
+            skipped: Vec::new(),
+            violations: Vec::new(),
+            reinserted: HashSet::default(),
+            fingerprint: None,
+            sorted: false,
+            seeds: Seeds::default(),
+        }
+    }
+
+    pub fn new_with_dict(
+        t_l: impl IntoIterator<Item = String>,
+        i_l: Vec<String>,
+        d_l: Vec<String>,
+        mut dict: Dict,
+        meta: &mut Meta,
+        grouping: &Grouping,
+    ) -> Self {
+        let triples = TripleCollection::new(t_l, &mut dict, meta, false, grouping);
+        let insertions = TripleCollection::new(i_l, &mut dict, meta, true, grouping);
+        let deletions = TripleCollection::new_with_deletion(d_l, &mut dict, meta, grouping);
+
+        Self {
+            dict,
+            triples,
+            insertions,
+            deletions,
+            passthrough: Vec::new(),
+            skipped: Vec::new(),
+            violations: Vec::new(),
+            reinserted: HashSet::default(),
+            fingerprint: None,
+            sorted: false,
+            seeds: Seeds::default(),
+        }
+    }
+
+    /// Marks the insertions `lines` as following a deletion of the same triple.
+    pub fn set_reinserted(&mut self, lines: &[String]) {
+        self.reinserted = lines
+            .iter()
+            .map(|l| Triple::from_string(l, &mut self.dict))
+            .collect();
+    }
+
+    /// Replaces the pending insertions and deletions with the lines `i_l` and `d_l`.
+    pub fn set_update(
+        &mut self,
+        i_l: Vec<String>,
+        d_l: Vec<String>,
+        meta: &mut Meta,
+        grouping: &Grouping,
+    ) {
+        self.insertions = TripleCollection::new(i_l, &mut self.dict, meta, true, grouping);
+        self.deletions = TripleCollection::new_with_deletion(d_l, &mut self.dict, meta, grouping);
+        self.reinserted.clear();
+    }
+
+    pub fn add_triple(&mut self, triple: Triple, meta: &Meta) {
+        let mut new_triple = triple.clone();
+
+        if let Some(p) = meta.get_parent(&triple.sub) {
+            new_triple.sub = p;
+        }
+        if let Some(p) = meta.get_parent(&triple.obj) {
+            new_triple.obj = p;
+        }
+        self.triples.add_data_triple(&new_triple);
+    }
+
+    pub fn split(&mut self, node: &u32, p: &u32, meta: &Meta, to_single: bool) {
+        // Self-loops of `p` join its members among themselves, and are split apart below.
+        let (loops, outgoing): (Vec<Triple>, Vec<Triple>) = self
+            .triples
+            .neighbors_out(p)
+            .into_iter()
+            .partition(|t| t.obj == *p);
+        let incoming: Vec<Triple> = self
+            .triples
+            .neighbors_in(p)
+            .into_iter()
+            .filter(|t| t.sub != *p)
+            .collect();
+
+        for t in outgoing {
+            if !meta.has_outgoing_triple(node, &t.pred, &t.obj) {
+                continue;
+            }
+            let mut new = t.clone();
+            new.sub = *node;
+            self.split_triple(&t, &new, meta.has_outgoing_triple(p, &t.pred, &t.obj));
+        }
+        for t in incoming {
+            if !meta.has_incoming_triple(&t.sub, &t.pred, node) {
+                continue;
+            }
+            let mut new = t.clone();
+            new.obj = *node;
+            self.split_triple(&t, &new, meta.has_incoming_triple(&t.sub, &t.pred, p));
+        }
+        for t in loops {
+            self.split_loop(&t, node, meta);
+        }
+
+        if !to_single {
+            self.dict.remove_from_name(p, node);
+        }
+    }
+
+    /// Splits the self-loop `old` of a supernode that `node` has just left into the edges of
+    /// `node` with the members left, in either direction, and with itself. The supernode keeps
+    /// `old` if its members left still have an edge among themselves.
+    fn split_loop(&mut self, old: &Triple, node: &u32, meta: &Meta) {
+        let p = old.sub;
+        for (sub, obj) in [(*node, p), (p, *node), (*node, *node)] {
+            if meta.has_edge_between(&sub, &old.pred, &obj) {
+                self.triples.add_data_triple(&Triple {
+                    sub,
+                    obj,
+                    ..old.clone()
+                });
+            }
+        }
+        if !meta.has_edge_between(&p, &old.pred, &p) {
+            self.triples.remove_triple(old);
+        }
+    }
+
+    /// Gives the split-off node the summary triple `old` of its supernode as `new`. The
+    /// supernode keeps `old` if it still `has_edge`.
+    fn split_triple(&mut self, old: &Triple, new: &Triple, has_edge: bool) {
+        if has_edge {
+            self.triples.add_data_triple(new);
+        } else {
+            self.triples.replace_triple(old, new);
+        }
+    }
+
+    pub fn to_single_node(&mut self, p: &u32, node: &u32) {
+        self.rename_triples(&vec![*p], node);
+        if self.dict.contains_value(p) {
+            self.dict.remove_by_value(p);
+        }
+    }
+
+    /// Removes all nodes in `snode` and inserts `new_node`.
+    ///
+    /// `new_node` takes an id of the range of supernodes, and a term no other id has.
+    pub fn new_snode(&mut self, snode: &Vec<u32>, meta: &Meta) -> u32 {
+        let mut remove: Vec<u32> = Vec::new();
+        let first = self.dict.key_by_value(&snode[0]).unwrap();
+        let mut snode_string = match rdf::quoted_terms(&first) {
+            Some(_) => format!("<{}{}", QUOTED_SNODE_PREFIX, Dict::get_name(&first)),
+            None => remove_angle_bracket_at_end(&first).to_string(),
+        };
+        if meta.contains_supernode(&snode[0]) {
+            remove.push(snode[0]);
+        }
+
+        for node in snode.iter().skip(1) {
+            let node_string = self.dict.key_by_value(node).unwrap();
+            snode_string.push_str("_");
+            snode_string.push_str(&Dict::get_name(&node_string));
+            if meta.contains_supernode(&node) {
+                remove.push(*node);
+            }
+        }
+
+        snode_string.push_str(">");
+        let id = self.dict.add_snode(&snode_string);
+        self.rename_triples(snode, &id);
+
+        for r in remove {
+            self.dict.remove_by_value(&r);
+        }
+
+        return id;
+    }
+
+    pub fn remove_supernode(&mut self, p: &u32, snode: Vec<u32>, meta: &mut Meta) {
+        let last = snode.last().copied();
+        meta.split_members(p, snode, |meta, node| self.split(node, p, meta, true));
+        if let Some(last) = last {
+            self.to_single_node(p, &last);
+        }
+    }
+
+    /// Replaces all occurences of a node in `old` with `new` in `triples`.
+    fn rename_triples(&mut self, old: &Vec<u32>, new: &u32) {
+        for n in old {
+            let mut triples = self.triples.neighbors_out(n);
+            triples.extend(
+                self.triples
+                    .neighbors_in(n)
+                    .into_iter()
+                    .filter(|t| t.sub != *n),
+            );
+            for t in triples {
+                let mut renamed = t.clone();
+                renamed.rename_node(n, new);
+                self.triples.replace_triple(&t, &renamed);
+            }
+        }
+    }
+
+    /// Calls `write` with the N-Triples line of each of `triples`, as [`Triple::to_string`]
+    /// returns it. The terms are decoded all at once and the lines built in a single buffer,
+    /// so writing a whole summary allocates next to nothing per triple.
+    pub fn decode_triples<'t, F>(
+        &self,
+        triples: impl IntoIterator<Item = &'t Triple>,
+        mut write: F,
+    ) -> Result<(), Error>
+    where
+        F: FnMut(&str) -> Result<(), Error>,
+    {
+        let terms = self.dict.term_slices()?;
+        let missing = |id: &u32| Error::new(ErrorKind::NotFound, format!("no term for id {}", id));
+        let mut line = String::new();
+        for t in triples {
+            let pred = if t.is_type {
+                TYPE_STRING
+            } else {
+                terms.pred(&t.pred).ok_or_else(|| missing(&t.pred))?
+            };
+            line.clear();
+            rdf::write_normalized(
+                terms.term(&t.sub).ok_or_else(|| missing(&t.sub))?,
+                &mut line,
+            );
+            line.push(' ');
+            rdf::write_normalized(pred, &mut line);
+            line.push(' ');
+            rdf::write_normalized(
+                terms.term(&t.obj).ok_or_else(|| missing(&t.obj))?,
+                &mut line,
+            );
+            line.push_str(" .");
+            write(&line)?;
+        }
+        Ok(())
+    }
+
+    pub fn key_by_value(&self, value: &u32) -> Option<String> {
+        return self.dict.key_by_value(value);
+    }
+
+    /// Like [`Dataset::key_by_value`], without copying the term out of the cache of decoded
+    /// terms.
+    pub fn term(&self, value: &u32) -> Option<Rc<str>> {
+        self.dict.term(value)
+    }
+
+    /// Sets the number of decoded terms the dict keeps.
+    pub fn set_term_cache(&mut self, size: usize) {
+        self.dict.set_term_cache(size);
+    }
+
+    pub fn term_cache_stats(&self) -> CacheStats {
+        self.dict.term_cache_stats()
+    }
+
+    /// Returns the estimated bytes the dict holds on the heap.
+    pub fn dict_heap_size(&self) -> usize {
+        self.dict.heap_size()
+    }
+
+    /// Returns the estimated bytes the summary triples and the changes hold on the heap.
+    pub fn triples_heap_size(&self) -> usize {
+        self.triples.heap_size()
+            + self.insertions.heap_size()
+            + self.deletions.heap_size()
+            + self.passthrough.heap_size()
+            + self.skipped.heap_size()
+            + self.violations.heap_size()
+            + self.reinserted.heap_size()
+    }
+
+    /// Returns the term of the predicate `pred`, or `None` for pseudo-predicates.
+    pub fn pred_term(&self, pred: &u32) -> Option<String> {
+        self.dict.pred_term(pred).cloned()
+    }
+
+    pub fn dict_len(&self) -> usize {
+        self.dict.term_count()
+    }
+
+    /// Applies `policy` to the repeated triples of the summary, see
+    /// [`TripleCollection::apply_duplicate_policy`]. With `Error`, the first repeated triple is
+    /// reported with its terms.
+    pub fn apply_duplicate_policy(&mut self, policy: &DuplicatePolicy) -> Result<(), Error> {
+        match self.triples.apply_duplicate_policy(policy) {
+            Ok(()) => Ok(()),
+            Err(t) => Err(Error::new(
+                ErrorKind::InvalidData,
+                format!("duplicate triple {}", t.to_string(self)),
+            )),
+        }
+    }
+
+    /// Returns an error if the dict ran out of ids.
+    pub fn check_ids(&self) -> Result<(), Error> {
+        self.dict.check_ids()
+    }
+
+    pub fn max_id(&self) -> u32 {
+        self.dict.max_id()
+    }
+
+    pub fn dict_strings(&self) -> Vec<String> {
+        return self.dict.to_strings();
+    }
+
+    /// Returns true if some literals are kept in the side file of literals.
+    pub fn keeps_literals(&self) -> bool {
+        self.dict.keeps_literals()
+    }
+
+    /// Returns the lines of the dict file, writing the literals kept out of memory to `path`.
+    pub fn dict_lines(&self, path: &Path) -> Result<Vec<String>, Error> {
+        self.dict.to_lines(path)
+    }
+
+    pub fn pred_strings(&self) -> Vec<String> {
+        self.dict.pred_strings()
+    }
+
+    pub fn source_strings(&self) -> Vec<String> {
+        self.dict.source_strings()
+    }
+
+    pub fn pred_count(&self) -> usize {
+        self.dict.pred_count()
+    }
+
+    pub fn remove_triple(&mut self, triple: &Triple) {
+        self.triples.remove_triple(triple);
+    }
+
+    /// Returns the id of the term `key`, if it is known.
+    pub fn id_of(&self, key: &str) -> Option<u32> {
+        self.dict.get(&key.to_string()).copied()
+    }
+
+    /// Returns the id of the predicate `key`, if it is known.
+    pub fn pred_id_of(&self, key: &str) -> Option<u32> {
+        self.dict.get_pred(&key.to_string()).copied()
+    }
+}
+
+fn remove_angle_bracket_at_end(string: &String) -> &str {
+    let mut chars = string.chars();
+    chars.next_back();
+
+    return chars.as_str();
+}
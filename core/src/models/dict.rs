@@ -0,0 +1,550 @@
+use super::id_allocator::{IdAllocator, IdKind};
+use super::literals::{self, LiteralRef, Literals};
+use crate::metrics::cache::CacheStats;
+use crate::rdf;
+use crate::util::hash::HashMap;
+use crate::util::heap_size::HeapSize;
+use crate::util::lru::Lru;
+use std::borrow::Cow;
+use std::cell::RefCell;
+use std::fs::File;
+use std::io::{BufWriter, Error, ErrorKind, Write};
+use std::path::Path;
+use std::rc::Rc;
+
+/// Largest id the `Dict` assigns to terms or predicates. Ids above it are left to the
+/// pseudo-predicates of type grouping, which count down from `u32::MAX`.
+pub const MAX_ID: u32 = u32::MAX / 2;
+
+/// Number of decoded terms a `Dict` keeps by default.
+pub const TERM_CACHE: usize = 4096;
+
+/// Ids of the terms of a summary.
+///
+/// Predicates have their own id space, separate from the resources and literals that can be
+/// nodes. There are few of them, so their ids stay small and dense. A term used both as a
+/// predicate and as a node has an id in both. Nodes and supernodes share the ids of the dict
+/// file, in disjoint ranges, see [`IdAllocator`].
+///
+/// With [`Dict::keep_literals`], large literals are kept in a side file instead of memory, and
+/// found by their hash.
+pub struct Dict {
+    dict: HashMap<String, u32>,
+    ids: IdAllocator,
+    literals: Option<Literals>,
+    /// Ids of the terms kept in `literals`, by their hash.
+    kept: HashMap<u64, u32>,
+    /// Where the term of each id of `kept` is in `literals`.
+    refs: HashMap<u32, LiteralRef>,
+    /// Why a literal could not be written to `literals`, if one could not.
+    literals_error: Option<Error>,
+    preds: HashMap<String, u32>,
+    /// Predicate terms, the term of id `i` at index `i - 1`. Predicates are never removed.
+    pred_terms: Vec<String>,
+    /// Ids of the graph terms naming the sources of triples, in their own id space like
+    /// predicates. Id 0 stands for an unknown source.
+    sources: HashMap<String, u32>,
+    /// Source terms, the term of id `i` at index `i - 1`. Sources are never removed.
+    source_terms: Vec<String>,
+    /// Number of terms that did not get an id because all ids up to `MAX_ID` were taken.
+    exhausted: usize,
+    /// Terms of the ids decoded last, which are otherwise found by a scan of `dict` or read
+    /// from `literals`.
+    cache: RefCell<Lru<u32, Rc<str>>>,
+}
+
+impl Dict {
+    /// Creates an new `Dict` from the lines of a dict file and a preds file.
+    pub fn new(dict_lines: &[String], pred_lines: &[String]) -> Self {
+        let mut dict = Dict::empty();
+        for l in pred_lines {
+            dict.pred_id(l);
+        }
+        let usable = dict_lines.len().min(MAX_ID as usize);
+        dict.exhausted = dict_lines.len() - usable;
+        dict.ids = IdAllocator::from_lines(dict_lines.iter().take(usable).map(|l| !l.is_empty()));
+        for (i, l) in dict_lines.iter().take(usable).enumerate() {
+            let id = i as u32 + 1;
+            if l.is_empty() {
+                continue;
+            } else if let Some(r) = LiteralRef::parse(l) {
+                dict.kept.insert(r.hash, id);
+                dict.refs.insert(id, r);
+            } else {
+                dict.dict.insert(l.to_string(), id);
+            }
+        }
+        return dict;
+    }
+
+    /// Creates an empty `Dict` that hands out ids with `ids`.
+    #[cfg(test)]
+    pub(crate) fn with_ids(ids: IdAllocator) -> Self {
+        Self {
+            ids,
+            ..Dict::empty()
+        }
+    }
+
+    /// Creates an empty `Dict`.
+    pub fn empty() -> Self {
+        return Self {
+            dict: HashMap::default(),
+            ids: IdAllocator::default(),
+            literals: None,
+            kept: HashMap::default(),
+            refs: HashMap::default(),
+            literals_error: None,
+            preds: HashMap::default(),
+            pred_terms: Vec::new(),
+            sources: HashMap::default(),
+            source_terms: Vec::new(),
+            exhausted: 0,
+            cache: RefCell::new(Lru::new(TERM_CACHE)),
+        };
+    }
+
+    /// Adds a new entry `key` to the `Dict`.
+    ///
+    /// The id assigned to `key` is returned. If the ids are exhausted, 0 is returned and
+    /// [`Dict::check_ids`] fails from then on.
+    pub fn add(&mut self, key: &String) -> u32 {
+        let id = match self.allocate(IdKind::Node) {
+            Some(id) => id,
+            None => return 0,
+        };
+        if key.is_empty() {
+            self.ids.free(id);
+        } else {
+            self.insert(key, id);
+        }
+        id
+    }
+
+    /// Adds the supernode named `key` under an id of the range of supernodes, and returns it.
+    ///
+    /// If `key` is already the term of another id, a numbered variant of it is added instead, see
+    /// [`Dict::unused_term`]. If the ids are exhausted, 0 is returned as in [`Dict::add`].
+    pub fn add_snode(&mut self, key: &str) -> u32 {
+        let key = self.unused_term(key);
+        match self.allocate(IdKind::Supernode) {
+            Some(id) => {
+                self.insert(&key, id);
+                id
+            }
+            None => 0,
+        }
+    }
+
+    /// Returns a free id for a term of `kind`, or `None` and counts the term as exhausted if
+    /// there is none.
+    fn allocate(&mut self, kind: IdKind) -> Option<u32> {
+        let id = self.ids.allocate(kind);
+        if id.is_none() {
+            self.exhausted += 1;
+        }
+        id
+    }
+
+    /// Keeps the literals of `literals` out of memory, and the new literals it keeps.
+    ///
+    /// Must be called before terms are added or read, as the dict file lines that refer to
+    /// literals are only read through it.
+    pub fn keep_literals(&mut self, literals: Literals) {
+        self.literals = Some(literals);
+    }
+
+    /// Returns true if some terms are kept in the side file of literals.
+    pub fn keeps_literals(&self) -> bool {
+        !self.refs.is_empty()
+    }
+
+    fn insert(&mut self, key: &String, id: u32) {
+        self.cache.get_mut().remove(&id);
+        let hash = literals::hash(key);
+        if let Some(literals) = self.literals.as_mut() {
+            // Terms with the hash of a kept one stay in memory, so hashes find a single term.
+            if literals.keeps(key) && !self.kept.contains_key(&hash) {
+                match literals.append(key) {
+                    Ok(r) => {
+                        self.kept.insert(hash, id);
+                        self.refs.insert(id, r);
+                        return;
+                    }
+                    Err(e) => {
+                        self.literals_error.get_or_insert(e);
+                    }
+                }
+            }
+        }
+        self.dict.insert(key.to_string(), id);
+    }
+
+    /// Returns the id of `key` if it is kept in the side file of literals.
+    fn find_kept(&self, key: &String) -> Option<&u32> {
+        if self.kept.is_empty() {
+            return None;
+        }
+        let id = self.kept.get(&literals::hash(key))?;
+        let r = &self.refs[id];
+        if r.len as usize != key.len() || self.read_kept(r).ok()? != *key {
+            return None;
+        }
+        Some(id)
+    }
+
+    fn read_kept(&self, r: &LiteralRef) -> Result<String, Error> {
+        match &self.literals {
+            Some(literals) => literals.read(r),
+            None => Err(Error::new(
+                ErrorKind::NotFound,
+                format!(
+                    "dict refers to the missing {} file",
+                    literals::LITERALS_FILE
+                ),
+            )),
+        }
+    }
+
+    /// Returns the id of the predicate `key`, adding it if it is new.
+    ///
+    /// If the ids are exhausted, 0 is returned as in [`Dict::add`].
+    pub fn pred_id(&mut self, key: &String) -> u32 {
+        if let Some(id) = self.preds.get(key) {
+            return *id;
+        }
+        if self.pred_terms.len() >= MAX_ID as usize {
+            self.exhausted += 1;
+            return 0;
+        }
+        self.pred_terms.push(key.to_string());
+        let id = self.pred_terms.len() as u32;
+        self.preds.insert(key.to_string(), id);
+        id
+    }
+
+    /// Returns the id of the predicate `key`, if it is known.
+    pub fn get_pred(&self, key: &String) -> Option<&u32> {
+        self.preds.get(key)
+    }
+
+    /// Returns the term of the predicate `id`. Pseudo-predicates of type grouping have none.
+    pub fn pred_term(&self, id: &u32) -> Option<&String> {
+        match *id {
+            0 => None,
+            id => self.pred_terms.get(id as usize - 1),
+        }
+    }
+
+    /// Returns the number of predicates in the `Dict`.
+    pub fn pred_count(&self) -> usize {
+        self.pred_terms.len()
+    }
+
+    pub fn pred_strings(&self) -> Vec<String> {
+        self.pred_terms.clone()
+    }
+
+    /// Adds the sources of a sources file, so they keep their ids.
+    pub fn add_sources(&mut self, source_lines: &[String]) {
+        for l in source_lines {
+            self.source_id(l);
+        }
+    }
+
+    /// Returns the id of the source `key`, adding it if it is new.
+    ///
+    /// If the ids are exhausted, 0 is returned as in [`Dict::add`].
+    pub fn source_id(&mut self, key: &String) -> u32 {
+        if let Some(id) = self.sources.get(key) {
+            return *id;
+        }
+        if self.source_terms.len() >= MAX_ID as usize {
+            self.exhausted += 1;
+            return 0;
+        }
+        self.source_terms.push(key.to_string());
+        let id = self.source_terms.len() as u32;
+        self.sources.insert(key.to_string(), id);
+        id
+    }
+
+    pub fn source_strings(&self) -> Vec<String> {
+        self.source_terms.clone()
+    }
+
+    /// Returns an error if a term did not get an id because the ids are exhausted, or a
+    /// literal could not be kept in the side file of literals.
+    pub fn check_ids(&self) -> Result<(), Error> {
+        if let Some(e) = &self.literals_error {
+            return Err(Error::new(
+                e.kind(),
+                format!("could not keep a literal out of memory: {}", e),
+            ));
+        }
+        if self.exhausted == 0 {
+            return Ok(());
+        }
+        Err(Error::new(
+            ErrorKind::OutOfMemory,
+            format!(
+                "dict ids exhausted: {} terms did not get an id, as ids are 32-bit and only \
+                 {} of them are available to terms; split the dataset into smaller summaries",
+                self.exhausted, MAX_ID
+            ),
+        ))
+    }
+
+    /// Returns the largest id assigned so far, including ids of removed terms.
+    pub fn max_id(&self) -> u32 {
+        self.total_len() as u32
+    }
+
+    pub fn add_if_new(&mut self, key: &String) -> u32 {
+        return if self.contains(key) {
+            *self.get(key).unwrap()
+        } else {
+            self.add(key)
+        };
+    }
+
+    /// Removes the entry `key` from the `Dict`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the `Dict` does not contain `key`.
+    pub fn remove(&mut self, key: &String) {
+        if !self.contains(key) {
+            panic!("[remove] Key {} not found in dict.", key);
+        };
+        let id = self.take(key).unwrap();
+        self.ids.free(id);
+    }
+
+    /// Removes `key` and returns its id, if it has one.
+    fn take(&mut self, key: &String) -> Option<u32> {
+        if let Some(id) = self.dict.remove(key) {
+            self.cache.get_mut().remove(&id);
+            return Some(id);
+        }
+        let id = *self.find_kept(key)?;
+        self.cache.get_mut().remove(&id);
+        self.kept.remove(&literals::hash(key));
+        self.refs.remove(&id);
+        Some(id)
+    }
+
+    /// Returns true if the `Dict` contains an entry `key`.
+    pub fn contains(&self, key: &String) -> bool {
+        self.get(key).is_some()
+    }
+
+    /// Returns a reference to the id of `key`.
+    pub fn get(&self, key: &String) -> Option<&u32> {
+        self.dict.get(key).or_else(|| self.find_kept(key))
+    }
+
+    /// Returns the key of the value `value`.
+    pub fn key_by_value(&self, value: &u32) -> Option<String> {
+        self.term(value).map(|t| t.to_string())
+    }
+
+    /// Returns the term of the id `value`, shared with the cache of decoded terms so repeated
+    /// lookups of the same id neither search the dict nor allocate.
+    pub fn term(&self, value: &u32) -> Option<Rc<str>> {
+        self.cache.borrow_mut().get_or_load(*value, || {
+            if let Some(r) = self.refs.get(value) {
+                return self.read_kept(r).ok().map(Rc::from);
+            }
+            self.dict
+                .iter()
+                .find(|(_, v)| **v == *value)
+                .map(|(k, _)| Rc::from(k.as_str()))
+        })
+    }
+
+    /// Sets the number of decoded terms kept, dropping those kept so far. 0 keeps none.
+    pub fn set_term_cache(&mut self, size: usize) {
+        self.cache = RefCell::new(Lru::new(size));
+    }
+
+    /// Returns how often decoded terms were found in the cache.
+    pub fn term_cache_stats(&self) -> CacheStats {
+        self.cache.borrow().stats()
+    }
+
+    /// Updates the key of an entry containing `old` to `new`.
+    pub fn update_key(&mut self, new: &String, old: &String) {
+        let val = self.take(old).unwrap();
+        if !self.contains(new) {
+            self.insert(new, val);
+        }
+    }
+
+    /// Returns the number of terms in the `Dict`.
+    pub fn term_count(&self) -> usize {
+        self.dict.len() + self.refs.len()
+    }
+
+    /// Returns the terms of all ids at once, borrowed where they are in memory, to decode many
+    /// triples without looking up every term on its own.
+    pub fn term_slices(&self) -> Result<TermSlices<'_>, Error> {
+        let mut terms: Vec<Cow<str>> = vec![Cow::Borrowed(""); self.total_len()];
+        for (k, v) in &self.dict {
+            terms[*v as usize - 1] = Cow::Borrowed(k);
+        }
+        for (v, r) in &self.refs {
+            terms[*v as usize - 1] = Cow::Owned(self.read_kept(r)?);
+        }
+        Ok(TermSlices {
+            terms,
+            preds: &self.pred_terms,
+        })
+    }
+
+    pub fn to_strings(&self) -> Vec<String> {
+        let mut vec: Vec<String> = Vec::new();
+        vec.resize(self.total_len(), String::new());
+
+        for (k, v) in &self.dict {
+            vec[*v as usize - 1] = k.to_string();
+        }
+        for (v, r) in &self.refs {
+            vec[*v as usize - 1] = self.read_kept(r).unwrap();
+        }
+        return vec;
+    }
+
+    /// Returns the lines of a dict file, writing the literals kept out of memory to the side
+    /// file at `path` and referring to them there.
+    pub fn to_lines(&self, path: &Path) -> Result<Vec<String>, Error> {
+        let mut vec: Vec<String> = Vec::new();
+        vec.resize(self.total_len(), String::new());
+
+        for (k, v) in &self.dict {
+            vec[*v as usize - 1] = k.to_string();
+        }
+        let mut ids: Vec<&u32> = self.refs.keys().collect();
+        ids.sort_unstable();
+        let mut file = BufWriter::new(File::create(path)?);
+        let mut offset = 0;
+        for id in ids {
+            let r = &self.refs[id];
+            let term = self.read_kept(r)?;
+            file.write_all(term.as_bytes())?;
+            vec[*id as usize - 1] = LiteralRef { offset, ..*r }.to_line();
+            offset += r.len as u64;
+        }
+        file.flush()?;
+        Ok(vec)
+    }
+
+    fn total_len(&self) -> usize {
+        self.ids.last_id() as usize
+    }
+
+    pub fn remove_from_name(&mut self, snode: &u32, node: &u32) {
+        let mut snode_string = self.key_by_value(snode).unwrap();
+        let node_string = Dict::get_name(&self.key_by_value(node).unwrap());
+
+        let index = snode_string.find(&node_string).unwrap();
+
+        let old_key = snode_string.clone();
+        if index + node_string.len() + 1 == snode_string.len() {
+            snode_string.replace_range(index - 1..index + node_string.len(), "");
+        } else {
+            snode_string.replace_range(index..index + node_string.len() + 1, "");
+        }
+
+        let snode_string = self.unused_term(&snode_string);
+        self.update_key(&snode_string, &old_key);
+    }
+
+    /// Returns `term`, or the first of `term_2`, `term_3`, ... that is not yet in the `Dict`, so
+    /// names made up for supernodes never take the term of another id.
+    pub fn unused_term(&self, term: &str) -> String {
+        if !self.contains(&term.to_string()) {
+            return term.to_string();
+        }
+        let stem = term.strip_suffix('>').unwrap_or(term);
+        let close = &term[stem.len()..];
+        (2..)
+            .map(|i| format!("{}_{}{}", stem, i, close))
+            .find(|t| !self.contains(t))
+            .unwrap()
+    }
+
+    pub fn get_name(string: &String) -> String {
+        // Quoted triples have spaces, which may not end up in the names of supernodes.
+        if let Some(terms) = rdf::quoted_terms(string) {
+            let names: Vec<String> = terms
+                .iter()
+                .map(|t| Dict::get_name(&t.to_string()))
+                .collect();
+            return names.join("-");
+        }
+        let mut name = String::new();
+        let mut chars = string.chars();
+        while let Some(c) = chars.next_back() {
+            if c == '/' {
+                break;
+            }
+            if c == '>' {
+                continue;
+            }
+            name = c.to_string() + &name;
+        }
+        return name;
+    }
+
+    pub fn remove_by_value(&mut self, value: &u32) {
+        let key = self.key_by_value(value).unwrap();
+        self.remove(&key);
+    }
+
+    pub fn contains_value(&self, value: &u32) -> bool {
+        if self.refs.contains_key(value) {
+            return true;
+        }
+        for v in self.dict.values() {
+            if *v == *value {
+                return true;
+            }
+        }
+        return false;
+    }
+}
+
+/// The terms of a [`Dict`] by id, see [`Dict::term_slices`].
+pub struct TermSlices<'a> {
+    /// The term of id `i` at index `i - 1`, or an empty one if the id is free.
+    terms: Vec<Cow<'a, str>>,
+    preds: &'a [String],
+}
+
+impl TermSlices<'_> {
+    pub fn term(&self, id: &u32) -> Option<&str> {
+        let term = self.terms.get((*id as usize).checked_sub(1)?)?;
+        (!term.is_empty()).then_some(term)
+    }
+
+    pub fn pred(&self, id: &u32) -> Option<&str> {
+        self.preds
+            .get((*id as usize).checked_sub(1)?)
+            .map(|p| p.as_str())
+    }
+}
+
+impl HeapSize for Dict {
+    fn heap_size(&self) -> usize {
+        self.dict.heap_size()
+            + self.ids.heap_size()
+            + self.kept.heap_size()
+            + self.refs.heap_size()
+            + self.preds.heap_size()
+            + self.pred_terms.heap_size()
+            + self.sources.heap_size()
+            + self.source_terms.heap_size()
+            + self.cache.borrow().heap_size()
+    }
+}
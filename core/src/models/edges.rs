@@ -0,0 +1,163 @@
+use crate::util::heap_size::HeapSize;
+use std::mem::size_of;
+use std::ops::{Deref, DerefMut};
+use std::slice;
+
+/// Number of edges kept without a heap allocation.
+pub const INLINE: usize = 4;
+
+/// Edges of a node as `[pred, other]` pairs: inline for up to [`INLINE`] edges, and a `Vec`
+/// once there are more.
+///
+/// Most nodes only have a handful of edges, so most nodes need no allocation for them. Edges
+/// that once moved to the heap stay there, as a node that had many edges tends to get more.
+/// Like a `Vec`, it derefs to a slice.
+#[derive(Clone, Debug)]
+pub enum Edges {
+    Inline(u8, [[u32; 2]; INLINE]),
+    Heap(Vec<[u32; 2]>),
+}
+
+impl Default for Edges {
+    fn default() -> Self {
+        Edges::Inline(0, [[0; 2]; INLINE])
+    }
+}
+
+impl Edges {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn push(&mut self, edge: [u32; 2]) {
+        match self {
+            Edges::Inline(len, edges) if (*len as usize) < INLINE => {
+                edges[*len as usize] = edge;
+                *len += 1;
+            }
+            Edges::Inline(_, edges) => {
+                let mut heap = Vec::with_capacity(2 * INLINE);
+                heap.extend_from_slice(edges);
+                heap.push(edge);
+                *self = Edges::Heap(heap);
+            }
+            Edges::Heap(edges) => edges.push(edge),
+        }
+    }
+
+    /// Removes and returns the edge at `index`, shifting the following ones.
+    ///
+    /// Panics if `index` is out of bounds.
+    pub fn remove(&mut self, index: usize) -> [u32; 2] {
+        match self {
+            Edges::Inline(len, edges) => {
+                let edge = edges[..*len as usize][index];
+                edges.copy_within(index + 1..*len as usize, index);
+                *len -= 1;
+                edge
+            }
+            Edges::Heap(edges) => edges.remove(index),
+        }
+    }
+
+    /// Keeps only the edges for which `keep` returns true, in their order.
+    pub fn retain<F>(&mut self, mut keep: F)
+    where
+        F: FnMut(&[u32; 2]) -> bool,
+    {
+        match self {
+            Edges::Inline(len, edges) => {
+                let mut kept = 0;
+                for i in 0..*len as usize {
+                    if keep(&edges[i]) {
+                        edges[kept] = edges[i];
+                        kept += 1;
+                    }
+                }
+                *len = kept as u8;
+            }
+            Edges::Heap(edges) => edges.retain(keep),
+        }
+    }
+
+    pub fn as_slice(&self) -> &[[u32; 2]] {
+        self
+    }
+
+    /// Returns true if the edges are kept inline.
+    pub fn is_inline(&self) -> bool {
+        matches!(self, Edges::Inline(..))
+    }
+}
+
+impl Deref for Edges {
+    type Target = [[u32; 2]];
+
+    fn deref(&self) -> &[[u32; 2]] {
+        match self {
+            Edges::Inline(len, edges) => &edges[..*len as usize],
+            Edges::Heap(edges) => edges,
+        }
+    }
+}
+
+impl DerefMut for Edges {
+    fn deref_mut(&mut self) -> &mut [[u32; 2]] {
+        match self {
+            Edges::Inline(len, edges) => &mut edges[..*len as usize],
+            Edges::Heap(edges) => edges,
+        }
+    }
+}
+
+impl<'a> IntoIterator for &'a Edges {
+    type Item = &'a [u32; 2];
+    type IntoIter = slice::Iter<'a, [u32; 2]>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl PartialEq for Edges {
+    fn eq(&self, other: &Self) -> bool {
+        **self == **other
+    }
+}
+
+impl PartialEq<Vec<[u32; 2]>> for Edges {
+    fn eq(&self, other: &Vec<[u32; 2]>) -> bool {
+        **self == **other
+    }
+}
+
+impl From<&[[u32; 2]]> for Edges {
+    fn from(edges: &[[u32; 2]]) -> Self {
+        if edges.len() <= INLINE {
+            let mut inline = [[0; 2]; INLINE];
+            inline[..edges.len()].copy_from_slice(edges);
+            Edges::Inline(edges.len() as u8, inline)
+        } else {
+            Edges::Heap(edges.to_vec())
+        }
+    }
+}
+
+impl From<Vec<[u32; 2]>> for Edges {
+    fn from(edges: Vec<[u32; 2]>) -> Self {
+        if edges.len() <= INLINE {
+            Edges::from(edges.as_slice())
+        } else {
+            Edges::Heap(edges)
+        }
+    }
+}
+
+impl HeapSize for Edges {
+    fn heap_size(&self) -> usize {
+        match self {
+            Edges::Inline(..) => 0,
+            Edges::Heap(edges) => edges.capacity() * size_of::<[u32; 2]>(),
+        }
+    }
+}
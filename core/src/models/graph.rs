@@ -0,0 +1,103 @@
+use crate::util::hash::HashMap;
+use std::collections::BTreeSet;
+
+/// The quotient graph of a summary: every node is a supernode or a node without a parent,
+/// and edges are labeled with predicates.
+///
+/// Edges are stored as `[pred, other]` pairs, in the same encoding as `NodeInfo`.
+#[derive(Default)]
+pub struct Graph {
+    outgoing: HashMap<u32, Vec<[u32; 2]>>,
+    incoming: HashMap<u32, Vec<[u32; 2]>>,
+}
+
+impl Graph {
+    /// Creates a `Graph` from `(sub, pred, obj)` edges. Duplicate edges are only added once.
+    pub fn from_edges<I>(edges: I) -> Self
+    where
+        I: IntoIterator<Item = [u32; 3]>,
+    {
+        let mut graph = Graph::default();
+        let unique: BTreeSet<[u32; 3]> = edges.into_iter().collect();
+
+        for [s, p, o] in unique {
+            graph.outgoing.entry(s).or_default().push([p, o]);
+            graph.incoming.entry(o).or_default().push([p, s]);
+            graph.outgoing.entry(o).or_default();
+            graph.incoming.entry(s).or_default();
+        }
+        graph
+    }
+
+    /// Adds `node` to the `Graph` without any edges, if it is not already present.
+    pub fn add_node(&mut self, node: u32) {
+        self.outgoing.entry(node).or_default();
+        self.incoming.entry(node).or_default();
+    }
+
+    /// Returns all nodes of the `Graph`.
+    pub fn nodes(&self) -> impl Iterator<Item = &u32> {
+        self.outgoing.keys()
+    }
+
+    pub fn node_count(&self) -> usize {
+        self.outgoing.len()
+    }
+
+    /// Returns the outgoing edges of `node` as `[pred, obj]` pairs.
+    pub fn outgoing(&self, node: &u32) -> &[[u32; 2]] {
+        self.outgoing.get(node).map(|e| e.as_slice()).unwrap_or(&[])
+    }
+
+    /// Returns the incoming edges of `node` as `[pred, sub]` pairs.
+    pub fn incoming(&self, node: &u32) -> &[[u32; 2]] {
+        self.incoming.get(node).map(|e| e.as_slice()).unwrap_or(&[])
+    }
+
+    /// Returns all edges of the `Graph` as `[sub, pred, obj]`.
+    pub fn edges(&self) -> impl Iterator<Item = [u32; 3]> + '_ {
+        self.outgoing
+            .iter()
+            .flat_map(|(s, e)| e.iter().map(move |[p, o]| [*s, *p, *o]))
+    }
+
+    /// Returns the edges of a shortest path along edge directions from `from` to `to` with at
+    /// most `max_hops` edges, as `[sub, pred, obj]`, or `None` if there is no such path. The
+    /// path from a node to itself is empty.
+    pub fn reachable(&self, from: u32, to: u32, max_hops: usize) -> Option<Vec<[u32; 3]>> {
+        if from == to {
+            return Some(Vec::new());
+        }
+        // The edge each reached node was first reached over, as `[pred, previous node]`.
+        let mut reached: HashMap<u32, [u32; 2]> = HashMap::default();
+        let mut frontier = vec![from];
+        for _ in 0..max_hops {
+            let mut next: Vec<u32> = Vec::new();
+            for n in &frontier {
+                for [p, o] in self.outgoing(n) {
+                    if *o == from || reached.contains_key(o) {
+                        continue;
+                    }
+                    reached.insert(*o, [*p, *n]);
+                    next.push(*o);
+                }
+            }
+            if reached.contains_key(&to) {
+                let mut path: Vec<[u32; 3]> = Vec::new();
+                let mut node = to;
+                while node != from {
+                    let [p, previous] = reached[&node];
+                    path.push([previous, p, node]);
+                    node = previous;
+                }
+                path.reverse();
+                return Some(path);
+            }
+            if next.is_empty() {
+                break;
+            }
+            frontier = next;
+        }
+        None
+    }
+}
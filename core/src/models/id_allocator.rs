@@ -0,0 +1,121 @@
+use super::dict::MAX_ID;
+use crate::util::heap_size::HeapSize;
+use std::collections::VecDeque;
+
+/// Kind of term an id of the dict is handed out to.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum IdKind {
+    Node,
+    Supernode,
+}
+
+impl IdKind {
+    /// Returns the kind of terms `id` is handed out to: nodes have odd ids, supernodes even ones.
+    pub fn of(id: u32) -> Self {
+        if id % 2 == 1 {
+            IdKind::Node
+        } else {
+            IdKind::Supernode
+        }
+    }
+
+    fn index(self) -> usize {
+        match self {
+            IdKind::Node => 0,
+            IdKind::Supernode => 1,
+        }
+    }
+}
+
+/// Hands out the ids of the terms of a [`Dict`](super::dict::Dict).
+///
+/// Nodes and supernodes take ids from disjoint ranges, told apart by the lowest bit, so a
+/// supernode never takes the id of a node or the other way round. Ids stay the line numbers of
+/// the dict file, which has an empty line for each free id, and the kind of an id is known from
+/// the id alone, so nothing but the dict file is needed to pick up where a run left off.
+///
+/// Ids freed by removed terms are handed out again first, to terms of the same kind. Meta
+/// folders written before nodes and supernodes had their own ranges keep their ids.
+#[derive(Clone, Debug)]
+pub struct IdAllocator {
+    /// Freed ids of each kind, below the next new id of that kind.
+    free: [VecDeque<u32>; 2],
+    /// The next new id of each kind.
+    next: [u32; 2],
+}
+
+impl Default for IdAllocator {
+    fn default() -> Self {
+        IdAllocator {
+            free: [VecDeque::new(), VecDeque::new()],
+            next: [1, 2],
+        }
+    }
+}
+
+impl IdAllocator {
+    /// Creates an `IdAllocator` for the lines of a dict file, where `taken[i]` is true if the
+    /// line of id `i + 1` has a term.
+    pub fn from_lines(taken: impl IntoIterator<Item = bool>) -> Self {
+        let mut ids = IdAllocator::default();
+        let mut free: Vec<u32> = Vec::new();
+        for (i, taken) in taken.into_iter().enumerate() {
+            let id = i as u32 + 1;
+            if taken {
+                let kind = IdKind::of(id).index();
+                ids.next[kind] = ids.next[kind].max(id + 2);
+            } else {
+                free.push(id);
+            }
+        }
+        for id in free {
+            // Free ids past the last taken one of their kind are left to be handed out as new.
+            if id < ids.next[IdKind::of(id).index()] {
+                ids.free(id);
+            }
+        }
+        ids
+    }
+
+    /// Creates an `IdAllocator` whose next new ids are `next`, for nodes and supernodes, as if
+    /// all ids below them were taken.
+    #[cfg(test)]
+    pub(crate) fn starting_at(next: [u32; 2]) -> Self {
+        IdAllocator {
+            next,
+            ..Default::default()
+        }
+    }
+
+    /// Returns a free id for a term of `kind`, or `None` if all of them up to
+    /// [`MAX_ID`] are taken.
+    pub fn allocate(&mut self, kind: IdKind) -> Option<u32> {
+        let k = kind.index();
+        if let Some(id) = self.free[k].pop_front() {
+            return Some(id);
+        }
+        let id = self.next[k];
+        if id > MAX_ID {
+            return None;
+        }
+        self.next[k] += 2;
+        Some(id)
+    }
+
+    /// Frees `id`, to be handed out again to a term of its kind.
+    pub fn free(&mut self, id: u32) {
+        self.free[IdKind::of(id).index()].push_back(id);
+    }
+
+    /// Returns the largest id handed out so far, free or not, which is the number of lines of
+    /// the dict file.
+    pub fn last_id(&self) -> u32 {
+        self.next.iter().map(|n| n.saturating_sub(2)).max().unwrap()
+    }
+}
+
+impl HeapSize for IdAllocator {
+    fn heap_size(&self) -> usize {
+        self.free[0].heap_size() + self.free[1].heap_size()
+    }
+}
@@ -0,0 +1,166 @@
+use crate::util::heap_size::HeapSize;
+use crate::util::rng::fnv1a;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::process;
+
+/// Name of the file in the meta folder that holds the literals kept out of the dict.
+pub const LITERALS_FILE: &str = "literals";
+
+/// Start of the dict file lines that refer to a literal in the literals file. Terms never start
+/// with `@`.
+const REF_PREFIX: &str = "@literal ";
+
+/// Where a term kept out of memory is in the literals file, and the hash it is found by.
+#[derive(Clone, Copy)]
+pub struct LiteralRef {
+    pub offset: u64,
+    pub len: u32,
+    pub hash: u64,
+}
+
+impl HeapSize for LiteralRef {
+    fn heap_size(&self) -> usize {
+        0
+    }
+}
+
+impl LiteralRef {
+    /// Parses a dict file line referring to a literal, `None` if it is a term.
+    pub fn parse(line: &str) -> Option<Self> {
+        let mut words = line.strip_prefix(REF_PREFIX)?.split(' ');
+        Some(LiteralRef {
+            offset: words.next()?.parse().ok()?,
+            len: words.next()?.parse().ok()?,
+            hash: u64::from_str_radix(words.next()?, 16).ok()?,
+        })
+    }
+
+    /// Returns the dict file line referring to the literal.
+    pub fn to_line(&self) -> String {
+        format!("{}{} {} {:x}", REF_PREFIX, self.offset, self.len, self.hash)
+    }
+}
+
+/// Returns the hash literals are found by. It is saved in the dict file, so it must not change
+/// between runs.
+pub fn hash(term: &str) -> u64 {
+    fnv1a(term)
+}
+
+/// Side file of the literals of a `Dict` that are too large to keep in memory.
+///
+/// Literals of earlier runs are read from the literals file of the meta folder. New ones are
+/// appended to a scratch file next to the meta folder, removed on drop, and offsets past the end
+/// of the literals file refer to it. The writer compacts both into a new literals file.
+pub struct Literals {
+    /// Literals longer than this many bytes are kept out of memory, `None` to keep new ones in.
+    max_len: Option<usize>,
+    old: Option<File>,
+    /// Length of the literals file, where the offsets of the scratch file start.
+    base: u64,
+    dir: PathBuf,
+    scratch: Option<(PathBuf, File)>,
+    scratch_len: u64,
+}
+
+impl Literals {
+    /// Opens the literals of the meta folder `folder`, keeping new literals longer than
+    /// `max_len` bytes out of memory.
+    pub fn open(folder: &Path, max_len: Option<usize>) -> io::Result<Self> {
+        let path = folder.join(LITERALS_FILE);
+        let old = if path.exists() {
+            Some(File::open(&path)?)
+        } else {
+            None
+        };
+        let base = match &old {
+            Some(file) => file.metadata()?.len(),
+            None => 0,
+        };
+        Ok(Literals {
+            max_len,
+            old,
+            base,
+            dir: folder.parent().unwrap_or(Path::new("")).to_path_buf(),
+            scratch: None,
+            scratch_len: 0,
+        })
+    }
+
+    /// Returns true if the term `key` is a literal to keep out of memory.
+    pub fn keeps(&self, key: &str) -> bool {
+        self.max_len.is_some_and(|n| key.len() > n) && key.starts_with('"')
+    }
+
+    /// Appends `key` to the scratch file and returns where it is.
+    pub fn append(&mut self, key: &str) -> io::Result<LiteralRef> {
+        if self.scratch.is_none() {
+            let path = self
+                .dir
+                .join(format!(".teriyaki-literals-{}", process::id()));
+            let file = OpenOptions::new()
+                .read(true)
+                .write(true)
+                .create_new(true)
+                .open(&path)?;
+            self.scratch = Some((path, file));
+        }
+        let mut file = &self.scratch.as_ref().unwrap().1;
+        file.seek(SeekFrom::Start(self.scratch_len))?;
+        file.write_all(key.as_bytes())?;
+        let offset = self.base + self.scratch_len;
+        self.scratch_len += key.len() as u64;
+        Ok(LiteralRef {
+            offset,
+            len: key.len() as u32,
+            hash: hash(key),
+        })
+    }
+
+    /// Reads the literal at `r`.
+    pub fn read(&self, r: &LiteralRef) -> io::Result<String> {
+        let (mut file, offset) = match (&self.old, &self.scratch) {
+            (Some(old), _) if r.offset < self.base => (old, r.offset),
+            (_, Some((_, scratch))) => (scratch, r.offset - self.base),
+            _ => {
+                return Err(io::Error::new(
+                    io::ErrorKind::NotFound,
+                    format!("no literal at offset {} of {}", r.offset, LITERALS_FILE),
+                ))
+            }
+        };
+        file.seek(SeekFrom::Start(offset))?;
+        let mut bytes = vec![0; r.len as usize];
+        file.read_exact(&mut bytes)?;
+        String::from_utf8(bytes).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+}
+
+impl Drop for Literals {
+    fn drop(&mut self) {
+        if let Some((path, file)) = self.scratch.take() {
+            // Closed first, since open files cannot be removed everywhere.
+            drop(file);
+            let _ = fs::remove_file(path);
+        }
+    }
+}
+
+/// Replaces the lines of a dict file that refer to the literals file of the meta folder
+/// `folder` by their literals.
+pub fn decode_lines(lines: &mut [String], folder: &Path) -> io::Result<()> {
+    let mut refs = lines
+        .iter_mut()
+        .filter_map(|l| LiteralRef::parse(l).map(|r| (r, l)))
+        .peekable();
+    if refs.peek().is_none() {
+        return Ok(());
+    }
+    let literals = Literals::open(folder, None)?;
+    for (r, l) in refs {
+        *l = literals.read(&r)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,871 @@
+use crate::util::hash::{HashMap, HashSet};
+use crate::util::heap_size::HeapSize;
+
+use super::compact_meta;
+pub use super::edges::Edges;
+use super::graph::Graph;
+use super::triple::Triple;
+use super::triple_filter::TripleFilter;
+
+pub struct Meta {
+    /// Members of each supernode in ascending order, so a member is found by binary search and
+    /// merged supernodes cannot list a node twice.
+    supernodes: HashMap<u32, Vec<u32>>,
+    nodes: HashMap<u32, NodeInfo>,
+    types: Vec<[u32; 2]>,
+    /// Predicates of the edges of the members of each supernode, which make up its signature.
+    snode_preds: HashMap<u32, PredCounts>,
+    /// Stable external identifier of each supernode, which survives the renumbering of
+    /// supernodes on merges and splits.
+    aliases: HashMap<u32, String>,
+    /// Aliases of supernodes removed during the current update, by their smallest member, so a
+    /// supernode rebuilt around that member gets the alias back.
+    released: HashMap<u32, String>,
+    /// Supernodes that lost a member to a split and may no longer form a valid group.
+    drifted: HashSet<u32>,
+    /// Source of each original edge `[sub, pred, obj]` read with one, as a source id of the
+    /// dict. Types kept apart from the edges have no source.
+    sources: HashMap<[u32; 3], u32>,
+    /// Every edge `[sub, pred, obj]`, so most edges that are not present are rejected without
+    /// scanning the edges of the subject.
+    filter: TripleFilter,
+}
+
+impl Meta {
+    pub fn new(
+        mut supernodes: HashMap<u32, Vec<u32>>,
+        nodes: HashMap<u32, NodeInfo>,
+        types: Vec<[u32; 2]>,
+    ) -> Self {
+        // Meta files written before members were kept sorted list them in any order.
+        for members in supernodes.values_mut() {
+            members.sort_unstable();
+            members.dedup();
+        }
+        let mut meta = Self {
+            supernodes,
+            nodes,
+            types,
+            snode_preds: HashMap::default(),
+            aliases: HashMap::default(),
+            released: HashMap::default(),
+            drifted: HashSet::default(),
+            sources: HashMap::default(),
+            filter: TripleFilter::with_capacity(0),
+        };
+        meta.rebuild_filter();
+        let members: Vec<(u32, u32)> = meta
+            .supernodes
+            .iter()
+            .flat_map(|(s, m)| m.iter().map(move |n| (*s, *n)))
+            .collect();
+        for (snode, node) in members {
+            meta.count_preds(&snode, &node, 1);
+        }
+        meta
+    }
+
+    pub fn get_nodes(&self) -> &HashMap<u32, NodeInfo> {
+        &self.nodes
+    }
+
+    /// Returns the alias of each supernode that has one.
+    pub fn get_aliases(&self) -> &HashMap<u32, String> {
+        &self.aliases
+    }
+
+    pub fn set_aliases(&mut self, aliases: HashMap<u32, String>) {
+        self.aliases = aliases;
+    }
+
+    /// Returns the source of each original edge read with one.
+    pub fn get_sources(&self) -> &HashMap<[u32; 3], u32> {
+        &self.sources
+    }
+
+    pub fn set_sources(&mut self, sources: HashMap<[u32; 3], u32>) {
+        self.sources = sources;
+    }
+
+    /// Returns the source id of the original edge `sub pred obj`, if it was read with one.
+    pub fn get_source(&self, sub: &u32, pred: &u32, obj: &u32) -> Option<u32> {
+        self.sources.get(&[*sub, *pred, *obj]).copied()
+    }
+
+    /// Records the source of `triple`, if it has one. A triple read again from another source
+    /// takes the new one.
+    fn add_source(&mut self, triple: &Triple) {
+        if triple.source != 0 {
+            self.sources
+                .insert([triple.sub, triple.pred, triple.obj], triple.source);
+        }
+    }
+
+    pub fn contains(&self, node: &u32) -> bool {
+        return self.nodes.contains_key(&node) || self.supernodes.contains_key(&node);
+    }
+
+    pub fn contains_supernode(&self, node: &u32) -> bool {
+        return self.supernodes.contains_key(&node);
+    }
+
+    /// Returns true if `node` is a member of the supernode `snode`.
+    pub fn is_member(&self, snode: &u32, node: &u32) -> bool {
+        self.supernodes
+            .get(snode)
+            .is_some_and(|members| members.binary_search(node).is_ok())
+    }
+
+    pub fn new_node(&mut self, triple: &Triple, is_sub: bool) {
+        let node = if is_sub { triple.sub } else { triple.obj };
+        let other = if is_sub { triple.obj } else { triple.sub };
+        if self.contains(&node) {
+            panic!("Trying to add new node {}, but it already exists", node);
+        }
+        if is_sub {
+            self.add_source(triple);
+        }
+        self.nodes.insert(
+            node,
+            if !is_sub {
+                NodeInfo::new(&None, &[[triple.pred, other]], &[])
+            } else {
+                NodeInfo::new(&None, &[], &[[triple.pred, other]])
+            },
+        );
+        if is_sub {
+            self.filter_edge(triple);
+        }
+    }
+
+    pub fn add_outgoing(&mut self, triple: &Triple) {
+        self.add_source(triple);
+        let node = self.nodes.get_mut(&triple.sub).unwrap();
+        node.outgoing.push([triple.pred, triple.obj]);
+        if let Some(p) = node.parent {
+            self.snode_preds
+                .entry(p)
+                .or_default()
+                .outgoing
+                .add(triple.pred, 1);
+        }
+        self.filter_edge(triple);
+    }
+
+    pub fn add_incoming(&mut self, triple: &Triple) {
+        let node = self.nodes.get_mut(&triple.obj).unwrap();
+        node.incoming.push([triple.pred, triple.sub]);
+        if let Some(p) = node.parent {
+            self.snode_preds
+                .entry(p)
+                .or_default()
+                .incoming
+                .add(triple.pred, 1);
+        }
+    }
+
+    /// Removes every occurrence of the edge of `triple` from the outgoing edges of its subject.
+    pub fn remove_outgoing(&mut self, triple: &Triple) {
+        self.sources.remove(&[triple.sub, triple.pred, triple.obj]);
+        let node = self.nodes.get_mut(&triple.sub).unwrap();
+        let removed = node.remove_outgoing(&triple.pred, &triple.obj, Removal::All);
+        for _ in 0..removed {
+            self.filter.remove([triple.sub, triple.pred, triple.obj]);
+        }
+        if let Some(p) = node.parent {
+            self.snode_preds
+                .get_mut(&p)
+                .unwrap()
+                .outgoing
+                .remove(triple.pred, removed);
+        }
+    }
+
+    /// Removes every occurrence of the edge of `triple` from the incoming edges of its object.
+    pub fn remove_incoming(&mut self, triple: &Triple) {
+        let node = self.nodes.get_mut(&triple.obj).unwrap();
+        let removed = node.remove_incoming(&triple.pred, &triple.sub, Removal::All);
+        if let Some(p) = node.parent {
+            self.snode_preds
+                .get_mut(&p)
+                .unwrap()
+                .incoming
+                .remove(triple.pred, removed);
+        }
+    }
+
+    /// Returns how many times the edge of `triple` is stored for its subject.
+    pub fn edge_multiplicity(&self, triple: &Triple) -> usize {
+        if !self
+            .filter
+            .may_contain([triple.sub, triple.pred, triple.obj])
+        {
+            return 0;
+        }
+        match self.nodes.get(&triple.sub) {
+            Some(n) => n
+                .outgoing
+                .iter()
+                .filter(|x| x[0] == triple.pred && x[1] == triple.obj)
+                .count(),
+            None => 0,
+        }
+    }
+
+    /// Returns true if the edge `sub pred obj` is stored. Most edges that are not are rejected
+    /// by the filter without looking at the edges of `sub`.
+    pub fn contains_edge(&self, sub: &u32, pred: &u32, obj: &u32) -> bool {
+        self.edge_multiplicity(&Triple::new(*sub, *pred, *obj, false)) > 0
+    }
+
+    /// Adds the edge of `triple`, just stored, to the filter, which is rebuilt larger instead once
+    /// it holds more edges than it was sized for.
+    fn filter_edge(&mut self, triple: &Triple) {
+        self.filter.insert([triple.sub, triple.pred, triple.obj]);
+        if self.filter.is_full() {
+            self.rebuild_filter();
+        }
+    }
+
+    /// Sizes the filter for twice the edges stored and fills it with them.
+    fn rebuild_filter(&mut self) {
+        let edges: usize = self.nodes.values().map(|n| n.outgoing.len()).sum();
+        self.filter = TripleFilter::with_capacity(2 * edges);
+        for (s, info) in &self.nodes {
+            for [p, o] in &info.outgoing {
+                self.filter.insert([*s, *p, *o]);
+            }
+        }
+    }
+
+    /// Removes a single occurrence of the edge of `triple` from both of its endpoints.
+    pub fn remove_edge_once(&mut self, triple: &Triple) {
+        let sub = self.nodes.get_mut(&triple.sub).unwrap();
+        let removed = sub.remove_outgoing(&triple.pred, &triple.obj, Removal::Once);
+        for _ in 0..removed {
+            self.filter.remove([triple.sub, triple.pred, triple.obj]);
+        }
+        if let Some(p) = sub.parent {
+            self.snode_preds
+                .get_mut(&p)
+                .unwrap()
+                .outgoing
+                .remove(triple.pred, removed);
+        }
+        let obj = self.nodes.get_mut(&triple.obj).unwrap();
+        let removed = obj.remove_incoming(&triple.pred, &triple.sub, Removal::Once);
+        if let Some(p) = obj.parent {
+            self.snode_preds
+                .get_mut(&p)
+                .unwrap()
+                .incoming
+                .remove(triple.pred, removed);
+        }
+    }
+
+    pub fn get_parent(&self, node: &u32) -> Option<u32> {
+        return self.nodes.get(node).unwrap().parent;
+    }
+
+    pub fn has_parent(&self, node: &u32) -> bool {
+        return self.get_parent(node).is_some();
+    }
+
+    pub fn remove_from_supernode(&mut self, node: &u32) -> bool {
+        let p = self.get_parent(node).unwrap();
+        self.remove_member(&p, node);
+        self.nodes.get_mut(node).unwrap().remove_parent();
+        if self.supernode_len(&p) == 1 {
+            return true;
+        }
+        return false;
+    }
+
+    /// Returns true if `node`, or a member of it if it is a supernode, has an outgoing edge
+    /// with `pred`. Takes constant time for supernodes.
+    pub fn has_outgoing_pred(&self, node: &u32, pred: &u32) -> bool {
+        match self.snode_preds.get(node) {
+            Some(preds) => preds.outgoing.contains(pred),
+            None => self.nodes[node].outgoing.iter().any(|e| e[0] == *pred),
+        }
+    }
+
+    /// Returns true if `node`, or a member of it if it is a supernode, has an incoming edge
+    /// with `pred`. Takes constant time for supernodes.
+    pub fn has_incoming_pred(&self, node: &u32, pred: &u32) -> bool {
+        match self.snode_preds.get(node) {
+            Some(preds) => preds.incoming.contains(pred),
+            None => self.nodes[node].incoming.iter().any(|e| e[0] == *pred),
+        }
+    }
+
+    /// Returns the signature of the supernode `snode`: the predicates of the edges of its
+    /// members. It is kept up to date on merges and splits, so it is not recomputed here.
+    pub fn get_signature(&self, snode: &u32) -> Option<Signature> {
+        let preds = self.snode_preds.get(snode)?;
+        Some(Signature {
+            outgoing: preds.outgoing.preds(),
+            incoming: preds.incoming.preds(),
+        })
+    }
+
+    pub fn has_incoming_triple(&self, s: &u32, p: &u32, o: &u32) -> bool {
+        if !self.has_incoming_pred(o, p) {
+            return false;
+        }
+        if !self.contains_supernode(o) {
+            for v in &self.nodes.get(o).unwrap().incoming {
+                // Members split off a supernode that is being dissolved still have it as their
+                // parent, so their own id is checked first, as in `has_outgoing_triple`.
+                if v[0] == *p && (v[1] == *s || self.get_parent(&v[1]) == Some(*s)) {
+                    return true;
+                }
+            }
+            return false;
+        } else {
+            for v in self.supernodes.get(o).unwrap() {
+                if self.has_incoming_triple(s, p, v) {
+                    return true;
+                }
+            }
+            return false;
+        }
+    }
+
+    /// Returns true if a member of `from` has an edge with `pred` to a member of `to`. Unlike
+    /// [`Meta::has_outgoing_triple`], the members of a supernode are those it lists, whatever
+    /// the parent of split-off nodes still is, so edges within a supernode are told apart.
+    pub fn has_edge_between(&self, from: &u32, pred: &u32, to: &u32) -> bool {
+        let targets = self.members_of(to);
+        self.members_of(from).iter().any(|s| {
+            self.nodes[s]
+                .outgoing
+                .iter()
+                .any(|e| e[0] == *pred && targets.binary_search(&e[1]).is_ok())
+        })
+    }
+
+    /// Returns the members of `node` in ascending order if it is a supernode, or else `node`
+    /// itself.
+    fn members_of<'a>(&'a self, node: &'a u32) -> &'a [u32] {
+        match self.supernodes.get(node) {
+            Some(members) => members,
+            None => std::slice::from_ref(node),
+        }
+    }
+
+    pub fn has_outgoing_triple(&self, s: &u32, p: &u32, o: &u32) -> bool {
+        if !self.has_outgoing_pred(s, p) {
+            return false;
+        }
+        if !self.contains_supernode(s) {
+            for v in &self.nodes.get(s).unwrap().outgoing {
+                if v[0] == *p {
+                    if v[1] == *o {
+                        return true;
+                    }
+                    if let Some(parent) = self.get_parent(&v[1]) {
+                        if parent == *o {
+                            return true;
+                        }
+                    }
+                }
+            }
+            return false;
+        } else {
+            for v in self.supernodes.get(s).unwrap() {
+                if self.has_outgoing_triple(v, p, o) {
+                    return true;
+                }
+            }
+            return false;
+        }
+    }
+
+    pub fn supernode_len(&self, node: &u32) -> usize {
+        if !self.contains_supernode(node) {
+            panic!("Trying to get length of non-supernode {:?}", node);
+        }
+        return self.supernodes.get(node).unwrap().len();
+    }
+
+    /// Turns the supernode `snode`, which has a single member left, back into that member and
+    /// returns it.
+    pub fn to_single_node(&mut self, snode: &u32) -> u32 {
+        if !self.contains_supernode(snode) {
+            panic!("Trying to convert non-supernode {:?} to single node", snode);
+        } else if self.supernode_len(snode) != 1 {
+            panic!(
+                "Trying to convert supernode {:?} to single node, but it has more than one node",
+                snode
+            );
+        }
+        let node = self.supernodes.get(snode).unwrap()[0];
+        self.nodes.get_mut(&node).unwrap().remove_parent();
+        self.supernodes.remove(snode);
+        self.snode_preds.remove(snode);
+        self.release_alias(snode, node);
+        self.drifted.remove(snode);
+        node
+    }
+
+    /// Combines all nodes in `snode` into a single supernode in `stuff.supernodes`.
+    /// Also updates the `parent` field of all nodes in `snode`.
+    pub fn new_snode(&mut self, old: &Vec<u32>, new: &u32) {
+        if self.contains(new) {
+            panic!("Trying to add new supernode {}, but the id is in use", new);
+        }
+        let mut new_snode: Vec<u32> = Vec::new();
+        let mut preds = PredCounts::default();
+        // The alias of the largest merged supernode is kept, the others are released.
+        let mut merged: Vec<(usize, u32)> = old
+            .iter()
+            .filter_map(|n| self.supernodes.get(n).map(|m| (m.len(), *n)))
+            .collect();
+        merged.sort_by_key(|(len, n)| (std::cmp::Reverse(*len), *n));
+        if let Some(alias) = merged.first().and_then(|(_, n)| self.aliases.remove(n)) {
+            self.aliases.insert(*new, alias);
+        }
+        for (_, n) in merged.iter().skip(1) {
+            let min = self.supernodes[n][0];
+            self.release_alias(n, min);
+        }
+
+        for n in old {
+            if self.contains_supernode(&n) {
+                let sn = self.supernodes.get(n).unwrap();
+                new_snode.extend(sn);
+
+                for s in sn {
+                    self.nodes.get_mut(s).unwrap().set_parent(new);
+                }
+                self.supernodes.remove(n);
+                if let Some(p) = self.snode_preds.remove(n) {
+                    preds.merge(p);
+                }
+            } else {
+                self.nodes.get_mut(n).unwrap().set_parent(new);
+                new_snode.push(*n);
+                preds.count(&self.nodes[n], 1);
+            }
+        }
+        new_snode.sort_unstable();
+        new_snode.dedup();
+        self.supernodes.insert(*new, new_snode);
+        self.snode_preds.insert(*new, preds);
+        if old.iter().any(|n| self.drifted.remove(n)) {
+            self.drifted.insert(*new);
+        }
+    }
+
+    /// Moves the alias of the removed supernode `snode` to the released aliases under `min`.
+    fn release_alias(&mut self, snode: &u32, min: u32) {
+        if let Some(alias) = self.aliases.remove(snode) {
+            self.released.insert(min, alias);
+        }
+    }
+
+    pub fn get_alias(&self, snode: &u32) -> Option<&String> {
+        self.aliases.get(snode)
+    }
+
+    /// Returns the supernode with the alias `alias`.
+    pub fn supernode_by_alias(&self, alias: &str) -> Option<u32> {
+        self.aliases
+            .iter()
+            .find(|(_, a)| a.as_str() == alias)
+            .map(|(s, _)| *s)
+    }
+
+    /// Gives every supernode without an alias one, and forgets the aliases released since the
+    /// last call.
+    ///
+    /// A supernode takes back a released alias if it contains the smallest member of the
+    /// supernode that had it. Otherwise its alias is `new_alias` of its smallest member, or of
+    /// the next smallest one if that alias is taken.
+    pub fn assign_aliases<F>(&mut self, new_alias: F)
+    where
+        F: Fn(&u32) -> String,
+    {
+        let mut missing: Vec<u32> = self
+            .supernodes
+            .keys()
+            .filter(|s| !self.aliases.contains_key(s))
+            .copied()
+            .collect();
+        missing.sort();
+
+        let mut taken: HashSet<String> = self.aliases.values().cloned().collect();
+        let released = std::mem::take(&mut self.released);
+        for snode in missing {
+            let members = &self.supernodes[&snode];
+            let alias = members
+                .iter()
+                .filter_map(|m| released.get(m))
+                .find(|a| !taken.contains(*a))
+                .cloned()
+                .or_else(|| members.iter().map(&new_alias).find(|a| !taken.contains(a)));
+            if let Some(alias) = alias {
+                taken.insert(alias.clone());
+                self.aliases.insert(snode, alias);
+            }
+        }
+    }
+
+    pub fn get_node(&self, n: &u32) -> Option<&NodeInfo> {
+        self.nodes.get(n)
+    }
+
+    pub fn get_supernode(&self, n: &u32) -> Option<&Vec<u32>> {
+        return self.supernodes.get(n);
+    }
+
+    /// Splits the `members` of `snode` off one at a time, all but the last, calling `split` with
+    /// each after it is no longer a member. Its parent still points at `snode` meanwhile, so its
+    /// edges within the supernode resolve as before. The members are restored afterwards, so
+    /// members and parents only disagree within `split`.
+    pub fn split_members<F>(&mut self, snode: &u32, members: Vec<u32>, mut split: F)
+    where
+        F: FnMut(&Meta, &u32),
+    {
+        for node in members.iter().take(members.len().saturating_sub(1)) {
+            self.remove_member(snode, node);
+            split(self, node);
+        }
+        self.restore_members(snode, members);
+    }
+
+    /// Removes `node` from the members of `snode` without touching its parent.
+    fn remove_member(&mut self, snode: &u32, node: &u32) {
+        let members = self.supernodes.get_mut(snode).unwrap();
+        if let Ok(i) = members.binary_search(node) {
+            members.remove(i);
+        }
+        self.count_preds(snode, node, -1);
+    }
+
+    /// Makes `members` the members of `snode` again after [`Meta::remove_member`].
+    fn restore_members(&mut self, snode: &u32, mut members: Vec<u32>) {
+        if !self.contains_supernode(snode) {
+            return;
+        }
+        members.sort_unstable();
+        self.snode_preds.remove(snode);
+        for n in &members {
+            self.count_preds(snode, n, 1);
+        }
+        self.supernodes.insert(*snode, members);
+    }
+
+    /// Replaces the predicate of every edge with `new_pred` of it.
+    pub fn map_preds<F>(&mut self, mut new_pred: F)
+    where
+        F: FnMut(u32) -> u32,
+    {
+        for info in self.nodes.values_mut() {
+            for edge in info.incoming.iter_mut().chain(info.outgoing.iter_mut()) {
+                edge[0] = new_pred(edge[0]);
+            }
+        }
+        self.rebuild_filter();
+        self.snode_preds.clear();
+        let members: Vec<(u32, u32)> = self
+            .supernodes
+            .iter()
+            .flat_map(|(s, m)| m.iter().map(move |n| (*s, *n)))
+            .collect();
+        for (snode, node) in members {
+            self.count_preds(&snode, &node, 1);
+        }
+    }
+
+    fn count_preds(&mut self, snode: &u32, node: &u32, sign: isize) {
+        self.snode_preds
+            .entry(*snode)
+            .or_default()
+            .count(&self.nodes[node], sign);
+    }
+
+    pub fn has_no_edges_left(&self, n: &u32, is_source: bool) -> bool {
+        if is_source {
+            return self.nodes.get(n).unwrap().outgoing.is_empty();
+        } else {
+            return self.nodes.get(n).unwrap().incoming.is_empty();
+        }
+    }
+
+    pub fn get_preds(&self, n: &u32, is_source: bool) -> Vec<u32> {
+        if is_source {
+            return self.get_outgoing_preds(n);
+        } else {
+            return self.get_incoming_preds(n);
+        }
+    }
+
+    pub fn get_incoming_preds(&self, n: &u32) -> Vec<u32> {
+        let mut preds: Vec<u32> = Vec::new();
+        for t in &self.nodes.get(n).unwrap().incoming {
+            preds.push(t[0]);
+        }
+        return preds;
+    }
+
+    pub fn get_outgoing_preds(&self, n: &u32) -> Vec<u32> {
+        let mut preds: Vec<u32> = Vec::new();
+        for t in &self.nodes.get(n).unwrap().outgoing {
+            preds.push(t[0]);
+        }
+        return preds;
+    }
+
+    pub fn remove_supernode(&mut self, id: &u32) {
+        let sn = self.get_supernode(id).unwrap().clone();
+        for n in sn {
+            self.nodes.get_mut(&n).unwrap().remove_parent();
+        }
+        let min = self.supernodes[id][0];
+        self.supernodes.remove(id);
+        self.snode_preds.remove(id);
+        self.release_alias(id, min);
+        self.drifted.remove(id);
+    }
+
+    /// Marks `snode` as possibly no longer forming a valid group.
+    pub fn mark_drifted(&mut self, snode: &u32) {
+        self.drifted.insert(*snode);
+    }
+
+    /// Returns the supernodes marked by [`Meta::mark_drifted`] in ascending order and clears the
+    /// marks.
+    pub fn take_drifted(&mut self) -> Vec<u32> {
+        let mut drifted: Vec<u32> = self.drifted.drain().collect();
+        drifted.sort();
+        drifted
+    }
+
+    /// Returns all supernodes and all nodes without a parent that still have edges.
+    pub fn top_level_nodes(&self) -> Vec<u32> {
+        let mut nodes: Vec<u32> = self.supernodes.keys().copied().collect();
+        for (n, info) in &self.nodes {
+            if info.parent.is_none() && !(info.incoming.is_empty() && info.outgoing.is_empty()) {
+                nodes.push(*n);
+            }
+        }
+        nodes
+    }
+
+    pub fn node_count(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn get_supernodes(&self) -> &HashMap<u32, Vec<u32>> {
+        &self.supernodes
+    }
+
+    /// Materializes the quotient graph of the summary, where nodes are replaced by their
+    /// supernode and edges are labeled with predicates.
+    pub fn summary_graph(&self) -> Graph {
+        let representative = |n: &u32| self.nodes.get(n).and_then(|i| i.parent).unwrap_or(*n);
+
+        let mut graph = Graph::from_edges(self.nodes.iter().flat_map(|(n, info)| {
+            let s = representative(n);
+            info.outgoing
+                .iter()
+                .map(move |[p, o]| [s, *p, representative(o)])
+        }));
+        for n in self.nodes.keys() {
+            graph.add_node(representative(n));
+        }
+        graph
+    }
+
+    /// Returns the meta as the flat arrays of a compact meta file, for `CompactMeta` to read.
+    /// Sources and the state kept between the steps of an update are left out.
+    pub fn serialize_compact(&self) -> Vec<u8> {
+        compact_meta::serialize(self)
+    }
+
+    pub fn get_types(&self) -> &Vec<[u32; 2]> {
+        return &self.types;
+    }
+
+    /// Adds the type `o` to `s`. Types are kept as a set, so adding a known type does nothing.
+    pub fn add_type(&mut self, s: &u32, o: &u32) {
+        if self.types.contains(&[*s, *o]) {
+            return;
+        }
+        self.types.push([*s, *o]);
+    }
+
+    /// Deletes the type `o` of `s`. Returns false if `s` does not have that type.
+    pub fn delete_type(&mut self, s: &u32, o: &u32) -> bool {
+        for i in 0..self.types.len() {
+            if self.types[i][0] == *s && self.types[i][1] == *o {
+                self.types.remove(i);
+                return true;
+            }
+        }
+        false
+    }
+}
+
+/// Predicates of the edges of the members of a supernode, by direction, in ascending order.
+#[derive(Debug, Default, PartialEq)]
+pub struct Signature {
+    pub outgoing: Vec<u32>,
+    pub incoming: Vec<u32>,
+}
+
+/// Number of edges per predicate of the members of a supernode, by direction.
+#[derive(Default)]
+struct PredCounts {
+    outgoing: Counts,
+    incoming: Counts,
+}
+
+impl PredCounts {
+    /// Adds (`sign` 1) or removes (`sign` -1) the edges of `node`.
+    fn count(&mut self, node: &NodeInfo, sign: isize) {
+        for [p, _] in &node.outgoing {
+            self.outgoing.change(*p, sign);
+        }
+        for [p, _] in &node.incoming {
+            self.incoming.change(*p, sign);
+        }
+    }
+
+    fn merge(&mut self, other: PredCounts) {
+        for (p, n) in other.outgoing.0 {
+            self.outgoing.add(p, n);
+        }
+        for (p, n) in other.incoming.0 {
+            self.incoming.add(p, n);
+        }
+    }
+}
+
+#[derive(Default)]
+struct Counts(HashMap<u32, usize>);
+
+impl Counts {
+    fn contains(&self, pred: &u32) -> bool {
+        self.0.contains_key(pred)
+    }
+
+    fn preds(&self) -> Vec<u32> {
+        let mut preds: Vec<u32> = self.0.keys().copied().collect();
+        preds.sort_unstable();
+        preds
+    }
+
+    fn add(&mut self, pred: u32, n: usize) {
+        if n > 0 {
+            *self.0.entry(pred).or_default() += n;
+        }
+    }
+
+    fn remove(&mut self, pred: u32, n: usize) {
+        if n == 0 {
+            return;
+        }
+        let count = self.0.get_mut(&pred).unwrap();
+        *count -= n;
+        if *count == 0 {
+            self.0.remove(&pred);
+        }
+    }
+
+    fn change(&mut self, pred: u32, sign: isize) {
+        if sign > 0 {
+            self.add(pred, 1);
+        } else {
+            self.remove(pred, 1);
+        }
+    }
+}
+
+/// How many occurrences of a matching edge to remove.
+#[derive(Clone, Copy)]
+pub enum Removal {
+    All,
+    Once,
+}
+
+pub struct NodeInfo {
+    pub parent: Option<u32>,
+    pub incoming: Edges,
+    pub outgoing: Edges,
+}
+
+impl NodeInfo {
+    pub fn new(parent: &Option<u32>, incoming: &[[u32; 2]], outgoing: &[[u32; 2]]) -> Self {
+        NodeInfo {
+            parent: parent.clone(),
+            incoming: Edges::from(incoming),
+            outgoing: Edges::from(outgoing),
+        }
+    }
+
+    fn remove_parent(&mut self) {
+        self.parent = None;
+    }
+
+    fn set_parent(&mut self, parent: &u32) {
+        self.parent = Some(*parent);
+    }
+
+    /// Removes the incoming edge `[pred, sub]`. Only edges matching both `pred` and `sub`
+    /// are removed. Returns the number of removed edges.
+    pub fn remove_incoming(&mut self, pred: &u32, sub: &u32, removal: Removal) -> usize {
+        remove_edge(&mut self.incoming, [*pred, *sub], removal)
+    }
+
+    /// Removes the outgoing edge `[pred, obj]`. Only edges matching both `pred` and `obj`
+    /// are removed. Returns the number of removed edges.
+    pub fn remove_outgoing(&mut self, pred: &u32, obj: &u32, removal: Removal) -> usize {
+        remove_edge(&mut self.outgoing, [*pred, *obj], removal)
+    }
+}
+
+fn remove_edge(edges: &mut Edges, edge: [u32; 2], removal: Removal) -> usize {
+    match removal {
+        Removal::All => {
+            let len = edges.len();
+            edges.retain(|x| *x != edge);
+            len - edges.len()
+        }
+        Removal::Once => match edges.iter().position(|x| *x == edge) {
+            Some(i) => {
+                edges.remove(i);
+                1
+            }
+            None => 0,
+        },
+    }
+}
+
+impl HeapSize for Meta {
+    fn heap_size(&self) -> usize {
+        self.supernodes.heap_size()
+            + self.nodes.heap_size()
+            + self.types.heap_size()
+            + self.snode_preds.heap_size()
+            + self.aliases.heap_size()
+            + self.released.heap_size()
+            + self.drifted.heap_size()
+            + self.sources.heap_size()
+            + self.filter.heap_size()
+    }
+}
+
+impl HeapSize for NodeInfo {
+    fn heap_size(&self) -> usize {
+        self.incoming.heap_size() + self.outgoing.heap_size()
+    }
+}
+
+impl HeapSize for PredCounts {
+    fn heap_size(&self) -> usize {
+        self.outgoing.0.heap_size() + self.incoming.0.heap_size()
+    }
+}
@@ -0,0 +1,333 @@
+use crate::util::heap_size::HeapSize;
+use std::mem::size_of;
+use std::slice;
+
+/// Number of nodes above which the nodes of a clique are kept in a bitmap by default.
+pub const BITMAP_THRESHOLD: usize = 4096;
+
+/// Most low halves an array container holds; a dense container takes as much memory.
+const ARRAY_MAX: usize = 4096;
+
+/// Words of a dense container, one bit for each of the 2^16 low halves.
+const DENSE_WORDS: usize = 1024;
+
+/// Nodes of a clique: a list for small cliques and a bitmap for large ones.
+///
+/// Most cliques hold a handful of nodes, for which a list is smallest and fastest, while a few
+/// giant cliques hold most of the nodes, for which a bitmap takes a fraction of the memory and
+/// removes nodes without their positions. A list keeps nodes in the order they were added, with
+/// a removed node replaced by the last; a bitmap in id order.
+#[derive(Clone, Debug, PartialEq)]
+pub enum NodeSet {
+    List(Vec<u32>),
+    Bitmap(Bitmap),
+}
+
+impl Default for NodeSet {
+    fn default() -> Self {
+        NodeSet::List(Vec::new())
+    }
+}
+
+impl NodeSet {
+    pub fn len(&self) -> usize {
+        match self {
+            NodeSet::List(list) => list.len(),
+            NodeSet::Bitmap(bitmap) => bitmap.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn is_bitmap(&self) -> bool {
+        matches!(self, NodeSet::Bitmap(_))
+    }
+
+    pub fn iter(&self) -> Nodes<'_> {
+        match self {
+            NodeSet::List(list) => Nodes::List(list.iter()),
+            NodeSet::Bitmap(bitmap) => Nodes::Bitmap(bitmap.iter()),
+        }
+    }
+
+    pub fn to_vec(&self) -> Vec<u32> {
+        self.iter().collect()
+    }
+
+    pub fn contains(&self, node: u32) -> bool {
+        match self {
+            NodeSet::List(list) => list.contains(&node),
+            NodeSet::Bitmap(bitmap) => bitmap.contains(node),
+        }
+    }
+
+    /// Adds `node`, which must not be in the set yet.
+    pub fn push(&mut self, node: u32) {
+        match self {
+            NodeSet::List(list) => list.push(node),
+            NodeSet::Bitmap(bitmap) => {
+                bitmap.insert(node);
+            }
+        }
+    }
+
+    pub fn extend(&mut self, nodes: Nodes) {
+        for n in nodes {
+            self.push(n);
+        }
+    }
+}
+
+/// Iterator over the nodes of a [`NodeSet`].
+pub enum Nodes<'a> {
+    List(slice::Iter<'a, u32>),
+    Bitmap(BitmapIter<'a>),
+}
+
+impl Iterator for Nodes<'_> {
+    type Item = u32;
+
+    fn next(&mut self) -> Option<u32> {
+        match self {
+            Nodes::List(iter) => iter.next().copied(),
+            Nodes::Bitmap(iter) => iter.next(),
+        }
+    }
+}
+
+/// Compressed set of ids in the manner of a roaring bitmap.
+///
+/// Ids are grouped by their high 16 bits into containers, each a sorted array of the low halves
+/// while it holds at most `ARRAY_MAX` of them and a dense bitmap of all 2^16 after that. Ids
+/// in a range are then two bytes each while sparse and a bit each while dense.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Bitmap {
+    /// Containers sorted by their high half.
+    containers: Vec<(u16, Container)>,
+    len: usize,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Container {
+    Array(Vec<u16>),
+    Dense { bits: Box<[u64]>, len: usize },
+}
+
+impl Bitmap {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn contains(&self, id: u32) -> bool {
+        let (high, low) = split(id);
+        match self.container(high) {
+            Ok(i) => match &self.containers[i].1 {
+                Container::Array(lows) => lows.binary_search(&low).is_ok(),
+                Container::Dense { bits, .. } => bits[low as usize / 64] & bit(low) != 0,
+            },
+            Err(_) => false,
+        }
+    }
+
+    /// Adds `id`, returning false if it was already in the set.
+    pub fn insert(&mut self, id: u32) -> bool {
+        let (high, low) = split(id);
+        let i = match self.container(high) {
+            Ok(i) => i,
+            Err(i) => {
+                self.containers
+                    .insert(i, (high, Container::Array(Vec::new())));
+                i
+            }
+        };
+        let container = &mut self.containers[i].1;
+        let added = match container {
+            Container::Array(lows) => match lows.binary_search(&low) {
+                Ok(_) => false,
+                Err(at) => {
+                    lows.insert(at, low);
+                    if lows.len() > ARRAY_MAX {
+                        *container = to_dense(lows);
+                    }
+                    true
+                }
+            },
+            Container::Dense { bits, len } => {
+                let word = &mut bits[low as usize / 64];
+                let added = *word & bit(low) == 0;
+                *word |= bit(low);
+                *len += added as usize;
+                added
+            }
+        };
+        self.len += added as usize;
+        added
+    }
+
+    /// Removes `id`, returning false if it was not in the set.
+    pub fn remove(&mut self, id: u32) -> bool {
+        let (high, low) = split(id);
+        let Ok(i) = self.container(high) else {
+            return false;
+        };
+        let container = &mut self.containers[i].1;
+        let removed = match container {
+            Container::Array(lows) => match lows.binary_search(&low) {
+                Ok(at) => {
+                    lows.remove(at);
+                    true
+                }
+                Err(_) => false,
+            },
+            Container::Dense { bits, len } => {
+                let word = &mut bits[low as usize / 64];
+                let removed = *word & bit(low) != 0;
+                *word &= !bit(low);
+                *len -= removed as usize;
+                if *len <= ARRAY_MAX {
+                    *container = to_array(bits);
+                }
+                removed
+            }
+        };
+        if matches!(container, Container::Array(lows) if lows.is_empty()) {
+            self.containers.remove(i);
+        }
+        self.len -= removed as usize;
+        removed
+    }
+
+    /// Returns the ids in increasing order.
+    pub fn iter(&self) -> BitmapIter<'_> {
+        BitmapIter {
+            containers: self.containers.iter(),
+            current: None,
+        }
+    }
+
+    fn container(&self, high: u16) -> Result<usize, usize> {
+        self.containers.binary_search_by_key(&high, |(h, _)| *h)
+    }
+}
+
+impl FromIterator<u32> for Bitmap {
+    fn from_iter<I: IntoIterator<Item = u32>>(ids: I) -> Self {
+        let mut bitmap = Bitmap::new();
+        for id in ids {
+            bitmap.insert(id);
+        }
+        bitmap
+    }
+}
+
+/// Iterator over the ids of a [`Bitmap`], in increasing order.
+pub struct BitmapIter<'a> {
+    containers: slice::Iter<'a, (u16, Container)>,
+    /// High half of the container being read, and where in it.
+    current: Option<(u32, Position<'a>)>,
+}
+
+enum Position<'a> {
+    Array(slice::Iter<'a, u16>),
+    /// The words left, the index of the current word and its bits not read yet.
+    Dense(&'a [u64], usize, u64),
+}
+
+impl Iterator for BitmapIter<'_> {
+    type Item = u32;
+
+    fn next(&mut self) -> Option<u32> {
+        loop {
+            if let Some((high, position)) = &mut self.current {
+                let low = match position {
+                    Position::Array(lows) => lows.next().map(|l| *l as u32),
+                    Position::Dense(words, index, bits) => loop {
+                        if *bits != 0 {
+                            let low = (*index * 64) as u32 + bits.trailing_zeros();
+                            *bits &= *bits - 1;
+                            break Some(low);
+                        }
+                        *index += 1;
+                        match words.get(*index) {
+                            Some(word) => *bits = *word,
+                            None => break None,
+                        }
+                    },
+                };
+                if let Some(low) = low {
+                    return Some(*high << 16 | low);
+                }
+            }
+            let (high, container) = self.containers.next()?;
+            let position = match container {
+                Container::Array(lows) => Position::Array(lows.iter()),
+                Container::Dense { bits, .. } => Position::Dense(bits, 0, bits[0]),
+            };
+            self.current = Some(((*high as u32), position));
+        }
+    }
+}
+
+fn split(id: u32) -> (u16, u16) {
+    ((id >> 16) as u16, id as u16)
+}
+
+fn bit(low: u16) -> u64 {
+    1 << (low % 64)
+}
+
+fn to_dense(lows: &[u16]) -> Container {
+    let mut bits = vec![0u64; DENSE_WORDS].into_boxed_slice();
+    for low in lows {
+        bits[*low as usize / 64] |= bit(*low);
+    }
+    Container::Dense {
+        bits,
+        len: lows.len(),
+    }
+}
+
+fn to_array(bits: &[u64]) -> Container {
+    let mut lows = Vec::with_capacity(ARRAY_MAX);
+    for (i, word) in bits.iter().enumerate() {
+        let mut word = *word;
+        while word != 0 {
+            lows.push((i * 64) as u16 + word.trailing_zeros() as u16);
+            word &= word - 1;
+        }
+    }
+    Container::Array(lows)
+}
+
+impl HeapSize for NodeSet {
+    fn heap_size(&self) -> usize {
+        match self {
+            NodeSet::List(list) => list.heap_size(),
+            NodeSet::Bitmap(bitmap) => bitmap.heap_size(),
+        }
+    }
+}
+
+impl HeapSize for Bitmap {
+    fn heap_size(&self) -> usize {
+        self.containers.capacity() * size_of::<(u16, Container)>()
+            + self
+                .containers
+                .iter()
+                .map(|(_, c)| match c {
+                    Container::Array(lows) => lows.capacity() * size_of::<u16>(),
+                    Container::Dense { bits, .. } => bits.len() * size_of::<u64>(),
+                })
+                .sum::<usize>()
+    }
+}
@@ -0,0 +1,69 @@
+//! Groups of nodes set by the user with `--seeds`, which supernodes keep together and apart.
+//!
+//! Each line of a seeds file is a comment starting with `#`, or
+//!
+//! ```text
+//! <node> <group>
+//! ```
+//!
+//! where the group is any word. Nodes of the same group must be in one supernode, and nodes of
+//! different groups must not be, whatever their predicates. Nodes without a group are merged
+//! as their predicates decide, into a group or not.
+
+use crate::rdf;
+use crate::util::hash::HashMap;
+use crate::util::io;
+use std::io::{Error, ErrorKind};
+use std::path::Path;
+
+#[derive(Default)]
+pub struct Seeds {
+    /// Number of the group of each node, by its term in canonical notation.
+    groups: HashMap<String, u32>,
+}
+
+impl Seeds {
+    /// Parses the `lines` of a seeds file.
+    pub fn parse(lines: &[String]) -> Result<Self, String> {
+        let mut names: HashMap<&str, u32> = HashMap::default();
+        let mut seeds = Seeds::default();
+        for (i, l) in lines.iter().enumerate() {
+            match l.split_whitespace().collect::<Vec<&str>>()[..] {
+                [] => {}
+                [first, ..] if first.starts_with('#') => {}
+                [node, group] if node.starts_with('<') || node.starts_with("_:") => {
+                    let next = names.len() as u32;
+                    let group = *names.entry(group).or_insert(next);
+                    match seeds.groups.insert(rdf::normalize(node), group) {
+                        Some(other) if other != group => {
+                            return Err(format!("line {}: {} is in two groups", i + 1, node))
+                        }
+                        _ => {}
+                    }
+                }
+                _ => return Err(format!("line {}: expected `<node> <group>`", i + 1)),
+            }
+        }
+        Ok(seeds)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.groups.is_empty()
+    }
+
+    /// Returns the nodes with a group, and their groups.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, u32)> {
+        self.groups.iter().map(|(t, g)| (t.as_str(), *g))
+    }
+
+    /// Returns the group of the node `term`, if it has one.
+    pub fn group(&self, term: &str) -> Option<u32> {
+        self.groups.get(term).copied()
+    }
+}
+
+pub fn read_seeds(path: &Path) -> Result<Seeds, Error> {
+    let lines = io::read_lines(&path)?;
+    Seeds::parse(&lines)
+        .map_err(|e| Error::new(ErrorKind::InvalidData, format!("{}: {}", path.display(), e)))
+}
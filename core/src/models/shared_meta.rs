@@ -0,0 +1,38 @@
+use super::meta::Meta;
+use std::sync::{Arc, PoisonError, RwLock, RwLockReadGuard, RwLockWriteGuard};
+
+/// `Meta` shared between threads: any number of readers, such as queries and stats, or a single
+/// writer, such as the updater, at a time.
+///
+/// Cloning the handle does not clone the metadata.
+// Nothing runs concurrently in this binary yet; this is the handle for a long-running mode.
+#[allow(dead_code)]
+#[derive(Clone)]
+pub struct SharedMeta(Arc<RwLock<Meta>>);
+
+#[allow(dead_code)]
+impl SharedMeta {
+    pub fn new(meta: Meta) -> Self {
+        SharedMeta(Arc::new(RwLock::new(meta)))
+    }
+
+    /// Blocks until no writer holds the metadata.
+    ///
+    /// A writer that panicked may have left the metadata half updated; it is returned anyway,
+    /// and `updater::verify_cliques` tells whether it is still consistent.
+    pub fn read(&self) -> RwLockReadGuard<'_, Meta> {
+        self.0.read().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Blocks until no reader or writer holds the metadata.
+    pub fn write(&self) -> RwLockWriteGuard<'_, Meta> {
+        self.0.write().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Returns the metadata if this is the last handle to it.
+    pub fn into_inner(self) -> Option<Meta> {
+        Arc::try_unwrap(self.0)
+            .ok()
+            .map(|lock| lock.into_inner().unwrap_or_else(PoisonError::into_inner))
+    }
+}
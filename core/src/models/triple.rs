@@ -0,0 +1,327 @@
+use super::dataset::Dataset;
+use super::dict::Dict;
+use super::meta::Meta;
+use super::triple_index::TripleIndex;
+use crate::util::hash::{HashMap, HashSet};
+use crate::util::heap_size::HeapSize;
+use crate::{rdf, DuplicatePolicy, Grouping};
+use std::hash::{Hash, Hasher};
+use std::iter;
+
+pub const TYPE_STRING: &str = "<http://www.w3.org/1999/02/22-rdf-syntax-ns#type>";
+
+#[derive(Clone)]
+pub struct Triple {
+    pub sub: u32,
+    pub pred: u32,
+    pub obj: u32,
+    pub is_type: bool,
+    /// Id of the graph the triple was read from in the `Dict`, or 0 if it has none.
+    pub source: u32,
+}
+
+// The source is provenance, not part of the triple: the same triple from two sources is a
+// duplicate.
+impl PartialEq for Triple {
+    fn eq(&self, other: &Self) -> bool {
+        (self.sub, self.pred, self.obj, self.is_type)
+            == (other.sub, other.pred, other.obj, other.is_type)
+    }
+}
+
+impl Eq for Triple {}
+
+impl Hash for Triple {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        (self.sub, self.pred, self.obj, self.is_type).hash(state);
+    }
+}
+
+impl Triple {
+    pub fn new(sub: u32, pred: u32, obj: u32, is_type: bool) -> Self {
+        Self {
+            sub,
+            pred,
+            obj,
+            is_type,
+            source: 0,
+        }
+    }
+
+    pub fn from_string(line: &String, dict: &mut Dict) -> Self {
+        let words = rdf::split_terms(line);
+        let sub_str = rdf::normalize(words[0]);
+        let pred_str = rdf::normalize(words[1]);
+        let obj_str = rdf::normalize(words[2]);
+        let source = match rdf::graph_of(line) {
+            Some(g) => dict.source_id(&rdf::normalize(g)),
+            None => 0,
+        };
+
+        Triple {
+            sub: dict.add_if_new(&sub_str),
+            pred: dict.pred_id(&pred_str),
+            obj: dict.add_if_new(&obj_str),
+            is_type: pred_str == TYPE_STRING,
+            source,
+        }
+    }
+
+    /// Returns the pseudo-predicate standing for `rdf:type <class>` when grouping by types.
+    ///
+    /// Pseudo-predicates are allocated from the top of the id space so they never collide with
+    /// the ids of predicates in the dict.
+    pub fn type_pred(class: u32) -> u32 {
+        u32::MAX - class
+    }
+
+    pub fn to_string(&self, dataset: &Dataset) -> String {
+        let sub_string = dataset.term(&self.sub).unwrap();
+        let pred_string = if self.is_type {
+            TYPE_STRING.to_string()
+        } else {
+            dataset.pred_term(&self.pred).unwrap()
+        };
+        let obj_string = dataset.term(&self.obj).unwrap();
+        rdf::triple_line(&sub_string, &pred_string, &obj_string)
+    }
+
+    pub fn rename_node(&mut self, old: &u32, new: &u32) {
+        if self.sub == *old {
+            self.sub = *new;
+        }
+        if self.obj == *old {
+            self.obj = *new;
+        }
+    }
+}
+
+pub struct TripleCollection {
+    pub data_triples: Vec<Triple>,
+    /// Number of duplicate triples found by `apply_duplicate_policy`.
+    pub duplicates: usize,
+    /// Occurrences of the triples read more than once with the `Count` policy. The triples
+    /// themselves are kept once, and written as many times as they occur.
+    pub counts: HashMap<Triple, usize>,
+    /// Index of `data_triples` built by [`TripleCollection::build_index`]. While it exists,
+    /// `data_triples` must only be changed through the methods of the collection.
+    index: Option<TripleIndex>,
+}
+
+impl TripleCollection {
+    /// Wraps triples that are already encoded.
+    pub fn from_triples(data_triples: Vec<Triple>) -> Self {
+        Self {
+            data_triples,
+            duplicates: 0,
+            counts: HashMap::default(),
+            index: None,
+        }
+    }
+
+    pub fn new(
+        triples: impl IntoIterator<Item = String>,
+        dict: &mut Dict,
+        meta: &mut Meta,
+        add_type: bool,
+        grouping: &Grouping,
+    ) -> Self {
+        let mut data_triples: Vec<Triple> = Vec::new();
+
+        for l in triples {
+            let t = Triple::from_string(&l, dict);
+            if !t.is_type {
+                data_triples.push(t);
+            } else if *grouping == Grouping::Untyped {
+                continue;
+            } else if *grouping != Grouping::Preds {
+                data_triples.push(Triple {
+                    source: t.source,
+                    ..Triple::new(t.sub, Triple::type_pred(t.obj), t.obj, true)
+                });
+            } else if add_type {
+                meta.add_type(&t.sub, &t.obj);
+            }
+        }
+
+        Self {
+            data_triples,
+            duplicates: 0,
+            counts: HashMap::default(),
+            index: None,
+        }
+    }
+
+    pub fn new_with_deletion(
+        triples: Vec<String>,
+        dict: &mut Dict,
+        meta: &mut Meta,
+        grouping: &Grouping,
+    ) -> Self {
+        let mut data_triples: Vec<Triple> = Vec::new();
+
+        for l in triples {
+            let t = Triple::from_string(&l, dict);
+            if !t.is_type {
+                data_triples.push(t);
+            } else if *grouping == Grouping::Untyped {
+                continue;
+            } else if *grouping != Grouping::Preds {
+                data_triples.push(Triple::new(t.sub, Triple::type_pred(t.obj), t.obj, true));
+            } else if !meta.delete_type(&t.sub, &t.obj) {
+                // Unknown types are left for the updater to reject.
+                data_triples.push(t);
+            }
+        }
+
+        Self {
+            data_triples,
+            duplicates: 0,
+            counts: HashMap::default(),
+            index: None,
+        }
+    }
+
+    /// Removes repeated triples from the collection according to `policy`.
+    ///
+    /// With `Dedupe`, only the first occurrence is kept and the number of dropped occurrences is
+    /// recorded in `duplicates`. `Count` records them too, and keeps the number of occurrences
+    /// of each repeated triple in `counts`. With `Error`, the first repeated triple is returned.
+    pub fn apply_duplicate_policy(&mut self, policy: &DuplicatePolicy) -> Result<(), Triple> {
+        let mut seen: HashSet<Triple> = HashSet::default();
+        let mut unique: Vec<Triple> = Vec::new();
+
+        for t in self.data_triples.drain(..) {
+            if seen.contains(&t) {
+                match policy {
+                    DuplicatePolicy::Dedupe => {}
+                    DuplicatePolicy::Count => *self.counts.entry(t).or_insert(1) += 1,
+                    DuplicatePolicy::Error => return Err(t),
+                }
+                self.duplicates += 1;
+            } else {
+                seen.insert(t.clone());
+                unique.push(t);
+            }
+        }
+        self.data_triples = unique;
+        if self.index.is_some() {
+            self.build_index();
+        }
+        Ok(())
+    }
+
+    /// Returns each triple as many times as it occurs, see `counts`.
+    pub fn occurrences(&self) -> impl Iterator<Item = &Triple> {
+        self.data_triples
+            .iter()
+            .flat_map(|t| iter::repeat_n(t, self.counts.get(t).copied().unwrap_or(1)))
+    }
+
+    /// Indexes the triples by subject and object, which makes looking up, adding, replacing and
+    /// removing triples take constant time instead of a scan.
+    ///
+    /// Removing a triple then moves the last triple into its place instead of shifting the
+    /// following ones.
+    pub fn build_index(&mut self) {
+        self.index = Some(TripleIndex::new(&self.data_triples));
+    }
+
+    pub fn contains(&self, triple: &Triple) -> bool {
+        match &self.index {
+            Some(index) => index.contains(triple),
+            None => self.data_triples.contains(triple),
+        }
+    }
+
+    pub fn add_data_triple(&mut self, triple: &Triple) {
+        if self.contains(triple) {
+            return;
+        }
+        if let Some(index) = &mut self.index {
+            index.insert(triple, self.data_triples.len());
+        }
+        self.data_triples.push(triple.clone());
+    }
+
+    pub fn remove_triple(&mut self, triple: &Triple) {
+        self.counts.remove(triple);
+        let index = match &mut self.index {
+            Some(index) => index,
+            None => return self.data_triples.retain(|t| t != triple),
+        };
+        if let Some(i) = index.remove(triple) {
+            self.data_triples.swap_remove(i);
+            if let Some(moved) = self.data_triples.get(i) {
+                index.insert(moved, i);
+            }
+        }
+    }
+
+    /// Replaces `old` with `new` in place, or removes `old` if `new` already exists.
+    pub fn replace_triple(&mut self, old: &Triple, new: &Triple) {
+        if self.contains(new) {
+            return self.remove_triple(old);
+        }
+        let i = match &mut self.index {
+            Some(index) => index.remove(old),
+            None => self.data_triples.iter().position(|t| t == old),
+        };
+        if let Some(i) = i {
+            if let Some(count) = self.counts.remove(old) {
+                self.counts.insert(new.clone(), count);
+            }
+            self.data_triples[i] = new.clone();
+            if let Some(index) = &mut self.index {
+                index.insert(new, i);
+            }
+        }
+    }
+
+    /// Keeps only the triples for which `keep` returns true.
+    pub fn retain<F>(&mut self, mut keep: F)
+    where
+        F: FnMut(&Triple) -> bool,
+    {
+        self.counts.retain(|t, _| keep(t));
+        self.data_triples.retain(keep);
+        if self.index.is_some() {
+            self.build_index();
+        }
+    }
+
+    /// Returns the triples with `node` as subject.
+    pub fn neighbors_out(&self, node: &u32) -> Vec<Triple> {
+        match &self.index {
+            Some(index) => index.neighbors_out(node),
+            None => self.filter(|t| t.sub == *node),
+        }
+    }
+
+    /// Returns the triples with `node` as object.
+    pub fn neighbors_in(&self, node: &u32) -> Vec<Triple> {
+        match &self.index {
+            Some(index) => index.neighbors_in(node),
+            None => self.filter(|t| t.obj == *node),
+        }
+    }
+
+    fn filter<F>(&self, f: F) -> Vec<Triple>
+    where
+        F: Fn(&Triple) -> bool,
+    {
+        self.data_triples.iter().filter(|t| f(t)).cloned().collect()
+    }
+}
+
+impl HeapSize for Triple {
+    fn heap_size(&self) -> usize {
+        0
+    }
+}
+
+impl HeapSize for TripleCollection {
+    fn heap_size(&self) -> usize {
+        self.data_triples.heap_size() + self.index.heap_size()
+    }
+}
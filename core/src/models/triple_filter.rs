@@ -0,0 +1,90 @@
+use crate::util::heap_size::HeapSize;
+
+/// Number of counters per expected triple, which keeps false positives near 1% with
+/// `HASHES` hashes.
+const COUNTERS_PER_TRIPLE: usize = 10;
+const HASHES: usize = 7;
+const MIN_COUNTERS: usize = 1024;
+
+/// Counting bloom filter over encoded triples `[sub, pred, obj]`.
+///
+/// Answers whether a triple may be present without looking at the meta: a triple that was never
+/// inserted is reported missing with high probability, an inserted one is always reported
+/// present. Counters make removals possible; a counter that saturates is never decremented
+/// again, which only costs precision.
+pub struct TripleFilter {
+    counters: Vec<u8>,
+    len: usize,
+    capacity: usize,
+}
+
+impl TripleFilter {
+    /// Returns an empty filter sized for `capacity` triples.
+    pub fn with_capacity(capacity: usize) -> Self {
+        let counters = (capacity * COUNTERS_PER_TRIPLE)
+            .max(MIN_COUNTERS)
+            .next_power_of_two();
+        TripleFilter {
+            counters: vec![0; counters],
+            len: 0,
+            capacity: counters / COUNTERS_PER_TRIPLE,
+        }
+    }
+
+    /// Returns whether more triples are stored than the filter is sized for, so it should be
+    /// rebuilt larger.
+    pub fn is_full(&self) -> bool {
+        self.len > self.capacity
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn insert(&mut self, triple: [u32; 3]) {
+        for i in self.positions(triple) {
+            self.counters[i] = self.counters[i].saturating_add(1);
+        }
+        self.len += 1;
+    }
+
+    /// Removes one occurrence of `triple`, which must have been inserted.
+    pub fn remove(&mut self, triple: [u32; 3]) {
+        for i in self.positions(triple) {
+            if self.counters[i] < u8::MAX {
+                self.counters[i] -= 1;
+            }
+        }
+        self.len -= 1;
+    }
+
+    /// Returns false if `triple` is certainly not present.
+    pub fn may_contain(&self, triple: [u32; 3]) -> bool {
+        self.positions(triple).all(|i| self.counters[i] > 0)
+    }
+
+    /// Returns the counters of `triple`, by double hashing.
+    fn positions(&self, [s, p, o]: [u32; 3]) -> impl Iterator<Item = usize> {
+        let mask = self.counters.len() as u64 - 1;
+        let h1 = mix(((s as u64) << 32 | p as u64) ^ mix(o as u64));
+        let h2 = mix(h1) | 1;
+        (0..HASHES as u64).map(move |k| (h1.wrapping_add(k.wrapping_mul(h2)) & mask) as usize)
+    }
+}
+
+/// The finalizer of SplitMix64, which spreads every bit of `x` over the whole word.
+fn mix(mut x: u64) -> u64 {
+    x = (x ^ (x >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    x = (x ^ (x >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    x ^ (x >> 31)
+}
+
+impl HeapSize for TripleFilter {
+    fn heap_size(&self) -> usize {
+        self.counters.heap_size()
+    }
+}
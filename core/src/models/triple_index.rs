@@ -0,0 +1,85 @@
+use super::triple::Triple;
+use crate::util::hash::{HashMap, HashSet};
+use crate::util::heap_size::HeapSize;
+
+/// SPO and OPS adjacency of a list of triples, with the position of each triple in the list.
+///
+/// Lets the triples of a node be found, and triples be tested, replaced and removed, without
+/// scanning the list.
+#[derive(Default)]
+pub struct TripleIndex {
+    positions: HashMap<Triple, usize>,
+    /// Triples by subject.
+    out: HashMap<u32, HashSet<Triple>>,
+    /// Triples by object.
+    inc: HashMap<u32, HashSet<Triple>>,
+}
+
+impl TripleIndex {
+    /// Indexes `triples`, which must not contain duplicates.
+    pub fn new(triples: &[Triple]) -> Self {
+        let mut index = TripleIndex::default();
+        for (i, t) in triples.iter().enumerate() {
+            index.insert(t, i);
+        }
+        index
+    }
+
+    pub fn contains(&self, triple: &Triple) -> bool {
+        self.positions.contains_key(triple)
+    }
+
+    /// Records `triple` at position `i`.
+    pub fn insert(&mut self, triple: &Triple, i: usize) {
+        self.positions.insert(triple.clone(), i);
+        self.out
+            .entry(triple.sub)
+            .or_default()
+            .insert(triple.clone());
+        self.inc
+            .entry(triple.obj)
+            .or_default()
+            .insert(triple.clone());
+    }
+
+    /// Forgets `triple` and returns its position.
+    pub fn remove(&mut self, triple: &Triple) -> Option<usize> {
+        let i = self.positions.remove(triple)?;
+        remove_adjacent(&mut self.out, &triple.sub, triple);
+        remove_adjacent(&mut self.inc, &triple.obj, triple);
+        Some(i)
+    }
+
+    /// Returns the triples with `node` as subject, in list order.
+    pub fn neighbors_out(&self, node: &u32) -> Vec<Triple> {
+        self.in_order(self.out.get(node))
+    }
+
+    /// Returns the triples with `node` as object, in list order.
+    pub fn neighbors_in(&self, node: &u32) -> Vec<Triple> {
+        self.in_order(self.inc.get(node))
+    }
+
+    /// Sorts `triples` by position, so callers changing them one by one do so in the same order
+    /// on every run.
+    fn in_order(&self, triples: Option<&HashSet<Triple>>) -> Vec<Triple> {
+        let mut triples: Vec<Triple> = triples.into_iter().flatten().cloned().collect();
+        triples.sort_by_key(|t| self.positions[t]);
+        triples
+    }
+}
+
+fn remove_adjacent(map: &mut HashMap<u32, HashSet<Triple>>, node: &u32, triple: &Triple) {
+    if let Some(triples) = map.get_mut(node) {
+        triples.remove(triple);
+        if triples.is_empty() {
+            map.remove(node);
+        }
+    }
+}
+
+impl HeapSize for TripleIndex {
+    fn heap_size(&self) -> usize {
+        self.positions.heap_size() + self.out.heap_size() + self.inc.heap_size()
+    }
+}
@@ -0,0 +1,61 @@
+use crate::models::clique::CliqueCollection;
+use crate::models::dataset::Dataset;
+use crate::models::meta::Meta;
+use crate::util::hash::HashMap;
+use crate::util::profile::span;
+use crate::{Config, Grouping};
+pub mod clique;
+pub mod dataset;
+pub mod encoded;
+#[cfg(feature = "endpoint")]
+pub mod endpoint;
+pub mod exclusion;
+pub mod inverse;
+pub mod meta;
+#[cfg(feature = "sqlite")]
+pub mod meta_db;
+pub mod n3;
+pub mod schema;
+
+pub fn run(
+    config: &Config,
+) -> Result<(Dataset, Meta, CliqueCollection, CliqueCollection), std::io::Error> {
+    span!("parse");
+    let mut meta = meta::parse_meta(&config)?;
+    let dataset = dataset::parse_dataset(&config, &mut meta)?;
+    let saved = match (config.use_fast, dataset.fingerprint) {
+        (false, Some(f)) => clique::read_cliques(&config.meta_folder_path, f),
+        _ => None,
+    };
+    let (mut sc, mut tc) = match saved {
+        Some(cliques) => cliques,
+        None if config.sorted_input || dataset.sorted => {
+            clique::create_cliques_grouped(&dataset.triples.data_triples)
+        }
+        None => clique::create_cliques(&dataset.triples.data_triples),
+    };
+    sc.set_bitmap_threshold(config.clique_bitmap_threshold);
+    tc.set_bitmap_threshold(config.clique_bitmap_threshold);
+
+    Ok((dataset, meta, sc, tc))
+}
+
+/// Prepares the summary of the in-memory `triples` without reading any files.
+///
+/// Terms are in N-Triples notation. As in fast mode, all triples are pending insertions, so the
+/// summary is only built by `updater::run`.
+pub fn from_triples(
+    triples: Vec<(String, String, String)>,
+    grouping: &Grouping,
+) -> (Dataset, Meta, CliqueCollection, CliqueCollection) {
+    span!("parse");
+    let mut meta = Meta::new(HashMap::default(), HashMap::default(), Vec::new());
+    let lines: Vec<String> = triples
+        .into_iter()
+        .map(|(s, p, o)| format!("{} {} {} .", s, p, o))
+        .collect();
+    let dataset = Dataset::new(Vec::new(), lines, Vec::new(), &mut meta, grouping);
+    let (sc, tc) = clique::create_cliques(&dataset.triples.data_triples);
+
+    (dataset, meta, sc, tc)
+}
@@ -0,0 +1,190 @@
+use crate::models::clique::{Clique, CliqueCollection};
+use crate::models::triple::Triple;
+use crate::util::hash::HashMap;
+use crate::util::profile::span;
+use crate::util::rng::fnv1a;
+use crate::Grouping;
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "json")]
+use std::fs;
+use std::iter;
+use std::path::Path;
+
+/// Name of the file in the meta folder the cliques are saved to.
+pub const CLIQUES_FILE: &str = "cliques.json";
+
+/// Creates the source and target cliques of `triples`.
+pub fn create_cliques(triples: &[Triple]) -> (CliqueCollection, CliqueCollection) {
+    span!("create_cliques");
+    let mut sc = CliqueCollection::new();
+    let mut tc = CliqueCollection::new();
+
+    for t in triples {
+        sc.new_triple(&t.sub, &t.pred);
+        tc.new_triple(&t.obj, &t.pred);
+    }
+
+    add_lone_nodes(triples, &mut sc, &mut tc);
+    return (sc, tc);
+}
+
+/// Like [`create_cliques`], but adds the predicates of each run of triples with the same subject
+/// at once, so the clique of the subject is looked up once per run instead of once per triple.
+///
+/// The cliques are the same as those of `create_cliques` for triples in any order; the fewer
+/// runs, the faster, so it pays when the triples of every subject are next to each other.
+pub fn create_cliques_grouped(triples: &[Triple]) -> (CliqueCollection, CliqueCollection) {
+    span!("create_cliques");
+    let mut sc = CliqueCollection::new();
+    let mut tc = CliqueCollection::new();
+
+    // The distinct predicates of the run, in the order they first appear.
+    let mut signature: Vec<u32> = Vec::new();
+    for run in triples.chunk_by(|a, b| a.sub == b.sub) {
+        signature.clear();
+        for t in run {
+            if !signature.contains(&t.pred) {
+                signature.push(t.pred);
+            }
+            tc.new_triple(&t.obj, &t.pred);
+        }
+        sc.add_signature(&run[0].sub, &signature);
+    }
+
+    add_lone_nodes(triples, &mut sc, &mut tc);
+    (sc, tc)
+}
+
+/// Puts the nodes of `triples` that `sc` or `tc` lack into its empty clique, in the order they
+/// first appear. The classes of type triples are not nodes.
+fn add_lone_nodes(triples: &[Triple], sc: &mut CliqueCollection, tc: &mut CliqueCollection) {
+    for t in triples {
+        let obj = (!t.is_type).then_some(t.obj);
+        for n in iter::once(t.sub).chain(obj) {
+            if !sc.contains_node(&n) {
+                sc.add_node_to_empty_clique(&n);
+            }
+            if !tc.contains_node(&n) {
+                tc.add_node_to_empty_clique(&n);
+            }
+        }
+    }
+}
+
+/// Returns a fingerprint of the summary `lines` grouped by `grouping`, which does not depend on
+/// the order of the lines.
+pub fn fingerprint(lines: &[String], grouping: &Grouping) -> u64 {
+    let mut fingerprint = Fingerprint::default();
+    for l in lines {
+        fingerprint.add(l);
+    }
+    fingerprint.finish(grouping)
+}
+
+/// The `fingerprint` of summary lines that are added as they are read.
+#[derive(Default)]
+pub struct Fingerprint {
+    sum: u64,
+    lines: u64,
+}
+
+impl Fingerprint {
+    pub fn add(&mut self, line: &str) {
+        self.sum = self.sum.wrapping_add(fnv1a(line));
+        self.lines += 1;
+    }
+
+    pub fn finish(&self, grouping: &Grouping) -> u64 {
+        self.sum ^ self.lines.rotate_left(32) ^ *grouping as u64
+    }
+}
+
+/// Reads the cliques saved in the meta folder `folder`, if they were saved for the summary with
+/// the `fingerprint`.
+pub fn read_cliques(
+    folder: &Path,
+    fingerprint: u64,
+) -> Option<(CliqueCollection, CliqueCollection)> {
+    span!("read_cliques");
+    let file = read_cliques_file(folder)?;
+    if file.f != fingerprint {
+        return None;
+    }
+    Some((file.s.restore(), file.t.restore()))
+}
+
+/// Reads the cliques file of the meta folder `folder`, whatever summary it was saved for.
+#[cfg(feature = "json")]
+pub fn read_cliques_file(folder: &Path) -> Option<CliquesFile> {
+    let file_str = fs::read_to_string(folder.join(CLIQUES_FILE)).ok()?;
+    serde_json::from_str(&file_str).ok()
+}
+
+#[cfg(not(feature = "json"))]
+pub fn read_cliques_file(_folder: &Path) -> Option<CliquesFile> {
+    None
+}
+
+/// Returns the cliques file record of `sc` and `tc`, the cliques of the summary with the
+/// `fingerprint`.
+pub fn to_file(sc: &CliqueCollection, tc: &CliqueCollection, fingerprint: u64) -> CliquesFile {
+    CliquesFile {
+        f: fingerprint,
+        s: CollectionRecord::of(sc),
+        t: CollectionRecord::of(tc),
+    }
+}
+
+/// The cliques file, which saves the source and target cliques of a summary so the next run on
+/// it does not have to create them again.
+#[derive(Serialize, Deserialize)]
+pub struct CliquesFile {
+    /// Fingerprint of the summary the cliques belong to.
+    pub f: u64,
+    pub s: CollectionRecord,
+    pub t: CollectionRecord,
+}
+
+/// The record of a [`CliqueCollection`].
+#[derive(Serialize, Deserialize)]
+pub struct CollectionRecord {
+    /// Preds and nodes of each clique, by index.
+    pub c: Vec<(Vec<u32>, Vec<u32>)>,
+    pub q: Vec<usize>,
+    /// Clique index of each node.
+    pub n: Vec<(u32, usize)>,
+    /// Clique index of each pred.
+    pub p: Vec<(u32, usize)>,
+}
+
+impl CollectionRecord {
+    fn of(cc: &CliqueCollection) -> Self {
+        let sorted = |map: &HashMap<u32, usize>| {
+            let mut entries: Vec<(u32, usize)> = map.iter().map(|(k, v)| (*k, *v)).collect();
+            entries.sort();
+            entries
+        };
+        Self {
+            c: cc
+                .cliques()
+                .iter()
+                .map(|c| (c.preds.clone(), c.nodes.to_vec()))
+                .collect(),
+            q: cc.queued().iter().copied().collect(),
+            n: sorted(cc.node_indices()),
+            p: sorted(cc.pred_indices()),
+        }
+    }
+
+    fn restore(self) -> CliqueCollection {
+        CliqueCollection::from_parts(
+            self.c
+                .into_iter()
+                .map(|(preds, nodes)| Clique::new(&preds, &nodes))
+                .collect(),
+            self.q.into_iter().collect(),
+            self.n.into_iter().collect(),
+            self.p.into_iter().collect(),
+        )
+    }
+}
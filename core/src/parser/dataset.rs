@@ -0,0 +1,622 @@
+use super::clique::Fingerprint;
+use super::encoded;
+use super::exclusion::{self, AutoExclude};
+use super::n3;
+use super::schema;
+use crate::models::dataset::Dataset;
+use crate::models::dict::{Dict, MAX_ID};
+use crate::models::literals::{self, Literals};
+use crate::models::meta::Meta;
+use crate::models::seeds;
+use crate::models::triple::{Triple, TripleCollection, TYPE_STRING};
+use crate::util::io;
+use crate::util::profile::span;
+use crate::util::rng::{fnv1a, Rng};
+use crate::util::source;
+use crate::util::wal;
+use crate::{rdf, Config, Grouping};
+use std::collections::{HashMap, HashSet};
+use std::io::{Error, ErrorKind};
+use std::iter;
+use std::path::Path;
+
+/// Name of the file in the meta folder that holds the source terms, the term of source id `i`
+/// on line `i`.
+pub const SOURCES_FILE: &str = "sources";
+
+pub fn parse_dataset(config: &Config, meta: &mut Meta) -> Result<Dataset, Error> {
+    span!("parse_dataset");
+    let mut u_l = read_update_lines(&config.update_path, config.line_limit)?;
+    let mut errors = 0;
+    let mut update_skipped: Vec<String> = Vec::new();
+    let update_file = config.update_path.display().to_string();
+    match config.max_errors {
+        Some(max) => skip_malformed(
+            &mut u_l,
+            &update_file,
+            true,
+            max,
+            &mut errors,
+            &mut update_skipped,
+        )?,
+        None => reject_empty_lines(&u_l, &update_file)?,
+    }
+    if let Some(fraction) = config.sample {
+        sample(&mut u_l, fraction, config.seed);
+    }
+    // Changes logged after the meta folder was last written came before any new update, so
+    // they are replayed on their own before one is taken.
+    let logged = wal::read(&config.wal_path())?;
+    if !logged.is_empty() {
+        if !u_l.is_empty() {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!(
+                    "{} holds {} changes not yet in the meta folder; replay them with an empty \
+                     update before applying {}",
+                    config.wal_path().display(),
+                    logged.len(),
+                    update_file
+                ),
+            ));
+        }
+        u_l = logged;
+    }
+    let mut update = get_update_lines(u_l);
+    config.inverse_preds.rewrite(
+        &mut update.insertions,
+        &mut update.deletions,
+        &mut update.reinserted,
+    );
+    let (mut i_l, mut d_l) = (update.insertions, update.deletions);
+    if config.provenance {
+        tag_with_source(&mut i_l, &file_source(&config.update_path));
+    }
+    let violations = match &config.schema {
+        Some(path) => schema::read_schema(path)?.check(&mut i_l),
+        None => Vec::new(),
+    };
+    let mut excluded = config.excluded_preds.clone();
+    if let Some(mode) = config.auto_exclude {
+        excluded.extend(auto_exclude(config, mode, &i_l)?);
+    }
+    let focus = Focus {
+        preds: match config.grouping {
+            Grouping::Types => Some(vec![TYPE_STRING.to_string()]),
+            _ => config.focus_preds.clone(),
+        },
+        excluded,
+    };
+    let (i_pass, d_pass) = if focus.is_everything() {
+        (Vec::new(), Vec::new())
+    } else {
+        (
+            take_out_of_focus(&focus, &mut i_l),
+            take_out_of_focus(&focus, &mut d_l),
+        )
+    };
+
+    let literals = Literals::open(&config.meta_folder_path, config.keep_literals_over)?;
+    let (dict, encoded) = if config.use_fast {
+        (None, None)
+    } else {
+        let dict_l = io::read_lines(&config.meta_folder_path.join("dict"))?;
+        let preds_path = config.meta_folder_path.join("preds");
+        let pred_l = if preds_path.exists() {
+            io::read_lines(&preds_path)?
+        } else {
+            legacy_preds(meta, &dict_l)
+        };
+        let sources = read_sources(&config.meta_folder_path)?;
+        let encoded = read_encoded(config, &focus, &dict_l, &pred_l, &sources);
+        let mut dict = Dict::new(&dict_l, &pred_l);
+        dict.add_sources(&sources);
+        (Some(dict), encoded)
+    };
+
+    // The summary is read while its triples are parsed, instead of all at once, unless its
+    // encoded triples are read instead.
+    let mut t_l = SummaryLines {
+        lines: match encoded {
+            Some(_) => Box::new(iter::empty()),
+            None => read_dataset_lines(config)?,
+        },
+        file: config.dataset_path.display().to_string(),
+        index: 0,
+        config,
+        focus: &focus,
+        fingerprint: Fingerprint::default(),
+        previous: String::new(),
+        sorted: true,
+        errors,
+        skipped: Vec::new(),
+        passthrough: Vec::new(),
+        error: None,
+    };
+    let mut dataset = match dict {
+        None => Dataset::new_with_literals(&mut t_l, i_l, d_l, literals, meta, &config.grouping),
+        Some(mut dict) => {
+            dict.keep_literals(literals);
+            Dataset::new_with_dict(&mut t_l, i_l, d_l, dict, meta, &config.grouping)
+        }
+    };
+    if let Some(e) = t_l.error {
+        return Err(e);
+    }
+    dataset.set_term_cache(config.term_cache);
+
+    let mut passthrough = t_l.passthrough;
+    apply_to_passthrough(&mut passthrough, i_pass, &d_pass);
+    let mut skipped = t_l.skipped;
+    skipped.extend(update_skipped);
+    dataset.passthrough = passthrough;
+    dataset.skipped = skipped;
+    dataset.violations = violations;
+    if let Some(path) = &config.seeds {
+        dataset.seeds = seeds::read_seeds(path)?;
+    }
+    dataset.sorted = encoded.is_none() && t_l.sorted;
+    let fingerprint = match encoded {
+        Some((triples, fingerprint)) => {
+            dataset.triples = TripleCollection::from_triples(triples);
+            fingerprint
+        }
+        None => t_l.fingerprint.finish(&config.grouping),
+    };
+    dataset.fingerprint = match (&config.sample, &config.focus_preds) {
+        (None, None) if focus.excluded.is_empty() => Some(fingerprint),
+        _ => None,
+    };
+    dataset.set_reinserted(&update.reinserted);
+    dataset.check_ids()?;
+    dataset.apply_duplicate_policy(&config.duplicates)?;
+    Ok(dataset)
+}
+
+/// The lines of a summary, checked, sampled and split off the pass-through lines as they are
+/// read.
+struct SummaryLines<'a> {
+    lines: Lines,
+    file: String,
+    /// Index of the next line in `file`.
+    index: usize,
+    config: &'a Config,
+    focus: &'a Focus,
+    /// Fingerprint of all lines read, before any is left out.
+    fingerprint: Fingerprint,
+    /// The last line read while they are in sorted order.
+    previous: String,
+    sorted: bool,
+    errors: usize,
+    skipped: Vec<String>,
+    passthrough: Vec<String>,
+    /// Why the lines ended early, if they did.
+    error: Option<Error>,
+}
+
+impl Iterator for SummaryLines<'_> {
+    type Item = String;
+
+    fn next(&mut self) -> Option<String> {
+        loop {
+            let l = match self.lines.next()? {
+                Ok(l) => l,
+                Err(e) => {
+                    self.error = Some(e);
+                    return None;
+                }
+            };
+            let i = self.index;
+            self.index += 1;
+            self.fingerprint.add(&l);
+            if self.sorted {
+                if l < self.previous {
+                    self.sorted = false;
+                } else {
+                    self.previous.clear();
+                    self.previous.push_str(&l);
+                }
+            }
+
+            let l = match self.config.max_errors {
+                Some(max) => {
+                    let checked = skip_if_malformed(
+                        l,
+                        &self.file,
+                        i,
+                        false,
+                        max,
+                        &mut self.errors,
+                        &mut self.skipped,
+                    );
+                    match checked {
+                        Ok(Some(l)) => l,
+                        Ok(None) => continue,
+                        Err(e) => {
+                            self.error = Some(e);
+                            return None;
+                        }
+                    }
+                }
+                None => l,
+            };
+            if let Some(fraction) = self.config.sample {
+                if !is_sampled(&l, fraction, self.config.seed) {
+                    continue;
+                }
+            }
+            if !self.focus.contains(&l) {
+                self.passthrough.push(l);
+                continue;
+            }
+            return Some(l);
+        }
+    }
+}
+
+/// Reads the encoded triples saved for the summary, and the fingerprint of its lines, if
+/// they are as valid as parsing it: all of the summary is summarized, and neither it nor the
+/// dict with the `dict_l`, `pred_l` and `sources` lines changed since they were written.
+fn read_encoded(
+    config: &Config,
+    focus: &Focus,
+    dict_l: &[String],
+    pred_l: &[String],
+    sources: &[String],
+) -> Option<(Vec<Triple>, u64)> {
+    #[cfg(feature = "endpoint")]
+    if config.endpoint.is_some() {
+        return None;
+    }
+    if config.sample.is_some() || !focus.is_everything() {
+        return None;
+    }
+    let path = config.meta_folder_path.join(encoded::TRIPLES_FILE);
+    if !path.exists() {
+        return None;
+    }
+    let key = encoded::Key {
+        dataset: encoded::hash_file(&config.dataset_path).ok()?,
+        dict: encoded::hash_dict(dict_l, pred_l, sources),
+        grouping: config.grouping,
+    };
+    encoded::read_encoded(&path, &key)
+}
+
+/// Keeps about `fraction` of `lines`, picked by `seed`.
+///
+/// Whether a triple is kept depends only on its line and `seed`, so the same triples are
+/// sampled in every run and a deletion is sampled exactly if the insertion it undoes was.
+pub fn sample(lines: &mut Vec<String>, fraction: f64, seed: u64) {
+    lines.retain(|l| is_sampled(l, fraction, seed));
+}
+
+/// Returns true if `sample` keeps the line `l`.
+fn is_sampled(l: &str, fraction: f64, seed: u64) -> bool {
+    let threshold = (fraction * u64::MAX as f64) as u64;
+    let triple = l.strip_prefix('-').unwrap_or(l);
+    Rng::new(seed ^ fnv1a(triple)).next_u64() <= threshold
+}
+
+/// Returns the lines of the sources file of the meta folder `folder`, which only exists once a
+/// triple was read with a source.
+pub fn read_sources(folder: &Path) -> Result<Vec<String>, Error> {
+    let path = folder.join(SOURCES_FILE);
+    if path.exists() {
+        io::read_lines(&path)
+    } else {
+        Ok(Vec::new())
+    }
+}
+
+/// Reads the preds file of the meta folder `folder`, whose dict file has the lines `dict_l`.
+///
+/// Meta folders written before predicates had their own ids have no preds file, and their
+/// edges use the ids of the dict, so the dict stands in for it. Unlike [`legacy_preds`], the
+/// edges are left as they are, so this also does for meta that is only read.
+pub fn read_preds(folder: &Path, dict_l: &[String]) -> Result<Vec<String>, Error> {
+    let path = folder.join("preds");
+    if path.exists() {
+        io::read_lines(&path)
+    } else {
+        Ok(dict_l.to_vec())
+    }
+}
+
+/// Reads the dict file of the meta folder `folder`, with the literals kept in its side file
+/// read back in.
+pub fn read_dict(folder: &Path) -> Result<Vec<String>, Error> {
+    let mut lines = io::read_lines(&folder.join("dict"))?;
+    literals::decode_lines(&mut lines, folder)?;
+    Ok(lines)
+}
+
+/// Returns the graph term naming the file at `path` as a source.
+pub fn file_source(path: &Path) -> String {
+    format!("<file:{}>", path.display().to_string().replace(' ', "%20"))
+}
+
+/// Names `source` as the source of the `insertions` that do not name one.
+fn tag_with_source(insertions: &mut [String], source: &str) {
+    for l in insertions.iter_mut() {
+        if rdf::graph_of(l).is_some() {
+            continue;
+        }
+        if let Some(triple) = l.strip_suffix(" .") {
+            *l = format!("{} {} .", triple, source);
+        }
+    }
+}
+
+/// Returns the predicates of a meta folder written before predicates had their own ids, and
+/// renumbers the edges of `meta` to them.
+///
+/// Such folders have no preds file and the edges use the ids of the predicates in `dict_l`.
+pub fn legacy_preds(meta: &mut Meta, dict_l: &[String]) -> Vec<String> {
+    let mut preds: Vec<String> = Vec::new();
+    let mut ids: HashMap<u32, u32> = HashMap::new();
+    meta.map_preds(|p| {
+        // Pseudo-predicates of type grouping are not dict ids.
+        if p > MAX_ID {
+            return p;
+        }
+        *ids.entry(p).or_insert_with(|| {
+            preds.push(dict_l[p as usize - 1].to_string());
+            preds.len() as u32
+        })
+    });
+    preds
+}
+
+/// The predicates whose triples are summarized. The triples of other predicates are passed
+/// through to the summary unchanged.
+struct Focus {
+    /// The predicates to summarize, or `None` for all that are not excluded.
+    preds: Option<Vec<String>>,
+    excluded: Vec<String>,
+}
+
+impl Focus {
+    fn is_everything(&self) -> bool {
+        self.preds.is_none() && self.excluded.is_empty()
+    }
+
+    /// Returns true if the triple of the line `l` is summarized.
+    fn contains(&self, l: &str) -> bool {
+        match rdf::split_terms(l).get(1) {
+            Some(&p) => {
+                self.preds
+                    .as_ref()
+                    .is_none_or(|preds| preds.iter().any(|f| f == p))
+                    && !self.excluded.iter().any(|e| e == p)
+            }
+            None => true,
+        }
+    }
+}
+
+/// Removes the lines out of `focus` from the update lines `lines` and returns them.
+fn take_out_of_focus(focus: &Focus, lines: &mut Vec<String>) -> Vec<String> {
+    let out: Vec<String> = lines
+        .iter()
+        .filter(|l| !focus.contains(l))
+        .cloned()
+        .collect();
+    lines.retain(|l| focus.contains(l));
+    out
+}
+
+/// Looks for predicates that barely compress in the summary and the `insertions`, and prints
+/// them with why. Returns them if `mode` applies the exclusions.
+fn auto_exclude(
+    config: &Config,
+    mode: AutoExclude,
+    insertions: &[String],
+) -> Result<Vec<String>, Error> {
+    let mut lines: Vec<String> = Vec::new();
+    for l in read_dataset_lines(config)? {
+        lines.push(l?);
+    }
+    lines.extend(insertions.iter().cloned());
+    let mut exclusions = exclusion::find_exclusions(lines);
+    exclusions.retain(|e| !config.excluded_preds.contains(&e.pred));
+
+    let verb = match mode {
+        AutoExclude::Suggest => "Consider excluding",
+        AutoExclude::Apply => "Excluding",
+    };
+    for e in &exclusions {
+        println!("{} {}", verb, e);
+    }
+    let preds: Vec<String> = exclusions.into_iter().map(|e| e.pred).collect();
+    if !preds.is_empty() {
+        println!(
+            "Pass --exclude-pred {} to keep these out of later updates",
+            preds.join(",")
+        );
+    }
+    Ok(match mode {
+        AutoExclude::Suggest => Vec::new(),
+        AutoExclude::Apply => preds,
+    })
+}
+
+/// Applies the `insertions` and `deletions` taken out of focus to the `passthrough` lines, the
+/// dataset lines out of focus. They are written to the summary unchanged.
+pub fn apply_to_passthrough(
+    passthrough: &mut Vec<String>,
+    insertions: Vec<String>,
+    deletions: &[String],
+) {
+    let deleted: HashSet<&str> = deletions.iter().map(String::as_str).collect();
+    passthrough.retain(|p| !deleted.contains(p.as_str()));
+    let mut present: HashSet<String> = passthrough.iter().cloned().collect();
+    for l in insertions {
+        if !deleted.contains(l.as_str()) && present.insert(l.clone()) {
+            passthrough.push(l);
+        }
+    }
+}
+
+/// Removes malformed lines from `lines`, adding each to `skipped` after a comment with its
+/// position in `file` and what is wrong with it.
+///
+/// `errors` counts the malformed lines across files. Returns an error once it exceeds `max`.
+pub fn skip_malformed(
+    lines: &mut Vec<String>,
+    file: &str,
+    is_update: bool,
+    max: usize,
+    errors: &mut usize,
+    skipped: &mut Vec<String>,
+) -> Result<(), Error> {
+    let mut kept: Vec<String> = Vec::with_capacity(lines.len());
+    for (i, l) in lines.drain(..).enumerate() {
+        if let Some(l) = skip_if_malformed(l, file, i, is_update, max, errors, skipped)? {
+            kept.push(l);
+        }
+    }
+    *lines = kept;
+    Ok(())
+}
+
+/// Fails on the first empty or whitespace-only line of `lines`, the lines of `file`, which has
+/// no change to apply. `--max-errors` skips them like other malformed lines instead.
+pub fn reject_empty_lines(lines: &[String], file: &str) -> Result<(), Error> {
+    match lines.iter().position(|l| l.trim().is_empty()) {
+        Some(i) => Err(Error::new(
+            ErrorKind::InvalidData,
+            format!("{}:{}: empty line; skip it with --max-errors", file, i + 1),
+        )),
+        None => Ok(()),
+    }
+}
+
+/// Returns the line `l` at index `i` of `file`, or `None` once it is added to `skipped` as
+/// `skip_malformed` does.
+fn skip_if_malformed(
+    l: String,
+    file: &str,
+    i: usize,
+    is_update: bool,
+    max: usize,
+    errors: &mut usize,
+    skipped: &mut Vec<String>,
+) -> Result<Option<String>, Error> {
+    let triple = if is_update {
+        l.strip_prefix('-').unwrap_or(&l)
+    } else {
+        l.as_str()
+    };
+    let reason = match rdf::check_line(triple) {
+        Some(reason) => reason,
+        None => return Ok(Some(l)),
+    };
+
+    *errors += 1;
+    if *errors > max {
+        return Err(Error::new(
+            ErrorKind::InvalidData,
+            format!(
+                "{}:{}: {}; more than {} malformed lines, aborting",
+                file,
+                i + 1,
+                reason,
+                max
+            ),
+        ));
+    }
+    skipped.push(format!("# {}:{}: {}", file, i + 1, reason));
+    skipped.push(l);
+    Ok(None)
+}
+
+/// Reads the lines of the update file at `path`, which may be remote. An update in N3 or Turtle
+/// only has insertions.
+pub fn read_update_lines(path: &Path, limit: Option<io::LineLimit>) -> Result<Vec<String>, Error> {
+    if n3::is_n3(path) {
+        return n3::read(path);
+    }
+    if source::is_remote(path) {
+        return io::LineReader::new(std::io::BufReader::new(source::open(path)?), limit).collect();
+    }
+    io::read_lines_limited(&path, limit)
+}
+
+/// Lines of the summary, as they are read.
+pub type Lines = Box<dyn Iterator<Item = Result<String, Error>>>;
+
+#[cfg(feature = "endpoint")]
+pub fn read_dataset_lines(config: &Config) -> Result<Lines, Error> {
+    if let Some(url) = &config.endpoint {
+        let lines = super::endpoint::read_lines(url, config.page_size, config.endpoint_timeout)?;
+        return Ok(Box::new(lines.into_iter().map(Ok)));
+    }
+    read_file_lines(config)
+}
+
+#[cfg(not(feature = "endpoint"))]
+pub fn read_dataset_lines(config: &Config) -> Result<Lines, Error> {
+    read_file_lines(config)
+}
+
+/// Streams the lines of the dataset file, which may be remote.
+fn read_file_lines(config: &Config) -> Result<Lines, Error> {
+    if n3::is_n3(&config.dataset_path) {
+        return Ok(Box::new(
+            n3::read(&config.dataset_path)?.into_iter().map(Ok),
+        ));
+    }
+    if source::is_remote(&config.dataset_path) {
+        let file = source::open(&config.dataset_path)?;
+        return Ok(Box::new(io::stream_reader(
+            file,
+            config.channel_capacity,
+            config.line_limit,
+        )));
+    }
+    let lines = io::stream_lines_limited(
+        &config.dataset_path,
+        config.channel_capacity,
+        config.line_limit,
+    )?;
+    Ok(Box::new(lines))
+}
+
+/// Lines of an update file.
+struct UpdateLines {
+    insertions: Vec<String>,
+    deletions: Vec<String>,
+    /// Insertions whose triple is also deleted by the update, but earlier.
+    reinserted: Vec<String>,
+}
+
+fn get_update_lines(lines: Vec<String>) -> UpdateLines {
+    let mut i_l: Vec<String> = Vec::new();
+    let mut d_l: Vec<String> = Vec::new();
+    let mut last_is_insertion: HashMap<String, bool> = HashMap::new();
+
+    for l in lines {
+        // if l starts with '-', then it is a deletion
+        if let Some(l) = l.strip_prefix('-') {
+            let l = l.to_string();
+            last_is_insertion.insert(l.clone(), false);
+            d_l.push(l);
+        } else {
+            last_is_insertion.insert(l.clone(), true);
+            i_l.push(l);
+        }
+    }
+
+    let deleted: HashSet<&String> = d_l.iter().collect();
+    let reinserted = i_l
+        .iter()
+        .filter(|l| deleted.contains(l) && last_is_insertion[*l])
+        .cloned()
+        .collect();
+    UpdateLines {
+        insertions: i_l,
+        deletions: d_l,
+        reinserted,
+    }
+}
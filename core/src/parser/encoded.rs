@@ -0,0 +1,128 @@
+use crate::models::triple::Triple;
+use crate::util::mmap::Words;
+use crate::util::profile::span;
+use crate::util::rng::Fnv1a;
+use crate::Grouping;
+use std::fs::File;
+use std::io::{self, BufReader, Read};
+use std::path::Path;
+
+/// First word of an encoded triples file, "TRKT".
+const MAGIC: u32 = u32::from_le_bytes(*b"TRKT");
+const VERSION: u32 = 1;
+
+/// Length of the header: magic, version, grouping, the dataset hash, the dict hash and the
+/// fingerprint in two words each, and the number of triples.
+const HEADER: usize = 10;
+/// Words of each triple: subject, predicate, object, source and whether it is a type triple.
+const TRIPLE: usize = 5;
+
+/// Name of the file in the meta folder that holds the encoded triples of the summary.
+pub const TRIPLES_FILE: &str = "triples";
+
+/// What the encoded triples of a summary were written for. They are only valid for a summary
+/// with the same content, read with the same dict and grouping.
+#[derive(Clone, Copy, PartialEq)]
+pub struct Key {
+    /// [`hash_file`] of the summary.
+    pub dataset: u64,
+    /// [`hash_dict`] of the dict the triples are encoded with.
+    pub dict: u64,
+    pub grouping: Grouping,
+}
+
+/// Returns the hash of the contents of the file at `path`.
+pub fn hash_file(path: &Path) -> io::Result<u64> {
+    span!("hash_file");
+    let mut file = BufReader::new(File::open(path)?);
+    let mut hash = Fnv1a::default();
+    let mut buf = vec![0; 1 << 16];
+    loop {
+        match file.read(&mut buf)? {
+            0 => break,
+            n => hash.write(&buf[..n]),
+        }
+    }
+    Ok(hash.finish())
+}
+
+/// Returns the [`hash_file`] of a file of `lines`, as `util::io::write_lines` writes them.
+pub fn hash_lines(lines: &[String]) -> u64 {
+    let mut hash = Fnv1a::default();
+    for l in lines {
+        hash_line(&mut hash, l);
+    }
+    hash.finish()
+}
+
+/// Adds the line `l` to `hash`, as [`hash_lines`] does for each of its lines.
+pub fn hash_line(hash: &mut Fnv1a, l: &str) {
+    hash.write(l.as_bytes());
+    hash.write(b"\n");
+}
+
+/// Returns the hash of the dict, preds and sources files with the `dict`, `preds` and `sources`
+/// lines, which together fix the ids of the terms.
+pub fn hash_dict(dict: &[String], preds: &[String], sources: &[String]) -> u64 {
+    hash_lines(dict) ^ hash_lines(preds).rotate_left(21) ^ hash_lines(sources).rotate_left(42)
+}
+
+/// Reads the encoded triples at `path` and the fingerprint of their summary, if they were
+/// written for `key`.
+pub fn read_encoded(path: &Path, key: &Key) -> Option<(Vec<Triple>, u64)> {
+    span!("read_encoded");
+    let words = Words::open(path).ok()?;
+    if words.len() < HEADER || words[0] != MAGIC || words[1] != VERSION {
+        return None;
+    }
+    let long = |i: usize| words[i] as u64 | (words[i + 1] as u64) << 32;
+    let found = Key {
+        dataset: long(3),
+        dict: long(5),
+        grouping: grouping_of(words[2])?,
+    };
+    let count = words[HEADER - 1] as usize;
+    if found != *key || words.len() != HEADER + count * TRIPLE {
+        return None;
+    }
+
+    let triples = words[HEADER..]
+        .chunks_exact(TRIPLE)
+        .map(|w| Triple {
+            source: w[3],
+            ..Triple::new(w[0], w[1], w[2], w[4] != 0)
+        })
+        .collect();
+    Some((triples, long(7)))
+}
+
+/// Returns the encoded `triples` of the summary with `key` and `fingerprint`, as little-endian
+/// bytes.
+pub fn serialize<'a>(
+    triples: impl IntoIterator<Item = &'a Triple>,
+    key: &Key,
+    fingerprint: u64,
+) -> Vec<u8> {
+    let mut words: Vec<u32> = vec![MAGIC, VERSION, key.grouping as u32];
+    for long in [key.dataset, key.dict, fingerprint] {
+        words.extend([long as u32, (long >> 32) as u32]);
+    }
+    // The number of triples, filled in once they are counted.
+    words.push(0);
+    for t in triples {
+        words.extend([t.sub, t.pred, t.obj, t.source, t.is_type as u32]);
+    }
+    words[HEADER - 1] = ((words.len() - HEADER) / TRIPLE) as u32;
+    words.iter().flat_map(|w| w.to_le_bytes()).collect()
+}
+
+fn grouping_of(word: u32) -> Option<Grouping> {
+    [
+        Grouping::Preds,
+        Grouping::Types,
+        Grouping::Both,
+        Grouping::Untyped,
+    ]
+    .into_iter()
+    .find(|g| *g as u32 == word)
+}
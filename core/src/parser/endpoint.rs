@@ -0,0 +1,56 @@
+use crate::rdf;
+use crate::util::http;
+use std::io::{Error, ErrorKind};
+use std::time::Duration;
+
+/// Pages all triples out of the SPARQL endpoint at `url`, `page_size` at a time, waiting at most
+/// `timeout` on the network for each request.
+///
+/// Each page is fetched with a `CONSTRUCT` query using `LIMIT`/`OFFSET` and returned as
+/// N-Triples lines, so the result can be handled exactly like the lines of a dataset file.
+///
+/// The labels of blank nodes only hold within one response, so a blank node paged out twice
+/// would be read as two nodes. Results with blank nodes are only read if they fit in one page,
+/// and are an error otherwise.
+pub fn read_lines(url: &str, page_size: usize, timeout: Duration) -> Result<Vec<String>, Error> {
+    let mut lines: Vec<String> = Vec::new();
+    let mut offset = 0;
+    let mut blank = false;
+
+    loop {
+        let query = format!(
+            "CONSTRUCT {{ ?s ?p ?o }} WHERE {{ ?s ?p ?o }} ORDER BY ?s ?p ?o LIMIT {} OFFSET {}",
+            page_size, offset
+        );
+        let separator = if url.contains('?') { '&' } else { '?' };
+        let body = http::get(
+            &format!("{}{}query={}", url, separator, http::encode(&query)),
+            "application/n-triples",
+            timeout,
+        )?;
+
+        let page: Vec<String> = body
+            .lines()
+            .map(|l| l.trim().to_string())
+            .filter(|l| !l.is_empty() && !l.starts_with('#'))
+            .collect();
+
+        if page.is_empty() {
+            return Ok(lines);
+        }
+        blank = blank || page.iter().any(|l| !rdf::blank_nodes(l).is_empty());
+        if blank && offset > 0 {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                format!(
+                    "{} returned blank nodes in more than one page of {} triples, and their \
+                     labels differ between pages; raise --page-size above the number of \
+                     triples, or skolemize the blank nodes in the store",
+                    url, page_size
+                ),
+            ));
+        }
+        lines.extend(page);
+        offset += page_size;
+    }
+}
@@ -0,0 +1,138 @@
+use crate::models::triple::TYPE_STRING;
+use crate::rdf;
+use crate::util::hash::{HashMap, HashSet};
+use crate::util::profile::span;
+use crate::util::set_ops::UnionFind;
+use std::fmt;
+
+/// Number of triples a predicate needs before it is excluded; rarer ones cannot cost much.
+pub const MIN_TRIPLES: usize = 100;
+
+/// Share of the triples of a predicate that have to stay distinct edges of the summary for the
+/// predicate to be excluded.
+pub const MIN_DISTINCT_SHARE: f64 = 0.5;
+
+/// Whether `--auto-exclude` only prints the predicates it finds or also excludes them.
+#[derive(Clone, Copy, PartialEq)]
+pub enum AutoExclude {
+    Suggest,
+    Apply,
+}
+
+impl AutoExclude {
+    pub fn parse(arg: Option<&String>) -> Result<Self, &'static str> {
+        match arg.map(|s| s.as_str()) {
+            Some("suggest") => Ok(AutoExclude::Suggest),
+            Some("apply") => Ok(AutoExclude::Apply),
+            _ => Err("--auto-exclude expects suggest or apply"),
+        }
+    }
+}
+
+/// A predicate whose triples barely compress.
+pub struct Exclusion {
+    pub pred: String,
+    pub triples: usize,
+    /// Number of distinct summary edges its triples end up as.
+    pub summary_edges: usize,
+}
+
+impl fmt::Display for Exclusion {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}: {} of its {} triples ({:.0}%) stay distinct in the summary",
+            self.pred,
+            self.summary_edges,
+            self.triples,
+            self.share() * 100.0
+        )
+    }
+}
+
+impl Exclusion {
+    fn share(&self) -> f64 {
+        self.summary_edges as f64 / self.triples as f64
+    }
+}
+
+/// Returns the predicates that barely compress when the triple `lines` are summarized, least
+/// compressed first.
+///
+/// Nodes are grouped by their source and target cliques, as a summary built from scratch groups
+/// them. The triples of a predicate that links nodes of many different groups stay apart in the
+/// summary, while the predicate still joins the cliques of the nodes it links, so the summary
+/// grows without gaining anything. `rdf:type` is never excluded, types have their own grouping.
+pub fn find_exclusions<I>(lines: I) -> Vec<Exclusion>
+where
+    I: IntoIterator<Item = String>,
+{
+    span!("find_exclusions");
+    let mut nodes: HashMap<String, u32> = HashMap::default();
+    let mut preds: HashMap<String, u32> = HashMap::default();
+    let mut triples: Vec<[u32; 3]> = Vec::new();
+    for l in lines {
+        let (s, p, o) = match rdf::split_terms(&l)[..] {
+            [s, p, o, ..] if p != TYPE_STRING => (s, p, o),
+            _ => continue,
+        };
+        triples.push([
+            intern(&mut nodes, s),
+            intern(&mut preds, p),
+            intern(&mut nodes, o),
+        ]);
+    }
+
+    // The cliques: predicates sharing a subject, or an object, are in the same clique.
+    let mut sc = UnionFind::new(preds.len());
+    let mut tc = UnionFind::new(preds.len());
+    let mut out_pred: HashMap<u32, u32> = HashMap::default();
+    let mut in_pred: HashMap<u32, u32> = HashMap::default();
+    for [s, p, o] in &triples {
+        sc.union(*out_pred.entry(*s).or_insert(*p), *p);
+        tc.union(*in_pred.entry(*o).or_insert(*p), *p);
+    }
+    let group = |n: &u32, sc: &mut UnionFind, tc: &mut UnionFind| {
+        [
+            out_pred.get(n).map_or(u32::MAX, |p| sc.find(*p)),
+            in_pred.get(n).map_or(u32::MAX, |p| tc.find(*p)),
+        ]
+    };
+
+    let mut counts: Vec<usize> = vec![0; preds.len()];
+    let mut edges: HashSet<[u32; 5]> = HashSet::default();
+    for [s, p, o] in &triples {
+        let [a, b] = group(s, &mut sc, &mut tc);
+        let [c, d] = group(o, &mut sc, &mut tc);
+        counts[*p as usize] += 1;
+        edges.insert([a, b, *p, c, d]);
+    }
+    let mut summary_edges: Vec<usize> = vec![0; preds.len()];
+    for [_, _, p, _, _] in &edges {
+        summary_edges[*p as usize] += 1;
+    }
+
+    let mut exclusions: Vec<Exclusion> = preds
+        .into_iter()
+        .map(|(pred, p)| Exclusion {
+            pred,
+            triples: counts[p as usize],
+            summary_edges: summary_edges[p as usize],
+        })
+        .filter(|e| e.triples >= MIN_TRIPLES && e.share() >= MIN_DISTINCT_SHARE)
+        .collect();
+    exclusions.sort_by(|a, b| b.share().total_cmp(&a.share()).then(a.pred.cmp(&b.pred)));
+    exclusions
+}
+
+/// Returns the id of `term` in `ids`, giving it the next one if it has none.
+fn intern(ids: &mut HashMap<String, u32>, term: &str) -> u32 {
+    match ids.get(term) {
+        Some(id) => *id,
+        None => {
+            let id = ids.len() as u32;
+            ids.insert(term.to_string(), id);
+            id
+        }
+    }
+}
@@ -0,0 +1,90 @@
+use crate::util::hash::HashMap;
+use crate::{as_iri, rdf};
+
+/// The `owl:inverseOf` predicate, which declares two predicates inverse.
+pub const INVERSE_OF: &str = "<http://www.w3.org/2002/07/owl#inverseOf>";
+
+/// Pairs of inverse predicates whose triples are summarized as one, given with
+/// `--inverse-preds`.
+///
+/// A triple `s q o` of the inverse `q` of `p` states the same as `o p s`, so it is read as that
+/// triple instead: nodes modeled either way then share the same signature, and end up in the
+/// same cliques. The summary, and what is extracted from it, has the triples of `p` only. The
+/// same pairs have to be given on every run of a summary, or deletions of earlier triples no
+/// longer find them.
+#[derive(Clone, Default)]
+pub struct InversePreds {
+    /// Each predicate with the inverse read as it.
+    pub pairs: Vec<(String, String)>,
+    /// Whether pairs are also taken from the `owl:inverseOf` triples of the update.
+    pub owl: bool,
+}
+
+impl InversePreds {
+    /// Parses a list of `pred=inverse` pairs, and `owl` to take pairs from the update.
+    pub fn parse(arg: Option<&String>) -> Result<Self, &'static str> {
+        let err = "--inverse-preds expects a list of pred=inverse pairs or owl";
+        let mut inverses = InversePreds::default();
+        for item in arg.ok_or(err)?.split(',') {
+            match item.split_once('=') {
+                _ if item == "owl" => inverses.owl = true,
+                Some((p, q)) if !p.is_empty() && !q.is_empty() => {
+                    inverses.pairs.push((as_iri(p), as_iri(q)))
+                }
+                _ => return Err(err),
+            }
+        }
+        Ok(inverses)
+    }
+
+    /// Rewrites the triples of inverse predicates in the update lines `insertions`,
+    /// `deletions` and `reinserted` as triples of the predicates they are the inverse of.
+    /// Returns the number of lines rewritten.
+    ///
+    /// Triples with a literal object cannot be turned around and are left as they are.
+    pub fn rewrite(
+        &self,
+        insertions: &mut [String],
+        deletions: &mut [String],
+        reinserted: &mut [String],
+    ) -> usize {
+        let mut declared = self.pairs.clone();
+        if self.owl {
+            for l in insertions.iter() {
+                let words = rdf::split_terms(l);
+                if words.len() >= 3 && rdf::normalize(words[1]) == INVERSE_OF {
+                    declared.push((rdf::normalize(words[0]), rdf::normalize(words[2])));
+                }
+            }
+        }
+        // The first declaration of a pair decides which of the two is kept.
+        let mut canonical: HashMap<String, String> = HashMap::default();
+        for (p, q) in declared {
+            if p != q && !canonical.contains_key(&p) && !canonical.contains_key(&q) {
+                canonical.insert(q, p);
+            }
+        }
+        if canonical.is_empty() {
+            return 0;
+        }
+
+        let mut count = 0;
+        for l in insertions
+            .iter_mut()
+            .chain(deletions.iter_mut())
+            .chain(reinserted.iter_mut())
+        {
+            let words = rdf::split_terms(l);
+            if words.len() < 4 || words[2].starts_with('"') {
+                continue;
+            }
+            if let Some(p) = canonical.get(&rdf::normalize(words[1])) {
+                let mut turned = vec![words[2], p.as_str(), words[0]];
+                turned.extend(&words[3..]);
+                *l = turned.join(" ");
+                count += 1;
+            }
+        }
+        count
+    }
+}
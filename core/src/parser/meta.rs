@@ -0,0 +1,311 @@
+use crate::models::compact_meta::CompactMeta;
+use crate::models::meta::{Edges, Meta, NodeInfo};
+use crate::util::hash::HashMap;
+use crate::util::mmap::Words;
+use crate::util::profile::span;
+use crate::Config;
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "json")]
+use std::fs;
+use std::io;
+use std::path::Path;
+#[cfg(feature = "json")]
+use std::thread;
+
+/// Name of the metadata file in the meta folder.
+pub const META_FILE: &str = "meta.json";
+
+/// Name of the metadata file in the meta folder when it is compressed with `--zstd-meta`.
+pub const COMPRESSED_META_FILE: &str = "meta.json.zst";
+
+/// Name of the metadata database in the meta folder, written with `--sqlite-meta` instead of
+/// the metadata file.
+pub const META_DB_FILE: &str = "meta.sqlite";
+
+/// Name of the compact metadata file in the meta folder, written with `--compact-meta`.
+pub const COMPACT_META_FILE: &str = "meta.bin";
+
+pub fn parse_meta(config: &Config) -> Result<Meta, io::Error> {
+    span!("parse_meta");
+    if config.use_fast {
+        let supernodes: HashMap<u32, Vec<u32>> = HashMap::default();
+        let nodes: HashMap<u32, NodeInfo> = HashMap::default();
+        let types: Vec<[u32; 2]> = Vec::new();
+        Ok(Meta::new(supernodes, nodes, types))
+    } else {
+        read_meta_on(&config.meta_folder_path, config.threads)
+    }
+}
+
+/// Reads the metadata file in the meta folder `folder`, or its metadata database if it has no
+/// metadata file.
+pub fn read_meta(folder: &Path) -> Result<Meta, io::Error> {
+    read_meta_on(folder, None)
+}
+
+/// Like [`read_meta`], but parses the metadata file on `threads` threads, or on one per core if
+/// `None`.
+#[cfg_attr(not(feature = "json"), allow(unused_variables))]
+pub fn read_meta_on(folder: &Path, threads: Option<usize>) -> Result<Meta, io::Error> {
+    let path = folder.join(META_FILE);
+    let db_path = folder.join(META_DB_FILE);
+    if !path.exists() && db_path.exists() {
+        #[cfg(feature = "sqlite")]
+        return Ok(from_file(super::meta_db::read(&db_path)?));
+        #[cfg(not(feature = "sqlite"))]
+        return Err(io::Error::other(format!(
+            "found {}, but this build cannot read meta databases; build with --features sqlite",
+            META_DB_FILE
+        )));
+    }
+    #[cfg(not(feature = "json"))]
+    return Err(io::Error::other(format!(
+        "this build cannot read {}; build with --features json",
+        META_FILE
+    )));
+    #[cfg(feature = "json")]
+    {
+        let compressed_path = folder.join(COMPRESSED_META_FILE);
+        let file_str = if !path.exists() && compressed_path.exists() {
+            read_compressed(&compressed_path)?
+        } else {
+            fs::read_to_string(&path)?
+        };
+        parse_meta_file(&file_str, threads)
+    }
+}
+
+/// Parses the meta file `file_str` on `threads` threads, or on one per core if `None`.
+#[cfg(feature = "json")]
+fn parse_meta_file(file_str: &str, threads: Option<usize>) -> Result<Meta, io::Error> {
+    // Finding the records costs an extra pass over the file, which only pays off with more
+    // than one thread.
+    let threads = threads.unwrap_or_else(|| thread::available_parallelism().map_or(1, |n| n.get()));
+    let file_data = if threads > 1 && file_str.len() >= PARALLEL_MIN_BYTES {
+        parse_parallel(file_str, threads)
+    } else {
+        None
+    };
+    let file_data: MetaFile = match file_data {
+        Some(data) => data,
+        None => serde_json::from_str(file_str)?,
+    };
+    Ok(from_file(file_data))
+}
+
+/// Reads the zstd compressed metadata file at `path`.
+#[cfg(feature = "zstd")]
+fn read_compressed(path: &Path) -> Result<String, io::Error> {
+    let data = crate::util::zstd::decompress(&fs::read(path)?)?;
+    String::from_utf8(data).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+#[cfg(all(feature = "json", not(feature = "zstd")))]
+fn read_compressed(_path: &Path) -> Result<String, io::Error> {
+    Err(io::Error::other(format!(
+        "found {}, but this build cannot read compressed meta; build with --features zstd",
+        COMPRESSED_META_FILE
+    )))
+}
+
+/// Reads the metadata of the meta folder `folder` for queries: maps its compact metadata file
+/// if it has one, and otherwise reads the metadata file and compacts it.
+pub fn read_compact_meta(folder: &Path) -> Result<CompactMeta, io::Error> {
+    span!("read_compact_meta");
+    let path = folder.join(COMPACT_META_FILE);
+    let words = if path.exists() {
+        Words::open(&path)?
+    } else {
+        Words::from_le_bytes(&read_meta(folder)?.serialize_compact())
+    };
+    CompactMeta::new(words)
+}
+
+/// Size from which meta files are parsed in parallel.
+#[cfg(feature = "json")]
+const PARALLEL_MIN_BYTES: usize = 1 << 20;
+
+/// Parses the sections of a meta file concurrently, splitting the node records, which make up
+/// most of the file, into one chunk for each of the `threads`.
+///
+/// Returns `None` if `s` is not a valid meta file; parse it with `serde_json` then to get the
+/// error.
+#[cfg(feature = "json")]
+pub fn parse_parallel(s: &str, threads: usize) -> Option<MetaFile> {
+    let mut sections: HashMap<String, &str> = HashMap::default();
+    for (start, end) in elements(s)? {
+        let field = &s[start..end];
+        let colon = field.find(':')?;
+        let key: String = serde_json::from_str(&field[..colon]).ok()?;
+        sections.insert(key, &field[colon + 1..]);
+    }
+    let (s_json, q, t_json) = (sections.get("s")?, sections.get("q")?, sections.get("t")?);
+    let a_json = sections.get("a");
+    let g_json = sections.get("g");
+    let nodes = elements(q)?;
+    let chunk_len = nodes.len().div_ceil(threads.max(1)).max(1);
+
+    thread::scope(|scope| {
+        let s = scope.spawn(|| serde_json::from_str::<Vec<Supernode>>(s_json).ok());
+        let t = scope.spawn(|| serde_json::from_str::<Vec<[u32; 2]>>(t_json).ok());
+        let a = scope.spawn(|| match a_json {
+            Some(a) => serde_json::from_str::<Vec<(u32, String)>>(a).ok(),
+            None => Some(Vec::new()),
+        });
+        let g = scope.spawn(|| match g_json {
+            Some(g) => serde_json::from_str::<Vec<([u32; 3], u32)>>(g).ok(),
+            None => Some(Vec::new()),
+        });
+        let chunks: Vec<_> = nodes
+            .chunks(chunk_len)
+            .map(|chunk| {
+                scope.spawn(move || {
+                    chunk
+                        .iter()
+                        .map(|(start, end)| serde_json::from_str::<Node>(&q[*start..*end]).ok())
+                        .collect::<Option<Vec<Node>>>()
+                })
+            })
+            .collect();
+
+        let mut q: Vec<Node> = Vec::with_capacity(nodes.len());
+        for chunk in chunks {
+            q.extend(chunk.join().ok()??);
+        }
+        Some(MetaFile {
+            s: s.join().ok()??,
+            q,
+            t: t.join().ok()??,
+            a: a.join().ok()??,
+            g: g.join().ok()??,
+        })
+    })
+}
+
+/// Returns the byte ranges of the elements of the JSON array or fields of the JSON object `s`,
+/// or `None` if its brackets do not match.
+#[cfg(feature = "json")]
+fn elements(s: &str) -> Option<Vec<(usize, usize)>> {
+    let trimmed = s.trim_start();
+    let offset = s.len() - trimmed.len() + 1;
+    let trimmed = trimmed.trim_end();
+    match (trimmed.bytes().next()?, trimmed.bytes().last()?) {
+        (b'[', b']') | (b'{', b'}') if trimmed.len() >= 2 => {}
+        _ => return None,
+    }
+    let inner = &trimmed[1..trimmed.len() - 1];
+
+    let mut ranges: Vec<(usize, usize)> = Vec::new();
+    let (mut depth, mut in_string, mut escaped, mut start) = (0, false, false, 0);
+    for (i, b) in inner.bytes().enumerate() {
+        if in_string {
+            if escaped {
+                escaped = false;
+            } else if b == b'\\' {
+                escaped = true;
+            } else if b == b'"' {
+                in_string = false;
+            }
+            continue;
+        }
+        match b {
+            b'"' => in_string = true,
+            b'[' | b'{' => depth += 1,
+            b']' | b'}' if depth == 0 => return None,
+            b']' | b'}' => depth -= 1,
+            b',' if depth == 0 => {
+                ranges.push((offset + start, offset + i));
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    if depth != 0 || in_string {
+        return None;
+    }
+    if !inner[start..].trim().is_empty() {
+        ranges.push((offset + start, offset + inner.len()));
+    }
+    Some(ranges)
+}
+
+/// Returns the meta file record of `meta`.
+pub fn to_file(meta: &Meta) -> MetaFile {
+    MetaFile {
+        s: meta
+            .get_supernodes()
+            .iter()
+            .map(|(i, g)| Supernode {
+                i: *i,
+                g: g.clone(),
+            })
+            .collect(),
+        q: meta
+            .get_nodes()
+            .iter()
+            .map(|(i, info)| Node {
+                i: *i,
+                p: info.parent,
+                n: info.incoming.to_vec(),
+                o: info.outgoing.to_vec(),
+            })
+            .collect(),
+        t: meta.get_types().clone(),
+        a: meta
+            .get_aliases()
+            .iter()
+            .map(|(i, a)| (*i, a.clone()))
+            .collect(),
+        g: meta.get_sources().iter().map(|(e, g)| (*e, *g)).collect(),
+    }
+}
+
+/// Builds the metadata recorded in `file`.
+pub fn from_file(file: MetaFile) -> Meta {
+    let supernodes = file.s.into_iter().map(|s| (s.i, s.g)).collect();
+    let nodes = file
+        .q
+        .into_iter()
+        .map(|n| {
+            let info = NodeInfo {
+                parent: n.p,
+                incoming: Edges::from(n.n),
+                outgoing: Edges::from(n.o),
+            };
+            (n.i, info)
+        })
+        .collect();
+    let mut meta = Meta::new(supernodes, nodes, file.t);
+    meta.set_aliases(file.a.into_iter().collect());
+    meta.set_sources(file.g.into_iter().collect());
+    meta
+}
+
+/// The meta file: the (de)serialized form of [`Meta`], with short field names to keep it small.
+#[derive(Serialize, Deserialize)]
+pub struct MetaFile {
+    pub s: Vec<Supernode>,
+    pub q: Vec<Node>,
+    pub t: Vec<[u32; 2]>,
+    /// Aliases of the supernodes. Missing in meta files written before aliases existed.
+    #[serde(default)]
+    pub a: Vec<(u32, String)>,
+    /// Sources of the original edges read with one. Left out if there are none.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub g: Vec<([u32; 3], u32)>,
+}
+
+/// The record of a [`NodeInfo`] and the id of its node.
+#[derive(Serialize, Deserialize)]
+pub struct Node {
+    pub i: u32,
+    pub p: Option<u32>,
+    pub n: Vec<[u32; 2]>,
+    pub o: Vec<[u32; 2]>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct Supernode {
+    pub i: u32,
+    pub g: Vec<u32>,
+}
@@ -0,0 +1,159 @@
+//! The meta kept in a SQLite database instead of the meta file, with `--sqlite-meta`.
+//!
+//! Each record of the meta file is a row of a table, so the meta can be inspected with SQL, and
+//! it is replaced in a single transaction, so an interrupted run leaves the meta of the last
+//! one whole. Rows are read back in the order they were written, which keeps the edges of each
+//! node in their order.
+
+use super::meta::{MetaFile, Node, Supernode};
+use crate::util::hash::HashMap;
+use crate::util::profile::span;
+use crate::util::sqlite::{Connection, Value};
+use std::io::Error;
+use std::path::Path;
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS nodes (id INTEGER PRIMARY KEY, parent INTEGER);
+CREATE TABLE IF NOT EXISTS edges (
+    node INTEGER NOT NULL,
+    incoming INTEGER NOT NULL,
+    pred INTEGER NOT NULL,
+    other INTEGER NOT NULL
+);
+CREATE INDEX IF NOT EXISTS edges_node ON edges (node);
+CREATE TABLE IF NOT EXISTS supernodes (id INTEGER NOT NULL, member INTEGER NOT NULL);
+CREATE TABLE IF NOT EXISTS types (node INTEGER NOT NULL, class INTEGER NOT NULL);
+CREATE TABLE IF NOT EXISTS aliases (supernode INTEGER PRIMARY KEY, alias TEXT NOT NULL);
+CREATE TABLE IF NOT EXISTS sources (
+    sub INTEGER NOT NULL,
+    pred INTEGER NOT NULL,
+    obj INTEGER NOT NULL,
+    source INTEGER NOT NULL
+);
+";
+
+const TABLES: [&str; 6] = [
+    "nodes",
+    "edges",
+    "supernodes",
+    "types",
+    "aliases",
+    "sources",
+];
+
+/// Replaces the meta in the database at `path` by `file`, creating the database if needed.
+pub fn write(path: &Path, file: &MetaFile) -> Result<(), Error> {
+    span!("write_meta_db");
+    let conn = Connection::open(path, false)?;
+    conn.execute(SCHEMA)?;
+    conn.execute("BEGIN IMMEDIATE")?;
+    match insert(&conn, file) {
+        Ok(()) => conn.execute("COMMIT"),
+        Err(e) => {
+            conn.execute("ROLLBACK")?;
+            Err(e)
+        }
+    }
+}
+
+fn insert(conn: &Connection, file: &MetaFile) -> Result<(), Error> {
+    for table in TABLES {
+        conn.execute(&format!("DELETE FROM {}", table))?;
+    }
+    let id = |n: u32| Value::Int(n as i64);
+
+    let mut nodes = conn.prepare("INSERT INTO nodes VALUES (?, ?)")?;
+    let mut edges = conn.prepare("INSERT INTO edges VALUES (?, ?, ?, ?)")?;
+    for n in &file.q {
+        nodes.run(&[id(n.i), n.p.map_or(Value::Null, id)])?;
+        for (incoming, list) in [(1, &n.n), (0, &n.o)] {
+            for [p, other] in list {
+                edges.run(&[id(n.i), Value::Int(incoming), id(*p), id(*other)])?;
+            }
+        }
+    }
+    let mut supernodes = conn.prepare("INSERT INTO supernodes VALUES (?, ?)")?;
+    for s in &file.s {
+        for m in &s.g {
+            supernodes.run(&[id(s.i), id(*m)])?;
+        }
+    }
+    let mut types = conn.prepare("INSERT INTO types VALUES (?, ?)")?;
+    for [n, class] in &file.t {
+        types.run(&[id(*n), id(*class)])?;
+    }
+    let mut aliases = conn.prepare("INSERT INTO aliases VALUES (?, ?)")?;
+    for (s, alias) in &file.a {
+        aliases.run(&[id(*s), Value::Text(alias)])?;
+    }
+    let mut sources = conn.prepare("INSERT INTO sources VALUES (?, ?, ?, ?)")?;
+    for ([s, p, o], source) in &file.g {
+        sources.run(&[id(*s), id(*p), id(*o), id(*source)])?;
+    }
+    Ok(())
+}
+
+/// Reads the meta in the database at `path`.
+pub fn read(path: &Path) -> Result<MetaFile, Error> {
+    span!("read_meta_db");
+    let conn = Connection::open(path, true)?;
+    let id = |n: Option<i64>| n.unwrap_or_default() as u32;
+
+    let mut q: Vec<Node> = Vec::new();
+    let mut index: HashMap<u32, usize> = HashMap::default();
+    let mut rows = conn.prepare("SELECT id, parent FROM nodes ORDER BY rowid")?;
+    while rows.step()? {
+        index.insert(id(rows.int(0)), q.len());
+        q.push(Node {
+            i: id(rows.int(0)),
+            p: rows.int(1).map(|p| p as u32),
+            n: Vec::new(),
+            o: Vec::new(),
+        });
+    }
+    let mut rows = conn.prepare("SELECT node, incoming, pred, other FROM edges ORDER BY rowid")?;
+    while rows.step()? {
+        let node = match index.get(&id(rows.int(0))) {
+            Some(i) => &mut q[*i],
+            None => return Err(Error::other("sqlite: edge of an unknown node")),
+        };
+        let list = match rows.int(1) {
+            Some(0) => &mut node.o,
+            _ => &mut node.n,
+        };
+        list.push([id(rows.int(2)), id(rows.int(3))]);
+    }
+
+    let mut s: Vec<Supernode> = Vec::new();
+    let mut rows = conn.prepare("SELECT id, member FROM supernodes ORDER BY rowid")?;
+    while rows.step()? {
+        let (snode, member) = (id(rows.int(0)), id(rows.int(1)));
+        match s.last_mut() {
+            Some(last) if last.i == snode => last.g.push(member),
+            _ => s.push(Supernode {
+                i: snode,
+                g: vec![member],
+            }),
+        }
+    }
+
+    let mut t: Vec<[u32; 2]> = Vec::new();
+    let mut rows = conn.prepare("SELECT node, class FROM types ORDER BY rowid")?;
+    while rows.step()? {
+        t.push([id(rows.int(0)), id(rows.int(1))]);
+    }
+    let mut a: Vec<(u32, String)> = Vec::new();
+    let mut rows = conn.prepare("SELECT supernode, alias FROM aliases ORDER BY rowid")?;
+    while rows.step()? {
+        a.push((id(rows.int(0)), rows.text(1)));
+    }
+    let mut g: Vec<([u32; 3], u32)> = Vec::new();
+    let mut rows = conn.prepare("SELECT sub, pred, obj, source FROM sources ORDER BY rowid")?;
+    while rows.step()? {
+        g.push((
+            [id(rows.int(0)), id(rows.int(1)), id(rows.int(2))],
+            id(rows.int(3)),
+        ));
+    }
+    Ok(MetaFile { s, q, t, a, g })
+}
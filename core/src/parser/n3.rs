@@ -0,0 +1,631 @@
+//! Reads the Turtle-compatible subset of Notation3 into N-Triples lines.
+//!
+//! Several public datasets are only published in N3 or Turtle, which is read the same way. Besides Turtle, N3 has formulas, rules and
+//! quantified variables, which only mean something to a reasoner; statements that use them are
+//! skipped with a warning. The rest is plain triples, which are read like Turtle: prefixes and a
+//! base, `;` and `,` lists, `a`, blank node property lists, collections, long strings and bare
+//! numbers and booleans. The N3 shorthands `=` for `owl:sameAs` and `is <p> of` are read too.
+
+use crate::rdf::{write_term, Suffix, Term};
+use crate::util::hash::HashMap;
+use crate::util::source;
+use std::io::{self, ErrorKind, Read};
+use std::path::Path;
+
+const RDF: &str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#";
+const XSD: &str = "http://www.w3.org/2001/XMLSchema#";
+const SAME_AS: &str = "http://www.w3.org/2002/07/owl#sameAs";
+
+/// Returns true if the file at `path` is in N3 or Turtle, judging by its extension.
+pub fn is_n3(path: &Path) -> bool {
+    syntax(path).is_some()
+}
+
+/// Returns the name of the syntax of the file at `path`, if it is N3 or Turtle.
+fn syntax(path: &Path) -> Option<&'static str> {
+    let extension = path.extension()?.to_str()?.to_ascii_lowercase();
+    match extension.as_str() {
+        "n3" => Some("N3"),
+        "ttl" => Some("Turtle"),
+        _ => None,
+    }
+}
+
+/// The triples of an N3 document, and why the statements that were skipped were.
+#[derive(Debug, Default)]
+pub struct N3 {
+    /// The triples in N-Triples notation, in document order.
+    pub lines: Vec<String>,
+    pub warnings: Vec<String>,
+}
+
+/// Reads the N3 or Turtle file at `path`, which may be remote, as N-Triples lines, printing a
+/// warning for every statement skipped.
+pub fn read(path: &Path) -> io::Result<Vec<String>> {
+    let mut text = String::new();
+    source::open(path)?.read_to_string(&mut text)?;
+    let n3 = parse(&text).map_err(|e| {
+        io::Error::new(
+            ErrorKind::InvalidData,
+            format!(
+                "{} is not valid {}, {}",
+                path.display(),
+                syntax(path).unwrap_or("N3"),
+                e
+            ),
+        )
+    })?;
+    for warning in &n3.warnings {
+        println!("Skipped {} of {}", warning, path.display());
+    }
+    Ok(n3.lines)
+}
+
+/// Parses the N3 document `text`. Fails on the first statement that is not valid N3, with the
+/// line it starts on.
+pub fn parse(text: &str) -> Result<N3, String> {
+    let tokens = tokenize(text)?;
+    let mut parser = Parser {
+        tokens: &tokens,
+        i: 0,
+        prefixes: HashMap::default(),
+        base: String::new(),
+        blanks: 0,
+        n3: N3::default(),
+    };
+    while parser.i < tokens.len() {
+        parser.statement()?;
+    }
+    Ok(parser.n3)
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Tok {
+    Iri(String),
+    /// Prefix and local part of a prefixed name.
+    PName(String, String),
+    Blank(String),
+    Str(String),
+    /// A number, with the local name of its XSD datatype.
+    Number(String, &'static str),
+    /// `@` and the word after it: a directive, a quantifier or a language tag.
+    At(String),
+    Var(String),
+    Word(String),
+    /// `^^`, `=`, `=>`, `<=` and the punctuation `. ; , [ ] ( ) { }`.
+    Punct(&'static str),
+}
+
+/// Splits `text` into tokens with the line each starts on.
+fn tokenize(text: &str) -> Result<Vec<(Tok, usize)>, String> {
+    let chars: Vec<char> = text.chars().collect();
+    let mut tokens = Vec::new();
+    let mut line = 1;
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        let start = line;
+        let error = |message: &str| format!("line {}: {}", start, message);
+        let next = chars.get(i + 1).copied();
+        let tok = match c {
+            '\n' => {
+                line += 1;
+                i += 1;
+                continue;
+            }
+            c if c.is_whitespace() => {
+                i += 1;
+                continue;
+            }
+            '#' => {
+                while i < chars.len() && chars[i] != '\n' {
+                    i += 1;
+                }
+                continue;
+            }
+            '<' if next == Some('=') => {
+                i += 2;
+                Tok::Punct("<=")
+            }
+            '<' => {
+                let end = (i + 1..chars.len())
+                    .find(|j| chars[*j] == '>')
+                    .ok_or_else(|| error("unterminated IRI"))?;
+                let raw: String = chars[i + 1..end].iter().collect();
+                i = end + 1;
+                Tok::Iri(unescape(&raw).ok_or_else(|| error("bad escape in IRI"))?)
+            }
+            '"' | '\'' => {
+                let long = next == Some(c) && chars.get(i + 2) == Some(&c);
+                let quote = if long { 3 } else { 1 };
+                let mut j = i + quote;
+                loop {
+                    match chars.get(j) {
+                        None => return Err(error("unterminated string")),
+                        Some('\\') => j += 2,
+                        Some('\n') if !long => return Err(error("line break in string")),
+                        Some(d) if *d == c && (!long || chars[j..].starts_with(&[c, c, c])) => {
+                            break
+                        }
+                        Some('\n') => {
+                            line += 1;
+                            j += 1;
+                        }
+                        Some(_) => j += 1,
+                    }
+                }
+                let raw: String = chars[i + quote..j].iter().collect();
+                i = j + quote;
+                Tok::Str(unescape(&raw).ok_or_else(|| error("bad escape in string"))?)
+            }
+            '^' if next == Some('^') => {
+                i += 2;
+                Tok::Punct("^^")
+            }
+            '=' if next == Some('>') => {
+                i += 2;
+                Tok::Punct("=>")
+            }
+            '=' => {
+                i += 1;
+                Tok::Punct("=")
+            }
+            '.' if !next.is_some_and(|d| d.is_ascii_digit()) => {
+                i += 1;
+                Tok::Punct(".")
+            }
+            ';' | ',' | '[' | ']' | '(' | ')' | '{' | '}' => {
+                i += 1;
+                Tok::Punct(match c {
+                    ';' => ";",
+                    ',' => ",",
+                    '[' => "[",
+                    ']' => "]",
+                    '(' => "(",
+                    ')' => ")",
+                    '{' => "{",
+                    _ => "}",
+                })
+            }
+            '@' | '?' => {
+                let word = name(&chars, i + 1);
+                if word.is_empty() {
+                    return Err(error(&format!("expected a name after `{}`", c)));
+                }
+                i += 1 + word.chars().count();
+                match c {
+                    '@' => Tok::At(word),
+                    _ => Tok::Var(word),
+                }
+            }
+            c if c.is_ascii_digit() || matches!(c, '+' | '-' | '.') => {
+                let (number, datatype) = number(&chars, i).ok_or_else(|| error("bad number"))?;
+                i += number.len();
+                Tok::Number(number, datatype)
+            }
+            _ => {
+                let word = name(&chars, i);
+                if word.is_empty() {
+                    return Err(error(&format!("unexpected `{}`", c)));
+                }
+                i += word.chars().count();
+                let unescaped = word.replace('\\', "");
+                match (word.strip_prefix("_:"), unescaped.split_once(':')) {
+                    (Some(label), _) => Tok::Blank(label.to_string()),
+                    (None, Some((prefix, local))) => {
+                        Tok::PName(prefix.to_string(), local.to_string())
+                    }
+                    (None, None) => Tok::Word(word),
+                }
+            }
+        };
+        tokens.push((tok, start));
+    }
+    Ok(tokens)
+}
+
+/// Returns the name starting at `i`: letters, digits and `_ - : .` and escapes with `\`, but not
+/// ending with `.`, which ends the statement instead.
+fn name(chars: &[char], i: usize) -> String {
+    let mut j = i;
+    while let Some(c) = chars.get(j) {
+        match c {
+            '\\' if j + 1 < chars.len() => j += 2,
+            c if c.is_alphanumeric() || matches!(c, '_' | '-' | ':' | '.' | '%') => j += 1,
+            _ => break,
+        }
+    }
+    while j > i && chars[j - 1] == '.' {
+        j -= 1;
+    }
+    chars[i..j].iter().collect()
+}
+
+/// Returns the number starting at `i` and the local name of its datatype.
+fn number(chars: &[char], i: usize) -> Option<(String, &'static str)> {
+    let mut j = i;
+    if matches!(chars.get(j), Some('+' | '-')) {
+        j += 1;
+    }
+    let digits = |j: &mut usize| {
+        let from = *j;
+        while chars.get(*j).is_some_and(|c| c.is_ascii_digit()) {
+            *j += 1;
+        }
+        *j > from
+    };
+    let mut whole = digits(&mut j);
+    let mut datatype = "integer";
+    if chars.get(j) == Some(&'.') && chars.get(j + 1).is_some_and(|c| c.is_ascii_digit()) {
+        j += 1;
+        whole |= digits(&mut j);
+        datatype = "decimal";
+    }
+    if !whole {
+        return None;
+    }
+    if matches!(chars.get(j), Some('e' | 'E')) {
+        j += 1;
+        if matches!(chars.get(j), Some('+' | '-')) {
+            j += 1;
+        }
+        if !digits(&mut j) {
+            return None;
+        }
+        datatype = "double";
+    }
+    Some((chars[i..j].iter().collect(), datatype))
+}
+
+/// Resolves the escapes of strings and IRIs in `raw`.
+fn unescape(raw: &str) -> Option<String> {
+    let mut out = String::with_capacity(raw.len());
+    let mut chars = raw.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        let escaped = match chars.next()? {
+            't' => '\t',
+            'b' => '\u{8}',
+            'n' => '\n',
+            'r' => '\r',
+            'f' => '\u{c}',
+            'u' => hex(&mut chars, 4)?,
+            'U' => hex(&mut chars, 8)?,
+            c @ ('"' | '\'' | '\\') => c,
+            _ => return None,
+        };
+        out.push(escaped);
+    }
+    Some(out)
+}
+
+fn hex(chars: &mut std::str::Chars, digits: usize) -> Option<char> {
+    let code: String = chars.take(digits).collect();
+    if code.len() != digits {
+        return None;
+    }
+    char::from_u32(u32::from_str_radix(&code, 16).ok()?)
+}
+
+struct Parser<'a> {
+    tokens: &'a [(Tok, usize)],
+    i: usize,
+    prefixes: HashMap<String, String>,
+    base: String,
+    /// Blank nodes generated for `[]` and collections so far.
+    blanks: usize,
+    n3: N3,
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<&Tok> {
+        self.tokens.get(self.i).map(|(t, _)| t)
+    }
+
+    fn line(&self) -> usize {
+        let i = self.i.min(self.tokens.len().saturating_sub(1));
+        self.tokens.get(i).map_or(1, |(_, l)| *l)
+    }
+
+    fn error<T>(&self, message: &str) -> Result<T, String> {
+        Err(format!("line {}: {}", self.line(), message))
+    }
+
+    fn next(&mut self) -> Option<Tok> {
+        let tok = self.peek().cloned();
+        self.i += 1;
+        tok
+    }
+
+    fn eat(&mut self, punct: &'static str) -> bool {
+        if self.peek() == Some(&Tok::Punct(punct)) {
+            self.i += 1;
+            return true;
+        }
+        false
+    }
+
+    fn expect(&mut self, punct: &'static str) -> Result<(), String> {
+        if self.eat(punct) {
+            return Ok(());
+        }
+        self.error(&format!("expected `{}`", punct))
+    }
+
+    fn statement(&mut self) -> Result<(), String> {
+        match self.peek().cloned() {
+            Some(Tok::At(word)) if word == "prefix" => {
+                self.i += 1;
+                self.prefix()?;
+                self.expect(".")
+            }
+            Some(Tok::At(word)) if word == "base" => {
+                self.i += 1;
+                self.base()?;
+                self.expect(".")
+            }
+            Some(Tok::Word(word)) if word.eq_ignore_ascii_case("prefix") => {
+                self.i += 1;
+                self.prefix()
+            }
+            Some(Tok::Word(word)) if word.eq_ignore_ascii_case("base") => {
+                self.i += 1;
+                self.base()
+            }
+            _ => match self.unsupported() {
+                Some(feature) => {
+                    let line = self.line();
+                    self.skip_statement()?;
+                    self.n3
+                        .warnings
+                        .push(format!("{} at line {}", feature, line));
+                    Ok(())
+                }
+                None => self.triples(),
+            },
+        }
+    }
+
+    fn prefix(&mut self) -> Result<(), String> {
+        let prefix = match self.next() {
+            Some(Tok::PName(prefix, local)) if local.is_empty() => prefix,
+            _ => return self.error("expected a prefix such as `ex:`"),
+        };
+        let iri = match self.next() {
+            Some(Tok::Iri(iri)) => self.resolve(&iri),
+            _ => return self.error("expected the IRI of the prefix"),
+        };
+        self.prefixes.insert(prefix, iri);
+        Ok(())
+    }
+
+    fn base(&mut self) -> Result<(), String> {
+        match self.next() {
+            Some(Tok::Iri(iri)) => {
+                self.base = self.resolve(&iri);
+                Ok(())
+            }
+            _ => self.error("expected the base IRI"),
+        }
+    }
+
+    /// Returns the N3 feature beyond Turtle that the statement at hand uses, if any.
+    fn unsupported(&self) -> Option<&'static str> {
+        let mut depth = 0;
+        for (tok, _) in &self.tokens[self.i..] {
+            match tok {
+                Tok::Punct("{") | Tok::Punct("}") => return Some("formula"),
+                Tok::Punct("=>") | Tok::Punct("<=") => return Some("rule"),
+                Tok::Var(_) => return Some("quantified variable"),
+                Tok::At(word) if matches!(word.as_str(), "forAll" | "forSome") => {
+                    return Some("quantifier")
+                }
+                Tok::At(word) if word == "keywords" => return Some("@keywords"),
+                Tok::Punct("[") | Tok::Punct("(") => depth += 1,
+                Tok::Punct("]") | Tok::Punct(")") => depth -= 1,
+                Tok::Punct(".") if depth == 0 => return None,
+                _ => {}
+            }
+        }
+        None
+    }
+
+    /// Skips to after the `.` that ends the statement at hand, past any nested formulas.
+    fn skip_statement(&mut self) -> Result<(), String> {
+        let mut depth = 0;
+        while let Some(tok) = self.next() {
+            match tok {
+                Tok::Punct("[") | Tok::Punct("(") | Tok::Punct("{") => depth += 1,
+                Tok::Punct("]") | Tok::Punct(")") | Tok::Punct("}") => depth -= 1,
+                Tok::Punct(".") if depth <= 0 => return Ok(()),
+                _ => {}
+            }
+        }
+        self.error("statement does not end with `.`")
+    }
+
+    fn triples(&mut self) -> Result<(), String> {
+        let standalone = self.peek() == Some(&Tok::Punct("["));
+        let subject = self.node()?;
+        // A blank node property list may stand on its own.
+        if !(standalone && self.peek() == Some(&Tok::Punct("."))) {
+            self.predicate_objects(&subject)?;
+        }
+        self.expect(".")
+    }
+
+    fn predicate_objects(&mut self, subject: &Term) -> Result<(), String> {
+        loop {
+            let (predicate, inverse) = self.verb()?;
+            loop {
+                let object = self.node()?;
+                match inverse {
+                    true => self.emit(&object, &predicate, subject),
+                    false => self.emit(subject, &predicate, &object),
+                }
+                if !self.eat(",") {
+                    break;
+                }
+            }
+            if !self.eat(";") {
+                return Ok(());
+            }
+            // Repeated and trailing `;` are allowed.
+            while self.eat(";") {}
+            if matches!(self.peek(), Some(Tok::Punct(".")) | Some(Tok::Punct("]"))) {
+                return Ok(());
+            }
+        }
+    }
+
+    /// Returns the predicate at hand, and whether its subject and object are swapped.
+    fn verb(&mut self) -> Result<(Term, bool), String> {
+        match self.peek() {
+            Some(Tok::Word(word)) if word == "a" => {
+                self.i += 1;
+                Ok((Term::Iri(format!("{}type", RDF)), false))
+            }
+            Some(Tok::Punct("=")) => {
+                self.i += 1;
+                Ok((Term::Iri(SAME_AS.to_string()), false))
+            }
+            Some(Tok::Word(word)) if word == "has" => {
+                self.i += 1;
+                Ok((self.node()?, false))
+            }
+            Some(Tok::Word(word)) if word == "is" => {
+                self.i += 1;
+                let predicate = self.node()?;
+                match self.next() {
+                    Some(Tok::Word(word)) if word == "of" => Ok((predicate, true)),
+                    _ => self.error("expected `of` after `is <predicate>`"),
+                }
+            }
+            _ => Ok((self.node()?, false)),
+        }
+    }
+
+    fn node(&mut self) -> Result<Term, String> {
+        let tok = match self.next() {
+            Some(tok) => tok,
+            None => return self.error("unexpected end of document"),
+        };
+        match tok {
+            Tok::Iri(iri) => Ok(Term::Iri(self.resolve(&iri))),
+            Tok::PName(prefix, local) => match self.prefixes.get(&prefix) {
+                Some(iri) => Ok(Term::Iri(format!("{}{}", iri, local))),
+                None => self.error(&format!("unknown prefix `{}:`", prefix)),
+            },
+            Tok::Blank(label) => Ok(Term::Blank(label)),
+            Tok::Str(value) => {
+                let suffix = match self.peek().cloned() {
+                    Some(Tok::At(lang)) => {
+                        self.i += 1;
+                        Suffix::Lang(lang)
+                    }
+                    Some(Tok::Punct("^^")) => {
+                        self.i += 1;
+                        match self.node()? {
+                            Term::Iri(datatype) => Suffix::Datatype(datatype),
+                            _ => return self.error("expected a datatype IRI"),
+                        }
+                    }
+                    _ => Suffix::None,
+                };
+                Ok(Term::Literal { value, suffix })
+            }
+            Tok::Number(value, datatype) => Ok(Term::Literal {
+                value,
+                suffix: Suffix::Datatype(format!("{}{}", XSD, datatype)),
+            }),
+            Tok::Word(word) if word == "true" || word == "false" => Ok(Term::Literal {
+                value: word,
+                suffix: Suffix::Datatype(format!("{}boolean", XSD)),
+            }),
+            Tok::Punct("[") => {
+                let blank = self.new_blank();
+                if !self.eat("]") {
+                    self.predicate_objects(&blank)?;
+                    self.expect("]")?;
+                }
+                Ok(blank)
+            }
+            Tok::Punct("(") => self.collection(),
+            _ => {
+                self.i -= 1;
+                self.error("expected an IRI, blank node or literal")
+            }
+        }
+    }
+
+    /// Reads the rest of a collection as an `rdf:first`/`rdf:rest` list.
+    fn collection(&mut self) -> Result<Term, String> {
+        let first = Term::Iri(format!("{}first", RDF));
+        let rest = Term::Iri(format!("{}rest", RDF));
+        let mut items = Vec::new();
+        while !self.eat(")") {
+            items.push(self.node()?);
+        }
+        let nil = Term::Iri(format!("{}nil", RDF));
+        let cells: Vec<Term> = items.iter().map(|_| self.new_blank()).collect();
+        for (i, item) in items.iter().enumerate() {
+            self.emit(&cells[i], &first, item);
+            self.emit(&cells[i], &rest, cells.get(i + 1).unwrap_or(&nil));
+        }
+        Ok(cells.into_iter().next().unwrap_or(nil))
+    }
+
+    fn new_blank(&mut self) -> Term {
+        self.blanks += 1;
+        Term::Blank(format!("n3b{}", self.blanks))
+    }
+
+    fn emit(&mut self, subject: &Term, predicate: &Term, object: &Term) {
+        if matches!(subject, Term::Literal { .. }) || !matches!(predicate, Term::Iri(_)) {
+            let warning = format!(
+                "triple with a literal subject or a predicate that is not an IRI at line {}",
+                self.line()
+            );
+            self.n3.warnings.push(warning);
+            return;
+        }
+        let mut line = String::new();
+        for term in [subject, predicate, object] {
+            write_term(term, &mut line);
+            line.push(' ');
+        }
+        line.push('.');
+        self.n3.lines.push(line);
+    }
+
+    /// Resolves `iri` against the base, if it is relative.
+    fn resolve(&self, iri: &str) -> String {
+        let scheme = iri.split_once(':').is_some_and(|(s, _)| {
+            s.starts_with(|c: char| c.is_ascii_alphabetic())
+                && s.chars()
+                    .all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'))
+        });
+        if scheme || self.base.is_empty() {
+            return iri.to_string();
+        }
+        let base = self.base.split('#').next().unwrap();
+        if iri.is_empty() {
+            base.to_string()
+        } else if iri.starts_with('#') {
+            format!("{}{}", base, iri)
+        } else if iri.starts_with('/') {
+            // Keep the scheme and authority of the base.
+            let authority = base
+                .find("://")
+                .and_then(|i| base[i + 3..].find('/').map(|j| i + 3 + j))
+                .unwrap_or(base.len());
+            format!("{}{}", &base[..authority], iri)
+        } else {
+            let dir = base.rfind('/').map_or(0, |i| i + 1);
+            format!("{}{}", &base[..dir], iri)
+        }
+    }
+}
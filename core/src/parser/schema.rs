@@ -0,0 +1,131 @@
+//! A lightweight schema the insertions of an update are checked against with `--schema`.
+//!
+//! Each line of a schema file is a comment starting with `#`, or one of
+//!
+//! ```text
+//! <class> allows <pred> <pred> ...
+//! <pred> datatype <datatype>
+//! ```
+//!
+//! A subject typed with classes that have `allows` lines may only have the predicates they
+//! allow, and `rdf:type`. The objects of a predicate with a `datatype` line must be literals of
+//! that datatype; plain literals are `xsd:string`s and literals with a language tag
+//! `rdf:langString`s. Subjects are typed by the type triples of the same update, so a subject
+//! whose types were inserted earlier is not checked.
+
+use crate::models::triple::TYPE_STRING;
+use crate::rdf::{self, Suffix, Term};
+use crate::util::hash::{HashMap, HashSet};
+use crate::util::io;
+use std::io::{Error, ErrorKind};
+use std::path::Path;
+
+const XSD_STRING: &str = "http://www.w3.org/2001/XMLSchema#string";
+const RDF_LANG_STRING: &str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#langString";
+
+#[derive(Default)]
+pub struct Schema {
+    /// Predicates allowed on the instances of each class.
+    allowed: HashMap<String, HashSet<String>>,
+    /// Datatype IRI, without angle brackets, of the objects of each predicate.
+    datatypes: HashMap<String, String>,
+}
+
+impl Schema {
+    /// Parses the `lines` of a schema file.
+    pub fn parse(lines: &[String]) -> Result<Self, String> {
+        let mut schema = Schema::default();
+        for (i, l) in lines.iter().enumerate() {
+            let words: Vec<&str> = l.split_whitespace().collect();
+            match words[..] {
+                [] => {}
+                [first, ..] if first.starts_with('#') => {}
+                [class, "allows", ref preds @ ..] if class.starts_with('<') => {
+                    let allowed = schema.allowed.entry(rdf::normalize(class)).or_default();
+                    allowed.extend(preds.iter().map(|p| rdf::normalize(p)));
+                }
+                [pred, "datatype", datatype] if pred.starts_with('<') => {
+                    let datatype = match rdf::parse_term(datatype) {
+                        Some(Term::Iri(iri)) => iri,
+                        _ => return Err(format!("line {}: datatype must be an IRI", i + 1)),
+                    };
+                    schema.datatypes.insert(rdf::normalize(pred), datatype);
+                }
+                _ => {
+                    return Err(format!(
+                        "line {}: expected `<class> allows <pred> ...` or `<pred> datatype <iri>`",
+                        i + 1
+                    ))
+                }
+            }
+        }
+        Ok(schema)
+    }
+
+    /// Removes the triples that violate the schema from the insertion `lines`, and returns
+    /// each after a comment giving the reason, like skipped lines.
+    pub fn check(&self, lines: &mut Vec<String>) -> Vec<String> {
+        let mut types: HashMap<String, Vec<String>> = HashMap::default();
+        for l in lines.iter() {
+            let words = rdf::split_terms(l);
+            if words.len() >= 3 && rdf::normalize(words[1]) == TYPE_STRING {
+                types
+                    .entry(rdf::normalize(words[0]))
+                    .or_default()
+                    .push(rdf::normalize(words[2]));
+            }
+        }
+
+        let mut violations: Vec<String> = Vec::new();
+        lines.retain(|l| match self.violation(l, &types) {
+            Some(reason) => {
+                violations.push(format!("# {}", reason));
+                violations.push(l.clone());
+                false
+            }
+            None => true,
+        });
+        violations
+    }
+
+    /// Returns why the triple of the line `l` violates the schema, if it does, given the
+    /// `types` of the subjects.
+    fn violation(&self, l: &str, types: &HashMap<String, Vec<String>>) -> Option<String> {
+        let words = rdf::split_terms(l);
+        if words.len() < 3 {
+            return None;
+        }
+        let (s, p) = (rdf::normalize(words[0]), rdf::normalize(words[1]));
+        if p == TYPE_STRING {
+            return None;
+        }
+
+        let constrained: Vec<&HashSet<String>> = types
+            .get(&s)
+            .into_iter()
+            .flatten()
+            .filter_map(|class| self.allowed.get(class))
+            .collect();
+        if !constrained.is_empty() && !constrained.iter().any(|preds| preds.contains(&p)) {
+            return Some(format!("{} is not allowed for the types of {}", p, s));
+        }
+
+        let expected = self.datatypes.get(&p)?;
+        let datatype = match rdf::parse_term(words[2]) {
+            Some(Term::Literal { suffix, .. }) => match suffix {
+                Suffix::None => XSD_STRING.to_string(),
+                Suffix::Lang(_) => RDF_LANG_STRING.to_string(),
+                Suffix::Datatype(datatype) => datatype,
+            },
+            _ => return Some(format!("object of {} must be a literal", p)),
+        };
+        (datatype != *expected).then(|| format!("object of {} must be a <{}>", p, expected))
+    }
+}
+
+/// Reads the schema file at `path`.
+pub fn read_schema(path: &Path) -> Result<Schema, Error> {
+    let lines = io::read_lines(&path)?;
+    Schema::parse(&lines)
+        .map_err(|e| Error::new(ErrorKind::InvalidData, format!("{}: {}", path.display(), e)))
+}
@@ -0,0 +1,338 @@
+//! Terms in N-Triples notation.
+//!
+//! Terms are kept as N-Triples strings throughout the summarizer. Everything that writes them
+//! out goes through [`write_term`], so exports are escaped the same way no matter how the input
+//! was escaped.
+//!
+//! Quoted triples of RDF-star, `<< s p o >>`, are single terms as well. They name a triple
+//! without asserting it, and are summarized as opaque nodes. Lines are split into terms with
+//! [`split_terms`], which keeps the spaces inside them.
+
+use std::path::Path;
+
+/// A parsed RDF term with escapes resolved.
+#[derive(Debug, PartialEq)]
+pub enum Term {
+    Iri(String),
+    Blank(String),
+    Literal {
+        value: String,
+        suffix: Suffix,
+    },
+    /// RDF-star quoted triple: subject, predicate and object.
+    Quoted(Box<[Term; 3]>),
+}
+
+/// Language tag or datatype of a literal.
+#[derive(Debug, PartialEq)]
+pub enum Suffix {
+    None,
+    Lang(String),
+    /// Datatype IRI, without angle brackets.
+    Datatype(String),
+}
+
+/// Parses a term in N-Triples notation. Returns `None` if `s` is not a single valid term.
+pub fn parse_term(s: &str) -> Option<Term> {
+    if let Some([sub, pred, obj]) = quoted_terms(s) {
+        let quoted = [parse_term(sub)?, parse_term(pred)?, parse_term(obj)?];
+        return matches!(quoted[1], Term::Iri(_)).then(|| Term::Quoted(Box::new(quoted)));
+    }
+    if let Some(label) = s.strip_prefix("_:") {
+        return (!label.is_empty()).then(|| Term::Blank(label.to_string()));
+    }
+    if let Some(rest) = s.strip_prefix('<') {
+        let (iri, rest) = parse_until(rest, '>')?;
+        return rest.is_empty().then_some(Term::Iri(iri));
+    }
+
+    let (value, rest) = parse_until(s.strip_prefix('"')?, '"')?;
+    let suffix = if rest.is_empty() {
+        Suffix::None
+    } else if let Some(lang) = rest.strip_prefix('@') {
+        if lang.is_empty() || !lang.chars().all(|c| c.is_ascii_alphanumeric() || c == '-') {
+            return None;
+        }
+        Suffix::Lang(lang.to_string())
+    } else {
+        let (datatype, rest) = parse_until(rest.strip_prefix("^^<")?, '>')?;
+        if !rest.is_empty() {
+            return None;
+        }
+        Suffix::Datatype(datatype)
+    };
+    Some(Term::Literal { value, suffix })
+}
+
+/// Unescapes `s` up to the first unescaped `end`. Returns the unescaped part and the rest of
+/// `s` after `end`.
+fn parse_until(s: &str, end: char) -> Option<(String, &str)> {
+    let mut out = String::new();
+    let mut chars = s.char_indices();
+    while let Some((i, c)) = chars.next() {
+        if c == end {
+            return Some((out, &s[i + c.len_utf8()..]));
+        }
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        let escaped = match chars.next()?.1 {
+            // Only UCHAR escapes are valid in IRIs, but accepting ECHAR there as well is
+            // harmless.
+            't' => '\t',
+            'b' => '\u{8}',
+            'n' => '\n',
+            'r' => '\r',
+            'f' => '\u{c}',
+            '"' => '"',
+            '\'' => '\'',
+            '\\' => '\\',
+            'u' => parse_hex(&mut chars, 4)?,
+            'U' => parse_hex(&mut chars, 8)?,
+            _ => return None,
+        };
+        out.push(escaped);
+    }
+    None
+}
+
+fn parse_hex(chars: &mut std::str::CharIndices, digits: usize) -> Option<char> {
+    let mut code: u32 = 0;
+    for _ in 0..digits {
+        code = code * 16 + chars.next()?.1.to_digit(16)?;
+    }
+    char::from_u32(code)
+}
+
+/// Appends `term` in N-Triples notation to `out`.
+///
+/// Output follows canonical N-Triples: in literals, `"`, `\`, newlines, carriage returns, tabs,
+/// backspaces and form feeds use their short escapes and other control characters `\uXXXX`;
+/// in IRIs, characters that may not appear literally use `\uXXXX`. Everything else, including
+/// non-ASCII characters, is written as is.
+pub fn write_term(term: &Term, out: &mut String) {
+    match term {
+        Term::Iri(iri) => write_iri(iri, out),
+        Term::Blank(label) => {
+            out.push_str("_:");
+            out.push_str(label);
+        }
+        Term::Quoted(terms) => {
+            out.push_str("<<");
+            for t in terms.iter() {
+                out.push(' ');
+                write_term(t, out);
+            }
+            out.push_str(" >>");
+        }
+        Term::Literal { value, suffix } => {
+            out.push('"');
+            for c in value.chars() {
+                match c {
+                    '"' => out.push_str("\\\""),
+                    '\\' => out.push_str("\\\\"),
+                    '\n' => out.push_str("\\n"),
+                    '\r' => out.push_str("\\r"),
+                    '\t' => out.push_str("\\t"),
+                    '\u{8}' => out.push_str("\\b"),
+                    '\u{c}' => out.push_str("\\f"),
+                    c if c < ' ' || c == '\u{7f}' => push_uchar(c, out),
+                    c => out.push(c),
+                }
+            }
+            out.push('"');
+            match suffix {
+                Suffix::None => {}
+                Suffix::Lang(lang) => {
+                    out.push('@');
+                    out.push_str(lang);
+                }
+                Suffix::Datatype(datatype) => {
+                    out.push_str("^^");
+                    write_iri(datatype, out);
+                }
+            }
+        }
+    }
+}
+
+fn write_iri(iri: &str, out: &mut String) {
+    out.push('<');
+    for c in iri.chars() {
+        match c {
+            '\0'..=' ' | '<' | '>' | '"' | '{' | '}' | '|' | '^' | '`' | '\\' => push_uchar(c, out),
+            c => out.push(c),
+        }
+    }
+    out.push('>');
+}
+
+fn push_uchar(c: char, out: &mut String) {
+    out.push_str(&format!("\\u{:04X}", c as u32));
+}
+
+/// Splits the N-Triples line `l` into its terms and the final `.` at single spaces, keeping
+/// each quoted triple `<< s p o >>` whole. An unclosed quoted triple runs to the end of `l`.
+pub fn split_terms(l: &str) -> Vec<&str> {
+    let mut words = Vec::new();
+    let (mut depth, mut start, mut pos) = (0, 0, 0);
+    for w in l.split(' ') {
+        let end = pos + w.len();
+        match w {
+            "<<" => {
+                if depth == 0 {
+                    start = pos;
+                }
+                depth += 1;
+            }
+            ">>" if depth > 0 => {
+                depth -= 1;
+                if depth == 0 {
+                    words.push(&l[start..end]);
+                }
+            }
+            _ if depth == 0 => words.push(w),
+            _ => {}
+        }
+        pos = end + 1;
+    }
+    if depth > 0 {
+        words.push(&l[start..]);
+    }
+    words
+}
+
+/// Returns the subject, predicate and object of the quoted triple `term`, if it is one.
+pub fn quoted_terms(term: &str) -> Option<[&str; 3]> {
+    let inner = term.strip_prefix("<< ")?.strip_suffix(" >>")?;
+    match split_terms(inner)[..] {
+        [sub, pred, obj] => Some([sub, pred, obj]),
+        _ => None,
+    }
+}
+
+/// Returns the labels of the blank nodes in the subject and object of the N-Triples line `l`,
+/// also those within quoted triples.
+pub fn blank_nodes(l: &str) -> Vec<&str> {
+    fn collect<'a>(term: &'a str, labels: &mut Vec<&'a str>) {
+        match quoted_terms(term) {
+            Some([sub, _, obj]) => {
+                collect(sub, labels);
+                collect(obj, labels);
+            }
+            None if term.starts_with("_:") => labels.push(term),
+            None => {}
+        }
+    }
+    let mut labels = Vec::new();
+    if let [sub, _, obj, ..] = split_terms(l)[..] {
+        collect(sub, &mut labels);
+        collect(obj, &mut labels);
+    }
+    labels
+}
+
+/// Returns a description of what is wrong with the N-Triples line `l`, if anything.
+pub fn check_line(l: &str) -> Option<&'static str> {
+    let words = split_terms(l);
+
+    if l.is_empty() {
+        Some("empty line")
+    } else if words.len() < 4 || words[words.len() - 1] != "." {
+        Some("expected `<subject> <predicate> <object> .`")
+    } else if !words[0].starts_with('<') && !words[0].starts_with("_:") {
+        Some("subject must be an IRI or blank node")
+    } else if !words[1].starts_with('<') || words[1].starts_with("<<") {
+        Some("predicate must be an IRI")
+    } else if words[..3]
+        .iter()
+        .any(|w| w.starts_with("<<") && quoted_terms(w).is_none())
+    {
+        Some("quoted triple must be `<< <subject> <predicate> <object> >>`")
+    } else {
+        None
+    }
+}
+
+/// Returns the name of the RDF syntax the file at `path` is in, judging by its extension, if it
+/// is one that is not line-based.
+///
+/// Only N-Triples and N-Quads are read, and datasets in Turtle or the Turtle-compatible subset of
+/// N3, see [`crate::parser::n3`]. Other syntaxes need a full parser, such as rio or oxttl, which this
+/// build does not include, so such files are turned away up front instead of failing on their
+/// first line.
+pub fn unsupported_syntax(path: &Path) -> Option<&'static str> {
+    let extension = path.extension()?.to_str()?.to_ascii_lowercase();
+    match extension.as_str() {
+        "trig" => Some("TriG"),
+        "rdf" | "owl" | "xml" => Some("RDF/XML"),
+        "jsonld" => Some("JSON-LD"),
+        _ => None,
+    }
+}
+
+/// Returns the graph term of the N-Quads line `l`, or `None` if it is a triple.
+pub fn graph_of(l: &str) -> Option<&str> {
+    let words = split_terms(l);
+    match words[..] {
+        [_, _, _, g, "."] if g.starts_with('<') || g.starts_with("_:") => Some(g),
+        _ => None,
+    }
+}
+
+/// Returns `term` in canonical N-Triples notation, or unchanged if it cannot be parsed.
+pub fn normalize(term: &str) -> String {
+    match parse_term(term) {
+        Some(t) => {
+            let mut out = String::with_capacity(term.len());
+            write_term(&t, &mut out);
+            out
+        }
+        None => term.to_string(),
+    }
+}
+
+/// Appends `term` to `out` as [`normalize`] returns it. Terms already in canonical notation,
+/// which are most IRIs and blank nodes of a dict, are copied without being parsed.
+pub fn write_normalized(term: &str, out: &mut String) {
+    if is_canonical(term) {
+        return out.push_str(term);
+    }
+    match parse_term(term) {
+        Some(t) => write_term(&t, out),
+        None => out.push_str(term),
+    }
+}
+
+/// Returns true if `term` is an IRI or blank node that [`write_term`] would write unchanged.
+fn is_canonical(term: &str) -> bool {
+    if let Some(label) = term.strip_prefix("_:") {
+        return !label.is_empty();
+    }
+    match term.strip_prefix('<').and_then(|t| t.strip_suffix('>')) {
+        Some(iri) => !iri.chars().any(|c| {
+            matches!(
+                c,
+                '\0'..=' ' | '<' | '>' | '"' | '{' | '}' | '|' | '^' | '`' | '\\'
+            )
+        }),
+        None => false,
+    }
+}
+
+/// Returns the N-Triples line of the triple with the terms `s`, `p` and `o`.
+pub fn triple_line(s: &str, p: &str, o: &str) -> String {
+    format!("{} {} {} .", normalize(s), normalize(p), normalize(o))
+}
+
+/// Returns the N-Quads line of the triple with the terms `s`, `p` and `o` in the graph `g`.
+pub fn quad_line(s: &str, p: &str, o: &str, g: &str) -> String {
+    format!(
+        "{} {} {} {} .",
+        normalize(s),
+        normalize(p),
+        normalize(o),
+        normalize(g)
+    )
+}
@@ -0,0 +1,491 @@
+//! Programmatic updates of an in-memory summary, one triple at a time.
+
+use crate::metrics::drift::{DriftMonitor, DriftReport, DriftThresholds};
+use crate::models::clique::CliqueCollection;
+use crate::models::dataset::Dataset;
+use crate::models::meta::Meta;
+use crate::updater::{self, Options};
+use crate::util::hash::{HashMap, HashSet};
+use crate::{rdf, Grouping};
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::fmt;
+use std::time::Instant;
+
+/// A summary kept in memory that triples can be added to and deleted from by their terms, so
+/// callers never deal with dict ids.
+pub struct Summarizer {
+    pub dataset: Dataset,
+    pub meta: Meta,
+    pub sc: CliqueCollection,
+    pub tc: CliqueCollection,
+    grouping: Grouping,
+    options: Options,
+    recent: RecentChanges,
+    drift: DriftMonitor,
+    epochs: Epochs,
+}
+
+/// Optimistic versions of the supernodes, so a change prepared against an earlier state of the
+/// summary can be refused when the supernodes of its nodes changed since, as with several
+/// writers scheduled outside the summarizer.
+#[derive(Default)]
+struct Epochs {
+    /// Number of changes applied so far.
+    current: u64,
+    /// Epoch of the last change that created, removed or changed each supernode, by alias.
+    supernodes: HashMap<String, u64>,
+    /// Epoch of the last change that took each node out of a supernode, and that supernode.
+    left: HashMap<u32, (u64, String)>,
+}
+
+/// The last change of each triple among the most recent changes, so a change delivered again
+/// can be told apart from one that undoes an earlier change.
+#[derive(Default)]
+struct RecentChanges {
+    size: usize,
+    /// Whether the last change of a triple was a deletion, and the number of that change.
+    last: HashMap<String, (bool, u64)>,
+    order: VecDeque<(String, u64)>,
+    count: u64,
+}
+
+/// How a single change altered the supernodes of the summary.
+///
+/// Supernodes are named by their aliases, which stay the same as long as a supernode keeps its
+/// identity.
+#[derive(Serialize, Default)]
+pub struct StructuralChanges {
+    /// Supernodes that did not exist before the change.
+    pub created: Vec<String>,
+    /// Supernodes that no longer exist.
+    pub removed: Vec<String>,
+    /// Supernodes that gained or lost members.
+    pub changed: Vec<String>,
+    /// The change was a deletion of a triple not in the summary and was ignored.
+    pub rejected: bool,
+    /// The change was an insertion of a triple already in the summary.
+    pub duplicate: bool,
+    /// The change repeated the last change of its triple within the dedup window and was skipped.
+    pub repeated: bool,
+    /// Supernodes of the nodes of the change, or that they left, changed after the epoch the
+    /// change was prepared at, so it was not applied.
+    pub conflicts: Vec<String>,
+    /// Number of supernodes split up or created by the re-clustering pass a drift alert
+    /// triggered after the change, if it triggered one.
+    pub reclustered: Option<usize>,
+}
+
+/// Supernodes that may hold the subjects and objects of the triples matching a pattern, by
+/// alias.
+#[derive(Serialize, Default, Debug, PartialEq)]
+pub struct RelevantSupernodes {
+    pub subjects: Vec<String>,
+    pub objects: Vec<String>,
+}
+
+impl Summarizer {
+    /// Wraps a summary whose pending update has already been applied.
+    pub fn new(
+        dataset: Dataset,
+        meta: Meta,
+        sc: CliqueCollection,
+        tc: CliqueCollection,
+        grouping: Grouping,
+        options: Options,
+    ) -> Self {
+        Self {
+            dataset,
+            meta,
+            sc,
+            tc,
+            grouping,
+            options,
+            recent: RecentChanges::default(),
+            drift: DriftMonitor::default(),
+            epochs: Epochs::default(),
+        }
+    }
+
+    /// Skips changes that repeat the last change of their triple among the last `size` changes,
+    /// as when a change is delivered more than once. A size of 0 turns this off.
+    pub fn set_dedup_window(&mut self, size: usize) {
+        self.recent = RecentChanges {
+            size,
+            ..Default::default()
+        };
+    }
+
+    /// Re-clusters the summary after a change that raises an alert of `thresholds`.
+    pub fn set_drift_thresholds(&mut self, thresholds: DriftThresholds) {
+        self.drift = DriftMonitor::new(thresholds);
+    }
+
+    /// Returns the drift indicators of the summary.
+    pub fn drift(&mut self) -> DriftReport {
+        self.drift.report(&self.meta, Instant::now())
+    }
+
+    /// Returns the supernodes that may hold matches of the triple pattern `pattern`, whose
+    /// terms are variables, starting with `?`, or terms in N-Triples notation, so a query
+    /// engine can skip the sources of the other supernodes.
+    ///
+    /// A supernode may hold a subject if the outgoing predicates of its members include that of
+    /// the pattern, or any if it is a variable, and an object likewise by incoming predicates.
+    /// With a constant subject or object, only its supernode is left in that position, and the
+    /// other position is narrowed to the supernodes it has an edge with. Nodes outside
+    /// supernodes are not listed.
+    pub fn relevant_supernodes(&self, pattern: [&str; 3]) -> RelevantSupernodes {
+        let constant = |t: &str| !t.starts_with('?');
+        let [sub, pred, obj] = pattern;
+        let pred_id = self.dataset.pred_id_of(pred);
+        let [sub_id, obj_id] = [sub, obj].map(|t| self.dataset.id_of(t));
+        if (constant(pred) && pred_id.is_none())
+            || (constant(sub) && sub_id.is_none())
+            || (constant(obj) && obj_id.is_none())
+        {
+            return RelevantSupernodes::default();
+        }
+        let home = |id: Option<u32>| {
+            id.map(|n| self.meta.get_node(&n).and_then(|i| i.parent).unwrap_or(n))
+        };
+        let (sub_home, obj_home) = (home(sub_id), home(obj_id));
+
+        let fits = |snode: &u32, outgoing: bool| match (pred_id, outgoing) {
+            (Some(p), true) => self.meta.has_outgoing_pred(snode, &p),
+            (Some(p), false) => self.meta.has_incoming_pred(snode, &p),
+            (None, _) => self
+                .meta
+                .get_signature(snode)
+                .is_some_and(|s| match outgoing {
+                    true => !s.outgoing.is_empty(),
+                    false => !s.incoming.is_empty(),
+                }),
+        };
+        let candidates = |constant: Option<u32>, other: Option<u32>, outgoing: bool| {
+            let snodes: Vec<u32> = match constant {
+                Some(n) => vec![n],
+                None => self.meta.get_supernodes().keys().copied().collect(),
+            };
+            let mut aliases: Vec<String> = snodes
+                .into_iter()
+                .filter(|s| self.meta.contains_supernode(s) && fits(s, outgoing))
+                .filter(|s| match (pred_id, other) {
+                    (Some(p), Some(o)) if outgoing => self.meta.has_edge_between(s, &p, &o),
+                    (Some(p), Some(o)) => self.meta.has_edge_between(&o, &p, s),
+                    _ => true,
+                })
+                .filter_map(|s| self.meta.get_alias(&s).cloned())
+                .collect();
+            aliases.sort();
+            aliases
+        };
+        RelevantSupernodes {
+            subjects: candidates(sub_home, obj_home, true),
+            objects: candidates(obj_home, sub_home, false),
+        }
+    }
+
+    /// Returns the number of changes applied so far, to prepare a change against with
+    /// [`Summarizer::add_at`] or [`Summarizer::delete_at`].
+    pub fn epoch(&self) -> u64 {
+        self.epochs.current
+    }
+
+    /// Inserts the triple with the terms `sub`, `pred` and `obj` in N-Triples notation.
+    pub fn add(&mut self, sub: &str, pred: &str, obj: &str) -> Result<StructuralChanges, String> {
+        let line = line(sub, pred, obj)?;
+        if self.recent.repeats(&line, false) {
+            return Ok(StructuralChanges::repeated());
+        }
+        let changes = self.apply(vec![line.clone()], Vec::new())?;
+        self.recent.record(line, false);
+        Ok(changes)
+    }
+
+    /// Deletes the triple with the terms `sub`, `pred` and `obj` in N-Triples notation.
+    pub fn delete(
+        &mut self,
+        sub: &str,
+        pred: &str,
+        obj: &str,
+    ) -> Result<StructuralChanges, String> {
+        let line = line(sub, pred, obj)?;
+        if self.recent.repeats(&line, true) {
+            return Ok(StructuralChanges::repeated());
+        }
+        let changes = self.apply(Vec::new(), vec![line.clone()])?;
+        self.recent.record(line, true);
+        Ok(changes)
+    }
+
+    /// Returns true if inserting, or deleting if `delete`, the triple with the terms `sub`,
+    /// `pred` and `obj` would be skipped as a repeat of the last change of that triple, so a
+    /// caller logging changes can leave it out.
+    pub fn repeats(&self, sub: &str, pred: &str, obj: &str, delete: bool) -> bool {
+        line(sub, pred, obj).is_ok_and(|line| self.recent.repeats(&line, delete))
+    }
+
+    /// Like [`Summarizer::add`], but refuses the insertion with its conflicts if the supernodes
+    /// of `sub` or `obj` changed after `epoch`, so the caller can prepare it again and retry.
+    pub fn add_at(
+        &mut self,
+        sub: &str,
+        pred: &str,
+        obj: &str,
+        epoch: u64,
+    ) -> Result<StructuralChanges, String> {
+        match self.conflicts(sub, obj, epoch) {
+            Some(changes) => Ok(changes),
+            None => self.add(sub, pred, obj),
+        }
+    }
+
+    /// Like [`Summarizer::delete`], but refuses the deletion with its conflicts if the
+    /// supernodes of `sub` or `obj` changed after `epoch`.
+    pub fn delete_at(
+        &mut self,
+        sub: &str,
+        pred: &str,
+        obj: &str,
+        epoch: u64,
+    ) -> Result<StructuralChanges, String> {
+        match self.conflicts(sub, obj, epoch) {
+            Some(changes) => Ok(changes),
+            None => self.delete(sub, pred, obj),
+        }
+    }
+
+    /// Returns the refused change if the supernode of `sub` or `obj`, or one they left, changed
+    /// after `epoch`.
+    fn conflicts(&self, sub: &str, obj: &str, epoch: u64) -> Option<StructuralChanges> {
+        let mut conflicts = Vec::new();
+        for id in [sub, obj].iter().filter_map(|t| self.dataset.id_of(t)) {
+            if let Some((e, alias)) = self.epochs.left.get(&id) {
+                if *e > epoch {
+                    conflicts.push(alias.clone());
+                }
+            }
+            let alias = self
+                .meta
+                .get_node(&id)
+                .and_then(|n| n.parent)
+                .and_then(|p| self.meta.get_alias(&p));
+            if let Some(alias) = alias {
+                if self
+                    .epochs
+                    .supernodes
+                    .get(alias)
+                    .is_some_and(|e| *e > epoch)
+                {
+                    conflicts.push(alias.clone());
+                }
+            }
+        }
+        if conflicts.is_empty() {
+            return None;
+        }
+        conflicts.sort();
+        conflicts.dedup();
+        Some(StructuralChanges {
+            conflicts,
+            ..Default::default()
+        })
+    }
+
+    fn apply(&mut self, i_l: Vec<String>, d_l: Vec<String>) -> Result<StructuralChanges, String> {
+        let before = self.groups();
+        self.dataset
+            .set_update(i_l, d_l, &mut self.meta, &self.grouping);
+        let report = updater::run(
+            &mut self.dataset,
+            &mut self.meta,
+            &mut self.sc,
+            &mut self.tc,
+            &self.options,
+        )?;
+        let mut changes = StructuralChanges {
+            rejected: !report.rejected_deletions.is_empty(),
+            duplicate: report.duplicate_insertions > 0,
+            ..Default::default()
+        };
+
+        let now = Instant::now();
+        self.drift.record_splits(self.splits(&before), now);
+        if self.drift.check(&self.meta, now) {
+            changes.reclustered = Some(self.recluster()?);
+        }
+
+        let after = self.groups();
+        for (alias, members) in &after {
+            match before.get(alias) {
+                None => changes.created.push(alias.clone()),
+                Some(old) if old != members => changes.changed.push(alias.clone()),
+                Some(_) => {}
+            }
+        }
+        for alias in before.keys() {
+            if !after.contains_key(alias) {
+                changes.removed.push(alias.clone());
+            }
+        }
+        changes.created.sort();
+        changes.removed.sort();
+        changes.changed.sort();
+        self.epochs.record(&changes, &before, &after);
+        Ok(changes)
+    }
+
+    /// Re-clusters the whole summary and returns the number of supernodes split up or created.
+    fn recluster(&mut self) -> Result<usize, String> {
+        self.dataset
+            .set_update(Vec::new(), Vec::new(), &mut self.meta, &self.grouping);
+        self.options.recluster = true;
+        let report = updater::run(
+            &mut self.dataset,
+            &mut self.meta,
+            &mut self.sc,
+            &mut self.tc,
+            &self.options,
+        );
+        self.options.recluster = false;
+        self.drift.reclustered();
+        Ok(report?.reclustered)
+    }
+
+    /// Returns the number of the supernodes `before` whose members are no longer all in one
+    /// summary node.
+    fn splits(&self, before: &HashMap<String, Vec<u32>>) -> usize {
+        before
+            .values()
+            .filter(|members| {
+                let homes: HashSet<u32> = members
+                    .iter()
+                    .map(|m| self.meta.get_parent(m).unwrap_or(*m))
+                    .collect();
+                homes.len() > 1
+            })
+            .count()
+    }
+
+    /// Returns the sorted members of each supernode by alias.
+    fn groups(&self) -> HashMap<String, Vec<u32>> {
+        self.meta
+            .get_supernodes()
+            .iter()
+            .filter_map(|(id, members)| {
+                let mut members = members.clone();
+                members.sort();
+                self.meta.get_alias(id).map(|a| (a.clone(), members))
+            })
+            .collect()
+    }
+}
+
+impl RecentChanges {
+    /// Returns true if the last change of `line` in the window was the same.
+    fn repeats(&self, line: &str, delete: bool) -> bool {
+        self.size > 0 && self.last.get(line).is_some_and(|(d, _)| *d == delete)
+    }
+
+    /// Records a change of `line` that was applied, so only changes that took effect are
+    /// skipped as repeats.
+    fn record(&mut self, line: String, delete: bool) {
+        if self.size == 0 {
+            return;
+        }
+        self.count += 1;
+        self.last.insert(line.clone(), (delete, self.count));
+        self.order.push_back((line, self.count));
+        if self.order.len() > self.size {
+            let (old, n) = self.order.pop_front().unwrap();
+            // The triple may have changed again since, which keeps it in the window.
+            if self.last.get(&old).is_some_and(|(_, last)| *last == n) {
+                self.last.remove(&old);
+            }
+        }
+    }
+}
+
+impl Epochs {
+    /// Starts a new epoch with the supernodes `changes` touched, given the members of each
+    /// supernode `before` and `after` the change.
+    fn record(
+        &mut self,
+        changes: &StructuralChanges,
+        before: &HashMap<String, Vec<u32>>,
+        after: &HashMap<String, Vec<u32>>,
+    ) {
+        self.current += 1;
+        for alias in &changes.created {
+            self.supernodes.insert(alias.clone(), self.current);
+        }
+        for alias in changes.removed.iter().chain(&changes.changed) {
+            self.supernodes.insert(alias.clone(), self.current);
+            let kept = after.get(alias).map_or(&[][..], |m| m.as_slice());
+            for m in &before[alias] {
+                if kept.binary_search(m).is_err() {
+                    self.left.insert(*m, (self.current, alias.clone()));
+                }
+            }
+        }
+    }
+}
+
+impl StructuralChanges {
+    fn repeated() -> Self {
+        Self {
+            repeated: true,
+            ..Default::default()
+        }
+    }
+}
+
+fn line(sub: &str, pred: &str, obj: &str) -> Result<String, String> {
+    let line = format!("{} {} {} .", sub, pred, obj);
+    match rdf::check_line(&line) {
+        Some(problem) => Err(problem.to_string()),
+        None => Ok(line),
+    }
+}
+
+impl fmt::Display for StructuralChanges {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.rejected {
+            writeln!(f, "rejected: triple not in the summary")?;
+        }
+        if self.duplicate {
+            writeln!(f, "duplicate: triple already in the summary")?;
+        }
+        if self.repeated {
+            writeln!(
+                f,
+                "repeated: same change as the last one of this triple, skipped"
+            )?;
+        }
+        if !self.conflicts.is_empty() {
+            writeln!(
+                f,
+                "conflict: supernodes changed since the change was prepared: {}",
+                self.conflicts.join(", ")
+            )?;
+        }
+        if let Some(count) = self.reclustered {
+            writeln!(
+                f,
+                "drift alert: re-clustered, {} supernodes split up or created",
+                count
+            )?;
+        }
+        for (label, aliases) in [
+            ("created", &self.created),
+            ("removed", &self.removed),
+            ("changed", &self.changed),
+        ] {
+            for a in aliases {
+                writeln!(f, "{} supernode {}", label, a)?;
+            }
+        }
+        Ok(())
+    }
+}
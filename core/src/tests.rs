@@ -0,0 +1,9 @@
+mod builder_tests;
+mod fixtures;
+mod metrics_tests;
+mod models_tests;
+mod parser_tests;
+mod rdf_tests;
+mod summarizer_tests;
+mod updater_tests;
+mod util_tests;
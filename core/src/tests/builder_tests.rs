@@ -0,0 +1,150 @@
+use crate::parser::exclusion::AutoExclude;
+use crate::util::io;
+use crate::{Config, ConfigBuilder};
+use std::path::PathBuf;
+
+fn builder() -> ConfigBuilder {
+    Config::builder()
+        .dataset("summary.nt")
+        .update("update.nt")
+        .meta_folder("meta")
+}
+
+#[test]
+fn builder_starts_from_the_command_line_defaults() {
+    let config = builder().threads(8).build().unwrap();
+
+    assert_eq!(config.dataset_path, PathBuf::from("summary.nt"));
+    assert_eq!(config.summary_path(), PathBuf::from("summary.nt"));
+    assert_eq!(config.threads, Some(8));
+    assert_eq!(config.channel_capacity, io::STREAM_CAPACITY);
+    assert!(!config.use_fast);
+}
+
+#[test]
+fn builder_rejects_settings_that_do_not_go_together() {
+    assert!(Config::builder()
+        .update("update.nt")
+        .meta_folder("meta")
+        .build()
+        .is_err());
+    assert!(builder().threads(0).build().is_err());
+    assert!(builder().seed(7).build().is_err());
+    assert!(builder().sample(1.5).build().is_err());
+    assert!(builder().max_supernode_size(1).build().is_err());
+    assert!(builder().auto_exclude(AutoExclude::Apply).build().is_err());
+
+    let sampled = builder().sample(0.5).seed(7).build().unwrap();
+    assert_eq!((sampled.sample, sampled.seed), (Some(0.5), 7));
+    assert!(builder()
+        .fast(true)
+        .auto_exclude(AutoExclude::Apply)
+        .build()
+        .is_ok());
+}
+
+#[test]
+#[cfg(feature = "endpoint")]
+fn https_endpoints_are_rejected_up_front() {
+    let config = Config::builder()
+        .endpoint("https://ex.org/sparql")
+        .update("update.nt")
+        .meta_folder("meta")
+        .build()
+        .unwrap();
+    let problems = config.validate(false).unwrap_err();
+    assert!(problems
+        .iter()
+        .any(|p| p.starts_with("endpoint https://ex.org/sparql uses https")));
+}
+
+#[test]
+fn empty_and_malformed_update_lines_are_reported_by_line() {
+    use std::{env, fs, process};
+
+    let folder = env::temp_dir().join(format!("teriyaki-validate-{}", process::id()));
+    fs::create_dir_all(&folder).unwrap();
+    let dataset = folder.join("dataset.nt");
+    let update = folder.join("update.nt");
+    fs::write(&dataset, "<a> <p> <b> .\n").unwrap();
+    fs::write(&update, "<a> <p> <c> .\n\n-<a> <p>\n   \n-<a> <p> <b> .\n").unwrap();
+    let config = |max_errors: Option<usize>| {
+        let builder = Config::builder()
+            .dataset(&dataset)
+            .update(&update)
+            .meta_folder(folder.join("meta"))
+            .fast(true);
+        match max_errors {
+            Some(max) => builder.max_errors(max),
+            None => builder,
+        }
+        .build()
+        .unwrap()
+    };
+
+    let problems = config(None).validate(false).unwrap_err();
+    let skipped = config(Some(3)).validate(false);
+    fs::remove_dir_all(&folder).unwrap();
+
+    let update = update.display();
+    assert_eq!(
+        problems,
+        vec![
+            format!("update file {update} does not look like N-Triples, line 2: empty line"),
+            format!(
+                "update file {update} does not look like N-Triples, line 3: expected `<subject> <predicate> <object> .`"
+            ),
+            format!("update file {update} does not look like N-Triples, line 4: empty line"),
+        ]
+    );
+    assert!(skipped.is_ok(), "{:?}", skipped);
+}
+
+#[test]
+fn turtle_files_are_read_as_n3() {
+    use crate::parser::dataset::read_update_lines;
+    use std::{env, fs, process};
+
+    let folder = env::temp_dir().join(format!("teriyaki-turtle-{}", process::id()));
+    fs::create_dir_all(&folder).unwrap();
+    let dataset = folder.join("dataset.ttl");
+    let update = folder.join("update.ttl");
+    fs::write(
+        &dataset,
+        "@prefix ex: <http://ex.org/> .\nex:a ex:p ex:b .\n",
+    )
+    .unwrap();
+    fs::write(
+        &update,
+        "@prefix ex: <http://ex.org/> .\nex:a ex:p ex:c , ex:d .\n",
+    )
+    .unwrap();
+    let config = Config::builder()
+        .dataset(&dataset)
+        .update(&update)
+        .meta_folder(folder.join("meta"))
+        .fast(true)
+        .build()
+        .unwrap();
+
+    let validated = config.validate(false);
+    let lines = read_update_lines(&update, None);
+    fs::remove_dir_all(&folder).unwrap();
+
+    assert!(validated.is_ok(), "{:?}", validated);
+    assert_eq!(
+        lines.unwrap(),
+        vec![
+            "<http://ex.org/a> <http://ex.org/p> <http://ex.org/c> .",
+            "<http://ex.org/a> <http://ex.org/p> <http://ex.org/d> .",
+        ]
+    );
+}
+
+#[test]
+#[cfg(feature = "zstd")]
+fn zstd_levels_outside_the_range_of_zstd_are_rejected() {
+    assert!(builder().zstd_meta(0).build().is_err());
+    assert!(builder().zstd_meta(23).build().is_err());
+    assert_eq!(builder().zstd_meta(19).build().unwrap().zstd_meta, Some(19));
+}
@@ -0,0 +1,176 @@
+//! Builds summaries from small graphs written inline in tests.
+
+use crate::models::canonical::CanonicalSummary;
+use crate::models::clique::CliqueCollection;
+use crate::models::dataset::Dataset;
+use crate::models::meta::Meta;
+use crate::models::triple::TYPE_STRING;
+use crate::parser;
+use crate::rdf;
+use crate::updater::{self, Options, UpdateReport};
+use crate::Grouping;
+
+pub struct Fixture {
+    pub dataset: Dataset,
+    pub meta: Meta,
+    pub sc: CliqueCollection,
+    pub tc: CliqueCollection,
+    options: Options,
+}
+
+/// Summarizes `graph` like a fast-mode run that inserts all of its triples.
+///
+/// `graph` has one triple per line. Terms without angle brackets are expanded to
+/// `<http://ex.org/term>` and `rdf:type` to the full type predicate, so `a knows b` is a valid
+/// line, and `<< a knows b >>` a quoted triple.
+pub fn load(graph: &str) -> Fixture {
+    load_with(graph, Options::default())
+}
+
+/// Like [`load`], but applies this and later updates with `options`. Clique invariants are
+/// always verified.
+pub fn load_with(graph: &str, options: Options) -> Fixture {
+    let triples = lines(graph)
+        .iter()
+        .map(|l| {
+            let terms = rdf::split_terms(l);
+            (
+                terms[0].to_string(),
+                terms[1].to_string(),
+                terms[2].to_string(),
+            )
+        })
+        .collect();
+    let (dataset, meta, sc, tc) = parser::from_triples(triples, &Grouping::Preds);
+
+    let mut fixture = Fixture {
+        dataset,
+        meta,
+        sc,
+        tc,
+        options: Options {
+            paranoid: true,
+            ..options
+        },
+    };
+    fixture.run();
+    fixture
+}
+
+impl Fixture {
+    /// Applies `changes`, written like `graph` in [`load`]. Deletions start with `-`.
+    pub fn update(&mut self, changes: &str) -> UpdateReport {
+        let (deletions, insertions): (Vec<String>, Vec<String>) =
+            lines(changes).into_iter().partition(|l| l.starts_with('-'));
+        let deletions = deletions.into_iter().map(|l| l[1..].to_string()).collect();
+        self.dataset
+            .set_update(insertions, deletions, &mut self.meta, &Grouping::Preds);
+        self.run()
+    }
+
+    /// Like [`Fixture::update`], but applies the changes `chunk_size` at a time. Returns the
+    /// report and the number of changes applied at each snapshot.
+    pub fn update_in_chunks(
+        &mut self,
+        changes: &str,
+        chunk_size: usize,
+    ) -> (UpdateReport, Vec<usize>) {
+        let (deletions, insertions): (Vec<String>, Vec<String>) =
+            lines(changes).into_iter().partition(|l| l.starts_with('-'));
+        let deletions = deletions.into_iter().map(|l| l[1..].to_string()).collect();
+        self.dataset
+            .set_update(insertions, deletions, &mut self.meta, &Grouping::Preds);
+        let mut snapshots = Vec::new();
+        let report = updater::run_in_chunks(
+            &mut self.dataset,
+            &mut self.meta,
+            &mut self.sc,
+            &mut self.tc,
+            &self.options,
+            chunk_size,
+            |progress, _, _, _, _| snapshots.push(progress.applied),
+        )
+        .unwrap();
+        (report, snapshots)
+    }
+
+    /// Returns the summary triples as sorted N-Triples lines.
+    pub fn summary(&self) -> Vec<String> {
+        let mut lines: Vec<String> = self
+            .dataset
+            .triples
+            .data_triples
+            .iter()
+            .map(|t| t.to_string(&self.dataset))
+            .collect();
+        lines.sort();
+        lines
+    }
+
+    pub fn canonical(&self) -> CanonicalSummary {
+        CanonicalSummary::new(&self.dataset, &self.meta)
+    }
+
+    /// Returns the sorted IRIs of the members of the supernode containing `node`.
+    pub fn members(&self, node: &str) -> Vec<String> {
+        let id = self.dataset.id_of(&iri(node)).unwrap();
+        let mut members: Vec<String> = match self.meta.get_parent(&id) {
+            Some(p) => self.meta.get_supernode(&p).unwrap().clone(),
+            None => vec![id],
+        }
+        .iter()
+        .map(|m| self.dataset.key_by_value(m).unwrap())
+        .collect();
+        members.sort();
+        members
+    }
+
+    /// Returns the id and alias of the supernode containing `node`.
+    pub fn supernode(&self, node: &str) -> (u32, String) {
+        let id = self.dataset.id_of(&iri(node)).unwrap();
+        let p = self.meta.get_parent(&id).unwrap();
+        (p, self.meta.get_alias(&p).unwrap().clone())
+    }
+
+    fn run(&mut self) -> UpdateReport {
+        updater::run(
+            &mut self.dataset,
+            &mut self.meta,
+            &mut self.sc,
+            &mut self.tc,
+            &self.options,
+        )
+        .unwrap()
+    }
+}
+
+fn lines(graph: &str) -> Vec<String> {
+    graph
+        .lines()
+        .map(|l| l.trim())
+        .filter(|l| !l.is_empty())
+        .map(|l| {
+            let (prefix, l) = match l.strip_prefix('-') {
+                Some(rest) => ("-", rest),
+                None => ("", l),
+            };
+            let terms: Vec<String> = l
+                .trim_end_matches('.')
+                .split_whitespace()
+                .map(|t| if t == ">>" { t.to_string() } else { iri(t) })
+                .collect();
+            format!("{}{} .", prefix, terms.join(" "))
+        })
+        .collect()
+}
+
+/// Expands `term` to an IRI in N-Triples notation unless it already is one.
+pub fn iri(term: &str) -> String {
+    if term == "rdf:type" {
+        TYPE_STRING.to_string()
+    } else if term.starts_with('<') {
+        term.to_string()
+    } else {
+        format!("<http://ex.org/{}>", term)
+    }
+}
@@ -0,0 +1,142 @@
+use super::fixtures::load;
+use crate::metrics::centrality::{degrees, pagerank};
+use crate::metrics::compression::clique_compression;
+use crate::metrics::drift::{DriftMonitor, DriftThresholds};
+use crate::metrics::memory::MemoryReport;
+use crate::metrics::timing::{self, Phase};
+use crate::models::graph::Graph;
+use std::thread;
+use std::time::{Duration, Instant};
+
+#[test]
+fn clique_compression_counts_covered_and_saved_triples() {
+    let f = load(
+        "a knows x
+         b knows x
+         c knows x
+         d likes y",
+    );
+
+    let cliques = clique_compression(&f.sc, &f.tc, &f.meta, &f.dataset.triples);
+
+    // Each predicate is in one source and one target clique.
+    assert_eq!(cliques.len(), 4);
+    let best = &cliques[0];
+    assert_eq!(best.preds.len(), 1);
+    assert_eq!(
+        (best.original_triples, best.summary_triples, best.saved()),
+        (3, 1, 2)
+    );
+    let total: usize = cliques.iter().map(|c| c.original_triples).sum();
+    assert_eq!(total, 2 * 4);
+}
+
+#[test]
+fn drift_counts_splits_of_the_last_minute() {
+    let f = load(
+        "a knows x
+         b knows x
+         c likes y",
+    );
+    let mut monitor = DriftMonitor::new(DriftThresholds {
+        max_splits_per_minute: Some(1),
+        ..Default::default()
+    });
+    let start = Instant::now();
+
+    let report = monitor.report(&f.meta, start);
+    // Summary nodes: the supernode of a and b, x, c and y.
+    assert_eq!(report.singleton_share, 0.75);
+    assert_eq!(report.average_supernode_size, 2.0);
+
+    monitor.record_splits(2, start);
+    assert!(monitor.check(&f.meta, start));
+    assert!(!monitor.check(&f.meta, start + Duration::from_secs(30)));
+    let later = monitor.report(&f.meta, start + Duration::from_secs(60));
+    assert_eq!(later.splits_per_minute, 0);
+    assert!(later.alerts.is_empty());
+}
+
+#[test]
+fn memory_estimates_grow_with_the_summary() {
+    let small = load("a knows x");
+    let large = load(
+        &(0..200)
+            .map(|i| format!("n{} knows m{}\n", i, i % 7))
+            .collect::<String>(),
+    );
+
+    let small = MemoryReport::new(&small.dataset, &small.meta, &small.sc, &small.tc);
+    let large = MemoryReport::new(&large.dataset, &large.meta, &large.sc, &large.tc);
+    assert!(large.dict_bytes > small.dict_bytes);
+    assert!(large.triples_bytes > small.triples_bytes);
+    assert!(large.cliques_bytes > small.cliques_bytes);
+    assert!(large.meta_bytes > small.meta_bytes);
+    assert_eq!(
+        large.estimated_bytes(),
+        large.dict_bytes + large.triples_bytes + large.cliques_bytes + large.meta_bytes
+    );
+    if cfg!(target_os = "linux") {
+        assert!(large.peak_rss_bytes.unwrap() > 0);
+    }
+}
+
+#[test]
+fn pagerank_and_degrees_of_a_cycle_with_a_sink() {
+    // 1 -> 2 -> 3 -> 1, and 1 -> 4, which has no outgoing edges.
+    let graph = Graph::from_edges([[1, 10, 2], [2, 10, 3], [3, 10, 1], [1, 11, 4]]);
+
+    let degrees = degrees(&graph);
+    let degree = |n: u32| (degrees[&n].incoming, degrees[&n].outgoing);
+    assert_eq!(degree(1), (1, 2));
+    assert_eq!(degree(2), (1, 1));
+    assert_eq!(degree(4), (1, 0));
+    assert_eq!(degrees[&3].total(), 2);
+
+    // Solving r = (1 - d) / 4 + d * r4 / 4 + d * (rank flowing in) with d = 0.85 by hand:
+    // r1 = 2058/6685, r2 = r4 = 1429/6685 and r3 = 1769/6685.
+    let ranks = pagerank(&graph);
+    let expected = [(1, 2058.0), (2, 1429.0), (3, 1769.0), (4, 1429.0)];
+    for (n, r) in expected {
+        assert!(
+            (ranks[&n] - r / 6685.0).abs() < 1e-4,
+            "{}: {}",
+            n,
+            ranks[&n]
+        );
+    }
+    assert!((ranks.values().sum::<f64>() - 1.0).abs() < 1e-9);
+    assert!(pagerank(&Graph::default()).is_empty());
+}
+
+#[test]
+fn phase_timings_count_nested_phases_only_once() {
+    timing::take();
+    let start = Instant::now();
+    {
+        let _split = timing::phase(Phase::Split);
+        thread::sleep(Duration::from_millis(2));
+        let _cliques = timing::phase(Phase::Cliques);
+        thread::sleep(Duration::from_millis(5));
+    }
+    let elapsed = start.elapsed().as_micros() as u64;
+
+    let timings = timing::take();
+    assert!(timings.cliques_us >= 5_000, "{}", timings);
+    assert!(timings.split_us >= 2_000, "{}", timings);
+    // The time of the cliques phase is not counted for the split phase around it too.
+    assert!(
+        timings.split_us + timings.cliques_us <= elapsed,
+        "{}",
+        timings
+    );
+    assert_eq!(
+        timings.meta_edges_us + timings.index_us + timings.supernodes_us,
+        0
+    );
+    assert_eq!(timing::take().split_us, 0);
+
+    let mut total = timings.clone();
+    total.add_all(&timings);
+    assert_eq!(total.cliques_us, 2 * timings.cliques_us);
+}
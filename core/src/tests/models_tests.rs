@@ -0,0 +1,20 @@
+#[cfg(test)]
+mod clique_tests;
+#[cfg(test)]
+mod compact_meta_tests;
+#[cfg(test)]
+mod edges_tests;
+#[cfg(test)]
+mod graph_tests;
+#[cfg(test)]
+mod literals_tests;
+#[cfg(test)]
+mod meta_tests;
+#[cfg(test)]
+mod node_set_tests;
+#[cfg(test)]
+mod seeds_tests;
+#[cfg(test)]
+mod triple_filter_tests;
+#[cfg(test)]
+mod triple_tests;
@@ -0,0 +1,64 @@
+use crate::models::clique::CliqueCollection;
+
+#[test]
+fn verify() {
+    let mut cc = CliqueCollection::new();
+    cc.new_triple(&1, &10);
+    cc.new_triple(&2, &10);
+    cc.add_node_to_empty_clique(&3);
+
+    assert!(cc.verify(&[1, 2, 3]).is_ok());
+    assert!(cc.verify(&[1, 2]).is_ok());
+    assert!(cc.verify(&[1, 2, 3, 4]).is_err());
+    assert!(cc.verify(&[2, 3]).is_err());
+}
+
+#[test]
+fn verify_detached_split() {
+    let mut cc = CliqueCollection::new();
+    cc.new_triple(&1, &10);
+    cc.new_triple(&1, &11);
+    cc.new_triple(&2, &11);
+    let index = cc.get_index(&1);
+
+    cc.detach(index, &[11], &[2]);
+    assert!(cc.verify(&[1, 2]).is_err());
+
+    cc.new_clique(&vec![11], &vec![2]);
+    assert!(cc.verify(&[1, 2]).is_ok());
+}
+
+#[test]
+fn removals_keep_node_positions() {
+    let mut cc = CliqueCollection::new();
+    for n in 1..=4 {
+        cc.new_triple(&n, &10);
+    }
+    let index = cc.get_index(&1);
+
+    cc.remove_node(&2);
+    cc.move_node_to_empty_clique(&1);
+    assert_eq!(cc.get_nodes(index), vec![3, 4]);
+    cc.new_triple(&5, &10);
+    cc.remove_node(&4);
+    assert_eq!(cc.get_nodes(index), vec![3, 5]);
+    assert!(cc.verify(&[1, 3, 5]).is_ok());
+}
+
+#[test]
+fn nodes_shared_with_other_clique() {
+    let mut sc = CliqueCollection::new();
+    let mut tc = CliqueCollection::new();
+    for n in 1..=3 {
+        sc.new_triple(&n, &10);
+    }
+    for n in 2..=4 {
+        tc.new_triple(&n, &11);
+    }
+    tc.add_node_to_empty_clique(&1);
+
+    let (s, t) = (sc.get_index(&1), tc.get_index(&2));
+    assert_eq!(sc.nodes_shared_with(s, &tc, t), vec![2, 3]);
+    assert_eq!(tc.nodes_shared_with(t, &sc, s), vec![2, 3]);
+    assert_eq!(sc.nodes_shared_with(s, &tc, 0), vec![1]);
+}
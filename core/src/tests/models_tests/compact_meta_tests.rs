@@ -0,0 +1,44 @@
+use crate::models::compact_meta::CompactMeta;
+use crate::tests::fixtures::{iri, load};
+use crate::util::mmap::Words;
+use std::collections::BTreeSet;
+use std::env;
+use std::fs;
+use std::process;
+
+#[test]
+fn compact_meta_matches_meta() {
+    let f = load("a knows x\nb knows x\nc likes y\nx age n1\ny age n2");
+    let path = env::temp_dir().join(format!("teriyaki-compact-{}.bin", process::id()));
+    fs::write(&path, f.meta.serialize_compact()).unwrap();
+    let compact = CompactMeta::new(Words::open(&path).unwrap()).unwrap();
+
+    for (n, info) in f.meta.get_nodes() {
+        assert!(compact.contains(n));
+        assert_eq!(compact.get_parent(n), info.parent);
+        assert_eq!(compact.outgoing(n), info.outgoing.as_slice());
+        assert_eq!(compact.incoming(n), info.incoming.as_slice());
+    }
+    for (s, members) in f.meta.get_supernodes() {
+        let mut members = members.clone();
+        members.sort();
+        assert_eq!(compact.get_supernode(s), Some(members.as_slice()));
+    }
+    let (snode, alias) = f.supernode("a");
+    assert_eq!(compact.get_alias(&snode), Some(alias.clone()));
+    assert_eq!(compact.supernode_by_alias(&alias), Some(snode));
+    let a = f.dataset.id_of(&iri("a")).unwrap();
+    assert!(!compact.contains_supernode(&a));
+
+    let edges = |g: crate::models::graph::Graph| g.edges().collect::<BTreeSet<_>>();
+    assert_eq!(
+        edges(compact.summary_graph()),
+        edges(f.meta.summary_graph())
+    );
+
+    // Cutting the file short is caught on load.
+    let bytes = fs::read(&path).unwrap();
+    fs::write(&path, &bytes[..bytes.len() - 4]).unwrap();
+    assert!(CompactMeta::new(Words::open(&path).unwrap()).is_err());
+    fs::remove_file(&path).unwrap();
+}
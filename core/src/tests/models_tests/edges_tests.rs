@@ -0,0 +1,44 @@
+use crate::models::edges::{Edges, INLINE};
+use crate::util::heap_size::HeapSize;
+use crate::util::rng::Rng;
+
+#[test]
+fn edges_agree_with_a_vec() {
+    let mut rng = Rng::new(11);
+    let mut edges = Edges::new();
+    let mut vec: Vec<[u32; 2]> = Vec::new();
+
+    for round in 0..2_000 {
+        let edge = [rng.below(3) as u32, rng.below(5) as u32];
+        match rng.below(4) {
+            0 | 1 if round < 1_500 => {
+                edges.push(edge);
+                vec.push(edge);
+            }
+            2 if !vec.is_empty() => {
+                let i = rng.below(vec.len());
+                assert_eq!(edges.remove(i), vec.remove(i));
+            }
+            _ => {
+                edges.retain(|e| *e != edge);
+                vec.retain(|e| *e != edge);
+            }
+        }
+        assert_eq!(&*edges, vec.as_slice());
+    }
+}
+
+#[test]
+fn few_edges_stay_inline() {
+    let few: Vec<[u32; 2]> = (0..INLINE as u32).map(|i| [i, i]).collect();
+    let mut edges = Edges::from(few.clone());
+    assert!(edges.is_inline());
+    assert_eq!(edges.heap_size(), 0);
+    assert_eq!(edges, Edges::from(few.as_slice()));
+
+    edges.push([9, 9]);
+    assert!(!edges.is_inline());
+    assert!(edges.heap_size() > 0);
+    edges.retain(|e| e[0] == 9);
+    assert_eq!(edges.iter().collect::<Vec<_>>(), vec![&[9, 9]]);
+}
@@ -0,0 +1,15 @@
+use crate::models::graph::Graph;
+
+#[test]
+fn reachable_finds_a_shortest_path_within_max_hops() {
+    // 1 -> 2 -> 3 -> 4, with a shortcut 1 -> 3 and 5 unreachable from 1.
+    let graph = Graph::from_edges([[1, 10, 2], [2, 10, 3], [3, 11, 4], [1, 12, 3], [5, 10, 1]]);
+
+    assert_eq!(
+        graph.reachable(1, 4, usize::MAX),
+        Some(vec![[1, 12, 3], [3, 11, 4]])
+    );
+    assert_eq!(graph.reachable(1, 4, 1), None);
+    assert_eq!(graph.reachable(1, 5, usize::MAX), None);
+    assert_eq!(graph.reachable(2, 2, 0), Some(Vec::new()));
+}
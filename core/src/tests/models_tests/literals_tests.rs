@@ -0,0 +1,44 @@
+use crate::models::dict::Dict;
+use crate::models::literals::{self, Literals, LITERALS_FILE};
+use std::env;
+use std::fs;
+use std::process;
+
+#[test]
+fn dict_keeps_large_literals_in_side_file() {
+    let folder = env::temp_dir().join(format!("teriyaki-literals-{}", process::id()));
+    fs::create_dir_all(&folder).unwrap();
+    let long = format!("\"{}\"@en", "abstract_".repeat(20));
+    let short = String::from("\"short\"");
+    let iri = format!("<http://example.org/{}>", "x".repeat(200));
+
+    let mut dict = Dict::empty();
+    dict.keep_literals(Literals::open(&folder, Some(32)).unwrap());
+    let id = dict.add(&long);
+    dict.add(&short);
+    dict.add(&iri);
+    assert!(dict.keeps_literals());
+    assert_eq!(dict.get(&long), Some(&id));
+    assert_eq!(dict.key_by_value(&id), Some(long.clone()));
+    assert_eq!(dict.get(&format!("{}x", long)), None);
+
+    let lines = dict.to_lines(&folder.join(LITERALS_FILE)).unwrap();
+    // Nodes have odd ids, so the lines of the even ones are left empty.
+    assert_eq!(
+        lines[1..],
+        ["".to_string(), short.clone(), "".to_string(), iri.clone()]
+    );
+    assert_ne!(lines[0], long);
+    let mut decoded = lines.clone();
+    literals::decode_lines(&mut decoded, &folder).unwrap();
+    assert_eq!(decoded, dict.to_strings());
+
+    // A later run finds the literal through the side file, and can remove it.
+    let mut dict = Dict::new(&lines, &vec![]);
+    dict.keep_literals(Literals::open(&folder, None).unwrap());
+    assert_eq!(dict.get(&long), Some(&id));
+    dict.remove(&long);
+    assert!(!dict.contains(&long));
+    assert!(!dict.keeps_literals());
+    fs::remove_dir_all(&folder).unwrap();
+}
@@ -0,0 +1,305 @@
+use crate::models::meta::{Meta, NodeInfo, Removal, Signature};
+use crate::models::shared_meta::SharedMeta;
+use crate::models::triple::Triple;
+use crate::util::hash::HashMap;
+use crate::util::rng::Rng;
+use std::thread;
+
+#[test]
+fn remove_outgoing_matches_pred_and_obj() {
+    let mut n = NodeInfo::new(&None, &[], &[[1, 2], [1, 3], [4, 2]]);
+
+    assert_eq!(n.remove_outgoing(&1, &2, Removal::All), 1);
+    assert_eq!(n.outgoing, vec![[1, 3], [4, 2]]);
+}
+
+#[test]
+fn remove_incoming_matches_pred_and_sub() {
+    let mut n = NodeInfo::new(&None, &[[1, 2], [1, 3], [4, 2]], &[]);
+
+    assert_eq!(n.remove_incoming(&4, &2, Removal::All), 1);
+    assert_eq!(n.incoming, vec![[1, 2], [1, 3]]);
+}
+
+#[test]
+fn remove_once() {
+    let mut n = NodeInfo::new(&None, &[], &[[1, 2], [1, 2], [1, 3]]);
+
+    assert_eq!(n.remove_outgoing(&1, &2, Removal::Once), 1);
+    assert_eq!(n.outgoing, vec![[1, 2], [1, 3]]);
+    assert_eq!(n.remove_outgoing(&1, &2, Removal::All), 1);
+    assert_eq!(n.outgoing, vec![[1, 3]]);
+    assert_eq!(n.remove_outgoing(&1, &2, Removal::Once), 0);
+}
+
+#[test]
+fn supernode_preds_follow_members() {
+    let mut nodes = HashMap::default();
+    nodes.insert(1, NodeInfo::new(&None, &[], &[[10, 3]]));
+    nodes.insert(2, NodeInfo::new(&None, &[], &[[11, 3]]));
+    nodes.insert(3, NodeInfo::new(&None, &[[10, 1], [11, 2]], &[]));
+    let mut meta = Meta::new(HashMap::default(), nodes, Vec::new());
+
+    meta.new_snode(&vec![1, 2], &4);
+    assert!(meta.has_outgoing_pred(&4, &10));
+    assert!(meta.has_outgoing_pred(&4, &11));
+    assert!(!meta.has_incoming_pred(&4, &10));
+
+    meta.remove_from_supernode(&2);
+    assert!(meta.has_outgoing_pred(&4, &10));
+    assert!(!meta.has_outgoing_pred(&4, &11));
+
+    meta.remove_outgoing(&Triple::new(1, 10, 3, false));
+    assert!(!meta.has_outgoing_pred(&4, &10));
+}
+
+#[test]
+fn supernode_signatures_follow_merges_and_splits() {
+    let mut nodes = HashMap::default();
+    nodes.insert(1, NodeInfo::new(&None, &[[12, 5]], &[[11, 3]]));
+    nodes.insert(2, NodeInfo::new(&None, &[], &[[10, 3]]));
+    nodes.insert(3, NodeInfo::new(&None, &[[10, 2], [11, 1]], &[]));
+    nodes.insert(5, NodeInfo::new(&None, &[], &[[12, 1]]));
+    let mut meta = Meta::new(HashMap::default(), nodes, Vec::new());
+    assert_eq!(meta.get_signature(&1), None);
+
+    meta.new_snode(&vec![1, 2], &4);
+    let signature = Signature {
+        outgoing: vec![10, 11],
+        incoming: vec![12],
+    };
+    assert_eq!(meta.get_signature(&4), Some(signature));
+
+    meta.remove_from_supernode(&1);
+    let signature = Signature {
+        outgoing: vec![10],
+        incoming: vec![],
+    };
+    assert_eq!(meta.get_signature(&4), Some(signature));
+}
+
+/// Meta of the nodes 1 to `n`, where node `i` has an edge with predicate `10 + i % 3` to node
+/// `i + 1`, and the last node one back to the first.
+fn chain(n: u32) -> Meta {
+    let mut nodes = HashMap::default();
+    for i in 1..=n {
+        let next = i % n + 1;
+        let prev = (i + n - 2) % n + 1;
+        nodes.insert(
+            i,
+            NodeInfo::new(&None, &[[10 + prev % 3, prev]], &[[10 + i % 3, next]]),
+        );
+    }
+    Meta::new(HashMap::default(), nodes, Vec::new())
+}
+
+/// Asserts that parents and members agree and that supernode signatures match their members.
+fn assert_consistent(meta: &Meta) {
+    for (s, members) in meta.get_supernodes() {
+        assert!(!members.is_empty(), "supernode {} is empty", s);
+        assert!(
+            members.windows(2).all(|w| w[0] < w[1]),
+            "members of {} are not sorted and unique",
+            s
+        );
+        assert_eq!(meta.supernode_len(s), members.len());
+        for m in members {
+            assert_eq!(meta.get_parent(m), Some(*s), "member {} of {}", m, s);
+        }
+        let mut outgoing: Vec<u32> = members
+            .iter()
+            .flat_map(|m| meta.get_outgoing_preds(m))
+            .collect();
+        outgoing.sort_unstable();
+        outgoing.dedup();
+        assert_eq!(meta.get_signature(s).unwrap().outgoing, outgoing);
+    }
+    for (n, info) in meta.get_nodes() {
+        assert!(
+            !meta.contains_supernode(n),
+            "{} is a node and a supernode",
+            n
+        );
+        if let Some(p) = info.parent {
+            assert!(
+                meta.get_supernode(&p).unwrap().contains(n),
+                "{} not in {}",
+                n,
+                p
+            );
+        }
+    }
+}
+
+#[test]
+fn removing_a_member_only_clears_its_parent() {
+    let mut meta = chain(3);
+    meta.new_snode(&vec![1, 2], &4);
+    meta.remove_from_supernode(&1);
+
+    let info = meta.get_node(&1).unwrap();
+    assert_eq!(info.parent, None);
+    assert_eq!(info.incoming, vec![[10, 3]]);
+    assert_eq!(info.outgoing, vec![[11, 2]]);
+}
+
+#[test]
+fn split_members_restores_the_members_afterwards() {
+    let mut meta = chain(4);
+    meta.new_snode(&vec![1, 2, 3], &5);
+
+    let mut split = Vec::new();
+    meta.split_members(&5, vec![1, 2, 3], |meta, node| {
+        assert!(!meta.get_supernode(&5).unwrap().contains(node));
+        assert_eq!(meta.get_parent(node), Some(5));
+        split.push(*node);
+    });
+    assert_eq!(split, vec![1, 2]);
+    assert_eq!(meta.get_supernode(&5), Some(&vec![1, 2, 3]));
+    assert_consistent(&meta);
+}
+
+#[test]
+fn remove_from_supernode_tells_when_one_member_is_left() {
+    let mut meta = chain(4);
+    meta.new_snode(&vec![1, 2, 3], &5);
+
+    assert!(!meta.remove_from_supernode(&1));
+    assert_eq!(meta.get_parent(&1), None);
+    assert_eq!(meta.get_supernode(&5), Some(&vec![2, 3]));
+
+    assert!(meta.remove_from_supernode(&3));
+    assert_eq!(meta.get_supernode(&5), Some(&vec![2]));
+    assert_eq!(meta.get_parent(&2), Some(5));
+    assert_consistent(&meta);
+}
+
+#[test]
+fn members_are_kept_sorted_for_lookups() {
+    let mut meta = chain(6);
+    meta.new_snode(&vec![5, 2], &7);
+    meta.new_snode(&vec![4, 7, 1], &8);
+
+    assert_eq!(meta.get_supernode(&8), Some(&vec![1, 2, 4, 5]));
+    assert!(meta.is_member(&8, &4));
+    assert!(!meta.is_member(&8, &3));
+    assert!(!meta.is_member(&3, &3));
+
+    let mut nodes = HashMap::default();
+    for i in 1..=3 {
+        nodes.insert(i, NodeInfo::new(&Some(4), &[], &[]));
+    }
+    let mut supernodes = HashMap::default();
+    supernodes.insert(4, vec![3, 1, 2, 1]);
+    let loaded = Meta::new(supernodes, nodes, Vec::new());
+    assert_eq!(loaded.get_supernode(&4), Some(&vec![1, 2, 3]));
+}
+
+#[test]
+fn to_single_node_dissolves_a_supernode_by_id() {
+    let mut meta = chain(4);
+    meta.new_snode(&vec![2, 3], &5);
+    meta.remove_from_supernode(&3);
+
+    meta.to_single_node(&5);
+    assert!(!meta.contains_supernode(&5));
+    assert!(!meta.contains(&5));
+    assert_eq!(meta.get_parent(&2), None);
+    assert_eq!(meta.get_signature(&5), None);
+    assert_consistent(&meta);
+}
+
+#[test]
+#[should_panic(expected = "more than one node")]
+fn to_single_node_rejects_supernodes_with_more_members() {
+    let mut meta = chain(3);
+    meta.new_snode(&vec![1, 2], &4);
+    meta.to_single_node(&4);
+}
+
+#[test]
+#[should_panic(expected = "non-supernode")]
+fn to_single_node_rejects_nodes() {
+    let mut meta = chain(3);
+    meta.to_single_node(&1);
+}
+
+#[test]
+fn supernode_invariants_hold_after_random_operations() {
+    for seed in 0..20 {
+        let mut rng = Rng::new(seed);
+        let mut meta = chain(12);
+        let mut next_id = 100;
+        for _ in 0..200 {
+            let mut top: Vec<u32> = meta.top_level_nodes();
+            top.sort_unstable();
+            let mut members: Vec<u32> = meta.get_supernodes().values().flatten().copied().collect();
+            members.sort_unstable();
+            if top.len() >= 2 && (members.is_empty() || rng.below(2) == 0) {
+                let a = top.swap_remove(rng.below(top.len()));
+                let b = top.swap_remove(rng.below(top.len()));
+                meta.new_snode(&vec![a, b], &next_id);
+                next_id += 1;
+            } else if !members.is_empty() {
+                let node = members[rng.below(members.len())];
+                let parent = meta.get_parent(&node).unwrap();
+                if meta.remove_from_supernode(&node) {
+                    meta.to_single_node(&parent);
+                }
+            }
+            assert_consistent(&meta);
+        }
+    }
+}
+
+#[test]
+fn contains_edge_follows_added_and_removed_edges() {
+    let mut meta = Meta::new(HashMap::default(), HashMap::default(), Vec::new());
+    let triple = Triple::new(1, 10, 2, false);
+    meta.new_node(&triple, true);
+    meta.new_node(&triple, false);
+    assert!(meta.contains_edge(&1, &10, &2));
+    assert!(!meta.contains_edge(&2, &10, &1));
+
+    // Enough edges to rebuild the filter larger on the way.
+    for o in 3..5000 {
+        let triple = Triple::new(1, 11, o, false);
+        meta.add_outgoing(&triple);
+    }
+    assert!((3..5000).all(|o| meta.contains_edge(&1, &11, &o)));
+    assert!(meta.contains_edge(&1, &10, &2));
+
+    meta.remove_outgoing(&triple);
+    assert!(!meta.contains_edge(&1, &10, &2));
+    assert_eq!(meta.edge_multiplicity(&triple), 0);
+}
+
+#[test]
+fn shared_meta_readers_see_writes() {
+    let shared = SharedMeta::new(Meta::new(
+        HashMap::default(),
+        HashMap::default(),
+        Vec::new(),
+    ));
+
+    let writer = {
+        let shared = shared.clone();
+        thread::spawn(move || {
+            for class in 0..100 {
+                shared.write().add_type(&1, &class);
+            }
+        })
+    };
+    let readers: Vec<_> = (0..4)
+        .map(|_| {
+            let shared = shared.clone();
+            thread::spawn(move || shared.read().get_types().len() <= 100)
+        })
+        .collect();
+
+    writer.join().unwrap();
+    for r in readers {
+        assert!(r.join().unwrap());
+    }
+    assert_eq!(shared.into_inner().unwrap().get_types().len(), 100);
+}
@@ -0,0 +1,38 @@
+use crate::models::node_set::Bitmap;
+use crate::util::heap_size::HeapSize;
+use crate::util::rng::Rng;
+use std::collections::BTreeSet;
+
+#[test]
+fn bitmap_agrees_with_a_sorted_set() {
+    let mut rng = Rng::new(7);
+    let mut bitmap = Bitmap::new();
+    let mut set: BTreeSet<u32> = BTreeSet::new();
+
+    // Ids crowded into the first two containers, so one turns dense and back, and a few far off.
+    for round in 0..30_000 {
+        let id = match rng.below(10) {
+            0 => rng.next_u64() as u32,
+            _ => rng.below(100_000) as u32,
+        };
+        let added = round < 20_000;
+        if added {
+            assert_eq!(bitmap.insert(id), set.insert(id), "{}", id);
+        } else {
+            assert_eq!(bitmap.remove(id), set.remove(&id), "{}", id);
+        }
+        assert_eq!(bitmap.len(), set.len());
+    }
+    assert!(bitmap.iter().eq(set.iter().copied()));
+    assert!(set.iter().all(|id| bitmap.contains(*id)));
+    assert!(!bitmap.contains(u32::MAX) || set.contains(&u32::MAX));
+}
+
+#[test]
+fn dense_ids_take_about_a_bit_each() {
+    let bitmap: Bitmap = (0..1_000_000).collect();
+    assert_eq!(bitmap.len(), 1_000_000);
+    // The last container is only partly used.
+    assert!(bitmap.heap_size() < 1_000_000 / 8 * 11 / 10);
+    assert!(bitmap.iter().eq(0..1_000_000));
+}
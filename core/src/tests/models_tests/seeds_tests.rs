@@ -0,0 +1,31 @@
+use crate::models::seeds::Seeds;
+
+fn lines(s: &str) -> Vec<String> {
+    s.lines().map(|l| l.trim().to_string()).collect()
+}
+
+#[test]
+fn seeds_number_groups_by_name() {
+    let seeds = Seeds::parse(&lines(
+        "# people
+         <http://x/alice> people
+         <http://x/bob> people
+         _:c places",
+    ))
+    .unwrap();
+
+    assert_eq!(
+        seeds.group("<http://x/alice>"),
+        seeds.group("<http://x/bob>")
+    );
+    assert!(seeds.group("_:c").is_some());
+    assert_ne!(seeds.group("_:c"), seeds.group("<http://x/bob>"));
+    assert_eq!(seeds.group("<http://x/carol>"), None);
+}
+
+#[test]
+fn seeds_parse_rejects_nodes_in_two_groups() {
+    let err = Seeds::parse(&lines("<http://x/a> one\n<http://x/a> two")).err();
+    assert!(err.unwrap().starts_with("line 2:"));
+    assert!(Seeds::parse(&lines("<http://x/a>")).is_err());
+}
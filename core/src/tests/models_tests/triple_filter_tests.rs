@@ -0,0 +1,30 @@
+use crate::models::triple_filter::TripleFilter;
+
+#[test]
+fn filter_keeps_inserted_triples_until_removed() {
+    let mut filter = TripleFilter::with_capacity(2);
+    filter.insert([1, 2, 3]);
+    filter.insert([1, 2, 3]);
+    assert!(filter.may_contain([1, 2, 3]));
+
+    filter.remove([1, 2, 3]);
+    assert!(filter.may_contain([1, 2, 3]));
+    filter.remove([1, 2, 3]);
+    assert!(!filter.may_contain([1, 2, 3]));
+    assert!(filter.is_empty());
+}
+
+#[test]
+fn filter_rejects_most_missing_triples() {
+    let mut filter = TripleFilter::with_capacity(1000);
+    for i in 0..1000 {
+        filter.insert([i, i % 7, i + 1]);
+    }
+    assert!(!filter.is_full());
+    assert!((0..1000).all(|i| filter.may_contain([i, i % 7, i + 1])));
+
+    let false_positives = (0..1000)
+        .filter(|i| filter.may_contain([i + 1, i % 7, *i]))
+        .count();
+    assert!(false_positives < 50, "{} false positives", false_positives);
+}
@@ -0,0 +1,246 @@
+use crate::models::dict::{Dict, MAX_ID};
+use crate::models::id_allocator::IdAllocator;
+use crate::models::meta::Meta;
+use crate::models::triple::{Triple, TripleCollection, TYPE_STRING};
+use crate::tests::fixtures::{iri, load, Fixture};
+use crate::util::hash::HashMap;
+use crate::{DuplicatePolicy, Grouping};
+
+fn lines() -> Vec<String> {
+    vec![
+        "<a> <knows> <b> .".to_string(),
+        format!("<a> {} <Person> .", TYPE_STRING),
+    ]
+}
+
+#[test]
+fn types_are_kept_aside_when_grouping_by_preds() {
+    let mut meta = Meta::new(HashMap::default(), HashMap::default(), Vec::new());
+    let c = TripleCollection::new(
+        lines(),
+        &mut Dict::empty(),
+        &mut meta,
+        true,
+        &Grouping::Preds,
+    );
+
+    assert_eq!(c.data_triples.len(), 1);
+    assert_eq!(meta.get_types().len(), 1);
+}
+
+#[test]
+fn types_are_dropped_when_untyped() {
+    let mut meta = Meta::new(HashMap::default(), HashMap::default(), Vec::new());
+    let c = TripleCollection::new(
+        lines(),
+        &mut Dict::empty(),
+        &mut meta,
+        true,
+        &Grouping::Untyped,
+    );
+
+    assert_eq!(c.data_triples.len(), 1);
+    assert!(meta.get_types().is_empty());
+}
+
+#[test]
+fn types_are_predicates_when_included() {
+    let mut meta = Meta::new(HashMap::default(), HashMap::default(), Vec::new());
+    let c = TripleCollection::new(
+        lines(),
+        &mut Dict::empty(),
+        &mut meta,
+        true,
+        &Grouping::Both,
+    );
+
+    assert_eq!(c.data_triples.len(), 2);
+    assert!(c.data_triples[1].is_type);
+    assert!(meta.get_types().is_empty());
+}
+
+#[test]
+fn preds_have_their_own_ids() {
+    let mut dict = Dict::empty();
+    let t = Triple::from_string(&"<a> <knows> <knows> .".to_string(), &mut dict);
+
+    assert_eq!((t.sub, t.pred, t.obj), (1, 1, 3));
+    assert_eq!(dict.pred_term(&1), Some(&"<knows>".to_string()));
+    assert_eq!(dict.get(&"<knows>".to_string()), Some(&3));
+    assert_eq!(dict.pred_term(&Triple::type_pred(1)), None);
+}
+
+#[test]
+fn decoded_terms_are_cached_until_their_id_is_reused() {
+    let mut dict = Dict::empty();
+    let (a, b) = ("<a>".to_string(), "<b>".to_string());
+    let id = dict.add(&a);
+
+    assert_eq!(dict.term(&id).as_deref(), Some("<a>"));
+    assert_eq!(dict.key_by_value(&id), Some(a.clone()));
+    assert_eq!(dict.term(&99), None);
+    let stats = dict.term_cache_stats();
+    assert_eq!((stats.hits, stats.misses), (1, 2));
+
+    // The freed id goes to the next term, which must not be decoded as the old one.
+    dict.remove(&a);
+    assert_eq!(dict.add(&b), id);
+    assert_eq!(dict.term(&id).as_deref(), Some("<b>"));
+
+    dict.set_term_cache(0);
+    dict.term(&id);
+    dict.term(&id);
+    assert_eq!(dict.term_cache_stats().hits, 0);
+}
+
+#[test]
+fn nodes_and_supernodes_take_ids_from_their_own_ranges() {
+    let mut dict = Dict::empty();
+    let term = |t: &str| t.to_string();
+    assert_eq!(dict.add(&term("<a>")), 1);
+    assert_eq!(dict.add_snode("<s>"), 2);
+    assert_eq!(dict.add(&term("<b>")), 3);
+
+    // Freed ids only go to terms of their own kind.
+    dict.remove(&term("<s>"));
+    dict.remove(&term("<a>"));
+    assert_eq!(dict.add(&term("<c>")), 1);
+    assert_eq!(dict.add(&term("<d>")), 5);
+    assert_eq!(dict.add_snode("<t>"), 2);
+    assert_eq!(dict.add_snode("<u>"), 4);
+
+    // The ranges are kept by the lines of the dict file alone.
+    dict.remove(&term("<c>"));
+    dict.remove(&term("<t>"));
+    let mut dict = Dict::new(&dict.to_strings(), &vec![]);
+    assert_eq!(dict.add_snode("<v>"), 2);
+    assert_eq!(dict.add_snode("<w>"), 6);
+    assert_eq!(dict.add(&term("<e>")), 1);
+    assert_eq!(dict.add(&term("<f>")), 7);
+}
+
+#[test]
+fn exhausted_ids_are_reported_instead_of_wrapping() {
+    // MAX_ID is odd, so the last supernode id is one below it.
+    let mut dict = Dict::with_ids(IdAllocator::starting_at([MAX_ID, MAX_ID - 1]));
+    let term = |t: &str| t.to_string();
+    assert_eq!(dict.add(&term("<a>")), MAX_ID);
+    assert_eq!(dict.add_snode("<s>"), MAX_ID - 1);
+    assert_eq!(dict.max_id(), MAX_ID);
+    assert!(dict.check_ids().is_ok());
+
+    assert_eq!(dict.add(&term("<b>")), 0);
+    assert_eq!(dict.add_snode("<t>"), 0);
+    let err = dict.check_ids().unwrap_err();
+    assert!(
+        err.to_string().contains("2 terms did not get an id"),
+        "{}",
+        err
+    );
+
+    // Freed ids are still handed out once the new ones are gone.
+    dict.remove(&term("<a>"));
+    assert_eq!(dict.add(&term("<c>")), MAX_ID);
+}
+
+#[test]
+fn triples_are_decoded_in_bulk_as_one_by_one() {
+    let f = load(
+        "a knows x
+         b knows x
+         a rdf:type C
+         c <http://ex.org/\"quoted\"> <http://ex.org/a\\u0062>",
+    );
+    let triples = &f.dataset.triples.data_triples;
+
+    let mut lines = Vec::new();
+    f.dataset
+        .decode_triples(triples, |line| {
+            lines.push(line.to_string());
+            Ok(())
+        })
+        .unwrap();
+    let expected: Vec<String> = triples.iter().map(|t| t.to_string(&f.dataset)).collect();
+    assert_eq!(lines, expected);
+    assert_eq!(lines.len(), 3);
+}
+
+#[test]
+fn indexed_collection_tracks_neighbors() {
+    let mut meta = Meta::new(HashMap::default(), HashMap::default(), Vec::new());
+    let mut c = TripleCollection::new(
+        Vec::new(),
+        &mut Dict::empty(),
+        &mut meta,
+        true,
+        &Grouping::Both,
+    );
+    let (ab, ac, cb) = (
+        Triple::new(1, 1, 2, false),
+        Triple::new(1, 1, 3, false),
+        Triple::new(3, 1, 2, false),
+    );
+    c.add_data_triple(&ab);
+    c.add_data_triple(&ac);
+    c.build_index();
+    c.add_data_triple(&cb);
+    c.add_data_triple(&ab);
+
+    assert_eq!(c.data_triples.len(), 3);
+    assert!(c.neighbors_out(&1) == vec![ab.clone(), ac.clone()]);
+    assert!(c.neighbors_in(&2) == vec![ab.clone(), cb.clone()]);
+
+    let ad = Triple::new(1, 1, 4, false);
+    c.replace_triple(&ac, &ad);
+    c.remove_triple(&ab);
+
+    assert!(c.neighbors_out(&1) == vec![ad.clone()]);
+    assert!(c.neighbors_in(&2) == vec![cb.clone()]);
+    assert!(!c.contains(&ac));
+    assert_eq!(c.data_triples.len(), 2);
+}
+
+/// Loads a summary of two triples, then reads its triples again with the first one three times.
+fn repeated_triples() -> Fixture {
+    let mut f = load("a knows b\nc likes d");
+    let ts = f.dataset.triples.data_triples.clone();
+    let repeated = vec![ts[0].clone(), ts[1].clone(), ts[0].clone(), ts[0].clone()];
+    f.dataset.triples = TripleCollection::from_triples(repeated);
+    f
+}
+
+#[test]
+fn count_policy_keeps_the_occurrences_of_repeated_triples() {
+    let mut f = repeated_triples();
+    f.dataset
+        .apply_duplicate_policy(&DuplicatePolicy::Count)
+        .unwrap();
+    let triples = &f.dataset.triples;
+    assert_eq!((triples.data_triples.len(), triples.duplicates), (2, 2));
+    assert_eq!(triples.occurrences().count(), 4);
+
+    let first = triples.data_triples[0].clone();
+    f.dataset.triples.remove_triple(&first);
+    assert_eq!(f.dataset.triples.occurrences().count(), 1);
+}
+
+#[test]
+fn dedupe_policy_drops_repeated_triples() {
+    let mut f = repeated_triples();
+    f.dataset
+        .apply_duplicate_policy(&DuplicatePolicy::Dedupe)
+        .unwrap();
+    assert_eq!(f.dataset.triples.duplicates, 2);
+    assert_eq!(f.dataset.triples.occurrences().count(), 2);
+}
+
+#[test]
+fn error_policy_names_the_repeated_triple() {
+    let mut f = repeated_triples();
+    let err = f
+        .dataset
+        .apply_duplicate_policy(&DuplicatePolicy::Error)
+        .unwrap_err();
+    let line = format!("{} {} {} .", iri("a"), iri("knows"), iri("b"));
+    assert_eq!(err.to_string(), format!("duplicate triple {}", line));
+}
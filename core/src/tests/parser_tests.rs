@@ -0,0 +1,18 @@
+#[cfg(test)]
+mod clique_tests;
+#[cfg(test)]
+mod dataset_tests;
+#[cfg(test)]
+mod encoded_tests;
+#[cfg(all(test, feature = "endpoint"))]
+mod endpoint_tests;
+#[cfg(test)]
+mod exclusion_tests;
+#[cfg(test)]
+mod inverse_tests;
+#[cfg(test)]
+mod meta_tests;
+#[cfg(test)]
+mod n3_tests;
+#[cfg(test)]
+mod schema_tests;
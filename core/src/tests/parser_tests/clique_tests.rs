@@ -0,0 +1,103 @@
+use crate::models::clique::{Clique, CliqueCollection};
+use crate::models::node_set::NodeSet;
+use crate::models::triple::Triple;
+use crate::parser::clique::{create_cliques, create_cliques_grouped};
+use crate::util::rng::Rng;
+
+#[cfg(test)]
+#[test]
+fn new() {
+    let c1 = Clique {
+        preds: vec![1, 2, 3],
+        nodes: NodeSet::List(vec![4, 5, 6]),
+    };
+    let c2 = Clique::new(&vec![1, 2, 3], &vec![4, 5, 6]);
+
+    assert_eq!(c1.preds, c2.preds);
+    assert_eq!(c1.nodes, c2.nodes);
+}
+
+#[test]
+fn merge() {
+    let mut c1 = Clique::new(&vec![1, 2, 3], &vec![7, 8, 9]);
+    let c2 = Clique::new(&vec![4, 5, 6], &vec![10, 11, 12]);
+
+    c1.merge(&c2);
+    c1.preds.sort();
+    let mut nodes = c1.nodes.to_vec();
+    nodes.sort();
+
+    assert_eq!(c1.preds, vec![1, 2, 3, 4, 5, 6]);
+    assert_eq!(nodes, vec![7, 8, 9, 10, 11, 12]);
+}
+
+fn contents(cc: &CliqueCollection) -> Vec<(Vec<u32>, Vec<u32>)> {
+    cc.cliques()
+        .iter()
+        .map(|c| (c.preds.clone(), c.nodes.to_vec()))
+        .collect()
+}
+
+#[test]
+#[cfg(feature = "json")]
+fn saved_cliques_are_only_read_for_their_summary() {
+    use crate::parser::clique::{fingerprint, read_cliques, to_file, CLIQUES_FILE};
+    use crate::tests::fixtures::load;
+    use crate::Grouping;
+    use std::{env, fs, process};
+
+    let mut f = load("a knows x\nb knows x\nb age y\nc likes y");
+    f.update("-b age y");
+    let folder = env::temp_dir().join(format!("teriyaki-cliques-{}", process::id()));
+    fs::create_dir_all(&folder).unwrap();
+    let lines = f.summary();
+    let print = fingerprint(&lines, &Grouping::Preds);
+    let json = serde_json::to_string(&to_file(&f.sc, &f.tc, print)).unwrap();
+    fs::write(folder.join(CLIQUES_FILE), json).unwrap();
+
+    let (sc, tc) = read_cliques(&folder, print).unwrap();
+    let stale = read_cliques(&folder, fingerprint(&lines[1..], &Grouping::Preds));
+    fs::remove_dir_all(&folder).unwrap();
+
+    assert!(stale.is_none());
+    for (read, saved) in [(&sc, &f.sc), (&tc, &f.tc)] {
+        assert_eq!(read.queued(), saved.queued());
+        assert_eq!(read.node_indices(), saved.node_indices());
+        assert_eq!(read.pred_indices(), saved.pred_indices());
+        assert_eq!(contents(read), contents(saved));
+    }
+    let mut reordered = lines.clone();
+    reordered.reverse();
+    assert_eq!(fingerprint(&reordered, &Grouping::Preds), print);
+}
+
+#[test]
+fn grouped_cliques_match_those_built_triple_by_triple() {
+    let mut rng = Rng::new(692);
+    let mut triples: Vec<Triple> = (0..400)
+        .map(|_| {
+            let is_type = rng.below(10) == 0;
+            let obj = 1 + rng.below(60) as u32;
+            let pred = match is_type {
+                true => Triple::type_pred(obj),
+                false => 1 + rng.below(12) as u32,
+            };
+            Triple::new(1 + rng.below(40) as u32, pred, obj, is_type)
+        })
+        .collect();
+
+    // Once in random order, with many short runs per subject, and once sorted by subject.
+    for sort in [false, true] {
+        if sort {
+            triples.sort_by_key(|t| t.sub);
+        }
+        let (sc, tc) = create_cliques(&triples);
+        let (grouped_sc, grouped_tc) = create_cliques_grouped(&triples);
+        for (grouped, built) in [(&grouped_sc, &sc), (&grouped_tc, &tc)] {
+            assert_eq!(grouped.queued(), built.queued());
+            assert_eq!(grouped.node_indices(), built.node_indices());
+            assert_eq!(grouped.pred_indices(), built.pred_indices());
+            assert_eq!(contents(grouped), contents(built));
+        }
+    }
+}
@@ -0,0 +1,171 @@
+use crate::models::meta::{Meta, NodeInfo};
+use crate::parser::dataset::{
+    apply_to_passthrough, legacy_preds, read_preds, reject_empty_lines, sample, skip_malformed,
+};
+use crate::util::hash::HashMap;
+
+fn lines(ls: &[&str]) -> Vec<String> {
+    ls.iter().map(|l| l.to_string()).collect()
+}
+
+#[test]
+fn malformed_lines_are_skipped_with_reasons() {
+    let mut t_l = lines(&["<a> <p> <b> .", "garbage", "<a> <q> <c> ."]);
+    let mut u_l = lines(&["-<a> <p> <b> .", "", "-\"x\" <p> <b> ."]);
+    let (mut errors, mut skipped) = (0, Vec::new());
+
+    skip_malformed(&mut t_l, "data.nt", false, 3, &mut errors, &mut skipped).unwrap();
+    skip_malformed(&mut u_l, "update.nt", true, 3, &mut errors, &mut skipped).unwrap();
+
+    assert_eq!(t_l, lines(&["<a> <p> <b> .", "<a> <q> <c> ."]));
+    assert_eq!(u_l, lines(&["-<a> <p> <b> ."]));
+    assert_eq!(errors, 3);
+    assert_eq!(
+        skipped,
+        lines(&[
+            "# data.nt:2: expected `<subject> <predicate> <object> .`",
+            "garbage",
+            "# update.nt:2: empty line",
+            "",
+            "# update.nt:3: subject must be an IRI or blank node",
+            "-\"x\" <p> <b> .",
+        ])
+    );
+}
+
+#[test]
+fn too_many_malformed_lines_abort() {
+    let mut t_l = lines(&["garbage", "<a> <p> <b> .", "<a> <p>"]);
+    let (mut errors, mut skipped) = (0, Vec::new());
+
+    let err = skip_malformed(&mut t_l, "data.nt", false, 1, &mut errors, &mut skipped).unwrap_err();
+
+    assert!(err.to_string().starts_with("data.nt:3: "));
+    assert_eq!(errors, 2);
+}
+
+#[test]
+fn empty_update_lines_are_errors_without_max_errors() {
+    let u_l = lines(&["<a> <p> <b> .", "-<a> <q> <c> .", "  ", ""]);
+
+    let err = reject_empty_lines(&u_l, "update.nt").unwrap_err();
+
+    assert!(
+        err.to_string().starts_with("update.nt:3: empty line"),
+        "{}",
+        err
+    );
+    assert!(reject_empty_lines(&u_l[..2], "update.nt").is_ok());
+}
+
+#[test]
+fn preds_fall_back_to_the_dict_of_legacy_meta_folders() {
+    use std::{env, fs, process};
+
+    let folder = env::temp_dir().join(format!("teriyaki-read-preds-{}", process::id()));
+    fs::create_dir_all(&folder).unwrap();
+    let dict_l = lines(&["<a>", "<knows>", "<b>"]);
+
+    let legacy = read_preds(&folder, &dict_l).unwrap();
+    fs::write(folder.join("preds"), "<knows>\n").unwrap();
+    let preds = read_preds(&folder, &dict_l).unwrap();
+    fs::remove_dir_all(&folder).unwrap();
+
+    assert_eq!(legacy, dict_l);
+    assert_eq!(preds, lines(&["<knows>"]));
+}
+
+#[test]
+fn legacy_edges_are_renumbered_to_pred_ids() {
+    // Dict ids: a 1, knows 2, b 3. The type pseudo-predicate of class b is left alone.
+    let dict_l = lines(&["<a>", "<knows>", "<b>"]);
+    let type_pred = u32::MAX - 3;
+    let nodes = HashMap::from_iter([
+        (1, NodeInfo::new(&None, &[], &[[2, 3], [type_pred, 3]])),
+        (3, NodeInfo::new(&None, &[[2, 1], [type_pred, 1]], &[])),
+    ]);
+    let mut meta = Meta::new(HashMap::default(), nodes, Vec::new());
+
+    assert_eq!(legacy_preds(&mut meta, &dict_l), lines(&["<knows>"]));
+    assert_eq!(
+        meta.get_node(&1).unwrap().outgoing,
+        vec![[1, 3], [type_pred, 3]]
+    );
+    assert_eq!(
+        meta.get_node(&3).unwrap().incoming,
+        vec![[1, 1], [type_pred, 1]]
+    );
+}
+
+#[test]
+fn sample_is_reproducible_and_keeps_deletions_with_their_triples() {
+    let triples: Vec<String> = (0..1000).map(|i| format!("<s{}> <p> <o> .", i)).collect();
+    let mut a = triples.clone();
+    let mut b = triples.clone();
+    let mut deletions: Vec<String> = triples.iter().map(|t| format!("-{}", t)).collect();
+
+    sample(&mut a, 0.1, 42);
+    sample(&mut b, 0.1, 42);
+    sample(&mut deletions, 0.1, 42);
+
+    assert_eq!(a, b);
+    assert!(a.len() > 50 && a.len() < 150);
+    assert_eq!(
+        deletions,
+        a.iter().map(|t| format!("-{}", t)).collect::<Vec<_>>()
+    );
+    sample(&mut b, 0.1, 43);
+    assert_ne!(a, b);
+}
+
+#[test]
+fn passthrough_lines_take_changes_once() {
+    let mut passthrough = lines(&["<a> <p> <b> .", "<c> <p> <d> ."]);
+    let insertions = lines(&[
+        "<e> <p> <f> .",
+        "<a> <p> <b> .",
+        "<e> <p> <f> .",
+        "<g> <p> <h> .",
+    ]);
+    let deletions = lines(&["<c> <p> <d> .", "<g> <p> <h> ."]);
+
+    apply_to_passthrough(&mut passthrough, insertions, &deletions);
+    assert_eq!(passthrough, lines(&["<a> <p> <b> .", "<e> <p> <f> ."]));
+}
+
+#[test]
+fn logged_changes_are_replayed_in_place_of_an_update() {
+    use crate::util::wal::WAL_FILE;
+    use crate::{parser, Config};
+    use std::io::ErrorKind;
+    use std::{env, fs, process};
+
+    let folder = env::temp_dir().join(format!("teriyaki-wal-replay-{}", process::id()));
+    fs::create_dir_all(folder.join("meta")).unwrap();
+    fs::write(folder.join("empty.nt"), "").unwrap();
+    fs::write(folder.join("update.nt"), "<c> <knows> <d> .\n").unwrap();
+    // A session that crashed after logging two changes, and while logging a third.
+    fs::write(
+        folder.join("meta").join(WAL_FILE),
+        "<a> <knows> <b> .\n-<a> <knows> <b> .\n<a> <likes>",
+    )
+    .unwrap();
+    let config = |update: &str| {
+        Config::builder()
+            .dataset(folder.join("empty.nt"))
+            .update(folder.join(update))
+            .meta_folder(folder.join("meta"))
+            .fast(true)
+            .build()
+            .unwrap()
+    };
+
+    let refused = parser::run(&config("update.nt")).map(|_| ());
+    let replayed = parser::run(&config("empty.nt"));
+    fs::remove_dir_all(&folder).unwrap();
+
+    assert_eq!(refused.unwrap_err().kind(), ErrorKind::InvalidInput);
+    let (dataset, ..) = replayed.unwrap();
+    assert_eq!(dataset.insertions.data_triples.len(), 1);
+    assert_eq!(dataset.deletions.data_triples.len(), 1);
+}
@@ -0,0 +1,61 @@
+use crate::models::triple::Triple;
+use crate::parser::encoded::{self, Key};
+use crate::util::io;
+use crate::Grouping;
+use std::env;
+use std::fs;
+use std::process;
+
+#[test]
+fn encoded_triples_are_read_back_only_for_their_key() {
+    let folder = env::temp_dir().join(format!("teriyaki-encoded-{}", process::id()));
+    fs::create_dir_all(&folder).unwrap();
+    let summary = folder.join("summary.nt");
+    let lines = vec![
+        "<http://x/a> <http://x/knows> <http://x/b> .".to_string(),
+        "<http://x/a> <http://x/knows> <http://x/c> <http://x/g> .".to_string(),
+    ];
+    io::write_lines(&summary, &lines).unwrap();
+    let key = Key {
+        dataset: encoded::hash_lines(&lines),
+        dict: encoded::hash_dict(&lines, &[], &[]),
+        grouping: Grouping::Both,
+    };
+    // The hash of the lines is that of the file they are written to.
+    assert_eq!(key.dataset, encoded::hash_file(&summary).unwrap());
+
+    let triples = vec![
+        Triple::new(1, 2, 3, false),
+        Triple {
+            source: 5,
+            ..Triple::new(1, u32::MAX - 4, 4, true)
+        },
+    ];
+    let path = folder.join(encoded::TRIPLES_FILE);
+    fs::write(&path, encoded::serialize(&triples, &key, 42)).unwrap();
+    let (read, fingerprint) = encoded::read_encoded(&path, &key).unwrap();
+    assert_eq!(fingerprint, 42);
+    assert_eq!(read.len(), 2);
+    assert!(read
+        .iter()
+        .zip(&triples)
+        .all(|(a, b)| a == b && a.is_type == b.is_type && a.source == b.source));
+
+    for stale in [
+        Key {
+            dataset: key.dataset + 1,
+            ..key
+        },
+        Key {
+            dict: encoded::hash_dict(&lines[1..], &[], &[]),
+            ..key
+        },
+        Key {
+            grouping: Grouping::Preds,
+            ..key
+        },
+    ] {
+        assert!(encoded::read_encoded(&path, &stale).is_none());
+    }
+    fs::remove_dir_all(&folder).unwrap();
+}
@@ -0,0 +1,78 @@
+use crate::parser::endpoint::read_lines;
+use std::io::{BufRead, BufReader, Write};
+use std::net::TcpListener;
+use std::thread;
+use std::time::Duration;
+
+/// Serves `pages` as the N-Triples bodies of the requests to a new local endpoint, one per
+/// request, and returns its url.
+fn serve(pages: Vec<&'static str>) -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}/sparql", listener.local_addr().unwrap());
+    thread::spawn(move || {
+        for page in pages {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(&stream);
+            let mut line = String::new();
+            while reader.read_line(&mut line).unwrap() > 2 {
+                line.clear();
+            }
+            write!(stream, "HTTP/1.1 200 OK\r\n\r\n{}", page).unwrap();
+        }
+    });
+    url
+}
+
+#[test]
+fn pages_are_read_until_an_empty_one() {
+    let url = serve(vec![
+        "<a> <p> <b> .\n<b> <p> <c> .\n",
+        "<c> <p> <d> .\n",
+        "",
+    ]);
+    let lines = read_lines(&url, 2, Duration::from_secs(5)).unwrap();
+    assert_eq!(lines, ["<a> <p> <b> .", "<b> <p> <c> .", "<c> <p> <d> ."]);
+}
+
+#[test]
+fn blank_nodes_are_only_read_within_one_page() {
+    let url = serve(vec!["_:b0 <p> <a> .\n<a> <p> _:b1 .\n", ""]);
+    assert_eq!(
+        read_lines(&url, 10, Duration::from_secs(5)).unwrap().len(),
+        2
+    );
+
+    // The second page may label the same blank node differently.
+    let url = serve(vec!["_:b0 <p> <a> .\n", "_:b0 <q> <a> .\n", ""]);
+    let e = read_lines(&url, 1, Duration::from_secs(5)).unwrap_err();
+    assert!(e.to_string().contains("blank nodes"), "{}", e);
+}
+
+#[test]
+fn pushed_batches_keep_blank_nodes_together() {
+    use crate::writer::endpoint::batches;
+
+    let lines: Vec<String> = [
+        "_:a <p> <x> .",
+        "<y> <p> <z> .",
+        "<x> <p> _:b .",
+        "_:b <q> _:a .",
+        "<z> <p> <y> .",
+        "<w> <p> <v> .",
+    ]
+    .iter()
+    .map(|l| l.to_string())
+    .collect();
+
+    // `_:a` and `_:b` link the first, third and fourth lines, which stay together even though
+    // they are more than a batch.
+    assert_eq!(
+        batches(&lines, 2),
+        vec![
+            vec!["_:a <p> <x> .", "<x> <p> _:b .", "_:b <q> _:a ."],
+            vec!["<y> <p> <z> .", "<z> <p> <y> ."],
+            vec!["<w> <p> <v> ."],
+        ]
+    );
+    assert_eq!(batches(&lines, 10).len(), 1);
+}
@@ -0,0 +1,22 @@
+use crate::parser::exclusion::find_exclusions;
+
+#[test]
+fn predicates_linking_distinct_groups_are_excluded() {
+    let mut lines: Vec<String> = Vec::new();
+    for i in 0..200 {
+        // `<links>` joins a subject and an object that each have a clique of their own, so no
+        // two of its triples end up as the same summary edge.
+        lines.push(format!("<s{}> <links> <o{}> .", i, i));
+        lines.push(format!("<a{}> <in{}> <s{}> .", i, i, i));
+        lines.push(format!("<o{}> <out{}> <b{}> .", i, i, i));
+        // `<name>` links nodes of one group, and compresses to a single edge.
+        lines.push(format!("<x{}> <name> <y{}> .", i, i));
+    }
+
+    let exclusions = find_exclusions(lines);
+
+    assert_eq!(exclusions.len(), 1);
+    assert_eq!(exclusions[0].pred, "<links>");
+    assert_eq!(exclusions[0].triples, 200);
+    assert_eq!(exclusions[0].summary_edges, 200);
+}
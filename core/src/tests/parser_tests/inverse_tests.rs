@@ -0,0 +1,55 @@
+use crate::parser::inverse::InversePreds;
+use crate::tests::fixtures::{iri, load};
+
+fn line(s: &str, p: &str, o: &str) -> String {
+    format!("{} {} {} .", iri(s), iri(p), iri(o))
+}
+
+#[test]
+fn inverse_triples_are_turned_around() {
+    let inverses = InversePreds::parse(Some(&"owl".to_string())).unwrap();
+    let declaration = format!(
+        "{} <http://www.w3.org/2002/07/owl#inverseOf> {} .",
+        iri("hasPart"),
+        iri("partOf")
+    );
+    let mut insertions = vec![
+        declaration.clone(),
+        line("a", "hasPart", "x"),
+        line("y", "partOf", "b"),
+        format!("{} {} \"1\" .", iri("c"), iri("partOf")),
+    ];
+    let mut deletions = vec![line("z", "partOf", "a")];
+
+    let count = inverses.rewrite(&mut insertions, &mut deletions, &mut []);
+
+    assert_eq!(count, 2);
+    assert_eq!(insertions[2], line("b", "hasPart", "y"));
+    // Literals cannot be subjects.
+    assert!(insertions[3].contains("partOf"));
+    assert_eq!(deletions[0], line("a", "hasPart", "z"));
+
+    // Nodes modeled both ways now share their signature.
+    let graph: Vec<String> = insertions[1..3]
+        .iter()
+        .map(|l| l.trim_end_matches(" .").to_string())
+        .collect();
+    let f = load(&graph.join("\n"));
+    assert_eq!(f.supernode("a"), f.supernode("b"));
+}
+
+#[test]
+fn inverse_preds_parse_pairs_and_owl() {
+    let arg = "http://x/hasPart=<http://x/partOf>,owl".to_string();
+    let inverses = InversePreds::parse(Some(&arg)).unwrap();
+    assert!(inverses.owl);
+    assert_eq!(
+        inverses.pairs,
+        vec![(
+            "<http://x/hasPart>".to_string(),
+            "<http://x/partOf>".to_string()
+        )]
+    );
+    assert!(InversePreds::parse(Some(&"hasPart".to_string())).is_err());
+    assert!(InversePreds::parse(None).is_err());
+}
@@ -0,0 +1,121 @@
+#[cfg(feature = "json")]
+use crate::parser::meta::parse_parallel;
+#[cfg(any(feature = "json", feature = "sqlite"))]
+use crate::parser::meta::{to_file, MetaFile};
+#[cfg(any(feature = "json", feature = "sqlite"))]
+use crate::tests::fixtures::load;
+
+#[cfg(any(feature = "json", feature = "sqlite"))]
+fn meta_json() -> String {
+    let f = load(
+        "a knows b
+         c knows d
+         e likes b
+         a age x
+         c age y
+         a rdf:type Person",
+    );
+    serde_json::to_string(&to_file(&f.meta)).unwrap()
+}
+
+#[cfg(feature = "json")]
+#[test]
+fn parallel_parse_matches_serial_parse() {
+    let json = meta_json();
+
+    let parallel = parse_parallel(&json, 4).unwrap();
+
+    let serial: MetaFile = serde_json::from_str(&json).unwrap();
+    assert_eq!(
+        serde_json::to_string(&parallel).unwrap(),
+        serde_json::to_string(&serial).unwrap()
+    );
+}
+
+#[cfg(feature = "json")]
+#[test]
+fn parallel_parse_handles_brackets_in_strings_and_missing_aliases() {
+    let json = r#" {"s":[{"i":9,"g":[1,2]}], "q":[{"i":1,"p":9,"n":[],"o":[[3,4]]},
+        {"i":2,"p":9,"n":[],"o":[]}], "t":[[1,5]], "a":[[9,"x\",]}[{"]]} "#;
+
+    let parsed = parse_parallel(json, 4).unwrap();
+
+    assert_eq!(parsed.q.len(), 2);
+    assert_eq!(parsed.a, vec![(9, "x\",]}[{".to_string())]);
+    let without_aliases = r#"{"s":[],"q":[],"t":[]}"#;
+    assert!(parse_parallel(without_aliases, 4).unwrap().a.is_empty());
+}
+
+#[cfg(feature = "json")]
+#[test]
+fn parallel_parse_rejects_invalid_files() {
+    for json in [
+        r#"{"s":[],"q":[}"#,
+        r#"{"s":[],"t":[]}"#,
+        r#"{"s":[],"q":[1],"t":[]}"#,
+        "",
+    ] {
+        assert!(parse_parallel(json, 4).is_none(), "{}", json);
+    }
+}
+
+#[cfg(feature = "sqlite")]
+#[test]
+fn meta_db_round_trips_and_replaces_the_meta() {
+    use crate::parser::meta::{read_meta, META_DB_FILE};
+    use crate::parser::meta_db;
+    use std::{env, fs, process};
+
+    let folder = env::temp_dir().join(format!("teriyaki-meta-db-{}", process::id()));
+    fs::create_dir_all(&folder).unwrap();
+    let path = folder.join(META_DB_FILE);
+    let sorted = |mut file: MetaFile| {
+        file.q.sort_by_key(|n| n.i);
+        file.s.sort_by_key(|s| s.i);
+        serde_json::to_string(&file).unwrap()
+    };
+
+    let mut file: MetaFile = serde_json::from_str(&meta_json()).unwrap();
+    file.a = vec![(file.s[0].i, "it's \"x\"".to_string())];
+    file.g = vec![([1, 2, 3], 1)];
+    meta_db::write(&path, &file).unwrap();
+    // A second write replaces the rows of the first instead of adding to them.
+    meta_db::write(&path, &file).unwrap();
+    let expected = sorted(file);
+    assert_eq!(sorted(meta_db::read(&path).unwrap()), expected);
+
+    // Without a meta file, the meta folder is read from the database.
+    let meta = read_meta(&folder).unwrap();
+    assert_eq!(sorted(to_file(&meta)), expected);
+    fs::remove_dir_all(&folder).unwrap();
+}
+
+#[cfg(feature = "zstd")]
+#[test]
+fn compressed_meta_is_read_in_place_of_the_meta_file() {
+    use crate::parser::meta::{read_meta, COMPRESSED_META_FILE, META_FILE};
+    use crate::util::zstd;
+    use std::{env, fs, process};
+
+    let folder = env::temp_dir().join(format!("teriyaki-meta-zstd-{}", process::id()));
+    fs::create_dir_all(&folder).unwrap();
+    let sorted = |mut file: MetaFile| {
+        file.q.sort_by_key(|n| n.i);
+        file.s.sort_by_key(|s| s.i);
+        file.a.sort();
+        serde_json::to_string(&file).unwrap()
+    };
+    let json = meta_json();
+    fs::write(
+        folder.join(COMPRESSED_META_FILE),
+        zstd::compress(json.as_bytes(), 3).unwrap(),
+    )
+    .unwrap();
+    let compressed = sorted(to_file(&read_meta(&folder).unwrap()));
+
+    fs::remove_file(folder.join(COMPRESSED_META_FILE)).unwrap();
+    fs::write(folder.join(META_FILE), &json).unwrap();
+    let plain = sorted(to_file(&read_meta(&folder).unwrap()));
+    assert_eq!(compressed, plain);
+    fs::remove_dir_all(&folder).unwrap();
+}
@@ -0,0 +1,101 @@
+use crate::parser::n3::parse;
+
+const RDF_TYPE: &str = "<http://www.w3.org/1999/02/22-rdf-syntax-ns#type>";
+
+#[test]
+fn turtle_subset_is_read_as_n_triples() {
+    let n3 = parse(
+        r#"@prefix ex: <http://ex.org/> .
+        PREFIX foaf: <http://xmlns.com/foaf/0.1/>
+        @base <http://ex.org/base/doc> .
+        # A comment, with a "quote".
+        ex:a a foaf:Person ;
+            foaf:knows ex:b , <c> ;
+            foaf:age 42 ;
+            foaf:name "A \"x\""@en .
+        ex:b = ex:c .
+        ex:c is foaf:knows of ex:d .
+        "#,
+    )
+    .unwrap();
+
+    assert!(n3.warnings.is_empty());
+    assert_eq!(
+        n3.lines,
+        vec![
+            format!("<http://ex.org/a> {} <http://xmlns.com/foaf/0.1/Person> .", RDF_TYPE),
+            "<http://ex.org/a> <http://xmlns.com/foaf/0.1/knows> <http://ex.org/b> .".to_string(),
+            "<http://ex.org/a> <http://xmlns.com/foaf/0.1/knows> <http://ex.org/base/c> ."
+                .to_string(),
+            "<http://ex.org/a> <http://xmlns.com/foaf/0.1/age> \"42\"^^<http://www.w3.org/2001/XMLSchema#integer> ."
+                .to_string(),
+            "<http://ex.org/a> <http://xmlns.com/foaf/0.1/name> \"A \\\"x\\\"\"@en .".to_string(),
+            "<http://ex.org/b> <http://www.w3.org/2002/07/owl#sameAs> <http://ex.org/c> ."
+                .to_string(),
+            "<http://ex.org/d> <http://xmlns.com/foaf/0.1/knows> <http://ex.org/c> .".to_string(),
+        ]
+    );
+}
+
+#[test]
+fn blank_nodes_and_collections_get_generated_labels() {
+    let n3 = parse(
+        "@prefix : <http://ex.org/> .\n\
+         :a :p [ :q :b ] ; :r ( :x :y ) .\n\
+         :c :s \"\"\"two\nlines\"\"\" , 1.5 , true .",
+    )
+    .unwrap();
+
+    assert_eq!(
+        n3.lines,
+        vec![
+            "_:n3b1 <http://ex.org/q> <http://ex.org/b> .",
+            "<http://ex.org/a> <http://ex.org/p> _:n3b1 .",
+            "_:n3b2 <http://www.w3.org/1999/02/22-rdf-syntax-ns#first> <http://ex.org/x> .",
+            "_:n3b2 <http://www.w3.org/1999/02/22-rdf-syntax-ns#rest> _:n3b3 .",
+            "_:n3b3 <http://www.w3.org/1999/02/22-rdf-syntax-ns#first> <http://ex.org/y> .",
+            "_:n3b3 <http://www.w3.org/1999/02/22-rdf-syntax-ns#rest> <http://www.w3.org/1999/02/22-rdf-syntax-ns#nil> .",
+            "<http://ex.org/a> <http://ex.org/r> _:n3b2 .",
+            "<http://ex.org/c> <http://ex.org/s> \"two\\nlines\" .",
+            "<http://ex.org/c> <http://ex.org/s> \"1.5\"^^<http://www.w3.org/2001/XMLSchema#decimal> .",
+            "<http://ex.org/c> <http://ex.org/s> \"true\"^^<http://www.w3.org/2001/XMLSchema#boolean> .",
+        ]
+    );
+}
+
+#[test]
+fn rules_formulas_and_quantifiers_are_skipped_with_warnings() {
+    let n3 = parse(
+        "@prefix : <http://ex.org/> .\n\
+         @forAll :x .\n\
+         { ?x :p :b } => { ?x :q :b . } .\n\
+         :a :p :b .\n\
+         :a :says { :b :p :c } .\n",
+    )
+    .unwrap();
+
+    assert_eq!(
+        n3.lines,
+        vec!["<http://ex.org/a> <http://ex.org/p> <http://ex.org/b> ."]
+    );
+    assert_eq!(
+        n3.warnings,
+        vec![
+            "quantifier at line 2",
+            "formula at line 3",
+            "formula at line 5"
+        ]
+    );
+}
+
+#[test]
+fn malformed_statements_fail_with_their_line() {
+    let err = parse("@prefix : <http://ex.org/> .\n:a :p :b .\n:a :p .\n").unwrap_err();
+    assert!(err.starts_with("line 3:"), "{}", err);
+
+    let err = parse(":a :p :b .").unwrap_err();
+    assert!(err.contains("unknown prefix"), "{}", err);
+
+    let err = parse("<http://ex.org/a> <http://ex.org/p> \"open .").unwrap_err();
+    assert!(err.contains("unterminated string"), "{}", err);
+}
@@ -0,0 +1,46 @@
+use crate::parser::schema::Schema;
+
+fn lines(s: &str) -> Vec<String> {
+    s.lines().map(|l| l.trim().to_string()).collect()
+}
+
+#[test]
+fn schema_check_takes_out_violating_insertions() {
+    let schema = Schema::parse(&lines(
+        "# people
+         <http://x/Person> allows <http://x/knows> <http://x/age>
+         <http://x/age> datatype <http://www.w3.org/2001/XMLSchema#integer>",
+    ))
+    .unwrap();
+    let mut insertions = lines(
+        r#"<http://x/a> <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <http://x/Person> .
+        <http://x/a> <http://x/knows> <http://x/b> .
+        <http://x/a> <http://x/likes> <http://x/b> .
+        <http://x/a> <http://x/age> "7"^^<http://www.w3.org/2001/XMLSchema#integer> .
+        <http://x/b> <http://x/age> "seven" .
+        <http://x/b> <http://x/likes> <http://x/a> ."#,
+    );
+
+    let violations = schema.check(&mut insertions);
+
+    assert_eq!(insertions.len(), 4);
+    assert!(insertions.iter().all(|l| !violations.contains(l)));
+    assert_eq!(violations.len(), 4);
+    assert_eq!(
+        violations[0],
+        "# <http://x/likes> is not allowed for the types of <http://x/a>"
+    );
+    assert_eq!(
+        violations[1],
+        "<http://x/a> <http://x/likes> <http://x/b> ."
+    );
+    assert!(violations[2].contains("must be a <http://www.w3.org/2001/XMLSchema#integer>"));
+    assert_eq!(violations[3], r#"<http://x/b> <http://x/age> "seven" ."#);
+}
+
+#[test]
+fn schema_parse_rejects_unknown_lines() {
+    let err = Schema::parse(&lines("<http://x/Person> requires <http://x/name>")).err();
+    assert!(err.unwrap().starts_with("line 1:"));
+    assert!(Schema::parse(&lines("<http://x/age> datatype integer")).is_err());
+}
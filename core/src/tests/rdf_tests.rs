@@ -0,0 +1,199 @@
+use crate::rdf::{
+    blank_nodes, check_line, normalize, parse_term, quoted_terms, split_terms, triple_line,
+    unsupported_syntax, write_normalized, write_term, Suffix, Term,
+};
+use std::path::Path;
+
+fn written(term: Term) -> String {
+    let mut out = String::new();
+    write_term(&term, &mut out);
+    out
+}
+
+fn literal(value: &str) -> Term {
+    Term::Literal {
+        value: value.to_string(),
+        suffix: Suffix::None,
+    }
+}
+
+#[test]
+fn plain_terms_are_unchanged() {
+    for term in [
+        "<http://ex.org/a>",
+        "_:b0",
+        "\"plain\"",
+        "\"chat\"@fr",
+        "\"chat\"@en-GB",
+        "\"1\"^^<http://www.w3.org/2001/XMLSchema#integer>",
+        "\"\"",
+    ] {
+        assert_eq!(normalize(term), term);
+    }
+}
+
+#[test]
+fn literal_quotes_and_backslashes_are_escaped() {
+    assert_eq!(written(literal("say \"hi\"")), "\"say \\\"hi\\\"\"");
+    assert_eq!(written(literal("C:\\dir")), "\"C:\\\\dir\"");
+}
+
+#[test]
+fn literal_control_characters_are_escaped() {
+    assert_eq!(written(literal("a\nb\rc\td")), "\"a\\nb\\rc\\td\"");
+    assert_eq!(written(literal("\u{8}\u{c}")), "\"\\b\\f\"");
+    assert_eq!(
+        written(literal("\u{0}\u{1f}\u{7f}")),
+        "\"\\u0000\\u001F\\u007F\""
+    );
+}
+
+#[test]
+fn unicode_is_written_as_is() {
+    assert_eq!(written(literal("café ☕ 😀")), "\"café ☕ 😀\"");
+    assert_eq!(normalize("\"caf\\u00E9\""), "\"café\"");
+    assert_eq!(normalize("\"\\U0001F600\""), "\"😀\"");
+}
+
+#[test]
+fn iri_characters_are_escaped() {
+    assert_eq!(
+        written(Term::Iri("http://ex.org/a b<c>{d}|^`\\\"".to_string())),
+        "<http://ex.org/a\\u0020b\\u003Cc\\u003E\\u007Bd\\u007D\\u007C\\u005E\\u0060\\u005C\\u0022>"
+    );
+    assert_eq!(normalize("<http://ex.org/\\u00E9>"), "<http://ex.org/é>");
+}
+
+#[test]
+fn language_tags_and_datatypes_are_kept() {
+    assert_eq!(
+        parse_term("\"x\\ty\"@de"),
+        Some(Term::Literal {
+            value: "x\ty".to_string(),
+            suffix: Suffix::Lang("de".to_string()),
+        })
+    );
+    assert_eq!(
+        written(Term::Literal {
+            value: "a\"b".to_string(),
+            suffix: Suffix::Datatype("http://ex.org/type with space".to_string()),
+        }),
+        "\"a\\\"b\"^^<http://ex.org/type\\u0020with\\u0020space>"
+    );
+}
+
+#[test]
+fn short_escapes_are_canonicalized() {
+    assert_eq!(normalize("\"\\u0022\\u000A\\u0009\""), "\"\\\"\\n\\t\"");
+    assert_eq!(normalize("\"it\\'s\""), "\"it's\"");
+}
+
+#[test]
+fn invalid_terms_are_left_alone() {
+    for term in [
+        "\"unterminated",
+        "<http://ex.org/a",
+        "\"x\"@",
+        "\"x\"^^http://ex.org/t",
+        "\"bad \\q escape\"",
+        "\"\\u12\"",
+        "_:",
+        "plain",
+    ] {
+        assert_eq!(parse_term(term), None, "{}", term);
+        assert_eq!(normalize(term), term);
+    }
+}
+
+#[test]
+fn written_terms_parse_back() {
+    let value = "q\"b\\n\nr\rt\t\u{1}é😀";
+    let mut out = String::new();
+    write_term(&literal(value), &mut out);
+    assert_eq!(parse_term(&out), Some(literal(value)));
+}
+
+#[test]
+fn triple_lines_are_normalized() {
+    assert_eq!(
+        triple_line("<http://ex.org/a>", "<http://ex.org/p>", "\"\\u0041\""),
+        "<http://ex.org/a> <http://ex.org/p> \"A\" ."
+    );
+}
+
+#[test]
+fn syntaxes_other_than_n_triples_are_recognized() {
+    assert_eq!(unsupported_syntax(Path::new("data.trig")), Some("TriG"));
+    assert_eq!(
+        unsupported_syntax(Path::new("dir/onto.OWL")),
+        Some("RDF/XML")
+    );
+    assert_eq!(unsupported_syntax(Path::new("data.nt")), None);
+    assert_eq!(unsupported_syntax(Path::new("data.nq")), None);
+    assert_eq!(unsupported_syntax(Path::new("data.n3")), None);
+    assert_eq!(unsupported_syntax(Path::new("data.ttl")), None);
+    assert_eq!(unsupported_syntax(Path::new("update")), None);
+}
+
+#[test]
+fn write_normalized_appends_what_normalize_returns() {
+    let terms = [
+        "<http://ex.org/a>",
+        "<http://ex.org/a b>",
+        "<http://ex.org/\\u00E9>",
+        "<http://ex.org/\"x\">",
+        "<>",
+        "_:b0",
+        "_:",
+        "\"plain\"",
+        "\"tagged\"@en-GB",
+        "\"1\"^^<http://www.w3.org/2001/XMLSchema#integer>",
+        "\"it\\'s\"",
+        "not a term",
+    ];
+    let mut out = String::from("> ");
+    for term in terms {
+        out.truncate(2);
+        write_normalized(term, &mut out);
+        assert_eq!(out[2..], normalize(term), "{}", term);
+    }
+}
+
+#[test]
+fn quoted_triples_are_split_as_one_term() {
+    let l = "<< <http://ex.org/a> <http://ex.org/p> \"x\" >> <http://ex.org/q> << _:b <http://ex.org/p> << <http://ex.org/c> <http://ex.org/p> <http://ex.org/d> >> >> .";
+    assert_eq!(
+        split_terms(l),
+        vec![
+            "<< <http://ex.org/a> <http://ex.org/p> \"x\" >>",
+            "<http://ex.org/q>",
+            "<< _:b <http://ex.org/p> << <http://ex.org/c> <http://ex.org/p> <http://ex.org/d> >> >>",
+            "."
+        ]
+    );
+    assert_eq!(check_line(l), None);
+    assert_eq!(normalize(split_terms(l)[2]), split_terms(l)[2]);
+    assert_eq!(
+        quoted_terms(split_terms(l)[0]),
+        Some(["<http://ex.org/a>", "<http://ex.org/p>", "\"x\""])
+    );
+}
+
+#[test]
+fn malformed_quoted_triples_are_rejected() {
+    for l in [
+        "<http://ex.org/a> << <http://ex.org/a> <http://ex.org/p> <http://ex.org/b> >> <http://ex.org/b> .",
+        "<< <http://ex.org/a> <http://ex.org/p> >> <http://ex.org/q> <http://ex.org/b> .",
+        "<< <http://ex.org/a> <http://ex.org/p> <http://ex.org/b> <http://ex.org/q> <http://ex.org/b> .",
+    ] {
+        assert!(check_line(l).is_some(), "{}", l);
+    }
+}
+
+#[test]
+fn blank_nodes_are_found_in_quoted_triples() {
+    assert_eq!(blank_nodes("_:b <p> <a> ."), ["_:b"]);
+    assert_eq!(blank_nodes("<a> <p> << _:b <q> _:c >> ."), ["_:b", "_:c"]);
+    assert!(blank_nodes("<a> <p> \"_:b\" .").is_empty());
+    assert!(blank_nodes("<a> <p> <b> .").is_empty());
+}
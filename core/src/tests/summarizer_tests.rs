@@ -0,0 +1,192 @@
+use super::fixtures::{iri, load, Fixture};
+use crate::metrics::drift::DriftThresholds;
+use crate::summarizer::Summarizer;
+use crate::updater::Options;
+use crate::{DuplicatePolicy, Grouping};
+
+fn summarizer(f: Fixture) -> Summarizer {
+    let options = Options {
+        paranoid: true,
+        ..Default::default()
+    };
+    Summarizer::new(f.dataset, f.meta, f.sc, f.tc, Grouping::Preds, options)
+}
+
+#[test]
+fn changes_report_supernodes_by_alias() {
+    let mut s = summarizer(load("a knows x"));
+
+    let added = s.add(&iri("b"), &iri("knows"), &iri("x")).unwrap();
+    assert_eq!(added.created.len(), 1);
+    assert!(added.removed.is_empty() && added.changed.is_empty());
+    let alias = &added.created[0];
+    assert_eq!(
+        s.meta
+            .get_supernode(&s.meta.supernode_by_alias(alias).unwrap())
+            .unwrap()
+            .len(),
+        2
+    );
+
+    let grown = s.add(&iri("c"), &iri("knows"), &iri("x")).unwrap();
+    assert_eq!(&grown.changed, &vec![alias.clone()]);
+
+    s.delete(&iri("c"), &iri("knows"), &iri("x")).unwrap();
+    let deleted = s.delete(&iri("b"), &iri("knows"), &iri("x")).unwrap();
+    assert_eq!(&deleted.removed, &vec![alias.clone()]);
+}
+
+#[test]
+fn unknown_deletions_and_repeated_insertions_are_flagged() {
+    let mut s = summarizer(load("a knows x"));
+
+    let deleted = s.delete(&iri("a"), &iri("knows"), &iri("y")).unwrap();
+    assert!(deleted.rejected);
+    let added = s.add(&iri("a"), &iri("knows"), &iri("x")).unwrap();
+    assert!(added.duplicate);
+}
+
+#[test]
+fn malformed_terms_are_errors() {
+    let mut s = summarizer(load("a knows x"));
+
+    assert!(s.add("a", &iri("knows"), &iri("x")).is_err());
+    assert!(s.delete(&iri("a"), "\"knows\"", &iri("x")).is_err());
+}
+
+#[test]
+fn changes_prepared_before_their_supernodes_changed_conflict() {
+    let mut s = summarizer(load("a knows x\nz likes w"));
+    let (knows, likes) = (iri("knows"), iri("likes"));
+
+    // Two writers prepare changes at the same epoch; the first one groups a and b.
+    let epoch = s.epoch();
+    let first = s.add_at(&iri("b"), &knows, &iri("x"), epoch).unwrap();
+    assert!(first.conflicts.is_empty() && first.created.len() == 1);
+    assert_eq!(s.epoch(), epoch + 1);
+
+    let stale = s.add_at(&iri("a"), &likes, &iri("w"), epoch).unwrap();
+    assert_eq!(stale.conflicts, first.created);
+    // Changes of nodes whose supernodes did not change go through.
+    assert!(s
+        .add_at(&iri("z"), &likes, &iri("y"), epoch)
+        .unwrap()
+        .conflicts
+        .is_empty());
+
+    // Prepared again at the current epoch, the change applies.
+    let retried = s.add_at(&iri("a"), &likes, &iri("w"), s.epoch()).unwrap();
+    assert!(retried.conflicts.is_empty() && !retried.duplicate);
+
+    // A node that left a supernode conflicts with changes prepared before it left.
+    let epoch = s.epoch();
+    s.delete(&iri("b"), &knows, &iri("x")).unwrap();
+    assert_eq!(
+        s.delete_at(&iri("b"), &knows, &iri("y"), epoch)
+            .unwrap()
+            .conflicts
+            .len(),
+        1
+    );
+}
+
+#[test]
+fn repeated_changes_within_the_window_are_skipped() {
+    let mut s = summarizer(load("a knows x"));
+    s.set_dedup_window(2);
+    let (b, knows, x) = (iri("b"), iri("knows"), iri("x"));
+
+    assert!(!s.add(&b, &knows, &x).unwrap().repeated);
+    assert!(s.add(&b, &knows, &x).unwrap().repeated);
+    assert!(!s.delete(&b, &knows, &x).unwrap().repeated);
+    assert!(s.delete(&b, &knows, &x).unwrap().repeated);
+    // Re-adding after a deletion is a new change, not a repeat.
+    assert!(!s.add(&b, &knows, &x).unwrap().repeated);
+
+    // Once other changes push it out of the window, a change is applied again.
+    s.add(&iri("c"), &knows, &x).unwrap();
+    s.add(&iri("d"), &knows, &x).unwrap();
+    let added = s.add(&b, &knows, &x).unwrap();
+    assert!(!added.repeated && added.duplicate);
+}
+
+#[test]
+fn changes_that_fail_are_not_taken_for_repeats() {
+    let f = load("a knows x");
+    let options = Options {
+        duplicates: DuplicatePolicy::Error,
+        ..Default::default()
+    };
+    let mut s = Summarizer::new(f.dataset, f.meta, f.sc, f.tc, Grouping::Preds, options);
+    s.set_dedup_window(2);
+    let (a, b, knows, x) = (iri("a"), iri("b"), iri("knows"), iri("x"));
+
+    assert!(s.add(&a, &knows, &x).is_err());
+    assert!(!s.repeats(&a, &knows, &x, false));
+    assert!(s.add(&a, &knows, &x).is_err());
+
+    assert!(!s.repeats(&b, &knows, &x, false));
+    s.add(&b, &knows, &x).unwrap();
+    assert!(s.repeats(&b, &knows, &x, false));
+    assert!(!s.repeats(&b, &knows, &x, true));
+}
+
+#[test]
+fn raising_a_drift_alert_reclusters_the_summary() {
+    let mut s = summarizer(load("a knows x"));
+    s.set_drift_thresholds(DriftThresholds {
+        min_average_size: Some(3.0),
+        ..Default::default()
+    });
+    let (knows, x) = (iri("knows"), iri("x"));
+
+    assert!(s.add(&iri("b"), &knows, &x).unwrap().reclustered.is_some());
+    // The alert lasts, but only raising it triggers a pass.
+    let added = s.add(&iri("c"), &iri("likes"), &x).unwrap();
+    assert!(added.reclustered.is_none());
+    assert_eq!(s.drift().alerts, vec!["average supernode size"]);
+
+    s.add(&iri("d"), &knows, &x).unwrap();
+    assert!(s.drift().alerts.is_empty());
+    let deleted = s.delete(&iri("d"), &knows, &x).unwrap();
+    assert!(deleted.reclustered.is_some());
+    assert_eq!(s.drift().reclusterings, 2);
+}
+
+#[test]
+fn relevant_supernodes_follow_predicate_signatures() {
+    let s = summarizer(load("a knows x\nb knows y\nc likes z\nd likes w"));
+    let alias_of = |t: &str| {
+        let id = s.dataset.id_of(&iri(t)).unwrap();
+        vec![s
+            .meta
+            .get_alias(&s.meta.get_parent(&id).unwrap())
+            .unwrap()
+            .clone()]
+    };
+    let (knows, likes) = (iri("knows"), iri("likes"));
+
+    let known = s.relevant_supernodes(["?s", &knows, "?o"]);
+    assert_eq!(known.subjects, alias_of("a"));
+    assert_eq!(known.objects, alias_of("x"));
+
+    let any = s.relevant_supernodes(["?s", "?p", "?o"]);
+    let mut subjects = [alias_of("a"), alias_of("c")].concat();
+    subjects.sort();
+    assert_eq!(any.subjects, subjects);
+    assert_eq!(any.objects.len(), 2);
+
+    // A constant keeps its own supernode and the ones it has an edge with.
+    let from_c = s.relevant_supernodes([&iri("c"), "?p", "?o"]);
+    assert_eq!(from_c.subjects, alias_of("c"));
+    let from_c = s.relevant_supernodes([&iri("c"), &likes, "?o"]);
+    assert_eq!(from_c.objects, alias_of("z"));
+    assert_eq!(
+        s.relevant_supernodes([&iri("c"), &knows, "?o"]),
+        Default::default()
+    );
+    assert_eq!(
+        s.relevant_supernodes(["?s", &iri("hates"), "?o"]),
+        Default::default()
+    );
+}
@@ -0,0 +1,728 @@
+use super::fixtures::{iri, load, load_with, Fixture};
+use crate::models::id_allocator::IdKind;
+use crate::models::seeds::Seeds;
+use crate::models::triple::{Triple, TYPE_STRING};
+use crate::parser::meta::{from_file, to_file};
+use crate::updater::recluster::{recluster_all, repartition};
+use crate::updater::{verify_cliques, Options};
+use crate::{parser, updater, Coalescing, Config, Directions, Grouping, SplitStrategy};
+use std::time::{Duration, Instant};
+use std::{env, fs, process};
+
+#[test]
+fn nodes_with_same_preds_are_merged() {
+    let f = load(
+        "a knows b
+         c knows d
+         e likes b",
+    );
+
+    assert_eq!(f.members("a"), vec![iri("a"), iri("c")]);
+    assert_eq!(f.members("b"), vec![iri("b"), iri("d")]);
+    assert_eq!(f.members("e"), vec![iri("e")]);
+    assert_eq!(f.summary().len(), 2);
+}
+
+#[test]
+fn deletion_splits_supernode() {
+    let mut f = load(
+        "a knows b
+         c knows d
+         a age x
+         c age y",
+    );
+    assert_eq!(f.members("x"), vec![iri("x"), iri("y")]);
+
+    f.update("-c age y");
+
+    assert_eq!(f.members("x"), vec![iri("x")]);
+    assert_eq!(f.members("a"), vec![iri("a"), iri("c")]);
+}
+
+#[test]
+fn unknown_deletion_is_rejected() {
+    let mut f = load("a knows b");
+
+    let report = f.update("-a knows c");
+
+    assert_eq!(report.rejected_deletions.len(), 1);
+    assert_eq!(
+        f.summary(),
+        vec![format!("{} {} {} .", iri("a"), iri("knows"), iri("b"))]
+    );
+}
+
+#[test]
+fn types_are_kept_aside() {
+    let f = load(
+        "a knows b
+         c knows d
+         a rdf:type Person",
+    );
+
+    assert_eq!(f.members("a"), vec![iri("a"), iri("c")]);
+    assert_eq!(f.summary().len(), 2);
+}
+
+#[test]
+fn supernodes_are_capped() {
+    let mut f = load_with(
+        "a knows x
+         b knows x
+         c knows x
+         d knows x
+         e knows x",
+        Options {
+            max_supernode_size: Some(2),
+            ..Default::default()
+        },
+    );
+    f.update("g knows x");
+
+    let members: Vec<usize> = ["a", "b", "c", "d", "e", "g"]
+        .iter()
+        .map(|n| f.members(n).len())
+        .collect();
+    assert!(members.iter().all(|len| *len <= 2));
+    assert_eq!(members.iter().filter(|len| **len == 1).count(), 0);
+}
+
+#[test]
+fn deleted_type_leaves_summary() {
+    let mut f = load(
+        "a knows b
+         c knows d
+         a rdf:type Person",
+    );
+
+    f.update("-a rdf:type Person");
+
+    assert_eq!(f.summary().len(), 1);
+}
+
+#[test]
+fn canonical_summary_ignores_insertion_order() {
+    let graph = ["a knows b", "c knows d", "a age x", "c age y", "e likes b"];
+    let forward = load(&graph.join("\n"));
+    let backward = load(&graph.iter().rev().cloned().collect::<Vec<_>>().join("\n"));
+
+    assert_eq!(forward.canonical(), backward.canonical());
+    assert_eq!(forward.canonical().supernodes.len(), 3);
+}
+
+#[test]
+fn incremental_and_batch_summaries_are_equal() {
+    let mut incremental = load("a knows b");
+    incremental.update("c knows d");
+    let batch = load(
+        "a knows b
+         c knows d",
+    );
+
+    assert_eq!(incremental.canonical(), batch.canonical());
+}
+
+#[test]
+fn set_coalescing_skips_both_changes() {
+    let mut f = load_with(
+        "a knows b
+         c knows d",
+        Options {
+            coalescing: Coalescing::Set,
+            ..Default::default()
+        },
+    );
+
+    let report = f.update(
+        "e knows f
+         -e knows f
+         c knows d
+         -c knows d",
+    );
+
+    assert_eq!(report.coalesced, 4);
+    assert_eq!(f.members("a"), vec![iri("a"), iri("c")]);
+}
+
+#[test]
+fn sequence_coalescing_keeps_last_change() {
+    let mut f = load(
+        "a knows b
+         c knows d
+         a age x
+         c age y",
+    );
+
+    // Without positions, deletions count as coming after insertions.
+    let report = f.update(
+        "e knows f
+         -e knows f
+         c age y
+         -c age y",
+    );
+
+    assert_eq!(report.coalesced, 3);
+    assert_eq!(f.members("x"), vec![iri("x")]);
+    assert_eq!(f.summary().len(), 2);
+}
+
+#[test]
+fn alias_survives_merge() {
+    let mut f = load(
+        "a knows b
+         c knows d",
+    );
+    let (id, alias) = f.supernode("a");
+
+    f.update("e knows f");
+
+    assert_eq!(f.members("a"), vec![iri("a"), iri("c"), iri("e")]);
+    let (new_id, new_alias) = f.supernode("a");
+    assert_ne!(new_id, id);
+    assert_eq!(new_alias, alias);
+}
+
+#[test]
+fn alias_survives_split() {
+    let mut f = load(
+        "a knows b
+         c knows d
+         e knows f
+         a age x
+         c age y
+         e age z",
+    );
+    let (id, alias) = f.supernode("x");
+
+    f.update("-e age z");
+
+    assert_eq!(f.members("x"), vec![iri("x"), iri("y")]);
+    let (new_id, new_alias) = f.supernode("x");
+    assert_ne!(new_id, id);
+    assert_eq!(new_alias, alias);
+    assert_ne!(f.supernode("a").1, alias);
+}
+
+#[test]
+fn split_strategies_agree_on_valid_groups() {
+    let graph = "a knows b
+                 c knows d
+                 e knows f";
+    let mut ejected = load(graph);
+    ejected.update("z likes a");
+
+    for strategy in [SplitStrategy::Repartition, SplitStrategy::Lazy(1)] {
+        let mut f = load_with(
+            graph,
+            Options {
+                split_strategy: strategy,
+                ..Default::default()
+            },
+        );
+        let report = f.update("z likes a");
+
+        assert_eq!(report.reclustered, 0);
+        assert_eq!(f.members("c"), vec![iri("c"), iri("e")]);
+        assert_eq!(f.canonical(), ejected.canonical());
+    }
+}
+
+#[test]
+fn repartition_splits_members_in_other_cliques() {
+    let mut f = load(
+        "a knows b
+         c knows d
+         e knows f",
+    );
+    let (snode, _) = f.supernode("a");
+    // Take the edge away behind the cliques' back, so `a` no longer belongs with `c` and `e`.
+    let a = f.dataset.id_of(&iri("a")).unwrap();
+    let knows = f.dataset.pred_id_of(&iri("knows")).unwrap();
+    let b = f.dataset.id_of(&iri("b")).unwrap();
+    f.meta.remove_outgoing(&Triple::new(a, knows, b, false));
+
+    assert!(repartition(
+        &snode,
+        &mut f.dataset,
+        &mut f.meta,
+        &mut f.sc,
+        &mut f.tc
+    ));
+
+    assert_eq!(f.members("a"), vec![iri("a")]);
+    assert_eq!(f.members("c"), vec![iri("c"), iri("e")]);
+    assert!(f.sc.in_empty_clique(&a));
+    verify_cliques(&f.meta, &f.sc, &f.tc).unwrap();
+}
+
+#[test]
+fn recluster_merges_nodes_sharing_cliques() {
+    let mut f = load_with(
+        "a knows x
+         b knows x
+         c knows x",
+        Options {
+            max_supernode_size: Some(2),
+            ..Default::default()
+        },
+    );
+    assert_eq!(f.members("c"), vec![iri("c")]);
+
+    let count = recluster_all(&mut f.dataset, &mut f.meta, &mut f.sc, &mut f.tc, None);
+
+    assert_eq!(count, 1);
+    assert_eq!(f.members("c"), vec![iri("a"), iri("b"), iri("c")]);
+    verify_cliques(&f.meta, &f.sc, &f.tc).unwrap();
+}
+
+#[test]
+fn joint_directions_group_by_combined_preds() {
+    let graph = "a p x
+                 b p y
+                 x q z";
+    let f = load(graph);
+    assert_eq!(f.members("x"), vec![iri("x")]);
+
+    // x joins the source clique of q with the target clique of p, which y is in.
+    let mut f = load_with(
+        graph,
+        Options {
+            directions: Directions::Joint,
+            ..Default::default()
+        },
+    );
+    assert_eq!(f.members("a"), vec![iri("a"), iri("b")]);
+    assert_eq!(f.members("x"), vec![iri("x"), iri("y")]);
+
+    // Without its out-pred, no member of the supernode has one left.
+    f.update(
+        "-x q z
+         z q w",
+    );
+    assert_eq!(f.members("x"), vec![iri("x"), iri("y")]);
+    assert_eq!(f.members("z"), vec![iri("z")]);
+}
+
+/// Loads a graph where `a` and `b` share a supernode, and drops the supernode from the meta
+/// while they keep it as their parent.
+fn load_with_unknown_parent(options: Options) -> Fixture {
+    let mut f = load_with("a knows x\nb knows x\nc likes y", options);
+    let (snode, _) = f.supernode("a");
+    let mut file = to_file(&f.meta);
+    file.s.retain(|s| s.i != snode);
+    f.meta = from_file(file);
+    f
+}
+
+#[test]
+fn lenient_update_skips_changes_around_unknown_parents() {
+    let mut f = load_with_unknown_parent(Options {
+        lenient: true,
+        ..Default::default()
+    });
+
+    let report = f.update(
+        "-a knows x
+         c likes z",
+    );
+
+    // The skipped deletion follows its reason; verifying the cliques adds reasons of its own.
+    let deletion = format!("-{} {} {} .", iri("a"), iri("knows"), iri("x"));
+    let i = report
+        .inconsistent
+        .iter()
+        .position(|l| *l == deletion)
+        .unwrap();
+    assert!(report.inconsistent[i - 1].starts_with("# <http://ex.org/a> has unknown parent"));
+    assert!(report
+        .inconsistent
+        .iter()
+        .all(|l| l.starts_with('#') || *l == deletion));
+    // The insertion does not touch the inconsistent supernode, so it is applied.
+    assert_eq!(f.members("z"), vec![iri("y"), iri("z")]);
+}
+
+#[test]
+#[should_panic(expected = "has unknown parent")]
+fn strict_update_fails_on_unknown_parents() {
+    let mut f = load_with_unknown_parent(Options::default());
+    f.update("a likes z");
+}
+
+#[test]
+fn update_in_chunks_matches_a_single_run() {
+    let graph = "a knows b
+                 c knows d
+                 e likes f
+                 a age x
+                 c age y";
+    let changes = "g knows h
+                   e knows b
+                   i likes f
+                   -c age y
+                   -e likes f";
+    let mut whole = load(graph);
+    whole.update(changes);
+    let mut chunked = load(graph);
+
+    let (report, snapshots) = chunked.update_in_chunks(changes, 2);
+
+    // Two chunks of insertions and one of deletions, with a snapshot between each.
+    assert_eq!(snapshots, vec![2, 3]);
+    assert!(report.pending.is_empty());
+    assert_eq!(chunked.summary(), whole.summary());
+    assert!(chunked.canonical() == whole.canonical());
+    assert_eq!(chunked.dataset.insertions.data_triples.len(), 3);
+}
+
+#[test]
+fn a_passed_deadline_leaves_every_change_pending() {
+    let graph = "a knows b
+                 c knows d
+                 a age x";
+    let changes = "e knows b
+                   g knows h
+                   -a age x";
+    let pending = vec![
+        format!("{} {} {} .", iri("e"), iri("knows"), iri("b")),
+        format!("{} {} {} .", iri("g"), iri("knows"), iri("h")),
+        format!("-{} {} {} .", iri("a"), iri("age"), iri("x")),
+    ];
+    let passed = || Options {
+        deadline: Some(Instant::now()),
+        ..Default::default()
+    };
+
+    let mut f = load_with(graph, passed());
+    let before = f.summary();
+    let report = f.update(changes);
+    assert_eq!(report.pending, pending);
+    assert_eq!(f.summary(), before);
+
+    // Chunks after the one the deadline passed in are pending as a whole.
+    let mut chunked = load_with(graph, passed());
+    let (report, snapshots) = chunked.update_in_chunks(changes, 1);
+    assert_eq!(report.pending, pending);
+    assert!(snapshots.is_empty());
+    assert_eq!(chunked.summary(), before);
+}
+
+#[test]
+fn a_distant_deadline_applies_every_change() {
+    let graph = "a knows b
+                 c knows d
+                 a age x";
+    let changes = "e knows b
+                   -a age x";
+    let mut unlimited = load(graph);
+    unlimited.update(changes);
+    let mut f = load_with(
+        graph,
+        Options {
+            deadline: Some(Instant::now() + Duration::from_secs(3600)),
+            ..Default::default()
+        },
+    );
+
+    let report = f.update(changes);
+
+    assert!(report.pending.is_empty());
+    assert_eq!(f.summary(), unlimited.summary());
+}
+
+#[test]
+fn supernodes_are_named_apart_from_nodes() {
+    let f = load(
+        "a knows x
+         b knows x
+         a_b likes y
+         b_a hates z",
+    );
+
+    let (snode, _) = f.supernode("a");
+    assert_eq!(f.members("a"), vec![iri("a"), iri("b")]);
+    assert_eq!(IdKind::of(snode), IdKind::Supernode);
+    assert_eq!(f.dataset.key_by_value(&snode), Some(iri("a_b_2")));
+    for node in ["a", "b", "a_b", "b_a"] {
+        let id = f.dataset.id_of(&iri(node)).unwrap();
+        assert_eq!(IdKind::of(id), IdKind::Node);
+        assert_eq!(f.dataset.key_by_value(&id), Some(iri(node)));
+    }
+    assert_eq!(f.summary().len(), 3);
+}
+
+#[test]
+fn splits_rename_supernodes_apart_from_nodes() {
+    let mut f = load(
+        "s knows a
+         s knows b
+         s knows c
+         a_c likes y
+         c_a hates z",
+    );
+    assert_eq!(f.members("a"), vec![iri("a"), iri("b"), iri("c")]);
+
+    // Without `b`, the supernode would be named after `a` and `c`, which are taken.
+    f.update("-s knows b");
+    assert_eq!(f.members("a"), vec![iri("a"), iri("c")]);
+    let (snode, _) = f.supernode("a");
+    for node in ["a_c", "c_a"] {
+        let id = f.dataset.id_of(&iri(node)).unwrap();
+        assert_ne!(id, snode);
+        assert_eq!(f.dataset.key_by_value(&id), Some(iri(node)));
+    }
+    assert_eq!(f.summary().len(), 3);
+}
+
+#[test]
+fn deletion_within_a_supernode_splits_both_endpoints() {
+    let mut f = load(
+        "a knows b
+         b knows a
+         c knows d
+         d knows c",
+    );
+    assert_eq!(f.members("a"), vec![iri("a"), iri("b"), iri("c"), iri("d")]);
+
+    // `a` loses its outgoing edge and `b` its incoming one, so both leave the supernode, and
+    // its self-loop is split into the edge between them and the loop of `c` and `d`.
+    f.update("-a knows b");
+
+    assert_eq!(f.members("a"), vec![iri("a")]);
+    assert_eq!(f.members("b"), vec![iri("b")]);
+    assert_eq!(f.members("c"), vec![iri("c"), iri("d")]);
+    let fresh = load(
+        "b knows a
+         c knows d
+         d knows c",
+    );
+    assert_eq!(f.canonical(), fresh.canonical());
+}
+
+#[test]
+fn deletion_within_a_supernode_keeps_edges_to_the_members_left() {
+    let mut f = load(
+        "a knows b
+         b knows c
+         c knows a
+         d knows e
+         e knows f
+         f knows d",
+    );
+    f.update("-a knows b");
+
+    let fresh = load(
+        "b knows c
+         c knows a
+         d knows e
+         e knows f
+         f knows d",
+    );
+    assert_eq!(f.canonical(), fresh.canonical());
+    assert_eq!(f.members("c"), vec![iri("c"), iri("d"), iri("e"), iri("f")]);
+}
+
+#[test]
+fn deletion_within_a_supernode_splits_the_object_alone() {
+    let mut f = load(
+        "a knows b
+         b knows a
+         c knows d
+         d knows c
+         a age x
+         b age x
+         c age x
+         d age x",
+    );
+
+    // `a` keeps an outgoing edge, so only `b` leaves, from the target side.
+    f.update("-a knows b");
+
+    assert_eq!(f.members("a"), vec![iri("a"), iri("c"), iri("d")]);
+    assert_eq!(f.members("b"), vec![iri("b")]);
+    let fresh = load(
+        "b knows a
+         c knows d
+         d knows c
+         a age x
+         b age x
+         c age x
+         d age x",
+    );
+    assert_eq!(f.canonical(), fresh.canonical());
+}
+
+#[test]
+fn insertion_then_deletion_at_a_supernode_restores_the_summary() {
+    let mut f = load(
+        "a knows b
+         b knows a",
+    );
+    let before = f.canonical();
+
+    // `x` has no incoming edges, so it stays apart from the supernode of `a` and `b`. The
+    // summary triple is added between `x` and that supernode, and the deletion removes it from
+    // there rather than from `a`.
+    f.update("x knows a");
+    assert_eq!(f.members("x"), vec![iri("x")]);
+    f.update("-x knows a");
+
+    assert_eq!(f.canonical(), before);
+}
+
+#[test]
+fn self_loop_on_a_supernode_member_matches_a_batch_load() {
+    let mut f = load(
+        "b likes e
+         d likes f
+         g knows h
+         i knows j
+         k hates i",
+    );
+    assert_eq!(f.members("b"), vec![iri("b"), iri("d")]);
+
+    // The source side merges the clique of the supernode into the larger one of `g` and `i`, then the target side splits `b` off
+    // and leaves `d` alone in its place.
+    f.update("b knows b");
+
+    let batch = load(
+        "b likes e
+         d likes f
+         g knows h
+         i knows j
+         k hates i
+         b knows b",
+    );
+    assert_eq!(f.canonical(), batch.canonical());
+}
+
+#[test]
+fn quoted_triples_are_summarized_as_nodes() {
+    let mut f = load(
+        "<< a knows b >> since y1
+         << c knows d >> since y2",
+    );
+    let ab = format!("<< {} {} {} >>", iri("a"), iri("knows"), iri("b"));
+    let cd = format!("<< {} {} {} >>", iri("c"), iri("knows"), iri("d"));
+    assert_eq!(f.members(&ab), vec![ab.clone(), cd.clone()]);
+    assert_eq!(
+        f.summary(),
+        vec![format!(
+            "<urn:teriyaki:quoted:a-knows-b_c-knows-d> {} {} .",
+            iri("since"),
+            iri("y1_y2")
+        )]
+    );
+
+    f.update(&format!("e claims {}", ab));
+
+    assert_eq!(f.members(&ab), vec![ab.clone()]);
+    assert_eq!(f.members(&cd), vec![cd]);
+    assert!(f
+        .summary()
+        .contains(&format!("{} {} {} .", iri("e"), iri("claims"), ab)));
+}
+
+#[test]
+fn cliques_kept_in_bitmaps_summarize_alike() {
+    // Each `s` has an incoming pred of its own, so they share a source clique unmerged.
+    let graph: String = (0..12)
+        .map(|i| format!("s{} knows o{}\nx p{} s{}\n", i, i % 3, i, i))
+        .collect();
+    let changes = "-s0 knows o0
+         -x p1 s1
+         s2 likes o1
+         s12 knows o1
+         x p12 s12
+         -s3 knows o0
+         y p4 s5";
+    let mut lists = load(&graph);
+    let mut bitmaps = load(&graph);
+    for cc in [&mut lists.sc, &mut lists.tc] {
+        cc.set_bitmap_threshold(0);
+    }
+    for cc in [&mut bitmaps.sc, &mut bitmaps.tc] {
+        cc.set_bitmap_threshold(4);
+    }
+    assert!(bitmaps.sc.cliques().iter().any(|c| c.nodes.is_bitmap()));
+    assert!(!lists.sc.cliques().iter().any(|c| c.nodes.is_bitmap()));
+
+    lists.update(changes);
+    bitmaps.update(changes);
+
+    assert_eq!(bitmaps.canonical(), lists.canonical());
+    assert_eq!(bitmaps.members("s1"), lists.members("s1"));
+}
+
+#[test]
+fn seeds_keep_their_groups_together_and_apart() {
+    // Re-clustering the whole summary after each update respects the seeds too.
+    let options = Options {
+        recluster: true,
+        ..Default::default()
+    };
+    let mut f = load_with(
+        "a knows x
+         c likes y",
+        options,
+    );
+    let seeds = format!("{} one\n{} two\n{} one", iri("a"), iri("b"), iri("c"));
+    f.dataset.seeds = Seeds::parse(&seeds.lines().map(String::from).collect::<Vec<_>>()).unwrap();
+
+    // b has the preds of a, but a different group.
+    f.update("b knows x");
+    assert_eq!(f.members("a"), vec![iri("a"), iri("c")]);
+    assert_eq!(f.members("b"), vec![iri("b")]);
+
+    // Nodes without a group still join a group by their preds.
+    f.update("d knows x");
+    assert_eq!(f.members("d"), vec![iri("a"), iri("c"), iri("d")]);
+}
+
+#[test]
+fn grouping_by_types_merges_nodes_with_the_same_types() {
+    let folder = env::temp_dir().join(format!("teriyaki-types-{}", process::id()));
+    fs::create_dir_all(&folder).unwrap();
+    let [a, b, c, d] = ["a", "b", "c", "d"].map(iri);
+    let (knows, likes, person, city) = (iri("knows"), iri("likes"), iri("Person"), iri("City"));
+    let update = [
+        format!("{} {} {} .", a, knows, b),
+        format!("{} {} {} .", c, likes, d),
+        format!("{} {} {} .", a, TYPE_STRING, person),
+        format!("{} {} {} .", c, TYPE_STRING, person),
+        format!("{} {} {} .", b, TYPE_STRING, city),
+    ];
+    fs::write(folder.join("empty.nt"), "").unwrap();
+    fs::write(folder.join("update.nt"), update.join("\n") + "\n").unwrap();
+    let config = Config::builder()
+        .dataset(folder.join("empty.nt"))
+        .update(folder.join("update.nt"))
+        .meta_folder(folder.join("meta"))
+        .fast(true)
+        .grouping(Grouping::Types)
+        .build()
+        .unwrap();
+
+    let (mut dataset, mut meta, mut sc, mut tc) = parser::run(&config).unwrap();
+    updater::run(
+        &mut dataset,
+        &mut meta,
+        &mut sc,
+        &mut tc,
+        &config.update_options(),
+    )
+    .unwrap();
+    fs::remove_dir_all(&folder).unwrap();
+
+    let parent = |term: &str| meta.get_parent(&dataset.id_of(term).unwrap());
+    assert!(parent(&a).is_some());
+    assert_eq!(parent(&a), parent(&c));
+    assert_ne!(parent(&a), parent(&b));
+    // Only types decide the grouping; the other triples pass through unchanged.
+    assert!(dataset
+        .passthrough
+        .contains(&format!("{} {} {} .", a, knows, b)));
+    assert!(dataset
+        .passthrough
+        .contains(&format!("{} {} {} .", c, likes, d)));
+}
@@ -0,0 +1,323 @@
+use crate::util::exit::Failure;
+use crate::util::io;
+use crate::util::lock::Lock;
+use crate::util::lru::Lru;
+use crate::util::source::{self, DataSource, LocalSource};
+use crate::util::wal::{self, Wal};
+use std::env;
+use std::fs;
+use std::io::{ErrorKind, Write};
+use std::path::{Path, PathBuf};
+use std::process;
+
+fn lock_path(name: &str) -> PathBuf {
+    env::temp_dir().join(format!("teriyaki-{}-{}.lock", name, process::id()))
+}
+
+#[test]
+fn lock_is_exclusive_until_dropped() {
+    let path = lock_path("exclusive");
+    let lock = Lock::acquire(&path).unwrap();
+
+    let err = Lock::acquire(&path).err().unwrap();
+    assert!(err
+        .to_string()
+        .ends_with(&format!("already locked by pid {}", process::id())));
+
+    drop(lock);
+    assert!(!path.exists());
+    Lock::acquire(&path).unwrap();
+}
+
+#[test]
+fn stale_lock_is_taken_over() {
+    let path = lock_path("stale");
+    // Left behind by a run that exited without removing it; the pid does not matter, even
+    // that of a running process.
+    fs::write(&path, "1\n").unwrap();
+
+    let _lock = Lock::acquire(&path).unwrap();
+    assert_eq!(
+        fs::read_to_string(&path).unwrap(),
+        format!("{}\n", process::id())
+    );
+}
+
+#[test]
+fn first_line_skips_leading_empty_lines() {
+    let path = lock_path("first-line");
+    fs::write(&path, "\n\n<a> <b> <c> .\n<d> <e> <f> .\n").unwrap();
+    assert_eq!(
+        io::first_line(&path).unwrap().as_deref(),
+        Some("<a> <b> <c> .")
+    );
+
+    fs::write(&path, "\n").unwrap();
+    assert_eq!(io::first_line(&path).unwrap(), None);
+    fs::remove_file(&path).unwrap();
+    assert!(io::is_writable(&env::temp_dir()));
+}
+
+#[test]
+fn stream_lines_reads_every_line_in_order() {
+    let path = env::temp_dir().join(format!("teriyaki-stream-{}.nt", process::id()));
+    let lines: Vec<String> = (0..5000).map(|i| format!("line {}", i)).collect();
+    fs::write(&path, lines.join("\n")).unwrap();
+
+    // Room for a single batch, so the reader waits for every batch to be taken.
+    let streamed: Vec<String> = io::stream_lines(&path, 1)
+        .unwrap()
+        .collect::<Result<_, _>>()
+        .unwrap();
+    assert_eq!(streamed, lines);
+
+    // Dropping the stream early stops the reader.
+    let first: Vec<String> = io::stream_lines(&path, 1)
+        .unwrap()
+        .take(3)
+        .map(Result::unwrap)
+        .collect();
+    assert_eq!(first, lines[..3]);
+    fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn local_source_reads_ranges_and_lists_by_prefix() {
+    let dir = env::temp_dir().join(format!("teriyaki-source-{}", process::id()));
+    fs::create_dir_all(dir.join("dump-3")).unwrap();
+    for name in ["dump-2.nt", "dump-1.nt", "other.nt"] {
+        fs::write(dir.join(name), "<a> <b> <c> .\n").unwrap();
+    }
+    let prefix = format!("{}/dump-", dir.display());
+    let path = format!("{}1.nt", prefix);
+
+    // Folders are not listed.
+    assert_eq!(
+        LocalSource.list(&prefix).unwrap(),
+        [path.clone(), format!("{}2.nt", prefix)]
+    );
+    assert_eq!(LocalSource.read_range(&path, 4, 3).unwrap(), b"<b>");
+    assert_eq!(LocalSource.read_range(&path, 12, 10).unwrap(), b".\n");
+    assert_eq!(
+        source::first_line(Path::new(&path)).unwrap().as_deref(),
+        Some("<a> <b> <c> .")
+    );
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn remote_locations_are_told_apart() {
+    assert!(source::is_remote(Path::new("s3://bucket/dump.nt")));
+    assert!(source::is_remote(Path::new("http://ex.org/dump.nt")));
+    assert!(!source::is_remote(Path::new("data/dump.nt")));
+
+    assert!(source::looks_like_file(
+        "http://ex.org/dumps/dump.NT?version=2"
+    ));
+    assert!(!source::looks_like_file("http://ex.org/sparql"));
+    assert!(!source::looks_like_file("http://ex.org/v1.2/sparql"));
+}
+
+#[test]
+fn wal_replays_complete_changes_until_rotated() {
+    let path = lock_path("wal");
+    assert!(wal::read(&path).unwrap().is_empty());
+
+    let mut log = Wal::open(&path).unwrap();
+    log.append("<a> <b> <c> .").unwrap();
+    log.append("-<d> <e> <f> .").unwrap();
+    // A change cut short by a crash was never acknowledged.
+    fs::OpenOptions::new()
+        .append(true)
+        .open(&path)
+        .unwrap()
+        .write_all(b"<g> <h>")
+        .unwrap();
+    assert_eq!(
+        wal::read(&path).unwrap(),
+        ["<a> <b> <c> .", "-<d> <e> <f> ."]
+    );
+
+    wal::rotate(&path).unwrap();
+    assert!(!path.exists());
+    wal::rotate(&path).unwrap();
+}
+
+#[test]
+fn line_reader_applies_the_line_limit() {
+    let text = "<a> <b> \"\u{e9}\u{e9}\u{e9}\" .\r\nshort\n\nlast";
+    let read = |policy| {
+        let limit = io::LineLimit {
+            max_bytes: 12,
+            policy,
+        };
+        io::LineReader::new(text.as_bytes(), Some(limit)).collect::<Result<Vec<String>, _>>()
+    };
+
+    // Without a limit the lines are those of `lines`.
+    let all: Vec<String> = io::LineReader::new(text.as_bytes(), None)
+        .collect::<Result<_, _>>()
+        .unwrap();
+    let expected: Vec<String> = text.lines().map(String::from).collect();
+    assert_eq!(all, expected);
+
+    let err = read(io::LongLines::Fail).err().unwrap();
+    assert_eq!(err.kind(), ErrorKind::InvalidData);
+    assert_eq!(err.to_string(), "line 1 is longer than 12 bytes");
+
+    assert_eq!(read(io::LongLines::Skip).unwrap(), ["short", "", "last"]);
+
+    // The cut falls inside the second é, which is dropped.
+    assert_eq!(
+        read(io::LongLines::Truncate).unwrap(),
+        ["<a> <b> \"\u{e9}", "short", "", "last"]
+    );
+}
+
+#[test]
+fn lru_drops_the_least_recently_used_value() {
+    let mut lru: Lru<u32, u32> = Lru::new(3);
+    for k in [1, 2, 3] {
+        assert_eq!(lru.get_or_load(k, || Some(k * 10)), Some(k * 10));
+    }
+    // 1 is used again, so 2 is the least recently used when 4 needs room.
+    assert_eq!(lru.get_or_load(1, || None), Some(10));
+    lru.get_or_load(4, || Some(40));
+    assert_eq!(lru.len(), 3);
+    assert_eq!(lru.get_or_load(2, || None), None);
+
+    // Removing a value in the middle keeps the order of the others.
+    lru.remove(&1);
+    lru.get_or_load(5, || Some(50));
+    lru.get_or_load(6, || Some(60));
+    assert_eq!(lru.get_or_load(3, || None), None);
+    for k in [4, 5, 6] {
+        assert_eq!(lru.get_or_load(k, || None), Some(k * 10));
+    }
+    let stats = lru.stats();
+    assert_eq!((stats.hits, stats.misses), (4, 8));
+    assert!((stats.hit_rate() - 1.0 / 3.0).abs() < 1e-9);
+}
+
+#[test]
+fn failures_have_distinct_exit_codes() {
+    let failures = [
+        Failure::Arguments,
+        Failure::Parse,
+        Failure::Validation,
+        Failure::Conflict,
+        Failure::Io,
+        Failure::Inconsistent,
+    ];
+    for (i, a) in failures.iter().enumerate() {
+        assert!(a.code() > 1);
+        assert!(failures[i + 1..].iter().all(|b| b.code() != a.code()));
+    }
+
+    let error = |kind| std::io::Error::new(kind, "");
+    assert_eq!(Failure::of(&error(ErrorKind::InvalidData)), Failure::Parse);
+    assert_eq!(
+        Failure::of(&error(ErrorKind::WouldBlock)),
+        Failure::Conflict
+    );
+    assert_eq!(Failure::of(&error(ErrorKind::NotFound)), Failure::Io);
+}
+
+#[test]
+#[cfg(not(feature = "std-hash"))]
+fn fx_hash_depends_on_every_byte() {
+    use crate::util::hash::FxHasher;
+    use std::hash::Hasher;
+
+    let hash = |bytes: &[u8]| {
+        let mut hasher = FxHasher::default();
+        hasher.write(bytes);
+        hasher.finish()
+    };
+    assert_eq!(hash(b"<http://ex.org/a>"), hash(b"<http://ex.org/a>"));
+    assert_ne!(hash(b"<http://ex.org/a>"), hash(b"<http://ex.org/b>"));
+    assert_ne!(hash(b"ab"), hash(b"ba"));
+    assert_ne!(hash(b"0123456789"), hash(b"0123456788"));
+}
+
+#[test]
+#[cfg(any(feature = "endpoint", feature = "remote"))]
+fn http_urls_parse_into_host_port_and_path() {
+    use crate::util::http::Url;
+
+    let url = |host: &str, port, path: &str| Url {
+        host: host.to_string(),
+        port,
+        path: path.to_string(),
+    };
+    assert_eq!(
+        Url::parse("http://ex.org/sparql?q=1").unwrap(),
+        url("ex.org", 80, "/sparql?q=1")
+    );
+    assert_eq!(
+        Url::parse("http://localhost:3030").unwrap(),
+        url("localhost", 3030, "/")
+    );
+    assert!(Url::parse("https://ex.org/sparql").is_err());
+    assert!(Url::parse("http://ex.org:port/").is_err());
+}
+
+#[test]
+#[cfg(any(feature = "endpoint", feature = "remote"))]
+fn http_requests_are_framed_and_responses_decoded() {
+    use crate::util::http::{parse_response, request, Url};
+
+    let url = Url::parse("http://ex.org:8080/update").unwrap();
+    let framed = request("POST", &url, &[("Content-Type", "text/plain")], "abc");
+    assert_eq!(
+        framed,
+        "POST /update HTTP/1.1\r\nHost: ex.org\r\nConnection: close\r\nContent-Length: 3\r\n\
+         Content-Type: text/plain\r\n\r\nabc"
+    );
+
+    let plain = b"HTTP/1.1 200 OK\r\nContent-Length: 2\r\n\r\nok";
+    assert_eq!(parse_response(plain).unwrap(), "ok");
+    let chunked =
+        b"HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n3\r\nabc\r\n2;x\r\nde\r\n0\r\n\r\n";
+    assert_eq!(parse_response(chunked).unwrap(), "abcde");
+    let failed = b"HTTP/1.1 400 Bad Request\r\n\r\nno";
+    assert!(parse_response(failed).is_err());
+    assert!(parse_response(b"HTTP/1.1 200 OK\r\n").is_err());
+}
+
+#[test]
+#[cfg(any(feature = "endpoint", feature = "remote"))]
+fn http_requests_time_out_on_a_silent_server() {
+    use crate::util::http;
+    use std::io::ErrorKind;
+    use std::net::TcpListener;
+    use std::time::{Duration, Instant};
+
+    // The listener accepts the connection but never answers.
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}/", listener.local_addr().unwrap());
+    let start = Instant::now();
+    let e = http::get(&url, "text/plain", Duration::from_millis(200)).unwrap_err();
+    assert_eq!(e.kind(), ErrorKind::TimedOut);
+    assert!(e.to_string().contains("did not answer"), "{}", e);
+    assert!(start.elapsed() < Duration::from_secs(5));
+    drop(listener);
+}
+
+#[test]
+#[cfg(feature = "zstd")]
+fn zstd_round_trips_and_rejects_truncated_frames() {
+    use crate::util::zstd;
+
+    // Longer than one output chunk of the decompression stream.
+    let data: Vec<u8> = (0..1_000_000u32)
+        .flat_map(|i| (i % 977).to_le_bytes())
+        .collect();
+    let compressed = zstd::compress(&data, 3).unwrap();
+    assert!(compressed.len() < data.len());
+    assert_eq!(zstd::decompress(&compressed).unwrap(), data);
+
+    let truncated = &compressed[..compressed.len() / 2];
+    assert!(zstd::decompress(truncated).is_err());
+    assert!(zstd::decompress(b"not zstd").is_err());
+}
@@ -0,0 +1,727 @@
+use crate::util::hash::HashSet;
+use crate::{
+    metrics::timing::{self, Phase, UpdateTimings},
+    models::{
+        clique::CliqueChange,
+        clique::CliqueCollection,
+        dataset::Dataset,
+        meta::Meta,
+        triple::{Triple, TYPE_STRING},
+    },
+    util::{profile::span, rng::fnv1a, set_ops::get_disjoint_sets},
+};
+use crate::{Coalescing, Directions, DuplicatePolicy, SplitStrategy};
+use std::fmt;
+use std::time::Instant;
+mod deletion;
+mod insertion;
+pub mod recluster;
+
+/// Counts of changes that were not applied structurally.
+#[derive(Default)]
+pub struct UpdateReport {
+    /// Insertions of triples that already existed.
+    pub duplicate_insertions: usize,
+    /// Insertions and deletions skipped because the update also has the opposite change.
+    pub coalesced: usize,
+    /// Supernodes split up because their members no longer agreed on their cliques, or created
+    /// by a re-clustering pass.
+    pub reclustered: usize,
+    /// Deletions of triples that do not exist, in changeset format.
+    pub rejected_deletions: Vec<String>,
+    /// Changes left unapplied because the deadline passed, in changeset format.
+    pub pending: Vec<String>,
+    /// Changes skipped in lenient mode because the summary is inconsistent around them, each
+    /// after a comment giving the reason, in changeset format. Inconsistencies found by
+    /// verifying the cliques are only a comment.
+    pub inconsistent: Vec<String>,
+    /// Time spent in each phase, per batch.
+    pub timings: UpdateTimings,
+}
+
+/// Options controlling how an update is applied.
+#[derive(Default)]
+pub struct Options {
+    pub duplicates: DuplicatePolicy,
+    pub coalescing: Coalescing,
+    pub split_strategy: SplitStrategy,
+    /// Verify clique invariants after each batch of changes.
+    pub paranoid: bool,
+    /// Stop before the next change once this instant has passed.
+    pub deadline: Option<Instant>,
+    /// Largest number of nodes a merge may put into one supernode.
+    pub max_supernode_size: Option<usize>,
+    /// Re-cluster the whole summary after applying the changes.
+    pub recluster: bool,
+    /// Whether nodes are grouped by their source and target cliques apart or together.
+    pub directions: Directions,
+    /// Skip changes the summary is inconsistent around instead of failing.
+    pub lenient: bool,
+}
+
+impl Options {
+    fn deadline_passed(&self) -> bool {
+        self.deadline.is_some_and(|d| Instant::now() >= d)
+    }
+}
+
+/// Applies the insertions and then the deletions of `dataset`.
+///
+/// If the deadline in `options` passes, the update stops between two changes, leaving the
+/// summary consistent. The changes that were not applied are returned in `pending`, so they
+/// can be applied by a later run.
+pub fn run(
+    dataset: &mut Dataset,
+    meta: &mut Meta,
+    sc: &mut CliqueCollection,
+    tc: &mut CliqueCollection,
+    options: &Options,
+) -> Result<UpdateReport, String> {
+    span!("update");
+    let mut report = begin(dataset, meta, options)?;
+    apply(dataset, meta, sc, tc, options, 0, &mut report)?;
+    finish(dataset, meta, sc, tc, options, &mut report)?;
+    Ok(report)
+}
+
+/// Number of changes applied between two snapshots of a large update by default, see
+/// [`run_in_chunks`].
+pub const CHUNK_SIZE: usize = 1_000_000;
+
+/// How far an update applied in chunks got.
+pub struct ChunkProgress {
+    /// Number of the chunk just applied, from 1.
+    pub chunk: usize,
+    pub chunks: usize,
+    /// Changes applied so far, out of `changes`.
+    pub applied: usize,
+    pub changes: usize,
+}
+
+impl fmt::Display for ChunkProgress {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Applied chunk {} of {}: {} of {} changes",
+            self.chunk, self.chunks, self.applied, self.changes
+        )
+    }
+}
+
+/// Like [`run`], but applies the insertions and then the deletions `chunk_size` at a time, and
+/// calls `flush` with the summary after each chunk but the last, to write a snapshot of it.
+///
+/// The changes are applied in the same order as by `run`. Each chunk ends like an update of its
+/// own: lazily split supernodes are re-partitioned and aliases assigned, so the snapshot is a
+/// summary a later run can continue from. Re-clustering the whole summary and joint grouping
+/// are left to the end. Updates of at most `chunk_size` changes are applied by `run`.
+pub fn run_in_chunks<F>(
+    dataset: &mut Dataset,
+    meta: &mut Meta,
+    sc: &mut CliqueCollection,
+    tc: &mut CliqueCollection,
+    options: &Options,
+    chunk_size: usize,
+    mut flush: F,
+) -> Result<UpdateReport, String>
+where
+    F: FnMut(&ChunkProgress, &Dataset, &Meta, &CliqueCollection, &CliqueCollection),
+{
+    let changes = dataset.insertions.data_triples.len() + dataset.deletions.data_triples.len();
+    if changes <= chunk_size {
+        return run(dataset, meta, sc, tc, options);
+    }
+    span!("update");
+    let mut report = begin(dataset, meta, options)?;
+    let insertions = std::mem::take(&mut dataset.insertions.data_triples);
+    let deletions = std::mem::take(&mut dataset.deletions.data_triples);
+    let chunks: Vec<(&[Triple], bool)> = insertions
+        .chunks(chunk_size)
+        .map(|c| (c, false))
+        .chain(deletions.chunks(chunk_size).map(|c| (c, true)))
+        .collect();
+
+    let mut progress = ChunkProgress {
+        chunk: 0,
+        chunks: chunks.len(),
+        applied: 0,
+        changes: insertions.len() + deletions.len(),
+    };
+    for (chunk, is_deletion) in &chunks {
+        if !report.pending.is_empty() {
+            // The deadline passed in an earlier chunk.
+            for t in *chunk {
+                let line = t.to_string(dataset);
+                report.pending.push(match is_deletion {
+                    true => format!("-{}", line),
+                    false => line,
+                });
+            }
+            continue;
+        }
+        let (i, d) = match is_deletion {
+            true => (Vec::new(), chunk.to_vec()),
+            false => (chunk.to_vec(), Vec::new()),
+        };
+        dataset.insertions.data_triples = i;
+        dataset.deletions.data_triples = d;
+        apply(
+            dataset,
+            meta,
+            sc,
+            tc,
+            options,
+            progress.applied,
+            &mut report,
+        )?;
+
+        progress.chunk += 1;
+        progress.applied += chunk.len();
+        if progress.chunk < progress.chunks && report.pending.is_empty() {
+            meta.assign_aliases(|node| alias(&dataset.key_by_value(node).unwrap()));
+            add_types_to_dataset(dataset, meta);
+            flush(&progress, dataset, meta, sc, tc);
+            remove_types_from_dataset(dataset);
+        }
+    }
+    dataset.insertions.data_triples = insertions;
+    dataset.deletions.data_triples = deletions;
+
+    finish(dataset, meta, sc, tc, options, &mut report)?;
+    Ok(report)
+}
+
+/// Prepares `dataset` for its changes to be applied, and drops the changes that undo each
+/// other.
+fn begin(dataset: &mut Dataset, meta: &Meta, options: &Options) -> Result<UpdateReport, String> {
+    dataset.check_ids().map_err(|e| e.to_string())?;
+    dataset.triples.build_index();
+    let mut report = UpdateReport::default();
+    timing::take();
+    report.coalesced = coalesce(dataset, meta, options.coalescing);
+    Ok(report)
+}
+
+/// Applies the insertions and then the deletions of `dataset`, after `applied` changes of the
+/// update were applied already.
+fn apply(
+    dataset: &mut Dataset,
+    meta: &mut Meta,
+    sc: &mut CliqueCollection,
+    tc: &mut CliqueCollection,
+    options: &Options,
+    applied: usize,
+    report: &mut UpdateReport,
+) -> Result<(), String> {
+    for i in 0..dataset.insertions.data_triples.len() {
+        if options.deadline_passed() {
+            report.pending = pending_changes(dataset, i, 0);
+            break;
+        }
+
+        let triple = &dataset.insertions.data_triples[i];
+        if let Some(reason) = inconsistency(triple, dataset, meta, sc, tc) {
+            let line = triple.to_string(dataset);
+            skip_inconsistent(report, reason, line, options.lenient)?;
+            continue;
+        }
+        if meta.contains_edge(&triple.sub, &triple.pred, &triple.obj) {
+            let _phase = timing::phase(Phase::MetaEdges);
+            match options.duplicates {
+                DuplicatePolicy::Dedupe => {}
+                DuplicatePolicy::Count => {
+                    meta.add_outgoing(triple);
+                    meta.add_incoming(triple);
+                }
+                DuplicatePolicy::Error => {
+                    return Err(format!("duplicate insertion {}", triple.to_string(dataset)));
+                }
+            }
+            report.duplicate_insertions += 1;
+            continue;
+        }
+
+        let changes = insertion::get_changes(
+            &dataset.insertions.data_triples[i].clone(),
+            dataset,
+            meta,
+            sc,
+            tc,
+        );
+
+        if changes.is_empty() {
+            continue;
+        }
+
+        let snodes = get_super_nodes(changes, sc, tc);
+        let line = dataset.insertions.data_triples[i].to_string(dataset);
+        let snodes = drop_unknown(snodes, meta, report, line, options.lenient)?;
+        let snodes = separate_seeds(snodes, dataset, meta);
+        let snodes = cap_super_nodes(snodes, meta, options.max_supernode_size);
+        apply_changes(dataset, meta, &snodes, sc, tc);
+        report.reclustered += handle_drift(
+            dataset,
+            meta,
+            sc,
+            tc,
+            options.split_strategy,
+            applied + i + 1,
+        );
+    }
+    report.timings.insertions.add_all(&timing::take());
+    dataset.check_ids().map_err(|e| e.to_string())?;
+    check_cliques(meta, sc, tc, options, "insertions", report)?;
+
+    for i in 0..dataset.deletions.data_triples.len() {
+        if !report.pending.is_empty() {
+            // The insertions were interrupted, so all deletions are still pending.
+            break;
+        }
+        if options.deadline_passed() {
+            report.pending = pending_changes(dataset, dataset.insertions.data_triples.len(), i);
+            break;
+        }
+
+        let triple = &dataset.deletions.data_triples[i];
+        if !meta.contains_edge(&triple.sub, &triple.pred, &triple.obj) {
+            report
+                .rejected_deletions
+                .push(format!("-{}", triple.to_string(dataset)));
+            continue;
+        }
+        if let Some(reason) = inconsistency(triple, dataset, meta, sc, tc) {
+            let line = format!("-{}", triple.to_string(dataset));
+            skip_inconsistent(report, reason, line, options.lenient)?;
+            continue;
+        }
+        if options.duplicates == DuplicatePolicy::Count && meta.edge_multiplicity(triple) > 1 {
+            let _phase = timing::phase(Phase::MetaEdges);
+            meta.remove_edge_once(triple);
+            continue;
+        }
+
+        let changes = deletion::delete_triple(
+            &dataset.deletions.data_triples[i].clone(),
+            dataset,
+            meta,
+            sc,
+            tc,
+        );
+
+        if changes.is_empty() {
+            continue;
+        }
+
+        let snodes = get_super_nodes(changes, sc, tc);
+        let line = format!("-{}", dataset.deletions.data_triples[i].to_string(dataset));
+        let snodes = drop_unknown(snodes, meta, report, line, options.lenient)?;
+        let snodes = separate_seeds(snodes, dataset, meta);
+        let snodes = cap_super_nodes(snodes, meta, options.max_supernode_size);
+        apply_changes(dataset, meta, &snodes, sc, tc);
+    }
+
+    if let SplitStrategy::Lazy(_) = options.split_strategy {
+        report.reclustered += recluster::recluster(dataset, meta, sc, tc);
+    }
+    report.timings.deletions.add_all(&timing::take());
+    dataset.check_ids().map_err(|e| e.to_string())?;
+    check_cliques(meta, sc, tc, options, "deletions", report)?;
+    Ok(())
+}
+
+/// Re-clusters the summary after the changes were applied, as `options` ask for, and names
+/// the new supernodes.
+fn finish(
+    dataset: &mut Dataset,
+    meta: &mut Meta,
+    sc: &mut CliqueCollection,
+    tc: &mut CliqueCollection,
+    options: &Options,
+    report: &mut UpdateReport,
+) -> Result<(), String> {
+    if options.recluster && report.pending.is_empty() {
+        report.reclustered +=
+            recluster::recluster_all(dataset, meta, sc, tc, options.max_supernode_size);
+        check_cliques(meta, sc, tc, options, "re-clustering", report)?;
+    }
+    if options.directions == Directions::Joint && report.pending.is_empty() {
+        recluster::group_jointly(dataset, meta, sc, tc, options.max_supernode_size);
+        check_cliques(meta, sc, tc, options, "joint grouping", report)?;
+    }
+    if !dataset.seeds.is_empty() && report.pending.is_empty() {
+        recluster::join_seeds(dataset, meta, sc, tc);
+        check_cliques(meta, sc, tc, options, "seed grouping", report)?;
+    }
+
+    meta.assign_aliases(|node| alias(&dataset.key_by_value(node).unwrap()));
+    add_types_to_dataset(dataset, meta);
+    Ok(())
+}
+
+/// Deals with the supernodes a split left behind after the `applied`th insertion.
+///
+/// `Eject` leaves them as they are, `Repartition` re-partitions them right away and `Lazy`
+/// every so many insertions, and once more at the end of the update. Returns the number of
+/// supernodes that were split up.
+fn handle_drift(
+    dataset: &mut Dataset,
+    meta: &mut Meta,
+    sc: &mut CliqueCollection,
+    tc: &mut CliqueCollection,
+    strategy: SplitStrategy,
+    applied: usize,
+) -> usize {
+    match strategy {
+        SplitStrategy::Eject => {
+            meta.take_drifted();
+            0
+        }
+        SplitStrategy::Lazy(every) if !applied.is_multiple_of(every) => 0,
+        SplitStrategy::Repartition | SplitStrategy::Lazy(_) => {
+            recluster::recluster(dataset, meta, sc, tc)
+        }
+    }
+}
+
+/// Returns the alias of a supernode named after its member `iri`: the 64-bit FNV-1a hash of
+/// the IRI in hex, which does not depend on internal ids.
+fn alias(iri: &str) -> String {
+    format!("{:016x}", fnv1a(iri))
+}
+
+/// Drops insertions and deletions of the same triple from the update, so the triple's
+/// supernodes are not split and merged again for nothing.
+///
+/// With `Coalescing::Sequence`, the change that comes last is kept unless the summary already
+/// matches it. With `Coalescing::Set`, both changes are dropped. Returns the number of dropped
+/// changes.
+fn coalesce(dataset: &mut Dataset, meta: &Meta, coalescing: Coalescing) -> usize {
+    span!("coalesce");
+    let deleted: HashSet<&Triple> = dataset.deletions.data_triples.iter().collect();
+    let paired: HashSet<Triple> = dataset
+        .insertions
+        .data_triples
+        .iter()
+        .filter(|t| deleted.contains(t))
+        .cloned()
+        .collect();
+    if paired.is_empty() {
+        return 0;
+    }
+
+    // Which change of each pair is applied.
+    let (mut insert, mut delete): (HashSet<&Triple>, HashSet<&Triple>) =
+        (HashSet::default(), HashSet::default());
+    if coalescing == Coalescing::Sequence {
+        for t in &paired {
+            let exists = meta.contains_edge(&t.sub, &t.pred, &t.obj);
+            if dataset.reinserted.contains(t) && !exists {
+                insert.insert(t);
+            } else if !dataset.reinserted.contains(t) && exists {
+                delete.insert(t);
+            }
+        }
+    }
+
+    let before = dataset.insertions.data_triples.len() + dataset.deletions.data_triples.len();
+    dataset
+        .insertions
+        .data_triples
+        .retain(|t| !paired.contains(t) || insert.contains(t));
+    dataset
+        .deletions
+        .data_triples
+        .retain(|t| !paired.contains(t) || delete.contains(t));
+    before - dataset.insertions.data_triples.len() - dataset.deletions.data_triples.len()
+}
+
+/// Returns the insertions from index `insertions` and the deletions from index `deletions` on,
+/// in changeset format.
+fn pending_changes(dataset: &Dataset, insertions: usize, deletions: usize) -> Vec<String> {
+    let mut pending: Vec<String> = Vec::new();
+    for t in &dataset.insertions.data_triples[insertions..] {
+        pending.push(t.to_string(dataset));
+    }
+    for t in &dataset.deletions.data_triples[deletions..] {
+        pending.push(format!("-{}", t.to_string(dataset)));
+    }
+    pending
+}
+
+/// Returns what is inconsistent in the summary around the nodes of `triple`, if anything: a
+/// node whose parent is not a supernode, or a node of the meta whose supernode or itself is in
+/// no clique. Applying the change would fail halfway then.
+fn inconsistency(
+    triple: &Triple,
+    dataset: &Dataset,
+    meta: &Meta,
+    sc: &CliqueCollection,
+    tc: &CliqueCollection,
+) -> Option<String> {
+    let term = |n: &u32| dataset.key_by_value(n).unwrap_or_else(|| n.to_string());
+    for (node, cliques) in [(&triple.sub, sc), (&triple.obj, tc)] {
+        // Nodes the meta does not have yet are added by the insertion.
+        let Some(info) = meta.get_node(node) else {
+            continue;
+        };
+        if let Some(parent) = info.parent {
+            if !meta.contains_supernode(&parent) {
+                return Some(format!("{} has unknown parent {}", term(node), parent));
+            }
+        }
+        if !cliques.contains_node(&info.parent.unwrap_or(*node)) {
+            return Some(format!("{} is missing from the cliques", term(node)));
+        }
+    }
+    None
+}
+
+/// Records the change `line` as skipped for the inconsistent summary `reason` gives, or fails
+/// with it unless `lenient`.
+fn skip_inconsistent(
+    report: &mut UpdateReport,
+    reason: String,
+    line: String,
+    lenient: bool,
+) -> Result<(), String> {
+    if !lenient {
+        return Err(format!("{}, at {}", reason, line));
+    }
+    report.inconsistent.push(format!("# {}", reason));
+    report.inconsistent.push(line);
+    Ok(())
+}
+
+/// Removes the nodes the meta does not have from the groups `snodes` the change `line` merges,
+/// which the cliques of an inconsistent summary can still hold. The change is then applied only
+/// partly, so it is recorded as skipped, or fails unless `lenient`.
+fn drop_unknown(
+    snodes: Vec<Vec<u32>>,
+    meta: &Meta,
+    report: &mut UpdateReport,
+    line: String,
+    lenient: bool,
+) -> Result<Vec<Vec<u32>>, String> {
+    let unknown: Vec<u32> = snodes
+        .iter()
+        .flatten()
+        .filter(|n| !meta.contains(n))
+        .copied()
+        .collect();
+    if unknown.is_empty() {
+        return Ok(snodes);
+    }
+    let reason = format!(
+        "the cliques hold {:?}, which the meta does not have",
+        unknown
+    );
+    skip_inconsistent(report, reason, line, lenient)?;
+    Ok(snodes
+        .into_iter()
+        .map(|snode| {
+            snode
+                .into_iter()
+                .filter(|n| meta.contains(n))
+                .collect::<Vec<u32>>()
+        })
+        .filter(|snode| snode.len() >= 2)
+        .collect())
+}
+
+/// Verifies the cliques after the `batch` of changes if `paranoid` or `lenient` is set. Debug
+/// builds always verify and panic on a violation, unless `lenient` records it in the `report`.
+fn check_cliques(
+    meta: &Meta,
+    sc: &CliqueCollection,
+    tc: &CliqueCollection,
+    options: &Options,
+    batch: &str,
+    report: &mut UpdateReport,
+) -> Result<(), String> {
+    if !options.paranoid && !options.lenient && !cfg!(debug_assertions) {
+        return Ok(());
+    }
+    let Err(e) = verify_cliques(meta, sc, tc) else {
+        return Ok(());
+    };
+    if options.lenient {
+        report
+            .inconsistent
+            .push(format!("# after {}: {}", batch, e));
+        Ok(())
+    } else if options.paranoid {
+        Err(format!("after {}: {}", batch, e))
+    } else {
+        panic!("Clique invariant violated after {}: {}", batch, e);
+    }
+}
+
+/// Checks that every node of the summary is in exactly one source and one target clique.
+pub fn verify_cliques(
+    meta: &Meta,
+    sc: &CliqueCollection,
+    tc: &CliqueCollection,
+) -> Result<(), String> {
+    span!("verify_cliques");
+    let nodes = meta.top_level_nodes();
+    sc.verify(&nodes)
+        .map_err(|e| format!("source cliques: {}", e))?;
+    tc.verify(&nodes)
+        .map_err(|e| format!("target cliques: {}", e))
+}
+
+pub fn get_super_nodes(
+    changes: Vec<CliqueChange>,
+    sc: &mut CliqueCollection,
+    tc: &mut CliqueCollection,
+) -> Vec<Vec<u32>> {
+    span!("get_super_nodes");
+    let _phase = timing::phase(Phase::Supernodes);
+    if changes.len() == 1 {
+        return changes[0].clone().get_super_nodes(sc, tc);
+    }
+
+    let mut snodes = changes[0].clone().get_super_nodes(sc, tc);
+    snodes.extend(changes[1].clone().get_super_nodes(sc, tc));
+    if snodes.len() <= 1 {
+        return snodes;
+    }
+
+    return get_disjoint_sets(snodes);
+}
+
+/// Splits groups that would merge into a supernode of more than `max` nodes.
+///
+/// Members of a group are packed first-fit, largest first, into groups of at most `max` nodes.
+/// Existing supernodes are never split, so one that is already larger than `max` is left on
+/// its own. Groups left with a single member are dropped, as there is nothing to merge.
+fn cap_super_nodes(snodes: Vec<Vec<u32>>, meta: &Meta, max: Option<usize>) -> Vec<Vec<u32>> {
+    let max = match max {
+        Some(max) => max,
+        None => return snodes,
+    };
+    let size = |n: &u32| meta.get_supernode(n).map_or(1, |s| s.len());
+
+    let mut capped: Vec<Vec<u32>> = Vec::new();
+    for mut snode in snodes {
+        if snode.iter().map(size).sum::<usize>() <= max {
+            capped.push(snode);
+            continue;
+        }
+
+        snode.sort_by_key(|n| (std::cmp::Reverse(size(n)), *n));
+        let mut bins: Vec<(usize, Vec<u32>)> = Vec::new();
+        for n in snode {
+            match bins.iter_mut().find(|(len, _)| len + size(&n) <= max) {
+                Some((len, members)) => {
+                    *len += size(&n);
+                    members.push(n);
+                }
+                None => bins.push((size(&n), vec![n])),
+            }
+        }
+        capped.extend(
+            bins.into_iter()
+                .map(|(_, members)| members)
+                .filter(|members| members.len() >= 2),
+        );
+    }
+    capped
+}
+
+/// Splits groups that would merge nodes of different seed groups of `dataset`.
+///
+/// Each node of a group goes with the nodes of its seed group, and nodes without one with the
+/// first seed group of the group. Groups left with a single member are dropped, as in
+/// [`cap_super_nodes`].
+fn separate_seeds(snodes: Vec<Vec<u32>>, dataset: &Dataset, meta: &Meta) -> Vec<Vec<u32>> {
+    if dataset.seeds.is_empty() {
+        return snodes;
+    }
+    let mut separated: Vec<Vec<u32>> = Vec::new();
+    for snode in snodes {
+        let mut bins: Vec<(Option<u32>, Vec<u32>)> = Vec::new();
+        for n in snode {
+            let group = seed_group(&n, dataset, meta);
+            match bins
+                .iter_mut()
+                .find(|(g, _)| g.is_none() || group.is_none() || *g == group)
+            {
+                Some((g, members)) => {
+                    *g = g.or(group);
+                    members.push(n);
+                }
+                None => bins.push((group, vec![n])),
+            }
+        }
+        separated.extend(
+            bins.into_iter()
+                .map(|(_, members)| members)
+                .filter(|members| members.len() >= 2),
+        );
+    }
+    separated
+}
+
+/// Returns the seed group of `node`, or of the members of the supernode `node`, which all
+/// share the same one if any has one.
+fn seed_group(node: &u32, dataset: &Dataset, meta: &Meta) -> Option<u32> {
+    let group = |n: &u32| dataset.term(n).and_then(|t| dataset.seeds.group(&t));
+    match meta.get_supernode(node) {
+        Some(members) => members.iter().find_map(group),
+        None => group(node),
+    }
+}
+
+fn apply_changes(
+    dataset: &mut Dataset,
+    meta: &mut Meta,
+    snodes: &Vec<Vec<u32>>,
+    sc: &mut CliqueCollection,
+    tc: &mut CliqueCollection,
+) {
+    span!("apply_changes");
+    let _phase = timing::phase(Phase::Supernodes);
+    for snode in snodes {
+        let new_node = dataset.new_snode(snode, meta);
+        meta.new_snode(snode, &new_node);
+        sc.new_snode(snode, &new_node);
+        tc.new_snode(snode, &new_node);
+    }
+}
+
+/// Adds the types kept aside in `meta` to the summary, with each node replaced by its supernode.
+///
+/// Type triples added by an earlier run on the same dataset are replaced, so types deleted or
+/// nodes moved since are not left behind.
+fn add_types_to_dataset(dataset: &mut Dataset, meta: &mut Meta) {
+    span!("add_types_to_dataset");
+    remove_types_from_dataset(dataset);
+    if meta.get_types().is_empty() {
+        return;
+    }
+    let type_pred = dataset.pred_id_of(TYPE_STRING).unwrap();
+    for [s, o] in meta.get_types() {
+        if meta.contains(s) {
+            dataset.triples.add_data_triple(&Triple::new(
+                meta.get_parent(s).unwrap_or(*s),
+                type_pred,
+                *o,
+                true,
+            ));
+        } else {
+            dataset
+                .triples
+                .add_data_triple(&Triple::new(*s, type_pred, *o, true));
+        }
+    }
+}
+
+/// Removes the type triples `add_types_to_dataset` added, which the updater keeps in the meta.
+fn remove_types_from_dataset(dataset: &mut Dataset) {
+    if let Some(type_pred) = dataset.pred_id_of(TYPE_STRING) {
+        dataset
+            .triples
+            .retain(|t| !(t.is_type && t.pred == type_pred));
+    }
+}
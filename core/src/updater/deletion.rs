@@ -0,0 +1,176 @@
+use crate::{
+    metrics::timing::{self, Phase},
+    models::{
+        clique::{CliqueChange, CliqueCollection, CliqueSide, Direction},
+        dataset::Dataset,
+        meta::Meta,
+        triple::Triple,
+    },
+    util::{
+        profile::span,
+        set_ops::{get_disjoint_sets, intersection, intersects},
+    },
+};
+
+pub fn delete_triple(
+    triple: &Triple,
+    dataset: &mut Dataset,
+    meta: &mut Meta,
+    sc: &mut CliqueCollection,
+    tc: &mut CliqueCollection,
+) -> Vec<CliqueChange> {
+    span!("deletion");
+    {
+        let _phase = timing::phase(Phase::MetaEdges);
+        prepare_triple(triple, meta, dataset);
+    }
+    let _phase = timing::phase(Phase::Cliques);
+
+    // If both endpoints are members of the same supernode, the edge is part of its self-loop,
+    // which a split from either side rewrites into the edges between the node split off and the
+    // members left. The source side may already dissolve or shrink that supernode, so the target
+    // side looks up the parent of the object again rather than sharing the one of the subject.
+    let mut changes: Vec<CliqueChange> = Vec::new();
+    for direction in [Direction::Source, Direction::Target] {
+        if let Some(change) = delete(triple, dataset, meta, CliqueSide::new(direction, sc, tc)) {
+            changes.push(change);
+        }
+    }
+    return changes;
+}
+
+fn delete(
+    triple: &Triple,
+    dataset: &mut Dataset,
+    meta: &mut Meta,
+    side: CliqueSide,
+) -> Option<CliqueChange> {
+    let node = side.direction.node(triple);
+    let n = meta.get_parent(node).unwrap_or(*node);
+
+    // CASE 1: If node is not in a supernode
+    if *node == n {
+        // Case 1.1: If node has no incoming/outgoing edges left, move to empty clique
+        if meta.has_no_edges_left(node, side.direction.is_source()) {
+            side.cliques.move_node_to_empty_clique(node);
+            return Some(side.change(0, vec![*node]));
+        }
+    }
+
+    // CASE 2: Check if the clique has to be split. A member of a supernode with no edges left
+    // moves to the empty clique as in case 1.1, so its supernode is split up too.
+    let (mut singlenodes, supernodes, mut edges) =
+        side.cliques
+            .get_all_edges(&n, side.direction.is_source(), meta);
+    let new_clique_preds = get_disjoint_sets(edges.clone());
+    if new_clique_preds.len() == 1 && !meta.has_no_edges_left(node, side.direction.is_source()) {
+        return None;
+    }
+
+    remove_supernodes(&supernodes, meta, dataset, side.cliques, side.other);
+
+    return split_clique_by_preds(
+        node,
+        &mut singlenodes,
+        supernodes,
+        &mut edges,
+        new_clique_preds,
+        meta,
+        dataset,
+        side,
+    );
+}
+
+fn prepare_triple(triple: &Triple, meta: &mut Meta, dataset: &mut Dataset) {
+    meta.remove_incoming(triple);
+    meta.remove_outgoing(triple);
+    // The summary has the triple between the supernodes of its endpoints, as insertions add it,
+    // and keeps it while other members of those still have an edge with the predicate.
+    let summary_triple = Triple {
+        sub: meta.get_parent(&triple.sub).unwrap_or(triple.sub),
+        obj: meta.get_parent(&triple.obj).unwrap_or(triple.obj),
+        ..triple.clone()
+    };
+    if !meta.has_edge_between(
+        &summary_triple.sub,
+        &summary_triple.pred,
+        &summary_triple.obj,
+    ) {
+        dataset.remove_triple(&summary_triple);
+    }
+}
+
+fn remove_supernodes(
+    supernodes: &Vec<Vec<u32>>,
+    meta: &mut Meta,
+    dataset: &mut Dataset,
+    cc: &mut CliqueCollection,
+    other_cc: &mut CliqueCollection,
+) {
+    span!("remove_supernodes");
+    let _phase = timing::phase(Phase::Split);
+    for s in supernodes {
+        let parent = meta.get_parent(&s[0]).unwrap();
+        dataset.remove_supernode(&parent, s.to_vec(), meta);
+        cc.remove_supernode(&parent, meta);
+        other_cc.remove_supernode(&parent, meta);
+        meta.remove_supernode(&parent);
+    }
+}
+
+pub fn split_clique_by_preds(
+    target: &u32,
+    singlenodes: &mut Vec<u32>,
+    supernodes: Vec<Vec<u32>>,
+    edges: &mut Vec<Vec<u32>>,
+    clique_preds: Vec<Vec<u32>>,
+    meta: &mut Meta,
+    dataset: &mut Dataset,
+    side: CliqueSide,
+) -> Option<CliqueChange> {
+    span!("split_clique_by_preds");
+    let _phase = timing::phase(Phase::Split);
+    let index = side.cliques.get_index(target);
+
+    for preds in clique_preds {
+        if preds.len() == 0 {
+            continue;
+        }
+
+        let mut new_nodes: Vec<u32> = Vec::new();
+
+        for i in (0..singlenodes.len()).rev() {
+            if intersects(&edges[i], &preds) {
+                new_nodes.push(singlenodes[i]);
+                singlenodes.remove(i);
+                edges.remove(i);
+            }
+        }
+
+        side.cliques.detach(index, &preds, &new_nodes);
+        side.cliques.new_clique(&preds, &new_nodes);
+
+        for i in (0..supernodes.len()).rev() {
+            if let Some(intersec) = intersection(&supernodes[i], &new_nodes) {
+                if intersec.len() > 1 {
+                    let new_snode = dataset.new_snode(&intersec, meta);
+                    meta.new_snode(&intersec, &new_snode);
+                    side.cliques.new_snode(&intersec, &new_snode);
+                    side.other.new_snode(&intersec, &new_snode);
+                }
+            }
+        }
+    }
+
+    if singlenodes.is_empty() {
+        side.cliques.remove_clique_by_index(index);
+        None
+    } else {
+        // Nodes left have no preds in this direction. Only members of supernodes grouped with
+        // `Directions::Joint` can be more than one.
+        for n in singlenodes.iter() {
+            side.cliques.move_node_to_empty_clique(n);
+        }
+        Some(side.change(0, singlenodes.clone()))
+    }
+}
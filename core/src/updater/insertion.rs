@@ -1,9 +1,11 @@
+use crate::metrics::timing::{self, Phase};
 use crate::models::{
     clique::{CliqueChange, CliqueCollection},
     dataset::Dataset,
     meta::Meta,
     triple::Triple,
 };
+use crate::util::profile::span;
 
 pub fn get_changes(
     triple: &Triple,
@@ -12,7 +14,12 @@ pub fn get_changes(
     sc: &mut CliqueCollection,
     tc: &mut CliqueCollection,
 ) -> Vec<CliqueChange> {
-    prepare_triple(triple, dataset, meta, sc, tc);
+    span!("insertion");
+    {
+        let _phase = timing::phase(Phase::MetaEdges);
+        prepare_triple(triple, dataset, meta, sc, tc);
+    }
+    let _phase = timing::phase(Phase::Cliques);
     return insert_triple(triple, dataset, meta, sc, tc);
 }
 
@@ -33,7 +40,8 @@ fn prepare_triple(
         meta.add_outgoing(triple);
     }
 
-    if !obj_known {
+    // The object of a self-loop was added as the subject just above.
+    if !obj_known && triple.obj != triple.sub {
         sc.add_node_to_empty_clique(&triple.obj);
         tc.add_node_to_empty_clique(&triple.obj);
         meta.new_node(triple, false);
@@ -67,9 +75,23 @@ fn insert_triple(
     if let Some(change) = insert(triple, dataset, meta, sc, tc, true) {
         changes.push(change);
     }
+    // With both endpoints in one supernode, the target side may split the object off and leave
+    // a single member, which replaces the supernode the source side just moved.
+    let parent = meta.get_parent(&triple.obj);
+    let members = parent.and_then(|p| meta.get_supernode(&p).cloned());
     if let Some(change) = insert(triple, dataset, meta, tc, sc, false) {
         changes.push(change);
     }
+    if let (Some(parent), Some(members)) = (parent, members) {
+        if !meta.contains_supernode(&parent) {
+            let single = members.into_iter().find(|m| *m != triple.obj).unwrap();
+            for change in changes.iter_mut() {
+                for n in change.new_nodes.iter_mut().filter(|n| **n == parent) {
+                    *n = single;
+                }
+            }
+        }
+    }
     return changes;
 }
 
@@ -85,7 +107,7 @@ fn insert(
     let n = meta.get_parent(node).unwrap_or(*node);
 
     // CASE 1: If node and pred are in the same clique, return None
-    if cc.in_same_clique(&n, &triple.pred) {
+    if cc.in_pred_clique(&n, &triple.pred) {
         return None;
     }
 
@@ -100,7 +122,7 @@ fn insert(
     if !meta.has_parent(node) {
         cc.move_node(&node, &triple.pred);
         return Some(CliqueChange::new(
-            cc.get_index(&triple.pred),
+            cc.get_pred_index(&triple.pred),
             vec![*node],
             is_source,
         ));
@@ -110,7 +132,7 @@ fn insert(
     split(node, &triple.pred, cc, other_cc, dataset, meta);
 
     return Some(CliqueChange::new(
-        cc.get_index(&triple.pred),
+        cc.get_pred_index(&triple.pred),
         vec![*node],
         is_source,
     ));
@@ -124,6 +146,8 @@ fn split(
     dataset: &mut Dataset,
     meta: &mut Meta,
 ) {
+    span!("split");
+    let _phase = timing::phase(Phase::Split);
     let parent = meta.get_parent(node).unwrap();
     let to_single = meta.remove_from_supernode(node);
 
@@ -132,10 +156,11 @@ fn split(
     dataset.split(node, &parent, meta, to_single);
 
     if to_single {
-        let n = meta.get_supernode(&parent).unwrap()[0];
-        meta.to_single_node(&parent);
+        let n = meta.to_single_node(&parent);
         cc.to_single_node(&parent, &n);
         other_cc.to_single_node(&parent, &n);
         dataset.to_single_node(&parent, &n);
+    } else {
+        meta.mark_drifted(&parent);
     }
 }
@@ -0,0 +1,227 @@
+use super::{apply_changes, cap_super_nodes, separate_seeds};
+use crate::metrics::timing::{self, Phase};
+use crate::models::{clique::CliqueCollection, dataset::Dataset, meta::Meta};
+use crate::util::profile::span;
+use crate::util::set_ops::UnionFind;
+use std::collections::BTreeMap;
+
+/// Index of the clique a node belongs to, and a predicate of that clique unless it is the empty
+/// clique.
+type Home = (usize, Option<u32>);
+
+/// Re-partitions all supernodes marked as drifted. Returns the number of supernodes that were
+/// split up.
+pub fn recluster(
+    dataset: &mut Dataset,
+    meta: &mut Meta,
+    sc: &mut CliqueCollection,
+    tc: &mut CliqueCollection,
+) -> usize {
+    span!("recluster");
+    let _phase = timing::phase(Phase::Split);
+    let mut count = 0;
+    for snode in meta.take_drifted() {
+        if meta.contains_supernode(&snode) && repartition(&snode, dataset, meta, sc, tc) {
+            count += 1;
+        }
+    }
+    count
+}
+
+/// Groups the members of `snode` by the source and target cliques their own predicates belong
+/// to, and replaces `snode` by one supernode per group if they do not all agree.
+///
+/// Members without predicates in a direction belong to the empty clique of that direction.
+/// Returns true if `snode` was split up.
+pub fn repartition(
+    snode: &u32,
+    dataset: &mut Dataset,
+    meta: &mut Meta,
+    sc: &mut CliqueCollection,
+    tc: &mut CliqueCollection,
+) -> bool {
+    let members = meta.get_supernode(snode).unwrap().clone();
+    let home = |preds: Vec<u32>, cc: &CliqueCollection| -> Home {
+        preds
+            .first()
+            .map_or((0, None), |p| (cc.get_pred_index(p), Some(*p)))
+    };
+
+    let mut groups: BTreeMap<(usize, usize), Vec<u32>> = BTreeMap::new();
+    let mut homes: Vec<(u32, Home, Home)> = Vec::new();
+    for m in &members {
+        let source = home(meta.get_outgoing_preds(m), sc);
+        let target = home(meta.get_incoming_preds(m), tc);
+        groups.entry((source.0, target.0)).or_default().push(*m);
+        homes.push((*m, source, target));
+    }
+    if groups.len() == 1 {
+        return false;
+    }
+
+    dataset.remove_supernode(snode, members, meta);
+    sc.remove_supernode(snode, meta);
+    tc.remove_supernode(snode, meta);
+    meta.remove_supernode(snode);
+
+    for (m, source, target) in homes {
+        for (cc, (index, pred)) in [(&mut *sc, source), (&mut *tc, target)] {
+            if cc.get_index(&m) == index {
+                continue;
+            }
+            match pred {
+                Some(p) => cc.move_node(&m, &p),
+                None => cc.move_node_to_empty_clique(&m),
+            }
+        }
+    }
+
+    let snodes: Vec<Vec<u32>> = groups.into_values().filter(|g| g.len() > 1).collect();
+    apply_changes(dataset, meta, &snodes, sc, tc);
+    true
+}
+
+/// Repairs the grouping of the whole summary against the current cliques.
+///
+/// Every supernode is re-partitioned, and then all nodes that share their source and target
+/// clique are merged, keeping supernodes within `max_size`. Returns the number of supernodes
+/// that were split up or created.
+pub fn recluster_all(
+    dataset: &mut Dataset,
+    meta: &mut Meta,
+    sc: &mut CliqueCollection,
+    tc: &mut CliqueCollection,
+    max_size: Option<usize>,
+) -> usize {
+    span!("recluster_all");
+    let _phase = timing::phase(Phase::Supernodes);
+    let mut count = 0;
+    let mut snodes: Vec<u32> = meta.get_supernodes().keys().copied().collect();
+    snodes.sort();
+    for snode in snodes {
+        if repartition(&snode, dataset, meta, sc, tc) {
+            count += 1;
+        }
+    }
+
+    let mut nodes = meta.top_level_nodes();
+    nodes.sort();
+    let mut groups: BTreeMap<(usize, usize), Vec<u32>> = BTreeMap::new();
+    for n in nodes {
+        groups
+            .entry((sc.get_index(&n), tc.get_index(&n)))
+            .or_default()
+            .push(n);
+    }
+    let snodes: Vec<Vec<u32>> = groups.into_values().filter(|g| g.len() > 1).collect();
+    let snodes = separate_seeds(snodes, dataset, meta);
+    let snodes = cap_super_nodes(snodes, meta, max_size);
+    count += snodes.len();
+    apply_changes(dataset, meta, &snodes, sc, tc);
+    count
+}
+
+/// Merges the top-level nodes whose incoming and outgoing preds together fall in the same joint
+/// clique, keeping supernodes within `max_size`.
+///
+/// A node with preds in both directions joins its source clique with its target clique, and
+/// nodes are grouped by the joint clique of their preds rather than by the pair of cliques. The
+/// cliques of the nodes merged are merged too, so the preds of each supernode stay in one source
+/// and one target clique. Returns the number of supernodes created.
+pub fn group_jointly(
+    dataset: &mut Dataset,
+    meta: &mut Meta,
+    sc: &mut CliqueCollection,
+    tc: &mut CliqueCollection,
+    max_size: Option<usize>,
+) -> usize {
+    span!("group_jointly");
+    let _phase = timing::phase(Phase::Supernodes);
+    let mut nodes = meta.top_level_nodes();
+    nodes.sort();
+
+    // Source cliques are numbered first, then target cliques; 0 is the empty clique of both.
+    let offset = sc.cliques().len();
+    let mut joint = UnionFind::new(offset + tc.cliques().len());
+    let homes: Vec<(usize, usize)> = nodes
+        .iter()
+        .map(|n| (sc.get_index(n), tc.get_index(n)))
+        .collect();
+    for (source, target) in &homes {
+        if *source != 0 && *target != 0 {
+            joint.union(*source as u32, (offset + target) as u32);
+        }
+    }
+
+    let mut groups: BTreeMap<u32, Vec<u32>> = BTreeMap::new();
+    for (n, (source, target)) in nodes.iter().zip(homes) {
+        let home = match (source, target) {
+            (0, 0) => 0,
+            (0, target) => joint.find((offset + target) as u32),
+            (source, _) => joint.find(source as u32),
+        };
+        groups.entry(home).or_default().push(*n);
+    }
+    let snodes: Vec<Vec<u32>> = groups.into_values().filter(|g| g.len() > 1).collect();
+    let snodes = separate_seeds(snodes, dataset, meta);
+    let snodes = cap_super_nodes(snodes, meta, max_size);
+    join(&snodes, dataset, meta, sc, tc);
+    snodes.len()
+}
+
+/// Merges the nodes of each seed group of `dataset` that are not in one supernode yet, whatever
+/// their preds and the largest size of supernodes. The cliques of the nodes merged are merged
+/// too, as in [`group_jointly`]. Returns the number of supernodes created.
+pub fn join_seeds(
+    dataset: &mut Dataset,
+    meta: &mut Meta,
+    sc: &mut CliqueCollection,
+    tc: &mut CliqueCollection,
+) -> usize {
+    span!("join_seeds");
+    let _phase = timing::phase(Phase::Supernodes);
+    let mut groups: BTreeMap<u32, Vec<u32>> = BTreeMap::new();
+    for (term, group) in dataset.seeds.iter() {
+        let Some(id) = dataset.id_of(term) else {
+            continue;
+        };
+        let Some(info) = meta.get_node(&id) else {
+            continue;
+        };
+        // Nodes without edges are not in the summary.
+        if info.incoming.is_empty() && info.outgoing.is_empty() {
+            continue;
+        }
+        groups
+            .entry(group)
+            .or_default()
+            .push(info.parent.unwrap_or(id));
+    }
+    let snodes: Vec<Vec<u32>> = groups
+        .into_values()
+        .map(|mut homes| {
+            homes.sort_unstable();
+            homes.dedup();
+            homes
+        })
+        .filter(|g| g.len() > 1)
+        .collect();
+    join(&snodes, dataset, meta, sc, tc);
+    snodes.len()
+}
+
+/// Replaces each group of `snodes` by a new supernode, merging the cliques of its nodes.
+fn join(
+    snodes: &[Vec<u32>],
+    dataset: &mut Dataset,
+    meta: &mut Meta,
+    sc: &mut CliqueCollection,
+    tc: &mut CliqueCollection,
+) {
+    for snode in snodes {
+        let new_node = dataset.new_snode(snode, meta);
+        meta.new_snode(snode, &new_node);
+        sc.join_snode(snode, &new_node);
+        tc.join_snode(snode, &new_node);
+    }
+}
@@ -0,0 +1,19 @@
+pub mod exit;
+pub mod hash;
+pub mod heap_size;
+#[cfg(any(feature = "endpoint", feature = "remote"))]
+pub mod http;
+pub mod io;
+pub mod lock;
+pub mod lru;
+pub mod mmap;
+pub mod profile;
+pub mod rng;
+pub mod set_ops;
+pub mod source;
+#[cfg(feature = "sqlite")]
+pub mod sqlite;
+pub mod wal;
+#[cfg(feature = "zstd")]
+pub mod zstd;
+// pub mod print;
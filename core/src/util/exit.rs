@@ -0,0 +1,97 @@
+use serde::Serialize;
+use std::io::{Error, ErrorKind};
+use std::process;
+
+/// Why a run failed. Each kind exits with its own status, so scripts can branch on it; any other
+/// failure exits with 1.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Failure {
+    /// The arguments could not be parsed.
+    Arguments,
+    /// An input file is malformed.
+    Parse,
+    /// The arguments or inputs were checked and found wrong.
+    Validation,
+    /// The update conflicts with the summary, or another run holds the meta folder.
+    Conflict,
+    /// A file could not be read or written.
+    Io,
+    /// A lenient run completed, but skipped changes the summary is inconsistent around.
+    Inconsistent,
+}
+
+impl Failure {
+    pub fn code(self) -> i32 {
+        match self {
+            Failure::Arguments => 2,
+            Failure::Parse => 3,
+            Failure::Validation => 4,
+            Failure::Conflict => 5,
+            Failure::Io => 6,
+            Failure::Inconsistent => 7,
+        }
+    }
+
+    /// Returns the failure that the error `e`, from reading or locking the input, stands for.
+    pub fn of(e: &Error) -> Self {
+        match e.kind() {
+            ErrorKind::InvalidData => Failure::Parse,
+            ErrorKind::WouldBlock => Failure::Conflict,
+            _ => Failure::Io,
+        }
+    }
+}
+
+/// A failure returned up to where it is reported, so what the run holds, like the lock on the
+/// meta folder, is released before it exits.
+#[derive(Debug)]
+pub struct Failed {
+    pub failure: Failure,
+    pub message: String,
+    pub problems: Vec<String>,
+}
+
+impl Failed {
+    pub fn new(failure: Failure, message: String) -> Self {
+        Failed {
+            failure,
+            message,
+            problems: Vec::new(),
+        }
+    }
+}
+
+/// A failure as printed by `--errors-json`.
+#[cfg(feature = "json")]
+#[derive(Serialize)]
+struct FailureRecord<'a> {
+    failure: Failure,
+    code: i32,
+    message: &'a str,
+    problems: &'a [String],
+}
+
+/// Prints `message` and the `problems` behind it, and exits with the status of `failure`.
+///
+/// With `json`, they are printed to stderr as a single JSON object instead, in builds with the
+/// `json` feature.
+#[cfg_attr(not(feature = "json"), allow(unused_variables))]
+pub fn fail(failure: Failure, message: &str, problems: &[String], json: bool) -> ! {
+    #[cfg(feature = "json")]
+    if json {
+        let record = FailureRecord {
+            failure,
+            code: failure.code(),
+            message,
+            problems,
+        };
+        eprintln!("{}", serde_json::to_string(&record).unwrap());
+        process::exit(failure.code());
+    }
+    println!("{}", message);
+    for problem in problems {
+        println!("  {}", problem);
+    }
+    process::exit(failure.code());
+}
@@ -0,0 +1,71 @@
+//! Hash maps and sets of the summary models.
+//!
+//! Their keys are mostly ids and terms of our own input, so they use the Fx hash of rustc,
+//! which is much cheaper than the DoS-resistant SipHash of the standard library. Build with the
+//! `std-hash` feature to get the standard hasher back, e.g. when keys come from untrusted
+//! clients.
+
+#[cfg(not(feature = "std-hash"))]
+pub type BuildHasher = std::hash::BuildHasherDefault<FxHasher>;
+#[cfg(feature = "std-hash")]
+pub type BuildHasher = std::collections::hash_map::RandomState;
+
+pub type HashMap<K, V> = std::collections::HashMap<K, V, BuildHasher>;
+pub type HashSet<T> = std::collections::HashSet<T, BuildHasher>;
+
+#[cfg(not(feature = "std-hash"))]
+pub use fx::FxHasher;
+
+#[cfg(not(feature = "std-hash"))]
+mod fx {
+    use std::hash::Hasher;
+
+    const SEED: u64 = 0x51_7c_c1_b7_27_22_0a_95;
+
+    /// The Fx hash: every word of input is folded in with a rotate, xor and multiply.
+    #[derive(Default)]
+    pub struct FxHasher {
+        hash: u64,
+    }
+
+    impl FxHasher {
+        fn add(&mut self, word: u64) {
+            self.hash = (self.hash.rotate_left(5) ^ word).wrapping_mul(SEED);
+        }
+    }
+
+    impl Hasher for FxHasher {
+        fn write(&mut self, bytes: &[u8]) {
+            let mut chunks = bytes.chunks_exact(8);
+            for chunk in &mut chunks {
+                self.add(u64::from_le_bytes(chunk.try_into().unwrap()));
+            }
+            let rest = chunks.remainder();
+            if !rest.is_empty() {
+                let mut word = [0u8; 8];
+                word[..rest.len()].copy_from_slice(rest);
+                self.add(u64::from_le_bytes(word));
+            }
+        }
+
+        fn write_u8(&mut self, i: u8) {
+            self.add(i as u64);
+        }
+
+        fn write_u32(&mut self, i: u32) {
+            self.add(i as u64);
+        }
+
+        fn write_u64(&mut self, i: u64) {
+            self.add(i);
+        }
+
+        fn write_usize(&mut self, i: usize) {
+            self.add(i as u64);
+        }
+
+        fn finish(&self) -> u64 {
+            self.hash
+        }
+    }
+}
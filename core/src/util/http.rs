@@ -0,0 +1,257 @@
+use std::io::{BufRead, BufReader, Error, ErrorKind, Read, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::time::Duration;
+
+/// How long connecting, and each read or write of a request, may take by default.
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// A parsed `http://` URL.
+#[derive(Debug, PartialEq)]
+pub struct Url {
+    pub host: String,
+    pub port: u16,
+    pub path: String,
+}
+
+impl Url {
+    /// Parses an `http://` url; `https://` and other schemes are errors, as there is no TLS.
+    pub fn parse(url: &str) -> Result<Self, Error> {
+        let rest = url.strip_prefix("http://").ok_or_else(|| {
+            Error::new(
+                ErrorKind::InvalidInput,
+                format!("only plain http:// endpoints are supported, got {}", url),
+            )
+        })?;
+
+        let (authority, path) = match rest.find('/') {
+            Some(i) => (&rest[..i], &rest[i..]),
+            None => (rest, "/"),
+        };
+        let (host, port) = match authority.rsplit_once(':') {
+            Some((h, p)) => (
+                h,
+                p.parse::<u16>()
+                    .map_err(|_| Error::new(ErrorKind::InvalidInput, "invalid port in url"))?,
+            ),
+            None => (authority, 80),
+        };
+
+        Ok(Self {
+            host: host.to_string(),
+            port,
+            path: path.to_string(),
+        })
+    }
+}
+
+/// Sends a GET request to `url` and returns the response body. Connecting, and each read or
+/// write, fails after `timeout`.
+pub fn get(url: &str, accept: &str, timeout: Duration) -> Result<String, Error> {
+    send("GET", url, &[("Accept", accept)], "", timeout)
+}
+
+/// Sends a POST request with `body` to `url` and returns the response body. Connecting, and
+/// each read or write, fails after `timeout`.
+pub fn post(url: &str, content_type: &str, body: &str, timeout: Duration) -> Result<String, Error> {
+    send(
+        "POST",
+        url,
+        &[("Content-Type", content_type)],
+        body,
+        timeout,
+    )
+}
+
+/// Sends a GET request with `headers` to `url` and returns the status and a reader of the
+/// response body, which is streamed instead of read whole. Failed requests are errors.
+///
+/// The request is HTTP/1.0, so the body is never chunked and ends when the connection closes.
+/// Connecting, and each read or write, fails after `timeout`.
+pub fn get_reader(
+    url: &str,
+    headers: &[(&str, &str)],
+    timeout: Duration,
+) -> Result<(u16, BufReader<TcpStream>), Error> {
+    let url = Url::parse(url)?;
+    let stream = connect(&url, timeout)?;
+
+    let mut request = format!("GET {} HTTP/1.0\r\nHost: {}\r\n", url.path, url.host);
+    for (k, v) in headers {
+        request.push_str(&format!("{}: {}\r\n", k, v));
+    }
+    request.push_str("\r\n");
+    (&stream).write_all(request.as_bytes())?;
+
+    let mut reader = BufReader::new(stream);
+    let mut line = String::new();
+    reader.read_line(&mut line)?;
+    let status = line
+        .split(' ')
+        .nth(1)
+        .and_then(|s| s.parse::<u16>().ok())
+        .ok_or_else(|| Error::new(ErrorKind::InvalidData, "missing http status"))?;
+    loop {
+        line.clear();
+        if reader.read_line(&mut line)? == 0 || line.trim_end().is_empty() {
+            break;
+        }
+    }
+
+    if !(200..300).contains(&status) {
+        let mut body = String::new();
+        reader.read_to_string(&mut body).ok();
+        return Err(Error::other(format!(
+            "{} returned status {}: {}",
+            url.host,
+            status,
+            body.trim()
+        )));
+    }
+    Ok((status, reader))
+}
+
+/// Percent-encodes `s` for use in a query string or form body.
+pub fn encode(s: &str) -> String {
+    let mut encoded = String::new();
+    for b in s.bytes() {
+        match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                encoded.push(b as char)
+            }
+            _ => encoded.push_str(&format!("%{:02X}", b)),
+        }
+    }
+    encoded
+}
+
+fn send(
+    method: &str,
+    url: &str,
+    headers: &[(&str, &str)],
+    body: &str,
+    timeout: Duration,
+) -> Result<String, Error> {
+    let url = Url::parse(url)?;
+    let exchange = || -> Result<Vec<u8>, Error> {
+        let mut stream = connect(&url, timeout)?;
+        stream.write_all(request(method, &url, headers, body).as_bytes())?;
+        let mut response: Vec<u8> = Vec::new();
+        stream.read_to_end(&mut response)?;
+        Ok(response)
+    };
+    match exchange() {
+        Ok(response) => parse_response(&response),
+        // Reads that time out fail with WouldBlock on unix.
+        Err(e) if matches!(e.kind(), ErrorKind::TimedOut | ErrorKind::WouldBlock) => {
+            Err(Error::new(
+                ErrorKind::TimedOut,
+                format!(
+                    "{} did not answer within {} seconds",
+                    url.host,
+                    timeout.as_secs_f64()
+                ),
+            ))
+        }
+        Err(e) => Err(e),
+    }
+}
+
+/// Connects to the host of `url`, trying each of its addresses in turn, with `timeout` for
+/// connecting and for each read and write after. A zero `timeout` waits forever.
+fn connect(url: &Url, timeout: Duration) -> Result<TcpStream, Error> {
+    if timeout.is_zero() {
+        return TcpStream::connect((url.host.as_str(), url.port));
+    }
+    let mut last = Error::new(ErrorKind::NotFound, format!("{} has no address", url.host));
+    for addr in (url.host.as_str(), url.port).to_socket_addrs()? {
+        match TcpStream::connect_timeout(&addr, timeout) {
+            Ok(stream) => {
+                stream.set_read_timeout(Some(timeout))?;
+                stream.set_write_timeout(Some(timeout))?;
+                return Ok(stream);
+            }
+            Err(e) => last = e,
+        }
+    }
+    Err(last)
+}
+
+/// Frames an HTTP/1.1 request that closes the connection after the response.
+pub fn request(method: &str, url: &Url, headers: &[(&str, &str)], body: &str) -> String {
+    let mut request = format!(
+        "{} {} HTTP/1.1\r\nHost: {}\r\nConnection: close\r\nContent-Length: {}\r\n",
+        method,
+        url.path,
+        url.host,
+        body.len()
+    );
+    for (k, v) in headers {
+        request.push_str(&format!("{}: {}\r\n", k, v));
+    }
+    request.push_str("\r\n");
+    request.push_str(body);
+    request
+}
+
+/// Returns the body of a whole `response`, decoding a chunked one. Statuses other than 2xx
+/// are errors.
+pub fn parse_response(response: &[u8]) -> Result<String, Error> {
+    let split = response
+        .windows(4)
+        .position(|w| w == b"\r\n\r\n")
+        .ok_or_else(|| Error::new(ErrorKind::InvalidData, "malformed http response"))?;
+    let head = String::from_utf8_lossy(&response[..split]).to_string();
+    let body = &response[split + 4..];
+
+    let status = head
+        .lines()
+        .next()
+        .and_then(|l| l.split(' ').nth(1))
+        .and_then(|s| s.parse::<u16>().ok())
+        .ok_or_else(|| Error::new(ErrorKind::InvalidData, "missing http status"))?;
+
+    let chunked = head.lines().any(|l| {
+        let l = l.to_ascii_lowercase();
+        l.starts_with("transfer-encoding:") && l.contains("chunked")
+    });
+    let body = if chunked {
+        decode_chunked(body)?
+    } else {
+        body.to_vec()
+    };
+    let body = String::from_utf8(body).map_err(|e| Error::new(ErrorKind::InvalidData, e))?;
+
+    if !(200..300).contains(&status) {
+        return Err(Error::other(format!(
+            "endpoint returned status {}: {}",
+            status,
+            body.trim()
+        )));
+    }
+    Ok(body)
+}
+
+fn decode_chunked(mut body: &[u8]) -> Result<Vec<u8>, Error> {
+    let invalid = || Error::new(ErrorKind::InvalidData, "malformed chunked body");
+    let mut decoded: Vec<u8> = Vec::new();
+
+    loop {
+        let line_end = body
+            .windows(2)
+            .position(|w| w == b"\r\n")
+            .ok_or_else(invalid)?;
+        let size_str = String::from_utf8_lossy(&body[..line_end]).to_string();
+        let size_str = size_str.split(';').next().unwrap_or("").trim();
+        let size = usize::from_str_radix(size_str, 16).map_err(|_| invalid())?;
+        body = &body[line_end + 2..];
+
+        if size == 0 {
+            return Ok(decoded);
+        }
+        if body.len() < size + 2 {
+            return Err(invalid());
+        }
+        decoded.extend_from_slice(&body[..size]);
+        body = &body[size + 2..];
+    }
+}
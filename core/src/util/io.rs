@@ -0,0 +1,280 @@
+use io::{BufReader, Error, ErrorKind};
+use std::fs::File;
+use std::fs::OpenOptions;
+use std::io::prelude::*;
+use std::io::{self, BufRead};
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::mpsc::{self, Receiver};
+use std::{mem, thread, vec};
+
+/// Number of lines the reader thread of a `LineStream` sends at a time.
+const STREAM_BATCH: usize = 1024;
+
+/// Number of batches a `LineStream` reads ahead of its consumer by default.
+pub const STREAM_CAPACITY: usize = 16;
+
+pub fn write_lines(path: &PathBuf, vec: &Vec<String>) -> Result<(), Error> {
+    let mut file = OpenOptions::new()
+        .create(true)
+        .write(true)
+        .truncate(true)
+        .open(path)?;
+
+    for s in vec {
+        writeln!(file, "{}", s)?
+    }
+    Ok(())
+}
+
+/// Returns the first non-empty line of the file at `path`, without reading the rest.
+pub fn first_line(path: &Path) -> io::Result<Option<String>> {
+    let file = File::open(path)?;
+    for line in LineReader::new(BufReader::new(file), None) {
+        let line = line?;
+        if !line.is_empty() {
+            return Ok(Some(line));
+        }
+    }
+    Ok(None)
+}
+
+/// Returns true if a file can be created in the directory `dir`.
+pub fn is_writable(dir: &Path) -> bool {
+    let probe = dir.join(format!(".teriyaki-write-{}", std::process::id()));
+    match OpenOptions::new().write(true).create_new(true).open(&probe) {
+        Ok(_) => std::fs::remove_file(&probe).is_ok(),
+        Err(_) => false,
+    }
+}
+
+pub fn read_lines<P>(path: &P) -> io::Result<Vec<String>>
+where
+    P: AsRef<Path>,
+{
+    read_lines_limited(path, None)
+}
+
+/// Like [`read_lines`], but applies `limit` to lines that are too long.
+pub fn read_lines_limited<P>(path: &P, limit: Option<LineLimit>) -> io::Result<Vec<String>>
+where
+    P: AsRef<Path>,
+{
+    let file = File::open(path)?;
+    LineReader::new(BufReader::new(file), limit).collect()
+}
+
+/// What happens to lines longer than the maximum of a [`LineLimit`].
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum LongLines {
+    /// Reading fails, naming the line (the default).
+    #[default]
+    Fail,
+    /// The line is left out.
+    Skip,
+    /// The line is cut to the maximum, at a character boundary.
+    Truncate,
+}
+
+impl LongLines {
+    pub fn parse(arg: Option<&String>) -> Result<Self, &'static str> {
+        match arg.map(|s| s.as_str()) {
+            Some("fail") => Ok(LongLines::Fail),
+            Some("skip") => Ok(LongLines::Skip),
+            Some("truncate") => Ok(LongLines::Truncate),
+            _ => Err("--long-lines expects fail, skip or truncate"),
+        }
+    }
+}
+
+/// The longest line read, in bytes without the line ending, and what happens to longer ones.
+///
+/// Longer lines are never held in memory beyond the maximum, so a document with a few huge
+/// literals can be read without running out of memory.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct LineLimit {
+    pub max_bytes: usize,
+    pub policy: LongLines,
+}
+
+/// Lines of a reader, read into one buffer that is reused for every line instead of growing a
+/// new string each time. Each line is then copied out at its final size.
+pub struct LineReader<R> {
+    reader: R,
+    buf: Vec<u8>,
+    limit: Option<LineLimit>,
+    /// Number of lines read so far.
+    line: usize,
+}
+
+impl<R: BufRead> LineReader<R> {
+    pub fn new(reader: R, limit: Option<LineLimit>) -> Self {
+        LineReader {
+            reader,
+            buf: Vec::new(),
+            limit,
+            line: 0,
+        }
+    }
+
+    /// Reads the next line into `buf`, keeping at most `max` bytes of it. Returns whether there
+    /// was a line, and whether it was longer than `max`.
+    fn fill_line(&mut self, max: usize) -> io::Result<Option<bool>> {
+        self.buf.clear();
+        let mut read_any = false;
+        let mut long = false;
+        loop {
+            let available = match self.reader.fill_buf() {
+                Ok(available) => available,
+                Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e),
+            };
+            if available.is_empty() {
+                break;
+            }
+            read_any = true;
+            let (chunk, used, done) = match available.iter().position(|b| *b == b'\n') {
+                Some(i) => (&available[..i], i + 1, true),
+                None => (available, available.len(), false),
+            };
+            let room = max - self.buf.len();
+            if chunk.len() > room {
+                long = true;
+            }
+            self.buf.extend_from_slice(&chunk[..chunk.len().min(room)]);
+            self.reader.consume(used);
+            if done {
+                break;
+            }
+        }
+        Ok(read_any.then_some(long))
+    }
+}
+
+impl<R: BufRead> Iterator for LineReader<R> {
+    type Item = io::Result<String>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let max = self.limit.map_or(usize::MAX, |l| l.max_bytes);
+        loop {
+            let long = match self.fill_line(max) {
+                Ok(Some(long)) => long,
+                Ok(None) => return None,
+                Err(e) => return Some(Err(e)),
+            };
+            self.line += 1;
+            if !long && self.buf.last() == Some(&b'\r') {
+                self.buf.pop();
+            }
+            if long {
+                match self.limit.map(|l| l.policy) {
+                    Some(LongLines::Skip) => continue,
+                    Some(LongLines::Truncate) => {
+                        // Drop a character cut in half by the maximum.
+                        if let Err(e) = std::str::from_utf8(&self.buf) {
+                            if e.error_len().is_none() {
+                                self.buf.truncate(e.valid_up_to());
+                            }
+                        }
+                    }
+                    _ => {
+                        return Some(Err(Error::new(
+                            ErrorKind::InvalidData,
+                            format!("line {} is longer than {} bytes", self.line, max),
+                        )))
+                    }
+                }
+            }
+            return Some(match std::str::from_utf8(&self.buf) {
+                Ok(line) => Ok(line.to_string()),
+                Err(_) => Err(Error::new(
+                    ErrorKind::InvalidData,
+                    format!("line {} is not valid UTF-8", self.line),
+                )),
+            });
+        }
+    }
+}
+
+/// Lines of a file, read on a separate thread while they are consumed.
+///
+/// The reader sends the lines in batches over a channel holding at most `capacity` batches, so
+/// it waits whenever the consumer falls behind and only that many lines are held in memory.
+pub struct LineStream {
+    receiver: Receiver<io::Result<Vec<String>>>,
+    batch: vec::IntoIter<String>,
+}
+
+/// Starts reading the lines of the file at `path` into a `LineStream` of `capacity` batches.
+pub fn stream_lines<P>(path: &P, capacity: usize) -> io::Result<LineStream>
+where
+    P: AsRef<Path>,
+{
+    stream_lines_limited(path, capacity, None)
+}
+
+/// Like [`stream_lines`], but applies `limit` to lines that are too long.
+pub fn stream_lines_limited<P>(
+    path: &P,
+    capacity: usize,
+    limit: Option<LineLimit>,
+) -> io::Result<LineStream>
+where
+    P: AsRef<Path>,
+{
+    let file = File::open(path)?;
+    Ok(stream_reader(file, capacity, limit))
+}
+
+/// Like [`stream_lines_limited`], but reads the lines of `reader`, such as the body of a remote
+/// file.
+pub fn stream_reader<R>(reader: R, capacity: usize, limit: Option<LineLimit>) -> LineStream
+where
+    R: Read + Send + 'static,
+{
+    let (sender, receiver) = mpsc::sync_channel(capacity);
+
+    thread::spawn(move || {
+        let mut batch: Vec<String> = Vec::with_capacity(STREAM_BATCH);
+        for line in LineReader::new(BufReader::new(reader), limit) {
+            match line {
+                Ok(l) => batch.push(l),
+                Err(e) => {
+                    let _ = sender.send(Err(e));
+                    return;
+                }
+            }
+            if batch.len() == STREAM_BATCH {
+                let full = mem::replace(&mut batch, Vec::with_capacity(STREAM_BATCH));
+                // The consumer is gone, so nobody needs the rest.
+                if sender.send(Ok(full)).is_err() {
+                    return;
+                }
+            }
+        }
+        if !batch.is_empty() {
+            let _ = sender.send(Ok(batch));
+        }
+    });
+
+    LineStream {
+        receiver,
+        batch: Vec::new().into_iter(),
+    }
+}
+
+impl Iterator for LineStream {
+    type Item = io::Result<String>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(l) = self.batch.next() {
+                return Some(Ok(l));
+            }
+            match self.receiver.recv().ok()? {
+                Ok(batch) => self.batch = batch.into_iter(),
+                Err(e) => return Some(Err(e)),
+            }
+        }
+    }
+}
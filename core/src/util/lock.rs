@@ -0,0 +1,82 @@
+use std::fs::{self, File, OpenOptions, TryLockError};
+use std::io::{Error, ErrorKind, Read, Write};
+use std::path::{Path, PathBuf};
+use std::process;
+
+/// Advisory lock on a meta folder, held from reading the summary until it is written back.
+///
+/// The lock is a file holding the pid of its owner, created next to the meta folder so it also
+/// works before a fast-mode run creates the folder. The file itself is locked with the system's
+/// file locks (`flock` on Unix), which are released when the owner exits however it exits, so a
+/// file left behind by a run that crashed or was killed does not keep the folder locked. It is
+/// removed when the `Lock` is dropped.
+pub struct Lock {
+    path: PathBuf,
+    _file: File,
+}
+
+impl Lock {
+    /// Takes the lock at `path`, or returns an error naming the pid holding it.
+    pub fn acquire(path: &Path) -> Result<Self, Error> {
+        loop {
+            let mut file = OpenOptions::new()
+                .read(true)
+                .write(true)
+                .create(true)
+                .truncate(false)
+                .open(path)?;
+            match file.try_lock() {
+                Ok(()) => {}
+                Err(TryLockError::WouldBlock) => {
+                    let mut owner = String::new();
+                    file.read_to_string(&mut owner)?;
+                    let message = match owner.trim().parse::<u32>() {
+                        Ok(pid) => format!("{} is already locked by pid {}", path.display(), pid),
+                        // The owner has locked the file, but not written its pid yet.
+                        Err(_) => format!("{} is already locked", path.display()),
+                    };
+                    return Err(Error::new(ErrorKind::WouldBlock, message));
+                }
+                Err(TryLockError::Error(e)) => return Err(e),
+            }
+
+            // The previous owner removes the file when it is done, and may have done so between
+            // it being opened and locked here. A lock on a removed file excludes no one.
+            if !is_at(&file, path)? {
+                continue;
+            }
+            file.set_len(0)?;
+            writeln!(file, "{}", process::id())?;
+            return Ok(Self {
+                path: path.to_path_buf(),
+                _file: file,
+            });
+        }
+    }
+}
+
+impl Drop for Lock {
+    fn drop(&mut self) {
+        // Removed while still locked, so a process that opens it after this finds a new file.
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+/// Returns true if `file` is still the file at `path`.
+#[cfg(unix)]
+fn is_at(file: &File, path: &Path) -> Result<bool, Error> {
+    use std::os::unix::fs::MetadataExt;
+
+    let open = file.metadata()?;
+    match fs::metadata(path) {
+        Ok(at) => Ok((open.dev(), open.ino()) == (at.dev(), at.ino())),
+        Err(e) if e.kind() == ErrorKind::NotFound => Ok(false),
+        Err(e) => Err(e),
+    }
+}
+
+/// Returns true if `file` is still the file at `path`. Elsewhere, open files cannot be removed.
+#[cfg(not(unix))]
+fn is_at(_file: &File, _path: &Path) -> Result<bool, Error> {
+    Ok(true)
+}
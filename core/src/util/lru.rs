@@ -0,0 +1,159 @@
+use crate::metrics::cache::CacheStats;
+use crate::util::hash::HashMap;
+use crate::util::heap_size::HeapSize;
+use std::hash::Hash;
+use std::mem::size_of;
+
+/// Marks the end of the list of entries.
+const NONE: usize = usize::MAX;
+
+/// Cache of at most `capacity` values, which drops the least recently used one to make room.
+///
+/// Entries are kept in a list from most to least recently used, linked through their slots, so
+/// lookups and insertions take constant time. A capacity of 0 caches nothing.
+pub struct Lru<K, V> {
+    capacity: usize,
+    /// Slot of each key.
+    slots: HashMap<K, usize>,
+    entries: Vec<Entry<K, V>>,
+    /// Most and least recently used slot.
+    head: usize,
+    tail: usize,
+    stats: CacheStats,
+}
+
+struct Entry<K, V> {
+    key: K,
+    value: V,
+    prev: usize,
+    next: usize,
+}
+
+impl<K: Copy + Eq + Hash, V: Clone> Lru<K, V> {
+    pub fn new(capacity: usize) -> Self {
+        Lru {
+            capacity,
+            slots: HashMap::default(),
+            entries: Vec::new(),
+            head: NONE,
+            tail: NONE,
+            stats: CacheStats::default(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.slots.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.slots.is_empty()
+    }
+
+    /// Returns the value of `key`, which becomes the most recently used, or caches the value
+    /// `load` returns for it. Values `load` does not find are not cached.
+    pub fn get_or_load<F>(&mut self, key: K, load: F) -> Option<V>
+    where
+        F: FnOnce() -> Option<V>,
+    {
+        if let Some(&slot) = self.slots.get(&key) {
+            self.stats.hits += 1;
+            self.unlink(slot);
+            self.push_front(slot);
+            return Some(self.entries[slot].value.clone());
+        }
+        self.stats.misses += 1;
+        let value = load()?;
+        self.insert(key, value.clone());
+        Some(value)
+    }
+
+    /// Drops the value of `key`, if it is cached.
+    pub fn remove(&mut self, key: &K) {
+        let Some(slot) = self.slots.remove(key) else {
+            return;
+        };
+        self.unlink(slot);
+        // The last slot takes its place, so the slots stay dense.
+        let last = self.entries.len() - 1;
+        if slot != last {
+            self.relink(last, slot);
+        }
+        self.entries.swap_remove(slot);
+    }
+
+    pub fn stats(&self) -> CacheStats {
+        self.stats.clone()
+    }
+
+    fn insert(&mut self, key: K, value: V) {
+        if self.capacity == 0 {
+            return;
+        }
+        let slot = if self.slots.len() < self.capacity {
+            self.entries.push(Entry {
+                key,
+                value,
+                prev: NONE,
+                next: NONE,
+            });
+            self.entries.len() - 1
+        } else {
+            let slot = self.tail;
+            self.unlink(slot);
+            self.slots.remove(&self.entries[slot].key);
+            self.entries[slot].key = key;
+            self.entries[slot].value = value;
+            slot
+        };
+        self.slots.insert(key, slot);
+        self.push_front(slot);
+    }
+
+    fn unlink(&mut self, slot: usize) {
+        let (prev, next) = (self.entries[slot].prev, self.entries[slot].next);
+        match prev {
+            NONE => self.head = next,
+            p => self.entries[p].next = next,
+        }
+        match next {
+            NONE => self.tail = prev,
+            n => self.entries[n].prev = prev,
+        }
+    }
+
+    fn push_front(&mut self, slot: usize) {
+        self.entries[slot].prev = NONE;
+        self.entries[slot].next = self.head;
+        match self.head {
+            NONE => self.tail = slot,
+            h => self.entries[h].prev = slot,
+        }
+        self.head = slot;
+    }
+
+    /// Points everything that refers to the linked slot `from` at `to` instead.
+    fn relink(&mut self, from: usize, to: usize) {
+        let (prev, next) = (self.entries[from].prev, self.entries[from].next);
+        match prev {
+            NONE => self.head = to,
+            p => self.entries[p].next = to,
+        }
+        match next {
+            NONE => self.tail = to,
+            n => self.entries[n].prev = to,
+        }
+        self.slots.insert(self.entries[from].key, to);
+    }
+}
+
+impl<K: HeapSize, V: HeapSize> HeapSize for Lru<K, V> {
+    fn heap_size(&self) -> usize {
+        self.slots.heap_size()
+            + self.entries.capacity() * size_of::<Entry<K, V>>()
+            + self
+                .entries
+                .iter()
+                .map(|e| e.key.heap_size() + e.value.heap_size())
+                .sum::<usize>()
+    }
+}
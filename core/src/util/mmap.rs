@@ -0,0 +1,131 @@
+use std::fs::File;
+use std::io::{self, ErrorKind, Read};
+use std::ops::Deref;
+use std::path::Path;
+
+/// The little-endian `u32` words of a file.
+///
+/// Where the platform allows, the file is mapped into memory rather than read: opening it costs
+/// nothing up front, pages are only read when used, and processes opening the same file share
+/// them. Files must not be changed in place while mapped; replace them with a rename instead.
+pub struct Words(Inner);
+
+enum Inner {
+    #[cfg(all(unix, target_endian = "little"))]
+    Mapped(sys::Map),
+    Owned(Vec<u32>),
+}
+
+impl Words {
+    /// Maps the file at `path`, or reads it where it cannot be mapped.
+    pub fn open(path: &Path) -> io::Result<Self> {
+        let mut file = File::open(path)?;
+        let len = file.metadata()?.len() as usize;
+        if !len.is_multiple_of(4) {
+            return Err(io::Error::new(
+                ErrorKind::InvalidData,
+                format!("{} is not a file of 32-bit words", path.display()),
+            ));
+        }
+        // Empty mappings are an error.
+        #[cfg(all(unix, target_endian = "little"))]
+        if len > 0 {
+            return Ok(Words(Inner::Mapped(sys::Map::new(&file, len)?)));
+        }
+        let mut bytes = Vec::with_capacity(len);
+        file.read_to_end(&mut bytes)?;
+        Ok(Words::from_le_bytes(&bytes))
+    }
+
+    /// Returns the words of `bytes`, which hold a whole number of them.
+    pub fn from_le_bytes(bytes: &[u8]) -> Self {
+        let words = bytes
+            .chunks_exact(4)
+            .map(|w| u32::from_le_bytes(w.try_into().unwrap()))
+            .collect();
+        Words(Inner::Owned(words))
+    }
+}
+
+impl Deref for Words {
+    type Target = [u32];
+
+    fn deref(&self) -> &[u32] {
+        match &self.0 {
+            #[cfg(all(unix, target_endian = "little"))]
+            Inner::Mapped(map) => map.words(),
+            Inner::Owned(words) => words,
+        }
+    }
+}
+
+#[cfg(all(unix, target_endian = "little"))]
+mod sys {
+    use std::ffi::c_void;
+    use std::fs::File;
+    use std::io;
+    use std::os::unix::io::AsRawFd;
+    use std::ptr;
+
+    const PROT_READ: i32 = 1;
+    const MAP_SHARED: i32 = 1;
+
+    extern "C" {
+        // `off_t` is as wide as a pointer on the platforms we build for.
+        fn mmap(
+            addr: *mut c_void,
+            len: usize,
+            prot: i32,
+            flags: i32,
+            fd: i32,
+            offset: isize,
+        ) -> *mut c_void;
+        fn munmap(addr: *mut c_void, len: usize) -> i32;
+    }
+
+    /// A read-only shared mapping of a whole file.
+    pub struct Map {
+        ptr: *mut c_void,
+        len: usize,
+    }
+
+    // The mapping is never written, so any thread may read it.
+    unsafe impl Send for Map {}
+    unsafe impl Sync for Map {}
+
+    impl Map {
+        pub fn new(file: &File, len: usize) -> io::Result<Self> {
+            // SAFETY: maps `len` bytes of an open file read-only at an address of the system's
+            // choosing; a failure is returned as `MAP_FAILED` and checked below.
+            let ptr = unsafe {
+                mmap(
+                    ptr::null_mut(),
+                    len,
+                    PROT_READ,
+                    MAP_SHARED,
+                    file.as_raw_fd(),
+                    0,
+                )
+            };
+            if ptr as isize == -1 {
+                return Err(io::Error::last_os_error());
+            }
+            Ok(Map { ptr, len })
+        }
+
+        pub fn words(&self) -> &[u32] {
+            // SAFETY: mappings are page aligned, `len` is a whole number of words, and the
+            // mapping lives as long as `self`.
+            unsafe { std::slice::from_raw_parts(self.ptr as *const u32, self.len / 4) }
+        }
+    }
+
+    impl Drop for Map {
+        fn drop(&mut self) {
+            // SAFETY: unmaps exactly the mapping made in `new`, which nothing borrows anymore.
+            unsafe {
+                munmap(self.ptr, self.len);
+            }
+        }
+    }
+}
@@ -8,7 +8,7 @@ pub fn cliques_string(cliques: &Vec<Clique>, dict: &Dict) {
         let mut node_strings: Vec<String> = Vec::new();
 
         for pred in &clique.preds {
-            pred_strings.push(dict.key_by_value(pred).unwrap());
+            pred_strings.push(dict.pred_term(pred).unwrap().to_string());
         }
         for node in &clique.nodes {
             node_strings.push(dict.key_by_value(node).unwrap());
@@ -21,7 +21,7 @@ pub fn cliques_string(cliques: &Vec<Clique>, dict: &Dict) {
 pub fn triples_string(triples: &Vec<Triple>, dict: &Dict) {
     for triple in triples {
         let sub_string = dict.key_by_value(&triple.sub).unwrap();
-        let pred_string = dict.key_by_value(&triple.pred).unwrap();
+        let pred_string = dict.pred_term(&triple.pred).unwrap();
         let obj_string = dict.key_by_value(&triple.obj).unwrap();
 
         println!("{} {} {}", sub_string, pred_string, obj_string);
@@ -0,0 +1,104 @@
+//! Coarse timing spans, compiled in with the `profiling` feature.
+//!
+//! Spans nest per thread. At exit, [`dump`] prints the self time of every distinct span stack
+//! in folded format (`outer;inner <microseconds>`), which can be fed to flamegraph tools.
+
+/// Times the rest of the enclosing scope under `$name` when the `profiling` feature is on.
+#[cfg(feature = "profiling")]
+#[macro_export]
+macro_rules! span {
+    ($name:expr) => {
+        let _span = $crate::util::profile::Span::enter($name);
+    };
+}
+
+#[cfg(not(feature = "profiling"))]
+#[macro_export]
+macro_rules! span {
+    ($name:expr) => {};
+}
+
+pub use crate::span;
+
+#[cfg(feature = "profiling")]
+pub use enabled::{dump, Span};
+
+#[cfg(feature = "profiling")]
+mod enabled {
+    use std::cell::RefCell;
+    use std::collections::HashMap;
+    use std::sync::Mutex;
+    use std::time::{Duration, Instant};
+
+    struct Frame {
+        name: &'static str,
+        start: Instant,
+        children: Duration,
+    }
+
+    thread_local! {
+        static STACK: RefCell<Vec<Frame>> = const { RefCell::new(Vec::new()) };
+    }
+
+    /// Self time and call count per folded span stack.
+    static FOLDED: Mutex<Option<HashMap<String, (Duration, usize)>>> = Mutex::new(None);
+
+    pub struct Span;
+
+    impl Span {
+        pub fn enter(name: &'static str) -> Self {
+            STACK.with(|s| {
+                s.borrow_mut().push(Frame {
+                    name,
+                    start: Instant::now(),
+                    children: Duration::ZERO,
+                })
+            });
+            Span
+        }
+    }
+
+    impl Drop for Span {
+        fn drop(&mut self) {
+            STACK.with(|s| {
+                let mut stack = s.borrow_mut();
+                let frame = stack.pop().unwrap();
+                let elapsed = frame.start.elapsed();
+
+                let mut key: Vec<&str> = stack.iter().map(|f| f.name).collect();
+                key.push(frame.name);
+                if let Some(parent) = stack.last_mut() {
+                    parent.children += elapsed;
+                }
+
+                let mut folded = FOLDED.lock().unwrap();
+                let entry = folded
+                    .get_or_insert_with(HashMap::new)
+                    .entry(key.join(";"))
+                    .or_insert((Duration::ZERO, 0));
+                entry.0 += elapsed.saturating_sub(frame.children);
+                entry.1 += 1;
+            });
+        }
+    }
+
+    /// Prints all recorded span stacks to stderr in folded format, followed by call counts.
+    pub fn dump() {
+        let folded = FOLDED.lock().unwrap();
+        let Some(folded) = folded.as_ref() else {
+            return;
+        };
+
+        let mut stacks: Vec<(&String, &(Duration, usize))> = folded.iter().collect();
+        stacks.sort_by(|a, b| a.0.cmp(b.0));
+
+        eprintln!("# profile: <span stack> <self time in us>");
+        for (stack, (time, _)) in &stacks {
+            eprintln!("{} {}", stack, time.as_micros());
+        }
+        eprintln!("# calls");
+        for (stack, (_, calls)) in &stacks {
+            eprintln!("# {} {}", stack, calls);
+        }
+    }
+}
@@ -0,0 +1,302 @@
+//! Where input files are read from: the local file system, a web server with the `remote`
+//! feature, or an S3 bucket with the `s3` feature.
+//!
+//! A location is a path, an `http://` url or an `s3://bucket/key` url, so dumps in object
+//! storage are summarized without copying them first. Remote files are streamed as they are
+//! read, and only what is read is transferred.
+
+use crate::util::io::LineReader;
+use std::fs::{self, File};
+use std::io::{self, Error, ErrorKind, Read, Seek, SeekFrom};
+use std::path::Path;
+
+/// Bytes read from the start of a remote file to find its first line.
+const FIRST_LINE_BYTES: u64 = 64 * 1024;
+
+/// A store of files, read whole, in ranges, or listed by prefix.
+pub trait DataSource {
+    /// Opens the file at `location` to read it from the start.
+    fn open(&self, location: &str) -> io::Result<Box<dyn Read + Send>>;
+
+    /// Reads `len` bytes of the file at `location` from byte `start`, fewer at its end.
+    fn read_range(&self, location: &str, start: u64, len: u64) -> io::Result<Vec<u8>>;
+
+    /// Returns the sorted locations of the files whose location starts with `prefix`.
+    fn list(&self, prefix: &str) -> io::Result<Vec<String>>;
+}
+
+/// Returns true if `location` is an url rather than a local path.
+pub fn is_remote(location: &Path) -> bool {
+    let location = location.to_string_lossy();
+    ["http://", "https://", "s3://"]
+        .iter()
+        .any(|scheme| location.starts_with(scheme))
+}
+
+/// Returns true if the last segment of `location` has the extension of an RDF file, which tells
+/// a dump on a web server apart from a SPARQL endpoint.
+pub fn looks_like_file(location: &str) -> bool {
+    let path = location.split(['?', '#']).next().unwrap_or("");
+    let name = path.rsplit('/').next().unwrap_or("");
+    match name.rsplit_once('.') {
+        Some((_, ext)) => {
+            ["nt", "nq", "n3", "ntriples", "nquads"].contains(&ext.to_ascii_lowercase().as_str())
+        }
+        None => false,
+    }
+}
+
+/// Returns the source that reads `location`, or an error if it needs a feature this build lacks.
+pub fn source_for(location: &Path) -> io::Result<Box<dyn DataSource>> {
+    let location = location.to_string_lossy();
+    if location.starts_with("s3://") {
+        #[cfg(feature = "s3")]
+        return Ok(Box::new(S3Source::from_env()));
+        #[cfg(not(feature = "s3"))]
+        return Err(Error::new(
+            ErrorKind::Unsupported,
+            "reading s3:// urls needs the s3 feature",
+        ));
+    }
+    if location.starts_with("http://") || location.starts_with("https://") {
+        #[cfg(feature = "remote")]
+        return Ok(Box::new(HttpSource));
+        #[cfg(not(feature = "remote"))]
+        return Err(Error::new(
+            ErrorKind::Unsupported,
+            "reading http:// urls needs the remote feature",
+        ));
+    }
+    Ok(Box::new(LocalSource))
+}
+
+/// Opens the file at `location`, wherever it is.
+pub fn open(location: &Path) -> io::Result<Box<dyn Read + Send>> {
+    source_for(location)?.open(&location.to_string_lossy())
+}
+
+/// Returns the first non-empty line of the file at `location`, reading only the start of
+/// remote files. A first line longer than that start is cut.
+pub fn first_line(location: &Path) -> io::Result<Option<String>> {
+    if !is_remote(location) {
+        return crate::util::io::first_line(location);
+    }
+    let start =
+        source_for(location)?.read_range(&location.to_string_lossy(), 0, FIRST_LINE_BYTES)?;
+    for line in LineReader::new(&start[..], None) {
+        let line = line?;
+        if !line.is_empty() {
+            return Ok(Some(line));
+        }
+    }
+    Ok(None)
+}
+
+/// Files on the local file system, at their path.
+pub struct LocalSource;
+
+impl DataSource for LocalSource {
+    fn open(&self, location: &str) -> io::Result<Box<dyn Read + Send>> {
+        Ok(Box::new(File::open(location)?))
+    }
+
+    fn read_range(&self, location: &str, start: u64, len: u64) -> io::Result<Vec<u8>> {
+        let mut file = File::open(location)?;
+        file.seek(SeekFrom::Start(start))?;
+        let mut bytes = Vec::new();
+        file.take(len).read_to_end(&mut bytes)?;
+        Ok(bytes)
+    }
+
+    fn list(&self, prefix: &str) -> io::Result<Vec<String>> {
+        // The entries of the folder the prefix ends in, named like the prefix.
+        let split = prefix.rfind('/').map_or(0, |i| i + 1);
+        let dir = if split == 0 { "." } else { &prefix[..split] };
+        let mut locations: Vec<String> = Vec::new();
+        for entry in fs::read_dir(dir)? {
+            let entry = entry?;
+            let location = format!(
+                "{}{}",
+                &prefix[..split],
+                entry.file_name().to_string_lossy()
+            );
+            if location.starts_with(prefix) && entry.file_type()?.is_file() {
+                locations.push(location);
+            }
+        }
+        locations.sort();
+        Ok(locations)
+    }
+}
+
+/// Files on a web server, at their `http://` url. Listing is not part of HTTP, so it fails.
+///
+/// `https://` urls are turned away, as this build has no TLS; a plain HTTP mirror or a local
+/// proxy can serve them.
+#[cfg(feature = "remote")]
+pub struct HttpSource;
+
+#[cfg(feature = "remote")]
+impl DataSource for HttpSource {
+    fn open(&self, location: &str) -> io::Result<Box<dyn Read + Send>> {
+        check_plain_http(location)?;
+        let (_, body) =
+            crate::util::http::get_reader(location, &[], crate::util::http::DEFAULT_TIMEOUT)?;
+        Ok(Box::new(body))
+    }
+
+    fn read_range(&self, location: &str, start: u64, len: u64) -> io::Result<Vec<u8>> {
+        check_plain_http(location)?;
+        let mut bytes = Vec::new();
+        if len == 0 {
+            return Ok(bytes);
+        }
+        let range = format!("bytes={}-{}", start, start + len - 1);
+        let (status, mut body) = crate::util::http::get_reader(
+            location,
+            &[("Range", &range)],
+            crate::util::http::DEFAULT_TIMEOUT,
+        )?;
+        // A server that ignores the range sends the whole file.
+        if status != 206 {
+            io::copy(&mut body.by_ref().take(start), &mut io::sink())?;
+        }
+        body.take(len).read_to_end(&mut bytes)?;
+        Ok(bytes)
+    }
+
+    fn list(&self, prefix: &str) -> io::Result<Vec<String>> {
+        Err(Error::new(
+            ErrorKind::Unsupported,
+            format!("cannot list {}: web servers have no listing", prefix),
+        ))
+    }
+}
+
+#[cfg(feature = "remote")]
+fn check_plain_http(location: &str) -> io::Result<()> {
+    if location.starts_with("https://") {
+        return Err(Error::new(
+            ErrorKind::Unsupported,
+            format!(
+                "cannot read {}: https needs TLS, which this build does not include; use http:// or a local copy",
+                location
+            ),
+        ));
+    }
+    Ok(())
+}
+
+/// Objects in S3 buckets, at their `s3://bucket/key` url.
+///
+/// Requests go to the path-style url of the object at the endpoint in `AWS_ENDPOINT_URL`, or
+/// `http://s3.amazonaws.com`, such as a MinIO server or a gateway. They are not signed, so only
+/// buckets that allow anonymous reads can be read.
+#[cfg(feature = "s3")]
+pub struct S3Source {
+    endpoint: String,
+}
+
+#[cfg(feature = "s3")]
+impl S3Source {
+    pub fn new(endpoint: &str) -> Self {
+        S3Source {
+            endpoint: endpoint.trim_end_matches('/').to_string(),
+        }
+    }
+
+    pub fn from_env() -> Self {
+        let endpoint = std::env::var("AWS_ENDPOINT_URL")
+            .unwrap_or_else(|_| "http://s3.amazonaws.com".to_string());
+        S3Source::new(&endpoint)
+    }
+
+    /// Returns the http url of the object at `location`.
+    fn url(&self, location: &str) -> io::Result<String> {
+        let (bucket, key) = split_s3(location)?;
+        let key: Vec<String> = key.split('/').map(crate::util::http::encode).collect();
+        Ok(format!("{}/{}/{}", self.endpoint, bucket, key.join("/")))
+    }
+}
+
+#[cfg(feature = "s3")]
+impl DataSource for S3Source {
+    fn open(&self, location: &str) -> io::Result<Box<dyn Read + Send>> {
+        HttpSource.open(&self.url(location)?)
+    }
+
+    fn read_range(&self, location: &str, start: u64, len: u64) -> io::Result<Vec<u8>> {
+        HttpSource.read_range(&self.url(location)?, start, len)
+    }
+
+    /// Pages through the keys with ListObjectsV2, which returns them sorted.
+    fn list(&self, prefix: &str) -> io::Result<Vec<String>> {
+        let (bucket, key_prefix) = split_s3(prefix)?;
+        let mut locations: Vec<String> = Vec::new();
+        let mut token: Option<String> = None;
+        loop {
+            let mut url = format!(
+                "{}/{}?list-type=2&prefix={}",
+                self.endpoint,
+                bucket,
+                crate::util::http::encode(key_prefix)
+            );
+            if let Some(token) = &token {
+                url.push_str(&format!(
+                    "&continuation-token={}",
+                    crate::util::http::encode(token)
+                ));
+            }
+            let (_, mut body) =
+                crate::util::http::get_reader(&url, &[], crate::util::http::DEFAULT_TIMEOUT)?;
+            let mut xml = String::new();
+            body.read_to_string(&mut xml)?;
+
+            for key in xml_values(&xml, "Key") {
+                locations.push(format!("s3://{}/{}", bucket, key));
+            }
+            token = xml_values(&xml, "NextContinuationToken").pop();
+            if token.is_none() {
+                return Ok(locations);
+            }
+        }
+    }
+}
+
+/// Splits `s3://bucket/key` into the bucket and the key.
+#[cfg(feature = "s3")]
+fn split_s3(location: &str) -> io::Result<(&str, &str)> {
+    location
+        .strip_prefix("s3://")
+        .and_then(|rest| rest.split_once('/'))
+        .ok_or_else(|| {
+            Error::new(
+                ErrorKind::InvalidInput,
+                format!("expected s3://bucket/key, got {}", location),
+            )
+        })
+}
+
+/// Returns the unescaped text of every `<tag>` element of `xml`.
+#[cfg(feature = "s3")]
+fn xml_values(xml: &str, tag: &str) -> Vec<String> {
+    let (open, close) = (format!("<{}>", tag), format!("</{}>", tag));
+    let mut values = Vec::new();
+    let mut rest = xml;
+    while let Some(start) = rest.find(&open) {
+        rest = &rest[start + open.len()..];
+        let Some(end) = rest.find(&close) else {
+            break;
+        };
+        values.push(
+            rest[..end]
+                .replace("&lt;", "<")
+                .replace("&gt;", ">")
+                .replace("&quot;", "\"")
+                .replace("&apos;", "'")
+                .replace("&amp;", "&"),
+        );
+        rest = &rest[end + close.len()..];
+    }
+    values
+}
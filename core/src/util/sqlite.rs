@@ -0,0 +1,226 @@
+//! A small binding of the system SQLite library: just enough to write tables in a transaction
+//! and read them back.
+
+use std::ffi::{c_char, c_int, c_void, CStr, CString};
+use std::io::{Error, ErrorKind};
+use std::path::Path;
+use std::ptr;
+
+const SQLITE_OK: c_int = 0;
+const SQLITE_ROW: c_int = 100;
+const SQLITE_DONE: c_int = 101;
+const SQLITE_NULL: c_int = 5;
+const SQLITE_OPEN_READONLY: c_int = 0x1;
+const SQLITE_OPEN_READWRITE: c_int = 0x2;
+const SQLITE_OPEN_CREATE: c_int = 0x4;
+const SQLITE_UTF8: u8 = 1;
+/// Makes SQLite copy a bound text before the call returns.
+const SQLITE_TRANSIENT: isize = -1;
+
+#[link(name = "sqlite3")]
+extern "C" {
+    fn sqlite3_open_v2(
+        filename: *const c_char,
+        db: *mut *mut c_void,
+        flags: c_int,
+        vfs: *const c_char,
+    ) -> c_int;
+    fn sqlite3_close(db: *mut c_void) -> c_int;
+    fn sqlite3_errmsg(db: *mut c_void) -> *const c_char;
+    fn sqlite3_exec(
+        db: *mut c_void,
+        sql: *const c_char,
+        callback: *const c_void,
+        arg: *mut c_void,
+        errmsg: *mut *mut c_char,
+    ) -> c_int;
+    fn sqlite3_prepare_v2(
+        db: *mut c_void,
+        sql: *const c_char,
+        len: c_int,
+        stmt: *mut *mut c_void,
+        tail: *mut *const c_char,
+    ) -> c_int;
+    fn sqlite3_bind_int64(stmt: *mut c_void, index: c_int, value: i64) -> c_int;
+    fn sqlite3_bind_null(stmt: *mut c_void, index: c_int) -> c_int;
+    fn sqlite3_bind_text64(
+        stmt: *mut c_void,
+        index: c_int,
+        value: *const c_char,
+        len: u64,
+        destructor: isize,
+        encoding: u8,
+    ) -> c_int;
+    fn sqlite3_step(stmt: *mut c_void) -> c_int;
+    fn sqlite3_reset(stmt: *mut c_void) -> c_int;
+    fn sqlite3_finalize(stmt: *mut c_void) -> c_int;
+    fn sqlite3_column_type(stmt: *mut c_void, col: c_int) -> c_int;
+    fn sqlite3_column_int64(stmt: *mut c_void, col: c_int) -> i64;
+    fn sqlite3_column_text(stmt: *mut c_void, col: c_int) -> *const u8;
+    fn sqlite3_column_bytes(stmt: *mut c_void, col: c_int) -> c_int;
+}
+
+/// A value bound to a parameter of a statement.
+pub enum Value<'a> {
+    Int(i64),
+    Text(&'a str),
+    Null,
+}
+
+/// An open database.
+pub struct Connection {
+    db: *mut c_void,
+}
+
+impl Connection {
+    /// Opens the database at `path`, creating it unless `read_only`.
+    pub fn open(path: &Path, read_only: bool) -> Result<Self, Error> {
+        let name = CString::new(path.display().to_string())
+            .map_err(|e| Error::new(ErrorKind::InvalidInput, e))?;
+        let flags = match read_only {
+            true => SQLITE_OPEN_READONLY,
+            false => SQLITE_OPEN_READWRITE | SQLITE_OPEN_CREATE,
+        };
+        let mut db = ptr::null_mut();
+        // SAFETY: `name` is a valid C string and `db` receives the handle, which SQLite sets
+        // even when opening fails, so it is closed on drop either way.
+        let code = unsafe { sqlite3_open_v2(name.as_ptr(), &mut db, flags, ptr::null()) };
+        let conn = Connection { db };
+        match code {
+            SQLITE_OK => Ok(conn),
+            _ => Err(conn.error()),
+        }
+    }
+
+    /// Runs the statements of `sql`, which take no parameters.
+    pub fn execute(&self, sql: &str) -> Result<(), Error> {
+        let sql = CString::new(sql).map_err(|e| Error::new(ErrorKind::InvalidInput, e))?;
+        // SAFETY: `sql` is a valid C string; no callback is passed.
+        let code = unsafe {
+            sqlite3_exec(
+                self.db,
+                sql.as_ptr(),
+                ptr::null(),
+                ptr::null_mut(),
+                ptr::null_mut(),
+            )
+        };
+        match code {
+            SQLITE_OK => Ok(()),
+            _ => Err(self.error()),
+        }
+    }
+
+    /// Compiles the single statement `sql`.
+    pub fn prepare(&self, sql: &str) -> Result<Statement<'_>, Error> {
+        let sql = CString::new(sql).map_err(|e| Error::new(ErrorKind::InvalidInput, e))?;
+        let mut stmt = ptr::null_mut();
+        // SAFETY: `sql` is a valid C string and `stmt` receives the compiled statement.
+        let code =
+            unsafe { sqlite3_prepare_v2(self.db, sql.as_ptr(), -1, &mut stmt, ptr::null_mut()) };
+        match code {
+            SQLITE_OK => Ok(Statement { conn: self, stmt }),
+            _ => Err(self.error()),
+        }
+    }
+
+    /// Returns the error of the last call that failed.
+    fn error(&self) -> Error {
+        // SAFETY: SQLite returns a valid C string for any handle, null included.
+        let message = unsafe { CStr::from_ptr(sqlite3_errmsg(self.db)) };
+        Error::other(format!("sqlite: {}", message.to_string_lossy()))
+    }
+}
+
+impl Drop for Connection {
+    fn drop(&mut self) {
+        // SAFETY: the statements borrow the connection, so they are all finalized by now.
+        unsafe { sqlite3_close(self.db) };
+    }
+}
+
+/// A compiled statement of a [`Connection`].
+pub struct Statement<'a> {
+    conn: &'a Connection,
+    stmt: *mut c_void,
+}
+
+impl Statement<'_> {
+    /// Runs the statement, which returns no rows, with the parameters `values`.
+    pub fn run(&mut self, values: &[Value]) -> Result<(), Error> {
+        for (i, value) in values.iter().enumerate() {
+            let index = i as c_int + 1;
+            // SAFETY: `self.stmt` is a live statement; texts are copied by SQLite.
+            let code = unsafe {
+                match value {
+                    Value::Int(n) => sqlite3_bind_int64(self.stmt, index, *n),
+                    Value::Null => sqlite3_bind_null(self.stmt, index),
+                    // Texts over the length limit of SQLite are refused with SQLITE_TOOBIG.
+                    Value::Text(s) => sqlite3_bind_text64(
+                        self.stmt,
+                        index,
+                        s.as_ptr() as *const c_char,
+                        s.len() as u64,
+                        SQLITE_TRANSIENT,
+                        SQLITE_UTF8,
+                    ),
+                }
+            };
+            if code != SQLITE_OK {
+                return Err(self.conn.error());
+            }
+        }
+        // SAFETY: `self.stmt` is a live statement, reset for the next run once it is done.
+        let code = unsafe {
+            let code = sqlite3_step(self.stmt);
+            sqlite3_reset(self.stmt);
+            code
+        };
+        match code {
+            SQLITE_DONE => Ok(()),
+            _ => Err(self.conn.error()),
+        }
+    }
+
+    /// Steps to the next row of the result, returning false once there are none left.
+    pub fn step(&mut self) -> Result<bool, Error> {
+        // SAFETY: `self.stmt` is a live statement.
+        match unsafe { sqlite3_step(self.stmt) } {
+            SQLITE_ROW => Ok(true),
+            SQLITE_DONE => Ok(false),
+            _ => Err(self.conn.error()),
+        }
+    }
+
+    /// Returns the integer in column `col` of the current row, `None` if it is null.
+    pub fn int(&self, col: usize) -> Option<i64> {
+        // SAFETY: `self.stmt` is on a row; out of range columns read as null.
+        unsafe {
+            match sqlite3_column_type(self.stmt, col as c_int) {
+                SQLITE_NULL => None,
+                _ => Some(sqlite3_column_int64(self.stmt, col as c_int)),
+            }
+        }
+    }
+
+    /// Returns the text in column `col` of the current row.
+    pub fn text(&self, col: usize) -> String {
+        // SAFETY: `self.stmt` is on a row; the text stays valid until the next step, and is
+        // copied before. The length is read after the text, as SQLite documents.
+        unsafe {
+            let text = sqlite3_column_text(self.stmt, col as c_int);
+            if text.is_null() {
+                return String::new();
+            }
+            let len = sqlite3_column_bytes(self.stmt, col as c_int) as usize;
+            String::from_utf8_lossy(std::slice::from_raw_parts(text, len)).into_owned()
+        }
+    }
+}
+
+impl Drop for Statement<'_> {
+    fn drop(&mut self) {
+        // SAFETY: `self.stmt` is live and not used after this.
+        unsafe { sqlite3_finalize(self.stmt) };
+    }
+}
@@ -0,0 +1,76 @@
+use std::fs::{self, File, OpenOptions};
+use std::io::{Error, ErrorKind, Write};
+use std::path::Path;
+
+/// Name of the write-ahead log in the meta folder.
+pub const WAL_FILE: &str = "wal";
+
+/// Write-ahead log of the changes applied to a summary in memory since the meta folder was last
+/// written, one line each in the syntax of an update file.
+///
+/// A change is appended and synced to disk before it is applied, so a change that was
+/// acknowledged survives a crash: the next run reading the meta folder replays the log in place
+/// of an update, and the log is rotated once a snapshot holding its changes is written.
+pub struct Wal {
+    file: File,
+}
+
+impl Wal {
+    /// Opens the log at `path` to append to it, creating it if there is none.
+    pub fn open(path: &Path) -> Result<Self, Error> {
+        let created = !path.exists();
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        // The entry of a new log must be durable too, or the whole log can be lost.
+        if created {
+            if let Some(dir) = path.parent().filter(|d| !d.as_os_str().is_empty()) {
+                File::open(dir)?.sync_all()?;
+            }
+        }
+        Ok(Self { file })
+    }
+
+    /// Appends the change `line` and waits until it is on disk.
+    pub fn append(&mut self, line: &str) -> Result<(), Error> {
+        self.append_all(&[line])
+    }
+
+    /// Appends the changes `lines` and waits until they are all on disk.
+    pub fn append_all<S: AsRef<str>>(&mut self, lines: &[S]) -> Result<(), Error> {
+        let mut text = String::new();
+        for line in lines {
+            text.push_str(line.as_ref());
+            text.push('\n');
+        }
+        self.file.write_all(text.as_bytes())?;
+        self.file.sync_data()
+    }
+}
+
+/// Returns the changes in the log at `path`, none if there is no log.
+///
+/// A last line without its line ending was cut short by a crash before it was acknowledged, so
+/// it is left out.
+pub fn read(path: &Path) -> Result<Vec<String>, Error> {
+    let text = match fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(e) if e.kind() == ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e),
+    };
+    let complete = match text.rfind('\n') {
+        Some(i) => &text[..i],
+        None => "",
+    };
+    Ok(complete
+        .lines()
+        .filter(|l| !l.is_empty())
+        .map(String::from)
+        .collect())
+}
+
+/// Drops the log at `path`, once a snapshot holding its changes has been written.
+pub fn rotate(path: &Path) -> Result<(), Error> {
+    match fs::remove_file(path) {
+        Err(e) if e.kind() != ErrorKind::NotFound => Err(e),
+        _ => Ok(()),
+    }
+}
@@ -0,0 +1,116 @@
+//! A small binding of the system zstd library: just enough to compress a buffer in one call and
+//! to decompress frames that may not record their size.
+
+use std::ffi::{c_char, c_int, c_void, CStr};
+use std::io::{Error, ErrorKind};
+
+/// Highest compression level zstd accepts.
+pub const MAX_LEVEL: i32 = 22;
+
+#[repr(C)]
+struct InBuffer {
+    src: *const c_void,
+    size: usize,
+    pos: usize,
+}
+
+#[repr(C)]
+struct OutBuffer {
+    dst: *mut c_void,
+    size: usize,
+    pos: usize,
+}
+
+#[link(name = "zstd")]
+extern "C" {
+    fn ZSTD_compressBound(src_size: usize) -> usize;
+    fn ZSTD_compress(
+        dst: *mut c_void,
+        dst_capacity: usize,
+        src: *const c_void,
+        src_size: usize,
+        level: c_int,
+    ) -> usize;
+    fn ZSTD_isError(code: usize) -> u32;
+    fn ZSTD_getErrorName(code: usize) -> *const c_char;
+    fn ZSTD_createDStream() -> *mut c_void;
+    fn ZSTD_freeDStream(stream: *mut c_void) -> usize;
+    fn ZSTD_DStreamOutSize() -> usize;
+    fn ZSTD_decompressStream(
+        stream: *mut c_void,
+        output: *mut OutBuffer,
+        input: *mut InBuffer,
+    ) -> usize;
+}
+
+/// Returns `code`, or the error it names.
+fn check(code: usize) -> Result<usize, Error> {
+    if unsafe { ZSTD_isError(code) } == 0 {
+        return Ok(code);
+    }
+    let name = unsafe { CStr::from_ptr(ZSTD_getErrorName(code)) };
+    Err(Error::new(
+        ErrorKind::InvalidData,
+        format!("zstd: {}", name.to_string_lossy()),
+    ))
+}
+
+/// Compresses `data` into one zstd frame at `level`.
+pub fn compress(data: &[u8], level: i32) -> Result<Vec<u8>, Error> {
+    let mut out: Vec<u8> = Vec::with_capacity(unsafe { ZSTD_compressBound(data.len()) });
+    let len = check(unsafe {
+        ZSTD_compress(
+            out.as_mut_ptr() as *mut c_void,
+            out.capacity(),
+            data.as_ptr() as *const c_void,
+            data.len(),
+            level,
+        )
+    })?;
+    unsafe { out.set_len(len) };
+    Ok(out)
+}
+
+/// Decompresses the zstd frames of `data`, such as the ones the `zstd` tool writes.
+pub fn decompress(data: &[u8]) -> Result<Vec<u8>, Error> {
+    let stream = unsafe { ZSTD_createDStream() };
+    if stream.is_null() {
+        return Err(Error::other("zstd: cannot create a decompression stream"));
+    }
+    let chunk = unsafe { ZSTD_DStreamOutSize() };
+    let mut out: Vec<u8> = Vec::new();
+    let mut input = InBuffer {
+        src: data.as_ptr() as *const c_void,
+        size: data.len(),
+        pos: 0,
+    };
+    // Non-zero while a frame is not complete.
+    let mut pending = 0;
+    let result = loop {
+        out.reserve(chunk);
+        let mut output = OutBuffer {
+            dst: unsafe { out.as_mut_ptr().add(out.len()) } as *mut c_void,
+            size: out.capacity() - out.len(),
+            pos: 0,
+        };
+        let code = unsafe { ZSTD_decompressStream(stream, &mut output, &mut input) };
+        unsafe { out.set_len(out.len() + output.pos) };
+        match check(code) {
+            Ok(hint) => pending = hint,
+            Err(e) => break Err(e),
+        }
+        // All input is read and nothing is left to flush once the output is not filled.
+        if input.pos == input.size && output.pos < output.size {
+            break Ok(());
+        }
+    };
+    unsafe { ZSTD_freeDStream(stream) };
+    result?;
+    if pending != 0 {
+        return Err(Error::new(
+            ErrorKind::UnexpectedEof,
+            "zstd: the data ends in the middle of a frame",
+        ));
+    }
+    Ok(out)
+}
@@ -0,0 +1,306 @@
+use crate::models::clique::CliqueCollection;
+use crate::models::dataset::Dataset;
+use crate::models::literals::LITERALS_FILE;
+use crate::models::meta::Meta;
+use crate::parser::clique::{Fingerprint, CLIQUES_FILE};
+use crate::parser::dataset::SOURCES_FILE;
+use crate::parser::encoded;
+use crate::parser::meta::{COMPACT_META_FILE, COMPRESSED_META_FILE, META_DB_FILE, META_FILE};
+use crate::util::io;
+use crate::util::profile::span;
+use crate::util::rng::Fnv1a;
+use crate::{Config, Grouping};
+use std::fs;
+use std::fs::create_dir;
+use std::fs::{remove_file, rename, File};
+use std::io::{BufWriter, Error, Write};
+use std::path::{Path, PathBuf};
+
+#[cfg(feature = "endpoint")]
+pub mod endpoint;
+
+pub fn run(
+    config: &Config,
+    dataset: &Dataset,
+    meta: &Meta,
+    sc: &CliqueCollection,
+    tc: &CliqueCollection,
+) {
+    span!("write");
+    let (lines_fingerprint, lines_hash) = write_triples(&config.summary_path(), dataset).unwrap();
+
+    // Written to more than once when the update is applied in chunks.
+    if config.use_fast && !config.meta_folder_path.is_dir() {
+        create_dir(&config.meta_folder_path).unwrap();
+    }
+
+    let dict_lines = write_literals(&config.meta_folder_path.join(LITERALS_FILE), dataset).unwrap();
+    write_dict(&config.meta_folder_path.join("dict"), &dict_lines).unwrap();
+    write_dict(
+        &config.meta_folder_path.join("preds"),
+        &dataset.pred_strings(),
+    )
+    .unwrap();
+    let sources = dataset.source_strings();
+    if !sources.is_empty() {
+        write_dict(&config.meta_folder_path.join(SOURCES_FILE), &sources).unwrap();
+    }
+    write_meta_to_folder(config, meta).unwrap();
+    let compact_path = config.meta_folder_path.join(COMPACT_META_FILE);
+    if config.compact_meta {
+        write_compact_meta(&compact_path, meta).unwrap();
+    } else if compact_path.exists() {
+        // A compact meta file left from an earlier run would no longer match.
+        remove_file(&compact_path).unwrap();
+    }
+    let fingerprint = lines_fingerprint.finish(&config.grouping);
+    write_cliques(
+        &config.meta_folder_path.join(CLIQUES_FILE),
+        sc,
+        tc,
+        fingerprint,
+    )
+    .unwrap();
+    let key = encoded::Key {
+        dataset: lines_hash,
+        dict: encoded::hash_dict(&dict_lines, &dataset.pred_strings(), &sources),
+        grouping: config.grouping,
+    };
+    write_encoded(
+        &config.meta_folder_path.join(encoded::TRIPLES_FILE),
+        dataset,
+        &key,
+        fingerprint,
+    )
+    .unwrap();
+
+    #[cfg(feature = "endpoint")]
+    if let Some(push) = &config.push {
+        endpoint::push(push, dataset, meta).unwrap();
+    }
+}
+
+/// Writes `rejected` changes to the rejected-changes file, or removes a stale one.
+pub fn write_rejected(config: &Config, rejected: &Vec<String>) {
+    let path = config.rejected_path();
+    if write_changes(&path, rejected) {
+        println!(
+            "Rejected {} changes, see {}",
+            rejected.len(),
+            path.display()
+        );
+    }
+}
+
+/// Writes the malformed lines `skipped` by the parser to the skipped-lines file, or removes a
+/// stale one.
+pub fn write_skipped(config: &Config, skipped: &Vec<String>) {
+    let path = config.skipped_path();
+    if write_changes(&path, skipped) {
+        println!(
+            "Skipped {} malformed lines, see {}",
+            skipped.len() / 2,
+            path.display()
+        );
+    }
+}
+
+/// Writes the `pending` changes of an interrupted update to the pending-changes file, or
+/// removes a stale one.
+pub fn write_pending(config: &Config, pending: &Vec<String>) {
+    let path = config.pending_path();
+    if write_changes(&path, pending) {
+        println!(
+            "Time budget exhausted, {} changes left in {}; apply it as the update to resume",
+            pending.len(),
+            path.display()
+        );
+    }
+}
+
+/// Writes the insertions that violate the schema to the violations file, or removes a stale
+/// one.
+pub fn write_violations(config: &Config, violations: &Vec<String>) {
+    let path = config.violations_path();
+    if write_changes(&path, violations) {
+        println!(
+            "Left out {} insertions that violate the schema, see {}",
+            violations.len() / 2,
+            path.display()
+        );
+    }
+}
+
+/// Writes the changes an update skipped as `inconsistent` to the inconsistent-changes file, or
+/// removes a stale one.
+pub fn write_inconsistent(config: &Config, inconsistent: &Vec<String>) {
+    let path = config.inconsistent_path();
+    if write_changes(&path, inconsistent) {
+        println!(
+            "Skipped {} changes the summary is inconsistent around, see {}",
+            inconsistent.iter().filter(|l| !l.starts_with('#')).count(),
+            path.display()
+        );
+    }
+}
+
+/// Writes `changes` to `path`, or removes `path` if there are none. Returns true if written.
+fn write_changes(path: &PathBuf, changes: &Vec<String>) -> bool {
+    if changes.is_empty() {
+        if path.exists() {
+            remove_file(path).unwrap();
+        }
+        return false;
+    }
+    io::write_lines(path, changes).unwrap();
+    true
+}
+
+/// Writes the summary triples to `path` and returns the fingerprint and the hash of the
+/// written lines, which are not kept.
+fn write_triples(path: &PathBuf, dataset: &Dataset) -> Result<(Fingerprint, u64), Error> {
+    let mut out = BufWriter::new(File::create(path)?);
+    let mut fingerprint = Fingerprint::default();
+    let mut hash = Fnv1a::default();
+    let mut write_line = |line: &str| {
+        fingerprint.add(line);
+        encoded::hash_line(&mut hash, line);
+        writeln!(out, "{}", line)
+    };
+
+    dataset.decode_triples(dataset.triples.occurrences(), &mut write_line)?;
+    for line in &dataset.passthrough {
+        write_line(line)?;
+    }
+    out.flush()?;
+    Ok((fingerprint, hash.finish()))
+}
+
+fn write_dict(path: &PathBuf, lines: &Vec<String>) -> Result<(), Error> {
+    if path.exists() {
+        remove_file(path)?;
+    }
+    io::write_lines(path, lines)
+}
+
+/// Writes the literals the dict keeps out of memory to `path`, or removes a stale file, and
+/// returns the lines of the dict file. The file is written through a temporary file, as the
+/// literals of the last run are read from the one at `path` while it is written.
+fn write_literals(path: &Path, dataset: &Dataset) -> Result<Vec<String>, Error> {
+    if !dataset.keeps_literals() {
+        if path.exists() {
+            remove_file(path)?;
+        }
+        return Ok(dataset.dict_strings());
+    }
+    let tmp = path.with_extension("tmp");
+    let lines = dataset.dict_lines(&tmp)?;
+    rename(&tmp, path)?;
+    Ok(lines)
+}
+
+#[cfg(feature = "json")]
+fn write_meta(path: &PathBuf, meta: &Meta) -> Result<(), Error> {
+    let data = crate::parser::meta::to_file(meta);
+    let file_str = serde_json::to_string(&data)?;
+    Ok(io::write_lines(path, &vec![file_str])?)
+}
+
+#[cfg(not(feature = "json"))]
+fn write_meta(_path: &PathBuf, _meta: &Meta) -> Result<(), Error> {
+    Err(Error::other(format!(
+        "this build cannot write {}; build with --features json",
+        META_FILE
+    )))
+}
+
+/// Writes the meta to the meta database of the meta folder with `--sqlite-meta`, to its
+/// compressed meta file with `--zstd-meta`, and to its meta file otherwise, and removes the
+/// others, which would no longer match.
+fn write_meta_to_folder(config: &Config, meta: &Meta) -> Result<(), Error> {
+    let path = config.meta_folder_path.join(META_FILE);
+    let compressed_path = config.meta_folder_path.join(COMPRESSED_META_FILE);
+    let db_path = config.meta_folder_path.join(META_DB_FILE);
+    #[cfg(feature = "sqlite")]
+    if config.sqlite_meta {
+        let data = crate::parser::meta::to_file(meta);
+        crate::parser::meta_db::write(&db_path, &data)?;
+        return remove_stale(&[&path, &compressed_path]);
+    }
+    #[cfg(feature = "zstd")]
+    if let Some(level) = config.zstd_meta {
+        let file_str = serde_json::to_string(&crate::parser::meta::to_file(meta))?;
+        let data = crate::util::zstd::compress(file_str.as_bytes(), level)?;
+        fs::write(&compressed_path, data)?;
+        return remove_stale(&[&path, &db_path]);
+    }
+    write_meta(&path, meta)?;
+    remove_stale(&[&compressed_path, &db_path])
+}
+
+/// Removes those of `paths` that exist.
+fn remove_stale(paths: &[&PathBuf]) -> Result<(), Error> {
+    for path in paths {
+        if path.exists() {
+            remove_file(path)?;
+        }
+    }
+    Ok(())
+}
+
+/// Writes the compact meta of `meta` to `path` through a temporary file, since queries may have
+/// the old file mapped, and changing it in place would change their data under them.
+fn write_compact_meta(path: &Path, meta: &Meta) -> Result<(), Error> {
+    let tmp = path.with_extension("bin.tmp");
+    fs::write(&tmp, meta.serialize_compact())?;
+    rename(&tmp, path)
+}
+
+#[cfg(feature = "json")]
+fn write_cliques(
+    path: &PathBuf,
+    sc: &CliqueCollection,
+    tc: &CliqueCollection,
+    fingerprint: u64,
+) -> Result<(), Error> {
+    let file_str = serde_json::to_string(&crate::parser::clique::to_file(sc, tc, fingerprint))?;
+    io::write_lines(path, &vec![file_str])
+}
+
+/// Without JSON the cliques are not saved, and the next run rebuilds them from the summary.
+#[cfg(not(feature = "json"))]
+fn write_cliques(
+    _path: &PathBuf,
+    _sc: &CliqueCollection,
+    _tc: &CliqueCollection,
+    _fingerprint: u64,
+) -> Result<(), Error> {
+    Ok(())
+}
+
+/// Writes the summary triples of `dataset` encoded for `key` to `path`, so the next run on the
+/// summary can read them instead of parsing it, or removes a stale file if the summary has
+/// pass-through lines, which are not encoded. The file is written through a temporary file, as
+/// it is mapped while it is read.
+fn write_encoded(
+    path: &Path,
+    dataset: &Dataset,
+    key: &encoded::Key,
+    fingerprint: u64,
+) -> Result<(), Error> {
+    if !dataset.passthrough.is_empty() {
+        if path.exists() {
+            remove_file(path)?;
+        }
+        return Ok(());
+    }
+    // Type triples are read from the meta instead of the summary, unless they are grouped on.
+    let skip_types = matches!(key.grouping, Grouping::Preds | Grouping::Untyped);
+    let triples = dataset
+        .triples
+        .occurrences()
+        .filter(|t| !(skip_types && t.is_type));
+    let tmp = path.with_extension("tmp");
+    fs::write(&tmp, encoded::serialize(triples, key, fingerprint))?;
+    rename(&tmp, path)
+}
@@ -0,0 +1,118 @@
+use crate::models::dataset::Dataset;
+use crate::models::meta::Meta;
+use crate::rdf;
+use crate::util::hash::HashMap;
+use crate::util::http;
+use crate::util::set_ops::UnionFind;
+use std::io::{Error, ErrorKind};
+use std::thread;
+use std::time::Duration;
+
+/// Predicate linking a supernode to each of its members in the pushed membership mapping.
+const MEMBER_PRED: &str = "<urn:teriyaki:member>";
+
+/// Options for pushing the summary to a SPARQL endpoint.
+pub struct PushConfig {
+    pub url: String,
+    pub batch_size: usize,
+    pub retries: usize,
+    /// How long connecting, and each read or write of a request, may take.
+    pub timeout: Duration,
+}
+
+/// Pushes the summary graph and the supernode membership mapping to the endpoint in
+/// `push.url` using batched `INSERT DATA` requests, see [`batches`].
+pub fn push(push: &PushConfig, dataset: &Dataset, meta: &Meta) -> Result<(), Error> {
+    let mut lines: Vec<String> = Vec::new();
+    dataset.decode_triples(&dataset.triples.data_triples, |line| {
+        lines.push(line.to_string());
+        Ok(())
+    })?;
+    lines.extend(dataset.passthrough.iter().cloned());
+    lines.extend(membership_lines(dataset, meta)?);
+
+    for batch in batches(&lines, push.batch_size) {
+        let update = format!("INSERT DATA {{\n{}\n}}", batch.join("\n"));
+        send_with_retry(push, &update)?;
+    }
+    Ok(())
+}
+
+/// Splits `lines` into batches of at most `size` lines, in order.
+///
+/// Each request scopes the labels of its blank nodes, so the lines sharing a blank node, directly
+/// or through other lines, go in the same batch; a group of them larger than `size` is a batch of
+/// its own.
+pub fn batches(lines: &[String], size: usize) -> Vec<Vec<&str>> {
+    let mut linked = UnionFind::new(lines.len());
+    let mut first: HashMap<&str, u32> = HashMap::default();
+    for (i, l) in lines.iter().enumerate() {
+        for label in rdf::blank_nodes(l) {
+            let j = *first.entry(label).or_insert(i as u32);
+            linked.union(j, i as u32);
+        }
+    }
+
+    // Groups in the order of their first line, which is their root.
+    let mut groups: Vec<Vec<&str>> = Vec::new();
+    let mut group_of: HashMap<u32, usize> = HashMap::default();
+    for (i, l) in lines.iter().enumerate() {
+        let root = linked.find(i as u32);
+        let group = *group_of.entry(root).or_insert_with(|| {
+            groups.push(Vec::new());
+            groups.len() - 1
+        });
+        groups[group].push(l);
+    }
+
+    let mut batches: Vec<Vec<&str>> = Vec::new();
+    let mut batch: Vec<&str> = Vec::new();
+    for group in groups {
+        if !batch.is_empty() && batch.len() + group.len() > size {
+            batches.push(std::mem::take(&mut batch));
+        }
+        batch.extend(group);
+    }
+    if !batch.is_empty() {
+        batches.push(batch);
+    }
+    batches
+}
+
+fn membership_lines(dataset: &Dataset, meta: &Meta) -> Result<Vec<String>, Error> {
+    let dict = dataset.dict_strings();
+    let term = |id: &u32| {
+        (*id as usize)
+            .checked_sub(1)
+            .and_then(|i| dict.get(i))
+            .filter(|t| !t.is_empty())
+            .ok_or_else(|| Error::new(ErrorKind::NotFound, format!("no term for id {}", id)))
+    };
+    let mut lines: Vec<String> = Vec::new();
+
+    for (snode, members) in meta.get_supernodes() {
+        let snode_str = term(snode)?;
+        for m in members {
+            lines.push(rdf::triple_line(snode_str, MEMBER_PRED, term(m)?));
+        }
+    }
+    Ok(lines)
+}
+
+fn send_with_retry(push: &PushConfig, update: &str) -> Result<(), Error> {
+    let mut attempt = 0;
+    loop {
+        match http::post(&push.url, "application/sparql-update", update, push.timeout) {
+            Ok(_) => return Ok(()),
+            Err(e) if attempt < push.retries => {
+                attempt += 1;
+                println!(
+                    "Pushing batch failed ({}), retrying {}/{}",
+                    e, attempt, push.retries
+                );
+                thread::sleep(Duration::from_millis(500 * attempt as u64));
+            }
+            Err(e) => return Err(e),
+        }
+    }
+}
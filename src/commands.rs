@@ -0,0 +1,52 @@
+use serde::Serialize;
+use std::fmt::Display;
+
+pub mod analyze;
+pub mod bench_cliques;
+pub mod bench_edges;
+pub mod dict;
+pub mod diff;
+pub mod diff_datasets;
+pub mod dry_run;
+pub mod dump_meta;
+pub mod embed;
+pub mod evaluate;
+pub mod evaluate_queries;
+pub mod explain;
+pub mod export_nodes;
+pub mod export_tables;
+pub mod extract;
+pub mod fuzz;
+pub mod help;
+pub mod moved;
+pub mod prepare;
+pub mod reach;
+pub mod repl;
+pub mod stats;
+pub mod validate;
+
+/// Output format of command reports.
+#[derive(Default, PartialEq)]
+pub enum Format {
+    #[default]
+    Text,
+    Json,
+}
+
+impl Format {
+    pub fn parse(arg: Option<&String>) -> Result<Self, &'static str> {
+        match arg.map(|s| s.as_str()) {
+            Some("text") => Ok(Format::Text),
+            Some("json") => Ok(Format::Json),
+            _ => Err("--format expects text or json"),
+        }
+    }
+}
+
+/// Prints `report` to stdout in the given `format`.
+pub fn print_report<T: Serialize + Display>(report: &T, format: &Format) {
+    match format {
+        Format::Text => print!("{}", report),
+        Format::Json => println!("{}", serde_json::to_string_pretty(report).unwrap()),
+    }
+}
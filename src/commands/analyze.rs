@@ -0,0 +1,141 @@
+use super::print_report;
+use crate::models::clique::CliqueCollection;
+use crate::models::dataset::Dataset;
+use crate::models::meta::Meta;
+use crate::models::triple::{Triple, TYPE_STRING};
+use crate::{read_input, Cli};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashSet};
+use std::fmt;
+
+#[derive(Serialize)]
+pub struct AnalyzeReport {
+    pub insertions: usize,
+    pub deletions: usize,
+    /// Insertions with a node or predicate that is not in the summary yet.
+    pub insertions_with_new_terms: usize,
+    /// Deletions of triples that are not in the summary.
+    pub unknown_deletions: usize,
+    /// Number of insertions and deletions per predicate.
+    pub preds: BTreeMap<String, PredChanges>,
+    /// Supernodes in the cliques of the changed predicates and nodes, which the update may
+    /// split or merge.
+    pub affected_supernodes: usize,
+}
+
+#[derive(Default, Serialize)]
+pub struct PredChanges {
+    pub insertions: usize,
+    pub deletions: usize,
+}
+
+impl fmt::Display for AnalyzeReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(
+            f,
+            "insertions:      {} ({} with new terms)",
+            self.insertions, self.insertions_with_new_terms
+        )?;
+        writeln!(
+            f,
+            "deletions:       {} ({} unknown)",
+            self.deletions, self.unknown_deletions
+        )?;
+        writeln!(f, "affected snodes: ~{}", self.affected_supernodes)?;
+        for (pred, c) in &self.preds {
+            writeln!(f, "  {} +{} -{}", pred, c.insertions, c.deletions)?;
+        }
+        Ok(())
+    }
+}
+
+/// Prints statistics about the update without applying it.
+pub fn run(cli: &Cli) {
+    let (dataset, meta, sc, tc) = read_input(cli);
+    print_report(&analyze(&dataset, &meta, &sc, &tc), &cli.format);
+}
+
+pub fn analyze(
+    dataset: &Dataset,
+    meta: &Meta,
+    sc: &CliqueCollection,
+    tc: &CliqueCollection,
+) -> AnalyzeReport {
+    let insertions = &dataset.insertions.data_triples;
+    let deletions = &dataset.deletions.data_triples;
+
+    let mut preds: BTreeMap<String, PredChanges> = BTreeMap::new();
+    for t in insertions {
+        preds.entry(pred_name(dataset, t)).or_default().insertions += 1;
+    }
+    for t in deletions {
+        preds.entry(pred_name(dataset, t)).or_default().deletions += 1;
+    }
+
+    let is_new =
+        |t: &Triple| !meta.contains(&t.sub) || !meta.contains(&t.obj) || !sc.contains_pred(&t.pred);
+
+    AnalyzeReport {
+        insertions: insertions.len(),
+        deletions: deletions.len(),
+        insertions_with_new_terms: insertions.iter().filter(|t| is_new(t)).count(),
+        unknown_deletions: deletions
+            .iter()
+            .filter(|t| !meta.contains_edge(&t.sub, &t.pred, &t.obj))
+            .count(),
+        preds,
+        affected_supernodes: affected_supernodes(insertions.iter().chain(deletions), meta, sc, tc),
+    }
+}
+
+/// Counts the supernodes sharing a source or target clique with the predicate or an endpoint of
+/// any of `triples`.
+fn affected_supernodes<'a>(
+    triples: impl Iterator<Item = &'a Triple>,
+    meta: &Meta,
+    sc: &CliqueCollection,
+    tc: &CliqueCollection,
+) -> usize {
+    let mut source: HashSet<usize> = HashSet::new();
+    let mut target: HashSet<usize> = HashSet::new();
+
+    for t in triples {
+        for (cc, node, indices) in [(sc, &t.sub, &mut source), (tc, &t.obj, &mut target)] {
+            if cc.contains_pred(&t.pred) {
+                indices.insert(cc.get_pred_index(&t.pred));
+            }
+            let node = representative(meta, node);
+            if cc.contains_node(&node) {
+                indices.insert(cc.get_index(&node));
+            }
+        }
+    }
+
+    let mut snodes: HashSet<u32> = HashSet::new();
+    for (cc, indices) in [(sc, &source), (tc, &target)] {
+        for i in indices.iter().filter(|i| **i != 0) {
+            snodes.extend(
+                cc.get_nodes(*i)
+                    .into_iter()
+                    .filter(|n| meta.contains_supernode(n)),
+            );
+        }
+    }
+    snodes.len()
+}
+
+/// Returns the supernode of `node`, or `node` itself if it has none or is not known yet.
+fn representative(meta: &Meta, node: &u32) -> u32 {
+    if meta.contains(node) && !meta.contains_supernode(node) {
+        meta.get_parent(node).unwrap_or(*node)
+    } else {
+        *node
+    }
+}
+
+fn pred_name(dataset: &Dataset, t: &Triple) -> String {
+    if t.is_type {
+        return TYPE_STRING.to_string();
+    }
+    dataset.pred_term(&t.pred).unwrap()
+}
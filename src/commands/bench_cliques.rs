@@ -0,0 +1,143 @@
+use super::{print_report, Format};
+use crate::models::clique::CliqueCollection;
+use crate::util::heap_size::HeapSize;
+use crate::util::rng::Rng;
+use serde::Serialize;
+use std::fmt;
+use std::hint::black_box;
+use std::time::Instant;
+
+/// Settings of `teriyaki bench-cliques`.
+pub struct BenchOptions {
+    /// Numbers of nodes of the cliques measured.
+    pub sizes: Vec<usize>,
+    /// Number of times each measurement is repeated, keeping the fastest.
+    pub rounds: usize,
+    pub seed: u64,
+}
+
+impl Default for BenchOptions {
+    fn default() -> Self {
+        BenchOptions {
+            sizes: vec![16, 256, 4096, 65536, 1 << 20],
+            rounds: 5,
+            seed: 0,
+        }
+    }
+}
+
+#[derive(Serialize)]
+pub struct BenchReport {
+    pub rows: Vec<BenchRow>,
+    /// Smallest size measured at which the bitmap takes less memory than the list.
+    pub memory_crossover: Option<usize>,
+}
+
+/// Costs of a clique of `size` nodes kept as a list or a bitmap, per node.
+#[derive(Serialize)]
+pub struct BenchRow {
+    pub size: usize,
+    pub bitmap: bool,
+    pub insert_ns: f64,
+    pub iterate_ns: f64,
+    pub remove_ns: f64,
+    /// Heap bytes of the clique collection, including its indices.
+    pub bytes: f64,
+}
+
+impl fmt::Display for BenchReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(
+            f,
+            "{:>9} {:<7} {:>10} {:>10} {:>10} {:>10}",
+            "nodes", "kind", "insert ns", "iter ns", "remove ns", "bytes"
+        )?;
+        for r in &self.rows {
+            writeln!(
+                f,
+                "{:>9} {:<7} {:>10.1} {:>10.1} {:>10.1} {:>10.1}",
+                r.size,
+                if r.bitmap { "bitmap" } else { "list" },
+                r.insert_ns,
+                r.iterate_ns,
+                r.remove_ns,
+                r.bytes
+            )?;
+        }
+        match self.memory_crossover {
+            Some(size) => writeln!(f, "bitmaps take less memory from {} nodes", size),
+            None => writeln!(f, "bitmaps take more memory at every size measured"),
+        }
+    }
+}
+
+/// Measures adding, iterating and removing the nodes of a clique of each size, kept as a list
+/// and as a bitmap, to choose `--clique-bitmap-threshold`.
+///
+/// Node ids are drawn from a range 16 times the size of the clique, like the members of a
+/// clique among the other nodes of a dataset, and removed in random order.
+pub fn run(options: &BenchOptions, format: &Format) {
+    let mut rng = Rng::new(options.seed);
+    let mut rows = Vec::new();
+    for &size in &options.sizes {
+        let mut nodes: Vec<u32> = (0..size).map(|_| rng.below(16 * size) as u32).collect();
+        nodes.sort();
+        nodes.dedup();
+        let mut order = nodes.clone();
+        for i in (1..order.len()).rev() {
+            order.swap(i, rng.below(i + 1));
+        }
+        for bitmap in [false, true] {
+            rows.push(measure(&nodes, &order, bitmap, options.rounds));
+        }
+    }
+
+    let memory_crossover = rows
+        .chunks(2)
+        .find(|pair| pair[1].bytes < pair[0].bytes)
+        .map(|pair| pair[0].size);
+    print_report(
+        &BenchReport {
+            rows,
+            memory_crossover,
+        },
+        format,
+    );
+}
+
+fn measure(nodes: &[u32], order: &[u32], bitmap: bool, rounds: usize) -> BenchRow {
+    let per_node = |nanos: u128| nanos as f64 / nodes.len().max(1) as f64;
+    let (mut insert, mut iterate, mut remove) = (u128::MAX, u128::MAX, u128::MAX);
+    let mut bytes = 0;
+    for _ in 0..rounds {
+        let mut cc = CliqueCollection::new();
+        // Any clique of more than one node is a bitmap, or none is.
+        cc.set_bitmap_threshold(if bitmap { 1 } else { 0 });
+
+        let start = Instant::now();
+        for n in nodes {
+            cc.new_triple(n, &0);
+        }
+        insert = insert.min(start.elapsed().as_nanos());
+        bytes = cc.heap_size();
+
+        let index = cc.get_pred_index(&0);
+        let start = Instant::now();
+        black_box(cc.cliques()[index].nodes.iter().map(u64::from).sum::<u64>());
+        iterate = iterate.min(start.elapsed().as_nanos());
+
+        let start = Instant::now();
+        for n in order {
+            cc.remove_node(n);
+        }
+        remove = remove.min(start.elapsed().as_nanos());
+    }
+    BenchRow {
+        size: nodes.len(),
+        bitmap,
+        insert_ns: per_node(insert),
+        iterate_ns: per_node(iterate),
+        remove_ns: per_node(remove),
+        bytes: bytes as f64 / nodes.len().max(1) as f64,
+    }
+}
@@ -0,0 +1,184 @@
+use super::{print_report, Format};
+use crate::models::edges::Edges;
+use crate::util::heap_size::HeapSize;
+use crate::util::rng::Rng;
+use serde::Serialize;
+use std::fmt;
+use std::hint::black_box;
+use std::mem::size_of;
+use std::time::Instant;
+
+/// Settings of `teriyaki bench-edges`.
+pub struct BenchOptions {
+    /// Numbers of edges per node measured.
+    pub degrees: Vec<usize>,
+    /// Number of nodes measured at each degree.
+    pub nodes: usize,
+    /// Number of times each measurement is repeated, keeping the fastest.
+    pub rounds: usize,
+    pub seed: u64,
+}
+
+impl Default for BenchOptions {
+    fn default() -> Self {
+        BenchOptions {
+            degrees: vec![1, 2, 4, 8, 32],
+            nodes: 100_000,
+            rounds: 5,
+            seed: 0,
+        }
+    }
+}
+
+#[derive(Serialize)]
+pub struct BenchEdgesReport {
+    pub rows: Vec<BenchEdgesRow>,
+}
+
+/// Costs of the edges of nodes with `degree` edges kept in a `Vec` or in `Edges`, per node.
+#[derive(Serialize)]
+pub struct BenchEdgesRow {
+    pub degree: usize,
+    pub inline: bool,
+    pub build_ns: f64,
+    pub scan_ns: f64,
+    pub remove_ns: f64,
+    /// Bytes of the edges, inline and on the heap.
+    pub bytes: f64,
+}
+
+impl fmt::Display for BenchEdgesReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(
+            f,
+            "{:>6} {:<6} {:>10} {:>10} {:>10} {:>10}",
+            "degree", "kind", "build ns", "scan ns", "remove ns", "bytes"
+        )?;
+        for r in &self.rows {
+            writeln!(
+                f,
+                "{:>6} {:<6} {:>10.1} {:>10.1} {:>10.1} {:>10.1}",
+                r.degree,
+                if r.inline { "edges" } else { "vec" },
+                r.build_ns,
+                r.scan_ns,
+                r.remove_ns,
+                r.bytes
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// The operations the meta does on the edges of a node, for both representations.
+trait EdgeList: Sized {
+    fn build(edges: &[[u32; 2]]) -> Self;
+    fn edges(&self) -> &[[u32; 2]];
+    fn remove_at(&mut self, index: usize);
+    fn bytes(&self) -> usize;
+}
+
+impl EdgeList for Vec<[u32; 2]> {
+    fn build(edges: &[[u32; 2]]) -> Self {
+        let mut list = Vec::new();
+        for e in edges {
+            list.push(*e);
+        }
+        list
+    }
+    fn edges(&self) -> &[[u32; 2]] {
+        self
+    }
+    fn remove_at(&mut self, index: usize) {
+        self.remove(index);
+    }
+    fn bytes(&self) -> usize {
+        size_of::<Self>() + self.capacity() * size_of::<[u32; 2]>()
+    }
+}
+
+impl EdgeList for Edges {
+    fn build(edges: &[[u32; 2]]) -> Self {
+        let mut list = Edges::new();
+        for e in edges {
+            list.push(*e);
+        }
+        list
+    }
+    fn edges(&self) -> &[[u32; 2]] {
+        self
+    }
+    fn remove_at(&mut self, index: usize) {
+        self.remove(index);
+    }
+    fn bytes(&self) -> usize {
+        size_of::<Self>() + self.heap_size()
+    }
+}
+
+/// Measures building, scanning and removing the edges of many nodes of each degree, kept as a
+/// `Vec` and as `Edges`, to check that keeping few edges inline pays off.
+pub fn run(options: &BenchOptions, format: &Format) {
+    let mut rng = Rng::new(options.seed);
+    let mut rows = Vec::new();
+    for &degree in &options.degrees {
+        let edges: Vec<Vec<[u32; 2]>> = (0..options.nodes)
+            .map(|_| {
+                (0..degree)
+                    .map(|_| [rng.below(64) as u32, rng.below(1 << 20) as u32])
+                    .collect()
+            })
+            .collect();
+        rows.push(measure::<Vec<[u32; 2]>>(
+            &edges,
+            degree,
+            false,
+            options.rounds,
+        ));
+        rows.push(measure::<Edges>(&edges, degree, true, options.rounds));
+    }
+    print_report(&BenchEdgesReport { rows }, format);
+}
+
+fn measure<L: EdgeList>(
+    edges: &[Vec<[u32; 2]>],
+    degree: usize,
+    inline: bool,
+    rounds: usize,
+) -> BenchEdgesRow {
+    let per_node = |nanos: u128| nanos as f64 / edges.len().max(1) as f64;
+    let (mut build, mut scan, mut remove) = (u128::MAX, u128::MAX, u128::MAX);
+    let mut bytes = 0;
+    for _ in 0..rounds {
+        let start = Instant::now();
+        let mut lists: Vec<L> = edges.iter().map(|e| L::build(e)).collect();
+        build = build.min(start.elapsed().as_nanos());
+        bytes = lists.iter().map(|l| l.bytes()).sum::<usize>();
+
+        let start = Instant::now();
+        let preds: u64 = lists
+            .iter()
+            .flat_map(|l| l.edges())
+            .map(|e| e[0] as u64)
+            .sum();
+        black_box(preds);
+        scan = scan.min(start.elapsed().as_nanos());
+
+        let start = Instant::now();
+        for l in &mut lists {
+            while !l.edges().is_empty() {
+                l.remove_at(0);
+            }
+        }
+        remove = remove.min(start.elapsed().as_nanos());
+        black_box(lists);
+    }
+    BenchEdgesRow {
+        degree,
+        inline,
+        build_ns: per_node(build),
+        scan_ns: per_node(scan),
+        remove_ns: per_node(remove),
+        bytes: bytes as f64 / edges.len().max(1) as f64,
+    }
+}
@@ -0,0 +1,151 @@
+use crate::models::literals::LITERALS_FILE;
+use crate::util::io;
+use crate::{lock, parser, Cli};
+use std::collections::HashSet;
+use std::fs;
+use std::io::Write;
+use std::path::PathBuf;
+use std::process;
+
+/// First line of a front-coded dict export.
+const FRONT_CODED_HEADER: &str = "# teriyaki front-coded dict";
+
+/// What `teriyaki dict` does with the dict of a meta folder.
+pub enum DictCommand {
+    /// Writes the dict as term file, front-coded if `compressed`, to a file or stdout.
+    Export {
+        out: Option<PathBuf>,
+        compressed: bool,
+    },
+    /// Replaces the dict by the one in a term file.
+    Import(PathBuf),
+}
+
+pub fn run(cli: &Cli, command: &DictCommand) {
+    let dict_path = cli.meta_folder_path.join("dict");
+    match command {
+        DictCommand::Export { out, compressed } => {
+            let dict_lines = parser::dataset::read_dict(&cli.meta_folder_path).unwrap();
+            let lines = if *compressed {
+                export_front_coded(&dict_lines)
+            } else {
+                export(&dict_lines)
+            };
+            match out {
+                Some(path) => io::write_lines(path, &lines).unwrap(),
+                None => {
+                    // Stop quietly when piped into a command that exits early, like `head`.
+                    let mut stdout = std::io::stdout().lock();
+                    for l in &lines {
+                        if writeln!(stdout, "{}", l).is_err() {
+                            break;
+                        }
+                    }
+                }
+            }
+        }
+        DictCommand::Import(path) => {
+            let lines = io::read_lines(path).unwrap();
+            let dict_lines = import(&lines).unwrap_or_else(|err| {
+                println!("Problem importing dict: {}", err);
+                process::exit(1);
+            });
+            let _lock = lock(cli).unwrap_or_else(|failed| cli.exit(failed));
+            io::write_lines(&dict_path, &dict_lines).unwrap();
+            // The imported terms are all in the dict file.
+            let literals_path = cli.meta_folder_path.join(LITERALS_FILE);
+            if literals_path.exists() {
+                fs::remove_file(literals_path).unwrap();
+            }
+            let terms = dict_lines.iter().filter(|t| !t.is_empty()).count();
+            println!("imported {} terms", terms);
+        }
+    }
+}
+
+/// Returns one `id<TAB>term` line per term of `dict_lines`, sorted by id.
+pub fn export(dict_lines: &[String]) -> Vec<String> {
+    dict_lines
+        .iter()
+        .enumerate()
+        .filter(|(_, term)| !term.is_empty())
+        .map(|(i, term)| format!("{}\t{}", i + 1, term))
+        .collect()
+}
+
+/// Returns the terms of `dict_lines` sorted by term and front-coded, after a header line.
+///
+/// Each line is `id<TAB>shared<TAB>suffix`, where the term is the first `shared` characters of
+/// the previous term followed by `suffix`. IRIs share long prefixes, so this is much smaller
+/// than [`export`] and still readable with standard tools.
+pub fn export_front_coded(dict_lines: &[String]) -> Vec<String> {
+    let mut terms: Vec<(&String, usize)> = dict_lines
+        .iter()
+        .enumerate()
+        .filter(|(_, term)| !term.is_empty())
+        .map(|(i, term)| (term, i + 1))
+        .collect();
+    terms.sort();
+
+    let mut lines = vec![FRONT_CODED_HEADER.to_string()];
+    let mut previous = "";
+    for (term, id) in terms {
+        let (shared, bytes) = previous
+            .chars()
+            .zip(term.chars())
+            .take_while(|(a, b)| a == b)
+            .fold((0, 0), |(n, len), (c, _)| (n + 1, len + c.len_utf8()));
+        lines.push(format!("{}\t{}\t{}", id, shared, &term[bytes..]));
+        previous = term;
+    }
+    lines
+}
+
+/// Parses a term file written by [`export`] or [`export_front_coded`] into dict lines, where
+/// line `i` holds the term with id `i + 1` and unused ids are empty.
+pub fn import(lines: &[String]) -> Result<Vec<String>, String> {
+    let front_coded = lines.first().is_some_and(|l| l == FRONT_CODED_HEADER);
+    let skip = if front_coded { 1 } else { 0 };
+
+    let mut dict_lines: Vec<String> = Vec::new();
+    let mut terms: HashSet<String> = HashSet::new();
+    let mut previous = String::new();
+    for (i, l) in lines.iter().enumerate().skip(skip) {
+        let error = |message: &str| format!("line {}: {}", i + 1, message);
+        let (id, term) = l.split_once('\t').ok_or_else(|| error("expected a tab"))?;
+        let id: usize = match id.parse() {
+            Ok(id) if id > 0 => id,
+            _ => return Err(error("expected a positive id")),
+        };
+        let term = if front_coded {
+            let (shared, suffix) = term
+                .split_once('\t')
+                .ok_or_else(|| error("expected a tab"))?;
+            let shared: usize = shared.parse().map_err(|_| error("expected a length"))?;
+            if shared > previous.chars().count() {
+                return Err(error("shares more than the previous term"));
+            }
+            let mut term: String = previous.chars().take(shared).collect();
+            term.push_str(suffix);
+            term
+        } else {
+            term.to_string()
+        };
+
+        if term.is_empty() {
+            return Err(error("empty term"));
+        }
+        if !terms.insert(term.clone()) {
+            return Err(error("duplicate term"));
+        }
+        if dict_lines.len() < id {
+            dict_lines.resize(id, String::new());
+        }
+        if !dict_lines[id - 1].is_empty() {
+            return Err(error("duplicate id"));
+        }
+        dict_lines[id - 1] = term.clone();
+        previous = term;
+    }
+    Ok(dict_lines)
+}
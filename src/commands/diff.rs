@@ -0,0 +1,54 @@
+use super::{print_report, Format};
+use crate::util::io;
+use serde::Serialize;
+use std::collections::HashSet;
+use std::fmt;
+use std::path::PathBuf;
+
+#[derive(Serialize)]
+pub struct DiffReport {
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+}
+
+impl fmt::Display for DiffReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for l in &self.removed {
+            writeln!(f, "- {}", l)?;
+        }
+        for l in &self.added {
+            writeln!(f, "+ {}", l)?;
+        }
+        writeln!(
+            f,
+            "{} added, {} removed",
+            self.added.len(),
+            self.removed.len()
+        )
+    }
+}
+
+/// Prints the triples that differ between the summary files `old` and `new`.
+pub fn run(old: &PathBuf, new: &PathBuf, format: &Format) {
+    let old_lines = io::read_lines(old).unwrap();
+    let new_lines = io::read_lines(new).unwrap();
+    print_report(&diff(&old_lines, &new_lines), format);
+}
+
+pub fn diff(old: &[String], new: &[String]) -> DiffReport {
+    let old_set: HashSet<&String> = old.iter().filter(|l| !l.is_empty()).collect();
+    let new_set: HashSet<&String> = new.iter().filter(|l| !l.is_empty()).collect();
+
+    let mut added: Vec<String> = new_set
+        .difference(&old_set)
+        .map(|l| l.to_string())
+        .collect();
+    let mut removed: Vec<String> = old_set
+        .difference(&new_set)
+        .map(|l| l.to_string())
+        .collect();
+    added.sort();
+    removed.sort();
+
+    DiffReport { added, removed }
+}
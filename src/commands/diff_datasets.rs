@@ -0,0 +1,212 @@
+use super::print_report;
+use crate::util::exit::Failure;
+use crate::util::io;
+use crate::Cli;
+use serde::Serialize;
+use std::cmp::{Ordering, Reverse};
+use std::collections::BinaryHeap;
+use std::fmt;
+use std::fs::{self, File};
+use std::io::{BufRead, BufReader, BufWriter, Lines, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering as AtomicOrdering};
+use std::{process, vec};
+
+/// Number of lines `diff-datasets` sorts in memory at a time by default.
+pub const CHUNK_LINES: usize = 1 << 20;
+
+/// Settings of `teriyaki diff-datasets`.
+pub struct DiffDatasetsOptions {
+    pub old: PathBuf,
+    pub new: PathBuf,
+    /// File the changeset is written to.
+    pub out: PathBuf,
+    /// Number of lines sorted in memory at a time; longer dumps are sorted in runs on disk.
+    pub chunk_lines: usize,
+}
+
+#[derive(Default, Serialize)]
+pub struct DatasetDiffReport {
+    pub added: usize,
+    pub deleted: usize,
+    pub unchanged: usize,
+}
+
+impl fmt::Display for DatasetDiffReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(
+            f,
+            "{} added, {} deleted, {} unchanged",
+            self.added, self.deleted, self.unchanged
+        )
+    }
+}
+
+/// Writes the changeset between two dataset dumps and prints how many triples it changes.
+pub fn run(cli: &Cli, options: &DiffDatasetsOptions) {
+    match diff_datasets(options) {
+        Ok(report) => print_report(&report, &cli.format),
+        Err(err) => cli.fail(
+            Failure::of(&err),
+            &format!("Problem diffing datasets: {}", err),
+        ),
+    }
+}
+
+/// Writes the changeset that turns the dump `old` into `new` to `out`, in the format of update
+/// files: the triples only in `old` prefixed with `-`, and the triples only in `new`, in sorted
+/// order.
+///
+/// Both dumps are sorted in runs of `chunk_lines` lines, spilled next to `out` and merged, so
+/// they need not fit in memory. Lines are compared with surrounding whitespace trimmed; blank
+/// lines, comments and repeated triples are ignored.
+pub fn diff_datasets(options: &DiffDatasetsOptions) -> std::io::Result<DatasetDiffReport> {
+    let dir = options.out.parent().unwrap_or(Path::new(""));
+    let mut old = SortedLines::new(&options.old, options.chunk_lines, dir)?;
+    let mut new = SortedLines::new(&options.new, options.chunk_lines, dir)?;
+    let mut out = BufWriter::new(File::create(&options.out)?);
+
+    let mut report = DatasetDiffReport::default();
+    let mut a = old.next().transpose()?;
+    let mut b = new.next().transpose()?;
+    loop {
+        let order = match (&a, &b) {
+            (None, None) => break,
+            (Some(_), None) => Ordering::Less,
+            (None, Some(_)) => Ordering::Greater,
+            (Some(x), Some(y)) => x.cmp(y),
+        };
+        match order {
+            Ordering::Less => {
+                writeln!(out, "-{}", a.unwrap())?;
+                report.deleted += 1;
+                a = old.next().transpose()?;
+            }
+            Ordering::Greater => {
+                writeln!(out, "{}", b.unwrap())?;
+                report.added += 1;
+                b = new.next().transpose()?;
+            }
+            Ordering::Equal => {
+                report.unchanged += 1;
+                a = old.next().transpose()?;
+                b = new.next().transpose()?;
+            }
+        }
+    }
+    out.flush()?;
+    Ok(report)
+}
+
+/// Number of runs spilled by this process so far, which keeps their file names apart.
+static SPILLED: AtomicUsize = AtomicUsize::new(0);
+
+/// The distinct lines of a file in sorted order, merged from sorted runs.
+pub struct SortedLines {
+    /// Number of lines read, repeats included.
+    pub read: usize,
+    runs: Vec<Run>,
+    /// The next line of each run that has one, with the index of the run, smallest first.
+    heap: BinaryHeap<Reverse<(String, usize)>>,
+    last: Option<String>,
+    /// Files of the runs spilled to disk, removed on drop.
+    spilled: Vec<PathBuf>,
+}
+
+enum Run {
+    Memory(vec::IntoIter<String>),
+    File(Lines<BufReader<File>>),
+}
+
+impl Run {
+    fn next(&mut self) -> std::io::Result<Option<String>> {
+        match self {
+            Run::Memory(lines) => Ok(lines.next()),
+            Run::File(lines) => lines.next().transpose(),
+        }
+    }
+}
+
+impl SortedLines {
+    /// Sorts the lines of the file at `path` in runs of `chunk_lines`, spilling all but the last
+    /// one to the directory `dir`.
+    pub fn new(path: &Path, chunk_lines: usize, dir: &Path) -> std::io::Result<Self> {
+        let mut sorted = SortedLines {
+            read: 0,
+            runs: Vec::new(),
+            heap: BinaryHeap::new(),
+            last: None,
+            spilled: Vec::new(),
+        };
+        let mut chunk: Vec<String> = Vec::new();
+        for line in io::stream_lines(&path, io::STREAM_CAPACITY)? {
+            let line = line?;
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            chunk.push(line.to_string());
+            sorted.read += 1;
+            if chunk.len() == chunk_lines {
+                sorted.spill(&mut chunk, dir)?;
+            }
+        }
+        chunk.sort_unstable();
+        chunk.dedup();
+        sorted.runs.push(Run::Memory(chunk.into_iter()));
+
+        for i in 0..sorted.runs.len() {
+            if let Some(line) = sorted.runs[i].next()? {
+                sorted.heap.push(Reverse((line, i)));
+            }
+        }
+        Ok(sorted)
+    }
+
+    fn spill(&mut self, chunk: &mut Vec<String>, dir: &Path) -> std::io::Result<()> {
+        chunk.sort_unstable();
+        chunk.dedup();
+        let n = SPILLED.fetch_add(1, AtomicOrdering::Relaxed);
+        let path = dir.join(format!(".teriyaki-sort-{}-{}", process::id(), n));
+        self.spilled.push(path.clone());
+
+        let mut file = BufWriter::new(File::create(&path)?);
+        for line in chunk.drain(..) {
+            writeln!(file, "{}", line)?;
+        }
+        file.flush()?;
+        self.runs
+            .push(Run::File(BufReader::new(File::open(&path)?).lines()));
+        Ok(())
+    }
+}
+
+impl Iterator for SortedLines {
+    type Item = std::io::Result<String>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let Reverse((line, i)) = self.heap.pop()?;
+            match self.runs[i].next() {
+                Ok(Some(next)) => self.heap.push(Reverse((next, i))),
+                Ok(None) => {}
+                Err(err) => return Some(Err(err)),
+            }
+            // Runs are free of repeats, but the same line can be in several of them.
+            if self.last.as_ref() != Some(&line) {
+                self.last = Some(line.clone());
+                return Some(Ok(line));
+            }
+        }
+    }
+}
+
+impl Drop for SortedLines {
+    fn drop(&mut self) {
+        // Closed first, since open files cannot be removed everywhere.
+        self.runs.clear();
+        for path in &self.spilled {
+            let _ = fs::remove_file(path);
+        }
+    }
+}
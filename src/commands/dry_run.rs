@@ -0,0 +1,111 @@
+use super::print_report;
+use crate::models::clique::CliqueCollection;
+use crate::models::dataset::Dataset;
+use crate::models::meta::Meta;
+use crate::models::triple::TYPE_STRING;
+use crate::util::exit::Failure;
+use crate::{read_input, updater, Cli};
+use serde::Serialize;
+use std::fmt;
+
+#[derive(Serialize)]
+pub struct DryRunReport {
+    pub insertions: usize,
+    pub deletions: usize,
+    pub duplicate_insertions: usize,
+    pub rejected_deletions: Vec<String>,
+    pub pending: usize,
+    /// Summary triples before and after the update, without the types kept aside in the meta.
+    pub triples_before: usize,
+    pub triples_after: usize,
+    pub supernodes_before: usize,
+    pub supernodes_after: usize,
+    pub summary_path: String,
+}
+
+impl fmt::Display for DryRunReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(
+            f,
+            "would apply {} insertions and {} deletions ({} duplicate insertions)",
+            self.insertions, self.deletions, self.duplicate_insertions
+        )?;
+        for r in &self.rejected_deletions {
+            writeln!(f, "would reject {}", r)?;
+        }
+        if self.pending > 0 {
+            writeln!(f, "would leave {} changes pending", self.pending)?;
+        }
+        writeln!(
+            f,
+            "summary triples: {} -> {}",
+            self.triples_before, self.triples_after
+        )?;
+        writeln!(
+            f,
+            "supernodes:      {} -> {}",
+            self.supernodes_before, self.supernodes_after
+        )?;
+        writeln!(f, "nothing was written to {}", self.summary_path)
+    }
+}
+
+/// Applies the update in memory without writing summary, dict or meta.
+pub fn run(cli: &Cli) {
+    let (mut dataset, mut meta, mut sc, mut tc) = read_input(cli);
+    let options = cli.update_options();
+    let summary_path = cli.summary_path().display().to_string();
+    let report = dry_run(
+        &mut dataset,
+        &mut meta,
+        &mut sc,
+        &mut tc,
+        &options,
+        summary_path,
+    )
+    .unwrap_or_else(|err| {
+        let message = format!("Problem applying update: {}", err);
+        cli.fail(Failure::Conflict, &message)
+    });
+    print_report(&report, &cli.format);
+}
+
+/// Applies the update set in `dataset` and reports what it changed.
+pub fn dry_run(
+    dataset: &mut Dataset,
+    meta: &mut Meta,
+    sc: &mut CliqueCollection,
+    tc: &mut CliqueCollection,
+    options: &updater::Options,
+    summary_path: String,
+) -> Result<DryRunReport, String> {
+    let triples_before = summary_triples(dataset);
+    let supernodes_before = meta.get_supernodes().len();
+
+    let update = updater::run(dataset, meta, sc, tc, options)?;
+
+    Ok(DryRunReport {
+        insertions: dataset.insertions.data_triples.len(),
+        deletions: dataset.deletions.data_triples.len(),
+        duplicate_insertions: update.duplicate_insertions,
+        rejected_deletions: update.rejected_deletions,
+        pending: update.pending.len(),
+        triples_before,
+        triples_after: summary_triples(dataset),
+        supernodes_before,
+        supernodes_after: meta.get_supernodes().len(),
+        summary_path,
+    })
+}
+
+/// Counts the summary triples other than the types the updater adds back from the meta, which
+/// the parser keeps aside when the summary is read.
+fn summary_triples(dataset: &Dataset) -> usize {
+    let type_pred = dataset.pred_id_of(TYPE_STRING);
+    dataset
+        .triples
+        .data_triples
+        .iter()
+        .filter(|t| !(t.is_type && Some(t.pred) == type_pred))
+        .count()
+}
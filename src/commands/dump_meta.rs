@@ -0,0 +1,204 @@
+use super::print_report;
+use crate::models::compact_meta::CompactMeta;
+use crate::models::triple::TYPE_STRING;
+use crate::{as_iri, parser, Cli};
+use serde::Serialize;
+use std::fmt;
+use std::process;
+
+/// What `dump-meta` prints: a node or a supernode, given by id, IRI or, for supernodes, alias.
+pub enum Target {
+    Node(String),
+    Supernode(String),
+}
+
+/// A node or supernode id with its term.
+#[derive(Serialize)]
+pub struct Term {
+    pub id: u32,
+    pub term: String,
+}
+
+/// The meta record of a node, decoded with the dict.
+#[derive(Serialize)]
+pub struct NodeDump {
+    pub node: Term,
+    pub parent: Option<SupernodeDump>,
+    /// Predicate and object of each outgoing edge.
+    pub outgoing: Vec<(String, Term)>,
+    /// Subject and predicate of each incoming edge.
+    pub incoming: Vec<(Term, String)>,
+}
+
+/// A supernode and its members, decoded with the dict.
+#[derive(Serialize)]
+pub struct SupernodeDump {
+    pub supernode: Term,
+    pub alias: Option<String>,
+    pub members: Vec<Term>,
+}
+
+/// Terms of a meta folder by id.
+pub(super) struct Terms<'a> {
+    pub(super) dict: &'a [String],
+    pub(super) preds: &'a [String],
+}
+
+/// Prints the meta record of a node, or the members of a supernode, with ids decoded to terms.
+pub fn run(cli: &Cli, target: &Target) {
+    let meta = parser::meta::read_compact_meta(&cli.meta_folder_path).unwrap();
+    let dict = parser::dataset::read_dict(&cli.meta_folder_path).unwrap();
+    let preds = parser::dataset::read_preds(&cli.meta_folder_path, &dict).unwrap();
+    let terms = Terms {
+        dict: &dict,
+        preds: &preds,
+    };
+
+    match target {
+        Target::Node(node) => match terms.find(node).filter(|id| meta.contains(id)) {
+            Some(id) => print_report(&dump_node(&meta, &dict, &preds, id), &cli.format),
+            None => exit(&format!("{} is not a node", node)),
+        },
+        Target::Supernode(snode) => {
+            let id = snode
+                .parse::<u32>()
+                .ok()
+                .or_else(|| meta.supernode_by_alias(snode))
+                .or_else(|| terms.find(snode));
+            match id.filter(|id| meta.contains_supernode(id)) {
+                Some(id) => print_report(&dump_supernode(&meta, &dict, id), &cli.format),
+                None => exit(&format!("{} is not a supernode", snode)),
+            }
+        }
+    }
+}
+
+fn exit(message: &str) -> ! {
+    println!("{}", message);
+    process::exit(1);
+}
+
+/// Returns the meta record of the node `id`, decoded with the terms of `dict_lines` and the
+/// predicates of `pred_lines`.
+pub fn dump_node(
+    meta: &CompactMeta,
+    dict_lines: &[String],
+    pred_lines: &[String],
+    id: u32,
+) -> NodeDump {
+    let terms = Terms {
+        dict: dict_lines,
+        preds: pred_lines,
+    };
+    NodeDump {
+        node: terms.term(id),
+        parent: meta
+            .get_parent(&id)
+            .map(|p| dump_supernode(meta, dict_lines, p)),
+        outgoing: meta
+            .outgoing(&id)
+            .iter()
+            .map(|[p, o]| (terms.pred(p), terms.term(*o)))
+            .collect(),
+        incoming: meta
+            .incoming(&id)
+            .iter()
+            .map(|[p, s]| (terms.term(*s), terms.pred(p)))
+            .collect(),
+    }
+}
+
+/// Returns the members of the supernode `id`, decoded with the terms of `dict_lines`.
+pub fn dump_supernode(meta: &CompactMeta, dict_lines: &[String], id: u32) -> SupernodeDump {
+    let terms = Terms {
+        dict: dict_lines,
+        preds: &[],
+    };
+    SupernodeDump {
+        supernode: terms.term(id),
+        alias: meta.get_alias(&id),
+        members: meta
+            .get_supernode(&id)
+            .unwrap()
+            .iter()
+            .map(|m| terms.term(*m))
+            .collect(),
+    }
+}
+
+impl Terms<'_> {
+    /// Returns the id `s` stands for, given as a number or an IRI.
+    pub(super) fn find(&self, s: &str) -> Option<u32> {
+        if let Ok(id) = s.parse::<u32>() {
+            return Some(id);
+        }
+        let iri = as_iri(s);
+        self.dict
+            .iter()
+            .position(|t| *t == iri)
+            .map(|i| i as u32 + 1)
+    }
+
+    pub(super) fn term(&self, id: u32) -> Term {
+        let term = match (id as usize).checked_sub(1).and_then(|i| self.dict.get(i)) {
+            Some(t) if !t.is_empty() => t.clone(),
+            _ => String::from("?"),
+        };
+        Term { id, term }
+    }
+
+    /// Returns the IRI of `pred`, or `rdf:type` and the class for the pseudo-predicates of type
+    /// grouping.
+    pub(super) fn pred(&self, pred: &u32) -> String {
+        match (*pred as usize)
+            .checked_sub(1)
+            .and_then(|i| self.preds.get(i))
+        {
+            Some(t) if !t.is_empty() => t.clone(),
+            _ => format!("{} {}", TYPE_STRING, self.term(u32::MAX - pred).term),
+        }
+    }
+}
+
+impl fmt::Display for Term {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} {}", self.id, self.term)
+    }
+}
+
+impl fmt::Display for NodeDump {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "node {}", self.node)?;
+        match &self.parent {
+            Some(parent) => writeln!(f, "parent: supernode {}", parent.supernode)?,
+            None => writeln!(f, "parent: none")?,
+        }
+        writeln!(f, "outgoing ({}):", self.outgoing.len())?;
+        for (p, o) in &self.outgoing {
+            writeln!(f, "  {} {}", p, o)?;
+        }
+        writeln!(f, "incoming ({}):", self.incoming.len())?;
+        for (s, p) in &self.incoming {
+            writeln!(f, "  {} {}", s, p)?;
+        }
+        if let Some(parent) = &self.parent {
+            write!(f, "{}", parent)?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for SupernodeDump {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "supernode {}", self.supernode)?;
+        match &self.alias {
+            Some(alias) => writeln!(f, " (alias {})", alias)?,
+            None => writeln!(f)?,
+        }
+        writeln!(f, "members ({}):", self.members.len())?;
+        for m in &self.members {
+            writeln!(f, "  {}", m)?;
+        }
+        Ok(())
+    }
+}
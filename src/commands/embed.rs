@@ -0,0 +1,202 @@
+use super::print_report;
+use crate::models::meta::Meta;
+use crate::util::{io, rng::Rng};
+use crate::{parser, Cli};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::PathBuf;
+
+/// Settings of `teriyaki embed`.
+pub struct EmbedOptions {
+    /// File the edge list is written to.
+    pub out: PathBuf,
+    /// File the random-walk corpus is written to, or `None` to not generate one.
+    pub walks: Option<PathBuf>,
+    /// Number of nodes in a walk, including the one it starts from.
+    pub walk_length: usize,
+    pub walks_per_node: usize,
+    /// Return parameter of node2vec: the higher, the less often a walk steps back.
+    pub p: f64,
+    /// In-out parameter of node2vec: the higher, the closer a walk stays to where it came from.
+    pub q: f64,
+    pub seed: u64,
+}
+
+impl Default for EmbedOptions {
+    fn default() -> Self {
+        // The defaults of the node2vec reference implementation.
+        Self {
+            out: PathBuf::new(),
+            walks: None,
+            walk_length: 80,
+            walks_per_node: 10,
+            p: 1.0,
+            q: 1.0,
+            seed: 0,
+        }
+    }
+}
+
+/// An edge of the summary graph, between supernodes or nodes without a parent.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct WeightedEdge {
+    pub from: u32,
+    pub to: u32,
+    /// Number of original triples the edge stands for, whatever their predicate.
+    pub weight: u64,
+}
+
+#[derive(Serialize)]
+pub struct EmbedReport {
+    pub nodes: usize,
+    pub edges: usize,
+    /// Number of walks written, if a corpus was generated.
+    pub walks: Option<usize>,
+}
+
+impl fmt::Display for EmbedReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "wrote {} edges between {} nodes", self.edges, self.nodes)?;
+        if let Some(walks) = self.walks {
+            writeln!(f, "wrote {} walks", walks)?;
+        }
+        Ok(())
+    }
+}
+
+/// Writes the summary graph of a meta folder as a weighted edge list, and optionally a corpus
+/// of node2vec random walks over it, for embedding toolkits.
+///
+/// Both use the ids of the meta folder: the term of id `n` is line `n` of its dict.
+pub fn run(cli: &Cli, options: &EmbedOptions) {
+    let meta = parser::meta::parse_meta(cli).unwrap();
+    let edges = edge_list(&meta);
+    let lines: Vec<String> = edges
+        .iter()
+        .map(|e| format!("{} {} {}", e.from, e.to, e.weight))
+        .collect();
+    io::write_lines(&options.out, &lines).unwrap();
+
+    let walks = options.walks.as_ref().map(|path| {
+        let walks = random_walks(&edges, options);
+        let lines: Vec<String> = walks
+            .iter()
+            .map(|w| {
+                let ids: Vec<String> = w.iter().map(|n| n.to_string()).collect();
+                ids.join(" ")
+            })
+            .collect();
+        io::write_lines(path, &lines).unwrap();
+        walks.len()
+    });
+
+    let mut nodes: Vec<u32> = edges.iter().flat_map(|e| [e.from, e.to]).collect();
+    nodes.sort_unstable();
+    nodes.dedup();
+    let report = EmbedReport {
+        nodes: nodes.len(),
+        edges: edges.len(),
+        walks,
+    };
+    print_report(&report, &cli.format);
+}
+
+/// Returns the edges of the summary graph of `meta`, sorted, with the original triples between
+/// the same two supernodes merged into one edge.
+pub fn edge_list(meta: &Meta) -> Vec<WeightedEdge> {
+    let representative = |n: &u32| meta.get_node(n).and_then(|i| i.parent).unwrap_or(*n);
+
+    let mut weights: BTreeMap<[u32; 2], u64> = BTreeMap::new();
+    for (n, info) in meta.get_nodes() {
+        let from = representative(n);
+        for [_, o] in &info.outgoing {
+            *weights.entry([from, representative(o)]).or_default() += 1;
+        }
+    }
+    weights
+        .into_iter()
+        .map(|([from, to], weight)| WeightedEdge { from, to, weight })
+        .collect()
+}
+
+/// Returns `walks_per_node` node2vec walks of `walk_length` nodes from every node of the graph
+/// of `edges`, which is walked as undirected and picks each step in proportion to the weight of
+/// its edge.
+pub fn random_walks(edges: &[WeightedEdge], options: &EmbedOptions) -> Vec<Vec<u32>> {
+    let mut neighbors: BTreeMap<u32, Vec<(u32, f64)>> = BTreeMap::new();
+    for e in edges {
+        let weight = e.weight as f64;
+        neighbors.entry(e.from).or_default().push((e.to, weight));
+        if e.from != e.to {
+            neighbors.entry(e.to).or_default().push((e.from, weight));
+        }
+    }
+    // Edges in both directions between two nodes are one undirected edge.
+    for list in neighbors.values_mut() {
+        list.sort_by_key(|(n, _)| *n);
+        list.dedup_by(|(n, w), (kept, kept_w)| {
+            let same = n == kept;
+            if same {
+                *kept_w += *w;
+            }
+            same
+        });
+    }
+
+    let mut rng = Rng::new(options.seed);
+    let mut starts: Vec<u32> = neighbors.keys().copied().collect();
+    let mut walks: Vec<Vec<u32>> = Vec::with_capacity(starts.len() * options.walks_per_node);
+    for _ in 0..options.walks_per_node {
+        // Shuffled so that walks from the same region do not end up next to each other.
+        for i in (1..starts.len()).rev() {
+            starts.swap(i, rng.below(i + 1));
+        }
+        for start in &starts {
+            let mut walk = vec![*start];
+            while walk.len() < options.walk_length {
+                let prev = walk.len().checked_sub(2).map(|i| walk[i]);
+                walk.push(step(
+                    &neighbors,
+                    walk[walk.len() - 1],
+                    prev,
+                    options,
+                    &mut rng,
+                ));
+            }
+            walks.push(walk);
+        }
+    }
+    walks
+}
+
+/// Picks the next node of a walk at `cur` that came from `prev`, with the bias of node2vec.
+fn step(
+    neighbors: &BTreeMap<u32, Vec<(u32, f64)>>,
+    cur: u32,
+    prev: Option<u32>,
+    options: &EmbedOptions,
+    rng: &mut Rng,
+) -> u32 {
+    let is_neighbor = |a: u32, b: u32| neighbors[&a].binary_search_by_key(&b, |(n, _)| *n).is_ok();
+    let candidates = &neighbors[&cur];
+    let weights: Vec<f64> = candidates
+        .iter()
+        .map(|(n, w)| match prev {
+            None => *w,
+            Some(prev) if *n == prev => w / options.p,
+            Some(prev) if is_neighbor(prev, *n) => *w,
+            Some(_) => w / options.q,
+        })
+        .collect();
+
+    let mut r = rng.next_f64() * weights.iter().sum::<f64>();
+    for ((n, _), w) in candidates.iter().zip(&weights) {
+        if r < *w {
+            return *n;
+        }
+        r -= w;
+    }
+    // Rounding can leave a little of `r` past the last candidate.
+    candidates[candidates.len() - 1].0
+}
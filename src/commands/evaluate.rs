@@ -0,0 +1,102 @@
+use super::{print_report, Format};
+use crate::models::meta::Meta;
+use crate::parser::dataset::read_dict;
+use crate::parser::meta::read_meta;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fmt;
+use std::path::Path;
+
+/// Pairwise agreement of the supernodes of an incremental summary with those of a summary
+/// built from scratch.
+///
+/// A pair of nodes counts if both are members of the same supernode. Precision is the share of
+/// incremental pairs that are also fresh pairs, recall the share of fresh pairs that are also
+/// incremental pairs. Both are 1 when there are no pairs to get wrong.
+#[derive(Serialize, Debug, PartialEq)]
+pub struct EvaluateReport {
+    pub incremental_pairs: u64,
+    pub fresh_pairs: u64,
+    /// Pairs grouped together in both summaries.
+    pub shared_pairs: u64,
+    pub precision: f64,
+    pub recall: f64,
+    pub f1: f64,
+}
+
+impl fmt::Display for EvaluateReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "incremental pairs: {}", self.incremental_pairs)?;
+        writeln!(f, "fresh pairs:       {}", self.fresh_pairs)?;
+        writeln!(f, "shared pairs:      {}", self.shared_pairs)?;
+        writeln!(f, "precision:         {:.4}", self.precision)?;
+        writeln!(f, "recall:            {:.4}", self.recall)?;
+        writeln!(f, "f1:                {:.4}", self.f1)
+    }
+}
+
+/// Compares the grouping of the summary in the meta folder `incremental` to that in `fresh`.
+pub fn run(incremental: &Path, fresh: &Path, format: &Format) {
+    let incremental = grouping(incremental);
+    let fresh = grouping(fresh);
+    print_report(&evaluate(&incremental, &fresh), format);
+}
+
+/// Returns the supernode of each member of a supernode in the meta folder `folder`, by IRI.
+fn grouping(folder: &Path) -> HashMap<String, u32> {
+    let meta: Meta = read_meta(folder).unwrap();
+    let dict_lines = read_dict(folder).unwrap();
+
+    let mut groups: HashMap<String, u32> = HashMap::new();
+    for (snode, members) in meta.get_supernodes() {
+        for m in members {
+            let iri = dict_lines[*m as usize - 1].clone();
+            groups.insert(iri, *snode);
+        }
+    }
+    groups
+}
+
+/// Scores the grouping `incremental` against `fresh`, which map members of supernodes to their
+/// supernode.
+pub fn evaluate(
+    incremental: &HashMap<String, u32>,
+    fresh: &HashMap<String, u32>,
+) -> EvaluateReport {
+    let pairs = |n: u64| n * n.saturating_sub(1) / 2;
+    let sizes = |groups: &HashMap<String, u32>| {
+        let mut sizes: HashMap<u32, u64> = HashMap::new();
+        for g in groups.values() {
+            *sizes.entry(*g).or_default() += 1;
+        }
+        sizes.into_values().map(pairs).sum::<u64>()
+    };
+
+    let mut overlaps: HashMap<(u32, u32), u64> = HashMap::new();
+    for (node, a) in incremental {
+        if let Some(b) = fresh.get(node) {
+            *overlaps.entry((*a, *b)).or_default() += 1;
+        }
+    }
+
+    let incremental_pairs = sizes(incremental);
+    let fresh_pairs = sizes(fresh);
+    let shared_pairs: u64 = overlaps.into_values().map(pairs).sum();
+    let ratio = |n: u64, d: u64| if d == 0 { 1.0 } else { n as f64 / d as f64 };
+    let precision = ratio(shared_pairs, incremental_pairs);
+    let recall = ratio(shared_pairs, fresh_pairs);
+    let f1 = if precision + recall == 0.0 {
+        0.0
+    } else {
+        2.0 * precision * recall / (precision + recall)
+    };
+
+    EvaluateReport {
+        incremental_pairs,
+        fresh_pairs,
+        shared_pairs,
+        precision,
+        recall,
+        f1,
+    }
+}
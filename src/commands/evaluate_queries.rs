@@ -0,0 +1,239 @@
+use super::print_report;
+use crate::models::dict::Dict;
+use crate::models::meta::Meta;
+use crate::util::exit::Failure;
+use crate::util::io;
+use crate::{parser, rdf, Cli};
+use serde::Serialize;
+use std::collections::HashSet;
+use std::fmt;
+use std::path::PathBuf;
+
+/// Settings of `teriyaki evaluate-queries`.
+pub struct EvaluateQueriesOptions {
+    /// The original triples.
+    pub original: PathBuf,
+    /// The summary of the original triples, with the meta folder of the config.
+    pub summary: PathBuf,
+    /// File of triple patterns, one per line.
+    pub queries: PathBuf,
+}
+
+/// A triple pattern: each of its terms is a variable, starting with `?`, or a term in N-Triples
+/// notation.
+pub struct Pattern {
+    pub terms: [String; 3],
+}
+
+impl Pattern {
+    /// Parses the line `l` of a queries file, `None` if it is blank or a comment.
+    pub fn parse(l: &str) -> Option<Result<Self, String>> {
+        let l = l.trim();
+        if l.is_empty() || l.starts_with('#') {
+            return None;
+        }
+        let words: Vec<&str> = l
+            .strip_suffix('.')
+            .unwrap_or(l)
+            .split_whitespace()
+            .collect();
+        match words[..] {
+            [s, p, o] => Some(Ok(Pattern {
+                terms: [s, p, o].map(|t| match t.starts_with('?') {
+                    true => t.to_string(),
+                    false => rdf::normalize(t),
+                }),
+            })),
+            _ => Some(Err(format!("expected a triple pattern: {}", l))),
+        }
+    }
+
+    /// Returns the names of the variables, in order of first appearance.
+    fn variables(&self) -> Vec<&str> {
+        let mut vars: Vec<&str> = Vec::new();
+        for t in &self.terms {
+            if t.starts_with('?') && !vars.contains(&t.as_str()) {
+                vars.push(t);
+            }
+        }
+        vars
+    }
+
+    /// Adds to `results` the bindings of the variables under which the pattern matches a
+    /// triple whose terms are any of `candidates`, one list for each position.
+    fn bind(&self, candidates: [&[String]; 3], results: &mut HashSet<Vec<String>>) {
+        // Constants are matched before the candidates are combined.
+        let [subs, preds, objs] = [0, 1, 2].map(|i| {
+            let pattern = &self.terms[i];
+            candidates[i]
+                .iter()
+                .filter(|t| pattern.starts_with('?') || *t == pattern)
+                .collect::<Vec<&String>>()
+        });
+        let vars = self.variables();
+        for s in &subs {
+            for p in &preds {
+                for o in &objs {
+                    let mut binding: Vec<Option<&String>> = vec![None; vars.len()];
+                    let fits = self.terms.iter().zip([s, p, o]).all(|(pattern, term)| {
+                        match vars.iter().position(|v| v == pattern) {
+                            Some(i) => *binding[i].get_or_insert(term) == *term,
+                            None => true,
+                        }
+                    });
+                    if fits {
+                        results.insert(binding.into_iter().map(|t| t.unwrap().clone()).collect());
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// How the answers of a triple pattern on the summary, with its supernodes expanded to their
+/// members, compare to those on the original triples.
+#[derive(Serialize)]
+pub struct QueryScore {
+    pub query: String,
+    pub original_results: usize,
+    pub summary_results: usize,
+    /// Results found on both.
+    pub shared_results: usize,
+    pub precision: f64,
+    pub recall: f64,
+}
+
+#[derive(Serialize)]
+pub struct EvaluateQueriesReport {
+    pub queries: Vec<QueryScore>,
+    /// Precision and recall averaged over the queries.
+    pub mean_precision: f64,
+    pub mean_recall: f64,
+}
+
+impl fmt::Display for EvaluateQueriesReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for q in &self.queries {
+            writeln!(
+                f,
+                "{}: precision {:.4}, recall {:.4} ({} original, {} summary, {} shared)",
+                q.query,
+                q.precision,
+                q.recall,
+                q.original_results,
+                q.summary_results,
+                q.shared_results
+            )?;
+        }
+        writeln!(f, "mean precision: {:.4}", self.mean_precision)?;
+        writeln!(f, "mean recall:    {:.4}", self.mean_recall)
+    }
+}
+
+/// Runs the triple patterns of the queries file on the original triples and on the summary,
+/// and prints the precision and recall of the summary for each.
+pub fn run(cli: &Cli, options: &EvaluateQueriesOptions) {
+    let fail = |err: std::io::Error| -> ! {
+        cli.fail(
+            Failure::of(&err),
+            &format!("Problem evaluating queries: {}", err),
+        )
+    };
+    let mut queries: Vec<Pattern> = Vec::new();
+    for l in io::read_lines(&options.queries).unwrap_or_else(|e| fail(e)) {
+        match Pattern::parse(&l) {
+            Some(Ok(pattern)) => queries.push(pattern),
+            Some(Err(err)) => cli.fail(Failure::Parse, &err),
+            None => {}
+        }
+    }
+    let meta = parser::meta::parse_meta(cli).unwrap_or_else(|e| fail(e));
+    let dict_lines = parser::dataset::read_dict(&cli.meta_folder_path).unwrap_or_else(|e| fail(e));
+    let original = io::read_lines(&options.original).unwrap_or_else(|e| fail(e));
+    let summary = io::read_lines(&options.summary).unwrap_or_else(|e| fail(e));
+
+    let dict = Dict::new(&dict_lines, &vec![]);
+    let expand = |term: &str| members(&meta, &dict, &dict_lines, term);
+    let report = evaluate_queries(&queries, &original, &summary, expand);
+    print_report(&report, &cli.format);
+}
+
+/// Returns the terms of the members of the supernode `term`, or `term` itself if it is not a
+/// supernode.
+fn members(meta: &Meta, dict: &Dict, dict_lines: &[String], term: &str) -> Vec<String> {
+    let snode = dict
+        .get(&term.to_string())
+        .and_then(|id| meta.get_supernode(id));
+    match snode {
+        Some(members) => members
+            .iter()
+            .map(|m| dict_lines[*m as usize - 1].clone())
+            .collect(),
+        None => vec![term.to_string()],
+    }
+}
+
+/// Scores the answers of `queries` on the `summary` lines against those on the `original`
+/// lines. Terms of the summary stand for the terms `expand` returns for them.
+///
+/// Precision is the share of the summary results that are original results, recall the share
+/// of the original results that are summary results. Both are 1 when there are no results to
+/// get wrong.
+pub fn evaluate_queries<F>(
+    queries: &[Pattern],
+    original: &[String],
+    summary: &[String],
+    expand: F,
+) -> EvaluateQueriesReport
+where
+    F: Fn(&str) -> Vec<String>,
+{
+    let answer = |lines: &[String], expand: &dyn Fn(&str) -> Vec<String>| {
+        let mut results: Vec<HashSet<Vec<String>>> = vec![HashSet::new(); queries.len()];
+        for l in lines {
+            let words: Vec<&str> = l.split(' ').collect();
+            if words.len() < 3 || l.starts_with('#') {
+                continue;
+            }
+            let terms: Vec<Vec<String>> = words[..3]
+                .iter()
+                .map(|t| expand(&rdf::normalize(t)))
+                .collect();
+            for (q, r) in queries.iter().zip(&mut results) {
+                q.bind([&terms[0], &terms[1], &terms[2]], r);
+            }
+        }
+        results
+    };
+    let original = answer(original, &|t| vec![t.to_string()]);
+    let summary = answer(summary, &expand);
+
+    let share = |part: usize, whole: usize| match whole {
+        0 => 1.0,
+        _ => part as f64 / whole as f64,
+    };
+    let scores: Vec<QueryScore> = queries
+        .iter()
+        .zip(original.iter().zip(&summary))
+        .map(|(q, (o, s))| {
+            let shared = o.intersection(s).count();
+            QueryScore {
+                query: q.terms.join(" "),
+                original_results: o.len(),
+                summary_results: s.len(),
+                shared_results: shared,
+                precision: share(shared, s.len()),
+                recall: share(shared, o.len()),
+            }
+        })
+        .collect();
+    let mean = |score: fn(&QueryScore) -> f64| match scores.len() {
+        0 => 1.0,
+        n => scores.iter().map(score).sum::<f64>() / n as f64,
+    };
+    EvaluateQueriesReport {
+        mean_precision: mean(|s| s.precision),
+        mean_recall: mean(|s| s.recall),
+        queries: scores,
+    }
+}
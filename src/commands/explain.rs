@@ -0,0 +1,219 @@
+use super::print_report;
+use crate::models::clique::CliqueCollection;
+use crate::models::dataset::Dataset;
+use crate::models::meta::Meta;
+use crate::models::triple::TYPE_STRING;
+use crate::util::exit::Failure;
+use crate::{read_input, updater, Cli};
+use serde::Serialize;
+use std::collections::BTreeSet;
+use std::fmt;
+use std::process;
+
+/// Why two nodes are or are not in the same supernode.
+#[derive(Serialize)]
+pub struct Explanation {
+    pub node_a: String,
+    pub node_b: String,
+    pub supernode_a: Option<String>,
+    pub supernode_b: Option<String>,
+    pub merged: bool,
+    /// Signature of each supernode of the two nodes.
+    pub signatures: Vec<SupernodeSignature>,
+    /// Comparison of the outgoing predicates.
+    pub source: CliqueComparison,
+    /// Comparison of the incoming predicates.
+    pub target: CliqueComparison,
+}
+
+/// The predicates of the edges of the members of a supernode.
+#[derive(Serialize)]
+pub struct SupernodeSignature {
+    pub supernode: String,
+    pub outgoing: Vec<String>,
+    pub incoming: Vec<String>,
+}
+
+#[derive(Serialize)]
+pub struct CliqueComparison {
+    pub same_clique: bool,
+    /// Predicates of the clique containing both nodes, if they share one.
+    pub shared_preds: Vec<String>,
+    /// Predicates only the first node has.
+    pub only_a: Vec<String>,
+    /// Predicates only the second node has.
+    pub only_b: Vec<String>,
+}
+
+impl fmt::Display for Explanation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match (&self.merged, &self.supernode_a) {
+            (true, Some(s)) => writeln!(
+                f,
+                "{} and {} are merged into {}",
+                self.node_a, self.node_b, s
+            )?,
+            _ => writeln!(f, "{} and {} are not merged", self.node_a, self.node_b)?,
+        }
+        let preds = |preds: &Vec<String>| match preds.is_empty() {
+            true => String::from("none"),
+            false => preds.join(" "),
+        };
+        for signature in &self.signatures {
+            writeln!(
+                f,
+                "signature of {}: out {}; in {}",
+                signature.supernode,
+                preds(&signature.outgoing),
+                preds(&signature.incoming)
+            )?;
+        }
+        self.source
+            .fmt_named(f, "source", &self.node_a, &self.node_b)?;
+        self.target
+            .fmt_named(f, "target", &self.node_a, &self.node_b)
+    }
+}
+
+impl CliqueComparison {
+    fn fmt_named(&self, f: &mut fmt::Formatter, name: &str, a: &str, b: &str) -> fmt::Result {
+        if self.same_clique && self.shared_preds.is_empty() {
+            writeln!(f, "same {} clique: no predicates", name)?;
+        } else if self.same_clique {
+            writeln!(f, "same {} clique: {}", name, self.shared_preds.join(" "))?;
+        } else {
+            writeln!(f, "different {} cliques", name)?;
+        }
+        if !self.only_a.is_empty() {
+            writeln!(f, "  only {}: {}", a, self.only_a.join(" "))?;
+        }
+        if !self.only_b.is_empty() {
+            writeln!(f, "  only {}: {}", b, self.only_b.join(" "))?;
+        }
+        Ok(())
+    }
+}
+
+/// Applies the update in memory and explains the grouping of the nodes `a` and `b`.
+pub fn run(cli: &Cli, a: &str, b: &str) {
+    let (mut dataset, mut meta, mut sc, mut tc) = read_input(cli);
+    if let Err(err) = updater::run(
+        &mut dataset,
+        &mut meta,
+        &mut sc,
+        &mut tc,
+        &cli.update_options(),
+    ) {
+        cli.fail(
+            Failure::Conflict,
+            &format!("Problem applying update: {}", err),
+        )
+    }
+
+    match explain(&dataset, &meta, &sc, &tc, a, b) {
+        Ok(explanation) => print_report(&explanation, &cli.format),
+        Err(err) => {
+            println!("Problem explaining nodes: {}", err);
+            process::exit(1);
+        }
+    }
+}
+
+/// Explains why the nodes `a` and `b`, given as IRIs, are or are not in the same supernode.
+///
+/// Nodes are merged when they are in the same source clique and in the same target clique.
+pub fn explain(
+    dataset: &Dataset,
+    meta: &Meta,
+    sc: &CliqueCollection,
+    tc: &CliqueCollection,
+    a: &str,
+    b: &str,
+) -> Result<Explanation, String> {
+    let a_id = node_id(dataset, meta, a)?;
+    let b_id = node_id(dataset, meta, b)?;
+    let a_parent = meta.get_parent(&a_id);
+    let b_parent = meta.get_parent(&b_id);
+
+    let name = |id: &u32| dataset.key_by_value(id).unwrap();
+    let mut snodes: Vec<u32> = a_parent.into_iter().chain(b_parent).collect();
+    snodes.dedup();
+    let names =
+        |preds: Vec<u32>| -> Vec<String> { preds.iter().map(|p| pred_name(dataset, p)).collect() };
+    let signatures = snodes
+        .iter()
+        .filter_map(|s| {
+            let signature = meta.get_signature(s)?;
+            Some(SupernodeSignature {
+                supernode: name(s),
+                outgoing: names(signature.outgoing),
+                incoming: names(signature.incoming),
+            })
+        })
+        .collect();
+    Ok(Explanation {
+        node_a: a.to_string(),
+        node_b: b.to_string(),
+        supernode_a: a_parent.as_ref().map(name),
+        supernode_b: b_parent.as_ref().map(name),
+        merged: a_parent.is_some() && a_parent == b_parent,
+        signatures,
+        source: compare(dataset, meta, sc, &a_id, &b_id, true),
+        target: compare(dataset, meta, tc, &a_id, &b_id, false),
+    })
+}
+
+fn node_id(dataset: &Dataset, meta: &Meta, iri: &str) -> Result<u32, String> {
+    match dataset.id_of(iri) {
+        Some(id) if meta.contains_supernode(&id) => Err(format!("{} is a supernode", iri)),
+        Some(id) if meta.contains(&id) => Ok(id),
+        _ => Err(format!("unknown node {}", iri)),
+    }
+}
+
+fn compare(
+    dataset: &Dataset,
+    meta: &Meta,
+    cc: &CliqueCollection,
+    a: &u32,
+    b: &u32,
+    is_source: bool,
+) -> CliqueComparison {
+    let a_preds: BTreeSet<u32> = meta.get_preds(a, is_source).into_iter().collect();
+    let b_preds: BTreeSet<u32> = meta.get_preds(b, is_source).into_iter().collect();
+
+    let a_node = meta.get_parent(a).unwrap_or(*a);
+    let b_node = meta.get_parent(b).unwrap_or(*b);
+    let same_clique = cc.contains_node(&a_node)
+        && cc.contains_node(&b_node)
+        && cc.in_same_clique(&a_node, &b_node);
+
+    let names = |preds: &mut dyn Iterator<Item = &u32>| -> Vec<String> {
+        preds.map(|p| pred_name(dataset, p)).collect()
+    };
+    CliqueComparison {
+        same_clique,
+        shared_preds: if same_clique {
+            names(&mut cc.get_clique_by_node(&a_node).preds.iter())
+        } else {
+            Vec::new()
+        },
+        only_a: names(&mut a_preds.difference(&b_preds)),
+        only_b: names(&mut b_preds.difference(&a_preds)),
+    }
+}
+
+/// Returns the IRI of `pred`, or `rdf:type <class>` for the pseudo-predicates of type grouping.
+pub fn pred_name(dataset: &Dataset, pred: &u32) -> String {
+    match dataset.pred_term(pred) {
+        Some(iri) => iri,
+        None => {
+            let class = u32::MAX - pred;
+            format!(
+                "{} {}",
+                TYPE_STRING,
+                dataset.key_by_value(&class).unwrap_or_default()
+            )
+        }
+    }
+}
@@ -0,0 +1,114 @@
+use super::dump_meta::Terms;
+use super::print_report;
+use crate::models::meta::Meta;
+use crate::parser::clique::{read_cliques_file, CliquesFile};
+use crate::util::hash::HashMap;
+use crate::util::io;
+use crate::{parser, Cli};
+use serde::Serialize;
+use std::collections::BTreeSet;
+use std::fmt;
+use std::path::PathBuf;
+
+/// The features of a node of the summary, one line of the file `export-nodes` writes.
+#[derive(Serialize)]
+pub struct NodeFeatures {
+    pub id: u32,
+    pub term: String,
+    pub in_degree: usize,
+    pub out_degree: usize,
+    /// Distinct predicates of the outgoing edges.
+    pub out_preds: Vec<String>,
+    /// Distinct predicates of the incoming edges.
+    pub in_preds: Vec<String>,
+    pub supernode: Option<u32>,
+    /// Index of the source clique of the node, or of its supernode, in the cliques file.
+    pub source_clique: Option<usize>,
+    /// Index of the target clique of the node, or of its supernode, in the cliques file.
+    pub target_clique: Option<usize>,
+}
+
+#[derive(Serialize)]
+pub struct ExportNodesReport {
+    pub nodes: usize,
+    pub out: PathBuf,
+}
+
+impl fmt::Display for ExportNodesReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(
+            f,
+            "wrote the features of {} nodes to {}",
+            self.nodes,
+            self.out.display()
+        )
+    }
+}
+
+/// Writes the features of every node of a meta folder to `out` as JSON Lines, one object per
+/// node, for training models on the summary.
+pub fn run(cli: &Cli, out: &PathBuf) {
+    let meta = parser::meta::parse_meta(cli).unwrap();
+    let dict = parser::dataset::read_dict(&cli.meta_folder_path).unwrap();
+    let preds = parser::dataset::read_preds(&cli.meta_folder_path, &dict).unwrap();
+    let cliques = read_cliques_file(&cli.meta_folder_path);
+
+    let features = node_features(&meta, &dict, &preds, cliques.as_ref());
+    let lines: Vec<String> = features
+        .iter()
+        .map(|n| serde_json::to_string(n).unwrap())
+        .collect();
+    io::write_lines(out, &lines).unwrap();
+
+    let report = ExportNodesReport {
+        nodes: features.len(),
+        out: out.clone(),
+    };
+    print_report(&report, &cli.format);
+}
+
+/// Returns the features of the nodes of `meta` by id, decoded with the terms of `dict_lines`
+/// and the predicates of `pred_lines`. Clique indices are `None` without `cliques`.
+pub fn node_features(
+    meta: &Meta,
+    dict_lines: &[String],
+    pred_lines: &[String],
+    cliques: Option<&CliquesFile>,
+) -> Vec<NodeFeatures> {
+    let terms = Terms {
+        dict: dict_lines,
+        preds: pred_lines,
+    };
+    let (sc, tc): (HashMap<u32, usize>, HashMap<u32, usize>) = match cliques {
+        Some(file) => (
+            file.s.n.iter().copied().collect(),
+            file.t.n.iter().copied().collect(),
+        ),
+        None => Default::default(),
+    };
+
+    let mut ids: Vec<&u32> = meta.get_nodes().keys().collect();
+    ids.sort_unstable();
+    ids.into_iter()
+        .map(|id| {
+            let info = meta.get_node(id).unwrap();
+            let pred_names = |edges: &[[u32; 2]]| {
+                let preds: BTreeSet<u32> = edges.iter().map(|[p, _]| *p).collect();
+                preds.iter().map(|p| terms.pred(p)).collect()
+            };
+            // Cliques hold the nodes of the summary, which members stand for.
+            let representative = info.parent.unwrap_or(*id);
+            NodeFeatures {
+                id: *id,
+                term: terms.term(*id).term,
+                in_degree: info.incoming.len(),
+                out_degree: info.outgoing.len(),
+                out_preds: pred_names(&info.outgoing),
+                in_preds: pred_names(&info.incoming),
+                supernode: info.parent,
+                source_clique: sc.get(&representative).copied(),
+                target_clique: tc.get(&representative).copied(),
+            }
+        })
+        .collect()
+}
@@ -0,0 +1,151 @@
+use super::dump_meta::Terms;
+use super::print_report;
+use crate::models::meta::Meta;
+use crate::models::triple::TYPE_STRING;
+use crate::util::io;
+use crate::{parser, Cli};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::path::PathBuf;
+
+/// A table `export-tables` writes, as the file `<name>.csv`.
+pub struct Table {
+    pub name: &'static str,
+    pub header: &'static [&'static str],
+    pub rows: Vec<Vec<String>>,
+}
+
+#[derive(Serialize)]
+pub struct ExportTablesReport {
+    /// Name and number of rows of each table written.
+    pub tables: Vec<(&'static str, usize)>,
+    pub out: PathBuf,
+}
+
+impl fmt::Display for ExportTablesReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (name, rows) in &self.tables {
+            writeln!(
+                f,
+                "wrote {} rows to {}",
+                rows,
+                self.out.join(format!("{}.csv", name)).display()
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Writes the original triples, the members of the supernodes and the edges of the summary of
+/// a meta folder as CSV files with a header to the folder `out`, which SQL engines and
+/// dataframe libraries read as they are.
+pub fn run(cli: &Cli, out: &PathBuf) {
+    let meta = parser::meta::parse_meta(cli).unwrap();
+    let dict = parser::dataset::read_dict(&cli.meta_folder_path).unwrap();
+    let preds = parser::dataset::read_preds(&cli.meta_folder_path, &dict).unwrap();
+
+    fs::create_dir_all(out).unwrap();
+    let mut report = ExportTablesReport {
+        tables: Vec::new(),
+        out: out.clone(),
+    };
+    for table in tables(&meta, &dict, &preds) {
+        let mut lines: Vec<String> = vec![csv_line(table.header)];
+        lines.extend(table.rows.iter().map(|r| csv_line(r)));
+        io::write_lines(&out.join(format!("{}.csv", table.name)), &lines).unwrap();
+        report.tables.push((table.name, table.rows.len()));
+    }
+    print_report(&report, &cli.format);
+}
+
+/// Returns the tables of `meta`, decoded with the terms of `dict_lines` and the predicates of
+/// `pred_lines`:
+///
+/// - `triples`: the original triples, types included.
+/// - `members`: each supernode with each of its members.
+/// - `summary_edges`: the edges of the summary, with the number of original triples each
+///   stands for.
+pub fn tables(meta: &Meta, dict_lines: &[String], pred_lines: &[String]) -> Vec<Table> {
+    let terms = Terms {
+        dict: dict_lines,
+        preds: pred_lines,
+    };
+    let term = |id: &u32| terms.term(*id).term;
+    let representative = |n: &u32| meta.get_parent(n).unwrap_or(*n);
+
+    // Keyed by ids for a stable order; `None` is the type predicate.
+    let mut triples: BTreeMap<(u32, Option<u32>, u32), u64> = BTreeMap::new();
+    let mut edges: BTreeMap<(u32, Option<u32>, u32), u64> = BTreeMap::new();
+    for (n, info) in meta.get_nodes() {
+        for [p, o] in &info.outgoing {
+            *triples.entry((*n, Some(*p), *o)).or_default() += 1;
+            *edges
+                .entry((representative(n), Some(*p), representative(o)))
+                .or_default() += 1;
+        }
+    }
+    for [s, class] in meta.get_types() {
+        triples.insert((*s, None, *class), 1);
+        *edges.entry((representative(s), None, *class)).or_default() += 1;
+    }
+    let pred = |p: &Option<u32>| match p {
+        Some(p) => terms.pred(p),
+        None => TYPE_STRING.to_string(),
+    };
+
+    let mut snodes: Vec<(&u32, &Vec<u32>)> = meta.get_supernodes().iter().collect();
+    snodes.sort_unstable();
+    let mut members: Vec<Vec<String>> = Vec::new();
+    for (snode, nodes) in snodes {
+        let mut nodes = nodes.clone();
+        nodes.sort_unstable();
+        members.extend(nodes.iter().map(|m| vec![term(snode), term(m)]));
+    }
+
+    vec![
+        Table {
+            name: "triples",
+            header: &["subject", "predicate", "object"],
+            rows: triples
+                .iter()
+                .flat_map(|((s, p, o), count)| {
+                    // Triples the meta holds more than once are repeated.
+                    let row = vec![term(s), pred(p), term(o)];
+                    std::iter::repeat_n(row, *count as usize)
+                })
+                .collect(),
+        },
+        Table {
+            name: "members",
+            header: &["supernode", "node"],
+            rows: members,
+        },
+        Table {
+            name: "summary_edges",
+            header: &["subject", "predicate", "object", "triples"],
+            rows: edges
+                .iter()
+                .map(|((s, p, o), count)| vec![term(s), pred(p), term(o), count.to_string()])
+                .collect(),
+        },
+    ]
+}
+
+/// Returns the CSV line of `fields`. Fields with a comma, quote or line break are quoted, as
+/// literals often have them.
+pub fn csv_line<S: AsRef<str>>(fields: &[S]) -> String {
+    let quoted: Vec<String> = fields
+        .iter()
+        .map(|f| {
+            let f = f.as_ref();
+            if f.contains([',', '"', '\n', '\r']) {
+                format!("\"{}\"", f.replace('"', "\"\""))
+            } else {
+                f.to_string()
+            }
+        })
+        .collect();
+    quoted.join(",")
+}
@@ -0,0 +1,100 @@
+use crate::models::dict::Dict;
+use crate::models::meta::Meta;
+use crate::models::triple::TYPE_STRING;
+use crate::util::io;
+use crate::{as_iri, parser, rdf, Cli};
+use std::collections::BTreeSet;
+use std::path::PathBuf;
+use std::process;
+
+/// Writes the original triples of the members of `supernode` to `out`, or to stdout if `out`
+/// is `None`.
+///
+/// `supernode` is the id of the supernode, its alias or its IRI.
+pub fn run(cli: &Cli, supernode: &str, out: &Option<PathBuf>) {
+    let meta = parser::meta::parse_meta(cli).unwrap();
+    let dict_lines = parser::dataset::read_dict(&cli.meta_folder_path).unwrap();
+    let pred_lines = parser::dataset::read_preds(&cli.meta_folder_path, &dict_lines).unwrap();
+    let source_lines = parser::dataset::read_sources(&cli.meta_folder_path).unwrap();
+
+    let id = match supernode.parse::<u32>() {
+        Ok(id) => Some(id),
+        Err(_) => meta.supernode_by_alias(supernode).or_else(|| {
+            Dict::new(&dict_lines, &vec![])
+                .get(&as_iri(supernode))
+                .copied()
+        }),
+    };
+    let lines = match id {
+        Some(id) if meta.contains_supernode(&id) => {
+            extract(&meta, &dict_lines, &pred_lines, &source_lines, &id)
+        }
+        _ => {
+            println!("{} is not a supernode", supernode);
+            process::exit(1);
+        }
+    };
+
+    match out {
+        Some(path) => {
+            io::write_lines(path, &lines).unwrap();
+            println!("wrote {} triples to {}", lines.len(), path.display());
+        }
+        None => {
+            for l in &lines {
+                println!("{}", l);
+            }
+        }
+    }
+}
+
+/// Returns the sorted original triples with a member of `snode` as subject or object, decoded
+/// with the terms of `dict_lines` and the predicates of `pred_lines`.
+///
+/// Triples read with a source are written as N-Quads, with the term of their source in
+/// `source_lines` as graph.
+pub fn extract(
+    meta: &Meta,
+    dict_lines: &[String],
+    pred_lines: &[String],
+    source_lines: &[String],
+    snode: &u32,
+) -> Vec<String> {
+    let term = |id: &u32| match dict_lines.get(*id as usize - 1) {
+        Some(t) if !t.is_empty() => t.to_string(),
+        _ => id.to_string(),
+    };
+    // Edges of type grouping use pseudo-predicates outside the dict for `rdf:type`.
+    let pred = |id: &u32| match pred_lines.get(*id as usize - 1) {
+        Some(t) if !t.is_empty() => t.to_string(),
+        _ => TYPE_STRING.to_string(),
+    };
+
+    let line = |s: &u32, p: &u32, o: &u32| {
+        let source = meta
+            .get_source(s, p, o)
+            .and_then(|g| source_lines.get(g as usize - 1));
+        match source {
+            Some(g) => rdf::quad_line(&term(s), &pred(p), &term(o), g),
+            None => rdf::triple_line(&term(s), &pred(p), &term(o)),
+        }
+    };
+
+    let members = meta.get_supernode(snode).unwrap();
+    let mut lines: BTreeSet<String> = BTreeSet::new();
+    for m in members {
+        let node = meta.get_node(m).unwrap();
+        for [p, o] in &node.outgoing {
+            lines.insert(line(m, p, o));
+        }
+        for [p, s] in &node.incoming {
+            lines.insert(line(s, p, m));
+        }
+    }
+    for [s, o] in meta.get_types() {
+        if members.binary_search(s).is_ok() {
+            lines.insert(rdf::triple_line(&term(s), TYPE_STRING, &term(o)));
+        }
+    }
+    lines.into_iter().collect()
+}
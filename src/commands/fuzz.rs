@@ -0,0 +1,239 @@
+use super::{print_report, Format};
+use crate::models::canonical::CanonicalSummary;
+use crate::updater::{self, Options};
+use crate::util::{io, rng::Rng};
+use crate::{parser, Grouping};
+use serde::Serialize;
+use std::collections::HashSet;
+use std::fmt;
+use std::panic::{self, AssertUnwindSafe};
+use std::path::PathBuf;
+use std::process;
+
+/// Settings of `teriyaki fuzz`.
+pub struct FuzzOptions {
+    pub rounds: usize,
+    /// Number of changes sampled per round.
+    pub changes: usize,
+    pub seed: u64,
+}
+
+#[derive(Serialize)]
+pub struct FuzzReport {
+    pub seed: u64,
+    pub rounds: usize,
+    /// The first round whose summaries differed, if any.
+    pub divergence: Option<Divergence>,
+}
+
+#[derive(Serialize)]
+pub struct Divergence {
+    pub round: usize,
+    /// Smallest changeset found that still diverges, in changeset format.
+    pub changes: Vec<String>,
+    pub incremental: Outcome,
+    pub batch: Outcome,
+}
+
+/// Result of summarizing.
+#[derive(Serialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Outcome {
+    Summary(CanonicalSummary),
+    /// The update failed or panicked.
+    Failure(String),
+}
+
+impl fmt::Display for FuzzReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match &self.divergence {
+            None => writeln!(
+                f,
+                "{} rounds without divergence (seed {})",
+                self.rounds, self.seed
+            ),
+            Some(d) => {
+                writeln!(f, "divergence in round {} (seed {})", d.round, self.seed)?;
+                writeln!(f, "changes:")?;
+                for c in &d.changes {
+                    writeln!(f, "  {}", c)?;
+                }
+                d.incremental.fmt_named(f, "incremental")?;
+                d.batch.fmt_named(f, "batch")
+            }
+        }
+    }
+}
+
+impl Outcome {
+    fn fmt_named(&self, f: &mut fmt::Formatter, name: &str) -> fmt::Result {
+        match self {
+            Outcome::Summary(summary) => {
+                writeln!(f, "{} summary:", name)?;
+                for l in summary.to_string().lines() {
+                    writeln!(f, "  {}", l)?;
+                }
+                Ok(())
+            }
+            Outcome::Failure(msg) => writeln!(f, "{} run failed: {}", name, msg),
+        }
+    }
+}
+
+/// Applies random changesets to the dataset at `path` incrementally and from scratch, and
+/// compares the summaries.
+///
+/// Every round starts from the dataset itself, so a divergence is reproduced by updating a
+/// summary of the dataset with the reported changes. Exits with a non-zero status on the first
+/// divergence.
+pub fn run(path: &PathBuf, options: &FuzzOptions, format: &Format) {
+    let base: Vec<String> = io::read_lines(path)
+        .unwrap()
+        .into_iter()
+        .filter(|l| !l.trim().is_empty())
+        .collect();
+
+    // Panics are part of the report, so keep them from cluttering the output.
+    let hook = panic::take_hook();
+    panic::set_hook(Box::new(|_| {}));
+
+    let mut rng = Rng::new(options.seed);
+    let mut report = FuzzReport {
+        seed: options.seed,
+        rounds: 0,
+        divergence: None,
+    };
+    for round in 1..=options.rounds {
+        report.rounds = round;
+        let changes = sample_changes(&base, options.changes, &mut rng);
+        if let Some(d) = compare(&base, &changes) {
+            let changes = minimize(&base, changes);
+            let (incremental, batch) = compare(&base, &changes).unwrap_or(d);
+            report.divergence = Some(Divergence {
+                round,
+                changes,
+                incremental,
+                batch,
+            });
+            break;
+        }
+    }
+
+    panic::set_hook(hook);
+    print_report(&report, format);
+    if report.divergence.is_some() {
+        process::exit(1);
+    }
+}
+
+/// Samples up to `n` changes: deletions of lines of `base` and insertions of new triples
+/// recombined from the terms of `base`.
+fn sample_changes(base: &[String], n: usize, rng: &mut Rng) -> Vec<String> {
+    let terms: Vec<Vec<&str>> = base.iter().map(|l| l.split(' ').collect()).collect();
+    let existing: HashSet<&String> = base.iter().collect();
+
+    let mut changes: Vec<String> = Vec::new();
+    for _ in 0..n {
+        if base.is_empty() {
+            break;
+        }
+        let change = if rng.below(2) == 0 {
+            format!("-{}", base[rng.below(base.len())])
+        } else {
+            let s = terms[rng.below(terms.len())][0];
+            let p = terms[rng.below(terms.len())][1];
+            let o = terms[rng.below(terms.len())][2];
+            let line = format!("{} {} {} .", s, p, o);
+            if existing.contains(&line) {
+                continue;
+            }
+            line
+        };
+        if !changes.contains(&change) {
+            changes.push(change);
+        }
+    }
+    changes
+}
+
+/// Returns the incremental and batch outcomes if they differ.
+fn compare(base: &[String], changes: &[String]) -> Option<(Outcome, Outcome)> {
+    let incremental = summarize(base, changes);
+    let batch = summarize(&apply(base, changes), &[]);
+    if incremental == batch && !matches!(incremental, Outcome::Failure(_)) {
+        None
+    } else {
+        Some((incremental, batch))
+    }
+}
+
+/// Removes changes one at a time as long as the rest still diverges.
+fn minimize(base: &[String], mut changes: Vec<String>) -> Vec<String> {
+    let mut i = 0;
+    while i < changes.len() && changes.len() > 1 {
+        let mut fewer = changes.clone();
+        fewer.remove(i);
+        if compare(base, &fewer).is_some() {
+            changes = fewer;
+        } else {
+            i += 1;
+        }
+    }
+    changes
+}
+
+fn apply(base: &[String], changes: &[String]) -> Vec<String> {
+    let mut lines = base.to_vec();
+    for c in changes {
+        match c.strip_prefix('-') {
+            Some(deleted) => lines.retain(|l| l != deleted),
+            None => lines.push(c.to_string()),
+        }
+    }
+    lines
+}
+
+/// Summarizes `lines` and then applies `changes` to the summary.
+fn summarize(lines: &[String], changes: &[String]) -> Outcome {
+    let result = panic::catch_unwind(AssertUnwindSafe(|| {
+        let triples = lines
+            .iter()
+            .map(|l| {
+                let terms: Vec<&str> = l.split(' ').collect();
+                (
+                    terms[0].to_string(),
+                    terms[1].to_string(),
+                    terms[2].to_string(),
+                )
+            })
+            .collect();
+        let (mut dataset, mut meta, mut sc, mut tc) =
+            parser::from_triples(triples, &Grouping::Preds);
+        let options = Options {
+            paranoid: true,
+            ..Default::default()
+        };
+        updater::run(&mut dataset, &mut meta, &mut sc, &mut tc, &options)?;
+
+        if !changes.is_empty() {
+            let (deletions, insertions): (Vec<String>, Vec<String>) =
+                changes.iter().cloned().partition(|c| c.starts_with('-'));
+            let deletions = deletions.into_iter().map(|d| d[1..].to_string()).collect();
+            dataset.set_update(insertions, deletions, &mut meta, &Grouping::Preds);
+            updater::run(&mut dataset, &mut meta, &mut sc, &mut tc, &options)?;
+        }
+        Ok::<_, String>(CanonicalSummary::new(&dataset, &meta))
+    }));
+
+    match result {
+        Ok(Ok(summary)) => Outcome::Summary(summary),
+        Ok(Err(err)) => Outcome::Failure(err),
+        Err(payload) => Outcome::Failure(
+            payload
+                .downcast_ref::<String>()
+                .cloned()
+                .or_else(|| payload.downcast_ref::<&str>().map(|s| s.to_string()))
+                .unwrap_or_default(),
+        ),
+    }
+}
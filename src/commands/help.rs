@@ -0,0 +1,691 @@
+use std::fmt::Write;
+
+/// A subcommand as shown by `--help` and offered by the shell completions.
+pub struct Subcommand {
+    pub name: &'static str,
+    /// Arguments after the name, with optional ones in brackets.
+    pub usage: &'static str,
+    pub about: &'static str,
+    /// Flags completed after the name; those of a run if `None`.
+    pub flags: Option<&'static [&'static str]>,
+    pub examples: &'static [&'static str],
+}
+
+/// A flag of a run, with the values it takes if it takes one.
+pub struct Flag {
+    pub name: &'static str,
+    pub value: Option<&'static str>,
+    pub about: &'static str,
+}
+
+pub const SUBCOMMANDS: &[Subcommand] = &[
+    Subcommand {
+        name: "update",
+        usage: "<dataset> <update> <meta> [flags]",
+        about: "Applies the update and writes summary and meta (the default)",
+        flags: None,
+        examples: &[
+            "teriyaki update empty.nt dataset.nt meta --fast",
+            "teriyaki update summary.nt changes.nt meta --split-strategy lazy",
+        ],
+    },
+    Subcommand {
+        name: "stats",
+        usage: "<dataset> <update> <meta> [flags]",
+        about: "Applies the update in memory and prints statistics about the summary",
+        flags: None,
+        examples: &["teriyaki stats summary.nt changes.nt meta --format json"],
+    },
+    Subcommand {
+        name: "recluster",
+        usage: "<dataset> <update> <meta> [flags]",
+        about: "Applies the update, then repairs the grouping of the whole summary",
+        flags: None,
+        examples: &["teriyaki recluster summary.nt changes.nt meta"],
+    },
+    Subcommand {
+        name: "validate",
+        usage: "<dataset> <update> <meta> [flags]",
+        about: "Checks that the inputs of a run are well-formed",
+        flags: None,
+        examples: &["teriyaki validate summary.nt changes.nt meta"],
+    },
+    Subcommand {
+        name: "dry-run",
+        usage: "<dataset> <update> <meta> [flags]",
+        about: "Applies the update in memory without writing anything",
+        flags: None,
+        examples: &["teriyaki dry-run summary.nt changes.nt meta --format json"],
+    },
+    Subcommand {
+        name: "repl",
+        usage: "<dataset> <update> <meta> [flags]",
+        about: "Queries the summary interactively",
+        flags: None,
+        examples: &["teriyaki repl summary.nt changes.nt meta --wal"],
+    },
+    Subcommand {
+        name: "explain",
+        usage: "<node a> <node b> <dataset> <update> <meta> [flags]",
+        about: "Explains why two nodes are or are not merged",
+        flags: None,
+        examples: &["teriyaki explain http://ex.org/a http://ex.org/b summary.nt changes.nt meta"],
+    },
+    Subcommand {
+        name: "diff",
+        usage: "<old> <new> [--format text|json]",
+        about: "Compares two summary files",
+        flags: Some(&["--format"]),
+        examples: &["teriyaki diff old-summary.nt summary.nt"],
+    },
+    Subcommand {
+        name: "diff-datasets",
+        usage: "<old> <new> --out <file> [--chunk-lines N] [--format text|json]",
+        about: "Writes the changeset between two dataset dumps",
+        flags: Some(&["--out", "--chunk-lines", "--format"]),
+        examples: &["teriyaki diff-datasets monday.nt tuesday.nt --out changes.nt"],
+    },
+    Subcommand {
+        name: "prepare",
+        usage: "<input> --out <folder> [--shards N] [--chunk-lines N] [--format text|json]",
+        about: "Sorts, deduplicates and optionally shards a dataset into a folder with an index",
+        flags: Some(&["--out", "--shards", "--chunk-lines", "--format"]),
+        examples: &["teriyaki prepare dump.nt --out prepared --shards 8"],
+    },
+    Subcommand {
+        name: "evaluate",
+        usage: "<incremental meta> <fresh meta> [--format text|json]",
+        about: "Scores the grouping of an incremental summary against one built from scratch",
+        flags: Some(&["--format"]),
+        examples: &["teriyaki evaluate meta fresh-meta"],
+    },
+    Subcommand {
+        name: "evaluate-queries",
+        usage: "<original> <summary> <meta> <queries> [--format text|json]",
+        about: "Scores the answers of triple patterns on a summary against the original triples",
+        flags: Some(&["--format"]),
+        examples: &["teriyaki evaluate-queries dataset.nt summary.nt meta patterns.txt"],
+    },
+    Subcommand {
+        name: "moved",
+        usage: "<old meta> <new meta> [--decode] [--format text|json]",
+        about: "Lists the nodes whose supernode differs between two meta folders",
+        flags: Some(&["--decode", "--format"]),
+        examples: &["teriyaki moved meta-before meta --decode"],
+    },
+    Subcommand {
+        name: "extract",
+        usage: "<meta> --supernode <id|alias|iri> [--out <file>]",
+        about: "Writes the original triples of the members of a supernode",
+        flags: Some(&["--supernode", "--out"]),
+        examples: &["teriyaki extract meta --supernode 42 --out members.nt"],
+    },
+    Subcommand {
+        name: "export-nodes",
+        usage: "<meta> --out <file> [--format text|json]",
+        about: "Writes the features of every node for machine learning, as JSON Lines",
+        flags: Some(&["--out", "--format"]),
+        examples: &["teriyaki export-nodes meta --out nodes.jsonl"],
+    },
+    Subcommand {
+        name: "export-tables",
+        usage: "<meta> --out <folder> [--format text|json]",
+        about: "Writes the triples, supernode members and summary edges as CSV tables",
+        flags: Some(&["--out", "--format"]),
+        examples: &["teriyaki export-tables meta --out tables"],
+    },
+    Subcommand {
+        name: "dump-meta",
+        usage: "<meta> (--node <id|iri> | --supernode <id|alias|iri>) [--format text|json]",
+        about: "Prints a readable view of the meta record of a node or supernode",
+        flags: Some(&["--node", "--supernode", "--format"]),
+        examples: &[
+            "teriyaki dump-meta meta --node http://ex.org/alice",
+            "teriyaki dump-meta meta --supernode 7 --format json",
+        ],
+    },
+    Subcommand {
+        name: "reach",
+        usage: "<meta> <from> <to> [--max-hops N] [--format text|json]",
+        about: "Finds a shortest path between two supernodes in the summary graph",
+        flags: Some(&["--max-hops", "--format"]),
+        examples: &["teriyaki reach meta 3 12 --max-hops 4"],
+    },
+    Subcommand {
+        name: "embed",
+        usage: "<meta> --out <file> [--walks <file>] [--walk-length N] [--walks-per-node N] \
+                [--p X] [--q X] [--seed N] [--format text|json]",
+        about: "Exports the summary graph for embedding toolkits",
+        flags: Some(&[
+            "--out",
+            "--walks",
+            "--walk-length",
+            "--walks-per-node",
+            "--p",
+            "--q",
+            "--seed",
+            "--format",
+        ]),
+        examples: &["teriyaki embed meta --out edges.tsv --walks walks.txt --walk-length 20"],
+    },
+    Subcommand {
+        name: "dict",
+        usage: "export <meta> [--out <file>] [--compressed] | import <file> <meta>",
+        about: "Exports or imports the dict of a meta folder",
+        flags: Some(&["export", "import", "--out", "--compressed"]),
+        examples: &[
+            "teriyaki dict export meta --out dict.txt",
+            "teriyaki dict import dict.txt meta",
+        ],
+    },
+    Subcommand {
+        name: "fuzz",
+        usage: "<dataset> [--rounds N] [--changes N] [--seed N] [--format text|json]",
+        about: "Compares incremental and batch summaries of random changesets",
+        flags: Some(&["--rounds", "--changes", "--seed", "--format"]),
+        examples: &["teriyaki fuzz dataset.nt --rounds 100 --seed 7"],
+    },
+    Subcommand {
+        name: "bench-cliques",
+        usage: "[--sizes N,N...] [--rounds N] [--seed N] [--format text|json]",
+        about: "Measures the costs of keeping the nodes of cliques as lists and as bitmaps",
+        flags: Some(&["--sizes", "--rounds", "--seed", "--format"]),
+        examples: &["teriyaki bench-cliques --sizes 256,65536 --rounds 3"],
+    },
+    Subcommand {
+        name: "bench-edges",
+        usage: "[--degrees N,N...] [--nodes N] [--rounds N] [--seed N] [--format text|json]",
+        about: "Measures the costs of keeping the edges of nodes in vectors and inline",
+        flags: Some(&["--degrees", "--nodes", "--rounds", "--seed", "--format"]),
+        examples: &["teriyaki bench-edges --degrees 1,4,32 --nodes 100000"],
+    },
+    Subcommand {
+        name: "completions",
+        usage: "bash|zsh|fish",
+        about: "Prints a completion script for the shell",
+        flags: Some(&["bash", "zsh", "fish"]),
+        examples: &["teriyaki completions bash > /etc/bash_completion.d/teriyaki"],
+    },
+];
+
+const fn flag(name: &'static str, value: Option<&'static str>, about: &'static str) -> Flag {
+    Flag { name, value, about }
+}
+
+/// The flags of a run, shared by `update` and the subcommands reading a summary.
+pub const RUN_FLAGS: &[Flag] = &[
+    flag(
+        "--fast",
+        None,
+        "Summarizes the dataset from scratch into a new meta folder",
+    ),
+    flag("--format", Some("text json"), "Output format of reports"),
+    flag("--errors-json", None, "Prints failures as JSON"),
+    flag(
+        "--grouping",
+        Some("preds types both"),
+        "Which part of the signature of a node decides its supernode",
+    ),
+    flag(
+        "--types",
+        Some("aside include exclude"),
+        "What happens to rdf:type triples when grouping by preds",
+    ),
+    flag(
+        "--directions",
+        Some("separate joint"),
+        "Whether in and out predicates decide supernodes apart or together",
+    ),
+    flag(
+        "--duplicates",
+        Some("dedupe count error"),
+        "What to do when a triple is added that already exists",
+    ),
+    flag(
+        "--coalesce",
+        Some("sequence set"),
+        "What to do when the update inserts and deletes a triple",
+    ),
+    flag(
+        "--split-strategy",
+        Some("eject repartition lazy"),
+        "What happens to a supernode after a member is split off",
+    ),
+    flag(
+        "--recluster-every",
+        Some("N"),
+        "Insertions between re-partitions of marked supernodes",
+    ),
+    flag(
+        "--max-supernode-size",
+        Some("N"),
+        "Largest number of members of a supernode",
+    ),
+    flag(
+        "--focus-pred",
+        Some("PREDS"),
+        "The only predicates whose cliques decide the grouping",
+    ),
+    flag(
+        "--exclude-pred",
+        Some("PREDS"),
+        "Predicates passed through instead of summarized",
+    ),
+    flag(
+        "--auto-exclude",
+        Some("suggest apply"),
+        "Looks for predicates that barely compress, and excludes them",
+    ),
+    flag(
+        "--inverse-preds",
+        Some("PAIRS"),
+        "Inverse predicates read as one: pred=inverse pairs, or owl for the update's owl:inverseOf triples",
+    ),
+    flag(
+        "--schema",
+        Some("FILE"),
+        "Schema file the insertions are checked against",
+    ),
+    flag(
+        "--seeds",
+        Some("FILE"),
+        "Groups of nodes that supernodes keep together and apart",
+    ),
+    flag(
+        "--sample",
+        Some("X"),
+        "Fraction of the triples to summarize",
+    ),
+    flag("--seed", Some("N"), "Seed that picks the sampled triples"),
+    flag(
+        "--time-budget",
+        Some("SECONDS"),
+        "Time after which the rest of the update is left pending",
+    ),
+    flag(
+        "--max-errors",
+        Some("N"),
+        "Malformed input lines skipped before the run fails",
+    ),
+    flag(
+        "--lenient",
+        None,
+        "Skips changes the summary is inconsistent around instead of failing",
+    ),
+    flag(
+        "--max-line-bytes",
+        Some("N"),
+        "Longest line read from the summary and update files",
+    ),
+    flag(
+        "--long-lines",
+        Some("fail skip truncate"),
+        "What happens to longer lines",
+    ),
+    flag(
+        "--keep-literals-over",
+        Some("BYTES"),
+        "Length above which new literals are kept in a side file",
+    ),
+    flag(
+        "--dedup-window",
+        Some("N"),
+        "Recent repl changes that a repeated change is skipped within",
+    ),
+    flag(
+        "--sorted-input",
+        None,
+        "The triples of every subject are next to each other in the summary",
+    ),
+    flag(
+        "--chunk-size",
+        Some("N"),
+        "Changes applied between snapshots of the summary",
+    ),
+    flag("--term-cache", Some("N"), "Decoded terms the dict keeps"),
+    flag(
+        "--clique-bitmap-threshold",
+        Some("N"),
+        "Nodes above which a clique keeps its nodes in a bitmap",
+    ),
+    flag(
+        "--channel-capacity",
+        Some("N"),
+        "Batches of summary lines read ahead of the parser",
+    ),
+    flag("--threads", Some("N"), "Threads the meta file is parsed on"),
+    flag(
+        "--drift-max-singletons",
+        Some("X"),
+        "Share of single nodes past which the repl re-clusters",
+    ),
+    flag(
+        "--drift-min-size",
+        Some("N"),
+        "Average supernode size below which the repl re-clusters",
+    ),
+    flag(
+        "--drift-max-splits",
+        Some("N"),
+        "Splits per minute past which the repl re-clusters",
+    ),
+    flag(
+        "--paranoid",
+        None,
+        "Verifies the cliques after every batch of changes",
+    ),
+    flag(
+        "--analyze",
+        None,
+        "Prints statistics about the update without applying it",
+    ),
+    flag(
+        "--centrality",
+        None,
+        "Reports the centrality of summary nodes in stats",
+    ),
+    flag(
+        "--pagerank",
+        None,
+        "Adds the pagerank of summary nodes to the centrality report",
+    ),
+    flag(
+        "--cliques",
+        None,
+        "Reports the compression of each clique in stats",
+    ),
+    flag("--timings", None, "Reports how long each stage took"),
+    flag(
+        "--profile-memory",
+        None,
+        "Prints the peak memory and the size of the models at exit",
+    ),
+    flag(
+        "--provenance",
+        None,
+        "Gives insertions without a graph term the update file as source",
+    ),
+    flag(
+        "--compact-meta",
+        None,
+        "Writes a compact meta file for queries next to the meta file",
+    ),
+    #[cfg(feature = "sqlite")]
+    flag(
+        "--sqlite-meta",
+        None,
+        "Keeps the meta in a SQLite database instead of the meta file",
+    ),
+    #[cfg(feature = "zstd")]
+    flag(
+        "--zstd-meta",
+        Some("LEVEL"),
+        "Level the meta file is compressed at with zstd",
+    ),
+    flag(
+        "--wal",
+        None,
+        "Logs the update and each repl change before applying them; an empty update replays the log",
+    ),
+    #[cfg(feature = "endpoint")]
+    flag(
+        "--page-size",
+        Some("N"),
+        "Triples read per request from a SPARQL endpoint",
+    ),
+    #[cfg(feature = "endpoint")]
+    flag(
+        "--endpoint-timeout",
+        Some("SECONDS"),
+        "Time a request to an endpoint may wait on the network",
+    ),
+    #[cfg(feature = "endpoint")]
+    flag(
+        "--push",
+        Some("URL"),
+        "Pushes the summary and the supernode mapping to an endpoint",
+    ),
+    #[cfg(feature = "endpoint")]
+    flag(
+        "--push-batch",
+        Some("N"),
+        "Triples sent per request to the --push endpoint",
+    ),
+    #[cfg(feature = "endpoint")]
+    flag(
+        "--push-retries",
+        Some("N"),
+        "Retries of a failed request to the --push endpoint",
+    ),
+];
+
+/// The text of `teriyaki --help`.
+pub fn usage() -> String {
+    let mut out = String::from(
+        "Summarizes an RDF graph incrementally.\n\n\
+         Usage: teriyaki [subcommand] <dataset> <update> <meta> [flags]\n\n\
+         Subcommands:\n",
+    );
+    for s in SUBCOMMANDS {
+        writeln!(out, "  {:<18} {}", s.name, s.about).unwrap();
+    }
+    out.push_str("\nFlags:\n");
+    for f in RUN_FLAGS {
+        let name = match f.value {
+            Some(value) => format!("{} <{}>", f.name, value.replace(' ', "|")),
+            None => f.name.to_string(),
+        };
+        writeln!(out, "  {:<40} {}", name, f.about).unwrap();
+    }
+    out.push_str(
+        "\nExamples:\n  teriyaki empty.nt dataset.nt meta --fast\n  \
+         teriyaki summary.nt changes.nt meta\n\n\
+         See `teriyaki <subcommand> --help` for the arguments of each subcommand.\n",
+    );
+    out
+}
+
+/// The text of `teriyaki <name> --help`, if `name` is a subcommand.
+pub fn subcommand_usage(name: &str) -> Option<String> {
+    let s = SUBCOMMANDS.iter().find(|s| s.name == name)?;
+    let mut out = format!("{}.\n\nUsage: teriyaki {} {}\n", s.about, s.name, s.usage);
+    if s.flags.is_none() {
+        out.push_str("\nTakes the flags of a run, see `teriyaki --help`.\n");
+    }
+    out.push_str("\nExamples:\n");
+    for example in s.examples {
+        writeln!(out, "  {}", example).unwrap();
+    }
+    Some(out)
+}
+
+/// A shell `teriyaki completions` writes a script for.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Shell {
+    Bash,
+    Zsh,
+    Fish,
+}
+
+impl Shell {
+    pub fn parse(arg: Option<&String>) -> Result<Self, &'static str> {
+        match arg.map(|s| s.as_str()) {
+            Some("bash") => Ok(Shell::Bash),
+            Some("zsh") => Ok(Shell::Zsh),
+            Some("fish") => Ok(Shell::Fish),
+            _ => Err("completions expects bash, zsh or fish"),
+        }
+    }
+}
+
+pub fn run(shell: Shell) {
+    print!("{}", completions(shell));
+}
+
+/// The completion script for `shell`, completing subcommands, their flags, and the values of
+/// run flags that take one of a few.
+pub fn completions(shell: Shell) -> String {
+    match shell {
+        Shell::Bash => bash(),
+        Shell::Zsh => zsh(),
+        Shell::Fish => fish(),
+    }
+}
+
+fn run_flag_names() -> String {
+    let names: Vec<&str> = RUN_FLAGS.iter().map(|f| f.name).collect();
+    names.join(" ")
+}
+
+/// Values of a flag that takes one of a few words, rather than a number or a file.
+fn choices(f: &Flag) -> Option<&'static str> {
+    f.value
+        .filter(|v| v.chars().all(|c| c.is_ascii_lowercase() || c == ' '))
+}
+
+fn bash() -> String {
+    let names: Vec<&str> = SUBCOMMANDS.iter().map(|s| s.name).collect();
+    let mut out = String::from("# bash completion for teriyaki\n_teriyaki() {\n");
+    out.push_str("    local cur prev words\n");
+    out.push_str(
+        "    cur=\"${COMP_WORDS[COMP_CWORD]}\"\n    prev=\"${COMP_WORDS[COMP_CWORD-1]}\"\n",
+    );
+    out.push_str("    case \"$prev\" in\n");
+    for f in RUN_FLAGS {
+        if let Some(values) = choices(f) {
+            writeln!(
+                out,
+                "        {}) COMPREPLY=($(compgen -W \"{}\" -- \"$cur\")); return ;;",
+                f.name, values
+            )
+            .unwrap();
+        }
+    }
+    out.push_str("    esac\n");
+    writeln!(
+        out,
+        "    if [ \"$COMP_CWORD\" -eq 1 ]; then\n        \
+         COMPREPLY=($(compgen -W \"{} --help\" -- \"$cur\") $(compgen -f -- \"$cur\"))\n        \
+         return\n    fi",
+        names.join(" ")
+    )
+    .unwrap();
+    out.push_str("    case \"${COMP_WORDS[1]}\" in\n");
+    for s in SUBCOMMANDS {
+        if let Some(flags) = s.flags {
+            writeln!(
+                out,
+                "        {}) words=\"{} --help\" ;;",
+                s.name,
+                flags.join(" ")
+            )
+            .unwrap();
+        }
+    }
+    writeln!(out, "        *) words=\"{} --help\" ;;", run_flag_names()).unwrap();
+    out.push_str("    esac\n");
+    out.push_str(
+        "    if [[ \"$cur\" == -* ]]; then\n        \
+         COMPREPLY=($(compgen -W \"$words\" -- \"$cur\"))\n    else\n        \
+         COMPREPLY=($(compgen -W \"$words\" -- \"$cur\") $(compgen -f -- \"$cur\"))\n    fi\n",
+    );
+    out.push_str("}\ncomplete -o filenames -F _teriyaki teriyaki\n");
+    out
+}
+
+fn zsh() -> String {
+    let mut out = String::from("#compdef teriyaki\n\n_teriyaki() {\n    local -a subcommands\n");
+    out.push_str("    subcommands=(\n");
+    for s in SUBCOMMANDS {
+        writeln!(
+            out,
+            "        '{}:{}'",
+            s.name,
+            s.about.replace('\'', "'\\''")
+        )
+        .unwrap();
+    }
+    out.push_str("    )\n    local -a run_flags\n    run_flags=(\n");
+    for f in RUN_FLAGS {
+        let value = match (choices(f), f.value) {
+            (Some(values), _) => format!(":{}:({})", f.name.trim_start_matches('-'), values),
+            (None, Some(_)) => format!(":{}:_files", f.name.trim_start_matches('-')),
+            (None, None) => String::new(),
+        };
+        writeln!(out, "        '{}[{}]{}'", f.name, f.about, value).unwrap();
+    }
+    out.push_str("    )\n    if (( CURRENT == 2 )); then\n");
+    out.push_str(
+        "        _describe 'subcommand' subcommands\n        _files\n        return\n    fi\n",
+    );
+    out.push_str("    case \"$words[2]\" in\n");
+    for s in SUBCOMMANDS {
+        if let Some(flags) = s.flags {
+            writeln!(
+                out,
+                "        {}) compadd -- {} --help; _files ;;",
+                s.name,
+                flags.join(" ")
+            )
+            .unwrap();
+        }
+    }
+    out.push_str(
+        "        *) _arguments -s \"${run_flags[@]}\" '*:file:_files' ;;\n    esac\n}\n\n",
+    );
+    out.push_str("_teriyaki \"$@\"\n");
+    out
+}
+
+fn fish() -> String {
+    let mut out = String::from("# fish completion for teriyaki\ncomplete -c teriyaki -f\n");
+    let names: Vec<&str> = SUBCOMMANDS.iter().map(|s| s.name).collect();
+    let with_own_flags: Vec<&str> = SUBCOMMANDS
+        .iter()
+        .filter(|s| s.flags.is_some())
+        .map(|s| s.name)
+        .collect();
+    for s in SUBCOMMANDS {
+        writeln!(
+            out,
+            "complete -c teriyaki -n 'not __fish_seen_subcommand_from {}' -a {} -d '{}'",
+            names.join(" "),
+            s.name,
+            s.about
+        )
+        .unwrap();
+    }
+    for s in SUBCOMMANDS {
+        for flag in s.flags.unwrap_or_default() {
+            let arg = match flag.strip_prefix("--") {
+                Some(long) => format!("-l {}", long),
+                None => format!("-a {}", flag),
+            };
+            writeln!(
+                out,
+                "complete -c teriyaki -n '__fish_seen_subcommand_from {}' {}",
+                s.name, arg
+            )
+            .unwrap();
+        }
+    }
+    for f in RUN_FLAGS {
+        let value = match (choices(f), f.value) {
+            (Some(values), _) => format!(" -x -a '{}'", values),
+            (None, Some(_)) => " -r".to_string(),
+            (None, None) => String::new(),
+        };
+        writeln!(
+            out,
+            "complete -c teriyaki -n 'not __fish_seen_subcommand_from {}' -l {}{} -d '{}'",
+            with_own_flags.join(" "),
+            f.name.trim_start_matches('-'),
+            value,
+            f.about
+        )
+        .unwrap();
+    }
+    out.push_str("complete -c teriyaki -l help -s h -d 'Prints help'\n");
+    out.push_str("complete -c teriyaki -F -n 'not string match -q -- \"-*\" (commandline -ct)'\n");
+    out
+}
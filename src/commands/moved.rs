@@ -0,0 +1,111 @@
+use super::{print_report, Format};
+use crate::models::meta::Meta;
+use crate::parser::dataset::read_dict;
+use crate::parser::meta::read_meta;
+use serde::Serialize;
+use std::fmt;
+use std::path::Path;
+
+/// A node whose supernode differs between two meta snapshots.
+#[derive(Serialize)]
+pub struct Move {
+    pub node: u32,
+    /// Supernode in the old snapshot, if the node had one.
+    pub old: Option<u32>,
+    pub new: Option<u32>,
+    /// IRIs of the node and its old and new supernode, when decoded.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub node_term: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub old_term: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub new_term: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct MovedReport {
+    /// Nodes in both snapshots.
+    pub nodes: usize,
+    pub moved: Vec<Move>,
+}
+
+impl fmt::Display for MovedReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let show = |id: Option<u32>, term: &Option<String>| match (id, term) {
+            (Some(id), Some(term)) => format!("{} {}", id, term),
+            (Some(id), None) => id.to_string(),
+            (None, _) => String::from("-"),
+        };
+        for m in &self.moved {
+            writeln!(
+                f,
+                "{}: {} -> {}",
+                show(Some(m.node), &m.node_term),
+                show(m.old, &m.old_term),
+                show(m.new, &m.new_term)
+            )?;
+        }
+        writeln!(f, "{} of {} nodes moved", self.moved.len(), self.nodes)
+    }
+}
+
+/// Prints the nodes whose supernode differs between the meta folders `old` and `new`, with
+/// their IRIs if `decode` is set.
+pub fn run(old: &Path, new: &Path, decode: bool, format: &Format) {
+    let old_meta = read_meta(old).unwrap();
+    let new_meta = read_meta(new).unwrap();
+    let dicts = if decode {
+        Some((read_dict(old).unwrap(), read_dict(new).unwrap()))
+    } else {
+        None
+    };
+    let dicts = dicts.as_ref().map(|(o, n)| (o.as_slice(), n.as_slice()));
+    print_report(&moved(&old_meta, &new_meta, dicts), format);
+}
+
+/// Returns the nodes of both `old` and `new` whose supernode differs, by id, decoded with the
+/// old and new dict lines of `dicts` if given.
+///
+/// Nodes keep their ids across the snapshots of a meta folder, but supernodes get a new id on
+/// every merge, so members of a supernode that only gained members are listed as well.
+pub fn moved(old: &Meta, new: &Meta, dicts: Option<(&[String], &[String])>) -> MovedReport {
+    let term =
+        |lines: &[String], id: u32| match (id as usize).checked_sub(1).and_then(|i| lines.get(i)) {
+            Some(t) if !t.is_empty() => t.clone(),
+            _ => String::from("?"),
+        };
+
+    let mut nodes: Vec<u32> = old
+        .get_nodes()
+        .keys()
+        .filter(|n| new.get_node(n).is_some())
+        .copied()
+        .collect();
+    nodes.sort_unstable();
+
+    let mut moves = Vec::new();
+    for node in &nodes {
+        let (from, to) = (old.get_parent(node), new.get_parent(node));
+        if from == to {
+            continue;
+        }
+        let mut m = Move {
+            node: *node,
+            old: from,
+            new: to,
+            node_term: None,
+            old_term: None,
+            new_term: None,
+        };
+        if let Some((old_lines, new_lines)) = dicts {
+            m.node_term = Some(term(new_lines, *node));
+            m.old_term = from.map(|s| term(old_lines, s));
+            m.new_term = to.map(|s| term(new_lines, s));
+        }
+        moves.push(m);
+    }
+    MovedReport {
+        nodes: nodes.len(),
+        moved: moves,
+    }
+}
@@ -0,0 +1,139 @@
+use super::diff_datasets::SortedLines;
+use super::print_report;
+use crate::rdf;
+use crate::util::exit::Failure;
+use crate::util::rng::fnv1a;
+use crate::Cli;
+use serde::Serialize;
+use std::fmt;
+use std::fs::{self, File};
+use std::io::{BufWriter, Write};
+use std::path::PathBuf;
+
+/// Name of the index written next to the prepared files.
+pub const INDEX_FILE: &str = "index.json";
+
+/// Settings of `teriyaki prepare`.
+pub struct PrepareOptions {
+    pub input: PathBuf,
+    /// Folder the prepared files and their index are written to.
+    pub out: PathBuf,
+    /// Number of lines sorted in memory at a time; longer inputs are sorted in runs on disk.
+    pub chunk_lines: usize,
+    /// Number of files the triples are split over by subject, or `None` for a single file.
+    pub shards: Option<usize>,
+}
+
+/// A file written by `prepare`, as listed in the index.
+#[derive(Serialize)]
+pub struct PreparedFile {
+    /// Path relative to the output folder.
+    pub path: String,
+    pub triples: usize,
+    pub subjects: usize,
+    /// Smallest and largest subject of the file, if it has triples.
+    pub first: Option<String>,
+    pub last: Option<String>,
+}
+
+/// The index of a prepared folder, which is also what `prepare` reports.
+#[derive(Serialize)]
+pub struct PrepareReport {
+    pub input: String,
+    /// Order of the lines of every file; the triples of a subject are always next to each other.
+    pub sorted_by: &'static str,
+    pub triples: usize,
+    /// Repeated triples left out.
+    pub duplicates: usize,
+    pub files: Vec<PreparedFile>,
+}
+
+impl fmt::Display for PrepareReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for file in &self.files {
+            writeln!(
+                f,
+                "{}: {} triples of {} subjects",
+                file.path, file.triples, file.subjects
+            )?;
+        }
+        writeln!(
+            f,
+            "{} triples, {} duplicates left out",
+            self.triples, self.duplicates
+        )
+    }
+}
+
+/// Sorts, deduplicates and optionally shards a dataset, and prints its index.
+pub fn run(cli: &Cli, options: &PrepareOptions) {
+    match prepare(options) {
+        Ok(report) => print_report(&report, &cli.format),
+        Err(err) => cli.fail(
+            Failure::of(&err),
+            &format!("Problem preparing dataset: {}", err),
+        ),
+    }
+}
+
+/// Writes the distinct triples of `input` in sorted order to the folder `out`, as `dataset.nt`
+/// or as `shard-<i>.nt` files, with an index of the files in `index.json`.
+///
+/// Lines are sorted as a whole, which keeps the triples of a subject together since a subject
+/// ends at the first space. Shards are picked by a hash of the subject, so every subject is in
+/// exactly one shard and each shard is sorted as well. The input is sorted in runs of
+/// `chunk_lines` lines spilled to `out`, so it need not fit in memory. Blank lines and comments
+/// are left out.
+pub fn prepare(options: &PrepareOptions) -> std::io::Result<PrepareReport> {
+    fs::create_dir_all(&options.out)?;
+    let mut lines = SortedLines::new(&options.input, options.chunk_lines, &options.out)?;
+
+    let names: Vec<String> = match options.shards {
+        None => vec![String::from("dataset.nt")],
+        Some(n) => (0..n).map(|i| format!("shard-{}.nt", i)).collect(),
+    };
+    let mut writers = Vec::with_capacity(names.len());
+    let mut files = Vec::with_capacity(names.len());
+    for name in names {
+        writers.push(BufWriter::new(File::create(options.out.join(&name))?));
+        files.push(PreparedFile {
+            path: name,
+            triples: 0,
+            subjects: 0,
+            first: None,
+            last: None,
+        });
+    }
+
+    let mut triples = 0;
+    for line in &mut lines {
+        let line = line?;
+        let subject = rdf::split_terms(&line)[0];
+        // The low bits of FNV-1a barely depend on the text, so the high ones pick the shard.
+        let i = ((fnv1a(subject) >> 32) % files.len() as u64) as usize;
+        writeln!(writers[i], "{}", line)?;
+
+        let file = &mut files[i];
+        if file.last.as_deref() != Some(subject) {
+            file.subjects += 1;
+            file.first.get_or_insert_with(|| subject.to_string());
+            file.last = Some(subject.to_string());
+        }
+        file.triples += 1;
+        triples += 1;
+    }
+    for mut writer in writers {
+        writer.flush()?;
+    }
+
+    let report = PrepareReport {
+        input: options.input.display().to_string(),
+        sorted_by: "subject",
+        triples,
+        duplicates: lines.read - triples,
+        files,
+    };
+    let index = serde_json::to_string_pretty(&report).map_err(std::io::Error::from)?;
+    fs::write(options.out.join(INDEX_FILE), index + "\n")?;
+    Ok(report)
+}
@@ -0,0 +1,129 @@
+use super::dump_meta::Terms;
+use super::print_report;
+use crate::models::graph::Graph;
+use crate::{parser, Cli};
+use serde::Serialize;
+use std::fmt;
+use std::process;
+
+/// Whether one supernode of the summary reaches another.
+#[derive(Serialize)]
+pub struct Reachability {
+    pub from: String,
+    pub to: String,
+    /// Largest number of edges a path may have, or `None` for any number.
+    pub max_hops: Option<usize>,
+    /// Edges of a shortest path, or `None` if there is no path.
+    pub path: Option<Vec<Hop>>,
+}
+
+/// An edge of the summary graph on a path.
+#[derive(Serialize)]
+pub struct Hop {
+    pub from: String,
+    pub pred: String,
+    pub to: String,
+}
+
+/// Prints whether the supernode of `from` reaches the supernode of `to` in the summary graph
+/// of a meta folder, over at most `max_hops` edges.
+///
+/// `from` and `to` are ids, aliases or IRIs of supernodes or of nodes, which stand for their
+/// supernode if they have one.
+pub fn run(cli: &Cli, from: &str, to: &str, max_hops: Option<usize>) {
+    let meta = parser::meta::read_compact_meta(&cli.meta_folder_path).unwrap();
+    let dict = parser::dataset::read_dict(&cli.meta_folder_path).unwrap();
+    let preds = parser::dataset::read_preds(&cli.meta_folder_path, &dict).unwrap();
+    let terms = Terms {
+        dict: &dict,
+        preds: &preds,
+    };
+
+    let find = |s: &str| {
+        meta.supernode_by_alias(s)
+            .or_else(|| terms.find(s))
+            .filter(|id| meta.contains(id) || meta.contains_supernode(id))
+    };
+    match (find(from), find(to)) {
+        (Some(a), Some(b)) => {
+            let representative = |n: u32| meta.get_parent(&n).unwrap_or(n);
+            let report = reach(
+                &meta.summary_graph(),
+                representative(a),
+                representative(b),
+                max_hops,
+                |id| terms.term(*id).term,
+                |p| terms.pred(p),
+            );
+            print_report(&report, &cli.format);
+        }
+        (None, _) => exit(&format!("{} is not a node or supernode", from)),
+        (_, None) => exit(&format!("{} is not a node or supernode", to)),
+    }
+}
+
+fn exit(message: &str) -> ! {
+    println!("{}", message);
+    process::exit(1);
+}
+
+/// Looks for a shortest path from `from` to `to` in the summary `graph`, over at most
+/// `max_hops` edges. Both are supernodes or nodes without a parent.
+///
+/// Terms are named with `term` and predicates with `pred`.
+pub fn reach(
+    graph: &Graph,
+    from: u32,
+    to: u32,
+    max_hops: Option<usize>,
+    term: impl Fn(&u32) -> String,
+    pred: impl Fn(&u32) -> String,
+) -> Reachability {
+    let path = graph.reachable(from, to, max_hops.unwrap_or(usize::MAX));
+
+    Reachability {
+        from: term(&from),
+        to: term(&to),
+        max_hops,
+        path: path.map(|edges| {
+            edges
+                .iter()
+                .map(|[s, p, o]| Hop {
+                    from: term(s),
+                    pred: pred(p),
+                    to: term(o),
+                })
+                .collect()
+        }),
+    }
+}
+
+impl fmt::Display for Reachability {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match &self.path {
+            Some(path) => {
+                let hops = if path.len() == 1 { "hop" } else { "hops" };
+                writeln!(
+                    f,
+                    "{} reaches {} in {} {}",
+                    self.from,
+                    self.to,
+                    path.len(),
+                    hops
+                )?;
+                for hop in path {
+                    writeln!(f, "  {} {} {}", hop.from, hop.pred, hop.to)?;
+                }
+                Ok(())
+            }
+            None => match self.max_hops {
+                Some(max) => writeln!(
+                    f,
+                    "{} does not reach {} within {} hops",
+                    self.from, self.to, max
+                ),
+                None => writeln!(f, "{} does not reach {}", self.from, self.to),
+            },
+        }
+    }
+}
@@ -0,0 +1,353 @@
+use super::{explain, print_report, reach, stats};
+use crate::summarizer::Summarizer;
+use crate::updater::{Options, UpdateReport};
+use crate::util::exit::{Failed, Failure};
+use crate::util::lock::Lock;
+use crate::util::wal::{self, Wal};
+use crate::{as_iri, load_input, lock, parser, rdf, updater, writer, Cli};
+use std::collections::BTreeSet;
+use std::io::{self, BufRead, Write};
+
+const HELP: &str = "\
+lookup <iri>          show the id and supernode of a node
+members <snode>       list the members of a supernode
+preds <node>          list the outgoing and incoming predicates of a node
+stats                 print statistics about the summary
+drift                 print how far the summary has drifted from a re-clustering
+apply [-]<triple>     insert, or with '-' delete, a triple in memory
+explain <a> <b>       explain why two nodes are or are not merged
+reach <a> <b> [hops]  find a shortest path between the supernodes of two nodes
+snapshot              write the summary and meta folder, and rotate the write-ahead log
+help                  show this help
+quit                  leave the repl";
+
+struct Session<'a> {
+    cli: &'a Cli,
+    summary: Summarizer,
+    /// Totals of the update and the changes applied since.
+    update: UpdateReport,
+    /// Log of the changes applied since the meta folder was written, with `--wal`.
+    wal: Option<Wal>,
+    /// Held for the whole session with `--wal`, as the log is part of the meta folder.
+    lock: Option<Lock>,
+}
+
+/// Applies the update in memory and reads commands querying the summary from stdin.
+///
+/// Nothing is written back to the summary or meta until asked with `snapshot`. With `--wal`,
+/// the update and each change are logged before they are applied and acknowledged, and the log
+/// of an earlier session that ended without a snapshot is replayed when the update is empty.
+pub fn run(cli: &Cli) {
+    let mut session = Session::start(cli).unwrap_or_else(|failed| cli.exit(failed));
+
+    let stdin = io::stdin();
+    loop {
+        print!("> ");
+        io::stdout().flush().unwrap();
+
+        let mut line = String::new();
+        if stdin.lock().read_line(&mut line).unwrap() == 0 {
+            println!();
+            break;
+        }
+
+        let line = line.trim();
+        let (cmd, rest) = line.split_once(' ').unwrap_or((line, ""));
+        let rest = rest.trim();
+        let args: Vec<&str> = rest.split_whitespace().collect();
+        match (cmd, args.as_slice()) {
+            ("", _) => {}
+            ("quit" | "exit", _) => break,
+            ("help", _) => println!("{}", HELP),
+            ("lookup", [iri]) => session.lookup(&as_iri(iri)),
+            ("members", [snode]) => session.members(&as_iri(snode)),
+            ("preds", [node]) => session.preds(&as_iri(node)),
+            ("stats", []) => session.stats(),
+            ("drift", []) => session.drift(),
+            ("apply", [_, ..]) => session.apply(rest),
+            ("snapshot", []) => session.snapshot(),
+            ("explain", [a, b]) => session.explain(&as_iri(a), &as_iri(b)),
+            ("reach", [a, b]) => session.reach(&as_iri(a), &as_iri(b), None),
+            ("reach", [a, b, hops]) => match hops.parse::<usize>() {
+                Ok(hops) => session.reach(&as_iri(a), &as_iri(b), Some(hops)),
+                Err(_) => println!("expected a number of hops"),
+            },
+            _ => println!("unknown command, try help"),
+        }
+    }
+}
+
+impl<'a> Session<'a> {
+    /// Reads the input and applies the update. On failure, the lock taken for `--wal` is
+    /// released before returning.
+    fn start(cli: &'a Cli) -> Result<Self, Failed> {
+        let lock = cli.wal.then(|| lock(cli)).transpose()?;
+        // A log with changes is replayed in place of the update, which is already in it.
+        let replayed = cli.wal && !read_wal(cli)?.is_empty();
+        let (mut dataset, mut meta, mut sc, mut tc) = load_input(cli)?;
+        let update = updater::run(
+            &mut dataset,
+            &mut meta,
+            &mut sc,
+            &mut tc,
+            &cli.update_options(),
+        )
+        .map_err(|err| {
+            Failed::new(
+                Failure::Conflict,
+                format!("Problem applying update: {}", err),
+            )
+        })?;
+
+        // The time budget only limits the update; changes applied in the repl are single triples.
+        let options = Options {
+            deadline: None,
+            ..cli.update_options()
+        };
+        let mut summary = Summarizer::new(dataset, meta, sc, tc, cli.grouping, options);
+        summary.set_dedup_window(cli.dedup_window);
+        summary.set_drift_thresholds(cli.drift);
+        let wal = cli
+            .wal
+            .then(|| {
+                let mut log = Wal::open(&cli.wal_path())?;
+                if !replayed {
+                    log.append_all(&parser::dataset::read_update_lines(
+                        &cli.update_path,
+                        cli.line_limit,
+                    )?)?;
+                }
+                Ok(log)
+            })
+            .transpose()
+            .map_err(|err: io::Error| {
+                let message = format!("Problem opening write-ahead log: {}", err);
+                Failed::new(Failure::of(&err), message)
+            })?;
+        Ok(Session {
+            cli,
+            summary,
+            update,
+            wal,
+            lock,
+        })
+    }
+}
+
+/// Returns the changes in the log of `cli`.
+fn read_wal(cli: &Cli) -> Result<Vec<String>, Failed> {
+    wal::read(&cli.wal_path()).map_err(|err| {
+        let message = format!("Problem reading write-ahead log: {}", err);
+        Failed::new(Failure::of(&err), message)
+    })
+}
+
+impl Session<'_> {
+    fn lookup(&self, iri: &str) {
+        match self.summary.dataset.id_of(iri) {
+            Some(id) if self.summary.meta.contains_supernode(&id) => println!(
+                "{} is supernode {} (alias {}) with {} members",
+                iri,
+                id,
+                self.summary
+                    .meta
+                    .get_alias(&id)
+                    .map_or("none", |a| a.as_str()),
+                self.summary.meta.supernode_len(&id)
+            ),
+            Some(id) if self.summary.meta.contains(&id) => {
+                match self.summary.meta.get_parent(&id) {
+                    Some(p) => println!("{} is node {} in supernode {}", iri, id, self.name(&p)),
+                    None => println!("{} is node {}", iri, id),
+                }
+            }
+            Some(id) => println!("{} is term {}, but not a node", iri, id),
+            None => println!("unknown term {}", iri),
+        }
+    }
+
+    fn members(&self, iri: &str) {
+        let members = self
+            .summary
+            .dataset
+            .id_of(iri)
+            .and_then(|id| self.summary.meta.get_supernode(&id));
+        match members {
+            Some(members) => {
+                for m in members {
+                    println!("{}", self.name(m));
+                }
+            }
+            None => println!("{} is not a supernode", iri),
+        }
+    }
+
+    fn preds(&self, iri: &str) {
+        let id = match self.summary.dataset.id_of(iri) {
+            Some(id) if self.summary.meta.contains(&id) => id,
+            _ => return println!("unknown node {}", iri),
+        };
+        let nodes = match self.summary.meta.get_supernode(&id) {
+            Some(members) => members.clone(),
+            None => vec![id],
+        };
+
+        for (label, is_source) in [("out", true), ("in", false)] {
+            let preds: BTreeSet<u32> = nodes
+                .iter()
+                .flat_map(|n| self.summary.meta.get_preds(n, is_source))
+                .collect();
+            let names: Vec<String> = preds
+                .iter()
+                .map(|p| explain::pred_name(&self.summary.dataset, p))
+                .collect();
+            println!("{}: {}", label, names.join(" "));
+        }
+    }
+
+    fn stats(&self) {
+        let report = stats::report(
+            self.cli,
+            &self.summary.dataset,
+            &self.summary.meta,
+            &self.summary.sc,
+            &self.summary.tc,
+            &self.update,
+        );
+        print_report(&report, &self.cli.format);
+    }
+
+    fn drift(&mut self) {
+        let report = self.summary.drift();
+        print_report(&report, &self.cli.format);
+    }
+
+    fn apply(&mut self, line: &str) {
+        let (triple, delete) = match line.strip_prefix('-') {
+            Some(triple) => (triple, true),
+            None => (line, false),
+        };
+        let triple = triple.strip_suffix(" .").unwrap_or(triple);
+        let mut terms = triple.splitn(3, ' ');
+        let (s, p, o) = match (terms.next(), terms.next(), terms.next()) {
+            (Some(s), Some(p), Some(o)) => (s, p, o),
+            _ => return println!("expected a triple in N-Triples notation"),
+        };
+
+        // Repeats are skipped by the summary, and left out of the log so a replay skips them too.
+        let repeats = self.summary.repeats(s, p, o, delete);
+        if let Some(wal) = self.wal.as_mut().filter(|_| !repeats) {
+            let triple = format!("{} {} {} .", s, p, o);
+            if let Some(reason) = rdf::check_line(&triple) {
+                return println!("malformed triple: {}", reason);
+            }
+            let change = if delete {
+                format!("-{}", triple)
+            } else {
+                triple
+            };
+            if let Err(err) = wal.append(&change) {
+                return println!("Problem writing write-ahead log: {}", err);
+            }
+        }
+
+        let result = if delete {
+            self.summary.delete(s, p, o)
+        } else {
+            self.summary.add(s, p, o)
+        };
+        match result {
+            Ok(changes) => {
+                print!("{}", changes);
+                if changes.rejected {
+                    self.update.rejected_deletions.push(line.to_string());
+                }
+                if changes.duplicate {
+                    self.update.duplicate_insertions += 1;
+                }
+                println!(
+                    "summary has {} triples and {} supernodes",
+                    self.summary.dataset.triples.data_triples.len(),
+                    self.summary.meta.get_supernodes().len()
+                );
+            }
+            Err(err) => println!("Problem applying update: {}", err),
+        }
+    }
+
+    /// Writes the summary and meta folder as a run would, then rotates the log, whose changes
+    /// the meta folder now holds.
+    fn snapshot(&mut self) {
+        // Without `--wal`, the meta folder is only locked while it is written.
+        let _lock = match self.lock {
+            Some(_) => None,
+            None => match Lock::acquire(&self.cli.lock_path()) {
+                Ok(lock) => Some(lock),
+                Err(err) => return println!("Problem locking meta folder: {}", err),
+            },
+        };
+        let summary = &self.summary;
+        writer::run(
+            self.cli,
+            &summary.dataset,
+            &summary.meta,
+            &summary.sc,
+            &summary.tc,
+        );
+        if self.wal.is_some() {
+            let path = self.cli.wal_path();
+            match wal::rotate(&path).and_then(|_| Wal::open(&path)) {
+                Ok(log) => self.wal = Some(log),
+                Err(err) => return println!("Problem rotating write-ahead log: {}", err),
+            }
+        }
+        println!("wrote {}", self.cli.summary_path().display());
+    }
+
+    fn explain(&self, a: &str, b: &str) {
+        match explain::explain(
+            &self.summary.dataset,
+            &self.summary.meta,
+            &self.summary.sc,
+            &self.summary.tc,
+            a,
+            b,
+        ) {
+            Ok(explanation) => print_report(&explanation, &self.cli.format),
+            Err(err) => println!("{}", err),
+        }
+    }
+
+    fn reach(&self, a: &str, b: &str, max_hops: Option<usize>) {
+        let meta = &self.summary.meta;
+        let find = |iri: &str| {
+            self.summary
+                .dataset
+                .id_of(iri)
+                .filter(|id| meta.contains(id) || meta.contains_supernode(id))
+        };
+        match (find(a), find(b)) {
+            (Some(a), Some(b)) => {
+                let representative = |n: u32| meta.get_node(&n).and_then(|i| i.parent).unwrap_or(n);
+                let report = reach::reach(
+                    &meta.summary_graph(),
+                    representative(a),
+                    representative(b),
+                    max_hops,
+                    |id| self.name(id),
+                    |p| explain::pred_name(&self.summary.dataset, p),
+                );
+                print_report(&report, &self.cli.format);
+            }
+            (None, _) => println!("unknown node {}", a),
+            (_, None) => println!("unknown node {}", b),
+        }
+    }
+
+    fn name(&self, id: &u32) -> String {
+        self.summary
+            .dataset
+            .key_by_value(id)
+            .unwrap_or_else(|| id.to_string())
+    }
+}
@@ -0,0 +1,292 @@
+use super::{explain, print_report};
+use crate::metrics::cache::CacheStats;
+use crate::metrics::centrality;
+use crate::metrics::compression;
+use crate::metrics::timing::UpdateTimings;
+use crate::models::clique::CliqueCollection;
+use crate::models::dataset::Dataset;
+use crate::models::dict::MAX_ID;
+use crate::models::graph::Graph;
+use crate::models::meta::Meta;
+use crate::updater::UpdateReport;
+use crate::util::exit::Failure;
+use crate::{read_input, updater, Cli};
+use serde::Serialize;
+use std::fmt;
+
+/// Number of nodes listed in the text output of the centrality report.
+const TOP_NODES: usize = 10;
+
+/// Number of cliques listed in the text output of the clique report.
+const TOP_CLIQUES: usize = 10;
+
+#[derive(Serialize)]
+pub struct StatsReport {
+    pub summary_triples: usize,
+    pub type_triples: usize,
+    pub nodes: usize,
+    pub supernodes: usize,
+    pub dict_entries: usize,
+    /// Predicates, which have their own ids next to the dict entries.
+    pub preds: usize,
+    /// Largest dict id in use, out of `dict::MAX_ID`.
+    pub max_id: u32,
+    pub source_cliques: usize,
+    pub target_cliques: usize,
+    pub insertions: usize,
+    pub deletions: usize,
+    pub duplicate_triples: usize,
+    pub duplicate_insertions: usize,
+    pub coalesced: usize,
+    pub reclustered: usize,
+    pub rejected_deletions: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub centrality: Option<CentralityReport>,
+    /// Compression of each clique, the cliques that save the most triples first.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cliques: Option<Vec<CliqueStats>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timings: Option<UpdateTimings>,
+    /// Lookups of decoded terms in the cache of the dict, if it has one.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub term_cache: Option<CacheStats>,
+}
+
+#[derive(Serialize)]
+pub struct CentralityReport {
+    pub average_degree: f64,
+    pub max_in_degree: usize,
+    pub max_out_degree: usize,
+    /// All nodes of the summary graph, most central first.
+    pub nodes: Vec<NodeCentrality>,
+}
+
+#[derive(Serialize)]
+pub struct CliqueStats {
+    /// `source` or `target`.
+    pub direction: &'static str,
+    pub preds: Vec<String>,
+    pub nodes: usize,
+    pub original_triples: usize,
+    pub summary_triples: usize,
+    /// Original triples the summary saves on this clique.
+    pub saved: usize,
+    /// Summary triples per original triple.
+    pub ratio: f64,
+}
+
+#[derive(Serialize)]
+pub struct NodeCentrality {
+    pub node: String,
+    pub in_degree: usize,
+    pub out_degree: usize,
+    pub degree: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pagerank: Option<f64>,
+}
+
+impl fmt::Display for StatsReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "summary triples: {}", self.summary_triples)?;
+        writeln!(f, "type triples:    {}", self.type_triples)?;
+        writeln!(f, "nodes:           {}", self.nodes)?;
+        writeln!(f, "supernodes:      {}", self.supernodes)?;
+        writeln!(f, "dict entries:    {}", self.dict_entries)?;
+        writeln!(f, "preds:           {}", self.preds)?;
+        writeln!(
+            f,
+            "max id:          {} ({:.2}% of {})",
+            self.max_id,
+            100.0 * self.max_id as f64 / MAX_ID as f64,
+            MAX_ID
+        )?;
+        writeln!(f, "source cliques:  {}", self.source_cliques)?;
+        writeln!(f, "target cliques:  {}", self.target_cliques)?;
+        writeln!(f, "insertions:      {}", self.insertions)?;
+        writeln!(f, "deletions:       {}", self.deletions)?;
+        writeln!(f, "duplicates:      {}", self.duplicate_triples)?;
+        writeln!(f, "dup. insertions: {}", self.duplicate_insertions)?;
+        writeln!(f, "coalesced:       {}", self.coalesced)?;
+        writeln!(f, "reclustered:     {}", self.reclustered)?;
+        writeln!(f, "rejected:        {}", self.rejected_deletions)?;
+        if let Some(c) = &self.centrality {
+            write!(f, "{}", c)?;
+        }
+        if let Some(cliques) = &self.cliques {
+            writeln!(f, "cliques by saved triples:")?;
+            for c in cliques.iter().take(TOP_CLIQUES) {
+                writeln!(
+                    f,
+                    "  {} saved={} original={} summary={} ratio={:.2} nodes={} preds={}",
+                    c.direction,
+                    c.saved,
+                    c.original_triples,
+                    c.summary_triples,
+                    c.ratio,
+                    c.nodes,
+                    c.preds.join(" ")
+                )?;
+            }
+        }
+        if let Some(t) = &self.timings {
+            write!(f, "{}", t)?;
+        }
+        if let Some(c) = &self.term_cache {
+            writeln!(f, "term cache:      {}", c)?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for CentralityReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "average degree:  {:.2}", self.average_degree)?;
+        writeln!(f, "max in-degree:   {}", self.max_in_degree)?;
+        writeln!(f, "max out-degree:  {}", self.max_out_degree)?;
+        for n in self.nodes.iter().take(TOP_NODES) {
+            write!(f, "  {} in={} out={}", n.node, n.in_degree, n.out_degree)?;
+            match n.pagerank {
+                Some(rank) => writeln!(f, " pagerank={:.6}", rank)?,
+                None => writeln!(f)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Applies the update in memory and prints statistics about the resulting summary.
+pub fn run(cli: &Cli) {
+    let (mut dataset, mut meta, mut sc, mut tc) = read_input(cli);
+    let update = match updater::run(
+        &mut dataset,
+        &mut meta,
+        &mut sc,
+        &mut tc,
+        &cli.update_options(),
+    ) {
+        Ok(update) => update,
+        Err(err) => {
+            let message = format!("Problem applying update: {}", err);
+            cli.fail(Failure::Conflict, &message)
+        }
+    };
+
+    print_report(
+        &report(cli, &dataset, &meta, &sc, &tc, &update),
+        &cli.format,
+    );
+}
+
+/// Collects statistics about the summary after `update` was applied.
+pub fn report(
+    cli: &Cli,
+    dataset: &Dataset,
+    meta: &Meta,
+    sc: &CliqueCollection,
+    tc: &CliqueCollection,
+    update: &UpdateReport,
+) -> StatsReport {
+    StatsReport {
+        summary_triples: dataset.triples.data_triples.len(),
+        type_triples: meta.get_types().len(),
+        nodes: meta.node_count(),
+        supernodes: meta.get_supernodes().len(),
+        dict_entries: dataset.dict_len(),
+        preds: dataset.pred_count(),
+        max_id: dataset.max_id(),
+        source_cliques: sc.clique_count(),
+        target_cliques: tc.clique_count(),
+        insertions: dataset.insertions.data_triples.len(),
+        deletions: dataset.deletions.data_triples.len(),
+        duplicate_triples: dataset.triples.duplicates,
+        duplicate_insertions: update.duplicate_insertions,
+        coalesced: update.coalesced,
+        reclustered: update.reclustered,
+        rejected_deletions: update.rejected_deletions.len(),
+        centrality: if cli.centrality || cli.pagerank {
+            Some(centrality_report(
+                &meta.summary_graph(),
+                dataset,
+                cli.pagerank,
+            ))
+        } else {
+            None
+        },
+        cliques: if cli.clique_stats {
+            Some(clique_stats(dataset, meta, sc, tc))
+        } else {
+            None
+        },
+        timings: if cli.timings {
+            Some(update.timings.clone())
+        } else {
+            None
+        },
+        // Last, so the terms decoded for the fields above are counted.
+        term_cache: (cli.term_cache > 0).then(|| dataset.term_cache_stats()),
+    }
+}
+
+fn clique_stats(
+    dataset: &Dataset,
+    meta: &Meta,
+    sc: &CliqueCollection,
+    tc: &CliqueCollection,
+) -> Vec<CliqueStats> {
+    compression::clique_compression(sc, tc, meta, &dataset.triples)
+        .iter()
+        .map(|c| CliqueStats {
+            direction: if c.is_source { "source" } else { "target" },
+            preds: c
+                .preds
+                .iter()
+                .map(|p| explain::pred_name(dataset, p))
+                .collect(),
+            nodes: c.nodes,
+            original_triples: c.original_triples,
+            summary_triples: c.summary_triples,
+            saved: c.saved(),
+            ratio: c.ratio(),
+        })
+        .collect()
+}
+
+fn centrality_report(graph: &Graph, dataset: &Dataset, with_pagerank: bool) -> CentralityReport {
+    let degrees = centrality::degrees(graph);
+    let ranks = if with_pagerank {
+        Some(centrality::pagerank(graph))
+    } else {
+        None
+    };
+    let dict = dataset.dict_strings();
+
+    let mut nodes: Vec<NodeCentrality> = degrees
+        .iter()
+        .map(|(n, d)| NodeCentrality {
+            node: dict[*n as usize - 1].to_string(),
+            in_degree: d.incoming,
+            out_degree: d.outgoing,
+            degree: d.total(),
+            pagerank: ranks.as_ref().map(|r| r[n]),
+        })
+        .collect();
+    nodes.sort_by(|a, b| {
+        b.pagerank
+            .partial_cmp(&a.pagerank)
+            .unwrap()
+            .then(b.degree.cmp(&a.degree))
+            .then(a.node.cmp(&b.node))
+    });
+
+    let total: usize = degrees.values().map(|d| d.total()).sum();
+    CentralityReport {
+        average_degree: if degrees.is_empty() {
+            0.0
+        } else {
+            total as f64 / degrees.len() as f64
+        },
+        max_in_degree: degrees.values().map(|d| d.incoming).max().unwrap_or(0),
+        max_out_degree: degrees.values().map(|d| d.outgoing).max().unwrap_or(0),
+        nodes,
+    }
+}
@@ -0,0 +1,128 @@
+use super::print_report;
+use crate::parser;
+use crate::util::exit::Failure;
+use crate::{rdf, Cli};
+use serde::Serialize;
+use std::fmt;
+use std::process;
+
+#[derive(Serialize)]
+pub struct ValidateReport {
+    pub valid: bool,
+    pub dataset_lines: usize,
+    pub update_lines: usize,
+    pub problems: Vec<Problem>,
+}
+
+#[derive(Serialize)]
+pub struct Problem {
+    pub file: String,
+    /// 1-based line number, or 0 if the problem concerns the whole file.
+    pub line: usize,
+    pub message: String,
+}
+
+impl fmt::Display for ValidateReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for p in &self.problems {
+            writeln!(f, "{}:{}: {}", p.file, p.line, p.message)?;
+        }
+        writeln!(
+            f,
+            "checked {} dataset lines and {} update lines: {}",
+            self.dataset_lines,
+            self.update_lines,
+            if self.valid { "ok" } else { "invalid" }
+        )
+    }
+}
+
+/// Checks that the inputs of a run are well-formed without applying the update.
+///
+/// Exits with a non-zero status if any problem was found.
+pub fn run(cli: &Cli) {
+    let mut problems: Vec<Problem> = Vec::new();
+
+    let dataset_file = cli.dataset_path.display().to_string();
+    let dataset_lines = match parser::dataset::read_dataset_lines(cli)
+        .and_then(|lines| lines.collect::<Result<Vec<String>, _>>())
+    {
+        Ok(lines) => {
+            check_lines(&lines, &dataset_file, false, &mut problems);
+            lines.len()
+        }
+        Err(e) => {
+            problems.push(Problem::file(&dataset_file, e.to_string()));
+            0
+        }
+    };
+
+    let update_file = cli.update_path.display().to_string();
+    let update_lines = match parser::dataset::read_update_lines(&cli.update_path, cli.line_limit) {
+        Ok(lines) => {
+            check_lines(&lines, &update_file, true, &mut problems);
+            lines.len()
+        }
+        Err(e) => {
+            problems.push(Problem::file(&update_file, e.to_string()));
+            0
+        }
+    };
+
+    if !cli.use_fast {
+        let dict_path = cli.meta_folder_path.join("dict");
+        if let Err(e) = parser::dataset::read_dict(&cli.meta_folder_path) {
+            problems.push(Problem::file(
+                &dict_path.display().to_string(),
+                e.to_string(),
+            ));
+        }
+        if let Err(e) = parser::meta::parse_meta(cli) {
+            let meta_path = cli.meta_folder_path.join(parser::meta::META_FILE);
+            problems.push(Problem::file(
+                &meta_path.display().to_string(),
+                e.to_string(),
+            ));
+        }
+    }
+
+    let report = ValidateReport {
+        valid: problems.is_empty(),
+        dataset_lines,
+        update_lines,
+        problems,
+    };
+    print_report(&report, &cli.format);
+
+    if !report.valid {
+        process::exit(Failure::Validation.code());
+    }
+}
+
+impl Problem {
+    fn file(file: &str, message: String) -> Self {
+        Self {
+            file: file.to_string(),
+            line: 0,
+            message,
+        }
+    }
+}
+
+fn check_lines(lines: &[String], file: &str, is_update: bool, problems: &mut Vec<Problem>) {
+    for (i, l) in lines.iter().enumerate() {
+        let l = if is_update {
+            l.strip_prefix('-').unwrap_or(l)
+        } else {
+            l.as_str()
+        };
+
+        if let Some(message) = rdf::check_line(l) {
+            problems.push(Problem {
+                file: file.to_string(),
+                line: i + 1,
+                message: message.to_string(),
+            });
+        }
+    }
+}
@@ -1,22 +1,60 @@
-use std::{env, path::PathBuf, process};
+use commands::Format;
+use std::{
+    env,
+    ops::Deref,
+    path::PathBuf,
+    process,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+use teriyaki_core::metrics::drift::DriftThresholds;
+use teriyaki_core::metrics::memory::MemoryReport;
+use teriyaki_core::models::clique::CliqueCollection;
+use teriyaki_core::models::dataset::Dataset;
+use teriyaki_core::models::dict;
+use teriyaki_core::models::meta::Meta;
+use teriyaki_core::models::node_set;
+use teriyaki_core::parser::exclusion::AutoExclude;
+use teriyaki_core::parser::inverse::InversePreds;
+use teriyaki_core::util::exit::{self, Failed, Failure};
+use teriyaki_core::util::io;
+use teriyaki_core::util::lock::Lock;
+use teriyaki_core::util::profile::span;
+#[cfg(feature = "endpoint")]
+use teriyaki_core::util::source;
+use teriyaki_core::util::wal;
+// The modules of the core, so the subcommands reach them as `crate::parser` and so on.
+use teriyaki_core::{
+    as_iri, metrics, models, parser, rdf, summarizer, updater, util, writer, Coalescing, Config,
+    Directions, DuplicatePolicy, Grouping, SplitStrategy,
+};
 
-mod models;
-mod parser;
-mod updater;
-mod util;
-mod writer;
-// mod tests;
+mod commands;
+#[cfg(test)]
+mod tests;
 
 fn main() {
-    let args: Vec<String> = env::args().collect();
-    let config = Config::new(&args).unwrap_or_else(|err| {
-        println!("Problem parsing arguments: {}", err);
-        process::exit(1);
+    let mut args: Vec<String> = env::args().collect();
+    // Taken out before the other arguments are parsed, so it also applies if they cannot be.
+    let errors_json = args.iter().any(|a| a == "--errors-json");
+    args.retain(|a| a != "--errors-json");
+    let mut cli = Cli::new(&args).unwrap_or_else(|err| {
+        let message = format!("Problem parsing arguments: {}", err);
+        exit::fail(Failure::Arguments, &message, &[], errors_json)
     });
+    cli.errors_json = errors_json;
 
-    let (mut dataset, mut meta, mut sc, mut tc) = parser::run(&config).unwrap();
-    updater::run(&mut dataset, &mut meta, &mut sc, &mut tc);
-    writer::run(&config, &dataset, &meta);
+    if let Err(problems) = cli.validate() {
+        cli.fail_with(
+            Failure::Validation,
+            "Problems with the arguments:",
+            &problems,
+        );
+    }
+
+    run(&cli);
+
+    #[cfg(feature = "profiling")]
+    util::profile::dump();
 
     // println!("SOURCE CLIQUES");
     // util::print::cliques_string(&sc, &stuff.dict);
@@ -29,53 +67,1533 @@ fn main() {
     // // util::print::triples_string(&stuff.triples, &stuff.dict);
 }
 
-pub struct Config {
-    dataset_path: PathBuf,
-    meta_folder_path: PathBuf,
-    update_path: PathBuf,
-    use_fast: bool,
+fn run(cli: &Cli) {
+    span!("main");
+
+    match &cli.command {
+        Command::Summarize | Command::Recluster => {
+            summarize(cli).unwrap_or_else(|failed| cli.exit(failed))
+        }
+        Command::Analyze => commands::analyze::run(cli),
+        Command::Stats => commands::stats::run(cli),
+        Command::Validate => commands::validate::run(cli),
+        Command::DryRun => commands::dry_run::run(cli),
+        Command::Diff(old, new) => commands::diff::run(old, new, &cli.format),
+        Command::DiffDatasets(options) => commands::diff_datasets::run(cli, options),
+        Command::Prepare(options) => commands::prepare::run(cli, options),
+        Command::Evaluate(incremental, fresh) => {
+            commands::evaluate::run(incremental, fresh, &cli.format)
+        }
+        Command::EvaluateQueries(options) => commands::evaluate_queries::run(cli, options),
+        Command::Explain(a, b) => commands::explain::run(cli, a, b),
+        Command::ExportNodes(out) => commands::export_nodes::run(cli, out),
+        Command::ExportTables(out) => commands::export_tables::run(cli, out),
+        Command::Repl => commands::repl::run(cli),
+        Command::Extract(snode, out) => commands::extract::run(cli, snode, out),
+        Command::Dict(command) => commands::dict::run(cli, command),
+        Command::DumpMeta(target) => commands::dump_meta::run(cli, target),
+        Command::Embed(options) => commands::embed::run(cli, options),
+        Command::Reach(from, to, max_hops) => commands::reach::run(cli, from, to, *max_hops),
+        Command::Moved(old, new, decode) => commands::moved::run(old, new, *decode, &cli.format),
+        Command::Fuzz(options) => commands::fuzz::run(&cli.dataset_path, options, &cli.format),
+        Command::BenchCliques(options) => commands::bench_cliques::run(options, &cli.format),
+        Command::BenchEdges(options) => commands::bench_edges::run(options, &cli.format),
+        Command::Completions(shell) => commands::help::run(*shell),
+    }
 }
 
-impl Config {
-    fn new(args: &[String]) -> Result<Config, &'static str> {
+/// Applies the update and writes the summary and meta folder back, holding the lock on the
+/// meta folder until it returns.
+fn summarize(cli: &Cli) -> Result<(), Failed> {
+    let _lock = lock(cli)?;
+    let (mut dataset, mut meta, mut sc, mut tc) = load_input(cli)?;
+    let options = cli.update_options();
+    let report = match cli.chunk_size {
+        0 => updater::run(&mut dataset, &mut meta, &mut sc, &mut tc, &options),
+        size => updater::run_in_chunks(
+            &mut dataset,
+            &mut meta,
+            &mut sc,
+            &mut tc,
+            &options,
+            size,
+            |progress, dataset, meta, sc, tc| {
+                writer::run(cli, dataset, meta, sc, tc);
+                println!("{}", progress);
+            },
+        ),
+    }
+    .map_err(|err| {
+        Failed::new(
+            Failure::Conflict,
+            format!("Problem applying update: {}", err),
+        )
+    })?;
+    writer::run(cli, &dataset, &meta, &sc, &tc);
+    rotate_wal(cli)?;
+    writer::write_rejected(cli, &report.rejected_deletions);
+    writer::write_skipped(cli, &dataset.skipped);
+    writer::write_violations(cli, &dataset.violations);
+    writer::write_pending(cli, &report.pending);
+    writer::write_inconsistent(cli, &report.inconsistent);
+    if let Command::Recluster = cli.command {
+        println!("Re-clustered {} supernodes", report.reclustered);
+    }
+    if cli.timings {
+        print!("{}", report.timings);
+    }
+    if cli.profile_memory {
+        print!("{}", MemoryReport::new(&dataset, &meta, &sc, &tc));
+    }
+    if !report.inconsistent.is_empty() {
+        return Err(Failed {
+            failure: Failure::Inconsistent,
+            message: "Completed, but skipped changes the summary is inconsistent around:"
+                .to_string(),
+            problems: report
+                .inconsistent
+                .iter()
+                .filter_map(|l| l.strip_prefix("# "))
+                .map(String::from)
+                .collect(),
+        });
+    }
+    Ok(())
+}
+
+/// The subcommand selected on the command line.
+#[derive(Default)]
+pub enum Command {
+    /// Applies the update and writes summary and meta (the default).
+    #[default]
+    Summarize,
+    /// Prints statistics about the update without applying it.
+    Analyze,
+    Stats,
+    Validate,
+    /// Like `Summarize`, but also repairs the grouping of the whole summary afterwards.
+    Recluster,
+    DryRun,
+    /// Compares two summary files.
+    Diff(PathBuf, PathBuf),
+    /// Writes the changeset between two dataset dumps.
+    DiffDatasets(commands::diff_datasets::DiffDatasetsOptions),
+    /// Sorts, deduplicates and optionally shards a dataset into a folder with an index.
+    Prepare(commands::prepare::PrepareOptions),
+    /// Scores the grouping of an incremental summary against one built from scratch, given
+    /// their meta folders.
+    Evaluate(PathBuf, PathBuf),
+    /// Scores the answers of triple patterns on a summary against those on its original triples.
+    EvaluateQueries(commands::evaluate_queries::EvaluateQueriesOptions),
+    /// Explains why two nodes are or are not merged.
+    Explain(String, String),
+    /// Writes the features of every node for machine learning, as JSON Lines.
+    ExportNodes(PathBuf),
+    /// Writes the triples, supernode members and summary edges as CSV tables to a folder.
+    ExportTables(PathBuf),
+    /// Queries the summary interactively.
+    Repl,
+    /// Writes the original triples of the members of a supernode.
+    Extract(String, Option<PathBuf>),
+    /// Exports or imports the dict of a meta folder.
+    Dict(commands::dict::DictCommand),
+    /// Prints a readable view of the meta record of a node or supernode.
+    DumpMeta(commands::dump_meta::Target),
+    /// Exports the summary graph for embedding toolkits.
+    Embed(commands::embed::EmbedOptions),
+    /// Finds a shortest path between two supernodes in the summary graph, over at most the
+    /// given number of edges.
+    Reach(String, String, Option<usize>),
+    /// Lists the nodes whose supernode differs between two meta folders, with their IRIs if
+    /// set.
+    Moved(PathBuf, PathBuf, bool),
+    /// Compares incremental and batch summaries of random changesets.
+    Fuzz(commands::fuzz::FuzzOptions),
+    /// Measures the costs of keeping the nodes of cliques as lists and as bitmaps.
+    BenchCliques(commands::bench_cliques::BenchOptions),
+    BenchEdges(commands::bench_edges::BenchOptions),
+    /// Prints a completion script for a shell.
+    Completions(commands::help::Shell),
+}
+
+/// The parsed command line: the subcommand, how it reports, and the settings of the run.
+#[derive(Default)]
+pub struct Cli {
+    command: Command,
+    format: Format,
+    /// Whether failures are printed as JSON, see `exit::fail`.
+    errors_json: bool,
+    config: Config,
+}
+
+impl Deref for Cli {
+    type Target = Config;
+
+    fn deref(&self) -> &Config {
+        &self.config
+    }
+}
+
+impl Cli {
+    fn new(args: &[String]) -> Result<Cli, &'static str> {
         if args.len() == 1 || args[1] == "--help" || args[1] == "-h" {
-            println!("STFU LOSER BITCH");
+            print!("{}", commands::help::usage());
             process::exit(0);
         }
+        if args[2..].iter().any(|a| a == "--help" || a == "-h") {
+            if let Some(usage) = commands::help::subcommand_usage(&args[1]) {
+                print!("{}", usage);
+                process::exit(0);
+            }
+        }
 
-        if args.len() < 3 {
+        let (mut command, args) = match args[1].as_str() {
+            "update" => (Command::Summarize, &args[1..]),
+            "stats" => (Command::Stats, &args[1..]),
+            "recluster" => (Command::Recluster, &args[1..]),
+            "validate" => (Command::Validate, &args[1..]),
+            "dry-run" => (Command::DryRun, &args[1..]),
+            "repl" => (Command::Repl, &args[1..]),
+            "diff" => return Cli::new_diff(&args[1..]),
+            "diff-datasets" => return Cli::new_diff_datasets(&args[1..]),
+            "prepare" => return Cli::new_prepare(&args[1..]),
+            "evaluate" => return Cli::new_evaluate(&args[1..]),
+            "evaluate-queries" => return Cli::new_evaluate_queries(&args[1..]),
+            "extract" => return Cli::new_extract(&args[1..]),
+            "export-nodes" => return Cli::new_export_nodes(&args[1..]),
+            "export-tables" => return Cli::new_export_tables(&args[1..]),
+            "dict" => return Cli::new_dict(&args[1..]),
+            "dump-meta" => return Cli::new_dump_meta(&args[1..]),
+            "embed" => return Cli::new_embed(&args[1..]),
+            "reach" => return Cli::new_reach(&args[1..]),
+            "fuzz" => return Cli::new_fuzz(&args[1..]),
+            "bench-cliques" => return Cli::new_bench_cliques(&args[1..]),
+            "bench-edges" => return Cli::new_bench_edges(&args[1..]),
+            "moved" => return Cli::new_moved(&args[1..]),
+            "completions" => return Cli::new_completions(&args[1..]),
+            // teriyaki explain <node_a> <node_b> <dataset> <update> <meta> [flags]
+            "explain" if args.len() < 4 => return Err("explain expects two nodes"),
+            "explain" => (
+                Command::Explain(as_iri(&args[2]), as_iri(&args[3])),
+                &args[3..],
+            ),
+            _ => (Command::Summarize, args),
+        };
+
+        if args.len() < 4 {
             return Err("not enough arguments");
         }
 
+        // An url of an RDF file is a remote dataset, any other a SPARQL endpoint.
+        #[cfg(feature = "endpoint")]
+        let endpoint = if (args[1].starts_with("http://") || args[1].starts_with("https://"))
+            && !source::looks_like_file(&args[1])
+        {
+            Some(args[1].clone())
+        } else {
+            None
+        };
+
         let dataset_path = PathBuf::from(&args[1]);
-        if !dataset_path.exists() {
+        let update_path = PathBuf::from(&args[2]);
+        let meta_folder_path = PathBuf::from(&args[3]);
+
+        let mut use_fast = false;
+        let mut format = Format::Text;
+        let mut centrality = false;
+        let mut pagerank = false;
+        let mut clique_stats = false;
+        let mut focus_preds: Option<Vec<String>> = None;
+        let mut excluded_preds: Vec<String> = Vec::new();
+        let mut auto_exclude: Option<AutoExclude> = None;
+        let mut schema: Option<PathBuf> = None;
+        let mut seeds: Option<PathBuf> = None;
+        let mut inverse_preds = InversePreds::default();
+        let mut grouping = Grouping::Preds;
+        let mut types: Option<Grouping> = None;
+        let mut duplicates = DuplicatePolicy::Dedupe;
+        let mut coalescing = Coalescing::Sequence;
+        let mut split_strategy = SplitStrategy::Eject;
+        let mut directions = Directions::Separate;
+        let mut recluster_every: Option<usize> = None;
+        let mut paranoid = false;
+        let mut lenient = false;
+        let mut sorted_input = false;
+        let mut analyze = false;
+        let mut timings = false;
+        let mut profile_memory = false;
+        let mut wal = false;
+        let mut time_budget: Option<Duration> = None;
+        let mut max_supernode_size: Option<usize> = None;
+        let mut max_errors: Option<usize> = None;
+        let mut sample: Option<f64> = None;
+        let mut seed: Option<u64> = None;
+        let mut dedup_window = 0;
+        let mut drift = DriftThresholds::default();
+        let mut provenance = false;
+        let mut compact_meta = false;
+        #[cfg(feature = "sqlite")]
+        let mut sqlite_meta = false;
+        #[cfg(feature = "zstd")]
+        let mut zstd_meta = None;
+        let mut keep_literals_over: Option<usize> = None;
+        let mut term_cache = dict::TERM_CACHE;
+        let mut clique_bitmap_threshold = node_set::BITMAP_THRESHOLD;
+        let mut chunk_size = updater::CHUNK_SIZE;
+        let mut max_line_bytes: Option<usize> = None;
+        let mut long_lines: Option<io::LongLines> = None;
+        let mut channel_capacity = io::STREAM_CAPACITY;
+        let mut threads: Option<usize> = None;
+        #[cfg(feature = "endpoint")]
+        let mut page_size = 10000;
+        #[cfg(feature = "endpoint")]
+        let mut endpoint_timeout = util::http::DEFAULT_TIMEOUT;
+        #[cfg(feature = "endpoint")]
+        let mut push_url: Option<String> = None;
+        #[cfg(feature = "endpoint")]
+        let mut push_batch = 1000;
+        #[cfg(feature = "endpoint")]
+        let mut push_retries = 3;
+
+        let mut i = 4;
+        while i < args.len() {
+            match args[i].as_str() {
+                "--fast" | "-f" => use_fast = true,
+                "--centrality" => centrality = true,
+                "--pagerank" => pagerank = true,
+                "--cliques" => clique_stats = true,
+                "--paranoid" => paranoid = true,
+                "--lenient" => lenient = true,
+                "--sorted-input" => sorted_input = true,
+                "--analyze" => analyze = true,
+                "--timings" => timings = true,
+                "--profile-memory" => profile_memory = true,
+                "--wal" => wal = true,
+                "--grouping" => {
+                    i += 1;
+                    grouping = Grouping::parse(args.get(i))?;
+                }
+                "--types" => {
+                    i += 1;
+                    types = Some(Grouping::parse_types(args.get(i))?);
+                }
+                "--duplicates" => {
+                    i += 1;
+                    duplicates = DuplicatePolicy::parse(args.get(i))?;
+                }
+                "--coalesce" => {
+                    i += 1;
+                    coalescing = Coalescing::parse(args.get(i))?;
+                }
+                "--split-strategy" => {
+                    i += 1;
+                    split_strategy = SplitStrategy::parse(args.get(i))?;
+                }
+                "--directions" => {
+                    i += 1;
+                    directions = Directions::parse(args.get(i))?;
+                }
+                "--recluster-every" => {
+                    i += 1;
+                    recluster_every = match args.get(i).and_then(|s| s.parse::<usize>().ok()) {
+                        Some(n) if n > 0 => Some(n),
+                        _ => return Err("--recluster-every expects a positive number"),
+                    };
+                }
+                "--time-budget" => {
+                    i += 1;
+                    // Negative, infinite and overflowing budgets are rejected rather than panicking.
+                    time_budget = match args
+                        .get(i)
+                        .and_then(|s| s.parse::<f64>().ok())
+                        .map(Duration::try_from_secs_f64)
+                    {
+                        Some(Ok(budget)) => Some(budget),
+                        _ => return Err("--time-budget expects a number of seconds"),
+                    };
+                }
+                "--max-supernode-size" => {
+                    i += 1;
+                    max_supernode_size = match args.get(i).and_then(|s| s.parse::<usize>().ok()) {
+                        Some(max) if max >= 2 => Some(max),
+                        _ => return Err("--max-supernode-size expects a number of at least 2"),
+                    };
+                }
+                "--max-errors" => {
+                    i += 1;
+                    max_errors = match args.get(i).and_then(|s| s.parse::<usize>().ok()) {
+                        Some(max) => Some(max),
+                        None => return Err("--max-errors expects a number"),
+                    };
+                }
+                "--sample" => {
+                    i += 1;
+                    sample = match args.get(i).and_then(|s| s.parse::<f64>().ok()) {
+                        Some(f) if f > 0.0 && f <= 1.0 => Some(f),
+                        _ => return Err("--sample expects a fraction between 0 and 1"),
+                    };
+                }
+                "--seed" => {
+                    i += 1;
+                    seed = match args.get(i).and_then(|s| s.parse::<u64>().ok()) {
+                        Some(n) => Some(n),
+                        None => return Err("--seed expects a number"),
+                    };
+                }
+                "--dedup-window" => {
+                    i += 1;
+                    dedup_window = match args.get(i).and_then(|s| s.parse::<usize>().ok()) {
+                        Some(n) => n,
+                        None => return Err("--dedup-window expects a number"),
+                    };
+                }
+                "--drift-max-singletons" => {
+                    i += 1;
+                    drift.max_singleton_share = match args
+                        .get(i)
+                        .and_then(|s| s.parse::<f64>().ok())
+                    {
+                        Some(share) if (0.0..=1.0).contains(&share) => Some(share),
+                        _ => return Err("--drift-max-singletons expects a share between 0 and 1"),
+                    };
+                }
+                "--drift-min-size" => {
+                    i += 1;
+                    drift.min_average_size = match args.get(i).and_then(|s| s.parse::<f64>().ok()) {
+                        Some(size) if size >= 0.0 => Some(size),
+                        _ => return Err("--drift-min-size expects a number of members"),
+                    };
+                }
+                "--drift-max-splits" => {
+                    i += 1;
+                    drift.max_splits_per_minute =
+                        match args.get(i).and_then(|s| s.parse::<usize>().ok()) {
+                            Some(n) => Some(n),
+                            None => return Err("--drift-max-splits expects a number per minute"),
+                        };
+                }
+                "--provenance" => provenance = true,
+                "--compact-meta" => compact_meta = true,
+                #[cfg(feature = "sqlite")]
+                "--sqlite-meta" => sqlite_meta = true,
+                #[cfg(feature = "zstd")]
+                "--zstd-meta" => {
+                    i += 1;
+                    zstd_meta = match args.get(i).and_then(|s| s.parse::<i32>().ok()) {
+                        Some(level)
+                            if (1..=teriyaki_core::util::zstd::MAX_LEVEL).contains(&level) =>
+                        {
+                            Some(level)
+                        }
+                        _ => return Err("--zstd-meta expects a level between 1 and 22"),
+                    };
+                }
+                "--keep-literals-over" => {
+                    i += 1;
+                    keep_literals_over = match args.get(i).and_then(|s| s.parse::<usize>().ok()) {
+                        Some(n) => Some(n),
+                        None => return Err("--keep-literals-over expects a number of bytes"),
+                    };
+                }
+                "--max-line-bytes" => {
+                    i += 1;
+                    max_line_bytes = match args.get(i).and_then(|s| s.parse::<usize>().ok()) {
+                        Some(n) if n > 0 => Some(n),
+                        _ => return Err("--max-line-bytes expects a positive number"),
+                    };
+                }
+                "--long-lines" => {
+                    i += 1;
+                    long_lines = Some(io::LongLines::parse(args.get(i))?);
+                }
+                "--chunk-size" => {
+                    i += 1;
+                    chunk_size = match args.get(i).and_then(|s| s.parse::<usize>().ok()) {
+                        Some(n) => n,
+                        None => return Err("--chunk-size expects a number of changes"),
+                    };
+                }
+                "--term-cache" => {
+                    i += 1;
+                    term_cache = match args.get(i).and_then(|s| s.parse::<usize>().ok()) {
+                        Some(n) => n,
+                        None => return Err("--term-cache expects a number of terms"),
+                    };
+                }
+                "--clique-bitmap-threshold" => {
+                    i += 1;
+                    clique_bitmap_threshold = match args
+                        .get(i)
+                        .and_then(|s| s.parse::<usize>().ok())
+                    {
+                        Some(n) => n,
+                        None => return Err("--clique-bitmap-threshold expects a number of nodes"),
+                    };
+                }
+                "--channel-capacity" => {
+                    i += 1;
+                    channel_capacity = match args.get(i).and_then(|s| s.parse::<usize>().ok()) {
+                        Some(n) if n > 0 => n,
+                        _ => return Err("--channel-capacity expects a positive number"),
+                    };
+                }
+                "--threads" => {
+                    i += 1;
+                    threads = match args.get(i).and_then(|s| s.parse::<usize>().ok()) {
+                        Some(n) if n > 0 => Some(n),
+                        _ => return Err("--threads expects a positive number"),
+                    };
+                }
+                "--focus-pred" => {
+                    i += 1;
+                    focus_preds = match args.get(i) {
+                        Some(list) => Some(list.split(',').map(as_iri).collect()),
+                        None => return Err("--focus-pred expects a list of predicates"),
+                    };
+                }
+                "--exclude-pred" => {
+                    i += 1;
+                    excluded_preds = match args.get(i) {
+                        Some(list) => list.split(',').map(as_iri).collect(),
+                        None => return Err("--exclude-pred expects a list of predicates"),
+                    };
+                }
+                "--inverse-preds" => {
+                    i += 1;
+                    inverse_preds = InversePreds::parse(args.get(i))?;
+                }
+                "--schema" => {
+                    i += 1;
+                    schema = match args.get(i) {
+                        Some(path) => Some(PathBuf::from(path)),
+                        None => return Err("--schema expects a file"),
+                    };
+                }
+                "--seeds" => {
+                    i += 1;
+                    seeds = match args.get(i) {
+                        Some(path) => Some(PathBuf::from(path)),
+                        None => return Err("--seeds expects a file"),
+                    };
+                }
+                "--auto-exclude" => {
+                    i += 1;
+                    auto_exclude = Some(AutoExclude::parse(args.get(i))?);
+                }
+                "--format" => {
+                    i += 1;
+                    format = Format::parse(args.get(i))?;
+                }
+                #[cfg(feature = "endpoint")]
+                "--page-size" => {
+                    i += 1;
+                    page_size = match args.get(i).and_then(|s| s.parse::<usize>().ok()) {
+                        Some(n) if n > 0 => n,
+                        _ => return Err("--page-size expects a positive number"),
+                    };
+                }
+                #[cfg(feature = "endpoint")]
+                "--endpoint-timeout" => {
+                    i += 1;
+                    endpoint_timeout = match args
+                        .get(i)
+                        .and_then(|s| s.parse::<f64>().ok())
+                        .map(Duration::try_from_secs_f64)
+                    {
+                        Some(Ok(timeout)) => timeout,
+                        _ => return Err("--endpoint-timeout expects a number of seconds"),
+                    };
+                }
+                #[cfg(feature = "endpoint")]
+                "--push" => {
+                    i += 1;
+                    push_url = match args.get(i) {
+                        Some(url) => Some(url.clone()),
+                        None => return Err("--push expects an endpoint url"),
+                    };
+                }
+                #[cfg(feature = "endpoint")]
+                "--push-batch" => {
+                    i += 1;
+                    push_batch = match args.get(i).and_then(|s| s.parse::<usize>().ok()) {
+                        Some(n) if n > 0 => n,
+                        _ => return Err("--push-batch expects a positive number"),
+                    };
+                }
+                #[cfg(feature = "endpoint")]
+                "--push-retries" => {
+                    i += 1;
+                    push_retries = match args.get(i).and_then(|s| s.parse::<usize>().ok()) {
+                        Some(n) => n,
+                        None => return Err("--push-retries expects a number"),
+                    };
+                }
+                _ => return Err("unknown argument"),
+            }
+            i += 1;
+        }
+
+        if let Some(types) = types {
+            if grouping != Grouping::Preds {
+                return Err("--types only applies to grouping by preds");
+            }
+            grouping = types;
+        }
+
+        if let Some(every) = recluster_every {
+            match split_strategy {
+                SplitStrategy::Lazy(_) => split_strategy = SplitStrategy::Lazy(every),
+                _ => return Err("--recluster-every only applies to --split-strategy lazy"),
+            }
+        }
+
+        if seed.is_some() && sample.is_none() {
+            return Err("--seed only applies to --sample");
+        }
+
+        if long_lines.is_some() && max_line_bytes.is_none() {
+            return Err("--long-lines only applies to --max-line-bytes");
+        }
+
+        // The predicates found depend on the summary read, so excluding them in an update could
+        // summarize triples that an earlier run passed through.
+        if auto_exclude == Some(AutoExclude::Apply) && !use_fast {
+            return Err("--auto-exclude apply only applies to --fast");
+        }
+
+        #[cfg(all(feature = "zstd", feature = "sqlite"))]
+        if zstd_meta.is_some() && sqlite_meta {
+            return Err("--zstd-meta does not apply to --sqlite-meta");
+        }
+
+        if wal && !matches!(command, Command::Repl) {
+            return Err("--wal only applies to repl");
+        }
+
+        if analyze {
+            command = match command {
+                Command::Summarize => Command::Analyze,
+                _ => return Err("--analyze only applies to update"),
+            };
+        }
+
+        if use_fast && format == Format::Text {
+            println!("[ANON] GAMER MODE ACTIVATED _  _ _ xX_Using fast mode_Xx");
+        }
+
+        let recluster = matches!(command, Command::Recluster);
+        Ok(Cli {
+            command,
+            format,
+            errors_json: false,
+            config: Config {
+                dataset_path,
+                meta_folder_path,
+                update_path,
+                use_fast,
+                centrality,
+                pagerank,
+                clique_stats,
+                focus_preds,
+                excluded_preds,
+                schema,
+                seeds,
+                inverse_preds,
+                auto_exclude,
+                grouping,
+                duplicates,
+                coalescing,
+                split_strategy,
+                directions,
+                paranoid,
+                recluster,
+                lenient,
+                sorted_input,
+                timings,
+                profile_memory,
+                wal,
+                time_budget,
+                max_supernode_size,
+                max_errors,
+                sample,
+                seed: seed.unwrap_or_default(),
+                dedup_window,
+                drift,
+                provenance,
+                channel_capacity,
+                threads,
+                compact_meta,
+                #[cfg(feature = "sqlite")]
+                sqlite_meta,
+                #[cfg(feature = "zstd")]
+                zstd_meta,
+                keep_literals_over,
+                term_cache,
+                clique_bitmap_threshold,
+                chunk_size,
+                line_limit: max_line_bytes.map(|max_bytes| io::LineLimit {
+                    max_bytes,
+                    policy: long_lines.unwrap_or_default(),
+                }),
+                #[cfg(feature = "endpoint")]
+                endpoint,
+                #[cfg(feature = "endpoint")]
+                page_size,
+                #[cfg(feature = "endpoint")]
+                endpoint_timeout,
+                #[cfg(feature = "endpoint")]
+                push: push_url.map(|url| writer::endpoint::PushConfig {
+                    url,
+                    batch_size: push_batch,
+                    retries: push_retries,
+                    timeout: endpoint_timeout,
+                }),
+            },
+        })
+    }
+
+    /// Parses the arguments of `teriyaki diff <old> <new> [--format text|json]`.
+    fn new_diff(args: &[String]) -> Result<Cli, &'static str> {
+        if args.len() < 3 {
+            return Err("diff expects two summary files");
+        }
+
+        let old = PathBuf::from(&args[1]);
+        let new = PathBuf::from(&args[2]);
+        if !old.exists() || !new.exists() {
+            return Err("summary path does not exist");
+        }
+
+        let format = match args.get(3).map(|s| s.as_str()) {
+            None => Format::Text,
+            Some("--format") => Format::parse(args.get(4))?,
+            Some(_) => return Err("unknown argument"),
+        };
+
+        Ok(Cli {
+            command: Command::Diff(old, new),
+            format,
+            ..Default::default()
+        })
+    }
+
+    /// Parses the arguments of `teriyaki diff-datasets <old> <new> --out <file>
+    /// [--chunk-lines N] [--format text|json]`.
+    fn new_diff_datasets(args: &[String]) -> Result<Cli, &'static str> {
+        if args.len() < 3 {
+            return Err("diff-datasets expects two dataset files");
+        }
+
+        let old = PathBuf::from(&args[1]);
+        let new = PathBuf::from(&args[2]);
+        if !old.exists() || !new.exists() {
             return Err("dataset path does not exist");
         }
 
-        let update_path = PathBuf::from(&args[2]);
-        if !update_path.exists() {
-            return Err("update path does not exist");
+        let mut out: Option<PathBuf> = None;
+        let mut chunk_lines = commands::diff_datasets::CHUNK_LINES;
+        let mut format = Format::Text;
+        let mut i = 3;
+        while i < args.len() {
+            match args[i].as_str() {
+                "--out" => {
+                    i += 1;
+                    out = match args.get(i) {
+                        Some(path) => Some(PathBuf::from(path)),
+                        None => return Err("--out expects a file"),
+                    };
+                }
+                "--chunk-lines" => {
+                    i += 1;
+                    chunk_lines = match args.get(i).and_then(|s| s.parse::<usize>().ok()) {
+                        Some(n) if n > 0 => n,
+                        _ => return Err("--chunk-lines expects a positive number"),
+                    };
+                }
+                "--format" => {
+                    i += 1;
+                    format = Format::parse(args.get(i))?;
+                }
+                _ => return Err("unknown argument"),
+            }
+            i += 1;
+        }
+
+        match out {
+            Some(out) => Ok(Cli {
+                command: Command::DiffDatasets(commands::diff_datasets::DiffDatasetsOptions {
+                    old,
+                    new,
+                    out,
+                    chunk_lines,
+                }),
+                format,
+                ..Default::default()
+            }),
+            None => Err("diff-datasets expects --out"),
+        }
+    }
+
+    /// Parses the arguments of `teriyaki prepare <input> --out <folder> [--shards N]
+    /// [--chunk-lines N] [--format text|json]`.
+    fn new_prepare(args: &[String]) -> Result<Cli, &'static str> {
+        if args.len() < 2 {
+            return Err("prepare expects a dataset file");
+        }
+
+        let input = PathBuf::from(&args[1]);
+        if !input.exists() {
+            return Err("dataset path does not exist");
+        }
+
+        let mut out: Option<PathBuf> = None;
+        let mut shards: Option<usize> = None;
+        let mut chunk_lines = commands::diff_datasets::CHUNK_LINES;
+        let mut format = Format::Text;
+        let mut i = 2;
+        while i < args.len() {
+            match args[i].as_str() {
+                "--out" => {
+                    i += 1;
+                    out = match args.get(i) {
+                        Some(path) => Some(PathBuf::from(path)),
+                        None => return Err("--out expects a folder"),
+                    };
+                }
+                "--shards" => {
+                    i += 1;
+                    shards = match args.get(i).and_then(|s| s.parse::<usize>().ok()) {
+                        Some(n) if n > 0 => Some(n),
+                        _ => return Err("--shards expects a positive number"),
+                    };
+                }
+                "--chunk-lines" => {
+                    i += 1;
+                    chunk_lines = match args.get(i).and_then(|s| s.parse::<usize>().ok()) {
+                        Some(n) if n > 0 => n,
+                        _ => return Err("--chunk-lines expects a positive number"),
+                    };
+                }
+                "--format" => {
+                    i += 1;
+                    format = Format::parse(args.get(i))?;
+                }
+                _ => return Err("unknown argument"),
+            }
+            i += 1;
+        }
+
+        match out {
+            Some(out) => Ok(Cli {
+                command: Command::Prepare(commands::prepare::PrepareOptions {
+                    input,
+                    out,
+                    chunk_lines,
+                    shards,
+                }),
+                format,
+                ..Default::default()
+            }),
+            None => Err("prepare expects --out"),
+        }
+    }
+
+    /// Parses the arguments of
+    /// `teriyaki evaluate <incremental meta> <fresh meta> [--format text|json]`.
+    fn new_evaluate(args: &[String]) -> Result<Cli, &'static str> {
+        if args.len() < 3 {
+            return Err("evaluate expects two meta folders");
+        }
+
+        let incremental = PathBuf::from(&args[1]);
+        let fresh = PathBuf::from(&args[2]);
+        if !incremental.exists() || !fresh.exists() {
+            return Err("meta folder path does not exist");
+        }
+
+        let format = match args.get(3).map(|s| s.as_str()) {
+            None => Format::Text,
+            Some("--format") => Format::parse(args.get(4))?,
+            Some(_) => return Err("unknown argument"),
+        };
+
+        Ok(Cli {
+            command: Command::Evaluate(incremental, fresh),
+            format,
+            ..Default::default()
+        })
+    }
+
+    /// Parses the arguments of `teriyaki moved <old meta> <new meta> [--decode]
+    /// [--format text|json]`.
+    fn new_moved(args: &[String]) -> Result<Cli, &'static str> {
+        if args.len() < 3 {
+            return Err("moved expects two meta folders");
+        }
+
+        let old = PathBuf::from(&args[1]);
+        let new = PathBuf::from(&args[2]);
+        if !old.exists() || !new.exists() {
+            return Err("meta folder path does not exist");
+        }
+
+        let mut decode = false;
+        let mut format = Format::Text;
+        let mut i = 3;
+        while i < args.len() {
+            match args[i].as_str() {
+                "--decode" => decode = true,
+                "--format" => {
+                    i += 1;
+                    format = Format::parse(args.get(i))?;
+                }
+                _ => return Err("unknown argument"),
+            }
+            i += 1;
+        }
+
+        Ok(Cli {
+            command: Command::Moved(old, new, decode),
+            format,
+            ..Default::default()
+        })
+    }
+
+    /// Parses the arguments of
+    /// `teriyaki evaluate-queries <original> <summary> <meta> <queries> [--format text|json]`.
+    fn new_evaluate_queries(args: &[String]) -> Result<Cli, &'static str> {
+        if args.len() < 5 {
+            return Err("evaluate-queries expects the original, summary, meta folder and queries");
         }
 
+        let original = PathBuf::from(&args[1]);
+        let summary = PathBuf::from(&args[2]);
         let meta_folder_path = PathBuf::from(&args[3]);
+        let queries = PathBuf::from(&args[4]);
+        if [&original, &summary, &meta_folder_path, &queries]
+            .iter()
+            .any(|p| !p.exists())
+        {
+            return Err("evaluate-queries path does not exist");
+        }
 
-        let mut use_fast = false;
-        if args.len() > 4 && (args[4] == "--fast" || args[4] == "-f") {
-            println!("[ANON] GAMER MODE ACTIVATED _  _ _ xX_Using fast mode_Xx");
-            use_fast = true;
+        let format = match args.get(5).map(|s| s.as_str()) {
+            None => Format::Text,
+            Some("--format") => Format::parse(args.get(6))?,
+            Some(_) => return Err("unknown argument"),
+        };
+
+        Ok(Cli {
+            command: Command::EvaluateQueries(commands::evaluate_queries::EvaluateQueriesOptions {
+                original,
+                summary,
+                queries,
+            }),
+            format,
+            config: Config {
+                meta_folder_path,
+                ..Default::default()
+            },
+            ..Default::default()
+        })
+    }
+
+    /// Parses the arguments of `teriyaki extract <meta> --supernode <id|alias|iri> [--out <file>]`.
+    fn new_extract(args: &[String]) -> Result<Cli, &'static str> {
+        if args.len() < 2 {
+            return Err("extract expects a meta folder");
+        }
+
+        let meta_folder_path = PathBuf::from(&args[1]);
+        if !meta_folder_path.exists() {
+            return Err("meta folder path does not exist");
+        }
+
+        let mut supernode: Option<String> = None;
+        let mut out: Option<PathBuf> = None;
+        let mut i = 2;
+        while i < args.len() {
+            match args[i].as_str() {
+                "--supernode" => {
+                    i += 1;
+                    supernode = match args.get(i) {
+                        Some(s) => Some(s.clone()),
+                        None => return Err("--supernode expects an id, alias or iri"),
+                    };
+                }
+                "--out" => {
+                    i += 1;
+                    out = match args.get(i) {
+                        Some(path) => Some(PathBuf::from(path)),
+                        None => return Err("--out expects a file"),
+                    };
+                }
+                _ => return Err("unknown argument"),
+            }
+            i += 1;
+        }
+
+        match supernode {
+            Some(supernode) => Ok(Cli {
+                command: Command::Extract(supernode, out),
+                config: Config {
+                    meta_folder_path,
+                    ..Default::default()
+                },
+                ..Default::default()
+            }),
+            None => Err("extract expects --supernode"),
+        }
+    }
+
+    /// Parses the arguments of `teriyaki export-nodes <meta> --out <file> [--format text|json]`.
+    fn new_export_nodes(args: &[String]) -> Result<Cli, &'static str> {
+        let (out, cli) = Cli::new_export(args, "export-nodes expects --out <file>")?;
+        Ok(Cli {
+            command: Command::ExportNodes(out),
+            ..cli
+        })
+    }
+
+    /// Parses the arguments of `teriyaki export-tables <meta> --out <folder> [--format text|json]`.
+    fn new_export_tables(args: &[String]) -> Result<Cli, &'static str> {
+        let (out, cli) = Cli::new_export(args, "export-tables expects --out <folder>")?;
+        Ok(Cli {
+            command: Command::ExportTables(out),
+            ..cli
+        })
+    }
+
+    /// Parses the arguments the export subcommands share, returning the `--out` path.
+    fn new_export(
+        args: &[String],
+        missing_out: &'static str,
+    ) -> Result<(PathBuf, Cli), &'static str> {
+        if args.len() < 2 {
+            return Err("export expects a meta folder");
+        }
+
+        let meta_folder_path = PathBuf::from(&args[1]);
+        if !meta_folder_path.exists() {
+            return Err("meta folder path does not exist");
+        }
+
+        let mut out: Option<PathBuf> = None;
+        let mut format = Format::Text;
+        let mut i = 2;
+        while i < args.len() {
+            match args[i].as_str() {
+                "--out" => {
+                    i += 1;
+                    out = match args.get(i) {
+                        Some(path) => Some(PathBuf::from(path)),
+                        None => return Err("--out expects a path"),
+                    };
+                }
+                "--format" => {
+                    i += 1;
+                    format = Format::parse(args.get(i))?;
+                }
+                _ => return Err("unknown argument"),
+            }
+            i += 1;
+        }
+
+        let cli = Cli {
+            format,
+            config: Config {
+                meta_folder_path,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        Ok((out.ok_or(missing_out)?, cli))
+    }
+
+    /// Parses the arguments of `teriyaki dump-meta <meta> --node <id|iri> [--format text|json]`
+    /// and `teriyaki dump-meta <meta> --supernode <id|alias|iri> [--format text|json]`.
+    fn new_dump_meta(args: &[String]) -> Result<Cli, &'static str> {
+        if args.len() < 2 {
+            return Err("dump-meta expects a meta folder");
+        }
+
+        let meta_folder_path = PathBuf::from(&args[1]);
+        if !meta_folder_path.exists() {
+            return Err("meta folder path does not exist");
+        }
+
+        let mut target: Option<commands::dump_meta::Target> = None;
+        let mut format = Format::Text;
+        let mut i = 2;
+        while i < args.len() {
+            match args[i].as_str() {
+                "--node" => {
+                    i += 1;
+                    target = match args.get(i) {
+                        Some(s) => Some(commands::dump_meta::Target::Node(s.clone())),
+                        None => return Err("--node expects an id or iri"),
+                    };
+                }
+                "--supernode" => {
+                    i += 1;
+                    target = match args.get(i) {
+                        Some(s) => Some(commands::dump_meta::Target::Supernode(s.clone())),
+                        None => return Err("--supernode expects an id, alias or iri"),
+                    };
+                }
+                "--format" => {
+                    i += 1;
+                    format = Format::parse(args.get(i))?;
+                }
+                _ => return Err("unknown argument"),
+            }
+            i += 1;
+        }
+
+        match target {
+            Some(target) => Ok(Cli {
+                command: Command::DumpMeta(target),
+                format,
+                config: Config {
+                    meta_folder_path,
+                    ..Default::default()
+                },
+                ..Default::default()
+            }),
+            None => Err("dump-meta expects --node or --supernode"),
+        }
+    }
+
+    /// Parses the arguments of `teriyaki reach <meta> <from> <to> [--max-hops N]
+    /// [--format text|json]`, where `from` and `to` are ids, aliases or IRIs.
+    fn new_reach(args: &[String]) -> Result<Cli, &'static str> {
+        if args.len() < 4 {
+            return Err("reach expects a meta folder and two nodes or supernodes");
+        }
+
+        let meta_folder_path = PathBuf::from(&args[1]);
+        if !meta_folder_path.exists() {
+            return Err("meta folder path does not exist");
+        }
+
+        let mut max_hops: Option<usize> = None;
+        let mut format = Format::Text;
+        let mut i = 4;
+        while i < args.len() {
+            match args[i].as_str() {
+                "--max-hops" => {
+                    i += 1;
+                    max_hops = match args.get(i).and_then(|s| s.parse::<usize>().ok()) {
+                        Some(n) => Some(n),
+                        None => return Err("--max-hops expects a number"),
+                    };
+                }
+                "--format" => {
+                    i += 1;
+                    format = Format::parse(args.get(i))?;
+                }
+                _ => return Err("unknown argument"),
+            }
+            i += 1;
+        }
+
+        Ok(Cli {
+            command: Command::Reach(args[2].clone(), args[3].clone(), max_hops),
+            format,
+            config: Config {
+                meta_folder_path,
+                ..Default::default()
+            },
+            ..Default::default()
+        })
+    }
+
+    /// Parses the arguments of `teriyaki embed <meta> --out <file> [--walks <file>]
+    /// [--walk-length N] [--walks-per-node N] [--p X] [--q X] [--seed N] [--format text|json]`.
+    fn new_embed(args: &[String]) -> Result<Cli, &'static str> {
+        if args.len() < 2 {
+            return Err("embed expects a meta folder");
+        }
+
+        let meta_folder_path = PathBuf::from(&args[1]);
+        if !meta_folder_path.exists() {
+            return Err("meta folder path does not exist");
+        }
+
+        let mut format = Format::Text;
+        let mut out: Option<PathBuf> = None;
+        let mut options = commands::embed::EmbedOptions::default();
+        let number = |arg: Option<&String>| arg.and_then(|s| s.parse::<u64>().ok());
+        let positive = |arg: Option<&String>| {
+            arg.and_then(|s| s.parse::<f64>().ok())
+                .filter(|x| *x > 0.0 && x.is_finite())
+        };
+        let mut i = 2;
+        while i < args.len() {
+            match args[i].as_str() {
+                "--out" => {
+                    i += 1;
+                    out = match args.get(i) {
+                        Some(path) => Some(PathBuf::from(path)),
+                        None => return Err("--out expects a file"),
+                    };
+                }
+                "--walks" => {
+                    i += 1;
+                    options.walks = match args.get(i) {
+                        Some(path) => Some(PathBuf::from(path)),
+                        None => return Err("--walks expects a file"),
+                    };
+                }
+                "--walk-length" => {
+                    i += 1;
+                    options.walk_length = match number(args.get(i)) {
+                        Some(n) if n > 0 => n as usize,
+                        _ => return Err("--walk-length expects a positive number"),
+                    };
+                }
+                "--walks-per-node" => {
+                    i += 1;
+                    options.walks_per_node = match number(args.get(i)) {
+                        Some(n) if n > 0 => n as usize,
+                        _ => return Err("--walks-per-node expects a positive number"),
+                    };
+                }
+                "--p" => {
+                    i += 1;
+                    options.p = positive(args.get(i)).ok_or("--p expects a positive number")?;
+                }
+                "--q" => {
+                    i += 1;
+                    options.q = positive(args.get(i)).ok_or("--q expects a positive number")?;
+                }
+                "--seed" => {
+                    i += 1;
+                    options.seed = match number(args.get(i)) {
+                        Some(n) => n,
+                        None => return Err("--seed expects a number"),
+                    };
+                }
+                "--format" => {
+                    i += 1;
+                    format = Format::parse(args.get(i))?;
+                }
+                _ => return Err("unknown argument"),
+            }
+            i += 1;
+        }
+
+        match out {
+            Some(out) => Ok(Cli {
+                command: Command::Embed(commands::embed::EmbedOptions { out, ..options }),
+                format,
+                config: Config {
+                    meta_folder_path,
+                    ..Default::default()
+                },
+                ..Default::default()
+            }),
+            None => Err("embed expects --out"),
+        }
+    }
+
+    /// Parses the arguments of `teriyaki dict export <meta> [--out <file>] [--compressed]` and
+    /// `teriyaki dict import <file> <meta>`.
+    fn new_dict(args: &[String]) -> Result<Cli, &'static str> {
+        let (command, meta_folder_path) = match args.get(1).map(|s| s.as_str()) {
+            Some("export") => {
+                let meta = args.get(2).ok_or("dict export expects a meta folder")?;
+                let mut out: Option<PathBuf> = None;
+                let mut compressed = false;
+                let mut i = 3;
+                while i < args.len() {
+                    match args[i].as_str() {
+                        "--compressed" => compressed = true,
+                        "--out" => {
+                            i += 1;
+                            out = match args.get(i) {
+                                Some(path) => Some(PathBuf::from(path)),
+                                None => return Err("--out expects a file"),
+                            };
+                        }
+                        _ => return Err("unknown argument"),
+                    }
+                    i += 1;
+                }
+                (
+                    commands::dict::DictCommand::Export { out, compressed },
+                    PathBuf::from(meta),
+                )
+            }
+            Some("import") => {
+                if args.len() != 4 {
+                    return Err("dict import expects a term file and a meta folder");
+                }
+                let file = PathBuf::from(&args[2]);
+                if !file.exists() {
+                    return Err("term file does not exist");
+                }
+                (
+                    commands::dict::DictCommand::Import(file),
+                    PathBuf::from(&args[3]),
+                )
+            }
+            _ => return Err("dict expects export or import"),
+        };
+
+        if !meta_folder_path.exists() {
+            return Err("meta folder path does not exist");
+        }
+        Ok(Cli {
+            command: Command::Dict(command),
+            config: Config {
+                meta_folder_path,
+                ..Default::default()
+            },
+            ..Default::default()
+        })
+    }
+
+    /// Parses the arguments of
+    /// `teriyaki fuzz <dataset> [--rounds <n>] [--changes <n>] [--seed <n>] [--format text|json]`.
+    fn new_fuzz(args: &[String]) -> Result<Cli, &'static str> {
+        if args.len() < 2 {
+            return Err("fuzz expects a dataset");
+        }
+
+        let dataset_path = PathBuf::from(&args[1]);
+        if !dataset_path.exists() {
+            return Err("dataset path does not exist");
+        }
+
+        let mut format = Format::Text;
+        let mut options = commands::fuzz::FuzzOptions {
+            rounds: 100,
+            changes: 5,
+            seed: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |d| d.as_nanos() as u64),
+        };
+        let number = |arg: Option<&String>| arg.and_then(|s| s.parse::<u64>().ok());
+        let mut i = 2;
+        while i < args.len() {
+            match args[i].as_str() {
+                "--rounds" => {
+                    i += 1;
+                    options.rounds = match number(args.get(i)) {
+                        Some(n) => n as usize,
+                        None => return Err("--rounds expects a number"),
+                    };
+                }
+                "--changes" => {
+                    i += 1;
+                    options.changes = match number(args.get(i)) {
+                        Some(n) if n > 0 => n as usize,
+                        _ => return Err("--changes expects a positive number"),
+                    };
+                }
+                "--seed" => {
+                    i += 1;
+                    options.seed = match number(args.get(i)) {
+                        Some(n) => n,
+                        None => return Err("--seed expects a number"),
+                    };
+                }
+                "--format" => {
+                    i += 1;
+                    format = Format::parse(args.get(i))?;
+                }
+                _ => return Err("unknown argument"),
+            }
+            i += 1;
+        }
+
+        Ok(Cli {
+            command: Command::Fuzz(options),
+            format,
+            config: Config {
+                dataset_path,
+                ..Default::default()
+            },
+            ..Default::default()
+        })
+    }
+
+    /// Parses the arguments of
+    /// `teriyaki bench-cliques [--sizes <n>,<n>...] [--rounds <n>] [--seed <n>] [--format text|json]`.
+    fn new_bench_cliques(args: &[String]) -> Result<Cli, &'static str> {
+        let mut format = Format::Text;
+        let mut options = commands::bench_cliques::BenchOptions::default();
+        let number = |arg: Option<&String>| arg.and_then(|s| s.parse::<u64>().ok());
+        let mut i = 1;
+        while i < args.len() {
+            match args[i].as_str() {
+                "--sizes" => {
+                    i += 1;
+                    options.sizes = match args.get(i).map(|s| {
+                        s.split(',')
+                            .map(|n| n.parse::<usize>())
+                            .collect::<Result<Vec<_>, _>>()
+                    }) {
+                        Some(Ok(sizes)) if !sizes.is_empty() && !sizes.contains(&0) => sizes,
+                        _ => return Err("--sizes expects positive numbers separated by commas"),
+                    };
+                }
+                "--rounds" => {
+                    i += 1;
+                    options.rounds = match number(args.get(i)) {
+                        Some(n) if n > 0 => n as usize,
+                        _ => return Err("--rounds expects a positive number"),
+                    };
+                }
+                "--seed" => {
+                    i += 1;
+                    options.seed = match number(args.get(i)) {
+                        Some(n) => n,
+                        None => return Err("--seed expects a number"),
+                    };
+                }
+                "--format" => {
+                    i += 1;
+                    format = Format::parse(args.get(i))?;
+                }
+                _ => return Err("unknown argument"),
+            }
+            i += 1;
         }
 
-        if use_fast && meta_folder_path.exists() {
-            return Err("Using fast mode and meta folder path already exists");
-        } else if !use_fast && !meta_folder_path.exists() {
-            return Err("using slow mode and meta folder path does not exist");
+        Ok(Cli {
+            command: Command::BenchCliques(options),
+            format,
+            ..Default::default()
+        })
+    }
+
+    /// Parses the arguments of `teriyaki bench-edges [--degrees <n>,<n>...] [--nodes <n>]
+    /// [--rounds <n>] [--seed <n>] [--format text|json]`.
+    fn new_bench_edges(args: &[String]) -> Result<Cli, &'static str> {
+        let mut format = Format::Text;
+        let mut options = commands::bench_edges::BenchOptions::default();
+        let number = |arg: Option<&String>| arg.and_then(|s| s.parse::<u64>().ok());
+        let mut i = 1;
+        while i < args.len() {
+            match args[i].as_str() {
+                "--degrees" => {
+                    i += 1;
+                    options.degrees = match args.get(i).map(|s| {
+                        s.split(',')
+                            .map(|n| n.parse::<usize>())
+                            .collect::<Result<Vec<_>, _>>()
+                    }) {
+                        Some(Ok(degrees)) if !degrees.is_empty() => degrees,
+                        _ => return Err("--degrees expects numbers separated by commas"),
+                    };
+                }
+                "--nodes" => {
+                    i += 1;
+                    options.nodes = match number(args.get(i)) {
+                        Some(n) if n > 0 => n as usize,
+                        _ => return Err("--nodes expects a positive number"),
+                    };
+                }
+                "--rounds" => {
+                    i += 1;
+                    options.rounds = match number(args.get(i)) {
+                        Some(n) if n > 0 => n as usize,
+                        _ => return Err("--rounds expects a positive number"),
+                    };
+                }
+                "--seed" => {
+                    i += 1;
+                    options.seed = match number(args.get(i)) {
+                        Some(n) => n,
+                        None => return Err("--seed expects a number"),
+                    };
+                }
+                "--format" => {
+                    i += 1;
+                    format = Format::parse(args.get(i))?;
+                }
+                _ => return Err("unknown argument"),
+            }
+            i += 1;
         }
 
-        Ok(Config {
-            dataset_path,
-            meta_folder_path,
-            update_path,
-            use_fast,
+        Ok(Cli {
+            command: Command::BenchEdges(options),
+            format,
+            ..Default::default()
         })
     }
+
+    /// Parses the arguments of `teriyaki completions bash|zsh|fish`.
+    fn new_completions(args: &[String]) -> Result<Cli, &'static str> {
+        if args.len() > 2 {
+            return Err("unknown argument");
+        }
+        Ok(Cli {
+            command: Command::Completions(commands::help::Shell::parse(args.get(1))?),
+            ..Default::default()
+        })
+    }
+
+    /// Checks the inputs and outputs of the run with [`Config::validate`]; the subcommands
+    /// that do not summarize check their own arguments.
+    fn validate(&self) -> Result<(), Vec<String>> {
+        let writes = match self.command {
+            Command::Summarize | Command::Recluster => true,
+            Command::Analyze
+            | Command::Stats
+            | Command::Validate
+            | Command::DryRun
+            | Command::Explain(..)
+            | Command::Repl => false,
+            // The other subcommands check their own arguments.
+            _ => return Ok(()),
+        };
+        self.config.validate(writes)
+    }
+
+    /// Prints `message` and exits with the status of `failure`.
+    fn fail(&self, failure: Failure, message: &str) -> ! {
+        self.fail_with(failure, message, &[])
+    }
+
+    /// Prints the message and problems of `failed`, and exits with the status of its failure.
+    fn exit(&self, failed: Failed) -> ! {
+        self.fail_with(failed.failure, &failed.message, &failed.problems)
+    }
+
+    /// Prints `message` and the `problems` behind it, and exits with the status of `failure`.
+    fn fail_with(&self, failure: Failure, message: &str, problems: &[String]) -> ! {
+        exit::fail(failure, message, problems, self.errors_json)
+    }
+}
+
+/// Locks the meta folder of `cli` for a run that writes it, or fails if another process
+/// holds the lock.
+fn lock(cli: &Cli) -> Result<Lock, Failed> {
+    Lock::acquire(&cli.lock_path()).map_err(|err| {
+        let message = format!("Problem locking meta folder: {}", err);
+        Failed::new(Failure::of(&err), message)
+    })
+}
+
+/// Drops the write-ahead log once the meta folder holds its changes.
+fn rotate_wal(cli: &Cli) -> Result<(), Failed> {
+    wal::rotate(&cli.wal_path()).map_err(|err| {
+        let message = format!("Problem rotating write-ahead log: {}", err);
+        Failed::new(Failure::of(&err), message)
+    })
+}
+
+/// Reads the summary, update and meta folder of `cli`.
+fn load_input(cli: &Cli) -> Result<(Dataset, Meta, CliqueCollection, CliqueCollection), Failed> {
+    parser::run(cli)
+        .map_err(|err| Failed::new(Failure::of(&err), format!("Problem reading input: {}", err)))
+}
+
+/// Reads the summary, update and meta folder of `cli`, or exits if they cannot be read.
+fn read_input(cli: &Cli) -> (Dataset, Meta, CliqueCollection, CliqueCollection) {
+    load_input(cli).unwrap_or_else(|failed| cli.exit(failed))
 }
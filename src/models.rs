@@ -1,5 +0,0 @@
-pub mod clique;
-pub mod dataset;
-pub mod dict;
-pub mod meta;
-pub mod triple;
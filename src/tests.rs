@@ -1 +1,5 @@
-mod parser_tests;
+mod commands_tests;
+// The fixtures of the core tests, which the subcommand tests share; they use only some of them.
+#[allow(dead_code)]
+#[path = "../core/src/tests/fixtures.rs"]
+mod fixtures;
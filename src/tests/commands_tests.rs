@@ -0,0 +1,556 @@
+use super::fixtures::{iri, load};
+use crate::commands::analyze::analyze;
+use crate::commands::dict::{export, export_front_coded, import};
+use crate::commands::diff_datasets::{diff_datasets, DiffDatasetsOptions};
+use crate::commands::dry_run::dry_run;
+use crate::commands::dump_meta::dump_node;
+use crate::commands::embed::{edge_list, random_walks, EmbedOptions};
+use crate::commands::evaluate::evaluate;
+use crate::commands::evaluate_queries::{evaluate_queries, Pattern};
+use crate::commands::explain::explain;
+use crate::commands::export_nodes::node_features;
+use crate::commands::export_tables::{csv_line, tables};
+use crate::commands::extract::extract;
+use crate::commands::help::{completions, subcommand_usage, Shell, RUN_FLAGS, SUBCOMMANDS};
+use crate::commands::moved::moved;
+use crate::commands::prepare::{prepare, PrepareOptions, INDEX_FILE};
+use crate::models::compact_meta::CompactMeta;
+use crate::parser::clique;
+use crate::parser::meta::{from_file, to_file};
+use crate::updater::Options;
+use crate::util::mmap::Words;
+use crate::Grouping;
+use std::collections::HashMap;
+use std::{env, fs, process};
+
+fn grouping(groups: &[&[&str]]) -> HashMap<String, u32> {
+    let mut map: HashMap<String, u32> = HashMap::new();
+    for (i, members) in groups.iter().enumerate() {
+        for m in *members {
+            map.insert(m.to_string(), i as u32 + 100);
+        }
+    }
+    map
+}
+
+#[test]
+fn analyze_counts_new_terms_unknown_deletions_and_preds() {
+    let mut f = load("a knows x\nb knows x\nc likes y");
+    let line = |s: &str, p: &str, o: &str| format!("{} {} {} .", iri(s), iri(p), iri(o));
+    f.dataset.set_update(
+        vec![line("d", "knows", "x"), line("a", "likes", "y")],
+        vec![line("a", "knows", "x"), line("z", "knows", "x")],
+        &mut f.meta,
+        &Grouping::Preds,
+    );
+
+    let report = analyze(&f.dataset, &f.meta, &f.sc, &f.tc);
+
+    assert_eq!((report.insertions, report.deletions), (2, 2));
+    assert_eq!(report.insertions_with_new_terms, 1);
+    assert_eq!(report.unknown_deletions, 1);
+    let changes = |p: &str| {
+        let c = &report.preds[&iri(p)];
+        (c.insertions, c.deletions)
+    };
+    assert_eq!(changes("knows"), (1, 2));
+    assert_eq!(changes("likes"), (1, 0));
+    // The supernode of a and b shares the source clique of knows.
+    assert_eq!(report.affected_supernodes, 1);
+}
+
+#[test]
+fn evaluate_counts_shared_pairs() {
+    // Pairs ab, ac, bc, de against ab, cd, ce, de.
+    let incremental = grouping(&[&["a", "b", "c"], &["d", "e"]]);
+    let fresh = grouping(&[&["a", "b"], &["c", "d", "e"]]);
+
+    let report = evaluate(&incremental, &fresh);
+
+    assert_eq!(report.incremental_pairs, 4);
+    assert_eq!(report.fresh_pairs, 4);
+    assert_eq!(report.shared_pairs, 2);
+    assert_eq!(report.precision, 0.5);
+    assert_eq!(report.recall, 0.5);
+    assert_eq!(report.f1, 0.5);
+}
+
+#[test]
+fn evaluate_identical_groupings_is_perfect() {
+    let groups = grouping(&[&["a", "b"], &["c", "d", "e"]]);
+    let renumbered: HashMap<String, u32> = groups.iter().map(|(k, v)| (k.clone(), v + 1)).collect();
+
+    let report = evaluate(&groups, &renumbered);
+
+    assert_eq!(
+        (report.precision, report.recall, report.f1),
+        (1.0, 1.0, 1.0)
+    );
+    assert_eq!(evaluate(&HashMap::new(), &HashMap::new()).f1, 1.0);
+}
+
+fn lines(triples: &[&str]) -> Vec<String> {
+    triples.iter().map(|t| t.to_string()).collect()
+}
+
+#[test]
+fn evaluate_queries_expands_supernodes() {
+    let original = lines(&[
+        "<a> <knows> <c> .",
+        "<b> <likes> <c> .",
+        "<a> <age> \"1\" .",
+    ]);
+    // <s> stands for <a> and <b>, so the summary also claims that <b> knows <c>.
+    let summary = lines(&[
+        "<s> <knows> <c> .",
+        "<s> <likes> <c> .",
+        "<s> <age> \"1\" .",
+    ]);
+    let expand = |t: &str| match t {
+        "<s>" => vec!["<a>".to_string(), "<b>".to_string()],
+        _ => vec![t.to_string()],
+    };
+    let queries: Vec<Pattern> = ["?x <knows> <c>", "?x ?p <c> .", "<b> <age> ?v"]
+        .iter()
+        .map(|q| Pattern::parse(q).unwrap().unwrap())
+        .collect();
+
+    let report = evaluate_queries(&queries, &original, &summary, expand);
+
+    let scores: Vec<(usize, usize, usize)> = report
+        .queries
+        .iter()
+        .map(|q| (q.original_results, q.summary_results, q.shared_results))
+        .collect();
+    assert_eq!(scores, vec![(1, 2, 1), (2, 4, 2), (0, 1, 0)]);
+    assert_eq!(report.queries[0].precision, 0.5);
+    assert_eq!(report.queries[1].recall, 1.0);
+    assert_eq!(report.queries[2].precision, 0.0);
+    assert_eq!(report.queries[2].recall, 1.0);
+    assert_eq!(report.mean_precision, 1.0 / 3.0);
+}
+
+#[test]
+fn pattern_parse_skips_comments_and_rejects_partial_patterns() {
+    assert!(Pattern::parse("# people").is_none());
+    assert!(Pattern::parse("   ").is_none());
+    assert!(Pattern::parse("?x <knows>").unwrap().is_err());
+    let pattern = Pattern::parse("?x <knows> ?x .").unwrap().unwrap();
+    assert_eq!(pattern.terms[2], "?x");
+}
+
+fn dict_lines() -> Vec<String> {
+    [
+        "<http://ex.org/knows>",
+        "",
+        "<http://ex.org/a>",
+        "\"café\"",
+        "<http://ex.org/age>",
+    ]
+    .iter()
+    .map(|t| t.to_string())
+    .collect()
+}
+
+#[test]
+fn diff_datasets_writes_a_changeset_across_sorted_runs() {
+    let dir = env::temp_dir().join(format!("teriyaki-diff-datasets-{}", process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let triple = |s: &str| format!("{} {} {} .", iri(s), iri("p"), iri("o"));
+    let write = |name: &str, subjects: &[&str], pad: &str| {
+        let lines: Vec<String> = subjects.iter().map(|s| triple(s) + pad).collect();
+        fs::write(dir.join(name), lines.join("\n") + "\n# comment\n\n").unwrap();
+    };
+    write("old.nt", &["e", "a", "c", "b", "a", "d"], "");
+    write("new.nt", &["f", "c", "a", "g", "c", "d"], "  ");
+
+    // Runs of two lines, so both dumps are merged from several runs with repeats across them.
+    let options = DiffDatasetsOptions {
+        old: dir.join("old.nt"),
+        new: dir.join("new.nt"),
+        out: dir.join("changeset.nt"),
+        chunk_lines: 2,
+    };
+    let report = diff_datasets(&options).unwrap();
+
+    assert_eq!((report.added, report.deleted, report.unchanged), (2, 2, 3));
+    let changeset = fs::read_to_string(&options.out).unwrap();
+    let expected = [
+        format!("-{}", triple("b")),
+        format!("-{}", triple("e")),
+        triple("f"),
+        triple("g"),
+    ];
+    assert_eq!(changeset.lines().collect::<Vec<_>>(), expected);
+    // Only the inputs and the changeset are left.
+    assert_eq!(fs::read_dir(&dir).unwrap().count(), 3);
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn prepare_sorts_dedupes_and_shards_by_subject() {
+    let dir = env::temp_dir().join(format!("teriyaki-prepare-{}", process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let triple = |s: &str, o: &str| format!("{} {} {} .", iri(s), iri("p"), iri(o));
+    let input = [
+        triple("b", "x"),
+        triple("a", "y"),
+        triple("ab", "x"),
+        triple("b", "x"),
+        triple("a", "x"),
+        triple("c", "z"),
+        triple("a", "y"),
+    ];
+    fs::write(dir.join("input.nt"), input.join("\n") + "\n# comment\n").unwrap();
+
+    let mut options = PrepareOptions {
+        input: dir.join("input.nt"),
+        out: dir.join("single"),
+        chunk_lines: 2,
+        shards: None,
+    };
+    let report = prepare(&options).unwrap();
+    assert_eq!((report.triples, report.duplicates), (5, 2));
+    let written = fs::read_to_string(options.out.join("dataset.nt")).unwrap();
+    let expected = [
+        triple("a", "x"),
+        triple("a", "y"),
+        triple("ab", "x"),
+        triple("b", "x"),
+        triple("c", "z"),
+    ];
+    assert_eq!(written.lines().collect::<Vec<_>>(), expected);
+    assert_eq!(report.files[0].subjects, 4);
+    assert_eq!(report.files[0].first.as_deref(), Some(iri("a").as_str()));
+    // Only the dataset and its index are left.
+    assert_eq!(fs::read_dir(&options.out).unwrap().count(), 2);
+    let index = fs::read_to_string(options.out.join(INDEX_FILE)).unwrap();
+    assert!(index.contains("\"sorted_by\": \"subject\""));
+
+    // Every subject ends up in one shard, with all of its triples.
+    options.out = dir.join("sharded");
+    options.shards = Some(3);
+    let report = prepare(&options).unwrap();
+    assert_eq!(report.files.len(), 3);
+    let mut lines: Vec<String> = Vec::new();
+    for file in &report.files {
+        let shard = fs::read_to_string(options.out.join(&file.path)).unwrap();
+        let shard: Vec<String> = shard.lines().map(String::from).collect();
+        assert!(shard.windows(2).all(|w| w[0] < w[1]));
+        assert_eq!(shard.len(), file.triples);
+        lines.extend(shard);
+    }
+    lines.sort();
+    assert_eq!(lines, expected);
+    assert_eq!(report.files.iter().map(|f| f.subjects).sum::<usize>(), 4);
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn dict_export_round_trips() {
+    let lines = export(&dict_lines());
+
+    assert_eq!(lines[0], "1\t<http://ex.org/knows>");
+    assert_eq!(lines.len(), 4);
+    assert_eq!(import(&lines).unwrap(), dict_lines());
+}
+
+#[test]
+fn front_coded_dict_export_round_trips() {
+    let lines = export_front_coded(&dict_lines());
+
+    assert_eq!(
+        lines[1..],
+        [
+            "4\t0\t\"café\"",
+            "3\t0\t<http://ex.org/a>",
+            "5\t16\tge>",
+            "1\t15\tknows>",
+        ]
+    );
+    assert_eq!(import(&lines).unwrap(), dict_lines());
+}
+
+#[test]
+fn dict_import_rejects_bad_lines() {
+    let lines = |ls: &[&str]| -> Vec<String> { ls.iter().map(|l| l.to_string()).collect() };
+
+    assert!(import(&lines(&["1 <a>"])).is_err());
+    assert!(import(&lines(&["0\t<a>"])).is_err());
+    assert!(import(&lines(&["1\t<a>", "1\t<b>"])).is_err());
+    assert!(import(&lines(&["1\t<a>", "2\t<a>"])).is_err());
+}
+
+#[test]
+fn dump_node_decodes_edges_and_parent() {
+    let f = load("a knows x\nb knows x");
+    let (dict, preds) = (f.dataset.dict_strings(), f.dataset.pred_strings());
+    let a = f.dataset.id_of(&iri("a")).unwrap();
+
+    let meta = CompactMeta::new(Words::from_le_bytes(&f.meta.serialize_compact())).unwrap();
+
+    let dump = dump_node(&meta, &dict, &preds, a);
+
+    assert_eq!(dump.node.term, iri("a"));
+    assert_eq!(dump.outgoing.len(), 1);
+    assert_eq!(dump.outgoing[0].0, iri("knows"));
+    assert_eq!(dump.outgoing[0].1.term, iri("x"));
+    assert!(dump.incoming.is_empty());
+    let parent = dump.parent.unwrap();
+    let mut members: Vec<String> = parent.members.into_iter().map(|m| m.term).collect();
+    members.sort();
+    assert_eq!(members, vec![iri("a"), iri("b")]);
+}
+
+#[test]
+fn moved_lists_nodes_whose_supernode_changed() {
+    let mut f = load("a knows x\nb knows x\nc likes y");
+    let old = from_file(to_file(&f.meta));
+    let (snode, _) = f.supernode("a");
+
+    f.update("c knows x");
+    let id = |n: &str| f.dataset.id_of(&iri(n)).unwrap();
+    let (a, b, c) = (id("a"), id("b"), id("c"));
+    let dict = f.dataset.dict_strings();
+    let report = moved(&old, &f.meta, Some((&dict, &dict)));
+
+    let (new_snode, _) = f.supernode("a");
+    let mut nodes: Vec<u32> = report.moved.iter().map(|m| m.node).collect();
+    nodes.sort();
+    let mut expected = vec![a, b, c];
+    expected.sort();
+    assert_eq!(nodes, expected);
+    let c_move = report.moved.iter().find(|m| m.node == c).unwrap();
+    assert_eq!((c_move.old, c_move.new), (None, Some(new_snode)));
+    assert_eq!(c_move.new_term, f.dataset.key_by_value(&new_snode));
+    let a_move = report.moved.iter().find(|m| m.node == a).unwrap();
+    assert_eq!((a_move.old, a_move.new), (Some(snode), Some(new_snode)));
+    assert!(report.moved.iter().all(|m| m.node != id("x")));
+}
+
+#[test]
+fn embed_merges_edges_between_supernodes() {
+    let f = load("a knows x\nb knows x\nx likes y");
+    let (snode, _) = f.supernode("a");
+    let x = f.dataset.id_of(&iri("x")).unwrap();
+
+    let edges = edge_list(&f.meta);
+    assert_eq!(edges.iter().map(|e| e.weight).sum::<u64>(), 3);
+    let merged = edges.iter().find(|e| e.from == snode).unwrap();
+    assert_eq!((merged.to, merged.weight), (x, 2));
+
+    let options = EmbedOptions {
+        walk_length: 5,
+        walks_per_node: 2,
+        ..Default::default()
+    };
+    let walks = random_walks(&edges, &options);
+    assert_eq!(walks.len(), 2 * 3);
+    for walk in &walks {
+        assert_eq!(walk.len(), 5);
+        for pair in walk.windows(2) {
+            assert!(edges.iter().any(|e| {
+                (e.from, e.to) == (pair[0], pair[1]) || (e.to, e.from) == (pair[0], pair[1])
+            }));
+        }
+    }
+    assert!(random_walks(&edges, &options) == walks);
+}
+
+#[test]
+fn node_features_share_the_cliques_of_their_supernode() {
+    let f = load("a knows x\nb knows x\nx likes y");
+    let (dict, preds) = (f.dataset.dict_strings(), f.dataset.pred_strings());
+    let (snode, _) = f.supernode("a");
+    let cliques = clique::to_file(&f.sc, &f.tc, 0);
+
+    let features = node_features(&f.meta, &dict, &preds, Some(&cliques));
+    let node = |term: &str| features.iter().find(|n| n.term == iri(term)).unwrap();
+    let (a, b, x) = (node("a"), node("b"), node("x"));
+    assert_eq!((a.out_degree, a.in_degree), (1, 0));
+    assert_eq!(a.out_preds, vec![iri("knows")]);
+    assert_eq!((a.supernode, b.supernode), (Some(snode), Some(snode)));
+    assert_eq!(a.source_clique, Some(f.sc.get_index(&snode)));
+    assert_eq!(
+        (a.source_clique, a.target_clique),
+        (b.source_clique, b.target_clique)
+    );
+    assert_eq!((x.in_degree, x.out_degree), (2, 1));
+    assert_eq!(
+        (x.in_preds.clone(), x.supernode),
+        (vec![iri("knows")], None)
+    );
+
+    let without = node_features(&f.meta, &dict, &preds, None);
+    assert!(without.iter().all(|n| n.source_clique.is_none()));
+}
+
+#[test]
+fn export_tables_merge_summary_edges() {
+    let f = load("a knows x\nb knows x\nx likes y");
+    let (dict, preds) = (f.dataset.dict_strings(), f.dataset.pred_strings());
+    let (id, _) = f.supernode("a");
+    let snode = dict[id as usize - 1].clone();
+
+    let tables = tables(&f.meta, &dict, &preds);
+    let names: Vec<&str> = tables.iter().map(|t| t.name).collect();
+    assert_eq!(names, vec!["triples", "members", "summary_edges"]);
+    assert_eq!(tables[0].rows.len(), 3);
+    assert!(tables[0]
+        .rows
+        .contains(&vec![iri("b"), iri("knows"), iri("x")]));
+    assert_eq!(
+        tables[1].rows,
+        vec![vec![snode.clone(), iri("a")], vec![snode.clone(), iri("b")]]
+    );
+    let merged = vec![snode, iri("knows"), iri("x"), "2".to_string()];
+    assert!(tables[2].rows.contains(&merged));
+    assert_eq!(tables[2].rows.len(), 2);
+}
+
+#[test]
+fn csv_line_quotes_fields_with_separators() {
+    assert_eq!(csv_line(&["<a>", r#""x, y""#, "1"]), r#"<a>,"""x, y""",1"#);
+}
+
+#[test]
+fn extract_writes_the_source_of_triples_read_with_one() {
+    let mut f = load("b knows x");
+    f.update("a knows x g1");
+    let (dict, preds) = (f.dataset.dict_strings(), f.dataset.pred_strings());
+    let sources = f.dataset.source_strings();
+    assert_eq!(sources, vec![iri("g1")]);
+    let (snode, _) = f.supernode("a");
+
+    let lines = extract(&f.meta, &dict, &preds, &sources, &snode);
+    let quad = format!("{} {} {} {} .", iri("a"), iri("knows"), iri("x"), iri("g1"));
+    let triple = format!("{} {} {} .", iri("b"), iri("knows"), iri("x"));
+    assert_eq!(lines, vec![quad, triple]);
+
+    let [a, x] = [iri("a"), iri("x")].map(|t| f.dataset.id_of(&t).unwrap());
+    let knows = f.dataset.pred_id_of(&iri("knows")).unwrap();
+    assert_eq!(f.meta.get_source(&a, &knows, &x), Some(1));
+    f.update("-a knows x");
+    assert_eq!(f.meta.get_source(&a, &knows, &x), None);
+}
+
+#[test]
+fn explain_names_the_shared_clique_or_the_differing_preds() {
+    let f = load("a knows x\nb knows x\nc likes x");
+    let (a, b, c) = (iri("a"), iri("b"), iri("c"));
+    let explain = |x: &str, y: &str| explain(&f.dataset, &f.meta, &f.sc, &f.tc, x, y);
+
+    let merged = explain(&a, &b).unwrap();
+    assert!(merged.merged);
+    assert_eq!(merged.supernode_a, merged.supernode_b);
+    assert!(merged.source.same_clique);
+    assert_eq!(merged.source.shared_preds, vec![iri("knows")]);
+    assert!(merged.source.only_a.is_empty() && merged.source.only_b.is_empty());
+
+    let apart = explain(&a, &c).unwrap();
+    assert!(!apart.merged && apart.supernode_b.is_none());
+    assert!(!apart.source.same_clique);
+    assert_eq!(apart.source.only_a, vec![iri("knows")]);
+    assert_eq!(apart.source.only_b, vec![iri("likes")]);
+
+    assert!(explain(&a, &iri("nobody")).is_err());
+    let snode = merged.supernode_a.unwrap();
+    assert!(explain(&a, &snode).is_err());
+}
+
+#[test]
+fn completions_offer_every_subcommand_and_flag() {
+    for shell in [Shell::Bash, Shell::Zsh, Shell::Fish] {
+        let script = completions(shell);
+        for s in SUBCOMMANDS {
+            assert!(script.contains(s.name), "{:?} misses {}", shell, s.name);
+            assert!(subcommand_usage(s.name).unwrap().contains("teriyaki"));
+        }
+        for f in RUN_FLAGS {
+            let long = f.name.trim_start_matches('-');
+            assert!(script.contains(long), "{:?} misses {}", shell, f.name);
+        }
+    }
+    assert_eq!(subcommand_usage("update --fast"), None);
+}
+
+#[test]
+fn every_listed_run_flag_is_accepted() {
+    for f in RUN_FLAGS {
+        // main takes --errors-json out before parsing, as parse errors are printed with it.
+        if f.name == "--errors-json" {
+            continue;
+        }
+        let mut args: Vec<String> = ["teriyaki", "dataset.nt", "update.nt", "meta", f.name]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        let value = match f.value {
+            Some(choices) if choices.contains(' ') => choices.split(' ').next(),
+            Some("N" | "BYTES" | "LEVEL") => Some("2"),
+            Some("X" | "SECONDS") => Some("0.5"),
+            Some("PREDS") => Some("http://x/p"),
+            Some("PAIRS") => Some("owl"),
+            Some("FILE") => Some("schema.nt"),
+            Some("URL") => Some("http://localhost/sparql"),
+            Some(other) => panic!("{} takes an unknown value {}", f.name, other),
+            None => None,
+        };
+        args.extend(value.map(String::from));
+        // Flags that only apply alongside another one.
+        let with: &[&str] = match f.name {
+            "--recluster-every" => &["--split-strategy", "lazy"],
+            "--seed" => &["--sample", "0.5"],
+            "--long-lines" => &["--max-line-bytes", "100"],
+            "--wal" => {
+                args.insert(1, "repl".to_string());
+                &[]
+            }
+            _ => &[],
+        };
+        args.extend(with.iter().map(|s| s.to_string()));
+        assert!(crate::Cli::new(&args).is_ok(), "{} is not accepted", f.name);
+    }
+}
+
+#[test]
+fn dry_run_of_an_empty_update_changes_nothing() {
+    let mut f = load("a knows b\nc knows b\na rdf:type P\nc rdf:type P\nb rdf:type Q");
+    f.dataset
+        .set_update(Vec::new(), Vec::new(), &mut f.meta, &Grouping::Preds);
+    let report = dry_run(
+        &mut f.dataset,
+        &mut f.meta,
+        &mut f.sc,
+        &mut f.tc,
+        &Options::default(),
+        "summary.nt".to_string(),
+    )
+    .unwrap();
+    assert_eq!((report.insertions, report.deletions), (0, 0));
+    assert_eq!((report.triples_before, report.triples_after), (1, 1));
+    assert_eq!(report.supernodes_before, report.supernodes_after);
+}
+
+#[test]
+fn time_budgets_that_are_not_durations_are_argument_errors() {
+    let cli = |budget: &str| {
+        let args: Vec<String> = [
+            "teriyaki",
+            "dataset.nt",
+            "update.nt",
+            "meta",
+            "--time-budget",
+        ]
+        .iter()
+        .map(|s| s.to_string())
+        .chain([budget.to_string()])
+        .collect();
+        crate::Cli::new(&args)
+    };
+    let budget = cli("1.5").unwrap().time_budget;
+    assert_eq!(budget, Some(std::time::Duration::from_millis(1500)));
+    for budget in ["-1", "1e300", "inf", "NaN", "soon"] {
+        assert!(cli(budget).is_err(), "{}", budget);
+    }
+}
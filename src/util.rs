@@ -1,3 +0,0 @@
-pub mod io;
-pub mod set_ops;
-// pub mod print;
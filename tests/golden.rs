@@ -0,0 +1,73 @@
+//! Runs the teriyaki binary on the example datasets of `tests/data` and compares its summaries
+//! with the expected ones, so the whole pipeline from parsing to writing is covered.
+//!
+//! Each folder of `tests/data` holds `dataset.nt`, summarized in fast mode, and `changes.nt`,
+//! applied to that summary in a second run. The summaries after each run must equal
+//! `expected-summary.nt` and `expected-updated.nt`. Setting `TERIYAKI_BLESS` writes the
+//! summaries of the current binary as the expected ones instead.
+
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::{env, fs, process};
+
+const DATASETS: [&str; 3] = ["people", "cities", "library"];
+
+#[test]
+fn example_datasets_match_their_expected_summaries() {
+    for name in DATASETS {
+        let data = Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("tests/data")
+            .join(name);
+        let dir = env::temp_dir().join(format!("teriyaki-golden-{}-{}", name, process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("empty.nt"), "").unwrap();
+
+        teriyaki(
+            &dir,
+            &["empty.nt", &path(&data, "dataset.nt"), "meta", "--fast"],
+        );
+        check(&dir, &data, "expected-summary.nt");
+
+        fs::copy(dir.join("summary.nt"), dir.join("first.nt")).unwrap();
+        teriyaki(&dir, &["first.nt", &path(&data, "changes.nt"), "meta"]);
+        check(&dir, &data, "expected-updated.nt");
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
+
+/// Runs the binary in `dir` with `args`, failing on an unsuccessful exit.
+fn teriyaki(dir: &Path, args: &[&str]) {
+    let output = Command::new(env!("CARGO_BIN_EXE_teriyaki"))
+        .current_dir(dir)
+        .args(args)
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "teriyaki {} failed:\n{}",
+        args.join(" "),
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
+
+/// Compares the summary written to `dir` with the file `expected` of `data`.
+fn check(dir: &Path, data: &Path, expected: &str) {
+    let summary = fs::read_to_string(dir.join("summary.nt")).unwrap();
+    let expected: PathBuf = data.join(expected);
+    if env::var_os("TERIYAKI_BLESS").is_some() {
+        fs::write(&expected, &summary).unwrap();
+        return;
+    }
+    assert_eq!(
+        summary,
+        fs::read_to_string(&expected).unwrap(),
+        "summary differs from {}",
+        expected.display()
+    );
+}
+
+fn path(dir: &Path, file: &str) -> String {
+    dir.join(file).display().to_string()
+}
@@ -0,0 +1,71 @@
+//! Runs the teriyaki binary against a locked meta folder, and checks that a run that fails
+//! while holding the lock releases it.
+
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+use std::{env, fs, process};
+use teriyaki_core::util::lock::Lock;
+
+const CHANGE: &str = "<http://example.org/x> <http://example.org/p> <http://example.org/y> .\n";
+
+#[test]
+fn failed_run_releases_the_lock() {
+    let dir = summarized("failed");
+    fs::write(dir.join("twice.nt"), CHANGE.repeat(2)).unwrap();
+
+    let output = teriyaki(
+        &dir,
+        &["summary.nt", "twice.nt", "meta", "--duplicates", "error"],
+    );
+    assert_eq!(output.status.code(), Some(5));
+    assert!(!dir.join("meta.lock").exists());
+
+    fs::write(dir.join("once.nt"), CHANGE).unwrap();
+    assert!(teriyaki(&dir, &["summary.nt", "once.nt", "meta"])
+        .status
+        .success());
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn locked_meta_folder_is_not_written() {
+    let dir = summarized("locked");
+    fs::write(dir.join("once.nt"), CHANGE).unwrap();
+    let before = fs::read_to_string(dir.join("summary.nt")).unwrap();
+
+    let lock = Lock::acquire(&dir.join("meta.lock")).unwrap();
+    let output = teriyaki(&dir, &["summary.nt", "once.nt", "meta"]);
+    assert_eq!(output.status.code(), Some(5));
+    assert!(String::from_utf8_lossy(&output.stdout)
+        .contains(&format!("already locked by pid {}", process::id())));
+    assert_eq!(fs::read_to_string(dir.join("summary.nt")).unwrap(), before);
+
+    drop(lock);
+    assert!(teriyaki(&dir, &["summary.nt", "once.nt", "meta"])
+        .status
+        .success());
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+/// Returns a new folder holding the summary and meta folder of the `people` example dataset.
+fn summarized(name: &str) -> PathBuf {
+    let dataset = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/data/people/dataset.nt");
+    let dir = env::temp_dir().join(format!("teriyaki-lock-{}-{}", name, process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(dir.join("empty.nt"), "").unwrap();
+    let dataset = dataset.display().to_string();
+    assert!(teriyaki(&dir, &["empty.nt", &dataset, "meta", "--fast"])
+        .status
+        .success());
+    dir
+}
+
+/// Runs the binary in `dir` with `args`.
+fn teriyaki(dir: &Path, args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_teriyaki"))
+        .current_dir(dir)
+        .args(args)
+        .output()
+        .unwrap()
+}
@@ -0,0 +1,128 @@
+//! Runs the repl of the teriyaki binary: its commands, and a session with a write-ahead log
+//! that ends without a snapshot as a crash would.
+
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output, Stdio};
+use std::{env, fs, process};
+
+const X: &str = "<http://example.org/x>";
+const Z: &str = "<http://example.org/z>";
+
+#[test]
+fn commands_query_and_change_the_summary() {
+    let dir = summarized("commands");
+    let person = |name: &str| format!("<http://example.org/people/{}>", name);
+    let input = format!(
+        "lookup {ada}\nmembers {snode}\npreds {ada}\napply {zoe} {knows} {ada}\nlookup {zoe}\n\
+         lookup {nobody}\nbogus\n",
+        ada = person("ada"),
+        snode = person("ada_charles_mary"),
+        zoe = person("zoe"),
+        nobody = person("nobody"),
+        knows = "<http://xmlns.com/foaf/0.1/knows>",
+    );
+    let session = repl(&dir, &["empty.nt", "meta"], &input);
+    fs::remove_dir_all(&dir).unwrap();
+
+    assert!(session.status.success());
+    let stdout = String::from_utf8_lossy(&session.stdout);
+    let expected = [
+        "<http://example.org/people/ada> is node 1 in supernode \
+         <http://example.org/people/ada_charles_mary>",
+        "<http://example.org/people/charles>",
+        "out: <http://xmlns.com/foaf/0.1/knows> <http://xmlns.com/foaf/0.1/homepage>",
+        "summary has 6 triples and 2 supernodes",
+        "<http://example.org/people/zoe> is node",
+        "unknown term <http://example.org/people/nobody>",
+        "unknown command, try help",
+    ];
+    for line in expected {
+        assert!(
+            stdout.contains(line),
+            "{} is missing from:\n{}",
+            line,
+            stdout
+        );
+    }
+}
+
+#[test]
+fn changes_of_a_crashed_session_are_replayed_by_the_next_run() {
+    let dir = summarized("crash");
+    let insertion = format!("{} <http://example.org/p> <http://example.org/y> .", X);
+    fs::write(dir.join("once.nt"), format!("{}\n", insertion)).unwrap();
+
+    let change = format!("{} <http://example.org/p> <http://example.org/y>", Z);
+    let session = repl(
+        &dir,
+        &["once.nt", "meta", "--wal"],
+        &format!("apply {}\nquit\n", change),
+    );
+    assert!(session.status.success());
+    assert_eq!(
+        fs::read_to_string(dir.join("meta/wal")).unwrap(),
+        format!("{}\n{} .\n", insertion, change)
+    );
+
+    // A new update cannot go before the logged changes.
+    let refused = teriyaki(&dir, &["summary.nt", "once.nt", "meta"]);
+    assert!(!refused.status.success());
+    assert!(String::from_utf8_lossy(&refused.stdout).contains("not yet in the meta folder"));
+
+    assert!(teriyaki(&dir, &["summary.nt", "empty.nt", "meta"])
+        .status
+        .success());
+    assert!(!dir.join("meta/wal").exists());
+    let lookup = repl(
+        &dir,
+        &["empty.nt", "meta"],
+        &format!("lookup {}\nlookup {}\n", X, Z),
+    );
+    let stdout = String::from_utf8_lossy(&lookup.stdout);
+    assert!(!stdout.contains("unknown term"), "{}", stdout);
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+/// Returns a new folder holding the summary and meta folder of the `people` example dataset.
+fn summarized(name: &str) -> PathBuf {
+    let dataset = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/data/people/dataset.nt");
+    let dir = env::temp_dir().join(format!("teriyaki-repl-{}-{}", name, process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(dir.join("empty.nt"), "").unwrap();
+    let dataset = dataset.display().to_string();
+    assert!(teriyaki(&dir, &["empty.nt", &dataset, "meta", "--fast"])
+        .status
+        .success());
+    dir
+}
+
+/// Runs the binary in `dir` with `args`.
+fn teriyaki(dir: &Path, args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_teriyaki"))
+        .current_dir(dir)
+        .args(args)
+        .output()
+        .unwrap()
+}
+
+/// Runs the repl on the summary in `dir` with the update, meta folder and flags `args`, feeding it
+/// `input`.
+fn repl(dir: &Path, args: &[&str], input: &str) -> Output {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_teriyaki"))
+        .current_dir(dir)
+        .args(["repl", "summary.nt"])
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(input.as_bytes())
+        .unwrap();
+    child.wait_with_output().unwrap()
+}